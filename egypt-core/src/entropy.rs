@@ -0,0 +1,114 @@
+use alloc::collections::BTreeMap;
+use alloc::vec;
+use alloc::vec::Vec;
+
+use crate::math::log10;
+
+/// Variant entropy of a prefix automaton with `states` states (including the root) whose other
+/// states fall into partitions of the given sizes: `S·log(S) − Σ_p |p|·log(|p|)` with `S` the
+/// number of states without the root (Back, Debois, Slaats 2019).
+pub fn variant_entropy(states: usize, partition_sizes: impl IntoIterator<Item = usize>) -> f64 {
+    let s = without_root(states);
+    let sum_term: f64 = partition_sizes
+        .into_iter()
+        .map(|size| {
+            let size = size as f64;
+            size * log10(size)
+        })
+        .sum();
+    s * log10(s) - sum_term
+}
+
+/// [`variant_entropy`] scaled to [0, 1] by its maximum `S·log(S)`.
+pub fn normalized_variant_entropy(
+    states: usize,
+    partition_sizes: impl IntoIterator<Item = usize>,
+) -> f64 {
+    let s = without_root(states);
+    variant_entropy(states, partition_sizes) / (s * log10(s))
+}
+
+fn without_root(states: usize) -> f64 {
+    let s = states as f64;
+    if s > 1.0 {
+        s - 1.0
+    } else {
+        s
+    }
+}
+
+/// The partition of every state apart from the root of the prefix automaton of `traces`: a new
+/// partition starts wherever a state gets a second successor. The same partitions as the
+/// branching strategy of `egypt::ExtendedPrefixAutomaton`.
+pub fn branching_partitions<T: Ord + Clone>(traces: &[Vec<T>]) -> Vec<usize> {
+    // (source, activity) -> target, with the root as state 0
+    let mut transitions: BTreeMap<(usize, T), usize> = BTreeMap::new();
+    let mut successors = vec![0];
+    let mut partitions = vec![0];
+    let mut max_partition = 0;
+
+    for trace in traces {
+        let mut state = 0;
+        for activity in trace {
+            let key = (state, activity.clone());
+            state = match transitions.get(&key) {
+                Some(&target) => target,
+                None => {
+                    let partition = if state == 0 {
+                        1
+                    } else if successors[state] > 0 {
+                        max_partition + 1
+                    } else {
+                        partitions[state]
+                    };
+                    max_partition = max_partition.max(partition);
+                    successors[state] += 1;
+                    successors.push(0);
+                    partitions.push(partition);
+                    transitions.insert(key, partitions.len() - 1);
+                    partitions.len() - 1
+                }
+            };
+        }
+    }
+    partitions.remove(0);
+    partitions
+}
+
+/// Variant entropy of the prefix automaton of `traces`, with the partitions of
+/// [`branching_partitions`]. Only the distinct traces matter, so passing the variants is enough.
+pub fn trace_entropy<T: Ord + Clone>(traces: &[Vec<T>]) -> f64 {
+    let partitions = branching_partitions(traces);
+    variant_entropy(partitions.len() + 1, partition_sizes(&partitions))
+}
+
+/// [`trace_entropy`] scaled to [0, 1].
+pub fn normalized_trace_entropy<T: Ord + Clone>(traces: &[Vec<T>]) -> f64 {
+    let partitions = branching_partitions(traces);
+    normalized_variant_entropy(partitions.len() + 1, partition_sizes(&partitions))
+}
+
+fn partition_sizes(partitions: &[usize]) -> Vec<usize> {
+    let mut sizes: BTreeMap<usize, usize> = BTreeMap::new();
+    for &partition in partitions {
+        *sizes.entry(partition).or_default() += 1;
+    }
+    sizes.into_values().collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_trace_entropy() {
+        // a·b·c, a·b·d and a·c: the states a, ab, abc | abd | ac
+        let traces = vec![vec![0u32, 1, 2], vec![0, 1, 3], vec![0, 2]];
+        assert_eq!(branching_partitions(&traces), [1, 1, 1, 2, 3]);
+        let expected = 5.0 * log10(5.0) - 3.0 * log10(3.0);
+        assert!((trace_entropy(&traces) - expected).abs() < 1e-12);
+        assert!(normalized_trace_entropy(&traces) < 1.0);
+
+        assert_eq!(trace_entropy(&[vec![0u32, 1]]), 0.0);
+    }
+}
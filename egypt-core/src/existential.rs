@@ -0,0 +1,181 @@
+use alloc::vec::Vec;
+use core::fmt;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Direction {
+    Forward,
+    Backward,
+    Both,
+}
+
+#[derive(PartialEq, Eq, Debug, Clone)]
+pub enum DependencyType {
+    Implication,
+    Equivalence,
+    NegatedEquivalence,
+    Nand,
+    Or,
+}
+
+impl fmt::Display for DependencyType {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            DependencyType::Implication => write!(f, "⇒"),
+            DependencyType::Equivalence => write!(f, "⇔"),
+            DependencyType::NegatedEquivalence => write!(f, "⇎"),
+            DependencyType::Nand => write!(f, "⊼"),
+            DependencyType::Or => write!(f, "∨"),
+        }
+    }
+}
+
+/// How often two activities occur (or don't occur) in the same trace.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ContingencyTable {
+    pub both: usize,
+    pub only_from: usize,
+    pub only_to: usize,
+    pub neither: usize,
+}
+
+impl ContingencyTable {
+    pub fn from_variants<T: PartialEq>(from: T, to: T, variants: &[(Vec<T>, usize)]) -> Self {
+        let mut table = ContingencyTable {
+            both: 0,
+            only_from: 0,
+            only_to: 0,
+            neither: 0,
+        };
+        for (trace, count) in variants {
+            match (trace.contains(&from), trace.contains(&to)) {
+                (true, true) => table.both += count,
+                (true, false) => table.only_from += count,
+                (false, true) => table.only_to += count,
+                (false, false) => table.neither += count,
+            }
+        }
+        table
+    }
+
+    pub fn total(&self) -> usize {
+        self.both + self.only_from + self.only_to + self.neither
+    }
+
+    /// Row and column sums: (from, not from, to, not to).
+    pub fn margins(&self) -> (usize, usize, usize, usize) {
+        (
+            self.both + self.only_from,
+            self.only_to + self.neither,
+            self.both + self.only_to,
+            self.only_from + self.neither,
+        )
+    }
+
+    /// A table with an empty row or column, e.g. because an activity occurs in every trace,
+    /// carries no information about the association of the activities.
+    pub fn is_degenerate(&self) -> bool {
+        let (from, not_from, to, not_to) = self.margins();
+        from == 0 || not_from == 0 || to == 0 || not_to == 0
+    }
+}
+
+/// Checks if the occurrence of `from` implies the occurrence of `to`: `true` if the share of
+/// traces that either contain `to` or don't contain `from` is at least `threshold`.
+pub fn implies(table: &ContingencyTable, threshold: f64) -> bool {
+    let valid_traces = table.total() - table.only_from;
+    valid_traces as f64 / table.total() as f64 >= threshold
+}
+
+/// Checks if `from` and `to` don't occur together in at least `threshold` of the traces
+/// (NAND).
+pub fn excludes(table: &ContingencyTable, threshold: f64) -> bool {
+    let valid_traces = table.total() - table.both;
+    valid_traces as f64 / table.total() as f64 >= threshold
+}
+
+/// Checks if at least one of `from` and `to` occurs in at least `threshold` of the traces (OR).
+pub fn includes_either(table: &ContingencyTable, threshold: f64) -> bool {
+    let valid_traces = table.total() - table.neither;
+    valid_traces as f64 / table.total() as f64 >= threshold
+}
+
+/// The existential dependency of a contingency table: an implication in either direction or
+/// an equivalence if both hold, otherwise a negated equivalence if exactly one of the
+/// activities occurs, a NAND if they exclude each other and an OR if at least one occurs.
+///
+/// # Panics
+/// Panics if `threshold` is not between 0 and 1.
+pub fn classify(table: &ContingencyTable, threshold: f64) -> Option<(DependencyType, Direction)> {
+    assert!(
+        (0.0..=1.0).contains(&threshold),
+        "Threshold must be between 0 and 1"
+    );
+
+    let implication = implies(table, threshold);
+    let reverse = ContingencyTable {
+        only_from: table.only_to,
+        only_to: table.only_from,
+        ..*table
+    };
+
+    if implication || implies(&reverse, threshold) {
+        let dependency_type = if implication && implies(&reverse, threshold) {
+            DependencyType::Equivalence
+        } else {
+            DependencyType::Implication
+        };
+        let direction = if implication {
+            Direction::Forward
+        } else {
+            Direction::Backward
+        };
+        return Some((dependency_type, direction));
+    }
+
+    let dependency_type = match (excludes(table, threshold), includes_either(table, threshold)) {
+        (true, true) => DependencyType::NegatedEquivalence,
+        (true, false) => DependencyType::Nand,
+        (false, true) => DependencyType::Or,
+        (false, false) => return None,
+    };
+    Some((dependency_type, Direction::Forward))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::vec;
+
+    #[test]
+    fn test_classify() {
+        let variants = vec![(vec![0u32, 1], 2), (vec![0, 2], 1), (vec![3], 1)];
+        let table = ContingencyTable::from_variants(1, 0, &variants);
+        assert_eq!(table.margins(), (2, 2, 3, 1));
+        assert_eq!(
+            classify(&table, 1.0),
+            Some((DependencyType::Implication, Direction::Forward))
+        );
+        // trace [3] contains neither 1 nor 2
+        assert_eq!(
+            classify(&ContingencyTable::from_variants(1, 2, &variants), 1.0),
+            Some((DependencyType::Nand, Direction::Forward))
+        );
+        let exclusive = vec![(vec![1u32], 2), (vec![2], 1)];
+        assert_eq!(
+            classify(&ContingencyTable::from_variants(1, 2, &exclusive), 1.0),
+            Some((DependencyType::NegatedEquivalence, Direction::Forward))
+        );
+        let either = vec![(vec![1u32], 2), (vec![2], 1), (vec![1, 2], 1)];
+        assert_eq!(
+            classify(&ContingencyTable::from_variants(1, 2, &either), 1.0),
+            Some((DependencyType::Or, Direction::Forward))
+        );
+        let independent = ContingencyTable {
+            both: 1,
+            only_from: 1,
+            only_to: 1,
+            neither: 1,
+        };
+        assert_eq!(classify(&independent, 1.0), None);
+    }
+}
@@ -0,0 +1,32 @@
+//! The pure algorithms of egypt on traces of any activity type, e.g. the integer ids of a
+//! pre-tokenized log: temporal and existential dependency checks, variant counting and the
+//! variant entropy.
+//!
+//! The crate is `no_std` and only needs `alloc`, so it runs where there is no file system or
+//! clock, e.g. on an edge gateway. Parsing, IO and timestamps stay in `egypt`. Floating point
+//! math comes from `std` (the default `std` feature) or, without it, from the `libm` feature.
+//!
+//! ```
+//! use egypt_core::temporal::{dependency, DependencyType, Direction};
+//!
+//! // activities tokenized as integers
+//! let variants = vec![(vec![0u32, 1, 2], 3), (vec![0, 2], 1)];
+//! assert_eq!(
+//!     dependency(&0, &2, &variants, 1.0),
+//!     Some((DependencyType::Eventual, Direction::Forward))
+//! );
+//! ```
+#![no_std]
+
+extern crate alloc;
+#[cfg(feature = "std")]
+extern crate std;
+
+#[cfg(not(any(feature = "std", feature = "libm")))]
+compile_error!("egypt-core needs the `std` or the `libm` feature for floating point math");
+
+pub mod entropy;
+pub mod existential;
+mod math;
+pub mod temporal;
+pub mod variants;
@@ -0,0 +1,11 @@
+// Floating point functions that are not part of `core`
+
+#[cfg(feature = "std")]
+pub(crate) fn log10(x: f64) -> f64 {
+    x.log(10.0)
+}
+
+#[cfg(all(not(feature = "std"), feature = "libm"))]
+pub(crate) fn log10(x: f64) -> f64 {
+    libm::log(x) / libm::log(10.0)
+}
@@ -0,0 +1,220 @@
+use alloc::vec::Vec;
+use core::cmp::Ordering;
+use core::fmt;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Direction {
+    Forward,
+    Backward,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DependencyType {
+    Direct,
+    Eventual,
+}
+
+impl fmt::Display for DependencyType {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            DependencyType::Direct => write!(f, "d"),
+            DependencyType::Eventual => write!(f, ""),
+        }
+    }
+}
+
+/// The dependencies between the occurrences of two activities within a single trace, one per
+/// pair of matched occurrences.
+///
+/// Note: this is where the logic for determining the types and directions of the dependencies
+/// is implemented.
+pub fn trace_dependencies<T: PartialEq>(
+    from: &T,
+    to: &T,
+    trace: &[T],
+) -> Vec<(DependencyType, Direction)> {
+    let mut result = Vec::new();
+    let mut from_positions: Vec<usize> = Vec::new();
+    let mut to_positions: Vec<usize> = Vec::new();
+
+    // get the indexes of each `from` and each `to` activities
+    for (i, activity) in trace.iter().enumerate() {
+        if activity == from {
+            from_positions.push(i);
+        } else if activity == to {
+            to_positions.push(i);
+        }
+    }
+
+    // edge case for when `from` and `to` are the same
+    if from == to {
+        // check >2
+        if from_positions.len() > 2 {
+            result.push((DependencyType::Eventual, Direction::Forward));
+        } else if from_positions[0] + 1 == from_positions[1] {
+            // check if activity in between
+            result.push((DependencyType::Direct, Direction::Forward));
+        }
+    }
+
+    result.extend(position_dependencies(&from_positions, &to_positions));
+    result
+}
+
+/// The dependencies between the occurrences of two different activities at the given
+/// (ascending) positions of a trace.
+pub fn position_dependencies(
+    from_positions: &[usize],
+    to_positions: &[usize],
+) -> Vec<(DependencyType, Direction)> {
+    let mut result = Vec::new();
+    let mut from_index = 0;
+    let mut to_index = 0;
+
+    // iterate through the `from` and `to` positions except for the last one
+    while from_index < from_positions.len() && to_index < to_positions.len() {
+        let from_pos = from_positions[from_index];
+        let to_pos = to_positions[to_index];
+
+        match from_pos.cmp(&to_pos) {
+            Ordering::Less => {
+                let dependency_type = if to_pos - from_pos == 1 {
+                    DependencyType::Direct
+                } else {
+                    DependencyType::Eventual
+                };
+                result.push((dependency_type, Direction::Forward));
+                from_index += 1;
+                to_index += 1;
+            }
+            Ordering::Greater => {
+                let dependency_type = if from_pos - to_pos == 1 {
+                    DependencyType::Direct
+                } else {
+                    DependencyType::Eventual
+                };
+                result.push((dependency_type, Direction::Backward));
+                to_index += 1;
+            }
+            Ordering::Equal => unreachable!(),
+        }
+    }
+
+    // handle remaining 'from' activities
+    while from_index < from_positions.len() {
+        if to_positions
+            .last()
+            .is_some_and(|&last_to| last_to > from_positions[from_index])
+        {
+            result.push((DependencyType::Eventual, Direction::Forward));
+        }
+        from_index += 1;
+    }
+
+    // handle remaining 'to' activities
+    while to_index < to_positions.len() {
+        if from_positions
+            .last()
+            .is_some_and(|&last_from| last_from < to_positions[to_index])
+        {
+            result.push((DependencyType::Eventual, Direction::Forward));
+        } else {
+            result.push((DependencyType::Eventual, Direction::Backward));
+        }
+        to_index += 1;
+    }
+
+    result
+}
+
+/// The dependencies found between two activities, summed over the traces.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct PairStatistics {
+    forward: usize,
+    backward: usize,
+    eventual: bool,
+    // whether any dependency was found, even in a trace with weight 0
+    any: bool,
+}
+
+impl PairStatistics {
+    /// Counts a dependency found `count` times.
+    pub fn add(&mut self, dependency_type: &DependencyType, direction: &Direction, count: usize) {
+        match direction {
+            Direction::Forward => self.forward += count,
+            Direction::Backward => self.backward += count,
+        }
+        self.eventual |= *dependency_type == DependencyType::Eventual;
+        self.any = true;
+    }
+
+    /// The overall dependency: the direction that at least `threshold` of the dependencies
+    /// have, eventual if any of them is. `None` without dependencies or if neither direction
+    /// meets the threshold.
+    pub fn classify(&self, threshold: f64) -> Option<(DependencyType, Direction)> {
+        if !self.any {
+            return None;
+        }
+
+        let total_count = (self.forward + self.backward) as f64;
+        let forward_count = self.forward as f64;
+        let backward_count = total_count - forward_count;
+
+        let forward_ratio = forward_count / total_count;
+        let backward_ratio = backward_count / total_count;
+
+        let direction = if forward_ratio >= threshold {
+            Direction::Forward
+        } else if backward_ratio >= threshold {
+            Direction::Backward
+        } else {
+            return None; // if neither direction meets the threshold, it's independent
+        };
+
+        let dependency_type = if self.eventual {
+            DependencyType::Eventual
+        } else {
+            DependencyType::Direct
+        };
+        Some((dependency_type, direction))
+    }
+}
+
+/// The temporal dependency between two activities in variants weighted by their frequency:
+/// the dependencies of every variant are counted `frequency` times and then classified with
+/// [`PairStatistics::classify`].
+pub fn dependency<T: PartialEq>(
+    from: &T,
+    to: &T,
+    variants: &[(Vec<T>, usize)],
+    threshold: f64,
+) -> Option<(DependencyType, Direction)> {
+    let mut statistics = PairStatistics::default();
+    for (trace, count) in variants {
+        for (dependency_type, direction) in trace_dependencies(from, to, trace) {
+            statistics.add(&dependency_type, &direction, *count);
+        }
+    }
+    statistics.classify(threshold)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::vec;
+
+    #[test]
+    fn test_dependency() {
+        let variants = vec![(vec![0u32, 1, 2], 3), (vec![0, 2, 1], 1)];
+        assert_eq!(
+            dependency(&0, &1, &variants, 1.0),
+            Some((DependencyType::Eventual, Direction::Forward))
+        );
+        assert_eq!(dependency(&1, &2, &variants, 1.0), None);
+        assert_eq!(
+            dependency(&1, &2, &variants, 0.75),
+            Some((DependencyType::Direct, Direction::Forward))
+        );
+        assert_eq!(dependency(&0, &3, &variants, 1.0), None);
+    }
+}
@@ -0,0 +1,34 @@
+use alloc::collections::BTreeMap;
+use alloc::vec::Vec;
+
+/// Counts how often every distinct trace occurs; the variants are in order of their first
+/// occurrence.
+pub fn count_variants<T: Ord + Clone>(
+    traces: impl IntoIterator<Item = Vec<T>>,
+) -> Vec<(Vec<T>, usize)> {
+    let mut variants: Vec<(Vec<T>, usize)> = Vec::new();
+    let mut index: BTreeMap<Vec<T>, usize> = BTreeMap::new();
+    for trace in traces {
+        match index.get(&trace) {
+            Some(&idx) => variants[idx].1 += 1,
+            None => {
+                index.insert(trace.clone(), variants.len());
+                variants.push((trace, 1));
+            }
+        }
+    }
+    variants
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::vec;
+
+    #[test]
+    fn test_count_variants() {
+        let traces = vec![vec![1u32, 2], vec![3], vec![1, 2]];
+        assert_eq!(count_variants(traces), [(vec![1, 2], 2), (vec![3], 1)]);
+        assert!(count_variants(Vec::<Vec<u32>>::new()).is_empty());
+    }
+}
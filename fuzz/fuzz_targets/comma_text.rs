@@ -0,0 +1,25 @@
+//! The comma separated traces of the text area, one trace per line: the matrix, the
+//! directly-follows graph and the XES document generated from them, which has to be readable
+//! by the XES parser again.
+
+#![no_main]
+
+use egypt::dfg::DirectlyFollowsGraph;
+use egypt::parser::parse_into_traces;
+use egypt::{generate_adj_matrix_from_traces, generate_xes, get_activities_and_traces};
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    let Ok(text) = std::str::from_utf8(data) else {
+        return;
+    };
+    let (_, traces) = get_activities_and_traces(text);
+    let _ = DirectlyFollowsGraph::from_traces(traces.iter().map(|trace| trace.iter().copied()));
+    let _ = generate_adj_matrix_from_traces(
+        traces
+            .iter()
+            .map(|trace| trace.iter().map(|activity| activity.to_string()).collect())
+            .collect(),
+    );
+    let _ = parse_into_traces(None, Some(&generate_xes(text)));
+});
@@ -0,0 +1,12 @@
+//! Declare models as uploaded in the browser.
+
+#![no_main]
+
+use egypt::declare::parse_declare;
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    if let Ok(content) = std::str::from_utf8(data) {
+        let _ = parse_declare(content);
+    }
+});
@@ -0,0 +1,26 @@
+//! XES documents as uploaded in the browser, possibly gzip-compressed, parsed strictly and in
+//! the lenient mode.
+
+#![no_main]
+
+use egypt::parser::{
+    decode_xes, list_classifiers, parse_into_log_with_config, parse_into_variant_log_with_config,
+    ImportConfig,
+};
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    let Ok(content) = decode_xes(data) else {
+        return;
+    };
+    let content = content.as_str();
+    let lenient = ImportConfig {
+        lenient: true,
+        ..Default::default()
+    };
+    for config in [ImportConfig::default(), lenient] {
+        let _ = parse_into_variant_log_with_config(None, Some(content), &config);
+        let _ = parse_into_log_with_config(None, Some(content), &config);
+    }
+    let _ = list_classifiers(None, Some(content));
+});
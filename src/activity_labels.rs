@@ -0,0 +1,220 @@
+use std::collections::{BTreeMap, HashMap};
+
+/// Labels shorter than this are never compared by edit distance, otherwise short activity
+/// names like `A` and `B` would all be reported as near-duplicates.
+const MIN_EDIT_DISTANCE_LENGTH: usize = 5;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SimilarityReason {
+    /// The labels only differ in leading/trailing whitespace.
+    Whitespace,
+    /// The labels only differ in upper/lower case.
+    CaseOnly,
+    /// The labels differ in exactly one inserted, removed or replaced character.
+    EditDistanceOne,
+}
+
+impl std::fmt::Display for SimilarityReason {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            SimilarityReason::Whitespace => write!(f, "whitespace only"),
+            SimilarityReason::CaseOnly => write!(f, "case only"),
+            SimilarityReason::EditDistanceOne => write!(f, "one character apart"),
+        }
+    }
+}
+
+/// Two activity labels that probably denote the same activity.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SimilarLabels {
+    /// The more frequent of the two labels, suggested as the one to keep.
+    pub canonical: String,
+    /// The less frequent label, suggested to be merged into `canonical`.
+    pub duplicate: String,
+    pub reason: SimilarityReason,
+}
+
+/// Finds pairs of activity labels that look like dirty variants of each other.
+///
+/// # Parameters
+/// - `activity_frequencies`: every activity label with the number of events carrying it.
+///
+/// # Returns
+/// The suspicious pairs, sorted by their canonical and duplicate label.
+pub fn find_similar_labels(activity_frequencies: &BTreeMap<String, usize>) -> Vec<SimilarLabels> {
+    let mut labels: Vec<(&String, usize)> = activity_frequencies
+        .iter()
+        .map(|(label, &frequency)| (label, frequency))
+        .collect();
+    // most frequent first, so that the first label of a pair is the canonical one
+    labels.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(b.0)));
+
+    let mut result = Vec::new();
+    for (i, (first, _)) in labels.iter().enumerate() {
+        for (second, _) in &labels[i + 1..] {
+            if let Some(reason) = similarity(first, second) {
+                result.push(SimilarLabels {
+                    canonical: first.to_string(),
+                    duplicate: second.to_string(),
+                    reason,
+                });
+            }
+        }
+    }
+
+    result.sort_by(|a, b| {
+        a.canonical
+            .cmp(&b.canonical)
+            .then_with(|| a.duplicate.cmp(&b.duplicate))
+    });
+    result
+}
+
+/// Turns the confirmed merges, from duplicate label to the label to use instead, into a mapping
+/// for [`crate::variant_log::VariantLog::rename_activities`].
+///
+/// Chains (`a -> b`, `b -> c`) are resolved, so every duplicate maps to a label that is kept.
+pub fn merge_mapping(confirmed: &BTreeMap<String, String>) -> HashMap<String, String> {
+    let direct: HashMap<&str, &str> = confirmed
+        .iter()
+        .map(|(duplicate, canonical)| (duplicate.as_str(), canonical.as_str()))
+        .collect();
+
+    direct
+        .keys()
+        .map(|&duplicate| {
+            let mut target = direct[duplicate];
+            let mut steps = 0;
+            while let Some(&next) = direct.get(target) {
+                // guard against cycles in hand-crafted input
+                if steps > direct.len() {
+                    break;
+                }
+                target = next;
+                steps += 1;
+            }
+            (duplicate.to_string(), target.to_string())
+        })
+        .collect()
+}
+
+/// Why `a` and `b` probably denote the same activity, `None` if they look like different
+/// activities.
+pub fn similarity(a: &str, b: &str) -> Option<SimilarityReason> {
+    let (a_trimmed, b_trimmed) = (a.trim(), b.trim());
+
+    if a_trimmed == b_trimmed {
+        return Some(SimilarityReason::Whitespace);
+    }
+    if a_trimmed.to_lowercase() == b_trimmed.to_lowercase() {
+        return Some(SimilarityReason::CaseOnly);
+    }
+
+    let long_enough = a_trimmed.chars().count() >= MIN_EDIT_DISTANCE_LENGTH
+        && b_trimmed.chars().count() >= MIN_EDIT_DISTANCE_LENGTH;
+    // labels like "Step 1" and "Step 2" are usually different on purpose
+    let differ_in_digits_only = without_digits(a_trimmed) == without_digits(b_trimmed);
+    if long_enough && !differ_in_digits_only && levenshtein(a_trimmed, b_trimmed) == 1 {
+        return Some(SimilarityReason::EditDistanceOne);
+    }
+
+    None
+}
+
+/// How similar two labels are, from 0 for entirely different labels to 1 for equal ones: one
+/// minus their edit distance per character of the longer label.
+pub fn label_similarity(a: &str, b: &str) -> f64 {
+    let longer = a.chars().count().max(b.chars().count());
+    if longer == 0 {
+        return 1.0;
+    }
+    1.0 - levenshtein(a, b) as f64 / longer as f64
+}
+
+fn without_digits(s: &str) -> String {
+    s.chars().filter(|c| !c.is_ascii_digit()).collect()
+}
+
+fn levenshtein(a: &str, b: &str) -> usize {
+    let a: Vec<char> = a.chars().collect();
+    let b: Vec<char> = b.chars().collect();
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+
+    for (i, ca) in a.iter().enumerate() {
+        let mut current = vec![i + 1; b.len() + 1];
+        for (j, cb) in b.iter().enumerate() {
+            let substitution = previous[j] + usize::from(ca != cb);
+            current[j + 1] = substitution.min(previous[j + 1] + 1).min(current[j] + 1);
+        }
+        previous = current;
+    }
+
+    previous[b.len()]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn frequencies(labels: &[(&str, usize)]) -> BTreeMap<String, usize> {
+        labels
+            .iter()
+            .map(|(label, frequency)| (label.to_string(), *frequency))
+            .collect()
+    }
+
+    #[test]
+    fn test_find_similar_labels() {
+        let activity_frequencies = frequencies(&[
+            ("Register", 10),
+            ("register", 2),
+            ("Approve ", 1),
+            ("Approve", 8),
+            ("Send invoice", 5),
+            ("Send invoices", 1),
+            ("Step 1", 3),
+            ("Step 2", 3),
+            ("A", 4),
+            ("B", 4),
+        ]);
+
+        let expected = vec![
+            SimilarLabels {
+                canonical: "Approve".to_string(),
+                duplicate: "Approve ".to_string(),
+                reason: SimilarityReason::Whitespace,
+            },
+            SimilarLabels {
+                canonical: "Register".to_string(),
+                duplicate: "register".to_string(),
+                reason: SimilarityReason::CaseOnly,
+            },
+            SimilarLabels {
+                canonical: "Send invoice".to_string(),
+                duplicate: "Send invoices".to_string(),
+                reason: SimilarityReason::EditDistanceOne,
+            },
+        ];
+        assert_eq!(expected, find_similar_labels(&activity_frequencies));
+    }
+
+    #[test]
+    fn test_merge_mapping() {
+        let confirmed: BTreeMap<String, String> = [
+            ("register".to_string(), "Register".to_string()),
+            ("Register ".to_string(), "register".to_string()),
+        ]
+        .into();
+        let mapping = merge_mapping(&confirmed);
+        assert_eq!(mapping["register"], "Register");
+        assert_eq!(mapping["Register "], "Register");
+        assert_eq!(mapping.len(), 2);
+    }
+
+    #[test]
+    fn test_levenshtein() {
+        assert_eq!(levenshtein("kitten", "sitting"), 3);
+        assert_eq!(levenshtein("", "abc"), 3);
+        assert_eq!(levenshtein("abc", "abc"), 0);
+    }
+}
@@ -0,0 +1,163 @@
+//! When activities happen: the number of events of every activity per weekday and hour of
+//! the day, which shows shift patterns, batches processed at fixed times and automated
+//! activities running around the clock.
+
+use std::collections::BTreeMap;
+
+use chrono::{DateTime, Datelike, FixedOffset, Timelike};
+
+use crate::export::csv_field;
+use crate::i18n::{Language, Message};
+use crate::timeline::CaseTimeline;
+
+const WEEKDAYS: [(&str, &str); 7] = [
+    ("Mon", "Mo"),
+    ("Tue", "Di"),
+    ("Wed", "Mi"),
+    ("Thu", "Do"),
+    ("Fri", "Fr"),
+    ("Sat", "Sa"),
+    ("Sun", "So"),
+];
+
+// From no events to the busiest hour of an activity
+const SHADES: [char; 5] = [' ', '.', ':', '*', '#'];
+
+/// The events of an activity per weekday (Monday first) and hour of the day.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ActivityProfile {
+    pub activity: String,
+    pub events: [[usize; 24]; 7],
+}
+
+impl ActivityProfile {
+    pub fn total(&self) -> usize {
+        self.events.iter().flatten().sum()
+    }
+
+    /// The events per hour of the day, over all weekdays.
+    pub fn by_hour(&self) -> [usize; 24] {
+        let mut hours = [0; 24];
+        for day in &self.events {
+            for (hour, events) in day.iter().enumerate() {
+                hours[hour] += events;
+            }
+        }
+        hours
+    }
+
+    /// The events per weekday, Monday first.
+    pub fn by_weekday(&self) -> [usize; 7] {
+        self.events.map(|day| day.iter().sum())
+    }
+}
+
+/// Counts the recorded start and completion timestamps of every activity in the local time
+/// `utc_offset_minutes` from UTC, ordered by activity.
+pub fn activity_profiles(
+    timelines: &[CaseTimeline],
+    utc_offset_minutes: i32,
+) -> Vec<ActivityProfile> {
+    let offset = FixedOffset::east_opt(utc_offset_minutes * 60)
+        .unwrap_or(FixedOffset::east_opt(0).expect("valid offset"));
+    let mut profiles: BTreeMap<&str, [[usize; 24]; 7]> = BTreeMap::new();
+    for instance in timelines.iter().flat_map(|timeline| &timeline.instances) {
+        let events = profiles.entry(&instance.activity).or_insert([[0; 24]; 7]);
+        for time in [instance.start, instance.complete].into_iter().flatten() {
+            let local: DateTime<FixedOffset> = time.with_timezone(&offset);
+            events[local.weekday().num_days_from_monday() as usize][local.hour() as usize] += 1;
+        }
+    }
+    profiles
+        .into_iter()
+        .map(|(activity, events)| ActivityProfile {
+            activity: activity.to_string(),
+            events,
+        })
+        .collect()
+}
+
+/// The profiles as heatmap data, one row per activity, weekday (1 for Monday) and hour.
+pub fn to_csv(profiles: &[ActivityProfile]) -> String {
+    let mut output = "activity,weekday,hour,events\n".to_string();
+    for profile in profiles {
+        let activity = csv_field(&profile.activity);
+        for (day, hours) in profile.events.iter().enumerate() {
+            for (hour, events) in hours.iter().enumerate() {
+                output.push_str(&format!("{},{},{},{}\n", activity, day + 1, hour, events));
+            }
+        }
+    }
+    output
+}
+
+/// One line per activity with its events per hour of the day, shaded relative to its busiest
+/// hour, and its events per weekday.
+pub fn report(profiles: &[ActivityProfile], language: Language) -> String {
+    let mut output = format!(
+        "{}:\n{:<32}{:<10}{:<26}",
+        Message::ActivityProfile.text(language),
+        Message::Activity.text(language),
+        Message::Events.text(language),
+        " 0h    6h    12h   18h"
+    );
+    for (english, german) in WEEKDAYS {
+        let weekday = match language {
+            Language::English => english,
+            Language::German => german,
+        };
+        output.push_str(&format!("{:<6}", weekday));
+    }
+    output.push('\n');
+    for profile in profiles {
+        let hours = profile.by_hour();
+        let busiest = hours.iter().copied().max().unwrap_or(0).max(1);
+        let shading: String = hours
+            .iter()
+            .map(|&events| SHADES[(events * (SHADES.len() - 1)).div_ceil(busiest)])
+            .collect();
+        output.push_str(&format!(
+            "{:<32}{:<10}{:<26}",
+            profile.activity,
+            profile.total(),
+            format!("|{}|", shading)
+        ));
+        for events in profile.by_weekday() {
+            output.push_str(&format!("{:<6}", events));
+        }
+        output.push('\n');
+    }
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{TimeZone, Utc};
+
+    use super::*;
+
+    #[test]
+    fn test_activity_profiles() {
+        // Monday, 1 January 2024
+        let time = |day, hour| Some(Utc.with_ymd_and_hms(2024, 1, day, hour, 0, 0).unwrap());
+        let timelines = [CaseTimeline::of(
+            "1",
+            &[
+                ("Check", time(1, 8), time(1, 9), None),
+                ("Check", None, time(2, 23), None),
+                ("Batch", None, time(6, 2), None),
+            ],
+        )];
+        let profiles = activity_profiles(&timelines, 60);
+
+        assert_eq!(profiles[0].activity, "Batch");
+        assert_eq!(profiles[0].events[5][3], 1);
+        let check = &profiles[1];
+        assert_eq!(check.total(), 3);
+        // 23:00 UTC on Tuesday is midnight on Wednesday
+        assert_eq!(check.by_weekday(), [2, 0, 1, 0, 0, 0, 0]);
+        assert_eq!(check.by_hour()[0], 1);
+        assert_eq!(to_csv(&profiles).lines().count(), 1 + 2 * 7 * 24);
+        assert!(report(&profiles, Language::English).contains("|#"));
+    }
+}
@@ -0,0 +1,361 @@
+//! Alignments of traces with a Petri net: the cheapest way to explain a trace as a run of the
+//! model, step by step. Unlike the token counts of [`crate::conformance::token_replay`], they
+//! show process owners where a case deviates, event by event.
+
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, HashMap};
+
+use process_mining::PetriNet;
+
+use crate::conformance::ReplayNet;
+use crate::render::escape_xml;
+use crate::render::theme::{text_color_on, Theme};
+use crate::variant_log::VariantLog;
+
+/// States of the search for one alignment, beyond which the trace is given up on, e.g. for
+/// unbounded nets.
+const MAX_ALIGNMENT_STATES: usize = 100_000;
+
+/// Placeholder of the side of a move that didn't happen.
+const SKIP: &str = ">>";
+
+/// One step of an alignment.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AlignmentMove {
+    /// The event matches a transition the model could fire.
+    Synchronous(String),
+    /// The event has no counterpart in the model, e.g. an inserted or repeated activity.
+    LogOnly(String),
+    /// The model required the activity, but the trace skipped it.
+    ModelOnly(String),
+}
+
+impl AlignmentMove {
+    pub fn activity(&self) -> &str {
+        match self {
+            AlignmentMove::Synchronous(activity)
+            | AlignmentMove::LogOnly(activity)
+            | AlignmentMove::ModelOnly(activity) => activity,
+        }
+    }
+
+    pub fn is_deviation(&self) -> bool {
+        !matches!(self, AlignmentMove::Synchronous(_))
+    }
+}
+
+/// An optimal alignment of a trace. Invisible transitions of the model are left out.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TraceAlignment {
+    pub moves: Vec<AlignmentMove>,
+}
+
+impl TraceAlignment {
+    /// Number of log-only and model-only moves.
+    pub fn cost(&self) -> usize {
+        self.moves.iter().filter(|step| step.is_deviation()).count()
+    }
+
+    pub fn is_fitting(&self) -> bool {
+        self.cost() == 0
+    }
+
+    /// The alignment as two rows, the trace above the model run, with `>>` where a side has
+    /// no step.
+    ///
+    /// ```text
+    /// log   | A | C  | >>
+    /// model | A | >> | B
+    /// ```
+    pub fn text(&self) -> String {
+        self.labeled_text("log", "model")
+    }
+
+    /// Same as [`TraceAlignment::text`], with other labels of the rows, e.g. when a trace is
+    /// aligned with a reference trace instead of a model.
+    pub fn labeled_text(&self, log_label: &str, model_label: &str) -> String {
+        let (log, model): (Vec<&str>, Vec<&str>) = self
+            .moves
+            .iter()
+            .map(|step| match step {
+                AlignmentMove::Synchronous(activity) => (activity.as_str(), activity.as_str()),
+                AlignmentMove::LogOnly(activity) => (activity.as_str(), SKIP),
+                AlignmentMove::ModelOnly(activity) => (SKIP, activity.as_str()),
+            })
+            .unzip();
+        let widths: Vec<usize> = log
+            .iter()
+            .zip(&model)
+            .map(|(log, model)| log.chars().count().max(model.chars().count()))
+            .collect();
+        let label_width = log_label.chars().count().max(model_label.chars().count());
+        let row = |label: &str, cells: &[&str]| {
+            let cells: String = cells
+                .iter()
+                .zip(&widths)
+                .map(|(cell, &width)| format!(" | {:<width$}", cell, width = width))
+                .collect();
+            format!("{:<label_width$}{}", label, cells, label_width = label_width)
+                .trim_end()
+                .to_string()
+        };
+        format!("{}\n{}", row(log_label, &log), row(model_label, &model))
+    }
+
+    /// The alignment as an HTML table with one column per move, deviations highlighted in
+    /// the colors of `theme`.
+    pub fn html(&self, theme: &Theme) -> String {
+        self.labeled_html(theme, "log", "model")
+    }
+
+    /// Same as [`TraceAlignment::html`], with other labels of the rows.
+    pub fn labeled_html(&self, theme: &Theme, log_label: &str, model_label: &str) -> String {
+        let cell = |text: &str, fill: Option<&str>| match fill {
+            Some(fill) => format!(
+                "<td style=\"background-color: {}; color: {}; padding: 2px 6px;\">{}</td>",
+                fill,
+                text_color_on(fill),
+                escape_xml(text)
+            ),
+            None => format!("<td style=\"padding: 2px 6px;\">{}</td>", escape_xml(text)),
+        };
+        let (log_only, model_only) = (theme.palette.color(1), theme.palette.color(2));
+        let mut log = format!("<tr><th>{}</th>", escape_xml(log_label));
+        let mut model = format!("<tr><th>{}</th>", escape_xml(model_label));
+        for step in &self.moves {
+            let (log_cell, model_cell) = match step {
+                AlignmentMove::Synchronous(activity) => {
+                    (cell(activity, None), cell(activity, None))
+                }
+                AlignmentMove::LogOnly(activity) => {
+                    (cell(activity, Some(log_only)), cell(SKIP, Some(log_only)))
+                }
+                AlignmentMove::ModelOnly(activity) => (
+                    cell(SKIP, Some(model_only)),
+                    cell(activity, Some(model_only)),
+                ),
+            };
+            log.push_str(&log_cell);
+            model.push_str(&model_cell);
+        }
+        format!(
+            "<table style=\"border-collapse: collapse; color: {};\">{}</tr>{}</tr></table>",
+            theme.text_color(),
+            log,
+            model
+        )
+    }
+}
+
+/// Alignments of every variant of a log.
+#[derive(Debug, Clone)]
+pub struct AlignmentReport {
+    /// Every variant with its frequency and its alignment, `None` if the search gave up.
+    pub variants: Vec<(Vec<String>, usize, Option<TraceAlignment>)>,
+}
+
+impl AlignmentReport {
+    /// Number of cases whose variant could be aligned without deviations.
+    pub fn fitting_cases(&self) -> usize {
+        self.variants
+            .iter()
+            .filter(|(_, _, alignment)| alignment.as_ref().is_some_and(TraceAlignment::is_fitting))
+            .map(|(_, frequency, _)| frequency)
+            .sum()
+    }
+}
+
+/// Aligns every variant of `log` with `net`, see [`align_trace`].
+pub fn align_log(net: &PetriNet, log: &VariantLog) -> AlignmentReport {
+    let net = ReplayNet::new(net);
+    AlignmentReport {
+        variants: log
+            .variants()
+            .map(|(variant, frequency)| (variant.to_vec(), frequency, align(&net, variant)))
+            .collect(),
+    }
+}
+
+/// Computes an optimal alignment of `trace` with `net`: a run of the net from its initial
+/// to its first final marking (any marking without final marking) that agrees with as many
+/// events as possible. Log-only and visible model-only moves cost 1, synchronous and
+/// invisible moves nothing.
+///
+/// Returns `None` if no run reaches the final marking within [`MAX_ALIGNMENT_STATES`]
+/// search states.
+pub fn align_trace(net: &PetriNet, trace: &[String]) -> Option<TraceAlignment> {
+    align(&ReplayNet::new(net), trace)
+}
+
+// The step leading to a search state
+#[derive(Clone, Copy)]
+enum Step {
+    Synchronous,
+    Log,
+    Model(usize),
+}
+
+struct SearchState {
+    position: usize,
+    marking: Vec<u64>,
+    // the previous state and the step leading from it to this one
+    predecessor: Option<(usize, Step)>,
+}
+
+// Dijkstra's shortest path search over (trace position, marking)
+fn align(net: &ReplayNet, trace: &[String]) -> Option<TraceAlignment> {
+    let mut states = vec![SearchState {
+        position: 0,
+        marking: net.initial_marking.clone(),
+        predecessor: None,
+    }];
+    let mut costs: HashMap<(usize, Vec<u64>), usize> =
+        HashMap::from([((0, net.initial_marking.clone()), 0)]);
+    let mut queue = BinaryHeap::from([Reverse((0, 0))]);
+
+    while let Some(Reverse((cost, state))) = queue.pop() {
+        let position = states[state].position;
+        let marking = states[state].marking.clone();
+        // a cheaper way to the same state was found after this one was queued
+        if costs.get(&(position, marking.clone())) != Some(&cost) {
+            continue;
+        }
+        let is_final = net
+            .final_marking
+            .as_ref()
+            .is_none_or(|final_marking| *final_marking == marking);
+        if position == trace.len() && is_final {
+            return Some(moves(net, trace, &states, state));
+        }
+        if states.len() > MAX_ALIGNMENT_STATES {
+            return None;
+        }
+
+        let mut successors = Vec::new();
+        if position < trace.len() {
+            successors.push((position + 1, marking.clone(), Step::Log, 1));
+        }
+        for (idx, transition) in net.transitions.iter().enumerate() {
+            if !net.is_enabled(idx, &marking) {
+                continue;
+            }
+            let mut next = marking.clone();
+            for &(place, weight) in &transition.pre {
+                next[place] -= weight;
+            }
+            for &(place, weight) in &transition.post {
+                next[place] += weight;
+            }
+            match &transition.label {
+                Some(label) => {
+                    if trace.get(position) == Some(label) {
+                        successors.push((position + 1, next.clone(), Step::Synchronous, 0));
+                    }
+                    successors.push((position, next, Step::Model(idx), 1));
+                }
+                None => successors.push((position, next, Step::Model(idx), 0)),
+            }
+        }
+
+        for (next_position, next_marking, step, step_cost) in successors {
+            let next_cost = cost + step_cost;
+            let key = (next_position, next_marking);
+            if costs.get(&key).is_some_and(|&known| known <= next_cost) {
+                continue;
+            }
+            costs.insert(key.clone(), next_cost);
+            states.push(SearchState {
+                position: key.0,
+                marking: key.1,
+                predecessor: Some((state, step)),
+            });
+            queue.push(Reverse((next_cost, states.len() - 1)));
+        }
+    }
+    None
+}
+
+// Follows the predecessors of `state` back to the initial state
+fn moves(
+    net: &ReplayNet,
+    trace: &[String],
+    states: &[SearchState],
+    mut state: usize,
+) -> TraceAlignment {
+    let mut moves = Vec::new();
+    while let Some((previous, step)) = states[state].predecessor {
+        let position = states[previous].position;
+        match step {
+            Step::Synchronous => moves.push(AlignmentMove::Synchronous(trace[position].clone())),
+            Step::Log => moves.push(AlignmentMove::LogOnly(trace[position].clone())),
+            Step::Model(transition) => {
+                if let Some(label) = &net.transitions[transition].label {
+                    moves.push(AlignmentMove::ModelOnly(label.clone()));
+                }
+            }
+        }
+        state = previous;
+    }
+    moves.reverse();
+    TraceAlignment { moves }
+}
+
+#[cfg(test)]
+mod tests {
+    use process_mining::petri_net::petri_net_struct::{ArcType, Marking};
+
+    use super::*;
+    use crate::variant_log::trace;
+
+    // start -> A -> p1 -> B -> p2 -> (C | tau) -> end
+    fn net() -> PetriNet {
+        let mut net = PetriNet::new();
+        let places: Vec<_> = (0..4).map(|_| net.add_place(None)).collect();
+        for (idx, label) in [Some("A"), Some("B"), Some("C"), None]
+            .into_iter()
+            .enumerate()
+        {
+            let transition = net.add_transition(label.map(str::to_string), None);
+            let (from, to) = match idx {
+                3 => (2, 3),
+                _ => (idx, idx + 1),
+            };
+            net.add_arc(ArcType::place_to_transition(places[from], transition), None);
+            net.add_arc(ArcType::transition_to_place(transition, places[to]), None);
+        }
+        net.initial_marking = Some(Marking::from([(places[0], 1)]));
+        net.final_markings = Some(vec![Marking::from([(places[3], 1)])]);
+        net
+    }
+
+    #[test]
+    fn test_align_trace() {
+        let fitting = align_trace(&net(), &trace(&["A", "B"])).unwrap();
+        assert!(fitting.is_fitting());
+        assert_eq!(fitting.moves.len(), 2);
+
+        let deviating = align_trace(&net(), &trace(&["A", "X", "C"])).unwrap();
+        assert_eq!(
+            deviating.moves,
+            [
+                AlignmentMove::Synchronous("A".to_string()),
+                AlignmentMove::LogOnly("X".to_string()),
+                AlignmentMove::ModelOnly("B".to_string()),
+                AlignmentMove::Synchronous("C".to_string()),
+            ]
+        );
+        assert_eq!(deviating.cost(), 2);
+        assert_eq!(
+            deviating.text(),
+            "log   | A | X  | >> | C\nmodel | A | >> | B  | C"
+        );
+        let html = deviating.html(&Theme::default());
+        assert_eq!(html.matches("<td").count(), 8);
+        assert_eq!(html.matches("background-color").count(), 4);
+
+        let log =
+            VariantLog::from_traces(vec![trace(&["A", "B"]), trace(&["B"]), trace(&["A", "B"])]);
+        let report = align_log(&net(), &log);
+        assert_eq!(report.fitting_cases(), 2);
+    }
+}
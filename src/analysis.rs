@@ -0,0 +1,929 @@
+use std::borrow::Cow;
+use std::cmp::Reverse;
+use std::collections::{BTreeMap, HashSet};
+
+use process_mining::PetriNet;
+
+use crate::activity_labels::{find_similar_labels, merge_mapping, SimilarLabels};
+use crate::alignment::{align_log, AlignmentReport, TraceAlignment};
+use crate::arrivals::ArrivalStats;
+use crate::completeness::{case_ids, incomplete_cases, IncompleteCase};
+use crate::config::AnalysisConfig;
+use crate::conformance::{fitness_trend, token_replay, trend_report, FitnessWindow, ReplayReport};
+use crate::deviations::{self, aggregate_deviations, ActivityDeviation};
+use crate::glossary::{metric_for_label, Metric};
+use crate::health::LogHealth;
+use crate::i18n::{Language, Message};
+use crate::metrics::{MetricRegistry, MetricResult};
+use crate::monitoring::WindowSize;
+use crate::parser::diagnostics::ImportDiagnostics;
+use crate::parser::{read_xes, ImportedLog, ParseError};
+use crate::recommendation::ThresholdRecommendation;
+use crate::resource_dependencies::{self, resource_dependencies, ResourceDependency};
+use crate::subprocess::Milestones;
+use crate::long_distance::{self, long_distance_dependencies, LongDistanceDependency};
+use crate::random::SeededRng;
+use crate::repair::{self, suggest_from_alignments, suggest_from_dependencies, RepairSuggestion};
+use crate::sampling::{sample_cases, Approximation};
+use crate::simplicity::Simplicity;
+use crate::simulation::{SimulationComparison, SimulationModel};
+use crate::sla::{self, sla_reports, SlaReport};
+use crate::temporal_constraints::TemporalConformance;
+use crate::timeline::{case_timelines, CaseKpis, CaseTimeline};
+use crate::trace_length::TraceLengthDistribution;
+use crate::variant_log::VariantLog;
+use crate::weights::{case_weights, WeightedAnalysis};
+use crate::{generate_adj_matrix_from_variant_log_with_options, ExtendedPrefixAutomaton};
+
+/// Number of deviating variants whose alignment is shown in a [`ModelConformance::report`].
+const MAX_SHOWN_ALIGNMENTS: usize = 5;
+
+/// Where the XES log of an [`analyze`] call comes from.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LogInput {
+    /// Path of a log file.
+    Path(String),
+    /// The content of a log, e.g. uploaded in the browser.
+    Content(String),
+}
+
+impl LogInput {
+    // The text of the log, read and decompressed once for all parser functions
+    fn read(&self) -> Result<Cow<'_, str>, ParseError> {
+        match self {
+            LogInput::Path(path) => read_xes(path).map(Cow::Owned),
+            LogInput::Content(content) => Ok(Cow::Borrowed(content)),
+        }
+    }
+}
+
+/// The dependency matrix of a log and the relations counted while building it, see
+/// [`generate_adj_matrix_from_variant_log_with_options`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct DependencyMatrix {
+    /// The matrix as CSV.
+    pub text: String,
+    /// Activity pairs without temporal and existential dependency.
+    pub full_independences: usize,
+    /// Activity pairs with an existential but without a temporal dependency.
+    pub pure_existences: usize,
+    pub eventual_equivalences: usize,
+    pub direct_equivalences: usize,
+    pub activities: usize,
+    /// Number of activity pairs per dependency type.
+    pub relationship_counts: BTreeMap<String, usize>,
+    /// The sample the matrix was computed on, if the log was sampled, see
+    /// [`crate::sampling`].
+    pub approximation: Option<Approximation>,
+}
+
+impl DependencyMatrix {
+    /// Number of ordered activity pairs, including each activity with itself.
+    pub fn relations(&self) -> usize {
+        self.activities * self.activities
+    }
+}
+
+/// How the analyzed log conforms to a reference model, see [`AnalysisResult::conformance`].
+#[derive(Debug, Clone)]
+pub struct ModelConformance {
+    /// The token replay of the variants of the log.
+    pub replay: ReplayReport,
+    /// The size and interconnectedness of the model.
+    pub simplicity: Simplicity,
+    /// The fitness of about ten windows of consecutively completed cases, empty without
+    /// timestamps.
+    pub trend: Vec<FitnessWindow>,
+    pub alignments: AlignmentReport,
+    /// The deviations of the alignments per activity, see [`aggregate_deviations`].
+    pub deviations: Vec<ActivityDeviation>,
+    /// The repairs the alignments suggest, see [`suggest_from_alignments`].
+    pub repairs: Vec<RepairSuggestion>,
+}
+
+impl ModelConformance {
+    /// Renders the fitness, the model's simplicity, the fitness trend and, if cases deviate
+    /// from the model, their deviations, the suggested repairs and the most frequent deviating
+    /// variants as plain text.
+    pub fn report(&self, language: Language) -> String {
+        let line = |label: Message, value: String| {
+            format!("{:<48}{:<10}\n", format!("{}:", label.text(language)), value)
+        };
+        let mut deviating: Vec<(usize, &TraceAlignment)> = self
+            .alignments
+            .variants
+            .iter()
+            .filter_map(|(_, frequency, alignment)| {
+                alignment
+                    .as_ref()
+                    .filter(|alignment| !alignment.is_fitting())
+                    .map(|alignment| (*frequency, alignment))
+            })
+            .collect();
+        deviating.sort_by_key(|(frequency, _)| Reverse(*frequency));
+        let trend = if self.trend.is_empty() {
+            String::new()
+        } else {
+            format!(
+                "\n{}:\n{}",
+                Message::FitnessTrend.text(language),
+                trend_report(&self.trend, language)
+            )
+        };
+        let alignments = if deviating.is_empty() {
+            String::new()
+        } else {
+            format!(
+                "\n{}:\n{}\n{}\n{}:\n{}",
+                Message::DeviationsByActivity.text(language),
+                deviations::report(&self.deviations, language),
+                repair::report(&self.repairs, language),
+                Message::DeviatingVariants.text(language),
+                deviating
+                    .iter()
+                    .take(MAX_SHOWN_ALIGNMENTS)
+                    .map(|(frequency, alignment)| format!("{}x\n{}\n", frequency, alignment.text()))
+                    .collect::<Vec<String>>()
+                    .join("\n")
+            )
+        };
+        format!(
+            "{}:\n{}{}{}{}{}",
+            Message::TokenReplay.text(language),
+            line(Message::Fitness, format!("{:.4}", self.replay.fitness())),
+            line(
+                Message::FittingTraces,
+                format!("{} / {}", self.replay.fitting_cases(), self.replay.cases())
+            ),
+            self.simplicity
+                .metrics()
+                .into_iter()
+                .map(|(label, value)| line(label, value))
+                .collect::<String>(),
+            trend,
+            alignments
+        )
+        .trim_end()
+        .to_string()
+    }
+}
+
+/// Everything [`analyze`] computes for a log.
+#[derive(Debug)]
+pub struct AnalysisResult {
+    /// The configuration the analysis ran with.
+    pub config: AnalysisConfig,
+    /// The analyzed log, after the filters and label merging of the configuration.
+    pub log: VariantLog,
+    /// Data quality checks, `None` if they failed.
+    pub health: Option<LogHealth>,
+    /// Import diagnostics and notes about incomplete cases and a sampled matrix, in the
+    /// configured language.
+    pub diagnostics: Vec<String>,
+    /// Cases flagged by the completeness heuristics, excluded from `log` if configured.
+    pub incomplete_cases: Vec<IncompleteCase>,
+    /// Trace lengths of the filtered log with their outliers, excluded from `log` if
+    /// configured.
+    pub trace_lengths: Option<TraceLengthDistribution>,
+    /// Activity labels that only differ in spelling, found before merging the confirmed ones
+    /// in `log`, see [`crate::config::Filters::merged_labels`].
+    pub similar_labels: Vec<SimilarLabels>,
+    /// Key figures of the finished cases in `log`, in business time if a calendar is
+    /// configured.
+    pub cases: Vec<CaseKpis>,
+    /// The activity instances of the cases in `log`, with their activities renamed and
+    /// removed like in `log`, e.g. to export the cleaned log.
+    pub timelines: Vec<CaseTimeline>,
+    pub matrix: DependencyMatrix,
+    /// The prefix automaton of `log`, partitioned with the configured strategy.
+    pub epa: ExtendedPrefixAutomaton,
+    /// The built-in log metrics, with confidence intervals if bootstrapping is configured.
+    pub log_metrics: Vec<MetricResult>,
+    /// The recommended directly-follows graph threshold, if a sweep is configured.
+    pub threshold_recommendation: Option<ThresholdRecommendation>,
+    /// Long-distance dependencies between choices of `log`, if configured.
+    pub long_distance_dependencies: Vec<LongDistanceDependency>,
+    /// Repairs for the cases of `log` that violate high-confidence dependencies, if
+    /// configured.
+    pub repair_suggestions: Vec<RepairSuggestion>,
+    /// Dependencies that only hold with the same or with different resources, if configured.
+    pub resource_dependencies: Vec<ResourceDependency>,
+    /// Expected times between activities and the cases exceeding them, if configured.
+    pub temporal_conformance: Option<TemporalConformance>,
+    /// Breaches of the configured SLAs, in the order of the configuration.
+    pub sla_reports: Vec<SlaReport>,
+    /// Simulated key figures as observed and with the what-if parameters, if configured.
+    pub simulation: Option<SimulationComparison>,
+    /// Inter-arrival times and arrival rates of the cases in `log`, if configured and at
+    /// least two cases have timestamps.
+    pub arrivals: Option<ArrivalStats>,
+    /// Variants, dependencies and durations of `log` weighted by a case attribute, if
+    /// configured.
+    pub weighted: Option<WeightedAnalysis>,
+}
+
+/// Runs the standard analysis of a log: parsing and data quality checks, the filters of
+/// `config`, the dependency matrix, the prefix automaton with its entropy and the other log
+/// metrics. This is what the web UI and the CLI show for an imported log.
+///
+/// ```no_run
+/// use egypt::config::AnalysisConfig;
+/// use egypt::{analyze, LogInput};
+///
+/// let input = LogInput::Path("log.xes".to_string());
+/// let result = analyze(input, AnalysisConfig::default()).unwrap();
+/// println!("{}", result.report());
+/// ```
+pub fn analyze(input: LogInput, config: AnalysisConfig) -> Result<AnalysisResult, ParseError> {
+    let content = input.read()?;
+    let import_config = config.import.import_config();
+    let imported = ImportedLog::import(None, Some(&content), &import_config)?;
+    let log = imported.to_log(&import_config)?.to_variant_log();
+
+    let timelines = case_timelines(&imported.log, &config.import.classifier).unwrap_or_default();
+    let incomplete = incomplete_cases(&timelines, &config.completeness);
+    let log = if incomplete.is_empty() {
+        log
+    } else if config.filters.exclude_incomplete_cases {
+        log.without_cases(&case_ids(&incomplete))
+    } else if config.completeness.mark_ongoing {
+        log.mark_ongoing(&case_ids(&incomplete))
+    } else {
+        log
+    };
+    // what the filters and the label merging make of the activities of the timelines
+    let mut activity_mapping = config.filters.activity_mapping(&log.activities());
+    let log = config.filters.apply(log);
+    let similar_labels = find_similar_labels(&log.activity_frequencies());
+    let log = if config.filters.merged_labels.is_empty() {
+        log
+    } else {
+        let merged = merge_mapping(&config.filters.merged_labels);
+        for mapped in activity_mapping.values_mut().flatten() {
+            if let Some(canonical) = merged.get(mapped) {
+                *mapped = canonical.clone();
+            }
+        }
+        log.rename_activities(&merged)
+    };
+    let trace_lengths = config
+        .trace_length
+        .map(|settings| TraceLengthDistribution::of(&log, &settings));
+    let excluded_outliers = trace_lengths
+        .as_ref()
+        .filter(|_| config.trace_length.is_some_and(|settings| settings.exclude_outliers))
+        .map(TraceLengthDistribution::outlier_ids)
+        .unwrap_or_default();
+    let log = if excluded_outliers.is_empty() {
+        log
+    } else {
+        log.without_cases(&excluded_outliers)
+    };
+    let analyzed_cases: HashSet<&str> = log
+        .cases()
+        .iter()
+        .map(|case| case.case_id.as_str())
+        .collect();
+    let timelines: Vec<CaseTimeline> = timelines
+        .into_iter()
+        .filter(|timeline| analyzed_cases.contains(timeline.case_id.as_str()))
+        .collect();
+    let ongoing: HashSet<&str> = log
+        .cases()
+        .iter()
+        .filter(|case| case.ongoing)
+        .map(|case| case.case_id.as_str())
+        .collect();
+    let cases = timelines
+        .iter()
+        .filter(|timeline| !ongoing.contains(timeline.case_id.as_str()))
+        .map(|timeline| timeline.kpis(config.calendar.as_ref()))
+        .collect();
+    let resource_dependencies = config
+        .resource_dependencies
+        .map(|settings| resource_dependencies(&timelines, &settings))
+        .unwrap_or_default();
+    let temporal_conformance = config.temporal_constraints.map(|settings| {
+        TemporalConformance::of(&timelines, &settings, config.calendar.as_ref())
+    });
+    let sla_reports = sla_reports(&timelines, &config.sla, config.calendar.as_ref());
+    let weighted = config.weights.as_ref().map(|settings| {
+        let weights = case_weights(&imported.log, &settings.attribute);
+        WeightedAnalysis::of(&log, &timelines, &weights, settings, config.calendar.as_ref())
+    });
+    let arrivals = config
+        .arrivals
+        .and_then(|settings| ArrivalStats::of(&timelines, &settings));
+    let simulation = config.simulation.as_ref().and_then(|settings| {
+        SimulationModel::fit(&timelines).map(|model| model.compare(settings, config.seed))
+    });
+
+    // the health checks and diagnostics see the events before duplicates are dropped
+    let checked = imported.with_duplicates();
+    let health =
+        LogHealth::assess(checked, &config.import.classifier, &config.completeness).ok();
+    let mut diagnostics = ImportDiagnostics::assess(&content, checked, &import_config)
+        .map(|diagnostics| diagnostics.messages(&import_config, config.language))
+        .unwrap_or_default();
+    if !incomplete.is_empty() {
+        let message = if config.filters.exclude_incomplete_cases {
+            Message::IncompleteCasesExcluded
+        } else if config.completeness.mark_ongoing {
+            Message::IncompleteCasesOngoing
+        } else {
+            Message::IncompleteCases
+        };
+        diagnostics.push(message.format(config.language, &[&incomplete.len().to_string()]));
+    }
+    if !excluded_outliers.is_empty() {
+        diagnostics.push(
+            Message::TraceLengthOutliersExcluded
+                .format(config.language, &[&excluded_outliers.len().to_string()]),
+        );
+    }
+
+    let (
+        matrix,
+        epa,
+        log_metrics,
+        threshold_recommendation,
+        long_distance_dependencies,
+        repair_suggestions,
+    ) = control_flow_analyses(&log, &config);
+    if let Some(approximation) = &matrix.approximation {
+        diagnostics.push(Message::ApproximateMatrix.format(
+            config.language,
+            &[
+                &approximation.sampled_cases.to_string(),
+                &approximation.total_cases.to_string(),
+            ],
+        ));
+    }
+
+    let timelines = timelines
+        .into_iter()
+        .map(|mut timeline| {
+            timeline.instances.retain_mut(|instance| {
+                match activity_mapping.get(&instance.activity) {
+                    Some(Some(mapped)) => {
+                        instance.activity = mapped.clone();
+                        true
+                    }
+                    Some(None) => false,
+                    None => true,
+                }
+            });
+            timeline
+        })
+        .collect();
+
+    Ok(AnalysisResult {
+        config,
+        log,
+        health,
+        diagnostics,
+        incomplete_cases: incomplete,
+        trace_lengths,
+        similar_labels,
+        cases,
+        timelines,
+        matrix,
+        epa,
+        log_metrics,
+        threshold_recommendation,
+        long_distance_dependencies,
+        repair_suggestions,
+        resource_dependencies,
+        temporal_conformance,
+        sla_reports,
+        simulation,
+        arrivals,
+        weighted,
+    })
+}
+
+// The analyses that only depend on the variants of the log
+fn control_flow_analyses(
+    log: &VariantLog,
+    config: &AnalysisConfig,
+) -> (
+    DependencyMatrix,
+    ExtendedPrefixAutomaton,
+    Vec<MetricResult>,
+    Option<ThresholdRecommendation>,
+    Vec<LongDistanceDependency>,
+    Vec<RepairSuggestion>,
+) {
+    let sample = config
+        .sampling
+        .filter(|sampling| sampling.applies(log))
+        .map(|sampling| {
+            let mut rng = SeededRng::new(config.seed);
+            (sampling, sample_cases(log, sampling.sample_size, &mut rng))
+        });
+    let matrix = DependencyMatrix {
+        approximation: sample.as_ref().map(|(sampling, sample)| {
+            Approximation::of(
+                sample,
+                log.len(),
+                sampling.confidence_level,
+                config.thresholds.existential,
+            )
+        }),
+        ..generate_adj_matrix_from_variant_log_with_options(
+            sample.as_ref().map_or(log, |(_, sample)| sample),
+            &config.matrix_options(),
+            &[],
+        )
+    };
+
+    let registry = MetricRegistry::with_builtins_and_partition_strategy(config.partition_strategy);
+    let log_metrics = match &config.bootstrap {
+        Some(bootstrap) => registry.compute_with_bootstrap(log, config.language, bootstrap, config.seed),
+        None => registry.compute(log, config.language),
+    };
+    let epa = ExtendedPrefixAutomaton::from_variant_log_with_partition_strategy(
+        log,
+        config.partition_strategy,
+    );
+    let threshold_recommendation = config.threshold_sweep.and_then(|sweep| sweep.run(log));
+    let long_distance_dependencies = config
+        .long_distance
+        .map(|settings| long_distance_dependencies(log, &settings))
+        .unwrap_or_default();
+    let repair_suggestions = config
+        .repair
+        .map(|settings| suggest_from_dependencies(log, &settings))
+        .unwrap_or_default();
+    (
+        matrix,
+        epa,
+        log_metrics,
+        threshold_recommendation,
+        long_distance_dependencies,
+        repair_suggestions,
+    )
+}
+
+impl AnalysisResult {
+    /// Drills down into the sub-process between `milestones`: the dependency matrix, prefix
+    /// automaton and log metrics of the sub-log of `log`, see [`Milestones::sub_log`]. The
+    /// data quality checks, diagnostics and case key figures refer to whole cases and are
+    /// left empty.
+    pub fn sub_process(&self, milestones: &Milestones) -> AnalysisResult {
+        let log = milestones.sub_log(&self.log);
+        let (
+            matrix,
+            epa,
+            log_metrics,
+            threshold_recommendation,
+            long_distance_dependencies,
+            repair_suggestions,
+        ) = control_flow_analyses(&log, &self.config);
+        AnalysisResult {
+            config: self.config.clone(),
+            log,
+            health: None,
+            diagnostics: Vec::new(),
+            incomplete_cases: Vec::new(),
+            trace_lengths: None,
+            similar_labels: Vec::new(),
+            cases: Vec::new(),
+            timelines: Vec::new(),
+            matrix,
+            epa,
+            log_metrics,
+            threshold_recommendation,
+            long_distance_dependencies,
+            repair_suggestions,
+            resource_dependencies: Vec::new(),
+            temporal_conformance: None,
+            sla_reports: Vec::new(),
+            simulation: None,
+            arrivals: None,
+            weighted: None,
+        }
+    }
+
+    /// Checks `log` against the reference model `net`: token replay, its trend over the
+    /// completion of the cases, alignments and the deviations and repairs they show.
+    pub fn conformance(&self, net: &PetriNet) -> ModelConformance {
+        let alignments = align_log(net, &self.log);
+        let window = WindowSize::Cases(self.log.len().div_ceil(10).max(1));
+        ModelConformance {
+            replay: token_replay(net, &self.log),
+            simplicity: Simplicity::of_petri_net(net),
+            trend: fitness_trend(net, &self.log, &self.timelines, window),
+            deviations: aggregate_deviations(&alignments, &self.log),
+            repairs: suggest_from_alignments(&alignments, &self.log),
+            alignments,
+        }
+    }
+
+    /// The summary numbers of the dependency matrix.
+    pub fn matrix_metrics(&self) -> [(Message, String); 5] {
+        let relations = self.matrix.relations();
+        [
+            (Message::Relations, relations.to_string()),
+            (
+                Message::IndependencesPerRelations,
+                format!(
+                    "{:.4}",
+                    self.matrix.full_independences as f64 / relations as f64
+                ),
+            ),
+            (
+                Message::TemporalIndependencesPerRelations,
+                format!(
+                    "{:.4}",
+                    self.matrix.pure_existences as f64 / relations as f64
+                ),
+            ),
+            (
+                Message::EventualEquivalences,
+                self.matrix.eventual_equivalences.to_string(),
+            ),
+            (
+                Message::DirectEquivalences,
+                self.matrix.direct_equivalences.to_string(),
+            ),
+        ]
+    }
+
+    /// The matrix and log metrics with their labels, in report order.
+    pub fn summary(&self) -> Vec<(String, String)> {
+        let language = self.config.language;
+        self.matrix_metrics()
+            .into_iter()
+            .map(|(label, value)| (label.text(language).to_string(), value))
+            .chain(self.log_metrics.iter().map(|result| {
+                let value = match result.confidence_interval {
+                    Some(interval) => format!("{} {}", result.value, interval),
+                    None => result.value.to_string(),
+                };
+                (result.label.clone(), value)
+            }))
+            .collect()
+    }
+
+    /// Glossary entries of the metrics in the [`AnalysisResult::summary`].
+    pub fn glossary_metrics(&self) -> Vec<Metric> {
+        let registry =
+            MetricRegistry::with_builtins_and_partition_strategy(self.config.partition_strategy);
+        self.matrix_metrics()
+            .iter()
+            .filter_map(|(label, _)| metric_for_label(*label).copied())
+            .chain(
+                registry
+                    .metrics()
+                    .filter_map(|metric| metric.glossary().copied()),
+            )
+            .collect()
+    }
+
+    /// Renders the data quality checks, the matrix, the summary, the dependency type
+    /// frequencies, the estimated cells of a sampled matrix, the threshold recommendation, the
+    /// long-distance dependencies, the repair suggestions, the resource-aware dependencies, the
+    /// temporal constraints, the SLA breaches, the case arrivals, the what-if simulation, the
+    /// weighted analysis, the trace length distribution and the similar labels as plain text.
+    pub fn report(&self) -> String {
+        let language = self.config.language;
+        let health = self
+            .health
+            .as_ref()
+            .map(|health| format!("{}\n", health.report(language)))
+            .unwrap_or_default();
+        let summary: String = self
+            .summary()
+            .into_iter()
+            .map(|(label, value)| format!("{:<48}{:<10}\n", format!("{}:", label), value))
+            .collect();
+        let approximation = self
+            .matrix
+            .approximation
+            .as_ref()
+            .map(|approximation| format!("\n\n{}", approximation.report(language).trim_end()))
+            .unwrap_or_default();
+        let threshold = self
+            .threshold_recommendation
+            .as_ref()
+            .map(|recommendation| format!("\n\n{}", recommendation.report(language).trim_end()))
+            .unwrap_or_default();
+        let long_distance = if self.long_distance_dependencies.is_empty() {
+            String::new()
+        } else {
+            format!(
+                "\n\n{}",
+                long_distance::report(&self.long_distance_dependencies, language).trim_end()
+            )
+        };
+        let repair = if self.repair_suggestions.is_empty() {
+            String::new()
+        } else {
+            format!(
+                "\n\n{}",
+                repair::report(&self.repair_suggestions, language).trim_end()
+            )
+        };
+        let resource = if self.resource_dependencies.is_empty() {
+            String::new()
+        } else {
+            format!(
+                "\n\n{}",
+                resource_dependencies::report(&self.resource_dependencies, language).trim_end()
+            )
+        };
+        let temporal = self
+            .temporal_conformance
+            .as_ref()
+            .map(|conformance| format!("\n\n{}", conformance.report(language).trim_end()))
+            .unwrap_or_default();
+        let sla = if self.sla_reports.is_empty() {
+            String::new()
+        } else {
+            format!("\n\n{}", sla::report(&self.sla_reports, language).trim_end())
+        };
+        let arrivals = self
+            .arrivals
+            .as_ref()
+            .map(|arrivals| format!("\n\n{}", arrivals.report(language).trim_end()))
+            .unwrap_or_default();
+        let simulation = self
+            .simulation
+            .as_ref()
+            .map(|comparison| format!("\n\n{}", comparison.report(language).trim_end()))
+            .unwrap_or_default();
+        let weighted = self
+            .weighted
+            .as_ref()
+            .zip(self.config.weights.as_ref())
+            .map(|(weighted, settings)| {
+                format!("\n\n{}", weighted.report(settings.top, language).trim_end())
+            })
+            .unwrap_or_default();
+        let trace_lengths = self
+            .trace_lengths
+            .as_ref()
+            .map(|distribution| format!("\n\n{}", distribution.report(language).trim_end()))
+            .unwrap_or_default();
+        let merged_labels = &self.config.filters.merged_labels;
+        let unconfirmed: Vec<String> = self
+            .similar_labels
+            .iter()
+            .filter(|pair| merged_labels.get(&pair.duplicate) != Some(&pair.canonical))
+            .map(|pair| format!("'{}' ~ '{}' ({})", pair.canonical, pair.duplicate, pair.reason))
+            .collect();
+        let mut similar_labels = String::new();
+        if !unconfirmed.is_empty() {
+            similar_labels += &format!(
+                "\n\n{}:\n{}",
+                Message::SimilarActivityLabels.text(language),
+                unconfirmed.join("\n")
+            );
+        }
+        if !merged_labels.is_empty() {
+            similar_labels += &format!(
+                "\n\n{}:\n{}",
+                Message::MergedActivityLabels.text(language),
+                merged_labels
+                    .iter()
+                    .map(|(duplicate, canonical)| format!("'{}' -> '{}'", duplicate, canonical))
+                    .collect::<Vec<String>>()
+                    .join("\n")
+            );
+        }
+
+        format!(
+            "{}{}\n\n{}\n{}:\n{}{}{}{}{}{}{}{}{}{}{}{}{}",
+            health,
+            self.matrix.text,
+            summary,
+            Message::RelationshipTypeFrequencies.text(language),
+            self.matrix
+                .relationship_counts
+                .iter()
+                .map(|(relationship, count)| format!("{}: {}", relationship, count))
+                .collect::<Vec<String>>()
+                .join("\n"),
+            approximation,
+            threshold,
+            long_distance,
+            repair,
+            resource,
+            temporal,
+            sla,
+            arrivals,
+            simulation,
+            weighted,
+            trace_lengths,
+            similar_labels
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::Classifier;
+
+    #[test]
+    fn test_analyze() {
+        let config = AnalysisConfig {
+            language: Language::German,
+            ..Default::default()
+        };
+        let result = analyze(
+            LogInput::Path("./sample-data/exercise2.xes".to_string()),
+            config,
+        )
+        .unwrap();
+
+        assert!(!result.log.is_empty());
+        assert!(result.health.is_some());
+        assert_eq!(result.matrix.activities, result.log.activities().len());
+        assert_eq!(result.cases.len(), result.log.len());
+        assert_eq!(
+            result.epa.partition_strategy(),
+            result.config.partition_strategy
+        );
+        assert_eq!(result.summary().len(), 5 + result.log_metrics.len());
+        assert_eq!(result.glossary_metrics().len(), result.summary().len());
+        assert!(result.threshold_recommendation.is_none());
+        assert!(result.long_distance_dependencies.is_empty());
+        assert!(result.repair_suggestions.is_empty());
+        assert!(result.resource_dependencies.is_empty());
+        assert!(result.temporal_conformance.is_none());
+        assert!(result.sla_reports.is_empty());
+        assert!(result.simulation.is_none());
+        assert!(result.arrivals.is_none());
+        assert!(result.trace_lengths.is_none());
+        assert!(result.weighted.is_none());
+
+        let report = result.report();
+        assert!(report.contains(&result.matrix.text));
+        assert!(report.contains("Varianten-Entropie"));
+
+        let content = std::fs::read_to_string("./sample-data/exercise2.xes").unwrap();
+        let from_content = analyze(LogInput::Content(content), result.config.clone()).unwrap();
+        assert_eq!(from_content.log.to_traces(), result.log.to_traces());
+
+        let mut unknown_classifier = AnalysisConfig::default();
+        unknown_classifier.import.classifier = Classifier::Named("Does not exist".to_string());
+        assert!(analyze(
+            LogInput::Path("./sample-data/exercise2.xes".to_string()),
+            unknown_classifier
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn test_analyze_gzipped() {
+        use flate2::write::GzEncoder;
+        use flate2::Compression;
+        use std::io::Write;
+
+        let xes = std::fs::read("./sample-data/exercise2.xes").unwrap();
+        let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+        encoder.write_all(&xes).unwrap();
+        let path = std::env::temp_dir().join("egypt_test_analyze_gzipped.xes.gz");
+        std::fs::write(&path, encoder.finish().unwrap()).unwrap();
+        let result = analyze(
+            LogInput::Path(path.to_str().unwrap().to_string()),
+            AnalysisConfig::default(),
+        );
+        std::fs::remove_file(&path).unwrap();
+
+        let result = result.unwrap();
+        let plain = analyze(
+            LogInput::Path("./sample-data/exercise2.xes".to_string()),
+            AnalysisConfig::default(),
+        )
+        .unwrap();
+        assert!(result.health.is_some());
+        assert_eq!(result.health, plain.health);
+        assert_eq!(result.log.to_traces(), plain.log.to_traces());
+    }
+
+    #[test]
+    fn test_analyzed_timelines() {
+        let input = LogInput::Path("./sample-data/exercise2.xes".to_string());
+        let all = analyze(input.clone(), AnalysisConfig::default()).unwrap();
+        let excluded = all.log.activities().into_iter().next().unwrap();
+        let mut config = AnalysisConfig::default();
+        config.filters.excluded_activities = vec![excluded.clone()];
+        let result = analyze(input, config).unwrap();
+
+        assert_eq!(result.timelines.len(), result.log.len());
+        let activities: HashSet<&str> = result
+            .timelines
+            .iter()
+            .flat_map(|timeline| &timeline.instances)
+            .map(|instance| instance.activity.as_str())
+            .collect();
+        assert!(!activities.contains(excluded.as_str()));
+        assert_eq!(activities.len(), result.log.activities().len());
+    }
+
+    #[test]
+    fn test_merged_labels() {
+        let event = |activity: &str, minute: u32| {
+            format!(
+                concat!(
+                    r#"<event><string key="concept:name" value="{}"/>"#,
+                    r#"<date key="time:timestamp" value="2024-01-01T10:{:02}:00+00:00"/></event>"#
+                ),
+                activity, minute
+            )
+        };
+        let trace = |activities: &[&str]| {
+            let events: Vec<String> = activities
+                .iter()
+                .enumerate()
+                .map(|(minute, activity)| event(activity, minute as u32))
+                .collect();
+            format!("<trace>{}</trace>", events.concat())
+        };
+        let content = format!(
+            "<log>{}{}{}</log>",
+            trace(&["Register", "Approve", "Pay"]),
+            trace(&["Register", "Approve", "Pay"]),
+            trace(&["register", "Approve ", "Pay"])
+        );
+        let mut config = AnalysisConfig::default();
+        config.filters.merged_labels = [("register".to_string(), "Register".to_string())].into();
+        let result = analyze(LogInput::Content(content), config).unwrap();
+
+        // both pairs are reported, but only the confirmed one is merged
+        assert_eq!(result.similar_labels.len(), 2);
+        let activities = result.log.activities();
+        assert!(!activities.contains("register"));
+        assert!(activities.contains("Approve "));
+        let report = result.report();
+        assert!(report.contains("'register' -> 'Register'"));
+        assert!(report.contains("'Approve' ~ 'Approve '"));
+        assert!(!report.contains("'Register' ~ 'register'"));
+    }
+
+    #[test]
+    fn test_conformance() {
+        let result = analyze(
+            LogInput::Path("./sample-data/exercise2.xes".to_string()),
+            AnalysisConfig::default(),
+        )
+        .unwrap();
+        let net =
+            crate::parser::parse_petri_net(Some("./sample-data/sequence.pnml"), None).unwrap();
+        let conformance = result.conformance(&net);
+
+        assert_eq!(conformance.replay.cases(), result.log.len());
+        let trend_cases: usize = conformance.trend.iter().map(|window| window.report.cases()).sum();
+        assert_eq!(trend_cases, result.log.len());
+        assert_eq!(conformance.alignments.variants.len(), result.log.variants().count());
+        assert_eq!(
+            conformance.replay.fitting_cases() == result.log.len(),
+            conformance.deviations.is_empty()
+        );
+        assert!(conformance
+            .report(Language::English)
+            .starts_with(Message::TokenReplay.text(Language::English)));
+    }
+
+    #[test]
+    fn test_sub_process() {
+        let result = analyze(
+            LogInput::Path("./sample-data/exercise2.xes".to_string()),
+            AnalysisConfig::default(),
+        )
+        .unwrap();
+        let sub_process = result.sub_process(&Milestones::new("C", "E"));
+
+        assert!(!sub_process.log.is_empty());
+        assert!(sub_process.log.len() < result.log.len());
+        assert!(sub_process
+            .log
+            .traces()
+            .all(|trace| trace.first().is_some_and(|a| a == "C")
+                && trace.last().is_some_and(|a| a == "E")));
+        assert_eq!(sub_process.matrix.activities, sub_process.log.activities().len());
+        assert_eq!(sub_process.log_metrics.len(), result.log_metrics.len());
+        assert!(sub_process.health.is_none());
+    }
+
+    #[test]
+    fn test_ongoing_cases() {
+        let mut config = AnalysisConfig::default();
+        config.completeness.max_idle_days = Some(0);
+        config.completeness.mark_ongoing = true;
+        let result = analyze(
+            LogInput::Path("./sample-data/Example_SemiStructured.xes".to_string()),
+            config,
+        )
+        .unwrap();
+
+        let ongoing = result.log.ongoing_cases();
+        assert!(ongoing > 0);
+        assert_eq!(ongoing, result.incomplete_cases.len());
+        // still in the dependencies, but without a duration
+        assert_eq!(result.cases.len(), result.log.len() - ongoing);
+    }
+}
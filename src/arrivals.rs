@@ -0,0 +1,178 @@
+//! Case arrivals: how much time passes between the starts of consecutive cases and how many
+//! cases arrive per day, overall and per period, e.g. to see a rising workload or to drive the
+//! arrivals of a [simulation](crate::simulation).
+
+use std::collections::BTreeMap;
+
+use chrono::{DateTime, Duration, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::cohorts::DurationPercentiles;
+use crate::i18n::{Language, Message};
+use crate::render::format_duration;
+use crate::sla::SlaPeriod;
+use crate::timeline::CaseTimeline;
+
+/// How the arrivals are grouped into periods.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ArrivalSettings {
+    pub period: SlaPeriod,
+}
+
+/// The starts of the cases of `timelines`, i.e. their first timestamps, in order.
+pub fn arrival_times(timelines: &[CaseTimeline]) -> Vec<DateTime<Utc>> {
+    let mut arrivals: Vec<DateTime<Utc>> = timelines
+        .iter()
+        .filter_map(|timeline| timeline.span().map(|(first, _)| first))
+        .collect();
+    arrivals.sort();
+    arrivals
+}
+
+/// The times between the starts of consecutive cases.
+pub fn inter_arrival_times(timelines: &[CaseTimeline]) -> Vec<Duration> {
+    arrival_times(timelines)
+        .windows(2)
+        .map(|pair| pair[1] - pair[0])
+        .collect()
+}
+
+/// The cases arriving in one period.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ArrivalPeriod {
+    /// The first day of the period.
+    pub start: NaiveDate,
+    pub cases: usize,
+    pub rate_per_day: f64,
+}
+
+/// The distribution of the inter-arrival times and the fitted arrival rate.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ArrivalStats {
+    /// Cases with at least one timestamp.
+    pub cases: usize,
+    pub mean_inter_arrival_time: Duration,
+    pub inter_arrival_times: DurationPercentiles,
+    /// Arrivals per day of a Poisson process with the mean inter-arrival time.
+    pub rate_per_day: f64,
+    /// The periods from the first to the last arrival, including the ones without arrivals.
+    pub periods: Vec<ArrivalPeriod>,
+}
+
+impl ArrivalStats {
+    /// The arrivals of `timelines`, `None` if fewer than two cases have a timestamp.
+    pub fn of(timelines: &[CaseTimeline], settings: &ArrivalSettings) -> Option<Self> {
+        let arrivals = arrival_times(timelines);
+        let inter_arrival: Vec<Duration> =
+            arrivals.windows(2).map(|pair| pair[1] - pair[0]).collect();
+        let count = inter_arrival.len() as i32;
+        let total: Duration = inter_arrival.iter().copied().sum();
+        let inter_arrival_times = DurationPercentiles::of(inter_arrival)?;
+        let mean = total / count;
+        let day_millis = Duration::days(1).num_milliseconds() as f64;
+        let rate_per_day = if mean > Duration::zero() {
+            day_millis / mean.num_milliseconds() as f64
+        } else {
+            0.0
+        };
+
+        let mut per_period: BTreeMap<NaiveDate, usize> = BTreeMap::new();
+        for arrival in &arrivals {
+            *per_period
+                .entry(settings.period.start(arrival.date_naive()))
+                .or_default() += 1;
+        }
+        let mut periods = Vec::new();
+        let last = settings
+            .period
+            .start(arrivals[arrivals.len() - 1].date_naive());
+        let mut start = settings.period.start(arrivals[0].date_naive());
+        while start <= last {
+            let end = settings.period.next(start);
+            let cases = per_period.get(&start).copied().unwrap_or(0);
+            periods.push(ArrivalPeriod {
+                start,
+                cases,
+                rate_per_day: cases as f64 / (end - start).num_days() as f64,
+            });
+            start = end;
+        }
+
+        Some(ArrivalStats {
+            cases: arrivals.len(),
+            mean_inter_arrival_time: mean,
+            inter_arrival_times,
+            rate_per_day,
+            periods,
+        })
+    }
+
+    /// The inter-arrival times and the arrival rate, then one line per period.
+    pub fn report(&self, language: Language) -> String {
+        let p = &self.inter_arrival_times;
+        let mut output = format!(
+            "{} ({} {}):\n{:<48}{}\n{:<48}{}\n{:<48}{:.2}\n\n{:<16}{:<10}{}\n",
+            Message::CaseArrivals.text(language),
+            self.cases,
+            Message::Cases.text(language),
+            format!("{}:", Message::MeanInterArrivalTime.text(language)),
+            format_duration(self.mean_inter_arrival_time),
+            format!("{}:", Message::InterArrivalTimePercentiles.text(language)),
+            [p.p25, p.median, p.p75, p.p90]
+                .map(format_duration)
+                .join(" / "),
+            format!("{}:", Message::ArrivalsPerDay.text(language)),
+            self.rate_per_day,
+            Message::Period.text(language),
+            Message::Cases.text(language),
+            Message::ArrivalsPerDay.text(language)
+        );
+        for period in &self.periods {
+            output.push_str(&format!(
+                "{:<16}{:<10}{:.2}\n",
+                period.start.to_string(),
+                period.cases,
+                period.rate_per_day
+            ));
+        }
+        output
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{Datelike, TimeZone};
+
+    use super::*;
+
+    #[test]
+    fn test_arrivals() {
+        let timelines: Vec<CaseTimeline> = [(1, 0), (1, 12), (2, 0), (9, 0)]
+            .into_iter()
+            .map(|(day, hour)| {
+                let complete = Utc.with_ymd_and_hms(2024, 1, day, hour, 0, 0).unwrap();
+                CaseTimeline::of(&format!("{}-{}", day, hour), &[("A", None, Some(complete), None)])
+            })
+            .collect();
+        assert_eq!(inter_arrival_times(&timelines)[0], Duration::hours(12));
+
+        let settings = ArrivalSettings {
+            period: SlaPeriod::Week,
+        };
+        let stats = ArrivalStats::of(&timelines, &settings).unwrap();
+        assert_eq!(stats.mean_inter_arrival_time, Duration::hours(64));
+        assert_eq!(stats.inter_arrival_times.median, Duration::hours(12));
+        assert_eq!(stats.rate_per_day, 24.0 / 64.0);
+        // 1 January 2024 is a Monday
+        let periods: Vec<(u32, usize)> = stats
+            .periods
+            .iter()
+            .map(|period| (period.start.day(), period.cases))
+            .collect();
+        assert_eq!(periods, [(1, 3), (8, 1)]);
+        assert_eq!(stats.periods[0].rate_per_day, 3.0 / 7.0);
+        assert!(ArrivalStats::of(&timelines[..1], &settings).is_none());
+        assert!(stats.report(Language::English).contains("2024-01-08"));
+    }
+}
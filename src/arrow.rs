@@ -0,0 +1,262 @@
+//! Converting analysis results to Arrow record batches, e.g. to hand them to Polars or pandas
+//! over Arrow IPC without copying or parsing text.
+//!
+//! Only available with the `arrow` feature.
+
+use std::io::Write;
+use std::sync::Arc;
+
+use arrow_array::builder::{ListBuilder, StringBuilder};
+use arrow_array::{
+    ArrayRef, DurationMillisecondArray, Float64Array, RecordBatch, StringArray,
+    TimestampMillisecondArray, UInt64Array,
+};
+use arrow_ipc::writer::StreamWriter;
+use arrow_schema::{ArrowError, DataType, Field, Schema, TimeUnit};
+
+use crate::dependency_types::dependency::Dependency;
+use crate::timeline::CaseKpis;
+use crate::variant_log::VariantLog;
+
+/// One row per case with the columns `case_id`, `activity_instances`, `first`, `last`
+/// (UTC timestamps), `duration`, `waiting_time` (durations) and `resources`, all in
+/// milliseconds.
+pub fn case_kpis_batch(kpis: &[CaseKpis]) -> Result<RecordBatch, ArrowError> {
+    let timestamp = DataType::Timestamp(TimeUnit::Millisecond, Some("UTC".into()));
+    let duration = DataType::Duration(TimeUnit::Millisecond);
+    let schema = Schema::new(vec![
+        Field::new("case_id", DataType::Utf8, false),
+        Field::new("activity_instances", DataType::UInt64, false),
+        Field::new("first", timestamp.clone(), true),
+        Field::new("last", timestamp, true),
+        Field::new("duration", duration.clone(), true),
+        Field::new("waiting_time", duration, true),
+        Field::new("resources", DataType::UInt64, false),
+    ]);
+
+    let columns: Vec<ArrayRef> = vec![
+        Arc::new(StringArray::from_iter_values(
+            kpis.iter().map(|case| case.case_id.as_str()),
+        )),
+        Arc::new(UInt64Array::from_iter_values(
+            kpis.iter().map(|case| case.activity_instances as u64),
+        )),
+        Arc::new(
+            TimestampMillisecondArray::from_iter(
+                kpis.iter()
+                    .map(|case| case.first.map(|first| first.timestamp_millis())),
+            )
+            .with_timezone("UTC"),
+        ),
+        Arc::new(
+            TimestampMillisecondArray::from_iter(
+                kpis.iter()
+                    .map(|case| case.last.map(|last| last.timestamp_millis())),
+            )
+            .with_timezone("UTC"),
+        ),
+        Arc::new(DurationMillisecondArray::from_iter(kpis.iter().map(
+            |case| case.duration.map(|duration| duration.num_milliseconds()),
+        ))),
+        Arc::new(DurationMillisecondArray::from_iter(kpis.iter().map(
+            |case| case.waiting_time.map(|waiting| waiting.num_milliseconds()),
+        ))),
+        Arc::new(UInt64Array::from_iter_values(
+            kpis.iter().map(|case| case.resources as u64),
+        )),
+    ];
+    RecordBatch::try_new(Arc::new(schema), columns)
+}
+
+/// One row per matrix cell with the columns `from_activity`, `to_activity`, `temporal`,
+/// `existential`, `existential_p_value` and `custom`, the same cells the `sqlite` feature
+/// writes. The custom dependencies of a cell are joined with `,`.
+pub fn matrix_batch(dependencies: &[Dependency]) -> Result<RecordBatch, ArrowError> {
+    let schema = Schema::new(vec![
+        Field::new("from_activity", DataType::Utf8, false),
+        Field::new("to_activity", DataType::Utf8, false),
+        Field::new("temporal", DataType::Utf8, true),
+        Field::new("existential", DataType::Utf8, true),
+        Field::new("existential_p_value", DataType::Float64, true),
+        Field::new("custom", DataType::Utf8, true),
+    ]);
+
+    let columns: Vec<ArrayRef> = vec![
+        Arc::new(StringArray::from_iter_values(
+            dependencies
+                .iter()
+                .map(|dependency| dependency.from.as_str()),
+        )),
+        Arc::new(StringArray::from_iter_values(
+            dependencies.iter().map(|dependency| dependency.to.as_str()),
+        )),
+        Arc::new(StringArray::from_iter(dependencies.iter().map(
+            |dependency| {
+                dependency
+                    .temporal_dependency
+                    .as_ref()
+                    .map(|d| d.to_string())
+            },
+        ))),
+        Arc::new(StringArray::from_iter(dependencies.iter().map(
+            |dependency| {
+                dependency
+                    .existential_dependency
+                    .as_ref()
+                    .map(|d| d.to_string())
+            },
+        ))),
+        Arc::new(Float64Array::from_iter(
+            dependencies
+                .iter()
+                .map(|dependency| dependency.existential_p_value),
+        )),
+        Arc::new(StringArray::from_iter(dependencies.iter().map(
+            |dependency| {
+                (!dependency.custom_dependencies.is_empty()).then(|| {
+                    dependency
+                        .custom_dependencies
+                        .iter()
+                        .map(|d| d.to_string())
+                        .collect::<Vec<_>>()
+                        .join(",")
+                })
+            },
+        ))),
+    ];
+    RecordBatch::try_new(Arc::new(schema), columns)
+}
+
+/// One row per variant of `log` with the columns `variant`, `activities` (a list of
+/// activity names) and `frequency`.
+pub fn variants_batch(log: &VariantLog) -> Result<RecordBatch, ArrowError> {
+    let activity = Field::new("item", DataType::Utf8, false);
+    let schema = Schema::new(vec![
+        Field::new("variant", DataType::UInt64, false),
+        Field::new(
+            "activities",
+            DataType::List(Arc::new(activity.clone())),
+            false,
+        ),
+        Field::new("frequency", DataType::UInt64, false),
+    ]);
+
+    let mut activities = ListBuilder::new(StringBuilder::new()).with_field(activity);
+    let mut frequencies = Vec::new();
+    for (variant, frequency) in log.variants() {
+        activities.append_value(variant.iter().map(Some));
+        frequencies.push(frequency as u64);
+    }
+
+    let columns: Vec<ArrayRef> = vec![
+        Arc::new(UInt64Array::from_iter_values(0..frequencies.len() as u64)),
+        Arc::new(activities.finish()),
+        Arc::new(UInt64Array::from(frequencies)),
+    ];
+    RecordBatch::try_new(Arc::new(schema), columns)
+}
+
+/// Writes `batch` in the Arrow IPC streaming format, as read by `pyarrow.ipc.open_stream`
+/// or `polars.read_ipc_stream`.
+pub fn write_ipc(writer: impl Write, batch: &RecordBatch) -> Result<(), ArrowError> {
+    let mut writer = StreamWriter::try_new(writer, &batch.schema())?;
+    writer.write(batch)?;
+    writer.finish()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use arrow_array::{Array, ListArray};
+    use arrow_ipc::reader::StreamReader;
+    use chrono::{Duration, TimeZone, Utc};
+
+    use crate::{dependencies_from_variant_log_with_options, MatrixOptions};
+
+    fn log() -> VariantLog {
+        VariantLog::from_traces(vec![
+            vec!["A".to_string(), "B".to_string()],
+            vec!["A".to_string(), "B".to_string()],
+            vec!["A".to_string(), "C".to_string()],
+        ])
+    }
+
+    #[test]
+    fn test_case_kpis_batch() {
+        let first = Utc.with_ymd_and_hms(2024, 1, 1, 10, 0, 0).unwrap();
+        let kpis = [
+            CaseKpis {
+                case_id: "c1".to_string(),
+                activity_instances: 2,
+                first: Some(first),
+                last: Some(first + Duration::hours(1)),
+                duration: Some(Duration::hours(1)),
+                waiting_time: None,
+                resources: 1,
+            },
+            CaseKpis {
+                case_id: "c2".to_string(),
+                activity_instances: 1,
+                first: None,
+                last: None,
+                duration: None,
+                waiting_time: None,
+                resources: 0,
+            },
+        ];
+        let batch = case_kpis_batch(&kpis).unwrap();
+
+        assert_eq!((batch.num_rows(), batch.num_columns()), (2, 7));
+        let durations = batch
+            .column_by_name("duration")
+            .unwrap()
+            .as_any()
+            .downcast_ref::<DurationMillisecondArray>()
+            .unwrap();
+        assert_eq!(durations.value(0), 3_600_000);
+        assert!(durations.is_null(1));
+    }
+
+    #[test]
+    fn test_matrix_and_variants() {
+        let log = log();
+        let dependencies =
+            dependencies_from_variant_log_with_options(&log, &MatrixOptions::default(), &[]);
+        let matrix = matrix_batch(&dependencies).unwrap();
+        assert_eq!(matrix.num_rows(), 6);
+        let from = matrix
+            .column(0)
+            .as_any()
+            .downcast_ref::<StringArray>()
+            .unwrap();
+        assert_eq!(from.value(0), "A");
+
+        let variants = variants_batch(&log).unwrap();
+        assert_eq!(variants.num_rows(), 2);
+        let activities = variants
+            .column(1)
+            .as_any()
+            .downcast_ref::<ListArray>()
+            .unwrap();
+        assert_eq!(activities.value(0).len(), 2);
+        let frequencies = variants
+            .column(2)
+            .as_any()
+            .downcast_ref::<UInt64Array>()
+            .unwrap();
+        assert_eq!(frequencies.values().iter().sum::<u64>(), 3);
+    }
+
+    #[test]
+    fn test_write_ipc() {
+        let batch = variants_batch(&log()).unwrap();
+        let mut buffer = Vec::new();
+        write_ipc(&mut buffer, &batch).unwrap();
+
+        let batches: Vec<RecordBatch> = StreamReader::try_new(buffer.as_slice(), None)
+            .unwrap()
+            .collect::<Result<_, _>>()
+            .unwrap();
+        assert_eq!(batches, [batch]);
+    }
+}
@@ -0,0 +1,315 @@
+//! Case attributes as clean inputs for the attribute-based analyses (decision mining,
+//! cohorts): type inference from the values, imputation of missing values and normalization,
+//! without preprocessing the log elsewhere.
+
+use std::collections::{BTreeMap, HashMap};
+
+use chrono::{DateTime, NaiveDate};
+use process_mining::event_log::EventLog;
+use serde::{Deserialize, Serialize};
+
+use crate::parser::xes_extensions::{XesExtensions, CONCEPT_NAME};
+
+/// What the values of an attribute are.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum AttributeType {
+    Numeric,
+    /// RFC 3339 timestamps or `YYYY-MM-DD` dates.
+    Date,
+    Categorical,
+}
+
+impl AttributeType {
+    /// The most specific type all `values` fit, ignoring blank ones; categorical if there are
+    /// none.
+    pub fn infer<'a>(values: impl IntoIterator<Item = &'a str>) -> Self {
+        let mut values = values
+            .into_iter()
+            .map(str::trim)
+            .filter(|value| !value.is_empty())
+            .peekable();
+        if values.peek().is_none() {
+            return AttributeType::Categorical;
+        }
+        let (mut numeric, mut date) = (true, true);
+        for value in values {
+            numeric &= parse_number(value).is_some();
+            date &= parse_date(value).is_some();
+            if !numeric && !date {
+                return AttributeType::Categorical;
+            }
+        }
+        if numeric {
+            AttributeType::Numeric
+        } else {
+            AttributeType::Date
+        }
+    }
+}
+
+/// `value` as a finite number.
+pub fn parse_number(value: &str) -> Option<f64> {
+    value
+        .trim()
+        .parse::<f64>()
+        .ok()
+        .filter(|number| number.is_finite())
+}
+
+/// `value` as seconds since the Unix epoch, dates counting from midnight UTC.
+pub fn parse_date(value: &str) -> Option<i64> {
+    let value = value.trim();
+    DateTime::parse_from_rfc3339(value)
+        .map(|date| date.timestamp())
+        .ok()
+        .or_else(|| {
+            NaiveDate::parse_from_str(value, "%Y-%m-%d")
+                .ok()
+                .and_then(|date| date.and_hms_opt(0, 0, 0))
+                .map(|date| date.and_utc().timestamp())
+        })
+}
+
+/// How missing values of an attribute are filled in.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Imputation {
+    /// The mean of the numeric values.
+    Mean,
+    /// The median of the numeric values (the lower one of an even count).
+    Median,
+    /// The most frequent value, the smallest one of a tie.
+    Mode,
+    Constant(String),
+}
+
+/// How the present values of an attribute are rewritten.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Normalization {
+    /// Numbers scaled to `[0, 1]`.
+    MinMax,
+    /// Numbers as standard deviations from the mean.
+    ZScore,
+    /// Text trimmed and lowercased, e.g. to merge ` Web` and `web`.
+    Text,
+}
+
+/// The case (trace) attributes of a log, one column per attribute key.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct AttributeTable {
+    pub case_ids: Vec<String>,
+    /// Per attribute, the value of every case in the order of `case_ids`.
+    pub columns: BTreeMap<String, Vec<Option<String>>>,
+}
+
+impl AttributeTable {
+    /// Collects the scalar trace attributes of `log` (see [`XesExtensions::text_attribute`]),
+    /// except for `concept:name`, which names the cases.
+    pub fn of(log: &EventLog) -> Self {
+        let mut table = AttributeTable::default();
+        for (case_idx, trace) in log.traces.iter().enumerate() {
+            table.case_ids.push(
+                trace
+                    .concept_name()
+                    .map(str::to_string)
+                    .unwrap_or_else(|| format!("case_{}", case_idx)),
+            );
+            for attribute in &trace.attributes {
+                if attribute.key == CONCEPT_NAME {
+                    continue;
+                }
+                if let Some(value) = trace.text_attribute(&attribute.key) {
+                    table
+                        .columns
+                        .entry(attribute.key.clone())
+                        .or_insert_with(|| vec![None; log.traces.len()])[case_idx] = Some(value);
+                }
+            }
+        }
+        table
+    }
+
+    /// The inferred type of every attribute.
+    pub fn types(&self) -> BTreeMap<String, AttributeType> {
+        self.columns
+            .iter()
+            .map(|(key, values)| {
+                (
+                    key.clone(),
+                    AttributeType::infer(values.iter().flatten().map(String::as_str)),
+                )
+            })
+            .collect()
+    }
+
+    /// Number of cases without a (non-blank) value of `key`.
+    pub fn missing(&self, key: &str) -> usize {
+        self.columns.get(key).map_or(self.case_ids.len(), |values| {
+            values.iter().filter(|value| is_missing(value)).count()
+        })
+    }
+
+    /// Fills the missing and blank values of `key` and returns how many were filled. Mean and
+    /// median only apply to numeric attributes and fill nothing otherwise.
+    pub fn impute(&mut self, key: &str, imputation: &Imputation) -> usize {
+        let cases = self.case_ids.len();
+        let values = self
+            .columns
+            .entry(key.to_string())
+            .or_insert_with(|| vec![None; cases]);
+        let numbers = || -> Vec<f64> {
+            values
+                .iter()
+                .flatten()
+                .filter_map(|value| parse_number(value))
+                .collect()
+        };
+        let present: Vec<&String> = values
+            .iter()
+            .flatten()
+            .filter(|v| !v.trim().is_empty())
+            .collect();
+        let numeric = !present.is_empty()
+            && AttributeType::infer(present.iter().map(|value| value.as_str()))
+                == AttributeType::Numeric;
+        let fill = match imputation {
+            Imputation::Mean if numeric => {
+                let numbers = numbers();
+                Some((numbers.iter().sum::<f64>() / numbers.len() as f64).to_string())
+            }
+            Imputation::Median if numeric => {
+                let mut numbers = numbers();
+                numbers.sort_by(f64::total_cmp);
+                Some(numbers[(numbers.len() - 1) / 2].to_string())
+            }
+            Imputation::Mode => {
+                let mut counts: HashMap<&str, usize> = HashMap::new();
+                for value in &present {
+                    *counts.entry(value.as_str()).or_default() += 1;
+                }
+                counts
+                    .into_iter()
+                    .max_by(|a, b| a.1.cmp(&b.1).then_with(|| b.0.cmp(a.0)))
+                    .map(|(value, _)| value.to_string())
+            }
+            Imputation::Constant(value) => Some(value.clone()),
+            Imputation::Mean | Imputation::Median => None,
+        };
+        let Some(fill) = fill else {
+            return 0;
+        };
+        let mut filled = 0;
+        for value in values.iter_mut().filter(|value| is_missing(value)) {
+            *value = Some(fill.clone());
+            filled += 1;
+        }
+        filled
+    }
+
+    /// Rewrites the present values of `key`. Numeric normalizations leave attributes that
+    /// aren't numeric, and constant ones, unchanged.
+    pub fn normalize(&mut self, key: &str, normalization: Normalization) {
+        let Some(values) = self.columns.get_mut(key) else {
+            return;
+        };
+        if normalization == Normalization::Text {
+            for value in values.iter_mut().flatten() {
+                *value = value.trim().to_lowercase();
+            }
+            return;
+        }
+        let numbers: Option<Vec<f64>> = values
+            .iter()
+            .flatten()
+            .filter(|value| !value.trim().is_empty())
+            .map(|value| parse_number(value))
+            .collect();
+        let Some(numbers) = numbers.filter(|numbers| !numbers.is_empty()) else {
+            return;
+        };
+        let (offset, scale) = match normalization {
+            Normalization::MinMax => {
+                let min = numbers.iter().copied().fold(f64::INFINITY, f64::min);
+                let max = numbers.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+                (min, max - min)
+            }
+            _ => {
+                let mean = numbers.iter().sum::<f64>() / numbers.len() as f64;
+                let variance =
+                    numbers.iter().map(|n| (n - mean).powi(2)).sum::<f64>() / numbers.len() as f64;
+                (mean, variance.sqrt())
+            }
+        };
+        if scale == 0.0 {
+            return;
+        }
+        for value in values.iter_mut().flatten() {
+            if let Some(number) = parse_number(value) {
+                *value = ((number - offset) / scale).to_string();
+            }
+        }
+    }
+}
+
+fn is_missing(value: &Option<String>) -> bool {
+    value.as_deref().is_none_or(|value| value.trim().is_empty())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::{parse_into_event_log_with_config, ImportConfig};
+
+    #[test]
+    fn test_infer() {
+        assert_eq!(
+            AttributeType::infer(["1", " 2.5", ""]),
+            AttributeType::Numeric
+        );
+        assert_eq!(
+            AttributeType::infer(["2024-01-01", "2024-01-02T10:00:00+01:00"]),
+            AttributeType::Date
+        );
+        assert_eq!(
+            AttributeType::infer(["1", "web"]),
+            AttributeType::Categorical
+        );
+        assert_eq!(AttributeType::infer([]), AttributeType::Categorical);
+        assert_eq!(parse_date("1970-01-02"), Some(86_400));
+    }
+
+    #[test]
+    fn test_impute_and_normalize() {
+        let content = r#"<log>
+            <trace><int key="amount" value="10"/><string key="channel" value=" Web"/></trace>
+            <trace><int key="amount" value="30"/><string key="channel" value="web"/></trace>
+            <trace><string key="channel" value="mail"/></trace>
+            <trace><int key="amount" value="20"/></trace>
+        </log>"#;
+        let log = parse_into_event_log_with_config(None, Some(content), &ImportConfig::default())
+            .unwrap();
+        let mut table = AttributeTable::of(&log);
+
+        assert_eq!(table.case_ids, ["case_0", "case_1", "case_2", "case_3"]);
+        assert_eq!(table.types()["amount"], AttributeType::Numeric);
+        assert_eq!(table.missing("amount"), 1);
+        assert_eq!(table.impute("amount", &Imputation::Median), 1);
+        assert_eq!(table.columns["amount"][2].as_deref(), Some("20"));
+        // not numeric
+        assert_eq!(table.impute("channel", &Imputation::Mean), 0);
+
+        table.normalize("amount", Normalization::MinMax);
+        assert_eq!(
+            table.columns["amount"],
+            ["0", "1", "0.5", "0.5"].map(|value| Some(value.to_string()))
+        );
+        table.normalize("channel", Normalization::Text);
+        table.impute("channel", &Imputation::Mode);
+        assert_eq!(
+            table.columns["channel"],
+            ["web", "web", "mail", "web"].map(|value| Some(value.to_string()))
+        );
+    }
+}
@@ -0,0 +1,217 @@
+//! Access control and an audit trail for the analyses of a [`JobQueue`](crate::jobs::JobQueue):
+//! an [`Authenticator`] maps the API key sent with a submission to a user, and an [`AuditLog`]
+//! records who submitted which log with which configuration, and when.
+//!
+//! Both are traits, so a deployment can plug in its own user directory or storage. The
+//! [`ApiKeys`] and [`AuditFile`] implementations cover a fixed set of keys and a JSON lines file;
+//! with the `sqlite` feature, a `Mutex<rusqlite::Connection>` is an audit log as well.
+
+use std::fmt;
+use std::fs::{File, OpenOptions};
+use std::io::{self, BufRead, BufReader, Write};
+use std::path::PathBuf;
+use std::sync::Mutex;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::analysis::LogInput;
+use crate::config::AnalysisConfig;
+use crate::jobs::JobId;
+
+/// Decides who may submit analyses.
+pub trait Authenticator: Send + Sync {
+    /// The user `api_key` belongs to, `None` if the key isn't valid.
+    fn authenticate(&self, api_key: &str) -> Option<String>;
+}
+
+/// A fixed set of API keys, each belonging to a user. A key is compared with every known key in
+/// constant time, so the time a lookup takes doesn't tell how much of a guessed key is right.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ApiKeys {
+    // the keys with their users
+    users: Vec<(String, String)>,
+}
+
+impl ApiKeys {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Accepts `api_key` for `user`.
+    pub fn with_key(mut self, api_key: impl Into<String>, user: impl Into<String>) -> Self {
+        let api_key = api_key.into();
+        self.users.retain(|(key, _)| *key != api_key);
+        self.users.push((api_key, user.into()));
+        self
+    }
+}
+
+impl Authenticator for ApiKeys {
+    fn authenticate(&self, api_key: &str) -> Option<String> {
+        // every key is compared, also after a match
+        self.users
+            .iter()
+            .fold(None, |found, (key, user)| {
+                if constant_time_eq(key.as_bytes(), api_key.as_bytes()) {
+                    Some(user)
+                } else {
+                    found
+                }
+            })
+            .cloned()
+    }
+}
+
+// Compares all bytes of equally long keys, however early they differ
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0, |diff, (a, b)| diff | (a ^ b)) == 0
+}
+
+/// One submitted analysis.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AuditEntry {
+    pub time: DateTime<Utc>,
+    /// The authenticated user, `None` for submissions without authentication.
+    pub user: Option<String>,
+    pub job: JobId,
+    /// The path of the log, or the size of an uploaded one.
+    pub log: String,
+    pub config: AnalysisConfig,
+}
+
+impl AuditEntry {
+    /// An entry for a submission right now.
+    pub fn new(
+        user: Option<String>,
+        job: JobId,
+        input: &LogInput,
+        config: &AnalysisConfig,
+    ) -> Self {
+        AuditEntry {
+            time: Utc::now(),
+            user,
+            job,
+            log: match input {
+                LogInput::Path(path) => path.clone(),
+                LogInput::Content(content) => format!("upload of {} bytes", content.len()),
+            },
+            config: config.clone(),
+        }
+    }
+}
+
+/// Where audit entries are persisted.
+pub trait AuditLog: Send + Sync {
+    /// Persists `entry`; a submission is rejected if this fails.
+    fn record(&self, entry: &AuditEntry) -> io::Result<()>;
+}
+
+/// An audit log in a file, one JSON object per line. Entries are appended, so the file can be
+/// shared by restarts of the server.
+#[derive(Debug)]
+pub struct AuditFile {
+    path: PathBuf,
+    // serializes the appends of concurrent submissions
+    lock: Mutex<()>,
+}
+
+impl AuditFile {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        AuditFile {
+            path: path.into(),
+            lock: Mutex::new(()),
+        }
+    }
+
+    /// The entries recorded so far, oldest first; none if the file doesn't exist yet.
+    pub fn entries(&self) -> io::Result<Vec<AuditEntry>> {
+        let file = match File::open(&self.path) {
+            Ok(file) => file,
+            Err(error) if error.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(error) => return Err(error),
+        };
+        BufReader::new(file)
+            .lines()
+            .filter(|line| !matches!(line, Ok(line) if line.trim().is_empty()))
+            .map(|line| Ok(serde_json::from_str(&line?)?))
+            .collect()
+    }
+}
+
+impl AuditLog for AuditFile {
+    fn record(&self, entry: &AuditEntry) -> io::Result<()> {
+        let _guard = self
+            .lock
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)?;
+        writeln!(file, "{}", serde_json::to_string(entry)?)
+    }
+}
+
+/// Why a submission was rejected.
+#[derive(Debug)]
+pub enum AccessError {
+    /// The API key is unknown.
+    Denied,
+    /// The path of the log is outside of the upload directory, or there is none.
+    Forbidden(String),
+    /// The submission couldn't be recorded in the audit log.
+    Audit(io::Error),
+}
+
+impl fmt::Display for AccessError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            AccessError::Denied => write!(f, "invalid API key"),
+            AccessError::Forbidden(path) => write!(f, "{} is not an uploaded log", path),
+            AccessError::Audit(error) => write!(f, "could not write the audit log: {}", error),
+        }
+    }
+}
+
+impl std::error::Error for AccessError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            AccessError::Denied | AccessError::Forbidden(_) => None,
+            AccessError::Audit(error) => Some(error),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_audit_file() {
+        let keys = ApiKeys::new().with_key("secret", "alice");
+        assert_eq!(keys.authenticate("secret").as_deref(), Some("alice"));
+        assert_eq!(keys.authenticate("guess"), None);
+
+        let path = std::env::temp_dir().join(format!("egypt-audit-{}.jsonl", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        let audit = AuditFile::new(&path);
+        assert!(audit.entries().unwrap().is_empty());
+
+        let config = AnalysisConfig::default();
+        let upload = AuditEntry::new(None, 1, &LogInput::Content("<log/>".into()), &config);
+        let file = AuditEntry::new(
+            Some("alice".into()),
+            2,
+            &LogInput::Path("a.xes".into()),
+            &config,
+        );
+        audit.record(&upload).unwrap();
+        audit.record(&file).unwrap();
+        let entries = audit.entries().unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(entries, [upload, file]);
+        assert_eq!(entries[0].log, "upload of 6 bytes");
+    }
+}
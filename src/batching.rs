@@ -0,0 +1,240 @@
+use std::collections::BTreeMap;
+
+use chrono::{DateTime, Duration, Utc};
+
+use crate::i18n::{Language, Message};
+use crate::timeline::CaseTimeline;
+
+/// When executions of the same activity in different cases count as one batch.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BatchSettings {
+    /// Maximum time between the beginnings of consecutive executions of a batch.
+    pub max_gap: Duration,
+    /// Minimum number of distinct cases in a batch.
+    pub min_size: usize,
+    /// Only executions by the same resource form a batch.
+    pub by_resource: bool,
+}
+
+impl BatchSettings {
+    /// Many cases executing the activity at nearly the same time, e.g. a nightly job.
+    pub fn simultaneous() -> Self {
+        BatchSettings {
+            max_gap: Duration::minutes(1),
+            min_size: 3,
+            by_resource: false,
+        }
+    }
+
+    /// A resource executing the activity for many cases in quick succession.
+    pub fn resource_bursts() -> Self {
+        BatchSettings {
+            max_gap: Duration::minutes(10),
+            min_size: 3,
+            by_resource: true,
+        }
+    }
+}
+
+/// Executions of an activity in several cases that were processed together.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Batch {
+    pub activity: String,
+    /// The common resource, if batches are grouped by resource.
+    pub resource: Option<String>,
+    pub cases: Vec<String>,
+    pub first: DateTime<Utc>,
+    pub last: DateTime<Utc>,
+}
+
+/// How strongly an activity is executed in batches.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BatchingSignal {
+    pub activity: String,
+    /// Executions with a recorded timestamp.
+    pub executions: usize,
+    /// Executions that are part of a batch.
+    pub batched: usize,
+    pub batches: usize,
+}
+
+impl BatchingSignal {
+    pub fn batched_share(&self) -> f64 {
+        self.batched as f64 / self.executions as f64
+    }
+
+    pub fn mean_batch_size(&self) -> f64 {
+        self.batched as f64 / self.batches as f64
+    }
+}
+
+// Beginning and case of the executions of an activity, keyed by activity and resource
+type Executions<'a> = BTreeMap<(&'a str, Option<&'a str>), Vec<(DateTime<Utc>, &'a str)>>;
+
+// Executions with a timestamp, grouped by activity (and resource) and sorted by time
+fn executions<'a>(timelines: &'a [CaseTimeline], settings: &BatchSettings) -> Executions<'a> {
+    let mut groups: BTreeMap<_, Vec<_>> = BTreeMap::new();
+    for timeline in timelines {
+        for instance in &timeline.instances {
+            let Some(begin) = instance.begin() else {
+                continue;
+            };
+            let resource = if settings.by_resource {
+                let Some(resource) = instance.resource.as_deref() else {
+                    continue;
+                };
+                Some(resource)
+            } else {
+                None
+            };
+            groups
+                .entry((instance.activity.as_str(), resource))
+                .or_default()
+                .push((begin, timeline.case_id.as_str()));
+        }
+    }
+    for executions in groups.values_mut() {
+        executions.sort();
+    }
+    groups
+}
+
+/// Finds all batches, ordered by activity, resource and time.
+pub fn detect_batches(timelines: &[CaseTimeline], settings: &BatchSettings) -> Vec<Batch> {
+    let mut batches = Vec::new();
+    for ((activity, resource), executions) in executions(timelines, settings) {
+        // split the executions wherever the gap to the previous one is too large
+        let mut start = 0;
+        for end in 1..=executions.len() {
+            if end < executions.len()
+                && executions[end].0 - executions[end - 1].0 <= settings.max_gap
+            {
+                continue;
+            }
+            let mut cases: Vec<String> = executions[start..end]
+                .iter()
+                .map(|(_, case)| case.to_string())
+                .collect();
+            cases.sort();
+            cases.dedup();
+            if cases.len() >= settings.min_size {
+                batches.push(Batch {
+                    activity: activity.to_string(),
+                    resource: resource.map(|resource| resource.to_string()),
+                    cases,
+                    first: executions[start].0,
+                    last: executions[end - 1].0,
+                });
+            }
+            start = end;
+        }
+    }
+    batches
+}
+
+/// Batching statistics of every activity with at least one batch, the most strongly batched
+/// activities first.
+pub fn batching_signals(
+    timelines: &[CaseTimeline],
+    settings: &BatchSettings,
+) -> Vec<BatchingSignal> {
+    let mut signals: BTreeMap<&str, BatchingSignal> = BTreeMap::new();
+    for ((activity, _), executions) in executions(timelines, settings) {
+        signals
+            .entry(activity)
+            .or_insert_with(|| BatchingSignal {
+                activity: activity.to_string(),
+                executions: 0,
+                batched: 0,
+                batches: 0,
+            })
+            .executions += executions.len();
+    }
+    for batch in detect_batches(timelines, settings) {
+        let signal = signals
+            .get_mut(batch.activity.as_str())
+            .expect("batches have executions");
+        signal.batched += batch.cases.len();
+        signal.batches += 1;
+    }
+
+    let mut signals: Vec<_> = signals
+        .into_values()
+        .filter(|signal| signal.batches > 0)
+        .collect();
+    signals.sort_by(|a, b| b.batched_share().total_cmp(&a.batched_share()));
+    signals
+}
+
+/// One line per batched activity with its share of batched executions, number of batches and
+/// mean batch size.
+pub fn report(signals: &[BatchingSignal], language: Language) -> String {
+    let mut output = format!(
+        "{:<32}{:<16}{:<12}{}\n",
+        Message::Activity.text(language),
+        Message::BatchedShare.text(language),
+        Message::Batches.text(language),
+        Message::MeanBatchSize.text(language)
+    );
+    for signal in signals {
+        output.push_str(&format!(
+            "{:<32}{:<16.4}{:<12}{:.1}\n",
+            signal.activity,
+            signal.batched_share(),
+            signal.batches,
+            signal.mean_batch_size()
+        ));
+    }
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::TimeZone;
+
+    use super::*;
+    use crate::timeline::TestInstance;
+
+    fn completed<'a>(activity: &'a str, minute: i64, resource: &'a str) -> TestInstance<'a> {
+        let date = Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap() + Duration::minutes(minute);
+        (activity, None, Some(date), Some(resource))
+    }
+
+    fn timelines() -> Vec<CaseTimeline> {
+        vec![
+            // "Pay" runs for three cases at 1am, "Check" is done by Ann one after another
+            CaseTimeline::of("1", &[completed("Check", 0, "Ann"), completed("Pay", 60, "job")]),
+            CaseTimeline::of("2", &[completed("Check", 8, "Ann"), completed("Pay", 60, "job")]),
+            CaseTimeline::of("3", &[completed("Check", 15, "Ann"), completed("Pay", 60, "job")]),
+            CaseTimeline::of("4", &[completed("Check", 30, "Bob"), completed("Pay", 600, "job")]),
+        ]
+    }
+
+    #[test]
+    fn test_simultaneous_batches() {
+        let batches = detect_batches(&timelines(), &BatchSettings::simultaneous());
+        assert_eq!(batches.len(), 1);
+        assert_eq!(batches[0].activity, "Pay");
+        assert_eq!(batches[0].cases, ["1", "2", "3"]);
+
+        let signals = batching_signals(&timelines(), &BatchSettings::simultaneous());
+        assert_eq!(signals.len(), 1);
+        assert_eq!(signals[0].batched_share(), 0.75);
+        assert_eq!(signals[0].mean_batch_size(), 3.0);
+    }
+
+    #[test]
+    fn test_resource_bursts() {
+        let batches = detect_batches(&timelines(), &BatchSettings::resource_bursts());
+        // Ann's checks and the payment job
+        assert_eq!(batches.len(), 2);
+        assert_eq!(batches[0].activity, "Check");
+        assert_eq!(batches[0].resource.as_deref(), Some("Ann"));
+        assert_eq!(batches[0].last - batches[0].first, Duration::minutes(15));
+        assert!(report(
+            &batching_signals(&timelines(), &BatchSettings::resource_bursts()),
+            Language::English
+        )
+        .contains("Check"));
+    }
+}
@@ -0,0 +1,96 @@
+use std::process::ExitCode;
+
+use egypt::{
+    analyze, config::AnalysisConfig, parser::parse_petri_net, subprocess::Milestones, LogInput,
+};
+
+const USAGE: &str = "Usage: egypt-cli [--config <analysis.toml|analysis.json>] [--print-config] [--between <start> <end>] [--model <model.pnml>] <log.xes|log.xes.gz>
+
+Prints the dependency matrix and summary of an event log.
+
+Options:
+  --config <file>   Load the analysis settings from a TOML or JSON profile
+  --print-config    Print the effective settings as TOML and exit
+  --between <start> <end>
+                    Analyze only the sub-process between the two milestone activities
+  --model <file>    Check the analyzed log against a PNML reference model";
+
+struct Args {
+    config: AnalysisConfig,
+    print_config: bool,
+    milestones: Option<Milestones>,
+    model_path: Option<String>,
+    log_path: Option<String>,
+}
+
+fn parse_args(mut args: impl Iterator<Item = String>) -> Result<Args, String> {
+    let mut parsed = Args {
+        config: AnalysisConfig::default(),
+        print_config: false,
+        milestones: None,
+        model_path: None,
+        log_path: None,
+    };
+
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--config" => {
+                let path = args.next().ok_or("--config requires a file")?;
+                parsed.config = AnalysisConfig::load(&path).map_err(|e| e.to_string())?;
+            }
+            "--print-config" => parsed.print_config = true,
+            "--between" => {
+                let (Some(start), Some(end)) = (args.next(), args.next()) else {
+                    return Err("--between requires a start and an end activity".to_string());
+                };
+                parsed.milestones = Some(Milestones::new(&start, &end));
+            }
+            "--model" => parsed.model_path = Some(args.next().ok_or("--model requires a file")?),
+            "-h" | "--help" => return Err(USAGE.to_string()),
+            _ if arg.starts_with("--") => return Err(format!("Unknown option {}\n\n{}", arg, USAGE)),
+            _ if parsed.log_path.is_none() => parsed.log_path = Some(arg),
+            _ => return Err(format!("Unexpected argument {}\n\n{}", arg, USAGE)),
+        }
+    }
+
+    Ok(parsed)
+}
+
+fn run(args: Args) -> Result<String, String> {
+    let config = args.config;
+    if args.print_config {
+        return config.to_toml().map_err(|e| e.to_string());
+    }
+    let log_path = args.log_path.ok_or(USAGE)?;
+    let model = args
+        .model_path
+        .map(|path| parse_petri_net(Some(&path), None))
+        .transpose()
+        .map_err(|e| e.to_string())?;
+
+    let result = analyze(LogInput::Path(log_path), config).map_err(|e| e.to_string())?;
+    for message in &result.diagnostics {
+        eprintln!("{}", message);
+    }
+    let result = match args.milestones {
+        Some(milestones) => result.sub_process(&milestones),
+        None => result,
+    };
+    let conformance = model
+        .map(|net| format!("\n\n{}", result.conformance(&net).report(result.config.language)))
+        .unwrap_or_default();
+    Ok(format!("{}{}", result.report(), conformance))
+}
+
+fn main() -> ExitCode {
+    match parse_args(std::env::args().skip(1)).and_then(run) {
+        Ok(output) => {
+            println!("{}", output);
+            ExitCode::SUCCESS
+        }
+        Err(e) => {
+            eprintln!("{}", e);
+            ExitCode::FAILURE
+        }
+    }
+}
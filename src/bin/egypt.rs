@@ -1,24 +1,130 @@
 use egypt::{
-    generate_adj_matrix_from_traces, generate_xes,
-    parser::{parse_into_traces, variants_of_traces}, ExtendedPrefixAutomaton,
+    activity_profile::{activity_profiles, report as activity_profile_report, to_csv as activity_profile_csv},
+    analyze,
+    batching::{batching_signals, report as batching_report, BatchSettings},
+    calendar::BusinessCalendar,
+    cohorts::{cohorts, report as cohort_report},
+    completeness::{case_ids, incomplete_cases},
+    config::AnalysisConfig,
+    generate_xes,
+    export::{disco_csv_from_timelines, xes as export_xes, ExportedAttributes},
+    glossary::{glossary, metric_for_label, Metric as GlossaryMetric},
+    metrics::Bootstrap,
+    i18n::{Language, Message},
+    decisions::{mine_decisions, report as decision_report, DecisionSettings},
+    declare::{check_constraints, parse_declare},
+    dfg::DirectlyFollowsGraph,
+    gateways::{gateways, report as gateway_report},
+    ltl::{check_formula, Formula},
+    render::{
+        graph::{dfg_svg, grouped_dfg_svg},
+        theme::{Background, Palette},
+        timeline::timeline_svg,
+        variants::variants_svg,
+        Theme,
+    },
+    recommendation::ThresholdSweep,
+    repetition::{report as repetition_report, self_distances},
+    representatives::{case_summaries, report as representatives_report, variant_representatives},
+    timeline::{case_timelines, CaseTimeline},
+    trace_diff::TraceDiff,
+    parser::{
+        decode_xes, parse_into_event_log_with_config, parse_into_log_with_config, parse_petri_net,
+        parse_into_variant_log_with_config,
+        LifecyclePolicy, ParseError,
+    },
+    wip::WipSeries,
+    AnalysisResult, LogInput,
 };
 use wasm_bindgen::{closure::Closure, JsCast, JsValue, UnwrapThrowExt};
-use web_sys::{File, FileReader, HtmlAnchorElement, HtmlInputElement, HtmlTextAreaElement};
+use web_sys::{
+    File, FileReader, HtmlAnchorElement, HtmlInputElement, HtmlSelectElement, HtmlTextAreaElement,
+};
 use yew::prelude::*;
 
+// number of variants drawn by the variants view
+const TOP_VARIANTS: usize = 10;
+// sampling interval of the exported work in progress
+const WIP_INTERVAL: chrono::Duration = chrono::Duration::hours(1);
+
 enum Msg {
     TextInput(String),
+    SeedInput(String),
+    // a similar label, the label it would be merged into and whether the merge is confirmed
+    ConfirmLabelMerge(String, String, bool),
+    DropDuplicateEvents(bool),
+    ExcludeIncompleteCases(bool),
+    MarkOngoingCases(bool),
+    CaseAttributesInput(String),
+    TimestampKeyInput(String),
+    ResourceKeyInput(String),
+    LifecycleInput(String),
+    CaseGapInput(String),
+    SessionGapInput(String),
+    MinSupportInput(String),
     XESImport(Option<File>),
-    XESLoaded(Result<String, String>),
+    XESLoaded(String, Result<String, String>),
+    ModelImport(Option<File>),
+    ModelLoaded(String, Result<String, String>),
     ConvertToXES,
     DownloadXES,
+    DownloadCSV,
+    DownloadFullXES,
+    DownloadWipCSV,
+    DownloadActivityProfileCSV,
+    TimelineCaseInput(String),
+    ShowTimeline,
+    ReferenceCaseInput(String),
+    CompareWithReference,
+    ShowVariants,
+    ShowProcessMap,
+    ShowRepetitions,
+    ShowGateways,
+    MineDecisions,
+    ShowBatching,
+    ShowActivityProfile,
+    CohortAttributeInput(String),
+    CompareCohorts,
+    PaletteInput(String),
+    DarkBackground(bool),
+    FontSizeInput(String),
+    LanguageInput(String),
+    IncludeGlossary(bool),
+    ConfidenceIntervals(bool),
+    RecommendThreshold(bool),
+    BusinessHours(bool),
+    ConfigImport(Option<File>),
+    ConfigLoaded(String, Result<String, String>),
+    SaveConfig,
     // ConvertToAdjMatrix,
 }
 
 struct App {
     text: String,
     processed: bool,
+    // import, filter and rendering settings, can be saved and loaded as a profile
+    config: AnalysisConfig,
+    // file name and content of the last successfully analyzed log
+    imported: Option<(String, String)>,
+    // the analysis of the imported log, after the configured filters and label merging
+    analysis: Option<AnalysisResult>,
+    // case shown by the timeline view, the first case if empty
+    timeline_case: String,
+    // case the timeline case is compared with
+    reference_case: String,
+    // case attribute the cohort comparison groups the cases by
+    cohort_attribute: String,
+    // SVG markup shown below the text area
+    visualization: Option<String>,
+    // file name and content of the imported normative model (PNML or DECLARE)
+    model: Option<(String, String)>,
+    // append explanations of the reported metrics to the report
+    include_glossary: bool,
+    // metrics in the current report, explained by tooltips below the text area
+    report_metrics: Vec<GlossaryMetric>,
     file_reader_closure: Option<Closure<dyn FnMut(web_sys::ProgressEvent)>>, // store the closure
+    model_reader_closure: Option<Closure<dyn FnMut(web_sys::ProgressEvent)>>,
+    config_reader_closure: Option<Closure<dyn FnMut(web_sys::ProgressEvent)>>,
 }
 
 impl Component for App {
@@ -29,7 +135,28 @@ impl Component for App {
         Self {
             text: String::new(),
             processed: false,
+            config: AnalysisConfig {
+                // the text area is dark and somewhat larger by default
+                theme: Theme {
+                    background: Background::Dark,
+                    font_size: 16.0,
+                    ..Theme::default()
+                },
+                language: browser_language(),
+                ..AnalysisConfig::default()
+            },
+            imported: None,
+            analysis: None,
+            timeline_case: String::new(),
+            reference_case: String::new(),
+            cohort_attribute: String::new(),
+            visualization: None,
+            model: None,
+            include_glossary: false,
+            report_metrics: Vec::new(),
             file_reader_closure: None, // initialize the closure storage
+            model_reader_closure: None,
+            config_reader_closure: None,
         }
     }
 
@@ -40,136 +167,227 @@ impl Component for App {
                 self.processed = false;
                 true
             }
+            Msg::SeedInput(seed) => {
+                // keep the previous seed if the input is not a valid number
+                if let Ok(seed) = seed.trim().parse() {
+                    self.config.seed = seed;
+                }
+                false
+            }
+            Msg::ConfirmLabelMerge(duplicate, canonical, confirmed) => {
+                if confirmed {
+                    self.config.filters.merged_labels.insert(duplicate, canonical);
+                } else {
+                    self.config.filters.merged_labels.remove(&duplicate);
+                }
+                false
+            }
+            Msg::DropDuplicateEvents(drop) => {
+                self.config.import.drop_duplicate_events = drop;
+                false
+            }
+            Msg::ExcludeIncompleteCases(exclude) => {
+                self.config.filters.exclude_incomplete_cases = exclude;
+                false
+            }
+            Msg::MarkOngoingCases(mark) => {
+                self.config.completeness.mark_ongoing = mark;
+                false
+            }
+            Msg::CaseAttributesInput(case_attributes) => {
+                self.config.import.case_attributes = case_attributes
+                    .split(',')
+                    .map(|key| key.trim().to_string())
+                    .filter(|key| !key.is_empty())
+                    .collect();
+                false
+            }
+            Msg::TimestampKeyInput(key) => {
+                let key = key.trim();
+                self.config.import.timestamp_key = (!key.is_empty()).then(|| key.to_string());
+                false
+            }
+            Msg::ResourceKeyInput(key) => {
+                let key = key.trim();
+                self.config.import.resource_key = (!key.is_empty()).then(|| key.to_string());
+                false
+            }
+            Msg::LifecycleInput(lifecycle) => {
+                self.config.import.lifecycle = match lifecycle.as_str() {
+                    "start" => LifecyclePolicy::StartOnly,
+                    "all" => LifecyclePolicy::All,
+                    _ => LifecyclePolicy::CompleteOnly,
+                };
+                false
+            }
+            Msg::CaseGapInput(minutes) => {
+                self.config.import.case_gap_minutes = minutes.trim().parse().ok().filter(|&m: &i64| m > 0);
+                false
+            }
+            Msg::SessionGapInput(minutes) => {
+                self.config.import.session_gap_minutes = minutes.trim().parse().ok().filter(|&m: &i64| m > 0);
+                false
+            }
+            Msg::MinSupportInput(min_support) => {
+                self.config.thresholds.min_support = min_support.trim().parse().unwrap_or(0);
+                false
+            }
             Msg::XESImport(file_option) => {
                 if let Some(file) = file_option {
-                    let link = ctx.link().clone();
-                    let reader = FileReader::new().unwrap_throw();
-                    let reader_clone = reader.clone();
-
-                    let onload = Closure::once(move |_event: web_sys::ProgressEvent| {
-                        match reader_clone.result() {
-                            Ok(result) => match result.as_string() {
-                                Some(text) => link.send_message(Msg::XESLoaded(Ok(text))),
-                                None => link.send_message(Msg::XESLoaded(Err(
-                                    "Failed to convert file content to string".to_string(),
-                                ))),
-                            },
-                            Err(e) => link.send_message(Msg::XESLoaded(Err(format!(
-                                "Error reading file: {:?}",
-                                e
-                            )))),
+                    match read_file(ctx, &file, Msg::XESLoaded, decode_log) {
+                        // store the closure in self to keep it alive
+                        Ok(onload) => self.file_reader_closure = Some(onload),
+                        Err(_e) => {
+                            self.text = self.tr(Message::ErrorReadingFile).to_string();
+                            return true;
                         }
-                    });
-
-                    reader.set_onload(Some(onload.as_ref().unchecked_ref()));
-
-                    // store the closure in self to keep it alive
-                    self.file_reader_closure = Some(onload);
-
-                    if let Err(_e) = reader.read_as_text(&file) {
-                        self.text = "Error reading file".to_string();
-                        return true;
                     }
                 }
                 false
             }
-            Msg::XESLoaded(result) => {
+            Msg::ModelImport(file_option) => {
+                if let Some(file) = file_option {
+                    match read_file(ctx, &file, Msg::ModelLoaded, decode_text) {
+                        Ok(onload) => self.model_reader_closure = Some(onload),
+                        Err(_e) => {
+                            self.text = self.tr(Message::ErrorReadingFile).to_string();
+                            return true;
+                        }
+                    }
+                }
+                false
+            }
+            Msg::ModelLoaded(file_name, result) => {
+                let model = result.and_then(|content| {
+                    if is_ltl_model(&file_name) {
+                        return parse_formulas(&content)
+                            .map(|_| content)
+                            .map_err(|e| format!("{}: {}", file_name, e));
+                    }
+                    let parsed = if is_declare_model(&file_name) {
+                        parse_declare(&content).map(|_| ())
+                    } else {
+                        parse_petri_net(None, Some(&content)).map(|_| ())
+                    };
+                    parsed
+                        .map(|_| content)
+                        .map_err(|e| e.with_file(&file_name).to_string())
+                });
+                match model {
+                    Ok(content) => {
+                        self.text = Message::ModelLoaded.format(self.config.language, &[&file_name]);
+                        self.model = Some((file_name, content));
+                    }
+                    Err(e) => self.text = Message::ErrorLoadingModel.format(self.config.language, &[&e]),
+                }
+                true
+            }
+            Msg::XESLoaded(file_name, result) => {
+                self.visualization = None;
+                self.report_metrics.clear();
                 match result {
                     Ok(content) => {
-                        let traces = parse_into_traces(None, Some(&content));
-                        match traces {
-                            Ok(traces) => {
-                                let (
-                                    adj_matrix,
-                                    full_independences,
-                                    pure_existences,
-                                    eventual_equivalences,
-                                    direct_equivalences,
-                                    number_of_activities,
-                                    relationship_counts
-                                ) = generate_adj_matrix_from_traces(traces.clone());
-                                let relations = number_of_activities * number_of_activities;
-                                let independences_per_relations =
-                                    full_independences as f64 / relations as f64;
-                                let temporal_independences_per_relations =
-                                    pure_existences as f64 / relations as f64;
-                                let traces_as_str: Vec<Vec<&str>> = traces
-                                    .iter()
-                                    .map(|trace| trace.iter().map(|s| s.as_str()).collect())
-                                    .collect();
-                                let variants = variants_of_traces(traces_as_str);
-                                let max_variant_frequency =
-                                    *variants.values().max().unwrap() as f64 / traces.len() as f64;
-                                let variants_per_traces =
-                                    variants.len() as f64 / traces.len() as f64;
-                                let freq_over_variants = max_variant_frequency / variants.len() as f64;
-
-                                // NOTE: should probably also move this to lib.rs
-                                // Convert traces to the Event format required by ExtendedPrefixAutomaton
-                                let plain_log: Vec<Vec<egypt::Event>> = traces.clone()
+                        let result = analyze(LogInput::Content(content.clone()), self.config.clone())
+                            .map_err(|e| e.with_file(&file_name));
+                        match result {
+                            Ok(result) => {
+                                let log = &result.log;
+                                let diagnostics_report = if result.diagnostics.is_empty() {
+                                    String::new()
+                                } else {
+                                    format!("\n\n{}:\n{}", self.tr(Message::Diagnostics), result.diagnostics.join("\n"))
+                                };
+                                let conformance_report = match &self.model {
+                                    Some((file_name, model)) if is_ltl_model(file_name) => format!(
+                                        "\n\n{}:\n{}",
+                                        self.tr(Message::LtlFormulas),
+                                        parse_formulas(model)
+                                            .unwrap_or_default()
+                                            .iter()
+                                            .map(|formula| format!(
+                                                "{:<48}{:<10.4}",
+                                                formula.to_string(),
+                                                check_formula(formula, log).satisfaction_rate()
+                                            ))
+                                            .collect::<Vec<String>>()
+                                            .join("\n")
+                                    ),
+                                    Some((file_name, model)) if is_declare_model(file_name) => {
+                                        let constraints = parse_declare(model).unwrap_or_default();
+                                        format!(
+                                            "\n\n{}:\n{}",
+                                            self.tr(Message::DeclareConstraints),
+                                            check_constraints(&constraints, log)
+                                                .iter()
+                                                .map(|statistics| format!(
+                                                    "{:<48}{:<10.4}{:<10.4}{}",
+                                                    statistics.constraint.to_string(),
+                                                    statistics.support(),
+                                                    statistics.confidence(),
+                                                    statistics.violated
+                                                ))
+                                                .collect::<Vec<String>>()
+                                                .join("\n")
+                                        )
+                                    }
+                                    Some((_, model)) => parse_petri_net(None, Some(model))
+                                        .map(|net| format!("\n\n{}", result.conformance(&net).report(self.config.language)))
+                                        .unwrap_or_default(),
+                                    None => String::new(),
+                                };
+
+                                self.imported = Some((file_name, content));
+                                let conformance_metrics: &[Message] = match &self.model {
+                                    Some((file_name, _)) if is_ltl_model(file_name) => &[Message::LtlFormulas],
+                                    Some((file_name, _)) if is_declare_model(file_name) => {
+                                        &[Message::DeclareConstraints]
+                                    }
+                                    Some(_) => &[
+                                        Message::Fitness,
+                                        Message::FittingTraces,
+                                        Message::ModelNodes,
+                                        Message::ModelEdges,
+                                        Message::AverageConnectorDegree,
+                                        Message::CyclomaticComplexity,
+                                    ],
+                                    None => &[],
+                                };
+                                self.report_metrics = result
+                                    .glossary_metrics()
                                     .into_iter()
-                                    .enumerate()
-                                    .map(|(case_idx, trace)| {
-                                        trace
-                                            .into_iter()
-                                            .enumerate()
-                                            .map(|(event_idx, activity)| egypt::Event {
-                                                case: format!("case_{}", case_idx),
-                                                activity: activity.chars().next().unwrap(),
-                                                predecessor: if event_idx > 0 {
-                                                    Some(format!("case_{}", case_idx))
-                                                } else {
-                                                    None
-                                                },
-                                            })
-                                            .collect()
-                                    })
+                                    .chain(
+                                        conformance_metrics
+                                            .iter()
+                                            .filter_map(|&label| metric_for_label(label).copied()),
+                                    )
                                     .collect();
-
-                                let epa = ExtendedPrefixAutomaton::build(plain_log);
-                                let variant_entropy = epa.variant_entropy();
-                                let normalized_variant_entropy = epa.normalized_variant_entropy();
-
+                                let glossary_report = if self.include_glossary {
+                                    let reported: Vec<_> = self.report_metrics.iter().collect();
+                                    format!(
+                                        "\n\n{}:\n{}",
+                                        self.tr(Message::Glossary),
+                                        glossary(&reported, self.config.language)
+                                    )
+                                } else {
+                                    String::new()
+                                };
                                 self.text = format!(
-                                    "{}\n\n\
-                                    #relations:                                     {:<10}\n\
-                                    #independence / #relations:                     {:<10.4}\n\
-                                    #temporal independence / #relations:            {:<10.4}\n\
-                                    max. frequency of variants / total #traces:     {:<10.4}\n\
-                                    #variants / total #traces:                      {:<10.4}\n\
-                                    #(Eventual, <=>):                               {:<10}\n\
-                                    #(Direct, <=>):                                 {:<10}\n\
-                                    #variants:                                      {:<10}\n\
-                                    max. frequency of variants / #variants:         {:<10.4}\n\
-                                    Variant Entropy:                                {:<10.4}\n\
-                                    Normalized Variant Entropy:                     {:<10.4}\n\n\
-                                    Relationship Type Frequencies:\n{}",
-                                    adj_matrix,
-                                    relations,
-                                    independences_per_relations,
-                                    temporal_independences_per_relations,
-                                    max_variant_frequency,
-                                    variants_per_traces,
-                                    eventual_equivalences,
-                                    direct_equivalences,
-                                    variants.len() as f64,
-                                    freq_over_variants,
-                                    variant_entropy,
-                                    normalized_variant_entropy,
-                                    relationship_counts.iter()
-                                        .map(|(k, v)| format!("{}: {}", k, v))
-                                        .collect::<Vec<String>>()
-                                        .join("\n")
+                                    "{}{}{}{}",
+                                    result.report(),
+                                    diagnostics_report,
+                                    conformance_report,
+                                    glossary_report
                                 );
+                                self.analysis = Some(result);
                             }
 
                             Err(e) => {
-                                self.text = format!("Error parsing file: {}", e);
+                                self.text = e.to_string();
                             }
                         }
                     }
                     Err(e) => {
-                        self.text = format!("Error loading file: {}", e);
+                        self.text = Message::ErrorLoadingFile.format(self.config.language, &[&e]);
                     }
                 }
                 true
@@ -184,31 +402,326 @@ impl Component for App {
                 true
             }
             Msg::DownloadXES => {
-                let window = web_sys::window().unwrap_throw();
-                let document = window.document().unwrap_throw();
-
-                let blob = web_sys::Blob::new_with_str_sequence_and_options(
-                    &js_sys::Array::of1(&JsValue::from_str(&self.text)),
-                    web_sys::BlobPropertyBag::new().type_("text/plain"),
-                )
-                .unwrap_throw();
-
-                let url = web_sys::Url::create_object_url_with_blob(&blob).unwrap_throw();
-
-                let anchor: HtmlAnchorElement = document
-                    .create_element("a")
-                    .unwrap_throw()
-                    .dyn_into()
-                    .unwrap_throw();
-
-                anchor.set_href(&url);
-                anchor.set_download("event_log.xes");
-                anchor.click();
-
-                web_sys::Url::revoke_object_url(&url).unwrap_throw();
-
+                download("event_log.xes", &self.text);
+                false
+            }
+            Msg::TimelineCaseInput(case_id) => {
+                self.timeline_case = case_id.trim().to_string();
+                false
+            }
+            Msg::ShowTimeline => {
+                let Some((file_name, content)) = &self.imported else {
+                    return false;
+                };
+                let timelines = self.timelines(content).map_err(|e| e.with_file(file_name));
+                match timelines {
+                    Ok(timelines) => {
+                        let timeline = timelines.iter().find(|timeline| {
+                            self.timeline_case.is_empty() || timeline.case_id == self.timeline_case
+                        });
+                        match timeline {
+                            Some(timeline) => self.visualization = Some(timeline_svg(
+                                timeline,
+                                &self.config.theme,
+                                self.config.calendar.as_ref(),
+                            )),
+                            None => {
+                                self.text =
+                                    Message::UnknownCase.format(self.config.language, &[&self.timeline_case])
+                            }
+                        }
+                    }
+                    Err(e) => self.text = e.to_string(),
+                }
+                true
+            }
+            Msg::ReferenceCaseInput(case_id) => {
+                self.reference_case = case_id.trim().to_string();
+                false
+            }
+            Msg::CompareWithReference => {
+                let Some(result) = &self.analysis else {
+                    return false;
+                };
+                let log = &result.log;
+                let case = match log.cases().first() {
+                    Some(first) if self.timeline_case.is_empty() => first.case_id.clone(),
+                    _ => self.timeline_case.clone(),
+                };
+                match TraceDiff::of(log, &self.reference_case, &case) {
+                    Some(diff) => {
+                        self.visualization = Some(diff.html(&self.config.theme));
+                        self.text = diff.report(self.config.language);
+                    }
+                    None => {
+                        let known = |case_id: &str| log.cases().iter().any(|case| case.case_id == case_id);
+                        let unknown = if known(&self.reference_case) { &case } else { &self.reference_case };
+                        self.text = Message::UnknownCase.format(self.config.language, &[unknown]);
+                    }
+                }
+                true
+            }
+            Msg::ShowVariants => {
+                let Some(result) = &self.analysis else {
+                    return false;
+                };
+                self.visualization = Some(variants_svg(
+                    &result.log.top_variants(TOP_VARIANTS),
+                    result.log.len(),
+                    &self.config.theme,
+                ));
+                // a real case for each variant shown
+                let summaries = case_summaries(&result.log, &result.timelines, self.config.calendar.as_ref());
+                self.text = representatives_report(
+                    &variant_representatives(&summaries, TOP_VARIANTS),
+                    self.config.language,
+                );
+                true
+            }
+            Msg::ShowProcessMap => {
+                let Some((file_name, content)) = &self.imported else {
+                    return false;
+                };
+                match parse_into_variant_log_with_config(None, Some(content), &self.config.import.import_config()) {
+                    Ok(log) => {
+                        let log = self.config.filters.apply(log);
+                        let dfg = DirectlyFollowsGraph::from_variant_log(&log);
+                        self.visualization = Some(match &self.config.grouping {
+                            Some(grouping) => grouped_dfg_svg(
+                                &dfg,
+                                &grouping.strategy.groups(&dfg),
+                                grouping.collapsed,
+                                &self.config.theme,
+                            ),
+                            None => dfg_svg(&dfg, &self.config.theme),
+                        })
+                    }
+                    Err(e) => self.text = e.with_file(file_name).to_string(),
+                }
+                true
+            }
+            Msg::ShowRepetitions => {
+                let Some((file_name, content)) = &self.imported else {
+                    return false;
+                };
+                let timelines = self.analyzed_timelines(content).map_err(|e| e.with_file(file_name));
+                self.text = match timelines {
+                    Ok(timelines) => repetition_report(
+                        &self_distances(&timelines, self.config.calendar.as_ref()),
+                        self.config.language,
+                    ),
+                    Err(e) => e.to_string(),
+                };
+                true
+            }
+            Msg::ShowGateways => {
+                let Some((file_name, content)) = &self.imported else {
+                    return false;
+                };
+                self.text = match parse_into_variant_log_with_config(None, Some(content), &self.config.import.import_config()) {
+                    Ok(log) => gateway_report(&gateways(&self.config.filters.apply(log)), self.config.language),
+                    Err(e) => e.with_file(file_name).to_string(),
+                };
+                true
+            }
+            Msg::MineDecisions => {
+                let Some((file_name, content)) = &self.imported else {
+                    return false;
+                };
+                let points = parse_into_event_log_with_config(None, Some(content), &self.config.import.import_config())
+                    .and_then(|log| mine_decisions(&log, &self.config.import.classifier, &DecisionSettings::default()))
+                    .map_err(|e| e.with_file(file_name));
+                self.text = match points {
+                    Ok(points) => decision_report(&points, self.config.language),
+                    Err(e) => e.to_string(),
+                };
+                true
+            }
+            Msg::ShowBatching => {
+                let Some((file_name, content)) = &self.imported else {
+                    return false;
+                };
+                let timelines = self.analyzed_timelines(content).map_err(|e| e.with_file(file_name));
+                self.text = match timelines {
+                    Ok(timelines) => [
+                        (Message::SimultaneousBatches, BatchSettings::simultaneous()),
+                        (Message::ResourceBursts, BatchSettings::resource_bursts()),
+                    ]
+                    .iter()
+                    .map(|(title, settings)| {
+                        format!(
+                            "{}:\n{}",
+                            self.tr(*title),
+                            batching_report(&batching_signals(&timelines, settings), self.config.language)
+                        )
+                    })
+                    .collect::<Vec<String>>()
+                    .join("\n"),
+                    Err(e) => e.to_string(),
+                };
+                true
+            }
+            Msg::ShowActivityProfile => {
+                let Some((file_name, content)) = &self.imported else {
+                    return false;
+                };
+                self.text = match self.analyzed_timelines(content) {
+                    Ok(timelines) => activity_profile_report(
+                        &activity_profiles(&timelines, self.utc_offset_minutes()),
+                        self.config.language,
+                    ),
+                    Err(e) => e.with_file(file_name).to_string(),
+                };
+                true
+            }
+            Msg::CohortAttributeInput(attribute) => {
+                self.cohort_attribute = attribute.trim().to_string();
+                false
+            }
+            Msg::CompareCohorts => {
+                let Some((file_name, content)) = &self.imported else {
+                    return false;
+                };
+                let cohorts = parse_into_event_log_with_config(None, Some(content), &self.config.import.import_config())
+                    .and_then(|log| {
+                        cohorts(
+                            &log,
+                            &self.config.import.classifier,
+                            &self.cohort_attribute,
+                            self.config.calendar.as_ref(),
+                        )
+                    })
+                    .map_err(|e| e.with_file(file_name));
+                self.text = match cohorts {
+                    Ok(cohorts) => cohort_report(&self.cohort_attribute, &cohorts, 3, self.config.language),
+                    Err(e) => e.to_string(),
+                };
+                true
+            }
+            Msg::DownloadCSV => {
+                if let Some(result) = &self.analysis {
+                    download(
+                        "event_log.csv",
+                        &disco_csv_from_timelines(&result.timelines, &self.config.import.timezone),
+                    );
+                }
+                false
+            }
+            Msg::DownloadFullXES => {
+                if let Some((file_name, content)) = &self.imported {
+                    let xes = parse_into_log_with_config(None, Some(content), &self.config.import.import_config())
+                        .map(|log| export_xes(&log, &ExportedAttributes::All, &self.config.import.timezone))
+                        .map_err(|e| e.with_file(file_name));
+                    match xes {
+                        Ok(xes) => download("event_log.xes", &xes),
+                        Err(e) => {
+                            self.text = e.to_string();
+                            return true;
+                        }
+                    }
+                }
+                false
+            }
+            Msg::DownloadWipCSV => {
+                if let Some((file_name, content)) = &self.imported {
+                    match self.timelines(content) {
+                        Ok(timelines) => {
+                            download("wip.csv", &WipSeries::of(&timelines, WIP_INTERVAL).to_csv())
+                        }
+                        Err(e) => {
+                            self.text = e.with_file(file_name).to_string();
+                            return true;
+                        }
+                    }
+                }
+                false
+            }
+            Msg::DownloadActivityProfileCSV => {
+                if let Some((file_name, content)) = &self.imported {
+                    match self.analyzed_timelines(content) {
+                        Ok(timelines) => download(
+                            "activity_profile.csv",
+                            &activity_profile_csv(&activity_profiles(&timelines, self.utc_offset_minutes())),
+                        ),
+                        Err(e) => {
+                            self.text = e.with_file(file_name).to_string();
+                            return true;
+                        }
+                    }
+                }
+                false
+            }
+            Msg::PaletteInput(palette) => {
+                if let Ok(palette) = palette.parse::<Palette>() {
+                    self.config.theme.palette = palette;
+                }
+                // views already shown keep their colors until they are shown again
+                false
+            }
+            Msg::DarkBackground(dark) => {
+                self.config.theme.background = if dark { Background::Dark } else { Background::Light };
+                true
+            }
+            Msg::ConfigImport(file_option) => {
+                if let Some(file) = file_option {
+                    match read_file(ctx, &file, Msg::ConfigLoaded, decode_text) {
+                        Ok(onload) => self.config_reader_closure = Some(onload),
+                        Err(_e) => {
+                            self.text = self.tr(Message::ErrorReadingFile).to_string();
+                            return true;
+                        }
+                    }
+                }
+                false
+            }
+            Msg::ConfigLoaded(file_name, result) => {
+                let config = result.and_then(|content| {
+                    AnalysisConfig::from_file_content(&file_name, &content).map_err(|e| e.to_string())
+                });
+                match config {
+                    Ok(config) => self.config = config,
+                    Err(e) => self.text = Message::ErrorLoadingConfig.format(self.config.language, &[&e]),
+                }
+                true
+            }
+            Msg::SaveConfig => {
+                match self.config.to_toml() {
+                    Ok(toml) => download("analysis.toml", &toml),
+                    Err(e) => {
+                        self.text = e.to_string();
+                        return true;
+                    }
+                }
+                false
+            }
+            Msg::ConfidenceIntervals(include) => {
+                self.config.bootstrap = include.then(Bootstrap::default);
+                false
+            }
+            Msg::RecommendThreshold(recommend) => {
+                self.config.threshold_sweep = recommend.then(ThresholdSweep::default);
+                false
+            }
+            Msg::BusinessHours(business_hours) => {
+                self.config.calendar = business_hours.then(BusinessCalendar::default);
+                false
+            }
+            Msg::IncludeGlossary(include) => {
+                self.include_glossary = include;
                 false
             }
+            Msg::LanguageInput(language) => {
+                if let Ok(language) = language.parse() {
+                    self.config.language = language;
+                }
+                true
+            }
+            Msg::FontSizeInput(size) => {
+                if let Some(size) = size.trim().parse().ok().filter(|&size: &f64| size > 0.0) {
+                    self.config.theme.font_size = size;
+                }
+                true
+            }
         }
     }
 
@@ -218,6 +731,135 @@ impl Component for App {
             Msg::TextInput(input.value())
         });
 
+        let onseed = ctx.link().callback(|e: Event| {
+            let input: HtmlInputElement = e.target_unchecked_into();
+            Msg::SeedInput(input.value())
+        });
+
+        let oncaseattributes = ctx.link().callback(|e: Event| {
+            let input: HtmlInputElement = e.target_unchecked_into();
+            Msg::CaseAttributesInput(input.value())
+        });
+
+        let ontimestampkey = ctx.link().callback(|e: Event| {
+            let input: HtmlInputElement = e.target_unchecked_into();
+            Msg::TimestampKeyInput(input.value())
+        });
+
+        let onresourcekey = ctx.link().callback(|e: Event| {
+            let input: HtmlInputElement = e.target_unchecked_into();
+            Msg::ResourceKeyInput(input.value())
+        });
+
+        let onlifecycle = ctx.link().callback(|e: Event| {
+            let input: HtmlSelectElement = e.target_unchecked_into();
+            Msg::LifecycleInput(input.value())
+        });
+
+        let oncasegap = ctx.link().callback(|e: Event| {
+            let input: HtmlInputElement = e.target_unchecked_into();
+            Msg::CaseGapInput(input.value())
+        });
+
+        let onsessiongap = ctx.link().callback(|e: Event| {
+            let input: HtmlInputElement = e.target_unchecked_into();
+            Msg::SessionGapInput(input.value())
+        });
+
+        let onminsupport = ctx.link().callback(|e: Event| {
+            let input: HtmlInputElement = e.target_unchecked_into();
+            Msg::MinSupportInput(input.value())
+        });
+
+        let onmodelimport = ctx.link().callback(|e: Event| {
+            let input: HtmlInputElement = e.target_unchecked_into();
+            Msg::ModelImport(input.files().and_then(|files| files.get(0)))
+        });
+
+        let ontimelinecase = ctx.link().callback(|e: Event| {
+            let input: HtmlInputElement = e.target_unchecked_into();
+            Msg::TimelineCaseInput(input.value())
+        });
+        let onreferencecase = ctx.link().callback(|e: Event| {
+            let input: HtmlInputElement = e.target_unchecked_into();
+            Msg::ReferenceCaseInput(input.value())
+        });
+        let onpalette = ctx.link().callback(|e: Event| {
+            let input: HtmlSelectElement = e.target_unchecked_into();
+            Msg::PaletteInput(input.value())
+        });
+
+        let ondark = ctx.link().callback(|e: Event| {
+            let input: HtmlInputElement = e.target_unchecked_into();
+            Msg::DarkBackground(input.checked())
+        });
+
+        let onfontsize = ctx.link().callback(|e: Event| {
+            let input: HtmlInputElement = e.target_unchecked_into();
+            Msg::FontSizeInput(input.value())
+        });
+
+        let onlanguage = ctx.link().callback(|e: Event| {
+            let input: HtmlSelectElement = e.target_unchecked_into();
+            Msg::LanguageInput(input.value())
+        });
+
+        let ondropduplicates = ctx.link().callback(|e: Event| {
+            let input: HtmlInputElement = e.target_unchecked_into();
+            Msg::DropDuplicateEvents(input.checked())
+        });
+
+        let onexcludeincomplete = ctx.link().callback(|e: Event| {
+            let input: HtmlInputElement = e.target_unchecked_into();
+            Msg::ExcludeIncompleteCases(input.checked())
+        });
+
+        let onmarkongoing = ctx.link().callback(|e: Event| {
+            let input: HtmlInputElement = e.target_unchecked_into();
+            Msg::MarkOngoingCases(input.checked())
+        });
+
+        let onglossary = ctx.link().callback(|e: Event| {
+            let input: HtmlInputElement = e.target_unchecked_into();
+            Msg::IncludeGlossary(input.checked())
+        });
+
+        let onconfidence = ctx.link().callback(|e: Event| {
+            let input: HtmlInputElement = e.target_unchecked_into();
+            Msg::ConfidenceIntervals(input.checked())
+        });
+
+        let onrecommendthreshold = ctx.link().callback(|e: Event| {
+            let input: HtmlInputElement = e.target_unchecked_into();
+            Msg::RecommendThreshold(input.checked())
+        });
+
+        let onbusinesshours = ctx.link().callback(|e: Event| {
+            let input: HtmlInputElement = e.target_unchecked_into();
+            Msg::BusinessHours(input.checked())
+        });
+
+        let onconfigimport = ctx.link().callback(|e: Event| {
+            let input: HtmlInputElement = e.target_unchecked_into();
+            Msg::ConfigImport(input.files().and_then(|files| files.get(0)))
+        });
+        let onsaveconfig = ctx.link().callback(|_| Msg::SaveConfig);
+
+        let ontimeline = ctx.link().callback(|_| Msg::ShowTimeline);
+        let oncomparecases = ctx.link().callback(|_| Msg::CompareWithReference);
+        let onvariants = ctx.link().callback(|_| Msg::ShowVariants);
+        let onprocessmap = ctx.link().callback(|_| Msg::ShowProcessMap);
+        let onrepetitions = ctx.link().callback(|_| Msg::ShowRepetitions);
+        let ongateways = ctx.link().callback(|_| Msg::ShowGateways);
+        let ondecisions = ctx.link().callback(|_| Msg::MineDecisions);
+        let onbatching = ctx.link().callback(|_| Msg::ShowBatching);
+        let onprofile = ctx.link().callback(|_| Msg::ShowActivityProfile);
+        let oncohortattribute = ctx.link().callback(|e: Event| {
+            let input: HtmlInputElement = e.target_unchecked_into();
+            Msg::CohortAttributeInput(input.value())
+        });
+        let oncohorts = ctx.link().callback(|_| Msg::CompareCohorts);
+
         let onxesimport = ctx.link().callback(|e: Event| {
             let input: HtmlInputElement = e.target_unchecked_into();
             if let Some(file) = input.files().and_then(|files| files.get(0)) {
@@ -230,35 +872,347 @@ impl Component for App {
         // let onmatrix = ctx.link().callback(|_| Msg::ConvertToAdjMatrix);
         let onprocess = ctx.link().callback(|_| Msg::ConvertToXES);
         let ondownload = ctx.link().callback(|_| Msg::DownloadXES);
+        let oncsv = ctx.link().callback(|_| Msg::DownloadCSV);
+        let onfullxes = ctx.link().callback(|_| Msg::DownloadFullXES);
+        let onwipcsv = ctx.link().callback(|_| Msg::DownloadWipCSV);
+        let onprofilecsv = ctx.link().callback(|_| Msg::DownloadActivityProfileCSV);
 
         html! {
             <div style="height: 90vh; display: flex; flex-direction: column;">
                 <textarea
                     value={self.text.clone()}
                     oninput={oninput}
-                    placeholder="Enter your text here"
-                    style="flex-grow: 1; width: 99%; background-color: #393939; color: white; padding: 10px; font-size: 16px; resize: none;"
+                    placeholder={self.tr(Message::TextPlaceholder)}
+                    style={format!(
+                        "flex-grow: 1; width: 99%; background-color: {}; color: {}; padding: 10px; font-size: {}px; resize: none;",
+                        self.config.theme.background_color(),
+                        self.config.theme.text_color(),
+                        self.config.theme.font_size
+                    )}
                 />
+                <div style="display: flex; flex-wrap: wrap; padding: 10px 10px 0;">
+                    { for self.report_metrics.iter().map(|metric| html! {
+                        <abbr title={metric.summary()} style="padding: 0 10px 0 0; font-size: 14px; cursor: help;">
+                            {metric.label.text(self.config.language)}
+                        </abbr>
+                    }) }
+                </div>
                 <div style="display: flex; padding: 10px; justify-content: right;">
-                    <input type="file" id="xes-file" accept=".xes" onchange={onxesimport} style="display: none;" />
+                    <label for="seed" style="padding: 10px; font-size: 16px;">{self.tr(Message::Seed)}</label>
+                    <input type="number" id="seed" min="0" value={self.config.seed.to_string()} onchange={onseed} style="width: 120px; font-size: 16px; margin-right: 10px;" />
+                    <label for="case-attributes" style="padding: 10px; font-size: 16px;">{self.tr(Message::CaseAttributes)}</label>
+                    <input type="text" id="case-attributes" placeholder={self.tr(Message::CaseAttributesPlaceholder)} value={self.config.import.case_attributes.join(", ")} onchange={oncaseattributes} style="width: 160px; font-size: 16px; margin-right: 10px;" />
+                    <label for="timestamp-key" style="padding: 10px; font-size: 16px;">{self.tr(Message::TimestampKey)}</label>
+                    <input type="text" id="timestamp-key" placeholder="time:timestamp" value={self.config.import.timestamp_key.clone().unwrap_or_default()} onchange={ontimestampkey} style="width: 120px; font-size: 16px; margin-right: 10px;" />
+                    <label for="resource-key" style="padding: 10px; font-size: 16px;">{self.tr(Message::ResourceKey)}</label>
+                    <input type="text" id="resource-key" placeholder="org:resource" value={self.config.import.resource_key.clone().unwrap_or_default()} onchange={onresourcekey} style="width: 120px; font-size: 16px; margin-right: 10px;" />
+                    <label for="lifecycle" style="padding: 10px; font-size: 16px;">{self.tr(Message::LifecycleEvents)}</label>
+                    <select id="lifecycle" onchange={onlifecycle} style="font-size: 16px; margin-right: 10px;">
+                        <option value="complete" selected={self.config.import.lifecycle == LifecyclePolicy::CompleteOnly}>{self.tr(Message::LifecycleComplete)}</option>
+                        <option value="start" selected={self.config.import.lifecycle == LifecyclePolicy::StartOnly}>{self.tr(Message::LifecycleStart)}</option>
+                        <option value="all" selected={self.config.import.lifecycle == LifecyclePolicy::All}>{self.tr(Message::LifecycleAll)}</option>
+                    </select>
+                    <label for="case-gap" style="padding: 10px; font-size: 16px;">{self.tr(Message::MaxGap)}</label>
+                    <input type="number" id="case-gap" min="1" placeholder={self.tr(Message::MaxGapPlaceholder)} value={self.config.import.case_gap_minutes.map(|minutes| minutes.to_string()).unwrap_or_default()} onchange={oncasegap} style="width: 80px; font-size: 16px; margin-right: 10px;" />
+                    <label for="session-gap" style="padding: 10px; font-size: 16px;">{self.tr(Message::SessionGap)}</label>
+                    <input type="number" id="session-gap" min="1" placeholder={self.tr(Message::MaxGapPlaceholder)} value={self.config.import.session_gap_minutes.map(|minutes| minutes.to_string()).unwrap_or_default()} onchange={onsessiongap} style="width: 80px; font-size: 16px; margin-right: 10px;" />
+                    <label for="min-support" style="padding: 10px; font-size: 16px;">{self.tr(Message::MinSupport)}</label>
+                    <input type="number" id="min-support" min="0" value={self.config.thresholds.min_support.to_string()} onchange={onminsupport} style="width: 80px; font-size: 16px; margin-right: 10px;" />
+                    <label for="drop-duplicates" style="padding: 10px; font-size: 16px;">{self.tr(Message::DropDuplicateEvents)}</label>
+                    <input type="checkbox" id="drop-duplicates" checked={self.config.import.drop_duplicate_events} onchange={ondropduplicates} style="margin-right: 10px;" />
+                    <label for="exclude-incomplete" style="padding: 10px; font-size: 16px;">{self.tr(Message::ExcludeIncompleteCases)}</label>
+                    <input type="checkbox" id="exclude-incomplete" checked={self.config.filters.exclude_incomplete_cases} onchange={onexcludeincomplete} style="margin-right: 10px;" />
+                    <label for="mark-ongoing" style="padding: 10px; font-size: 16px;">{self.tr(Message::MarkOngoingCases)}</label>
+                    <input type="checkbox" id="mark-ongoing" checked={self.config.completeness.mark_ongoing} onchange={onmarkongoing} style="margin-right: 10px;" />
+                    <label for="glossary" style="padding: 10px; font-size: 16px;">{self.tr(Message::Glossary)}</label>
+                    <input type="checkbox" id="glossary" checked={self.include_glossary} onchange={onglossary} style="margin-right: 10px;" />
+                    <label for="confidence" style="padding: 10px; font-size: 16px;">{self.tr(Message::ConfidenceIntervals)}</label>
+                    <input type="checkbox" id="confidence" checked={self.config.bootstrap.is_some()} onchange={onconfidence} style="margin-right: 10px;" />
+                    <label for="recommend-threshold" style="padding: 10px; font-size: 16px;">{self.tr(Message::RecommendThreshold)}</label>
+                    <input type="checkbox" id="recommend-threshold" checked={self.config.threshold_sweep.is_some()} onchange={onrecommendthreshold} style="margin-right: 10px;" />
+                    <label for="business-hours" style="padding: 10px; font-size: 16px;">{self.tr(Message::BusinessHours)}</label>
+                    <input type="checkbox" id="business-hours" checked={self.config.calendar.is_some()} onchange={onbusinesshours} style="margin-right: 10px;" />
+                    <input type="file" id="model-file" accept=".pnml,.decl,.ltl" onchange={onmodelimport} style="display: none;" />
+                    <label for="model-file" style="padding: 10px 20px; font-size: 16px; margin-right: 10px; background-color: #4CAF50; color: white; cursor: pointer; border-radius: 5px;">
+                        {self.tr(Message::ImportModel)}
+                    </label>
+                    <input type="file" id="xes-file" accept=".xes,.gz" onchange={onxesimport} style="display: none;" />
                     <label for="xes-file" style="padding: 10px 20px; font-size: 16px; margin-right: 10px; background-color: #4CAF50; color: white; cursor: pointer; border-radius: 5px;">
-                        {"Import XES"}
+                        {self.tr(Message::ImportXes)}
                     </label>
                     // <button onclick={onmatrix} style="padding: 10px 20px; font-size: 16px; margin-right: 10px;">
                     //     {"Convert To Adjacency Matrix"}
                     // </button>
                     <button onclick={onprocess} disabled={self.processed} style="padding: 10px 20px; font-size: 16px; margin-right: 10px;">
-                        {"Convert To XES"}
+                        {self.tr(Message::ConvertToXes)}
+                    </button>
+                    <button onclick={ondownload} disabled={!self.processed} style="padding: 10px 20px; font-size: 16px; margin-right: 10px;">
+                        {self.tr(Message::DownloadXes)}
+                    </button>
+                    <button onclick={oncsv} disabled={self.analysis.is_none()} style="padding: 10px 20px; font-size: 16px; margin-right: 10px;">
+                        {self.tr(Message::ExportCsv)}
+                    </button>
+                    <button onclick={onfullxes} disabled={self.imported.is_none()} style="padding: 10px 20px; font-size: 16px; margin-right: 10px;">
+                        {self.tr(Message::ExportXes)}
+                    </button>
+                    <button onclick={onwipcsv} disabled={self.imported.is_none()} style="padding: 10px 20px; font-size: 16px; margin-right: 10px;">
+                        {self.tr(Message::ExportWipCsv)}
+                    </button>
+                    <button onclick={onprofilecsv} disabled={self.imported.is_none()} style="padding: 10px 20px; font-size: 16px;">
+                        {self.tr(Message::ExportActivityProfileCsv)}
+                    </button>
+                </div>
+                <div style="display: flex; padding: 0 10px 10px; justify-content: right;">
+                    <input type="file" id="config-file" accept=".toml,.json" onchange={onconfigimport} style="display: none;" />
+                    <label for="config-file" style="padding: 10px 20px; font-size: 16px; margin-right: 10px; background-color: #4CAF50; color: white; cursor: pointer; border-radius: 5px;">
+                        {self.tr(Message::LoadConfig)}
+                    </label>
+                    <button onclick={onsaveconfig} style="padding: 10px 20px; font-size: 16px; margin-right: 10px;">
+                        {self.tr(Message::SaveConfig)}
+                    </button>
+                    <label for="language" style="padding: 10px; font-size: 16px;">{self.tr(Message::Language)}</label>
+                    <select id="language" onchange={onlanguage} style="font-size: 16px; margin-right: 10px;">
+                        { for Language::ALL.iter().map(|language| html! {
+                            <option value={language.code()} selected={*language == self.config.language}>{language.name()}</option>
+                        }) }
+                    </select>
+                    <label for="palette" style="padding: 10px; font-size: 16px;">{self.tr(Message::Palette)}</label>
+                    <select id="palette" onchange={onpalette} style="font-size: 16px; margin-right: 10px;">
+                        <option value="default" selected={self.config.theme.palette == Palette::Default}>{self.tr(Message::PaletteDefault)}</option>
+                        <option value="color-blind-safe" selected={self.config.theme.palette == Palette::ColorBlindSafe}>{self.tr(Message::PaletteColorBlindSafe)}</option>
+                        <option value="grayscale" selected={self.config.theme.palette == Palette::Grayscale}>{self.tr(Message::PaletteGrayscale)}</option>
+                    </select>
+                    <label for="dark-background" style="padding: 10px; font-size: 16px;">{self.tr(Message::DarkBackground)}</label>
+                    <input type="checkbox" id="dark-background" checked={self.config.theme.background == Background::Dark} onchange={ondark} style="margin-right: 10px;" />
+                    <label for="font-size" style="padding: 10px; font-size: 16px;">{self.tr(Message::FontSize)}</label>
+                    <input type="number" id="font-size" min="6" value={self.config.theme.font_size.to_string()} onchange={onfontsize} style="width: 60px; font-size: 16px; margin-right: 10px;" />
+                    <label for="timeline-case" style="padding: 10px; font-size: 16px;">{self.tr(Message::Case)}</label>
+                    <input type="text" id="timeline-case" placeholder={self.tr(Message::FirstCase)} value={self.timeline_case.clone()} onchange={ontimelinecase} style="width: 160px; font-size: 16px; margin-right: 10px;" />
+                    <button onclick={ontimeline} disabled={self.imported.is_none()} style="padding: 10px 20px; font-size: 16px; margin-right: 10px;">
+                        {self.tr(Message::ShowTimeline)}
+                    </button>
+                    <label for="reference-case" style="padding: 10px; font-size: 16px;">{self.tr(Message::ReferenceCase)}</label>
+                    <input type="text" id="reference-case" value={self.reference_case.clone()} onchange={onreferencecase} style="width: 160px; font-size: 16px; margin-right: 10px;" />
+                    <button onclick={oncomparecases} disabled={self.analysis.is_none() || self.reference_case.is_empty()} style="padding: 10px 20px; font-size: 16px; margin-right: 10px;">
+                        {self.tr(Message::CompareWithReference)}
                     </button>
-                    <button onclick={ondownload} disabled={!self.processed} style="padding: 10px 20px; font-size: 16px;">
-                        {"Download XES"}
+                    <button onclick={onvariants} disabled={self.analysis.is_none()} style="padding: 10px 20px; font-size: 16px; margin-right: 10px;">
+                        {self.tr(Message::ShowVariants)}
+                    </button>
+                    <button onclick={onprocessmap} disabled={self.imported.is_none()} style="padding: 10px 20px; font-size: 16px; margin-right: 10px;">
+                        {self.tr(Message::ShowProcessMap)}
+                    </button>
+                    <button onclick={onrepetitions} disabled={self.imported.is_none()} style="padding: 10px 20px; font-size: 16px; margin-right: 10px;">
+                        {self.tr(Message::ShowRepetitions)}
+                    </button>
+                    <button onclick={ongateways} disabled={self.imported.is_none()} style="padding: 10px 20px; font-size: 16px; margin-right: 10px;">
+                        {self.tr(Message::ShowGateways)}
+                    </button>
+                    <button onclick={ondecisions} disabled={self.imported.is_none()} style="padding: 10px 20px; font-size: 16px; margin-right: 10px;">
+                        {self.tr(Message::MineDecisions)}
+                    </button>
+                    <button onclick={onbatching} disabled={self.imported.is_none()} style="padding: 10px 20px; font-size: 16px; margin-right: 10px;">
+                        {self.tr(Message::ShowBatching)}
+                    </button>
+                    <button onclick={onprofile} disabled={self.imported.is_none()} style="padding: 10px 20px; font-size: 16px; margin-right: 10px;">
+                        {self.tr(Message::ShowActivityProfile)}
+                    </button>
+                    <label for="cohort-attribute" style="padding: 10px; font-size: 16px;">{self.tr(Message::CohortAttribute)}</label>
+                    <input type="text" id="cohort-attribute" placeholder={self.tr(Message::CohortAttributePlaceholder)} value={self.cohort_attribute.clone()} onchange={oncohortattribute} style="width: 160px; font-size: 16px; margin-right: 10px;" />
+                    <button onclick={oncohorts} disabled={self.imported.is_none() || self.cohort_attribute.is_empty()} style="padding: 10px 20px; font-size: 16px;">
+                        {self.tr(Message::CompareCohorts)}
                     </button>
                 </div>
+                { self.similar_labels_view(ctx) }
+                { self.visualization.as_deref().map(|svg| svg_node(svg, &self.config.theme)).unwrap_or_default() }
+            </div>
+        }
+    }
+}
+
+// Embeds generated SVG markup into the page
+fn svg_node(svg: &str, theme: &Theme) -> Html {
+    let container = web_sys::window()
+        .unwrap_throw()
+        .document()
+        .unwrap_throw()
+        .create_element("div")
+        .unwrap_throw();
+    container.set_inner_html(svg);
+    container
+        .set_attribute(
+            "style",
+            &format!("padding: 10px; overflow-x: auto; background-color: {};", theme.background_color()),
+        )
+        .unwrap_throw();
+    Html::VRef(container.into())
+}
+
+fn is_declare_model(file_name: &str) -> bool {
+    file_name.to_lowercase().ends_with(".decl")
+}
+
+fn is_ltl_model(file_name: &str) -> bool {
+    file_name.to_lowercase().ends_with(".ltl")
+}
+
+// One LTLf formula per line, lines starting with `#` are comments
+fn parse_formulas(content: &str) -> Result<Vec<Formula>, String> {
+    content
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty() && !line.trim().starts_with('#'))
+        .map(|(line_idx, line)| {
+            line.parse()
+                .map_err(|e| format!("line {}: {}", line_idx + 1, e))
+        })
+        .collect()
+}
+
+// Reads a file in the background and sends `loaded` with its name and its content, turned into
+// text by `decode`
+fn read_file(
+    ctx: &Context<App>,
+    file: &File,
+    loaded: fn(String, Result<String, String>) -> Msg,
+    decode: fn(&[u8]) -> Result<String, String>,
+) -> Result<Closure<dyn FnMut(web_sys::ProgressEvent)>, JsValue> {
+    let link = ctx.link().clone();
+    let file_name = file.name();
+    let reader = FileReader::new().unwrap_throw();
+    let reader_clone = reader.clone();
+
+    let onload = Closure::once(move |_event: web_sys::ProgressEvent| {
+        match reader_clone.result() {
+            Ok(result) => {
+                let bytes = js_sys::Uint8Array::new(&result).to_vec();
+                link.send_message(loaded(file_name, decode(&bytes)))
+            }
+            Err(e) => link.send_message(loaded(file_name, Err(format!(
+                "Error reading file: {:?}",
+                e
+            )))),
+        }
+    });
+
+    reader.set_onload(Some(onload.as_ref().unchecked_ref()));
+    reader.read_as_array_buffer(file)?;
+    Ok(onload)
+}
+
+// Text files, without byte order mark and with invalid UTF-8 replaced like the text reader of
+// the browser does
+fn decode_text(bytes: &[u8]) -> Result<String, String> {
+    let text = bytes.strip_prefix(b"\xef\xbb\xbf").unwrap_or(bytes);
+    Ok(String::from_utf8_lossy(text).into_owned())
+}
+
+// Logs, which may be gzip-compressed
+fn decode_log(bytes: &[u8]) -> Result<String, String> {
+    decode_xes(bytes).map_err(|e| e.to_string())
+}
+
+impl App {
+    fn tr(&self, message: Message) -> &'static str {
+        message.text(self.config.language)
+    }
+
+    // The timelines of all imported cases
+    fn timelines(&self, content: &str) -> Result<Vec<CaseTimeline>, ParseError> {
+        parse_into_event_log_with_config(None, Some(content), &self.config.import.import_config())
+            .and_then(|log| case_timelines(&log, &self.config.import.classifier))
+    }
+
+    // The offset of the local time of the business calendar, UTC without one
+    fn utc_offset_minutes(&self) -> i32 {
+        self.config
+            .calendar
+            .as_ref()
+            .map_or(0, |calendar| calendar.utc_offset_minutes)
+    }
+
+    // The timelines that duration statistics are computed from, without the incomplete cases if
+    // they are excluded or analyzed as ongoing
+    fn analyzed_timelines(&self, content: &str) -> Result<Vec<CaseTimeline>, ParseError> {
+        let timelines = self.timelines(content)?;
+        if !self.config.filters.exclude_incomplete_cases && !self.config.completeness.mark_ongoing {
+            return Ok(timelines);
+        }
+        let incomplete = case_ids(&incomplete_cases(&timelines, &self.config.completeness));
+        Ok(timelines
+            .into_iter()
+            .filter(|timeline| !incomplete.contains(&timeline.case_id))
+            .collect())
+    }
+
+    // The similar labels found by the last analysis, each with a checkbox to confirm merging it
+    // into the suggested label on the next run
+    fn similar_labels_view(&self, ctx: &Context<Self>) -> Html {
+        let similar_labels = match &self.analysis {
+            Some(result) if !result.similar_labels.is_empty() => &result.similar_labels,
+            _ => return Html::default(),
+        };
+        html! {
+            <div style="padding: 0 10px 10px;">
+                <span style="padding: 10px; font-size: 16px;">{self.tr(Message::MergeSimilarLabels)}</span>
+                { for similar_labels.iter().enumerate().map(|(idx, pair)| {
+                    let (duplicate, canonical) = (pair.duplicate.clone(), pair.canonical.clone());
+                    let confirmed = self.config.filters.merged_labels.get(&duplicate) == Some(&canonical);
+                    let onchange = ctx.link().callback(move |e: Event| {
+                        let input: HtmlInputElement = e.target_unchecked_into();
+                        Msg::ConfirmLabelMerge(duplicate.clone(), canonical.clone(), input.checked())
+                    });
+                    let id = format!("merge-label-{}", idx);
+                    html! {
+                        <>
+                            <input type="checkbox" id={id.clone()} checked={confirmed} {onchange} />
+                            <label for={id} style="padding: 10px; font-size: 16px;">
+                                {format!("'{}' → '{}' ({})", pair.duplicate, pair.canonical, pair.reason)}
+                            </label>
+                        </>
+                    }
+                }) }
             </div>
         }
     }
 }
 
+// The browser's preferred language if it is supported, English otherwise
+fn browser_language() -> Language {
+    web_sys::window()
+        .and_then(|window| window.navigator().language())
+        .and_then(|language| language.parse().ok())
+        .unwrap_or_default()
+}
+
+// Lets the browser download `content` as a file
+fn download(file_name: &str, content: &str) {
+    let window = web_sys::window().unwrap_throw();
+    let document = window.document().unwrap_throw();
+
+    let blob_options = web_sys::BlobPropertyBag::new();
+    blob_options.set_type("text/plain");
+    let blob = web_sys::Blob::new_with_str_sequence_and_options(
+        &js_sys::Array::of1(&JsValue::from_str(content)),
+        &blob_options,
+    )
+    .unwrap_throw();
+
+    let url = web_sys::Url::create_object_url_with_blob(&blob).unwrap_throw();
+
+    let anchor: HtmlAnchorElement = document
+        .create_element("a")
+        .unwrap_throw()
+        .dyn_into()
+        .unwrap_throw();
+
+    anchor.set_href(&url);
+    anchor.set_download(file_name);
+    anchor.click();
+
+    web_sys::Url::revoke_object_url(&url).unwrap_throw();
+}
+
 fn main() {
     yew::start_app::<App>();
 }
@@ -0,0 +1,152 @@
+use chrono::{DateTime, Datelike, Duration, FixedOffset, NaiveDate, NaiveTime, Utc, Weekday};
+use serde::{Deserialize, Serialize};
+
+/// Working days, hours and holidays, to measure durations in business time.
+///
+/// ```
+/// use chrono::{Duration, TimeZone, Utc};
+/// use egypt::calendar::BusinessCalendar;
+///
+/// let calendar = BusinessCalendar::default(); // Monday to Friday, 9:00 to 17:00
+/// // from Friday 16:00 to Monday 10:00
+/// let friday = Utc.with_ymd_and_hms(2024, 1, 5, 16, 0, 0).unwrap();
+/// let monday = Utc.with_ymd_and_hms(2024, 1, 8, 10, 0, 0).unwrap();
+/// assert_eq!(calendar.duration(friday, monday), Duration::hours(2));
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct BusinessCalendar {
+    pub working_days: Vec<Weekday>,
+    /// Beginning of the working hours in local time.
+    pub start: NaiveTime,
+    /// End of the working hours in local time.
+    pub end: NaiveTime,
+    /// Days without working hours, in addition to the non-working days.
+    pub holidays: Vec<NaiveDate>,
+    /// Offset of the local time from UTC in minutes, e.g. 60 for CET.
+    pub utc_offset_minutes: i32,
+}
+
+impl Default for BusinessCalendar {
+    fn default() -> Self {
+        BusinessCalendar {
+            working_days: vec![
+                Weekday::Mon,
+                Weekday::Tue,
+                Weekday::Wed,
+                Weekday::Thu,
+                Weekday::Fri,
+            ],
+            start: NaiveTime::from_hms_opt(9, 0, 0).expect("valid time"),
+            end: NaiveTime::from_hms_opt(17, 0, 0).expect("valid time"),
+            holidays: Vec::new(),
+            utc_offset_minutes: 0,
+        }
+    }
+}
+
+impl BusinessCalendar {
+    fn offset(&self) -> FixedOffset {
+        FixedOffset::east_opt(self.utc_offset_minutes * 60)
+            .unwrap_or(FixedOffset::east_opt(0).expect("valid offset"))
+    }
+
+    pub fn is_working_day(&self, date: NaiveDate) -> bool {
+        self.working_days.contains(&date.weekday()) && !self.holidays.contains(&date)
+    }
+
+    /// The working time between `from` and `to`, negative if `to` is before `from`.
+    pub fn duration(&self, from: DateTime<Utc>, to: DateTime<Utc>) -> Duration {
+        if to < from {
+            return -self.duration(to, from);
+        }
+        let offset = self.offset();
+        let (from, to) = (from.with_timezone(&offset), to.with_timezone(&offset));
+
+        let mut total = Duration::zero();
+        let mut date = from.date_naive();
+        while date <= to.date_naive() {
+            if self.is_working_day(date) {
+                let local =
+                    |time: NaiveTime| date.and_time(time).and_local_timezone(offset).single();
+                if let (Some(start), Some(end)) = (local(self.start), local(self.end)) {
+                    let (start, end) = (start.max(from), end.min(to));
+                    if start < end {
+                        total += end - start;
+                    }
+                }
+            }
+            date = date.succ_opt().expect("date in range");
+        }
+        total
+    }
+}
+
+/// Time from `from` to `to`, in business time if there is a calendar.
+pub fn elapsed(
+    calendar: Option<&BusinessCalendar>,
+    from: DateTime<Utc>,
+    to: DateTime<Utc>,
+) -> Duration {
+    match calendar {
+        Some(calendar) => calendar.duration(from, to),
+        None => to - from,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::TimeZone;
+
+    use super::*;
+
+    fn at(day: u32, hour: u32, minute: u32) -> DateTime<Utc> {
+        // January 2024 starts on a Monday
+        Utc.with_ymd_and_hms(2024, 1, day, hour, minute, 0).unwrap()
+    }
+
+    #[test]
+    fn test_duration() {
+        let calendar = BusinessCalendar::default();
+        assert_eq!(
+            calendar.duration(at(1, 10, 0), at(1, 11, 30)),
+            Duration::minutes(90)
+        );
+        // before and after the working hours
+        assert_eq!(
+            calendar.duration(at(1, 6, 0), at(1, 20, 0)),
+            Duration::hours(8)
+        );
+        // over the weekend
+        assert_eq!(
+            calendar.duration(at(5, 16, 0), at(8, 10, 0)),
+            Duration::hours(2)
+        );
+        assert_eq!(
+            calendar.duration(at(8, 10, 0), at(5, 16, 0)),
+            Duration::hours(-2)
+        );
+        assert_eq!(
+            calendar.duration(at(6, 10, 0), at(7, 12, 0)),
+            Duration::zero()
+        );
+    }
+
+    #[test]
+    fn test_holidays_and_offset() {
+        let calendar = BusinessCalendar {
+            holidays: vec![NaiveDate::from_ymd_opt(2024, 1, 2).unwrap()],
+            utc_offset_minutes: 60,
+            ..Default::default()
+        };
+        // 9:00 local time is 8:00 UTC
+        assert_eq!(
+            calendar.duration(at(1, 7, 0), at(1, 9, 0)),
+            Duration::hours(1)
+        );
+        assert_eq!(
+            calendar.duration(at(1, 0, 0), at(3, 0, 0)),
+            Duration::hours(8)
+        );
+    }
+}
@@ -0,0 +1,227 @@
+use std::collections::HashMap;
+
+use chrono::Duration;
+use process_mining::event_log::EventLog;
+
+use crate::calendar::{elapsed, BusinessCalendar};
+use crate::i18n::{Language, Message};
+use crate::parser::xes_extensions::XesExtensions;
+use crate::parser::{to_event_log, Classifier, ParseError};
+use crate::render::format_duration;
+use crate::variant_log::{CaseMetadata, VariantLog};
+use crate::ExtendedPrefixAutomaton;
+
+/// Quartiles and 90th percentile of the case durations of a cohort.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DurationPercentiles {
+    pub p25: Duration,
+    pub median: Duration,
+    pub p75: Duration,
+    pub p90: Duration,
+}
+
+impl DurationPercentiles {
+    /// Nearest-rank percentiles of `durations`, `None` if there are none.
+    pub fn of(mut durations: Vec<Duration>) -> Option<Self> {
+        if durations.is_empty() {
+            return None;
+        }
+        durations.sort();
+        let percentile =
+            |share: f64| durations[(share * (durations.len() - 1) as f64).round() as usize];
+        Some(DurationPercentiles {
+            p25: percentile(0.25),
+            median: percentile(0.5),
+            p75: percentile(0.75),
+            p90: percentile(0.9),
+        })
+    }
+}
+
+/// The cases sharing one value of a case attribute, and their metrics.
+#[derive(Debug, Clone)]
+pub struct Cohort {
+    /// The attribute value, `None` for the cases without the attribute.
+    pub value: Option<String>,
+    pub log: VariantLog,
+    /// Percentiles of the time from the first to the last event of the cases with at least
+    /// one timestamp.
+    pub durations: Option<DurationPercentiles>,
+}
+
+impl Cohort {
+    pub fn cases(&self) -> usize {
+        self.log.len()
+    }
+
+    pub fn variant_entropy(&self) -> f64 {
+        ExtendedPrefixAutomaton::from_variant_log(&self.log).variant_entropy()
+    }
+}
+
+/// Splits the cases of `log` into cohorts by the value of the case (trace) attribute
+/// `attribute`, e.g. a sales channel, ordered by number of cases, most first. Durations are
+/// measured in business time if there is a calendar.
+pub fn cohorts(
+    log: &EventLog,
+    classifier: &Classifier,
+    attribute: &str,
+    calendar: Option<&BusinessCalendar>,
+) -> Result<Vec<Cohort>, ParseError> {
+    let traces = to_event_log(log, classifier)?.traces;
+    let mut cohorts: Vec<(Option<String>, VariantLog, Vec<Duration>)> = Vec::new();
+    let mut cohort_index: HashMap<Option<String>, usize> = HashMap::new();
+
+    for (original, trace) in log.traces.iter().zip(traces) {
+        let value = original.text_attribute(attribute);
+        let idx = *cohort_index.entry(value.clone()).or_insert_with(|| {
+            cohorts.push((value, VariantLog::new(), Vec::new()));
+            cohorts.len() - 1
+        });
+        let (_, cohort_log, durations) = &mut cohorts[idx];
+
+        let timestamps = trace.events.iter().filter_map(|event| event.timestamp);
+        if let (Some(first), Some(last)) = (timestamps.clone().min(), timestamps.max()) {
+            durations.push(elapsed(calendar, first, last));
+        }
+        let activities = trace.activities().map(str::to_string).collect();
+        cohort_log.push(
+            CaseMetadata {
+                case_id: trace.case_id,
+                ongoing: false,
+                attributes: trace.attributes,
+            },
+            activities,
+        );
+    }
+
+    let mut cohorts: Vec<Cohort> = cohorts
+        .into_iter()
+        .map(|(value, log, durations)| Cohort {
+            value,
+            log,
+            durations: DurationPercentiles::of(durations),
+        })
+        .collect();
+    // cases without the attribute come last among cohorts of the same size
+    cohorts.sort_by(|a, b| {
+        b.cases()
+            .cmp(&a.cases())
+            .then_with(|| a.value.is_none().cmp(&b.value.is_none()))
+            .then_with(|| a.value.cmp(&b.value))
+    });
+    Ok(cohorts)
+}
+
+/// Renders the cohorts as one table with a row per cohort, each followed by its
+/// `top_variants` most frequent variants.
+pub fn report(
+    attribute: &str,
+    cohorts: &[Cohort],
+    top_variants: usize,
+    language: Language,
+) -> String {
+    let mut output = format!(
+        "{:<24}{:<10}{:<12}{:<12}{}\n",
+        attribute,
+        Message::Cases.text(language),
+        Message::Variants.text(language),
+        Message::VariantEntropy.text(language),
+        Message::DurationPercentiles.text(language)
+    );
+    for cohort in cohorts {
+        let durations = cohort
+            .durations
+            .map(|d| {
+                [d.p25, d.median, d.p75, d.p90]
+                    .map(format_duration)
+                    .join(" / ")
+            })
+            .unwrap_or_else(|| "-".to_string());
+        output.push_str(&format!(
+            "{:<24}{:<10}{:<12}{:<12.4}{}\n",
+            cohort.value.as_deref().unwrap_or("-"),
+            cohort.cases(),
+            cohort.log.variant_count(),
+            cohort.variant_entropy(),
+            durations
+        ));
+        for (variant, frequency) in cohort.log.top_variants(top_variants) {
+            output.push_str(&format!("    {:<8}{}\n", frequency, variant.join(", ")));
+        }
+    }
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::{parse_into_event_log_with_config, ImportConfig};
+
+    const CONTENT: &str = r#"<log>
+        <trace>
+            <string key="concept:name" value="1"/>
+            <string key="channel" value="web"/>
+            <event>
+                <string key="concept:name" value="A"/>
+                <date key="time:timestamp" value="2024-01-01T10:00:00+00:00"/>
+            </event>
+            <event>
+                <string key="concept:name" value="B"/>
+                <date key="time:timestamp" value="2024-01-01T12:00:00+00:00"/>
+            </event>
+        </trace>
+        <trace>
+            <string key="concept:name" value="2"/>
+            <string key="channel" value="web"/>
+            <event>
+                <string key="concept:name" value="A"/>
+                <date key="time:timestamp" value="2024-01-02T10:00:00+00:00"/>
+            </event>
+            <event>
+                <string key="concept:name" value="C"/>
+                <date key="time:timestamp" value="2024-01-03T10:00:00+00:00"/>
+            </event>
+        </trace>
+        <trace>
+            <string key="concept:name" value="3"/>
+            <string key="channel" value="phone"/>
+            <event>
+                <string key="concept:name" value="A"/>
+                <date key="time:timestamp" value="2024-01-02T10:00:00+00:00"/>
+            </event>
+        </trace>
+        <trace>
+            <string key="concept:name" value="4"/>
+            <event>
+                <string key="concept:name" value="B"/>
+                <date key="time:timestamp" value="2024-01-02T10:00:00+00:00"/>
+            </event>
+        </trace>
+    </log>"#;
+
+    #[test]
+    fn test_cohorts() {
+        let log = parse_into_event_log_with_config(None, Some(CONTENT), &ImportConfig::default())
+            .unwrap();
+        let cohorts = cohorts(&log, &Classifier::default(), "channel", None).unwrap();
+
+        let values: Vec<_> = cohorts
+            .iter()
+            .map(|cohort| cohort.value.as_deref())
+            .collect();
+        assert_eq!(values, [Some("web"), Some("phone"), None]);
+        let web = &cohorts[0];
+        assert_eq!(web.cases(), 2);
+        assert_eq!(web.log.variant_count(), 2);
+        assert_eq!(web.log.cases()[1].case_id, "2");
+        let durations = web.durations.unwrap();
+        assert_eq!(durations.p25, Duration::hours(2));
+        assert_eq!(durations.p90, Duration::days(1));
+        assert_eq!(cohorts[1].durations.unwrap().median, Duration::zero());
+
+        let report = report("channel", &cohorts, 1, Language::English);
+        assert!(report.starts_with("channel"));
+        assert!(report.contains("\n-    "));
+    }
+}
@@ -0,0 +1,173 @@
+use std::collections::{BTreeMap, HashSet};
+
+use chrono::Duration;
+use serde::{Deserialize, Serialize};
+
+use crate::timeline::CaseTimeline;
+
+/// When a case counts as likely incomplete, e.g. because it was still running when the log
+/// was extracted or because it was abandoned.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct CompletenessSettings {
+    /// Minimum share of the cases an activity has to end for it to be a typical end activity.
+    pub min_end_share: f64,
+    /// Cases whose last event is more than this many days before the last event of the log,
+    /// not checked if missing.
+    pub max_idle_days: Option<i64>,
+    /// Keep the flagged cases as ongoing cases (see
+    /// [`crate::variant_log::CaseMetadata::ongoing`]): they count for the prefix automaton and
+    /// the activities that follow each other, but not for the end activities and durations.
+    /// Ignored if the cases are excluded.
+    pub mark_ongoing: bool,
+}
+
+impl Default for CompletenessSettings {
+    fn default() -> Self {
+        CompletenessSettings {
+            min_end_share: 0.05,
+            max_idle_days: None,
+            mark_ongoing: false,
+        }
+    }
+}
+
+/// Why a case looks incomplete.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum IncompleteReason {
+    /// The case ends with an activity that rarely ends cases.
+    AtypicalEnd(String),
+    /// The last event of the case is this long before the last event of the log.
+    Inactive(Duration),
+}
+
+/// A case that looks incomplete, with every heuristic that flagged it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IncompleteCase {
+    pub case_id: String,
+    pub reasons: Vec<IncompleteReason>,
+}
+
+fn last_activity(timeline: &CaseTimeline) -> Option<&str> {
+    timeline
+        .instances
+        .last()
+        .map(|instance| instance.activity.as_str())
+}
+
+/// The number of cases that end with each activity.
+pub fn end_activities(timelines: &[CaseTimeline]) -> BTreeMap<String, usize> {
+    let mut ends = BTreeMap::new();
+    for activity in timelines.iter().filter_map(last_activity) {
+        *ends.entry(activity.to_string()).or_default() += 1;
+    }
+    ends
+}
+
+/// Flags the cases that end with an atypical end activity or, if `max_idle_days` is set, have
+/// been inactive for too long before the end of the log. Cases without events are never
+/// flagged.
+pub fn incomplete_cases(
+    timelines: &[CaseTimeline],
+    settings: &CompletenessSettings,
+) -> Vec<IncompleteCase> {
+    let ends = end_activities(timelines);
+    let ended_cases: usize = ends.values().sum();
+    let log_end = timelines
+        .iter()
+        .filter_map(|timeline| timeline.span())
+        .map(|(_, end)| end)
+        .max();
+    let max_idle = settings.max_idle_days.map(Duration::days);
+
+    let mut incomplete = Vec::new();
+    for timeline in timelines {
+        let mut reasons = Vec::new();
+        if let Some(activity) = last_activity(timeline) {
+            if (ends[activity] as f64) < settings.min_end_share * ended_cases as f64 {
+                reasons.push(IncompleteReason::AtypicalEnd(activity.to_string()));
+            }
+        }
+        if let (Some(max_idle), Some(log_end), Some((_, end))) =
+            (max_idle, log_end, timeline.span())
+        {
+            if log_end - end > max_idle {
+                reasons.push(IncompleteReason::Inactive(log_end - end));
+            }
+        }
+        if !reasons.is_empty() {
+            incomplete.push(IncompleteCase {
+                case_id: timeline.case_id.clone(),
+                reasons,
+            });
+        }
+    }
+    incomplete
+}
+
+/// The ids of the flagged cases, e.g. to remove them with
+/// [`crate::variant_log::VariantLog::without_cases`].
+pub fn case_ids(incomplete: &[IncompleteCase]) -> HashSet<String> {
+    incomplete.iter().map(|case| case.case_id.clone()).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{TimeZone, Utc};
+
+    use super::*;
+    use crate::timeline::TestInstance;
+
+    // completed `day` days after 1 January 2024
+    fn completed(activity: &str, day: i64) -> TestInstance<'_> {
+        let date = Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap() + Duration::days(day);
+        (activity, None, Some(date), None)
+    }
+
+    fn timelines() -> Vec<CaseTimeline> {
+        let mut timelines: Vec<CaseTimeline> = (0..29)
+            .map(|idx| {
+                let instances = [completed("Order", idx), completed("Ship", idx + 2)];
+                CaseTimeline::of(&idx.to_string(), &instances)
+            })
+            .collect();
+        // stopped after the order, long before the log ends on day 30
+        timelines.push(CaseTimeline::of("stuck", &[completed("Order", 0)]));
+        timelines.push(CaseTimeline::of("empty", &[]));
+        timelines
+    }
+
+    #[test]
+    fn test_atypical_end() {
+        assert_eq!(
+            end_activities(&timelines()),
+            BTreeMap::from([("Order".to_string(), 1), ("Ship".to_string(), 29)])
+        );
+        let incomplete = incomplete_cases(&timelines(), &CompletenessSettings::default());
+        assert_eq!(
+            incomplete,
+            vec![IncompleteCase {
+                case_id: "stuck".to_string(),
+                reasons: vec![IncompleteReason::AtypicalEnd("Order".to_string())],
+            }]
+        );
+    }
+
+    #[test]
+    fn test_inactive() {
+        let settings = CompletenessSettings {
+            min_end_share: 0.0,
+            max_idle_days: Some(26),
+            ..Default::default()
+        };
+        let incomplete = incomplete_cases(&timelines(), &settings);
+        assert_eq!(
+            case_ids(&incomplete),
+            HashSet::from(["0".to_string(), "1".to_string(), "stuck".to_string()])
+        );
+        assert_eq!(
+            incomplete[2].reasons,
+            vec![IncompleteReason::Inactive(Duration::days(30))]
+        );
+    }
+}
@@ -0,0 +1,427 @@
+use std::collections::{BTreeMap, HashSet};
+
+use serde::Serialize;
+
+use crate::glossary::{self, Metric};
+use crate::i18n::{Language, Message};
+use crate::metrics::{LogMetric, LogView, MetricValue};
+use crate::variant_log::VariantLog;
+use crate::{ExtendedPrefixAutomaton, PartitionStrategy};
+
+/// Number of phrases of the Lempel-Ziv (1976) parsing of `sequence`: it is read from left to
+/// right and a new phrase starts as soon as the current one can no longer be copied from
+/// earlier in the sequence. Repetitive sequences need few phrases, irregular ones many.
+///
+/// ```
+/// use egypt::complexity::lempel_ziv_complexity;
+///
+/// // 0 · 001 · 10 · 100 · 1000 · 101
+/// let sequence: Vec<char> = "0001101001000101".chars().collect();
+/// assert_eq!(lempel_ziv_complexity(&sequence), 6);
+/// ```
+pub fn lempel_ziv_complexity<T: PartialEq>(sequence: &[T]) -> usize {
+    let n = sequence.len();
+    if n <= 1 {
+        return n;
+    }
+    // Kaspar and Schuster's algorithm: `phrase_start` is where the current phrase starts,
+    // `copy_start` where its copy is searched and `length` the length of the current match
+    let (mut phrases, mut phrase_start, mut copy_start) = (1, 1, 0);
+    let (mut length, mut max_length) = (1, 1);
+    loop {
+        if sequence[copy_start + length - 1] == sequence[phrase_start + length - 1] {
+            length += 1;
+            if phrase_start + length > n {
+                phrases += 1;
+                break;
+            }
+        } else {
+            max_length = max_length.max(length);
+            copy_start += 1;
+            if copy_start == phrase_start {
+                phrases += 1;
+                phrase_start += max_length;
+                if phrase_start + 1 > n {
+                    break;
+                }
+                copy_start = 0;
+                max_length = 1;
+            }
+            length = 1;
+        }
+    }
+    phrases
+}
+
+/// Lempel-Ziv complexity of `sequence` scaled by `log_k(n) / n`, with `n` its length and `k`
+/// the number of distinct symbols (at least 2). Close to 1 for random sequences, close to 0 for
+/// very regular ones, so sequences of different lengths can be compared.
+pub fn normalized_lempel_ziv_complexity<T: PartialEq>(sequence: &[T]) -> Option<f64> {
+    if sequence.is_empty() {
+        return None;
+    }
+    let mut symbols: Vec<&T> = Vec::new();
+    for symbol in sequence {
+        if !symbols.contains(&symbol) {
+            symbols.push(symbol);
+        }
+    }
+    let n = sequence.len() as f64;
+    let base = symbols.len().max(2) as f64;
+    Some(lempel_ziv_complexity(sequence) as f64 * n.log(base) / n)
+}
+
+/// The cases of `log` in order as one sequence, with `None` after every case, so that
+/// compression can't merge the end of one case with the start of the next.
+pub fn log_sequence(log: &VariantLog) -> Vec<Option<&str>> {
+    log.traces()
+        .flat_map(|trace| {
+            trace
+                .iter()
+                .map(|activity| Some(activity.as_str()))
+                .chain([None])
+        })
+        .collect()
+}
+
+/// Normalized compression distance between two logs, with the Lempel-Ziv complexity of
+/// their [`log_sequence`]s as the compressed size:
+/// `(C(ab) - min(C(a), C(b))) / max(C(a), C(b))`.
+///
+/// About 0 if one log adds nothing new to the other, about 1 if they have nothing in common.
+/// `None` if both logs are empty.
+pub fn normalized_compression_distance(a: &VariantLog, b: &VariantLog) -> Option<f64> {
+    let a = log_sequence(a);
+    let b = log_sequence(b);
+    let (size_a, size_b) = (lempel_ziv_complexity(&a), lempel_ziv_complexity(&b));
+    let joint = lempel_ziv_complexity(&[a, b].concat());
+    let max = size_a.max(size_b);
+    (max > 0).then(|| joint.saturating_sub(size_a.min(size_b)) as f64 / max as f64)
+}
+
+/// Mean normalized Lempel-Ziv complexity of the distinct variants of `log`, weighted by their
+/// frequency: how irregular the single cases are, regardless of how many variants there are.
+pub fn mean_variant_complexity(log: &VariantLog) -> Option<f64> {
+    let mut total = 0.0;
+    let mut cases = 0;
+    for (variant, frequency) in log.variants() {
+        if let Some(complexity) = normalized_lempel_ziv_complexity(variant) {
+            total += frequency as f64 * complexity;
+            cases += frequency;
+        }
+    }
+    (cases > 0).then(|| total / cases as f64)
+}
+
+/// The normalized Lempel-Ziv complexity of the [`log_sequence`]. Not one of the built-in
+/// metrics; register it with [`crate::metrics::MetricRegistry::register`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct LempelZivComplexity;
+
+impl LogMetric for LempelZivComplexity {
+    fn id(&self) -> &str {
+        "lempel_ziv_complexity"
+    }
+
+    fn label(&self, language: Language) -> String {
+        Message::LempelZivComplexity.text(language).to_string()
+    }
+
+    fn glossary(&self) -> Option<&Metric> {
+        glossary::metric("lempel_ziv_complexity")
+    }
+
+    fn compute(&self, view: &LogView) -> MetricValue {
+        normalized_lempel_ziv_complexity(&log_sequence(view.log()))
+            .map_or(MetricValue::Undefined, MetricValue::Number)
+    }
+}
+
+/// Mean overlap of the directly-follows pairs of every two cases of `log`, with the overlap of
+/// two cases being `|F1 ∩ F2| / |F1 ∪ F2|` of their sets of directly-follows pairs (1 if both
+/// have none). `None` for fewer than two cases.
+pub fn affinity(log: &VariantLog) -> Option<f64> {
+    let variants: Vec<(HashSet<(&str, &str)>, usize)> = log
+        .variants()
+        .map(|(variant, frequency)| {
+            let pairs = variant
+                .windows(2)
+                .map(|pair| (pair[0].as_str(), pair[1].as_str()))
+                .collect();
+            (pairs, frequency)
+        })
+        .collect();
+    let cases = log.len();
+    if cases < 2 {
+        return None;
+    }
+
+    let overlap = |a: &HashSet<(&str, &str)>, b: &HashSet<(&str, &str)>| {
+        let union = a.union(b).count();
+        if union == 0 {
+            1.0
+        } else {
+            a.intersection(b).count() as f64 / union as f64
+        }
+    };
+    // cases of the same variant overlap completely
+    let mut total: f64 = variants
+        .iter()
+        .map(|(_, frequency)| (frequency * (frequency - 1) / 2) as f64)
+        .sum();
+    for (idx, (pairs, frequency)) in variants.iter().enumerate() {
+        for (other_pairs, other_frequency) in &variants[idx + 1..] {
+            total += (frequency * other_frequency) as f64 * overlap(pairs, other_pairs);
+        }
+    }
+    Some(total / (cases * (cases - 1) / 2) as f64)
+}
+
+/// How far the directly-follows counts between the activities of `log` are from being spread
+/// evenly over all activity pairs: the distance of the count matrix from the uniform matrix,
+/// divided by the largest possible distance (all transitions between one pair). 0 if every
+/// activity is equally likely to follow every other one, 1 if the log always repeats a single
+/// transition. `None` without directly-follows pairs.
+pub fn deviation_from_random(log: &VariantLog) -> Option<f64> {
+    // ordered, so that the squared deviations are always summed up in the same order
+    let mut counts: BTreeMap<(&str, &str), usize> = BTreeMap::new();
+    for (variant, frequency) in log.variants() {
+        for pair in variant.windows(2) {
+            *counts.entry((&pair[0], &pair[1])).or_default() += frequency;
+        }
+    }
+    let transitions = counts.values().sum::<usize>() as f64;
+    if transitions == 0.0 {
+        return None;
+    }
+    let cells = (log.activities().len() * log.activities().len()) as f64;
+    if cells <= 1.0 {
+        return Some(0.0);
+    }
+    let mean = transitions / cells;
+    let observed = counts
+        .values()
+        .map(|&count| (count as f64 - mean).powi(2))
+        .sum::<f64>()
+        + (cells - counts.len() as f64) * mean * mean;
+    let maximum = (transitions - mean).powi(2) + (cells - 1.0) * mean * mean;
+    Some((observed / maximum).sqrt())
+}
+
+/// The complexity measures of a log from the literature, computed in one call, e.g. to compare
+/// logs or to track how filtering changes a log.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ComplexityProfile {
+    /// Number of events.
+    pub magnitude: usize,
+    /// Number of cases.
+    pub support: usize,
+    /// Number of distinct activities.
+    pub variety: usize,
+    /// Mean number of distinct activities per case.
+    pub level_of_detail: f64,
+    pub average_trace_length: f64,
+    pub variants: usize,
+    pub affinity: Option<f64>,
+    pub deviation_from_random: Option<f64>,
+    pub lempel_ziv_complexity: Option<f64>,
+    pub partition_strategy: PartitionStrategy,
+    pub variant_entropy: f64,
+    pub normalized_variant_entropy: f64,
+}
+
+impl ComplexityProfile {
+    /// Computes the profile of `log`, with the variant entropies over the partitions of
+    /// `strategy`.
+    pub fn of(log: &VariantLog, strategy: PartitionStrategy) -> Self {
+        let mut magnitude = 0;
+        let mut distinct_activities = 0;
+        for (variant, frequency) in log.variants() {
+            magnitude += frequency * variant.len();
+            distinct_activities += frequency * variant.iter().collect::<HashSet<_>>().len();
+        }
+        let support = log.len();
+        let per_case = |total: usize| {
+            if support == 0 {
+                0.0
+            } else {
+                total as f64 / support as f64
+            }
+        };
+        let epa = ExtendedPrefixAutomaton::from_variant_log_with_partition_strategy(log, strategy);
+
+        ComplexityProfile {
+            magnitude,
+            support,
+            variety: log.activities().len(),
+            level_of_detail: per_case(distinct_activities),
+            average_trace_length: per_case(magnitude),
+            variants: log.variant_count(),
+            affinity: affinity(log),
+            deviation_from_random: deviation_from_random(log),
+            lempel_ziv_complexity: normalized_lempel_ziv_complexity(&log_sequence(log)),
+            partition_strategy: strategy,
+            variant_entropy: epa.variant_entropy(),
+            normalized_variant_entropy: epa.normalized_variant_entropy(),
+        }
+    }
+
+    /// The measures with their labels, in report order.
+    pub fn metrics(&self, language: Language) -> Vec<(String, String)> {
+        let optional = |value: Option<f64>| value.map_or("-".to_string(), |v| format!("{:.4}", v));
+        let entropy =
+            |message: Message| format!("{} ({})", message.text(language), self.partition_strategy);
+        vec![
+            (
+                Message::Magnitude.text(language).to_string(),
+                self.magnitude.to_string(),
+            ),
+            (
+                Message::Cases.text(language).to_string(),
+                self.support.to_string(),
+            ),
+            (
+                Message::Variety.text(language).to_string(),
+                self.variety.to_string(),
+            ),
+            (
+                Message::LevelOfDetail.text(language).to_string(),
+                format!("{:.4}", self.level_of_detail),
+            ),
+            (
+                Message::AverageTraceLength.text(language).to_string(),
+                format!("{:.4}", self.average_trace_length),
+            ),
+            (
+                Message::Variants.text(language).to_string(),
+                self.variants.to_string(),
+            ),
+            (
+                Message::Affinity.text(language).to_string(),
+                optional(self.affinity),
+            ),
+            (
+                Message::DeviationFromRandom.text(language).to_string(),
+                optional(self.deviation_from_random),
+            ),
+            (
+                Message::LempelZivComplexity.text(language).to_string(),
+                optional(self.lempel_ziv_complexity),
+            ),
+            (
+                entropy(Message::VariantEntropy),
+                format!("{:.4}", self.variant_entropy),
+            ),
+            (
+                entropy(Message::NormalizedVariantEntropy),
+                format!("{:.4}", self.normalized_variant_entropy),
+            ),
+        ]
+    }
+
+    /// Renders the profile with one line per measure.
+    pub fn report(&self, language: Language) -> String {
+        self.metrics(language)
+            .into_iter()
+            .map(|(label, value)| format!("{:<48}{}\n", format!("{}:", label), value))
+            .collect()
+    }
+
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).expect("complexity profiles are always valid JSON")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::metrics::MetricRegistry;
+
+    fn log(traces: &[&str]) -> VariantLog {
+        VariantLog::from_traces(
+            traces
+                .iter()
+                .map(|trace| trace.chars().map(String::from).collect())
+                .collect(),
+        )
+    }
+
+    #[test]
+    fn test_lempel_ziv_complexity() {
+        assert_eq!(lempel_ziv_complexity::<char>(&[]), 0);
+        assert_eq!(lempel_ziv_complexity(&['a']), 1);
+        assert_eq!(lempel_ziv_complexity(&['a'; 10]), 2);
+        let periodic: Vec<char> = "abababababab".chars().collect();
+        assert_eq!(lempel_ziv_complexity(&periodic), 3);
+
+        let irregular: Vec<char> = "abbabaaabbbaabab".chars().collect();
+        assert!(
+            normalized_lempel_ziv_complexity(&irregular).unwrap()
+                > normalized_lempel_ziv_complexity(&periodic).unwrap()
+        );
+        assert_eq!(normalized_lempel_ziv_complexity::<char>(&[]), None);
+    }
+
+    #[test]
+    fn test_compression_distance() {
+        let a = log(&["abc", "abc", "abd"]);
+        let b = log(&["xyz", "xzy"]);
+        assert_eq!(log_sequence(&log(&["ab"])), [Some("a"), Some("b"), None]);
+
+        let same = normalized_compression_distance(&a, &a).unwrap();
+        let different = normalized_compression_distance(&a, &b).unwrap();
+        assert!(same < different);
+        assert_eq!(
+            normalized_compression_distance(&VariantLog::new(), &VariantLog::new()),
+            None
+        );
+
+        assert!(mean_variant_complexity(&a).unwrap() > 0.0);
+        assert_eq!(mean_variant_complexity(&VariantLog::new()), None);
+    }
+
+    #[test]
+    fn test_affinity_and_deviation_from_random() {
+        // a-b-c shares one of three pairs with a-b-d
+        let affinity = affinity(&log(&["abc", "abc", "abd"])).unwrap();
+        assert!((affinity - (1.0 + 2.0 / 3.0) / 3.0).abs() < 1e-9);
+        assert_eq!(super::affinity(&log(&["abc"])), None);
+
+        assert_eq!(deviation_from_random(&log(&["ab", "ab"])), Some(1.0));
+        // every pair of a and b once
+        let uniform = deviation_from_random(&log(&["aabba"])).unwrap();
+        assert!(uniform.abs() < 1e-9);
+        assert_eq!(deviation_from_random(&log(&["a", "b"])), None);
+    }
+
+    #[test]
+    fn test_profile() {
+        let profile = ComplexityProfile::of(&log(&["abca", "ab"]), PartitionStrategy::Branching);
+        assert_eq!(profile.magnitude, 6);
+        assert_eq!(profile.support, 2);
+        assert_eq!(profile.variety, 3);
+        assert_eq!(profile.level_of_detail, 2.5);
+        assert_eq!(profile.average_trace_length, 3.0);
+        assert_eq!(profile.variants, 2);
+
+        let report = profile.report(Language::English);
+        assert_eq!(report.lines().count(), 11);
+        assert!(report.contains("Variant Entropy (branching):"));
+        let json: serde_json::Value = serde_json::from_str(&profile.to_json()).unwrap();
+        assert_eq!(json["partition_strategy"], "branching");
+
+        let empty = ComplexityProfile::of(&VariantLog::new(), PartitionStrategy::Branching);
+        assert_eq!(empty.affinity, None);
+        assert_eq!(empty.level_of_detail, 0.0);
+    }
+
+    #[test]
+    fn test_metric() {
+        let mut registry = MetricRegistry::empty();
+        registry.register(Box::new(LempelZivComplexity));
+        let results = registry.compute(&log(&["abc", "acb"]), Language::English);
+        assert_eq!(results[0].id, "lempel_ziv_complexity");
+        assert!(matches!(results[0].value, MetricValue::Number(value) if value > 0.0));
+        assert!(registry.metrics().next().unwrap().glossary().is_some());
+    }
+}
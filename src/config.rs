@@ -0,0 +1,667 @@
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+use std::fmt;
+use std::path::Path;
+
+use chrono::Duration;
+use serde::{Deserialize, Serialize};
+
+use crate::arrivals::ArrivalSettings;
+use crate::calendar::BusinessCalendar;
+use crate::completeness::CompletenessSettings;
+use crate::dependency_types::significance::Significance;
+use crate::grouping::ActivityGrouping;
+use crate::i18n::Language;
+use crate::long_distance::LongDistanceSettings;
+use crate::metrics::Bootstrap;
+use crate::parser::abstraction::EventAbstraction;
+use crate::parser::time_window::TimeWindow;
+use crate::parser::timezone::TimezoneOptions;
+use crate::parser::{CaseNotion, Classifier, ImportConfig, LifecyclePolicy};
+use crate::random::DEFAULT_SEED;
+use crate::recommendation::ThresholdSweep;
+use crate::render::Theme;
+use crate::repair::RepairSettings;
+use crate::resource_dependencies::ResourceDependencySettings;
+use crate::sampling::SamplingSettings;
+use crate::simulation::SimulationSettings;
+use crate::sla::SlaSettings;
+use crate::taxonomy::ActivityTaxonomy;
+use crate::temporal_constraints::TemporalConstraintSettings;
+use crate::trace_length::TraceLengthSettings;
+use crate::variant_log::VariantLog;
+use crate::weights::WeightSettings;
+use crate::{MatrixOptions, PartitionStrategy};
+
+/// All settings of an analysis, so it can be saved, reviewed and repeated.
+///
+/// Profiles are stored as TOML or JSON; every field is optional and falls back to its default,
+/// e.g.
+///
+/// ```toml
+/// seed = 7
+/// partition_strategy = "prefix-length"
+///
+/// [import]
+/// classifier = { keys = ["Activity"] }
+/// case_attributes = ["order", "item"]
+/// timestamp_key = "EventTime"
+/// resource_key = "User"
+/// lifecycle = { custom = ["start", "complete"] }
+/// session_gap_minutes = 30
+///
+/// [import.time_window]
+/// start = "2024-01-01T00:00:00Z"
+/// end = "2024-04-01T00:00:00Z"
+/// mode = "cases"
+///
+/// [import.timezone]
+/// naive_offset_minutes = 60
+///
+/// [import.abstraction]
+/// collapse_repetitions = true
+/// patterns = [{ activities = ["Type", "Submit"], activity = "Fill form" }]
+///
+/// [thresholds]
+/// temporal = 0.9
+/// min_support = 5
+///
+/// [significance]
+/// test = "fisher-exact"
+/// alpha = 0.01
+///
+/// [bootstrap]
+/// resamples = 500
+///
+/// [sampling]
+/// min_cases = 500000
+/// sample_size = 50000
+///
+/// [long_distance]
+/// min_confidence = 0.9
+///
+/// [repair]
+/// min_confidence = 0.95
+///
+/// [resource_dependencies]
+/// min_cases = 5
+///
+/// [threshold_sweep]
+/// fitness_weight = 0.8
+///
+/// [calendar]
+/// working_days = ["Mon", "Tue", "Wed", "Thu"]
+/// start = "08:00:00"
+/// holidays = ["2024-12-25"]
+///
+/// [temporal_constraints]
+/// percentile = 0.9
+///
+/// [sla]
+/// period = "week"
+/// slas = [
+///     { name = "Approval", from = "Create Order", to = "Approve Order", max_minutes = 480 },
+///     { name = "Case", max_minutes = 4320 },
+/// ]
+///
+/// [arrivals]
+/// period = "week"
+///
+/// [simulation]
+/// cases = 500
+/// arrivals = "poisson"
+///
+/// [simulation.what_if]
+/// arrival_rate_scaling = 1.2
+/// activity_duration_scaling = { "Check Invoice" = 0.5 }
+/// removed_activities = ["Send reminder"]
+///
+/// [completeness]
+/// max_idle_days = 30
+///
+/// [weights]
+/// attribute = "order_value"
+/// top = 5
+///
+/// [trace_length]
+/// method = "percentile"
+/// upper_percentile = 0.995
+/// exclude_outliers = true
+///
+/// [grouping]
+/// strategy = { prefix = ":" }
+/// collapsed = false
+///
+/// [filters]
+/// excluded_activities = ["Send reminder"]
+/// focus_activities = ["Create Order", "Approve Order", "Ship Order"]
+/// exclude_incomplete_cases = true
+/// merged_labels = { "register order" = "Register Order" }
+///
+/// [filters.taxonomy]
+/// level = 0
+/// parents = { "Create Purchase Order" = "Ordering" }
+///
+/// [theme]
+/// palette = "color-blind-safe"
+/// ```
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct AnalysisConfig {
+    /// Seed of all stochastic analyses, see [`crate::random`].
+    pub seed: u64,
+    /// How the prefix automaton is partitioned for the variant entropy.
+    pub partition_strategy: PartitionStrategy,
+    pub import: ImportSettings,
+    pub thresholds: Thresholds,
+    /// Significance test of existential dependencies, off if missing.
+    pub significance: Option<Significance>,
+    /// Bootstrap confidence intervals of the log metrics, off if missing.
+    pub bootstrap: Option<Bootstrap>,
+    /// Computing the matrix of large logs on a sample of the cases, always exact if missing.
+    pub sampling: Option<SamplingSettings>,
+    /// Mining of long-distance dependencies between choices, off if missing.
+    pub long_distance: Option<LongDistanceSettings>,
+    /// Repair suggestions for the cases violating high-confidence dependencies, off if missing.
+    pub repair: Option<RepairSettings>,
+    /// Dependencies that only hold with the same or with different resources, off if missing.
+    pub resource_dependencies: Option<ResourceDependencySettings>,
+    /// Recommendation of a directly-follows graph threshold, off if missing.
+    pub threshold_sweep: Option<ThresholdSweep>,
+    /// Working hours to measure durations in, wall-clock time if missing.
+    pub calendar: Option<BusinessCalendar>,
+    /// Mining of the expected times between activities, off if missing.
+    pub temporal_constraints: Option<TemporalConstraintSettings>,
+    /// Service level agreements whose breaches are reported, see [`crate::sla`].
+    pub sla: SlaSettings,
+    /// What-if simulation of the cases, off if missing, see [`crate::simulation`].
+    pub simulation: Option<SimulationSettings>,
+    /// Inter-arrival times and arrival rates of the cases, off if missing.
+    pub arrivals: Option<ArrivalSettings>,
+    /// Heuristics that flag likely incomplete cases, see [`crate::completeness`].
+    pub completeness: CompletenessSettings,
+    /// Trace length histogram and outliers, off if missing.
+    pub trace_length: Option<TraceLengthSettings>,
+    /// Variants, dependencies and durations weighted by a case attribute, off if missing.
+    pub weights: Option<WeightSettings>,
+    /// Grouping of the activities of the process map, one node per activity if missing.
+    pub grouping: Option<ActivityGrouping>,
+    pub filters: Filters,
+    pub theme: Theme,
+    pub language: Language,
+}
+
+impl Default for AnalysisConfig {
+    fn default() -> Self {
+        AnalysisConfig {
+            seed: DEFAULT_SEED,
+            partition_strategy: PartitionStrategy::default(),
+            import: ImportSettings::default(),
+            thresholds: Thresholds::default(),
+            significance: None,
+            bootstrap: None,
+            sampling: None,
+            long_distance: None,
+            repair: None,
+            resource_dependencies: None,
+            threshold_sweep: None,
+            calendar: None,
+            temporal_constraints: None,
+            sla: SlaSettings::default(),
+            simulation: None,
+            arrivals: None,
+            completeness: CompletenessSettings::default(),
+            trace_length: None,
+            weights: None,
+            grouping: None,
+            filters: Filters::default(),
+            theme: Theme::default(),
+            language: Language::default(),
+        }
+    }
+}
+
+/// How the log is turned into cases and activities, see [`ImportConfig`].
+#[derive(Debug, Clone, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ImportSettings {
+    pub classifier: Classifier,
+    /// Attribute keys that define the cases, empty to keep the log's traces.
+    pub case_attributes: Vec<String>,
+    /// Attribute key of the event timestamps, `time:timestamp` if missing.
+    pub timestamp_key: Option<String>,
+    /// Attribute key of the event resources, `org:resource` if missing.
+    pub resource_key: Option<String>,
+    /// Which events are analyzed by their lifecycle transition.
+    pub lifecycle: LifecyclePolicy,
+    /// Start a new case after this many minutes without events (grouped by `case_attributes`).
+    pub case_gap_minutes: Option<i64>,
+    /// Split every case into sessions at gaps of more than this many minutes between events,
+    /// e.g. for logs with one long case per user.
+    pub session_gap_minutes: Option<i64>,
+    /// Analyze only the events or the cases of this period.
+    pub time_window: Option<TimeWindow>,
+    pub lenient: bool,
+    pub timezone: TimezoneOptions,
+    /// Remove exact duplicate events, e.g. from retries of the source system.
+    pub drop_duplicate_events: bool,
+    /// Merge repeated and low-level events into higher-level activities.
+    pub abstraction: EventAbstraction,
+}
+
+/// Share of traces that has to satisfy a dependency for it to hold in the matrix.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Thresholds {
+    pub temporal: f64,
+    pub existential: f64,
+    /// Number of traces an activity pair has to occur in (either activity) to be classified.
+    pub min_support: usize,
+}
+
+impl Default for Thresholds {
+    fn default() -> Self {
+        Thresholds {
+            temporal: 1.0,
+            existential: 1.0,
+            min_support: 0,
+        }
+    }
+}
+
+/// Changes to the log before it is analyzed.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Filters {
+    /// Map the activities to a level of an activity hierarchy, see [`crate::taxonomy`].
+    pub taxonomy: Option<ActivityTaxonomy>,
+    /// Activity labels confirmed to be spelling variants, each mapped to the label it is merged
+    /// into. The similar labels found by [`crate::activity_labels`] are only reported until they
+    /// are confirmed here.
+    pub merged_labels: BTreeMap<String, String>,
+    /// Activities whose events are removed.
+    pub excluded_activities: Vec<String>,
+    /// If not empty, the only activities whose events are kept, to analyze a part of a large
+    /// process.
+    pub focus_activities: Vec<String>,
+    /// Drop cases whose variant occurs less often.
+    pub min_variant_frequency: usize,
+    /// Leave the cases flagged by the completeness heuristics out of the duration and
+    /// dependency statistics.
+    pub exclude_incomplete_cases: bool,
+}
+
+impl Default for Filters {
+    fn default() -> Self {
+        Filters {
+            taxonomy: None,
+            merged_labels: BTreeMap::new(),
+            excluded_activities: Vec::new(),
+            focus_activities: Vec::new(),
+            min_variant_frequency: 1,
+            exclude_incomplete_cases: false,
+        }
+    }
+}
+
+impl ImportSettings {
+    pub fn import_config(&self) -> ImportConfig {
+        let keys = self.case_attributes.clone();
+        ImportConfig {
+            classifier: self.classifier.clone(),
+            case_notion: match self.case_gap_minutes {
+                Some(minutes) => CaseNotion::TimeGap {
+                    max_gap: Duration::minutes(minutes),
+                    keys,
+                },
+                None if keys.is_empty() => CaseNotion::Trace,
+                None => CaseNotion::Attributes(keys),
+            },
+            lifecycle: self.lifecycle.clone(),
+            timestamp_key: self.timestamp_key.clone(),
+            resource_key: self.resource_key.clone(),
+            session_gap: self.session_gap_minutes.map(Duration::minutes),
+            time_window: self.time_window,
+            lenient: self.lenient,
+            timezone: self.timezone,
+            drop_duplicate_events: self.drop_duplicate_events,
+            abstraction: self.abstraction.clone(),
+            ..Default::default()
+        }
+    }
+}
+
+impl Filters {
+    /// What [`Filters::apply`] makes of each of `activities`: its category at the taxonomy
+    /// level, or `None` if the excluded activities or the focus remove its events.
+    pub fn activity_mapping(
+        &self,
+        activities: &BTreeSet<String>,
+    ) -> BTreeMap<String, Option<String>> {
+        let excluded: HashSet<&String> = self.excluded_activities.iter().collect();
+        let focus: HashSet<&String> = self.focus_activities.iter().collect();
+        activities
+            .iter()
+            .map(|activity| {
+                let mapped = match &self.taxonomy {
+                    Some(taxonomy) => taxonomy.abstraction(activity, taxonomy.level).to_string(),
+                    None => activity.clone(),
+                };
+                let kept =
+                    !excluded.contains(&mapped) && (focus.is_empty() || focus.contains(&mapped));
+                (activity.clone(), kept.then_some(mapped))
+            })
+            .collect()
+    }
+
+    /// Maps the activities to the taxonomy level, removes the excluded activities and the ones
+    /// outside the focus, then the infrequent variants. Merging the confirmed labels is left to
+    /// the caller, which usually also wants to report the unconfirmed similar ones.
+    pub fn apply(&self, log: VariantLog) -> VariantLog {
+        let mapping = self.activity_mapping(&log.activities());
+        let renamed: HashMap<String, String> = mapping
+            .iter()
+            .filter_map(|(activity, mapped)| {
+                mapped
+                    .as_ref()
+                    .filter(|mapped| *mapped != activity)
+                    .map(|mapped| (activity.clone(), mapped.clone()))
+            })
+            .collect();
+        let excluded: HashSet<String> = mapping
+            .into_iter()
+            .filter(|(_, mapped)| mapped.is_none())
+            .map(|(activity, _)| activity)
+            .collect();
+        let log = if excluded.is_empty() {
+            log
+        } else {
+            log.remove_activities(&excluded)
+        };
+        let log = if renamed.is_empty() {
+            log
+        } else {
+            log.rename_activities(&renamed)
+        };
+        if self.min_variant_frequency > 1 {
+            log.frequent_variants(self.min_variant_frequency)
+        } else {
+            log
+        }
+    }
+}
+
+/// Error of reading or writing an [`AnalysisConfig`].
+#[derive(Debug)]
+pub enum ConfigError {
+    Io(std::io::Error),
+    Toml(toml::de::Error),
+    TomlSerialization(toml::ser::Error),
+    Json(serde_json::Error),
+    /// The file extension is neither `.toml` nor `.json`.
+    UnknownFormat(String),
+    /// A setting is out of its range, e.g. a threshold above 1.
+    InvalidValue(String),
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ConfigError::Io(e) => write!(f, "Could not read configuration: {}", e),
+            ConfigError::Toml(e) => write!(f, "Invalid TOML configuration: {}", e),
+            ConfigError::TomlSerialization(e) => {
+                write!(f, "Could not serialize configuration: {}", e)
+            }
+            ConfigError::Json(e) => write!(f, "Invalid JSON configuration: {}", e),
+            ConfigError::UnknownFormat(path) => write!(
+                f,
+                "Unknown configuration format of {}, expected .toml or .json",
+                path
+            ),
+            ConfigError::InvalidValue(message) => {
+                write!(f, "Invalid configuration: {}", message)
+            }
+        }
+    }
+}
+
+impl std::error::Error for ConfigError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            ConfigError::Io(e) => Some(e),
+            ConfigError::Toml(e) => Some(e),
+            ConfigError::TomlSerialization(e) => Some(e),
+            ConfigError::Json(e) => Some(e),
+            ConfigError::UnknownFormat(_) | ConfigError::InvalidValue(_) => None,
+        }
+    }
+}
+
+impl AnalysisConfig {
+    pub fn from_toml(content: &str) -> Result<Self, ConfigError> {
+        toml::from_str::<Self>(content)
+            .map_err(ConfigError::Toml)?
+            .validated()
+    }
+
+    pub fn from_json(content: &str) -> Result<Self, ConfigError> {
+        serde_json::from_str::<Self>(content)
+            .map_err(ConfigError::Json)?
+            .validated()
+    }
+
+    // Rejects the values the analyses can't work with, which deserializing alone lets through
+    fn validated(self) -> Result<Self, ConfigError> {
+        for (name, threshold) in [
+            ("temporal", self.thresholds.temporal),
+            ("existential", self.thresholds.existential),
+        ] {
+            if !(0.0..=1.0).contains(&threshold) {
+                return Err(ConfigError::InvalidValue(format!(
+                    "thresholds.{} is {}, expected a share between 0 and 1",
+                    name, threshold
+                )));
+            }
+        }
+        Ok(self)
+    }
+
+    pub fn to_toml(&self) -> Result<String, ConfigError> {
+        toml::to_string_pretty(self).map_err(ConfigError::TomlSerialization)
+    }
+
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).expect("configuration is always valid JSON")
+    }
+
+    /// Parses `content` as JSON or TOML, depending on the extension of `file_name`.
+    pub fn from_file_content(file_name: &str, content: &str) -> Result<Self, ConfigError> {
+        let extension = Path::new(file_name)
+            .extension()
+            .map(|extension| extension.to_string_lossy().to_lowercase());
+        match extension.as_deref() {
+            Some("toml") => Self::from_toml(content),
+            Some("json") => Self::from_json(content),
+            _ => Err(ConfigError::UnknownFormat(file_name.to_string())),
+        }
+    }
+
+    pub fn matrix_options(&self) -> MatrixOptions {
+        MatrixOptions {
+            temporal_threshold: self.thresholds.temporal,
+            existential_threshold: self.thresholds.existential,
+            significance: self.significance,
+            min_support: self.thresholds.min_support,
+        }
+    }
+
+    /// Reads a TOML or JSON profile, see [`AnalysisConfig::from_file_content`].
+    pub fn load(path: &str) -> Result<Self, ConfigError> {
+        let content = std::fs::read_to_string(path).map_err(ConfigError::Io)?;
+        Self::from_file_content(path, &content)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::grouping::GroupingStrategy;
+    use crate::parser::abstraction::ActivityPattern;
+    use crate::parser::time_window::WindowMode;
+    use crate::render::theme::Palette;
+    use crate::simulation::{ArrivalProcess, WhatIf};
+    use crate::sla::{Sla, SlaPeriod};
+    use crate::trace_length::OutlierMethod;
+    use chrono::{TimeZone, Utc};
+
+    #[test]
+    fn test_partial_toml() {
+        let config = AnalysisConfig::from_toml(
+            r#"
+            seed = 7
+            language = "de"
+
+            [import]
+            classifier = { named = "Activity" }
+            case_attributes = ["order"]
+
+            [thresholds]
+            temporal = 0.9
+
+            [theme]
+            palette = "color-blind-safe"
+            "#,
+        )
+        .unwrap();
+
+        assert_eq!(config.seed, 7);
+        assert_eq!(config.language, Language::German);
+        assert_eq!(
+            config.import.import_config().case_notion,
+            CaseNotion::Attributes(vec!["order".to_string()])
+        );
+        assert_eq!(config.import.classifier, Classifier::Named("Activity".to_string()));
+        assert_eq!(config.thresholds.temporal, 0.9);
+        assert_eq!(config.thresholds.existential, 1.0);
+        assert_eq!(config.theme.palette, Palette::ColorBlindSafe);
+        assert_eq!(config.theme.font_size, 12.0);
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let mut config = AnalysisConfig::default();
+        config.import.case_gap_minutes = Some(30);
+        config.import.session_gap_minutes = Some(45);
+        config.import.timestamp_key = Some("EventTime".to_string());
+        config.import.resource_key = Some("User".to_string());
+        config.import.time_window = Some(TimeWindow::new(
+            Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap(),
+            Utc.with_ymd_and_hms(2024, 4, 1, 0, 0, 0).unwrap(),
+            WindowMode::Cases,
+        ));
+        config.import.lifecycle = LifecyclePolicy::Custom(vec!["start".to_string()]);
+        config.import.abstraction = EventAbstraction {
+            collapse_repetitions: true,
+            patterns: vec![ActivityPattern {
+                activities: vec!["a".to_string(), "b".to_string()],
+                activity: "ab".to_string(),
+            }],
+        };
+        config.filters.excluded_activities = vec!["x".to_string()];
+        config.filters.focus_activities = vec!["a".to_string(), "b".to_string()];
+        config.filters.merged_labels = [("A".to_string(), "a".to_string())].into();
+        config.filters.taxonomy = Some(ActivityTaxonomy {
+            parents: [("a".to_string(), "b".to_string())].into(),
+            level: 1,
+        });
+        config.significance = Some(Significance::default());
+        config.bootstrap = Some(Bootstrap::default());
+        config.sampling = Some(SamplingSettings::default());
+        config.long_distance = Some(LongDistanceSettings::default());
+        config.repair = Some(RepairSettings::default());
+        config.completeness.mark_ongoing = true;
+        config.resource_dependencies = Some(ResourceDependencySettings::default());
+        config.threshold_sweep = Some(ThresholdSweep::default());
+        config.calendar = Some(BusinessCalendar::default());
+        config.temporal_constraints = Some(TemporalConstraintSettings::default());
+        config.sla = SlaSettings {
+            slas: vec![Sla {
+                name: "Case".to_string(),
+                from: Some("a".to_string()),
+                to: None,
+                max_minutes: 60,
+            }],
+            period: SlaPeriod::Day,
+            worst_cases: 3,
+        };
+        config.trace_length = Some(TraceLengthSettings {
+            method: OutlierMethod::Percentile,
+            exclude_outliers: true,
+            ..Default::default()
+        });
+        config.weights = Some(WeightSettings {
+            attribute: "value".to_string(),
+            missing_weight: 1.0,
+            top: 3,
+        });
+        config.arrivals = Some(ArrivalSettings {
+            period: SlaPeriod::Week,
+        });
+        config.simulation = Some(SimulationSettings {
+            cases: 10,
+            arrivals: ArrivalProcess::Poisson,
+            what_if: WhatIf {
+                activity_duration_scaling: [("a".to_string(), 0.5)].into(),
+                removed_activities: vec!["b".to_string()],
+                ..Default::default()
+            },
+        });
+        config.partition_strategy = PartitionStrategy::RepetitionCount;
+        config.grouping = Some(ActivityGrouping {
+            strategy: GroupingStrategy::Prefix(":".to_string()),
+            collapsed: false,
+        });
+
+        let toml = config.to_toml().unwrap();
+        assert_eq!(AnalysisConfig::from_file_content("a.toml", &toml).unwrap(), config);
+        let json = config.to_json();
+        assert_eq!(AnalysisConfig::from_file_content("a.JSON", &json).unwrap(), config);
+        assert!(matches!(
+            AnalysisConfig::from_file_content("a.yaml", ""),
+            Err(ConfigError::UnknownFormat(_))
+        ));
+        assert!(AnalysisConfig::from_toml("unknown = 1").is_err());
+    }
+
+    #[test]
+    fn test_invalid_thresholds() {
+        assert!(matches!(
+            AnalysisConfig::from_toml("[thresholds]\nexistential = 1.5"),
+            Err(ConfigError::InvalidValue(_))
+        ));
+        assert!(matches!(
+            AnalysisConfig::from_toml("[thresholds]\ntemporal = nan"),
+            Err(ConfigError::InvalidValue(_))
+        ));
+        assert!(matches!(
+            AnalysisConfig::from_json(r#"{"thresholds": {"temporal": -0.1}}"#),
+            Err(ConfigError::InvalidValue(_))
+        ));
+        assert!(AnalysisConfig::from_toml("[thresholds]\ntemporal = 0.0").is_ok());
+    }
+
+    #[test]
+    fn test_focus_activities() {
+        let trace = |activities: &str| activities.chars().map(|a| a.to_string()).collect();
+        let log = VariantLog::from_traces(vec![trace("ASBSC"), trace("ASC")]);
+        let filters = Filters {
+            excluded_activities: vec!["C".to_string()],
+            focus_activities: vec!["A".to_string(), "B".to_string(), "C".to_string()],
+            ..Default::default()
+        };
+        let mapping = filters.activity_mapping(&log.activities());
+        assert_eq!(mapping["B"].as_deref(), Some("B"));
+        assert_eq!(mapping["C"], None);
+        assert_eq!(mapping["S"], None);
+        assert_eq!(filters.apply(log).to_traces(), [vec!["A", "B"], vec!["A"]]);
+    }
+}
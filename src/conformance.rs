@@ -0,0 +1,517 @@
+use std::collections::{HashMap, HashSet, VecDeque};
+
+use chrono::{DateTime, Utc};
+use process_mining::petri_net::petri_net_struct::{ArcType, Marking};
+use process_mining::PetriNet;
+
+use crate::i18n::{Language, Message};
+use crate::monitoring::WindowSize;
+use crate::timeline::CaseTimeline;
+use crate::variant_log::{CaseMetadata, VariantLog};
+
+/// How many invisible transitions the replay fires at most to enable the next visible one.
+const MAX_SILENT_STEPS: usize = 5;
+
+const TREND_DATE_FORMAT: &str = "%Y-%m-%d %H:%M";
+
+/// Token counts of replaying a single trace on a Petri net.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct TraceReplay {
+    pub produced: u64,
+    pub consumed: u64,
+    /// Tokens that had to be created artificially to fire a transition.
+    pub missing: u64,
+    /// Tokens left over after consuming the final marking.
+    pub remaining: u64,
+    /// Events whose activity doesn't label any transition of the model.
+    pub unknown_activities: usize,
+}
+
+impl TraceReplay {
+    /// Token-based fitness `0.5 * (1 - missing / consumed) + 0.5 * (1 - remaining / produced)`.
+    pub fn fitness(&self) -> f64 {
+        token_fitness(self.produced, self.consumed, self.missing, self.remaining)
+    }
+
+    /// A trace fits if it could be replayed without missing or remaining tokens and all of its
+    /// activities occur in the model.
+    pub fn is_fitting(&self) -> bool {
+        self.missing == 0 && self.remaining == 0 && self.unknown_activities == 0
+    }
+}
+
+/// Result of replaying every variant of a log on a Petri net.
+#[derive(Debug, Clone)]
+pub struct ReplayReport {
+    /// Every variant with its frequency and its replay result.
+    pub variants: Vec<(Vec<String>, usize, TraceReplay)>,
+}
+
+impl ReplayReport {
+    /// Log-level fitness, computed from the token counts of all cases.
+    pub fn fitness(&self) -> f64 {
+        let total = |count: fn(&TraceReplay) -> u64| -> u64 {
+            self.variants
+                .iter()
+                .map(|(_, frequency, replay)| count(replay) * *frequency as u64)
+                .sum()
+        };
+        token_fitness(
+            total(|r| r.produced),
+            total(|r| r.consumed),
+            total(|r| r.missing),
+            total(|r| r.remaining),
+        )
+    }
+
+    /// Returns the number of cases that fit the model.
+    pub fn fitting_cases(&self) -> usize {
+        self.variants
+            .iter()
+            .filter(|(_, _, replay)| replay.is_fitting())
+            .map(|(_, frequency, _)| frequency)
+            .sum()
+    }
+
+    /// Returns the number of replayed cases.
+    pub fn cases(&self) -> usize {
+        self.variants.iter().map(|(_, frequency, _)| frequency).sum()
+    }
+}
+
+fn token_fitness(produced: u64, consumed: u64, missing: u64, remaining: u64) -> f64 {
+    let ratio = |part: u64, whole: u64| if whole == 0 { 0.0 } else { part as f64 / whole as f64 };
+    0.5 * (1.0 - ratio(missing, consumed)) + 0.5 * (1.0 - ratio(remaining, produced))
+}
+
+/// Replays every variant of `log` on `net` (token-based replay).
+///
+/// For every event, a transition labelled with its activity is fired, preferring one that is
+/// enabled. If none is, up to [`MAX_SILENT_STEPS`] invisible transitions are fired to enable
+/// one; otherwise the missing tokens are created. At the end of the trace invisible transitions
+/// are used to reach the first final marking of the net, which is then consumed. Without a
+/// final marking all remaining tokens are consumed.
+pub fn token_replay(net: &PetriNet, log: &VariantLog) -> ReplayReport {
+    ReplayNet::new(net).replay_log(log)
+}
+
+/// The replay of the cases completed in one window of a [`fitness_trend`].
+#[derive(Debug, Clone)]
+pub struct FitnessWindow {
+    /// Completion of the first case of the window.
+    pub start: DateTime<Utc>,
+    /// Completion of the last case of the window.
+    pub end: DateTime<Utc>,
+    pub report: ReplayReport,
+}
+
+// A case of a fitness trend with its completion
+type CompletedCase<'a> = (DateTime<Utc>, (&'a CaseMetadata, &'a [String]));
+
+/// Replays the cases of `log` on a fixed reference model window by window, ordered by the
+/// completion of the cases (the last timestamp of their timeline in `timelines`), to see
+/// whether the process drifts away from the model over time.
+///
+/// [`WindowSize::Cases`] windows hold `n` consecutive cases each; [`WindowSize::Time`] windows
+/// hold the cases completed within consecutive periods of that length, starting with the first
+/// completion, and periods without cases are left out. Cases without a timestamp are skipped.
+pub fn fitness_trend(
+    net: &PetriNet,
+    log: &VariantLog,
+    timelines: &[CaseTimeline],
+    window: WindowSize,
+) -> Vec<FitnessWindow> {
+    let completions: HashMap<&str, DateTime<Utc>> = timelines
+        .iter()
+        .filter_map(|timeline| Some((timeline.case_id.as_str(), timeline.span()?.1)))
+        .collect();
+    let mut cases: Vec<CompletedCase> = log
+        .cases()
+        .iter()
+        .zip(log.traces())
+        .filter_map(|case| Some((*completions.get(case.0.case_id.as_str())?, case)))
+        .collect();
+    cases.sort_by_key(|(completed_at, _)| *completed_at);
+    let Some(&(first_completion, _)) = cases.first() else {
+        return Vec::new();
+    };
+
+    let mut windows: Vec<Vec<CompletedCase>> = Vec::new();
+    let mut current_window = None;
+    for (case_idx, case) in cases.into_iter().enumerate() {
+        let window_idx = match window {
+            WindowSize::Cases(n) => case_idx / n.max(1),
+            WindowSize::Time(period) => {
+                let period = period.num_milliseconds().max(1);
+                ((case.0 - first_completion).num_milliseconds() / period) as usize
+            }
+        };
+        if current_window != Some(window_idx) {
+            windows.push(Vec::new());
+            current_window = Some(window_idx);
+        }
+        windows.last_mut().expect("a window was just added").push(case);
+    }
+
+    let net = ReplayNet::new(net);
+    windows
+        .into_iter()
+        .map(|cases| {
+            let mut log = VariantLog::new();
+            for (_, (case, trace)) in &cases {
+                log.push((*case).clone(), trace.to_vec());
+            }
+            FitnessWindow {
+                start: cases[0].0,
+                end: cases[cases.len() - 1].0,
+                report: net.replay_log(&log),
+            }
+        })
+        .collect()
+}
+
+/// One line per window of a [`fitness_trend`] with its period, number of cases and fitness.
+pub fn trend_report(windows: &[FitnessWindow], language: Language) -> String {
+    let mut output = format!(
+        "{:<48}{:<10}{}\n",
+        Message::Period.text(language),
+        Message::Cases.text(language),
+        Message::Fitness.text(language)
+    );
+    for window in windows {
+        output.push_str(&format!(
+            "{:<48}{:<10}{:.4}\n",
+            format!(
+                "{} - {}",
+                window.start.format(TREND_DATE_FORMAT),
+                window.end.format(TREND_DATE_FORMAT)
+            ),
+            window.report.cases(),
+            window.report.fitness()
+        ));
+    }
+    output
+}
+
+pub(crate) struct ReplayTransition {
+    pub(crate) label: Option<String>,
+    // (place index, weight)
+    pub(crate) pre: Vec<(usize, u64)>,
+    pub(crate) post: Vec<(usize, u64)>,
+}
+
+// The net with places and transitions numbered, so markings can be plain vectors
+pub(crate) struct ReplayNet {
+    pub(crate) transitions: Vec<ReplayTransition>,
+    by_label: HashMap<String, Vec<usize>>,
+    silent: Vec<usize>,
+    pub(crate) initial_marking: Vec<u64>,
+    pub(crate) final_marking: Option<Vec<u64>>,
+}
+
+impl ReplayNet {
+    pub(crate) fn new(net: &PetriNet) -> Self {
+        let mut place_ids: Vec<_> = net.places.keys().copied().collect();
+        place_ids.sort();
+        let place_index: HashMap<_, usize> = place_ids
+            .iter()
+            .enumerate()
+            .map(|(idx, id)| (*id, idx))
+            .collect();
+
+        let mut transition_ids: Vec<_> = net.transitions.keys().copied().collect();
+        transition_ids.sort();
+        let transition_index: HashMap<_, usize> = transition_ids
+            .iter()
+            .enumerate()
+            .map(|(idx, id)| (*id, idx))
+            .collect();
+
+        let mut transitions: Vec<ReplayTransition> = transition_ids
+            .iter()
+            .map(|id| ReplayTransition {
+                label: net.transitions[id].label.clone(),
+                pre: Vec::new(),
+                post: Vec::new(),
+            })
+            .collect();
+        for arc in &net.arcs {
+            match arc.from_to {
+                ArcType::PlaceTransition(place, transition) => {
+                    if let (Some(&p), Some(&t)) =
+                        (place_index.get(&place), transition_index.get(&transition))
+                    {
+                        transitions[t].pre.push((p, arc.weight as u64));
+                    }
+                }
+                ArcType::TransitionPlace(transition, place) => {
+                    if let (Some(&p), Some(&t)) =
+                        (place_index.get(&place), transition_index.get(&transition))
+                    {
+                        transitions[t].post.push((p, arc.weight as u64));
+                    }
+                }
+            }
+        }
+
+        let mut by_label: HashMap<String, Vec<usize>> = HashMap::new();
+        let mut silent = Vec::new();
+        for (idx, transition) in transitions.iter().enumerate() {
+            match &transition.label {
+                Some(label) => by_label.entry(label.clone()).or_default().push(idx),
+                None => silent.push(idx),
+            }
+        }
+
+        let to_vector = |marking: &Marking| {
+            let mut tokens = vec![0; place_ids.len()];
+            for (place, &count) in marking {
+                if let Some(&idx) = place_index.get(&place.0) {
+                    tokens[idx] = count;
+                }
+            }
+            tokens
+        };
+
+        ReplayNet {
+            transitions,
+            by_label,
+            silent,
+            initial_marking: net
+                .initial_marking
+                .as_ref()
+                .map(to_vector)
+                .unwrap_or_else(|| vec![0; place_ids.len()]),
+            final_marking: net
+                .final_markings
+                .as_ref()
+                .and_then(|markings| markings.first())
+                .map(to_vector),
+        }
+    }
+
+    fn replay_log(&self, log: &VariantLog) -> ReplayReport {
+        ReplayReport {
+            variants: log
+                .variants()
+                .map(|(variant, frequency)| (variant.to_vec(), frequency, self.replay(variant)))
+                .collect(),
+        }
+    }
+
+    fn replay(&self, trace: &[String]) -> TraceReplay {
+        let mut marking = self.initial_marking.clone();
+        let mut replay = TraceReplay {
+            produced: marking.iter().sum(),
+            ..Default::default()
+        };
+
+        for activity in trace {
+            let Some(candidates) = self.by_label.get(activity) else {
+                replay.unknown_activities += 1;
+                continue;
+            };
+
+            let enabled = candidates
+                .iter()
+                .find(|&&t| self.is_enabled(t, &marking))
+                .copied();
+            let transition = match enabled {
+                Some(t) => t,
+                None => {
+                    let silent_path = candidates.iter().find_map(|&t| {
+                        self.silent_path(&marking, |m| self.is_enabled(t, m))
+                            .map(|path| (path, t))
+                    });
+                    match silent_path {
+                        Some((path, t)) => {
+                            for silent in path {
+                                self.fire(silent, &mut marking, &mut replay);
+                            }
+                            t
+                        }
+                        None => candidates[0],
+                    }
+                }
+            };
+            self.fire(transition, &mut marking, &mut replay);
+        }
+
+        match &self.final_marking {
+            Some(final_marking) => {
+                if let Some(path) = self.silent_path(&marking, |m| m == final_marking.as_slice()) {
+                    for silent in path {
+                        self.fire(silent, &mut marking, &mut replay);
+                    }
+                }
+                for (tokens, &required) in marking.iter().zip(final_marking) {
+                    replay.consumed += required;
+                    replay.missing += required.saturating_sub(*tokens);
+                    replay.remaining += tokens.saturating_sub(required);
+                }
+            }
+            None => replay.consumed += marking.iter().sum::<u64>(),
+        }
+
+        replay
+    }
+
+    pub(crate) fn is_enabled(&self, transition: usize, marking: &[u64]) -> bool {
+        self.transitions[transition]
+            .pre
+            .iter()
+            .all(|&(place, weight)| marking[place] >= weight)
+    }
+
+    // Fires a transition, creating the tokens it is missing
+    fn fire(&self, transition: usize, marking: &mut [u64], replay: &mut TraceReplay) {
+        let transition = &self.transitions[transition];
+        for &(place, weight) in &transition.pre {
+            replay.missing += weight.saturating_sub(marking[place]);
+            replay.consumed += weight;
+            marking[place] = marking[place].saturating_sub(weight);
+        }
+        for &(place, weight) in &transition.post {
+            marking[place] += weight;
+            replay.produced += weight;
+        }
+    }
+
+    // Shortest sequence of enabled invisible transitions leading to a marking satisfying `goal`
+    fn silent_path(&self, marking: &[u64], goal: impl Fn(&[u64]) -> bool) -> Option<Vec<usize>> {
+        let mut queue = VecDeque::from([(marking.to_vec(), Vec::new())]);
+        let mut visited = HashSet::from([marking.to_vec()]);
+
+        while let Some((current, path)) = queue.pop_front() {
+            if goal(&current) {
+                return Some(path);
+            }
+            if path.len() == MAX_SILENT_STEPS {
+                continue;
+            }
+            for &silent in &self.silent {
+                if !self.is_enabled(silent, &current) {
+                    continue;
+                }
+                let mut next = current.clone();
+                self.fire(silent, &mut next, &mut TraceReplay::default());
+                if visited.insert(next.clone()) {
+                    let mut next_path = path.clone();
+                    next_path.push(silent);
+                    queue.push_back((next, next_path));
+                }
+            }
+        }
+
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{Duration, TimeZone};
+
+    use super::*;
+    use crate::parser::parse_petri_net;
+    use crate::timeline::TestInstance;
+
+    fn traces(traces: &[&[&str]]) -> VariantLog {
+        VariantLog::from_traces(
+            traces
+                .iter()
+                .map(|trace| trace.iter().map(|s| s.to_string()).collect())
+                .collect(),
+        )
+    }
+
+    // start -> A -> p1 -> (B | tau) -> end
+    fn sequence_net() -> PetriNet {
+        let mut net = PetriNet::new();
+        let start = net.add_place(None);
+        let p1 = net.add_place(None);
+        let end = net.add_place(None);
+        let a = net.add_transition(Some("A".to_string()), None);
+        let b = net.add_transition(Some("B".to_string()), None);
+        let skip = net.add_transition(None, None);
+        net.add_arc(ArcType::place_to_transition(start, a), None);
+        net.add_arc(ArcType::transition_to_place(a, p1), None);
+        net.add_arc(ArcType::place_to_transition(p1, b), None);
+        net.add_arc(ArcType::transition_to_place(b, end), None);
+        net.add_arc(ArcType::place_to_transition(p1, skip), None);
+        net.add_arc(ArcType::transition_to_place(skip, end), None);
+        net.initial_marking = Some(Marking::from([(start, 1)]));
+        net.final_markings = Some(vec![Marking::from([(end, 1)])]);
+        net
+    }
+
+    #[test]
+    fn test_fitting_log() {
+        let report = token_replay(&sequence_net(), &traces(&[&["A", "B"], &["A"], &["A", "B"]]));
+        assert_eq!(report.cases(), 3);
+        assert_eq!(report.fitting_cases(), 3);
+        assert_eq!(report.fitness(), 1.0);
+    }
+
+    #[test]
+    fn test_deviating_trace() {
+        let report = token_replay(&sequence_net(), &traces(&[&["B", "C"]]));
+        let (_, _, replay) = &report.variants[0];
+        assert_eq!(replay.unknown_activities, 1);
+        // B lacks the token A would have produced, which is then left in the start place
+        assert_eq!(replay.missing, 1);
+        assert_eq!(replay.remaining, 1);
+        assert_eq!(replay.fitness(), 0.5);
+        assert_eq!(report.fitting_cases(), 0);
+    }
+
+    #[test]
+    fn test_replay_pnml_model() {
+        let net = parse_petri_net(Some("./sample-data/sequence.pnml"), None).unwrap();
+        let report = token_replay(&net, &traces(&[&["A", "B"], &["A"], &["B"]]));
+        assert_eq!(report.fitting_cases(), 2);
+        assert!(report.fitness() < 1.0);
+    }
+
+    #[test]
+    fn test_fitness_trend() {
+        let day = |day: u32| Some(Utc.with_ymd_and_hms(2024, 1, day, 12, 0, 0).unwrap());
+        let mut log = VariantLog::new();
+        let mut timelines = Vec::new();
+        for (case_id, activities, completed) in [
+            ("4", &["B", "A"][..], day(9)),
+            ("1", &["A", "B"], day(1)),
+            ("2", &["A"], day(2)),
+            ("3", &["B", "A"], day(8)),
+            ("no timestamp", &["A"], None),
+        ] {
+            log.push(
+                CaseMetadata {
+                    case_id: case_id.to_string(),
+                    ongoing: false,
+                    attributes: Default::default(),
+                },
+                activities.iter().map(|activity| activity.to_string()).collect(),
+            );
+            let instances: Vec<TestInstance> = activities
+                .iter()
+                .map(|&activity| (activity, None, completed, None))
+                .collect();
+            timelines.push(CaseTimeline::of(case_id, &instances));
+        }
+
+        let by_cases = fitness_trend(&sequence_net(), &log, &timelines, WindowSize::Cases(2));
+        let fitness: Vec<f64> = by_cases.iter().map(|window| window.report.fitness()).collect();
+        assert_eq!(fitness.len(), 2);
+        assert_eq!(fitness[0], 1.0);
+        assert!(fitness[1] < 1.0);
+        assert_eq!(by_cases[1].start, day(8).unwrap());
+
+        // the week of the first completion, then nothing until the second week
+        let week = WindowSize::Time(Duration::days(7));
+        let by_time = fitness_trend(&sequence_net(), &log, &timelines, week);
+        let cases: Vec<usize> = by_time.iter().map(|window| window.report.cases()).collect();
+        assert_eq!(cases, [2, 2]);
+        assert!(trend_report(&by_time, Language::English)
+            .contains("2024-01-08 12:00 - 2024-01-09 12:00"));
+    }
+}
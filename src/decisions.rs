@@ -0,0 +1,457 @@
+//! Decision mining: which case and event attributes explain the branch a case takes at an XOR
+//! split (see [`crate::gateways`]), learned as a small decision tree and read as rules, e.g.
+//! `amount > 10000 => Manual approval`.
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt;
+
+use process_mining::event_log::EventLog;
+
+use crate::attributes::{parse_number, AttributeType};
+use crate::gateways::{gateways, GatewayKind, GatewayPosition};
+use crate::i18n::{Language, Message};
+use crate::parser::xes_extensions::{XesExtensions, CONCEPT_NAME};
+use crate::parser::{to_event_log, Classifier, ParseError};
+use crate::variant_log::VariantLog;
+
+/// How large the decision trees grow.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DecisionSettings {
+    /// Maximum number of conditions of a rule.
+    pub max_depth: usize,
+    /// Minimum number of decisions on either side of a condition.
+    pub min_instances: usize,
+}
+
+impl Default for DecisionSettings {
+    fn default() -> Self {
+        DecisionSettings {
+            max_depth: 3,
+            min_instances: 5,
+        }
+    }
+}
+
+/// A test of one attribute.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Condition {
+    AtMost(String, f64),
+    Above(String, f64),
+    Equals(String, String),
+    /// Also holds if the attribute is missing.
+    NotEquals(String, String),
+}
+
+impl fmt::Display for Condition {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Condition::AtMost(attribute, value) => write!(f, "{} <= {}", attribute, value),
+            Condition::Above(attribute, value) => write!(f, "{} > {}", attribute, value),
+            Condition::Equals(attribute, value) => write!(f, "{} = {}", attribute, value),
+            Condition::NotEquals(attribute, value) => write!(f, "{} != {}", attribute, value),
+        }
+    }
+}
+
+/// A leaf of a decision tree: the branch taken by most decisions meeting all conditions.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DecisionRule {
+    pub conditions: Vec<Condition>,
+    pub branch: String,
+    /// Decisions meeting the conditions.
+    pub support: usize,
+    /// Share of them that took `branch`.
+    pub confidence: f64,
+}
+
+/// The rules learned for one XOR split.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DecisionPoint {
+    pub activity: String,
+    pub branches: Vec<String>,
+    /// Occurrences of the activity directly followed by one of the branches.
+    pub decisions: usize,
+    pub rules: Vec<DecisionRule>,
+    /// Share of the decisions the rules predict correctly.
+    pub accuracy: f64,
+    /// Share of the decisions that took the most frequent branch, what the rules should beat.
+    pub baseline: f64,
+}
+
+#[derive(Debug, Clone)]
+enum Value {
+    Number(f64),
+    Text(String),
+}
+
+struct Decision {
+    attributes: BTreeMap<String, Value>,
+    branch: usize,
+}
+
+/// Learns the decision rules of every XOR split of `log` (see [`crate::gateways::gateways`]).
+///
+/// Every occurrence of a split activity that is directly followed by one of its branches is a
+/// decision, described by the attributes of the case and the latest values of the attributes
+/// of its events up to the split activity. Attributes whose values are all numbers are
+/// compared by thresholds, all others by value. Splits without attributes are left out.
+pub fn mine_decisions(
+    log: &EventLog,
+    classifier: &Classifier,
+    settings: &DecisionSettings,
+) -> Result<Vec<DecisionPoint>, ParseError> {
+    let traces = to_event_log(log, classifier)?.traces;
+    let variant_log = VariantLog::from_trace_iter(
+        traces
+            .iter()
+            .map(|trace| trace.activities().map(str::to_string).collect()),
+    );
+    let mut points = Vec::new();
+    for gateway in gateways(&variant_log) {
+        if gateway.position != GatewayPosition::Split || gateway.kind != GatewayKind::Xor {
+            continue;
+        }
+        let mut raw: Vec<(BTreeMap<String, String>, usize)> = Vec::new();
+        for (original, trace) in log.traces.iter().zip(&traces) {
+            let mut attributes: BTreeMap<String, String> = original
+                .attributes
+                .iter()
+                .filter(|attribute| attribute.key != CONCEPT_NAME)
+                .filter_map(|attribute| {
+                    original
+                        .text_attribute(&attribute.key)
+                        .map(|value| (attribute.key.clone(), value))
+                })
+                .collect();
+            for (event, next) in trace.events.iter().zip(trace.events.iter().skip(1)) {
+                attributes.extend(event.attributes.clone());
+                if event.activity != gateway.activity {
+                    continue;
+                }
+                if let Some(branch) = gateway.branches.iter().position(|b| *b == next.activity) {
+                    raw.push((attributes.clone(), branch));
+                }
+            }
+        }
+        let decisions = typed(raw);
+        if decisions
+            .iter()
+            .all(|decision| decision.attributes.is_empty())
+        {
+            continue;
+        }
+        let mut rules = Vec::new();
+        let indices: Vec<usize> = (0..decisions.len()).collect();
+        grow(
+            &decisions,
+            &indices,
+            gateway.branches.len(),
+            settings,
+            Vec::new(),
+            &mut rules,
+        );
+        let correct: f64 = rules
+            .iter()
+            .map(|(rule, _)| rule.confidence * rule.support as f64)
+            .sum();
+        let baseline = majority(&decisions, &indices, gateway.branches.len()).1;
+        points.push(DecisionPoint {
+            activity: gateway.activity.clone(),
+            decisions: decisions.len(),
+            accuracy: correct / decisions.len() as f64,
+            baseline: baseline as f64 / decisions.len() as f64,
+            rules: rules
+                .into_iter()
+                .map(|(mut rule, branch)| {
+                    rule.branch = gateway.branches[branch].clone();
+                    rule
+                })
+                .collect(),
+            branches: gateway.branches,
+        });
+    }
+    Ok(points)
+}
+
+// Parses the attributes inferred as numeric
+fn typed(raw: Vec<(BTreeMap<String, String>, usize)>) -> Vec<Decision> {
+    let keys: BTreeSet<&String> = raw
+        .iter()
+        .flat_map(|(attributes, _)| attributes.keys())
+        .collect();
+    let numeric: BTreeSet<String> = keys
+        .into_iter()
+        .filter(|key| {
+            AttributeType::infer(
+                raw.iter()
+                    .filter_map(|(attributes, _)| attributes.get(*key))
+                    .map(String::as_str),
+            ) == AttributeType::Numeric
+        })
+        .cloned()
+        .collect();
+    raw.into_iter()
+        .map(|(attributes, branch)| Decision {
+            attributes: attributes
+                .into_iter()
+                .map(|(key, value)| {
+                    let value = match parse_number(&value) {
+                        Some(number) if numeric.contains(&key) => Value::Number(number),
+                        _ => Value::Text(value),
+                    };
+                    (key, value)
+                })
+                .collect(),
+            branch,
+        })
+        .collect()
+}
+
+// The most frequent branch among `indices` and its count
+fn majority(decisions: &[Decision], indices: &[usize], branches: usize) -> (usize, usize) {
+    let mut counts = vec![0; branches];
+    for &idx in indices {
+        counts[decisions[idx].branch] += 1;
+    }
+    counts
+        .into_iter()
+        .enumerate()
+        .max_by_key(|&(branch, count)| (count, std::cmp::Reverse(branch)))
+        .unwrap_or((0, 0))
+}
+
+fn gini(decisions: &[Decision], indices: &[usize], branches: usize) -> f64 {
+    let mut counts = vec![0; branches];
+    for &idx in indices {
+        counts[decisions[idx].branch] += 1;
+    }
+    let total = indices.len() as f64;
+    1.0 - counts
+        .iter()
+        .map(|&count| (count as f64 / total).powi(2))
+        .sum::<f64>()
+}
+
+// The pair of conditions splitting `indices` into the purest two parts
+fn best_split(
+    decisions: &[Decision],
+    indices: &[usize],
+    branches: usize,
+    min_instances: usize,
+) -> Option<(Condition, Condition)> {
+    let attributes: BTreeSet<&String> = indices
+        .iter()
+        .flat_map(|&idx| decisions[idx].attributes.keys())
+        .collect();
+    let mut candidates: Vec<(Condition, Condition)> = Vec::new();
+    for attribute in attributes {
+        let mut numbers: Vec<f64> = Vec::new();
+        let mut texts: BTreeSet<&str> = BTreeSet::new();
+        for &idx in indices {
+            match decisions[idx].attributes.get(attribute) {
+                Some(Value::Number(number)) => numbers.push(*number),
+                Some(Value::Text(text)) => {
+                    texts.insert(text);
+                }
+                None => {}
+            }
+        }
+        numbers.sort_by(f64::total_cmp);
+        numbers.dedup();
+        for pair in numbers.windows(2) {
+            let threshold = (pair[0] + pair[1]) / 2.0;
+            candidates.push((
+                Condition::AtMost(attribute.clone(), threshold),
+                Condition::Above(attribute.clone(), threshold),
+            ));
+        }
+        for text in texts {
+            candidates.push((
+                Condition::Equals(attribute.clone(), text.to_string()),
+                Condition::NotEquals(attribute.clone(), text.to_string()),
+            ));
+        }
+    }
+
+    let impurity = gini(decisions, indices, branches);
+    let mut best: Option<(f64, Condition, Condition)> = None;
+    for (matching, other) in candidates {
+        let (left, right): (Vec<usize>, Vec<usize>) = indices
+            .iter()
+            .partition(|&&idx| holds(&matching, &decisions[idx]));
+        if left.len() < min_instances || right.len() < min_instances {
+            continue;
+        }
+        let weighted = (left.len() as f64 * gini(decisions, &left, branches)
+            + right.len() as f64 * gini(decisions, &right, branches))
+            / indices.len() as f64;
+        let gain = impurity - weighted;
+        if gain > 1e-9
+            && best
+                .as_ref()
+                .is_none_or(|(best_gain, _, _)| gain > *best_gain)
+        {
+            best = Some((gain, matching, other));
+        }
+    }
+    best.map(|(_, matching, other)| (matching, other))
+}
+
+// Grows the tree below `indices` and collects its leaves as rules with the branch index
+fn grow(
+    decisions: &[Decision],
+    indices: &[usize],
+    branches: usize,
+    settings: &DecisionSettings,
+    conditions: Vec<Condition>,
+    rules: &mut Vec<(DecisionRule, usize)>,
+) {
+    let (branch, count) = majority(decisions, indices, branches);
+    let split = if conditions.len() < settings.max_depth && count < indices.len() {
+        best_split(decisions, indices, branches, settings.min_instances.max(1))
+    } else {
+        None
+    };
+    let Some((matching, other)) = split else {
+        rules.push((
+            DecisionRule {
+                conditions,
+                branch: String::new(),
+                support: indices.len(),
+                confidence: count as f64 / indices.len() as f64,
+            },
+            branch,
+        ));
+        return;
+    };
+    let (left, right): (Vec<usize>, Vec<usize>) = indices
+        .iter()
+        .partition(|&&idx| holds(&matching, &decisions[idx]));
+    for (condition, part) in [(matching, left), (other, right)] {
+        let mut conditions = conditions.clone();
+        conditions.push(condition);
+        grow(decisions, &part, branches, settings, conditions, rules);
+    }
+}
+
+fn holds(condition: &Condition, decision: &Decision) -> bool {
+    match condition {
+        Condition::AtMost(attribute, threshold) => matches!(
+            decision.attributes.get(attribute),
+            Some(Value::Number(n)) if n <= threshold
+        ),
+        Condition::Above(attribute, threshold) => {
+            !holds(&Condition::AtMost(attribute.clone(), *threshold), decision)
+        }
+        Condition::Equals(attribute, value) => matches!(
+            decision.attributes.get(attribute),
+            Some(Value::Text(text)) if text == value
+        ),
+        Condition::NotEquals(attribute, value) => !holds(
+            &Condition::Equals(attribute.clone(), value.clone()),
+            decision,
+        ),
+    }
+}
+
+/// Every decision point with its accuracy, followed by one line per rule.
+pub fn report(points: &[DecisionPoint], language: Language) -> String {
+    let mut output = String::new();
+    for point in points {
+        output.push_str(&format!(
+            "{}: {} ({} {}, {} {:.4}, {} {:.4})\n",
+            Message::DecisionPoint.text(language),
+            point.activity,
+            point.decisions,
+            Message::Decisions.text(language),
+            Message::Accuracy.text(language),
+            point.accuracy,
+            Message::Baseline.text(language),
+            point.baseline
+        ));
+        for rule in &point.rules {
+            let conditions = if rule.conditions.is_empty() {
+                "*".to_string()
+            } else {
+                rule.conditions
+                    .iter()
+                    .map(Condition::to_string)
+                    .collect::<Vec<String>>()
+                    .join(" & ")
+            };
+            output.push_str(&format!(
+                "  {} => {} ({} {}, {} {:.4})\n",
+                conditions,
+                rule.branch,
+                Message::Support.text(language),
+                rule.support,
+                Message::Confidence.text(language),
+                rule.confidence
+            ));
+        }
+    }
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::{parse_into_event_log_with_config, ImportConfig};
+
+    #[test]
+    fn test_mine_decisions() {
+        let traces: String = (0..20)
+            .map(|idx| {
+                let amount = 1000 * (idx + 1);
+                let branch = if amount > 10_000 { "Manual" } else { "Auto" };
+                format!(
+                    r#"<trace>
+                        <string key="concept:name" value="{idx}"/>
+                        <string key="channel" value="{channel}"/>
+                        <event>
+                            <string key="concept:name" value="Receive"/>
+                            <int key="amount" value="{amount}"/>
+                            <date key="time:timestamp" value="2024-01-01T10:00:00+00:00"/>
+                        </event>
+                        <event>
+                            <string key="concept:name" value="{branch}"/>
+                            <date key="time:timestamp" value="2024-01-01T11:00:00+00:00"/>
+                        </event>
+                    </trace>"#,
+                    channel = if idx % 2 == 0 { "web" } else { "mail" },
+                )
+            })
+            .collect();
+        let content = format!("<log>{}</log>", traces);
+        let log = parse_into_event_log_with_config(None, Some(&content), &ImportConfig::default())
+            .unwrap();
+        let points =
+            mine_decisions(&log, &Classifier::ConceptName, &DecisionSettings::default()).unwrap();
+
+        assert_eq!(points.len(), 1);
+        let point = &points[0];
+        assert_eq!(point.activity, "Receive");
+        assert_eq!(point.decisions, 20);
+        assert_eq!(point.accuracy, 1.0);
+        assert_eq!(point.baseline, 0.5);
+        let rules: Vec<(String, &str, usize)> = point
+            .rules
+            .iter()
+            .map(|rule| {
+                (
+                    rule.conditions[0].to_string(),
+                    rule.branch.as_str(),
+                    rule.support,
+                )
+            })
+            .collect();
+        assert_eq!(
+            rules,
+            [
+                ("amount <= 10500".to_string(), "Auto", 10),
+                ("amount > 10500".to_string(), "Manual", 10)
+            ]
+        );
+        assert!(report(&points, Language::English).contains("amount > 10500 => Manual"));
+    }
+}
@@ -0,0 +1,444 @@
+use crate::parser::error::{ParseErrorKind, Position};
+use crate::parser::ParseError;
+use crate::variant_log::VariantLog;
+
+/// The DECLARE templates that can be checked.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Template {
+    Existence,
+    Existence2,
+    Absence,
+    Absence2,
+    Exactly1,
+    Init,
+    End,
+    RespondedExistence,
+    CoExistence,
+    Response,
+    Precedence,
+    Succession,
+    AlternateResponse,
+    AlternatePrecedence,
+    AlternateSuccession,
+    ChainResponse,
+    ChainPrecedence,
+    ChainSuccession,
+    NotCoExistence,
+    NotSuccession,
+    NotChainSuccession,
+    Choice,
+    ExclusiveChoice,
+}
+
+const TEMPLATES: [Template; 23] = [
+    Template::Existence,
+    Template::Existence2,
+    Template::Absence,
+    Template::Absence2,
+    Template::Exactly1,
+    Template::Init,
+    Template::End,
+    Template::RespondedExistence,
+    Template::CoExistence,
+    Template::Response,
+    Template::Precedence,
+    Template::Succession,
+    Template::AlternateResponse,
+    Template::AlternatePrecedence,
+    Template::AlternateSuccession,
+    Template::ChainResponse,
+    Template::ChainPrecedence,
+    Template::ChainSuccession,
+    Template::NotCoExistence,
+    Template::NotSuccession,
+    Template::NotChainSuccession,
+    Template::Choice,
+    Template::ExclusiveChoice,
+];
+
+impl Template {
+    /// Number of activities the template is parameterized with.
+    pub fn arity(&self) -> usize {
+        match self {
+            Template::Existence
+            | Template::Existence2
+            | Template::Absence
+            | Template::Absence2
+            | Template::Exactly1
+            | Template::Init
+            | Template::End => 1,
+            _ => 2,
+        }
+    }
+
+    /// Looks up a template by name, ignoring case, spaces and dashes, so both `ChainResponse`
+    /// and `Chain Response` (or `Co-Existence`) are accepted.
+    pub fn from_name(name: &str) -> Option<Template> {
+        let normalize = |s: &str| {
+            s.chars()
+                .filter(|c| !c.is_whitespace() && *c != '-' && *c != '_')
+                .collect::<String>()
+                .to_lowercase()
+        };
+        let name = normalize(name);
+        TEMPLATES
+            .into_iter()
+            .find(|template| normalize(&format!("{:?}", template)) == name)
+    }
+}
+
+impl std::fmt::Display for Template {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "{:?}", self)
+    }
+}
+
+/// How a trace relates to a constraint.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Outcome {
+    Satisfied,
+    /// Satisfied only because the constraint was never activated (e.g. `Response[A, B]` on a
+    /// trace without `A`).
+    VacuouslySatisfied,
+    Violated,
+}
+
+/// A template instantiated with concrete activities, e.g. `Response[A, B]`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Constraint {
+    pub template: Template,
+    pub activities: Vec<String>,
+}
+
+impl std::fmt::Display for Constraint {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "{}[{}]", self.template, self.activities.join(", "))
+    }
+}
+
+impl Constraint {
+    pub fn check(&self, trace: &[String]) -> Outcome {
+        let a = self.activities[0].as_str();
+        let b = self.activities.get(1).map_or("", |b| b.as_str());
+        let count = |activity: &str| trace.iter().filter(|e| *e == activity).count();
+        let (count_a, count_b) = (count(a), count(b));
+        let holds = |satisfied: bool| {
+            if satisfied {
+                Outcome::Satisfied
+            } else {
+                Outcome::Violated
+            }
+        };
+        // binary templates are vacuously satisfied if they were not activated
+        let activated_by = |activated: bool, satisfied: bool| {
+            if !activated {
+                Outcome::VacuouslySatisfied
+            } else {
+                holds(satisfied)
+            }
+        };
+
+        match self.template {
+            Template::Existence => holds(count_a >= 1),
+            Template::Existence2 => holds(count_a >= 2),
+            Template::Absence => holds(count_a == 0),
+            Template::Absence2 => holds(count_a < 2),
+            Template::Exactly1 => holds(count_a == 1),
+            Template::Init => holds(trace.first().is_some_and(|e| e == a)),
+            Template::End => holds(trace.last().is_some_and(|e| e == a)),
+            Template::RespondedExistence => activated_by(count_a > 0, count_b > 0),
+            Template::CoExistence => {
+                activated_by(count_a > 0 || count_b > 0, (count_a > 0) == (count_b > 0))
+            }
+            Template::Response => activated_by(count_a > 0, response(trace, a, b)),
+            Template::Precedence => activated_by(count_b > 0, precedence(trace, a, b)),
+            Template::Succession => activated_by(
+                count_a > 0 || count_b > 0,
+                response(trace, a, b) && precedence(trace, a, b),
+            ),
+            Template::AlternateResponse => {
+                activated_by(count_a > 0, alternate_response(trace, a, b))
+            }
+            Template::AlternatePrecedence => {
+                activated_by(count_b > 0, alternate_precedence(trace, a, b))
+            }
+            Template::AlternateSuccession => activated_by(
+                count_a > 0 || count_b > 0,
+                alternate_response(trace, a, b) && alternate_precedence(trace, a, b),
+            ),
+            Template::ChainResponse => activated_by(count_a > 0, chain_response(trace, a, b)),
+            Template::ChainPrecedence => activated_by(count_b > 0, chain_precedence(trace, a, b)),
+            Template::ChainSuccession => activated_by(
+                count_a > 0 || count_b > 0,
+                chain_response(trace, a, b) && chain_precedence(trace, a, b),
+            ),
+            Template::NotCoExistence => {
+                activated_by(count_a > 0 || count_b > 0, count_a == 0 || count_b == 0)
+            }
+            Template::NotSuccession => activated_by(count_a > 0, {
+                let first_a = trace.iter().position(|e| e == a);
+                first_a.is_none_or(|idx| !trace[idx..].iter().any(|e| e == b))
+            }),
+            Template::NotChainSuccession => activated_by(
+                count_a > 0,
+                !trace.windows(2).any(|pair| pair[0] == a && pair[1] == b),
+            ),
+            Template::Choice => holds(count_a > 0 || count_b > 0),
+            Template::ExclusiveChoice => holds((count_a > 0) != (count_b > 0)),
+        }
+    }
+}
+
+// every a is eventually followed by b
+fn response(trace: &[String], a: &str, b: &str) -> bool {
+    let last_a = trace.iter().rposition(|e| e == a);
+    last_a.is_none_or(|idx| trace[idx + 1..].iter().any(|e| e == b))
+}
+
+// every b is preceded by an a
+fn precedence(trace: &[String], a: &str, b: &str) -> bool {
+    let first_b = trace.iter().position(|e| e == b);
+    first_b.is_none_or(|idx| trace[..idx].iter().any(|e| e == a))
+}
+
+// every a is followed by b before the next a
+fn alternate_response(trace: &[String], a: &str, b: &str) -> bool {
+    let mut pending = false;
+    for e in trace {
+        if e == a {
+            if pending {
+                return false;
+            }
+            pending = true;
+        } else if e == b {
+            pending = false;
+        }
+    }
+    !pending
+}
+
+// every b is preceded by an a with no other b in between
+fn alternate_precedence(trace: &[String], a: &str, b: &str) -> bool {
+    let mut available = false;
+    for e in trace {
+        if e == b {
+            if !available {
+                return false;
+            }
+            available = false;
+        } else if e == a {
+            available = true;
+        }
+    }
+    true
+}
+
+// every a is directly followed by b
+fn chain_response(trace: &[String], a: &str, b: &str) -> bool {
+    trace
+        .iter()
+        .enumerate()
+        .filter(|(_, e)| *e == a)
+        .all(|(idx, _)| trace.get(idx + 1).is_some_and(|next| next == b))
+}
+
+// every b is directly preceded by a
+fn chain_precedence(trace: &[String], a: &str, b: &str) -> bool {
+    trace
+        .iter()
+        .enumerate()
+        .filter(|(_, e)| *e == b)
+        .all(|(idx, _)| idx > 0 && trace[idx - 1] == a)
+}
+
+/// Parses a DECLARE model in the textual `.decl` format, e.g.
+///
+/// ```text
+/// activity A
+/// activity B
+/// Response[A, B] | |
+/// Chain Precedence[B, C] | |
+/// ```
+///
+/// Only the constraint lines (`Template[A, B]`) are used; `activity` and `bind` declarations,
+/// attribute definitions and `#` comments are skipped. Data conditions (the `| ... |` parts
+/// after a constraint) are not supported and rejected unless empty.
+pub fn parse_declare(content: &str) -> Result<Vec<Constraint>, ParseError> {
+    let mut constraints = Vec::new();
+    let mut byte = 0;
+
+    // the lines with their terminators, so the offsets stay right with "\r\n"
+    for (line_idx, line) in content.split_inclusive('\n').enumerate() {
+        let line_start = byte;
+        byte += line.len();
+        let error = |message: String| {
+            let mut error = ParseError::new(ParseErrorKind::InvalidConstraint(message));
+            error.position = Some(Position {
+                byte: line_start,
+                line: line_idx + 1,
+                column: 1,
+            });
+            error
+        };
+
+        let line = line.trim();
+        let Some(open) = line.find('[') else {
+            continue;
+        };
+        if line.starts_with('#') {
+            continue;
+        }
+        let close = line
+            .rfind(']')
+            .ok_or_else(|| error(format!("missing ']' in \"{}\"", line)))?;
+
+        let name = &line[..open];
+        let template = Template::from_name(name)
+            .ok_or_else(|| error(format!("unknown template \"{}\"", name.trim())))?;
+        let activities: Vec<String> = line[open + 1..close]
+            .split(',')
+            .map(|activity| activity.trim().to_string())
+            .filter(|activity| !activity.is_empty())
+            .collect();
+        if activities.len() != template.arity() {
+            return Err(error(format!(
+                "{} expects {} activities, got {}",
+                template,
+                template.arity(),
+                activities.len()
+            )));
+        }
+        if line[close + 1..].split('|').any(|condition| !condition.trim().is_empty()) {
+            return Err(error(format!(
+                "data conditions are not supported (in \"{}\")",
+                line
+            )));
+        }
+
+        constraints.push(Constraint {
+            template,
+            activities,
+        });
+    }
+
+    Ok(constraints)
+}
+
+/// How many cases satisfy or violate a constraint.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ConstraintStatistics {
+    pub constraint: Constraint,
+    pub satisfied: usize,
+    pub vacuously_satisfied: usize,
+    pub violated: usize,
+}
+
+impl ConstraintStatistics {
+    /// Fraction of cases satisfying the constraint (including vacuously).
+    pub fn support(&self) -> f64 {
+        let total = self.satisfied + self.vacuously_satisfied + self.violated;
+        if total == 0 {
+            return 0.0;
+        }
+        (self.satisfied + self.vacuously_satisfied) as f64 / total as f64
+    }
+
+    /// Fraction of the cases activating the constraint that satisfy it.
+    pub fn confidence(&self) -> f64 {
+        let activated = self.satisfied + self.violated;
+        if activated == 0 {
+            return 0.0;
+        }
+        self.satisfied as f64 / activated as f64
+    }
+}
+
+/// Checks every constraint against every case of the log.
+pub fn check_constraints(constraints: &[Constraint], log: &VariantLog) -> Vec<ConstraintStatistics> {
+    constraints
+        .iter()
+        .map(|constraint| {
+            let mut statistics = ConstraintStatistics {
+                constraint: constraint.clone(),
+                satisfied: 0,
+                vacuously_satisfied: 0,
+                violated: 0,
+            };
+            for (variant, frequency) in log.variants() {
+                match constraint.check(variant) {
+                    Outcome::Satisfied => statistics.satisfied += frequency,
+                    Outcome::VacuouslySatisfied => statistics.vacuously_satisfied += frequency,
+                    Outcome::Violated => statistics.violated += frequency,
+                }
+            }
+            statistics
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::variant_log::trace;
+
+    fn constraint(template: Template, activities: &[&str]) -> Constraint {
+        Constraint {
+            template,
+            activities: trace(activities),
+        }
+    }
+
+    #[test]
+    fn test_templates() {
+        let t = trace(&["A", "B", "A", "C", "B"]);
+        let check = |template, activities: &[&str]| constraint(template, activities).check(&t);
+
+        assert_eq!(check(Template::Existence2, &["A"]), Outcome::Satisfied);
+        assert_eq!(check(Template::Init, &["B"]), Outcome::Violated);
+        assert_eq!(check(Template::End, &["B"]), Outcome::Satisfied);
+        assert_eq!(check(Template::Response, &["A", "B"]), Outcome::Satisfied);
+        assert_eq!(check(Template::Response, &["D", "B"]), Outcome::VacuouslySatisfied);
+        assert_eq!(check(Template::Precedence, &["C", "B"]), Outcome::Violated);
+        assert_eq!(check(Template::AlternateResponse, &["A", "B"]), Outcome::Satisfied);
+        assert_eq!(check(Template::ChainResponse, &["A", "B"]), Outcome::Violated);
+        assert_eq!(check(Template::ChainPrecedence, &["C", "B"]), Outcome::Violated);
+        assert_eq!(check(Template::NotSuccession, &["C", "A"]), Outcome::Satisfied);
+        assert_eq!(check(Template::NotChainSuccession, &["C", "B"]), Outcome::Violated);
+        assert_eq!(check(Template::ExclusiveChoice, &["A", "D"]), Outcome::Satisfied);
+    }
+
+    #[test]
+    fn test_parse_declare() {
+        let content = "activity A\nactivity B\n# compliance rules\nChain Response[A, B] | |\nExistence[A]\n";
+        let constraints = parse_declare(content).unwrap();
+        assert_eq!(
+            constraints,
+            [
+                constraint(Template::ChainResponse, &["A", "B"]),
+                constraint(Template::Existence, &["A"]),
+            ]
+        );
+
+        let error = parse_declare("activity A\nResponds[A, B]").unwrap_err();
+        assert_eq!(error.position.map(|p| p.line), Some(2));
+        let error = parse_declare("activity A\r\nactivity B\r\nResponds[A, B]").unwrap_err();
+        assert_eq!(error.position.map(|p| (p.line, p.byte)), Some((3, 24)));
+        assert!(parse_declare("Response[A, B] |A.x > 2| |").is_err());
+    }
+
+    #[test]
+    fn test_check_constraints() {
+        let log = VariantLog::from_traces(vec![
+            trace(&["A", "B"]),
+            trace(&["A", "C"]),
+            trace(&["C"]),
+            trace(&["A", "B"]),
+        ]);
+        let statistics = check_constraints(&[constraint(Template::Response, &["A", "B"])], &log);
+        assert_eq!(statistics[0].satisfied, 2);
+        assert_eq!(statistics[0].vacuously_satisfied, 1);
+        assert_eq!(statistics[0].violated, 1);
+        assert_eq!(statistics[0].support(), 0.75);
+        assert!((statistics[0].confidence() - 2.0 / 3.0).abs() < 1e-9);
+    }
+}
@@ -0,0 +1,114 @@
+use std::collections::HashMap;
+
+/// A relation between two activities found by a [`DependencyChecker`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct CustomDependency {
+    /// Short label shown in the matrix, e.g. `res` for "same resource required".
+    pub label: String,
+    /// Share of the relevant traces supporting the relation, between 0 and 1.
+    pub confidence: f64,
+}
+
+impl std::fmt::Display for CustomDependency {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        if self.confidence < 1.0 {
+            write!(f, "{}({:.2})", self.label, self.confidence)
+        } else {
+            write!(f, "{}", self.label)
+        }
+    }
+}
+
+/// The variants of a log together with the variants every activity occurs in, built once per
+/// matrix and shared by all checkers.
+pub struct VariantIndex<'a> {
+    variants: &'a [(Vec<&'a str>, usize)],
+    occurrences: HashMap<&'a str, Vec<usize>>,
+    cases: usize,
+}
+
+impl<'a> VariantIndex<'a> {
+    pub fn new(variants: &'a [(Vec<&'a str>, usize)]) -> Self {
+        let mut occurrences: HashMap<&str, Vec<usize>> = HashMap::new();
+        for (variant_idx, (variant, _)) in variants.iter().enumerate() {
+            for &activity in variant {
+                let indices = occurrences.entry(activity).or_default();
+                if indices.last() != Some(&variant_idx) {
+                    indices.push(variant_idx);
+                }
+            }
+        }
+
+        VariantIndex {
+            variants,
+            occurrences,
+            cases: variants.iter().map(|(_, frequency)| frequency).sum(),
+        }
+    }
+
+    /// All variants with their frequencies.
+    pub fn variants(&self) -> &'a [(Vec<&'a str>, usize)] {
+        self.variants
+    }
+
+    /// The variants containing `activity` at least once, with their frequencies.
+    pub fn variants_with(&self, activity: &str) -> impl Iterator<Item = &'a (Vec<&'a str>, usize)> + '_ {
+        self.occurrences
+            .get(activity)
+            .into_iter()
+            .flatten()
+            .map(|&variant_idx| &self.variants[variant_idx])
+    }
+
+    /// Total number of cases.
+    pub fn cases(&self) -> usize {
+        self.cases
+    }
+}
+
+/// A domain-specific relation between two activities, checked for every activity pair of the
+/// dependency matrix in addition to the temporal and existential dependencies.
+///
+/// ```
+/// use egypt::dependency_types::custom::{CustomDependency, DependencyChecker, VariantIndex};
+///
+/// /// `from` is always repeated after `to`.
+/// struct Rework;
+///
+/// impl DependencyChecker for Rework {
+///     fn check(&self, from: &str, to: &str, log: &VariantIndex) -> Option<CustomDependency> {
+///         let (mut relevant, mut supporting) = (0, 0);
+///         for (variant, frequency) in log.variants_with(to) {
+///             relevant += frequency;
+///             let first_to = variant.iter().position(|a| *a == to).unwrap();
+///             if variant[first_to..].contains(&from) {
+///                 supporting += frequency;
+///             }
+///         }
+///         (supporting > 0).then(|| CustomDependency {
+///             label: "rw".to_string(),
+///             confidence: supporting as f64 / relevant as f64,
+///         })
+///     }
+/// }
+/// ```
+pub trait DependencyChecker {
+    fn check(&self, from: &str, to: &str, log: &VariantIndex) -> Option<CustomDependency>;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_variant_index() {
+        let variants = vec![(vec!["A", "B", "A"], 2), (vec!["C"], 1), (vec!["B"], 3)];
+        let index = VariantIndex::new(&variants);
+
+        assert_eq!(index.cases(), 6);
+        let with_a: Vec<_> = index.variants_with("A").collect();
+        assert_eq!(with_a, [&variants[0]]);
+        assert_eq!(index.variants_with("B").map(|(_, f)| f).sum::<usize>(), 5);
+        assert_eq!(index.variants_with("D").count(), 0);
+    }
+}
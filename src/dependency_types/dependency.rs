@@ -1,3 +1,4 @@
+use crate::dependency_types::custom::CustomDependency;
 use crate::dependency_types::existential::ExistentialDependency;
 use crate::dependency_types::temporal::TemporalDependency;
 
@@ -7,6 +8,12 @@ pub struct Dependency {
     pub to: String,
     pub temporal_dependency: Option<TemporalDependency>,
     pub existential_dependency: Option<ExistentialDependency>,
+    /// Relations found by user-supplied checkers, see
+    /// [`crate::dependency_types::custom::DependencyChecker`].
+    pub custom_dependencies: Vec<CustomDependency>,
+    /// p-value of the existential dependency, if it was tested for significance, see
+    /// [`crate::dependency_types::significance`].
+    pub existential_p_value: Option<f64>,
 }
 
 impl Dependency {
@@ -21,6 +28,8 @@ impl Dependency {
             to,
             temporal_dependency,
             existential_dependency,
+            custom_dependencies: Vec::new(),
+            existential_p_value: None,
         }
     }
 }
@@ -34,18 +43,28 @@ impl std::fmt::Display for Dependency {
     /// - If only `temporal_dependency` is present, it writes it followed by a comma and a dash.
     /// - If only `existential_dependency` is present, it writes a dash followed by the dependency.
     /// - If neither dependency is present, it writes "None".
+    ///
+    /// The p-value of a tested existential dependency follows it in parentheses. Custom
+    /// dependencies are appended, each preceded by a comma.
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         let temporal_dep = self.temporal_dependency.as_ref().map(|dep| dep.to_string());
         let existential_dep = self
             .existential_dependency
             .as_ref()
-            .map(|dep| dep.to_string());
+            .map(|dep| match self.existential_p_value {
+                Some(p_value) => format!("{}(p={:.3})", dep, p_value),
+                None => dep.to_string(),
+            });
 
         match (temporal_dep, existential_dep) {
-            (Some(t), Some(e)) => write!(f, "{},{}", t, e),
-            (Some(t), None) => write!(f, "{},-", t),
-            (None, Some(e)) => write!(f, "-,{}", e),
-            (None, None) => write!(f, "None"),
+            (Some(t), Some(e)) => write!(f, "{},{}", t, e)?,
+            (Some(t), None) => write!(f, "{},-", t)?,
+            (None, Some(e)) => write!(f, "-,{}", e)?,
+            (None, None) => write!(f, "None")?,
+        }
+        for custom in &self.custom_dependencies {
+            write!(f, ",{}", custom)?;
         }
+        Ok(())
     }
 }
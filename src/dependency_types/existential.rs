@@ -1,3 +1,9 @@
+use crate::dependency_types::significance::ContingencyTable;
+use crate::dependency_types::with_unit_weights;
+use egypt_core::existential::classify;
+
+pub use egypt_core::existential::{DependencyType, Direction};
+
 #[derive(Debug, Clone, PartialEq)]
 pub struct ExistentialDependency {
     pub from: String,
@@ -36,42 +42,12 @@ impl std::fmt::Display for ExistentialDependency {
     }
 }
 
-#[derive(Debug, Clone, PartialEq, Eq)]
-pub enum Direction {
-    Forward,
-    Backward,
-    Both,
-}
-
-#[allow(dead_code)]
-#[derive(PartialEq, Eq, Debug, Clone)]
-pub enum DependencyType {
-    Implication,
-    Equivalence,
-    NegatedEquivalence,
-    Nand,
-    Or,
-}
-
-impl std::fmt::Display for DependencyType {
-    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
-        match self {
-            DependencyType::Implication => write!(f, "⇒"),
-            DependencyType::Equivalence => write!(f, "⇔"),
-            DependencyType::NegatedEquivalence => write!(f, "⇎"),
-            DependencyType::Nand => write!(f, "⊼"),
-            DependencyType::Or => write!(f, "∨"),
-        }
-    }
-}
-
-// TODO: NAND and OR dependencies
 /// Checks for an existential dependency between two activities within a set of traces.
 ///
 /// This function analyzes the given traces to determine if there is an existential dependency
 /// between the `from` and `to` activities based on the specified threshold. It considers
-/// implications, equivalences, and negated equivalences to identify the type and direction
-/// of the dependency (in that order).
+/// implications, equivalences, negated equivalences, NAND and OR relations to identify the type
+/// and direction of the dependency (in that order).
 ///
 /// # Arguments
 ///
@@ -89,11 +65,30 @@ pub fn check_existential_dependency(
     traces: &[Vec<&str>],
     threshold: f64,
 ) -> Option<ExistentialDependency> {
-    assert!(
-        (0.0..=1.0).contains(&threshold),
-        "Threshold must be between 0 and 1"
-    );
+    check_existential_dependency_weighted(from, to, &with_unit_weights(traces), threshold)
+}
 
+/// Same as [`check_existential_dependency`], but operates on variants weighted by their frequency.
+///
+/// Every `(variant, frequency)` pair counts as `frequency` traces, so the result is identical
+/// to running the unweighted check on the expanded log while only scanning each variant once.
+///
+/// # Arguments
+///
+/// * `from` - The name of the starting activity.
+/// * `to` - The name of the target activity.
+/// * `variants` - The unique traces of the log together with how often each of them occurs.
+/// * `threshold` - A threshold value to determine if the dependency is significant.
+///
+/// # Returns
+///
+/// An `Option` containing an `ExistentialDependency` if a dependency is found, otherwise `None`.
+pub fn check_existential_dependency_weighted(
+    from: &str,
+    to: &str,
+    variants: &[(Vec<&str>, usize)],
+    threshold: f64,
+) -> Option<ExistentialDependency> {
     // if from == to {
     //     // first check if >2
     //     if traces.len() > 2 {
@@ -120,83 +115,39 @@ pub fn check_existential_dependency(
         // TODO: instead of traces.len(), we should use the number of from activities in traces
     // }
 
-    let implication = has_implication(from, to, traces, threshold);
-
-    if implication || has_implication(to, from, traces, threshold) {
-        return Some(ExistentialDependency {
-            from: from.to_string(),
-            to: to.to_string(),
-            dependency_type: if implication && has_implication(to, from, traces, threshold) {
-                DependencyType::Equivalence
-            } else {
-                DependencyType::Implication
-            },
-            direction: if implication {
-                Direction::Forward
-            } else {
-                Direction::Backward
-            },
-        });
-    }
-
-    let negated_equivalence = negated_equivalence(from, to, traces, threshold);
-
-    if negated_equivalence {
-        return Some(ExistentialDependency {
-            from: from.to_string(),
-            to: to.to_string(),
-            dependency_type: DependencyType::NegatedEquivalence,
-            direction: Direction::Forward,
-        });
-    }
-
-    None
+    check_existential_dependency_from_table(
+        from,
+        to,
+        &ContingencyTable::from_variants(from, to, variants),
+        threshold,
+    )
 }
 
-/// Checks if there is an implication relationship between two events within a set of event traces.
-///
-/// # Parameters
-/// - `from`: The event that implies the occurrence of another event.
-/// - `to`: The event that is implied by the occurrence of the `from` event.
-/// - `event_names`: A vector of vectors, where each inner vector represents a sequence of event names (a trace).
-/// - `threshold`: A threshold value between 0 and 1 that determines the minimum proportion of valid traces required to confirm the implication.
-///
-/// # Returns
-/// - `true` if the proportion of valid traces is greater than or equal to the threshold, indicating that the implication holds.
-/// - `false` otherwise.
-fn has_implication(from: &str, to: &str, event_names: &[Vec<&str>], threshold: f64) -> bool {
-    let total_traces = event_names.len();
-    let valid_traces = event_names
-        .iter()
-        .filter(|trace| {
-            if trace.contains(&from) {
-                trace.contains(&to)
-            } else {
-                true
-            }
-        })
-        .count();
-    valid_traces as f64 / total_traces as f64 >= threshold
-}
-
-fn negated_equivalence(from: &str, to: &str, event_names: &[Vec<&str>], threshold: f64) -> bool {
-    let total_traces = event_names.len();
-    let valid_traces = event_names
-        .iter()
-        .filter(|trace| {
-            if trace.contains(&from) {
-                !trace.contains(&to)
-            } else {
-                true
-            }
-        })
-        .count();
-    valid_traces as f64 / total_traces as f64 >= threshold
+/// Same as [`check_existential_dependency_weighted`], but from the contingency table of the two
+/// activities, e.g. computed for all pairs at once by
+/// [`crate::dependency_types::presence::ActivityPresence`].
+pub fn check_existential_dependency_from_table(
+    from: &str,
+    to: &str,
+    table: &ContingencyTable,
+    threshold: f64,
+) -> Option<ExistentialDependency> {
+    classify(table, threshold).map(|(dependency_type, direction)| ExistentialDependency {
+        from: from.to_string(),
+        to: to.to_string(),
+        dependency_type,
+        direction,
+    })
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use egypt_core::existential::implies;
+
+    fn has_implication(from: &str, to: &str, variants: &[(Vec<&str>, usize)], threshold: f64) -> bool {
+        implies(&ContingencyTable::from_variants(from, to, variants), threshold)
+    }
 
     #[test]
     fn test_has_implication() {
@@ -206,6 +157,7 @@ mod tests {
             vec!["A", "E", "D"],
             vec!["A", "D"],
         ];
+        let event_names = with_unit_weights(&event_names);
         let activities = ["A", "B", "C", "D", "E"];
         let pairs = vec![
             ("A", "D"),
@@ -241,6 +193,7 @@ mod tests {
             vec!["A", "D"],
             vec!["A", "C"], // Noise: D is missing
         ];
+        let event_names = with_unit_weights(&event_names);
         assert!(has_implication("A", "D", &event_names, 0.8));
         assert!(!has_implication("A", "D", &event_names, 1.0));
     }
@@ -258,6 +211,37 @@ mod tests {
         assert_eq!(expected, actual);
     }
 
+    #[test]
+    fn test_weighted_matches_expanded_log() {
+        let expanded = vec![
+            vec!["A", "B"],
+            vec!["A", "B"],
+            vec!["A", "B"],
+            vec!["A", "C"],
+        ];
+        let weighted = vec![(vec!["A", "B"], 3), (vec!["A", "C"], 1)];
+        for threshold in [0.5, 0.75, 1.0] {
+            assert_eq!(
+                check_existential_dependency("A", "B", &expanded, threshold),
+                check_existential_dependency_weighted("A", "B", &weighted, threshold)
+            );
+        }
+        assert_eq!(
+            Some(DependencyType::Equivalence),
+            check_existential_dependency_weighted("A", "B", &weighted, 0.75)
+                .map(|dep| dep.dependency_type)
+        );
+        assert_eq!(
+            Some(ExistentialDependency::new(
+                "A",
+                "B",
+                DependencyType::Implication,
+                Direction::Backward,
+            )),
+            check_existential_dependency_weighted("A", "B", &weighted, 1.0)
+        );
+    }
+
     // #[test]
     // fn test_same_activity_existential_2() {
     //     let traces = vec![vec!["A", "B", "C", "A", "A"]];
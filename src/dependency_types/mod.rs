@@ -1,3 +1,13 @@
+pub mod custom;
 pub mod dependency;
 pub mod existential;
-pub mod temporal;
\ No newline at end of file
+pub mod presence;
+pub mod significance;
+pub mod temporal;
+
+/// Turns a list of traces into `(trace, frequency)` pairs with every frequency set to 1.
+///
+/// Used by the unweighted dependency checks to delegate to their variant-weighted counterparts.
+pub(crate) fn with_unit_weights<'a>(traces: &[Vec<&'a str>]) -> Vec<(Vec<&'a str>, usize)> {
+    traces.iter().map(|trace| (trace.clone(), 1)).collect()
+}
@@ -0,0 +1,207 @@
+use std::collections::HashMap;
+
+use crate::dependency_types::significance::ContingencyTable;
+
+const WORD_BITS: usize = u64::BITS as usize;
+
+// A set of indices, one bit each
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+struct Bitset(Vec<u64>);
+
+impl Bitset {
+    fn new(len: usize) -> Self {
+        Bitset(vec![0; len.div_ceil(WORD_BITS)])
+    }
+
+    fn insert(&mut self, idx: usize) {
+        self.0[idx / WORD_BITS] |= 1 << (idx % WORD_BITS);
+    }
+
+    // The indices of the set bits
+    fn ones(&self) -> impl Iterator<Item = usize> + '_ {
+        self.0.iter().enumerate().flat_map(|(word_idx, &word)| {
+            let mut word = word;
+            std::iter::from_fn(move || {
+                (word != 0).then(|| {
+                    let bit = word.trailing_zeros() as usize;
+                    word &= word - 1;
+                    word_idx * WORD_BITS + bit
+                })
+            })
+        })
+    }
+}
+
+/// Which activities occur in which traces, stored as bitsets, to compute the contingency
+/// tables of the existential dependencies of all activity pairs.
+///
+/// Every trace is reduced to a bitset of the activities it contains (one bit per activity);
+/// traces with the same bitset form one presence pattern weighted by their number. Every
+/// activity then gets a bitset of the patterns it occurs in, so the traces containing both
+/// activities of a pair are a bitwise AND of two bitsets instead of a scan over all traces, and
+/// the traces containing either of them a bitwise OR. The NAND and OR relations of the matrix
+/// follow from them: the traces without both are the complement of the AND, the ones with
+/// neither the complement of the OR.
+#[derive(Debug, Clone)]
+pub struct ActivityPresence {
+    activity_index: HashMap<String, usize>,
+    // for every activity, the patterns it occurs in
+    patterns_of: Vec<Bitset>,
+    // number of traces per pattern
+    weights: Vec<usize>,
+    // number of traces every activity occurs in
+    counts: Vec<usize>,
+    total: usize,
+}
+
+impl ActivityPresence {
+    pub fn new(variants: &[(Vec<&str>, usize)]) -> Self {
+        let mut activity_index: HashMap<String, usize> = HashMap::new();
+        for (variant, _) in variants {
+            for &activity in variant {
+                if !activity_index.contains_key(activity) {
+                    activity_index.insert(activity.to_string(), activity_index.len());
+                }
+            }
+        }
+
+        let mut pattern_index: HashMap<Bitset, usize> = HashMap::new();
+        let mut patterns: Vec<Bitset> = Vec::new();
+        let mut weights: Vec<usize> = Vec::new();
+        for (variant, frequency) in variants {
+            let mut pattern = Bitset::new(activity_index.len());
+            for activity in variant {
+                pattern.insert(activity_index[*activity]);
+            }
+            let pattern_idx = *pattern_index.entry(pattern.clone()).or_insert_with(|| {
+                patterns.push(pattern);
+                weights.push(0);
+                weights.len() - 1
+            });
+            weights[pattern_idx] += frequency;
+        }
+
+        let mut patterns_of = vec![Bitset::new(patterns.len()); activity_index.len()];
+        let mut counts = vec![0; activity_index.len()];
+        for (pattern_idx, pattern) in patterns.iter().enumerate() {
+            for activity_idx in pattern.ones() {
+                patterns_of[activity_idx].insert(pattern_idx);
+                counts[activity_idx] += weights[pattern_idx];
+            }
+        }
+
+        ActivityPresence {
+            activity_index,
+            patterns_of,
+            weights,
+            counts,
+            total: variants.iter().map(|(_, frequency)| frequency).sum(),
+        }
+    }
+
+    /// Total number of traces.
+    pub fn traces(&self) -> usize {
+        self.total
+    }
+
+    /// Number of traces `activity` occurs in.
+    pub fn count(&self, activity: &str) -> usize {
+        self.activity_index
+            .get(activity)
+            .map_or(0, |&idx| self.counts[idx])
+    }
+
+    /// Number of traces containing both `from` and `to` (AND).
+    pub fn both(&self, from: &str, to: &str) -> usize {
+        self.combined(from, to, |a, b| a & b)
+    }
+
+    /// Number of traces containing at least one of `from` and `to` (OR).
+    pub fn either(&self, from: &str, to: &str) -> usize {
+        self.combined(from, to, |a, b| a | b)
+    }
+
+    /// Number of traces not containing both `from` and `to` (NAND).
+    pub fn not_both(&self, from: &str, to: &str) -> usize {
+        self.total - self.both(from, to)
+    }
+
+    // Number of traces of the patterns in `op` of the patterns of the two activities
+    fn combined(&self, from: &str, to: &str, op: impl Fn(u64, u64) -> u64) -> usize {
+        let empty = Bitset::new(self.weights.len());
+        let patterns_of = |activity: &str| {
+            self.activity_index
+                .get(activity)
+                .map_or(&empty, |&idx| &self.patterns_of[idx])
+        };
+        let (from, to) = (&patterns_of(from).0, &patterns_of(to).0);
+        let combined = Bitset(from.iter().zip(to).map(|(&a, &b)| op(a, b)).collect());
+        combined
+            .ones()
+            .map(|pattern_idx| self.weights[pattern_idx])
+            .sum()
+    }
+
+    /// The same table as [`ContingencyTable::from_variants`].
+    pub fn table(&self, from: &str, to: &str) -> ContingencyTable {
+        let (both, either) = (self.both(from, to), self.either(from, to));
+        ContingencyTable {
+            both,
+            only_from: self.count(from) - both,
+            only_to: self.count(to) - both,
+            neither: self.total - either,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_matches_trace_scan() {
+        // more than 64 activities, so the presence patterns span several words
+        let activities: Vec<String> = (0..100).map(|idx| format!("a{}", idx)).collect();
+        let variants: Vec<(Vec<&str>, usize)> = (0..100)
+            .map(|idx| {
+                let variant = activities
+                    .iter()
+                    .enumerate()
+                    .filter(|(activity_idx, _)| (activity_idx * 7 + idx) % (idx % 5 + 2) == 0)
+                    .map(|(_, activity)| activity.as_str())
+                    .collect();
+                (variant, idx % 3 + 1)
+            })
+            .collect();
+
+        let presence = ActivityPresence::new(&variants);
+        assert_eq!(presence.traces(), 199);
+        for from in ["a0", "a1", "a63", "a64", "a99", "unknown"] {
+            for to in ["a0", "a2", "a65", "a98", "unknown"] {
+                assert_eq!(
+                    presence.table(from, to),
+                    ContingencyTable::from_variants(from, to, &variants),
+                    "{} {}",
+                    from,
+                    to
+                );
+                assert_eq!(
+                    presence.not_both(from, to),
+                    variants
+                        .iter()
+                        .filter(|(variant, _)| !(variant.contains(&from) && variant.contains(&to)))
+                        .map(|(_, frequency)| frequency)
+                        .sum::<usize>()
+                );
+                assert_eq!(
+                    presence.either(from, to),
+                    variants
+                        .iter()
+                        .filter(|(variant, _)| variant.contains(&from) || variant.contains(&to))
+                        .map(|(_, frequency)| frequency)
+                        .sum::<usize>()
+                );
+            }
+        }
+    }
+}
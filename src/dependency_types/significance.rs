@@ -0,0 +1,188 @@
+use serde::{Deserialize, Serialize};
+
+pub use egypt_core::existential::ContingencyTable;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum SignificanceTest {
+    /// Pearson's chi-square test, only reliable if all expected counts are at least about 5.
+    ChiSquare,
+    /// Fisher's exact test (two-sided), exact for any sample size.
+    #[default]
+    FisherExact,
+}
+
+/// Testing whether the co-occurrence behind an existential dependency could be a coincidence.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Significance {
+    pub test: SignificanceTest,
+    /// Existential dependencies with a larger p-value are suppressed.
+    pub alpha: f64,
+}
+
+impl Default for Significance {
+    fn default() -> Self {
+        Significance {
+            test: SignificanceTest::default(),
+            alpha: 0.05,
+        }
+    }
+}
+
+impl Significance {
+    /// Returns the p-value of the null hypothesis that the occurrences of the two activities
+    /// are independent, or `None` for a degenerate table.
+    pub fn p_value(&self, table: &ContingencyTable) -> Option<f64> {
+        if table.is_degenerate() {
+            return None;
+        }
+        Some(match self.test {
+            SignificanceTest::ChiSquare => chi_square_p_value(table),
+            SignificanceTest::FisherExact => fisher_exact_p_value(table),
+        })
+    }
+}
+
+/// Pearson's chi-square test with one degree of freedom.
+pub fn chi_square_p_value(table: &ContingencyTable) -> f64 {
+    let (from, not_from, to, not_to) = table.margins();
+    let n = table.total() as f64;
+    let cells = [
+        (table.both, from, to),
+        (table.only_from, from, not_to),
+        (table.only_to, not_from, to),
+        (table.neither, not_from, not_to),
+    ];
+    let statistic: f64 = cells
+        .iter()
+        .map(|&(observed, row, column)| {
+            let expected = row as f64 * column as f64 / n;
+            (observed as f64 - expected).powi(2) / expected
+        })
+        .sum();
+    // survival function of the chi-square distribution with one degree of freedom
+    erfc((statistic / 2.0).sqrt())
+}
+
+/// Fisher's exact test, summing the probabilities of all tables with the same margins that are
+/// at most as likely as the observed one.
+pub fn fisher_exact_p_value(table: &ContingencyTable) -> f64 {
+    let (from, not_from, to, _) = table.margins();
+    let n = table.total();
+    // probability of a table with `both` co-occurrences (hypergeometric distribution)
+    let ln_probability = |both: usize| {
+        ln_binomial(from, both) + ln_binomial(not_from, to - both) - ln_binomial(n, to)
+    };
+
+    let observed = ln_probability(table.both);
+    let min_both = to.saturating_sub(not_from);
+    let max_both = from.min(to);
+    let p: f64 = (min_both..=max_both)
+        .map(ln_probability)
+        // relative tolerance against rounding errors of equally likely tables
+        .filter(|&ln_p| ln_p <= observed + 1e-7)
+        .map(f64::exp)
+        .sum();
+    p.min(1.0)
+}
+
+fn ln_binomial(n: usize, k: usize) -> f64 {
+    ln_gamma(n as f64 + 1.0) - ln_gamma(k as f64 + 1.0) - ln_gamma((n - k) as f64 + 1.0)
+}
+
+// Lanczos approximation (g = 7), accurate to about 15 digits for positive arguments
+fn ln_gamma(x: f64) -> f64 {
+    const COEFFICIENTS: [f64; 9] = [
+        0.999_999_999_999_809_9,
+        676.520_368_121_885_1,
+        -1_259.139_216_722_402_8,
+        771.323_428_777_653_1,
+        -176.615_029_162_140_6,
+        12.507_343_278_686_905,
+        -0.138_571_095_265_720_12,
+        9.984_369_578_019_572e-6,
+        1.505_632_735_149_311_6e-7,
+    ];
+    let x = x - 1.0;
+    let t = x + 7.5;
+    let series = COEFFICIENTS[1..]
+        .iter()
+        .enumerate()
+        .fold(COEFFICIENTS[0], |sum, (i, c)| {
+            sum + c / (x + i as f64 + 1.0)
+        });
+    0.5 * (2.0 * std::f64::consts::PI).ln() + (x + 0.5) * t.ln() - t + series.ln()
+}
+
+// Complementary error function (Numerical Recipes' erfcc, relative error below 1.2e-7)
+pub(crate) fn erfc(x: f64) -> f64 {
+    let z = x.abs();
+    let t = 1.0 / (1.0 + 0.5 * z);
+    let polynomial = [
+        -1.265_512_23,
+        1.000_023_68,
+        0.374_091_96,
+        0.096_784_18,
+        -0.186_288_06,
+        0.278_868_07,
+        -1.135_203_98,
+        1.488_515_87,
+        -0.822_152_23,
+        0.170_872_77,
+    ]
+    .iter()
+    .rev()
+    .fold(0.0, |sum, c| c + t * sum);
+    let result = t * (-z * z + polynomial).exp();
+    if x >= 0.0 {
+        result
+    } else {
+        2.0 - result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn table(both: usize, only_from: usize, only_to: usize, neither: usize) -> ContingencyTable {
+        ContingencyTable {
+            both,
+            only_from,
+            only_to,
+            neither,
+        }
+    }
+
+    #[test]
+    fn test_fisher_exact() {
+        // Fisher's tea tasting experiment
+        assert!((fisher_exact_p_value(&table(3, 1, 1, 3)) - 0.4857).abs() < 1e-4);
+        assert!((fisher_exact_p_value(&table(10, 0, 0, 10)) - 1.0825e-5).abs() < 1e-8);
+        assert!((fisher_exact_p_value(&table(2, 3, 3, 2)) - 1.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_chi_square() {
+        assert!((chi_square_p_value(&table(15, 5, 5, 15)) - 0.0016).abs() < 1e-4);
+        // statistic 4.0 -> p = 0.0455
+        assert!((chi_square_p_value(&table(30, 20, 20, 30)) - 0.0455).abs() < 1e-4);
+    }
+
+    #[test]
+    fn test_degenerate_table() {
+        let variants = vec![(vec!["A", "B"], 3), (vec!["A"], 2)];
+        let table = ContingencyTable::from_variants("A", "B", &variants);
+        assert_eq!(
+            table,
+            ContingencyTable {
+                both: 3,
+                only_from: 2,
+                only_to: 0,
+                neither: 0
+            }
+        );
+        assert_eq!(Significance::default().p_value(&table), None);
+    }
+}
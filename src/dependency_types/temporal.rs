@@ -1,5 +1,9 @@
+use crate::dependency_types::with_unit_weights;
+use egypt_core::temporal::{position_dependencies, trace_dependencies, PairStatistics};
 use log::{debug, info};
-use std::cmp::Ordering;
+use std::collections::HashMap;
+
+pub use egypt_core::temporal::{DependencyType, Direction};
 
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct TemporalDependency {
@@ -34,27 +38,6 @@ impl std::fmt::Display for TemporalDependency {
     }
 }
 
-#[derive(Debug, Clone, PartialEq, Eq)]
-pub enum Direction {
-    Forward,
-    Backward,
-}
-
-#[derive(Debug, Clone, PartialEq, Eq)]
-pub enum DependencyType {
-    Direct,
-    Eventual,
-}
-
-impl std::fmt::Display for DependencyType {
-    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
-        match self {
-            DependencyType::Direct => write!(f, "d"),
-            DependencyType::Eventual => write!(f, ""),
-        }
-    }
-}
-
 /// Checks for temporal dependencies between two activities across multiple traces.
 ///
 /// # Parameters
@@ -62,8 +45,8 @@ impl std::fmt::Display for DependencyType {
 /// - `to`: The ending activity in the dependency.
 /// - `traces`: A list of traces where each trace is an ordered sequence of activities.
 /// - `threshold`: The ratio threshold for considering the dependency direction.
-///    (for example, a threshold of 0.8 would mean that the dependency would be considered
-///    a Direct dependency if it is found in at least 80% of the traces)
+///   (for example, a threshold of 0.8 would mean that the dependency would be considered
+///   a Direct dependency if it is found in at least 80% of the traces)
 ///
 /// # Returns
 /// An `Option` containing the `TemporalDependency` if a dependency is found; otherwise, `None`.
@@ -72,15 +55,36 @@ pub fn check_temporal_dependency(
     to: &str,
     traces: &[Vec<&str>],
     threshold: f64,
+) -> Option<TemporalDependency> {
+    check_temporal_dependency_weighted(from, to, &with_unit_weights(traces), threshold)
+}
+
+/// Same as [`check_temporal_dependency`], but operates on variants weighted by their frequency.
+///
+/// Each variant is only scanned once; the dependencies found in it are counted `frequency` times.
+///
+/// # Parameters
+/// - `from`: The starting activity in the dependency.
+/// - `to`: The ending activity in the dependency.
+/// - `variants`: The unique traces of the log together with how often each of them occurs.
+/// - `threshold`: The ratio threshold for considering the dependency direction.
+///
+/// # Returns
+/// An `Option` containing the `TemporalDependency` if a dependency is found; otherwise, `None`.
+pub fn check_temporal_dependency_weighted(
+    from: &str,
+    to: &str,
+    variants: &[(Vec<&str>, usize)],
+    threshold: f64,
 ) -> Option<TemporalDependency> {
     info!("Checking temporal dependency for {} -> {}", from, to);
     let mut dependencies = Vec::new();
 
-    for (i, trace) in traces.iter().enumerate() {
-        debug!("Checking trace {}: {:?}", i, trace);
+    for (i, (trace, count)) in variants.iter().enumerate() {
+        debug!("Checking variant {} (x{}): {:?}", i, count, trace);
         let trace_deps = check_trace_dependency(from, to, trace);
-        debug!("Trace {} dependencies: {:?}", i, trace_deps);
-        dependencies.extend(trace_deps);
+        debug!("Variant {} dependencies: {:?}", i, trace_deps);
+        dependencies.extend(trace_deps.into_iter().map(|dep| (dep, *count)));
     }
 
     debug!("All dependencies: {:?}", dependencies);
@@ -97,98 +101,14 @@ pub fn check_temporal_dependency(
 /// - `trace`: A single trace (ordered sequence of activities).
 ///
 /// # Returns
-/// A vector of tuples where each tuple contains the `DependencyType` and `Direction`.
-///
-/// Note: this is where the logic for determining the types and directions of the dependencies
-/// is implemented.
+/// A vector of tuples where each tuple contains the `DependencyType` and `Direction`, see
+/// [`egypt_core::temporal::trace_dependencies`].
 fn check_trace_dependency(
     from: &str,
     to: &str,
     trace: &[&str],
 ) -> Vec<(DependencyType, Direction)> {
-
-    let mut result = Vec::new();
-    let mut from_positions: Vec<usize> = Vec::new();
-    let mut to_positions: Vec<usize> = Vec::new();
-
-    // get the indexes of each `from` and each `to` activities
-    for (i, activity) in trace.iter().enumerate() {
-        if activity == &from {
-            from_positions.push(i);
-        } else if activity == &to {
-            to_positions.push(i);
-        }
-    }
-
-    let mut from_index = 0;
-    let mut to_index = 0;
-
-
-    // edge case for when `from` and `to` are the same
-    if from == to {
-        // check >2
-        if from_positions.len() > 2 {
-            result.push((DependencyType::Eventual, Direction::Forward));
-        } else if from_positions[0] + 1 == from_positions[1] {
-            // check if activity in between
-            result.push((DependencyType::Direct, Direction::Forward));
-        }
-    }
-
-    // iterate through the `from` and `to` positions except for the last one
-    while from_index < from_positions.len() && to_index < to_positions.len() {
-        let from_pos = from_positions[from_index];
-        let to_pos = to_positions[to_index];
-
-        match from_pos.cmp(&to_pos) {
-            Ordering::Less => {
-                let dependency_type = if to_pos - from_pos == 1 {
-                    DependencyType::Direct
-                } else {
-                    DependencyType::Eventual
-                };
-                result.push((dependency_type, Direction::Forward));
-                from_index += 1;
-                to_index += 1;
-            }
-            Ordering::Greater => {
-                let dependency_type = if from_pos - to_pos == 1 {
-                    DependencyType::Direct
-                } else {
-                    DependencyType::Eventual
-                };
-                result.push((dependency_type, Direction::Backward));
-                to_index += 1;
-            }
-            Ordering::Equal => unreachable!(),
-        }
-    }
-
-    // handle remaining 'from' activities
-    while from_index < from_positions.len() {
-        if to_positions
-            .last()
-            .map_or(false, |&last_to| last_to > from_positions[from_index])
-        {
-            result.push((DependencyType::Eventual, Direction::Forward));
-        }
-        from_index += 1;
-    }
-
-    // handle remaining 'to' activities
-    while to_index < to_positions.len() {
-        if from_positions
-            .last()
-            .map_or(false, |&last_from| last_from < to_positions[to_index])
-        {
-            result.push((DependencyType::Eventual, Direction::Forward));
-        } else {
-            result.push((DependencyType::Eventual, Direction::Backward));
-        }
-        to_index += 1;
-    }
-
-    result
+    trace_dependencies(&from, &to, trace)
 }
 
 /// Classifies the dependencies based on their ratio to determine the overall dependency.
@@ -196,7 +116,7 @@ fn check_trace_dependency(
 /// # Parameters
 /// - `from`: The starting activity in the dependency.
 /// - `to`: The ending activity in the dependency.
-/// - `dependencies`: A vector of dependencies found in the traces.
+/// - `dependencies`: A vector of dependencies found in the traces, each with the number of traces it was found in.
 /// - `threshold`: The ratio threshold for determining the direction of the dependency.
 ///
 /// # Returns
@@ -204,46 +124,120 @@ fn check_trace_dependency(
 fn classify_dependencies(
     from: &str,
     to: &str,
-    dependencies: Vec<(DependencyType, Direction)>,
+    dependencies: Vec<((DependencyType, Direction), usize)>,
     threshold: f64,
 ) -> Option<TemporalDependency> {
     if dependencies.is_empty() {
         return None;
     }
 
-    let total_count = dependencies.len() as f64;
-    let forward_count = dependencies
-        .iter()
-        .filter(|(_, dir)| *dir == Direction::Forward)
-        .count() as f64;
-    let backward_count = total_count - forward_count;
-
-    let forward_ratio = forward_count / total_count;
-    let backward_ratio = backward_count / total_count;
-
-    let direction = if forward_ratio >= threshold {
-        Direction::Forward
-    } else if backward_ratio >= threshold {
-        Direction::Backward
-    } else {
-        return None; // if neither direction meets the threshold, it's independent
-    };
-
-    let dependency_type = if dependencies
-        .iter()
-        .any(|(dep, _)| *dep == DependencyType::Eventual)
-    {
-        DependencyType::Eventual
-    } else {
-        DependencyType::Direct
-    };
-
-    Some(TemporalDependency::new(
-        from,
-        to,
-        dependency_type,
-        direction,
-    ))
+    let mut statistics = PairStatistics::default();
+    for ((dependency_type, direction), count) in dependencies {
+        statistics.add(&dependency_type, &direction, count);
+    }
+    classify_statistics(from, to, &statistics, threshold)
+}
+
+// Same as `classify_dependencies`, from the dependencies already summed up
+fn classify_statistics(
+    from: &str,
+    to: &str,
+    statistics: &PairStatistics,
+    threshold: f64,
+) -> Option<TemporalDependency> {
+    statistics
+        .classify(threshold)
+        .map(|(dependency_type, direction)| {
+            TemporalDependency::new(from, to, dependency_type, direction)
+        })
+}
+
+/// The temporal dependencies of all pairs of different activities, computed in one pass over
+/// the variants.
+///
+/// Every variant is scanned once for the positions of its activities; the relations of each
+/// pair of activities it contains are counted `frequency` times. Looking up a dependency
+/// afterwards doesn't touch the variants again, so the same footprints serve every threshold.
+///
+/// The results are the same as those of [`check_temporal_dependency_weighted`].
+#[derive(Debug, Clone, Default)]
+pub struct TemporalFootprints {
+    // from -> to -> dependencies in the variants containing both activities
+    pairs: HashMap<String, HashMap<String, PairStatistics>>,
+    // from -> to -> occurrences of `to` in the variants containing both activities
+    together: HashMap<String, HashMap<String, usize>>,
+    // occurrences of every activity in the whole log
+    occurrences: HashMap<String, usize>,
+}
+
+impl TemporalFootprints {
+    pub fn new(variants: &[(Vec<&str>, usize)]) -> Self {
+        let mut footprints = TemporalFootprints::default();
+        for (variant, frequency) in variants {
+            let mut positions: Vec<(&str, Vec<usize>)> = Vec::new();
+            for (idx, &activity) in variant.iter().enumerate() {
+                match positions.iter_mut().find(|(other, _)| *other == activity) {
+                    Some((_, activity_positions)) => activity_positions.push(idx),
+                    None => positions.push((activity, vec![idx])),
+                }
+            }
+
+            for (to, to_positions) in &positions {
+                *footprints.occurrences.entry(to.to_string()).or_default() +=
+                    to_positions.len() * frequency;
+                for (from, from_positions) in &positions {
+                    if from == to {
+                        continue;
+                    }
+                    let statistics = footprints
+                        .pairs
+                        .entry(from.to_string())
+                        .or_default()
+                        .entry(to.to_string())
+                        .or_default();
+                    for (dependency_type, direction) in
+                        position_dependencies(from_positions, to_positions)
+                    {
+                        statistics.add(&dependency_type, &direction, *frequency);
+                    }
+                    *footprints
+                        .together
+                        .entry(from.to_string())
+                        .or_default()
+                        .entry(to.to_string())
+                        .or_default() += to_positions.len() * frequency;
+                }
+            }
+        }
+        footprints
+    }
+
+    /// The dependency between two different activities, see [`check_temporal_dependency`].
+    /// Always `None` if `from` and `to` are the same activity.
+    pub fn dependency(&self, from: &str, to: &str, threshold: f64) -> Option<TemporalDependency> {
+        if from == to {
+            return None;
+        }
+        let mut statistics = self
+            .pairs
+            .get(from)
+            .and_then(|pairs| pairs.get(to))
+            .copied()
+            .unwrap_or_default();
+        // every occurrence of `to` in a variant without `from` counts as an eventual backward
+        // dependency
+        let together = self
+            .together
+            .get(from)
+            .and_then(|together| together.get(to))
+            .copied()
+            .unwrap_or(0);
+        let alone = self.occurrences.get(to).copied().unwrap_or(0) - together;
+        if alone > 0 {
+            statistics.add(&DependencyType::Eventual, &Direction::Backward, alone);
+        }
+        classify_statistics(from, to, &statistics, threshold)
+    }
 }
 
 #[cfg(test)]
@@ -396,6 +390,32 @@ mod tests {
         assert_eq!(None, actual);
     }
 
+    #[test]
+    fn test_weighted_matches_expanded_log() {
+        let expanded = vec![
+            vec!["A", "B"],
+            vec!["A", "B"],
+            vec!["A", "B"],
+            vec!["B", "A"],
+        ];
+        let weighted = vec![(vec!["A", "B"], 3), (vec!["B", "A"], 1)];
+        for threshold in [0.5, 0.75, 1.0] {
+            assert_eq!(
+                check_temporal_dependency("A", "B", &expanded, threshold),
+                check_temporal_dependency_weighted("A", "B", &weighted, threshold)
+            );
+        }
+        assert_eq!(
+            Some(TemporalDependency::new(
+                "A",
+                "B",
+                DependencyType::Direct,
+                Direction::Forward,
+            )),
+            check_temporal_dependency_weighted("A", "B", &weighted, 0.75)
+        );
+    }
+
     #[test]
     fn test_same_activity_temporal_1() {
         let traces = vec![vec!["A", "A", "C", "A", "C"]];
@@ -421,4 +441,33 @@ mod tests {
         let actual = check_temporal_dependency("A", "A", &traces, 1.0);
         assert_eq!(expected, actual);
     }
+
+    #[test]
+    fn test_footprints_match_trace_scan() {
+        let variants = vec![
+            (vec!["A", "B", "C", "B"], 3),
+            (vec!["B", "A", "D"], 2),
+            (vec!["C", "C", "A"], 1),
+            (vec!["D"], 4),
+            (vec!["A", "D", "A", "B"], 1),
+        ];
+        let footprints = TemporalFootprints::new(&variants);
+        for from in ["A", "B", "C", "D", "unknown"] {
+            for to in ["A", "B", "C", "D", "unknown"] {
+                if from == to {
+                    continue;
+                }
+                for threshold in [0.5, 0.75, 1.0] {
+                    assert_eq!(
+                        footprints.dependency(from, to, threshold),
+                        check_temporal_dependency_weighted(from, to, &variants, threshold),
+                        "{} {} {}",
+                        from,
+                        to,
+                        threshold
+                    );
+                }
+            }
+        }
+    }
 }
@@ -0,0 +1,208 @@
+//! Aggregating the deviations of the alignments across a log, to see where a process breaks
+//! systematically rather than case by case.
+
+use std::cmp::Reverse;
+use std::collections::{BTreeMap, HashMap};
+
+use crate::alignment::{AlignmentMove, AlignmentReport, TraceAlignment};
+use crate::i18n::{Language, Message};
+use crate::variant_log::VariantLog;
+
+/// Example cases kept per deviation.
+const MAX_EXAMPLES: usize = 3;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum DeviationKind {
+    /// The model required the activity, but the case didn't execute it.
+    Skipped,
+    /// The case executed the activity where the model doesn't allow it.
+    Inserted,
+    /// The case executed the activity, but at another position than the model requires.
+    OutOfOrder,
+}
+
+impl DeviationKind {
+    pub fn message(&self) -> Message {
+        match self {
+            DeviationKind::Skipped => Message::Skipped,
+            DeviationKind::Inserted => Message::Inserted,
+            DeviationKind::OutOfOrder => Message::OutOfOrder,
+        }
+    }
+}
+
+/// How often an activity deviates from the model in one way.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ActivityDeviation {
+    pub activity: String,
+    pub kind: DeviationKind,
+    /// Cases with at least one such deviation.
+    pub cases: usize,
+    /// Deviations over all cases.
+    pub occurrences: usize,
+    /// The first cases with the deviation, in log order.
+    pub examples: Vec<String>,
+}
+
+/// The deviations of one alignment per activity. An activity that is both a log-only and a
+/// model-only move was executed out of order; the remaining log-only moves are insertions
+/// and model-only moves are skips.
+pub fn alignment_deviations(
+    alignment: &TraceAlignment,
+) -> BTreeMap<(String, DeviationKind), usize> {
+    let mut moves: BTreeMap<&str, (usize, usize)> = BTreeMap::new();
+    for step in &alignment.moves {
+        match step {
+            AlignmentMove::LogOnly(activity) => moves.entry(activity).or_default().0 += 1,
+            AlignmentMove::ModelOnly(activity) => moves.entry(activity).or_default().1 += 1,
+            AlignmentMove::Synchronous(_) => {}
+        }
+    }
+    let mut deviations = BTreeMap::new();
+    for (activity, (log_only, model_only)) in moves {
+        let moved = log_only.min(model_only);
+        for (kind, count) in [
+            (DeviationKind::OutOfOrder, moved),
+            (DeviationKind::Inserted, log_only - moved),
+            (DeviationKind::Skipped, model_only - moved),
+        ] {
+            if count > 0 {
+                deviations.insert((activity.to_string(), kind), count);
+            }
+        }
+    }
+    deviations
+}
+
+/// Aggregates the deviations of the aligned variants of `report` over the cases of `log`,
+/// most widespread first. Cases whose variant couldn't be aligned are left out.
+pub fn aggregate_deviations(report: &AlignmentReport, log: &VariantLog) -> Vec<ActivityDeviation> {
+    let by_variant: HashMap<&[String], BTreeMap<(String, DeviationKind), usize>> = report
+        .variants
+        .iter()
+        .filter_map(|(variant, _, alignment)| {
+            alignment
+                .as_ref()
+                .map(|alignment| (variant.as_slice(), alignment_deviations(alignment)))
+        })
+        .collect();
+
+    let mut aggregated: BTreeMap<(String, DeviationKind), ActivityDeviation> = BTreeMap::new();
+    for (case, trace) in log.cases().iter().zip(log.traces()) {
+        let Some(deviations) = by_variant.get(trace) else {
+            continue;
+        };
+        for ((activity, kind), count) in deviations {
+            let deviation = aggregated
+                .entry((activity.clone(), *kind))
+                .or_insert_with(|| ActivityDeviation {
+                    activity: activity.clone(),
+                    kind: *kind,
+                    cases: 0,
+                    occurrences: 0,
+                    examples: Vec::new(),
+                });
+            deviation.cases += 1;
+            deviation.occurrences += count;
+            if deviation.examples.len() < MAX_EXAMPLES {
+                deviation.examples.push(case.case_id.clone());
+            }
+        }
+    }
+
+    let mut deviations: Vec<ActivityDeviation> = aggregated.into_values().collect();
+    // stable, so equally widespread deviations stay ordered by activity and kind
+    deviations.sort_by_key(|deviation| Reverse(deviation.cases));
+    deviations
+}
+
+/// One line per activity and kind of deviation with its number of cases and occurrences and
+/// example cases.
+pub fn report(deviations: &[ActivityDeviation], language: Language) -> String {
+    let mut output = format!(
+        "{:<32}{:<24}{:<12}{:<12}{}\n",
+        Message::Activity.text(language),
+        Message::Deviation.text(language),
+        Message::Cases.text(language),
+        Message::Occurrences.text(language),
+        Message::ExampleCases.text(language)
+    );
+    for deviation in deviations {
+        output.push_str(&format!(
+            "{:<32}{:<24}{:<12}{:<12}{}\n",
+            deviation.activity,
+            deviation.kind.message().text(language),
+            deviation.cases,
+            deviation.occurrences,
+            deviation.examples.join(", ")
+        ));
+    }
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn alignment(moves: &[(&str, char)]) -> TraceAlignment {
+        TraceAlignment {
+            moves: moves
+                .iter()
+                .map(|&(activity, side)| match side {
+                    'l' => AlignmentMove::LogOnly(activity.to_string()),
+                    'm' => AlignmentMove::ModelOnly(activity.to_string()),
+                    _ => AlignmentMove::Synchronous(activity.to_string()),
+                })
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn test_aggregate_deviations() {
+        let trace = |activities: &[&str]| activities.iter().map(|a| a.to_string()).collect();
+        let swapped: Vec<String> = trace(&["B", "A", "C"]);
+        let skipped: Vec<String> = trace(&["A", "C"]);
+        let log = VariantLog::from_traces(vec![
+            swapped.clone(),
+            skipped.clone(),
+            skipped.clone(),
+            trace(&["A", "B", "C"]),
+        ]);
+        let aligned = AlignmentReport {
+            variants: vec![
+                (
+                    swapped,
+                    1,
+                    Some(alignment(&[
+                        ("B", 'l'),
+                        ("A", 's'),
+                        ("B", 'm'),
+                        ("C", 's'),
+                        ("X", 'm'),
+                    ])),
+                ),
+                (
+                    skipped,
+                    2,
+                    Some(alignment(&[("A", 's'), ("B", 'm'), ("C", 's')])),
+                ),
+            ],
+        };
+
+        let deviations = aggregate_deviations(&aligned, &log);
+        let summary: Vec<(&str, DeviationKind, usize)> = deviations
+            .iter()
+            .map(|d| (d.activity.as_str(), d.kind, d.cases))
+            .collect();
+        assert_eq!(
+            summary,
+            [
+                ("B", DeviationKind::Skipped, 2),
+                ("B", DeviationKind::OutOfOrder, 1),
+                ("X", DeviationKind::Skipped, 1),
+            ]
+        );
+        assert_eq!(deviations[0].examples, ["case_1", "case_2"]);
+        assert!(report(&deviations, Language::English).contains("out of order"));
+    }
+}
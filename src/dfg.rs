@@ -0,0 +1,270 @@
+use std::collections::{BTreeMap, HashMap};
+
+use crate::variant_log::VariantLog;
+
+/// A directly-follows graph: how often each activity occurs, directly follows another
+/// activity, and starts or ends a case.
+///
+/// The graph is built incrementally from streams of traces, so a log never has to be held in
+/// memory as a whole:
+///
+/// ```
+/// use egypt::dfg::DirectlyFollowsGraph;
+///
+/// let lines = "A,B,C\nA,C";
+/// let dfg = DirectlyFollowsGraph::from_traces(lines.lines().map(|line| line.split(',')));
+/// assert_eq!(dfg.frequency("A", "B"), 1);
+/// assert_eq!(dfg.frequency("A", "C"), 1);
+/// assert_eq!(dfg.start_frequency("A"), 2);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct DirectlyFollowsGraph {
+    // activities in the order they were first seen, and their index
+    activities: Vec<String>,
+    activity_index: HashMap<String, usize>,
+    activity_frequencies: Vec<usize>,
+    start_frequencies: Vec<usize>,
+    end_frequencies: Vec<usize>,
+    // keyed by activity index, so the edges are ordered like the activities
+    edges: BTreeMap<(usize, usize), usize>,
+}
+
+impl DirectlyFollowsGraph {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Builds the graph from a stream of traces, each a stream of activities.
+    pub fn from_traces<T, S>(traces: impl IntoIterator<Item = T>) -> Self
+    where
+        T: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        let mut dfg = DirectlyFollowsGraph::new();
+        for trace in traces {
+            dfg.add_trace(trace, 1);
+        }
+        dfg
+    }
+
+    /// Builds the graph from the variants of `log`, weighted by their frequency. The traces of
+    /// ongoing cases are added as prefixes, see [`DirectlyFollowsGraph::add_prefix`].
+    pub fn from_variant_log(log: &VariantLog) -> Self {
+        let mut dfg = DirectlyFollowsGraph::new();
+        for (variant, frequency, ongoing) in log.variants_with_ongoing() {
+            dfg.add_trace(variant, frequency - ongoing);
+            if ongoing > 0 {
+                dfg.add_prefix(variant, ongoing);
+            }
+        }
+        dfg
+    }
+
+    /// Adds a trace that occurs `frequency` times.
+    pub fn add_trace<S: AsRef<str>>(
+        &mut self,
+        trace: impl IntoIterator<Item = S>,
+        frequency: usize,
+    ) {
+        self.add(trace, frequency, true);
+    }
+
+    /// Adds the trace of an ongoing case that occurs `frequency` times: like
+    /// [`DirectlyFollowsGraph::add_trace`], but its last activity doesn't end a case.
+    pub fn add_prefix<S: AsRef<str>>(
+        &mut self,
+        trace: impl IntoIterator<Item = S>,
+        frequency: usize,
+    ) {
+        self.add(trace, frequency, false);
+    }
+
+    fn add<S: AsRef<str>>(&mut self, trace: impl IntoIterator<Item = S>, frequency: usize, ends: bool) {
+        let mut previous = None;
+        for activity in trace {
+            let current = self.index(activity.as_ref());
+            self.activity_frequencies[current] += frequency;
+            match previous {
+                Some(previous) => *self.edges.entry((previous, current)).or_default() += frequency,
+                None => self.start_frequencies[current] += frequency,
+            }
+            previous = Some(current);
+        }
+        if let Some(last) = previous.filter(|_| ends) {
+            self.end_frequencies[last] += frequency;
+        }
+    }
+
+    // only allocates for activities that weren't seen before
+    fn index(&mut self, activity: &str) -> usize {
+        if let Some(&idx) = self.activity_index.get(activity) {
+            return idx;
+        }
+        let idx = self.activities.len();
+        self.activities.push(activity.to_string());
+        self.activity_index.insert(activity.to_string(), idx);
+        self.activity_frequencies.push(0);
+        self.start_frequencies.push(0);
+        self.end_frequencies.push(0);
+        idx
+    }
+
+    /// The activities in the order they first occurred.
+    pub fn activities(&self) -> &[String] {
+        &self.activities
+    }
+
+    pub fn activity_frequency(&self, activity: &str) -> usize {
+        self.activity_index
+            .get(activity)
+            .map_or(0, |&idx| self.activity_frequencies[idx])
+    }
+
+    /// How often `to` directly follows `from`.
+    pub fn frequency(&self, from: &str, to: &str) -> usize {
+        match (self.activity_index.get(from), self.activity_index.get(to)) {
+            (Some(&from), Some(&to)) => self.edges.get(&(from, to)).copied().unwrap_or(0),
+            _ => 0,
+        }
+    }
+
+    pub fn start_frequency(&self, activity: &str) -> usize {
+        self.activity_index
+            .get(activity)
+            .map_or(0, |&idx| self.start_frequencies[idx])
+    }
+
+    pub fn end_frequency(&self, activity: &str) -> usize {
+        self.activity_index
+            .get(activity)
+            .map_or(0, |&idx| self.end_frequencies[idx])
+    }
+
+    /// Returns a copy of the graph with only the edges that occur at least `min_frequency`
+    /// times. Activities, starts and ends are kept.
+    pub fn filter_edges(&self, min_frequency: usize) -> DirectlyFollowsGraph {
+        let mut dfg = self.clone();
+        dfg.edges.retain(|_, frequency| *frequency >= min_frequency);
+        dfg
+    }
+
+    /// Returns a copy of the graph with the activities renamed according to `mapping`;
+    /// activities that get the same name are merged into one node, and steps between them
+    /// become a self-loop. Activities without a mapping keep their name.
+    pub fn rename_activities(&self, mapping: &HashMap<String, String>) -> DirectlyFollowsGraph {
+        let mut dfg = DirectlyFollowsGraph::new();
+        let renamed: Vec<usize> = self
+            .activities
+            .iter()
+            .map(|activity| dfg.index(mapping.get(activity).unwrap_or(activity)))
+            .collect();
+        for (idx, &new_idx) in renamed.iter().enumerate() {
+            dfg.activity_frequencies[new_idx] += self.activity_frequencies[idx];
+            dfg.start_frequencies[new_idx] += self.start_frequencies[idx];
+            dfg.end_frequencies[new_idx] += self.end_frequencies[idx];
+        }
+        for (&(from, to), &frequency) in &self.edges {
+            *dfg.edges.entry((renamed[from], renamed[to])).or_default() += frequency;
+        }
+        dfg
+    }
+
+    /// Share of the steps of the traces of `log` (starts, directly-follows pairs and ends,
+    /// weighted by the variant frequencies) that the graph allows; 1 for the graph of the log
+    /// itself, lower the more edges were filtered. 1 for a log without events.
+    pub fn fitness(&self, log: &VariantLog) -> f64 {
+        let mut steps = 0;
+        let mut allowed = 0;
+        for (variant, frequency) in log.variants() {
+            let (Some(first), Some(last)) = (variant.first(), variant.last()) else {
+                continue;
+            };
+            let mut allows = vec![self.start_frequency(first) > 0, self.end_frequency(last) > 0];
+            allows.extend(
+                variant
+                    .windows(2)
+                    .map(|pair| self.frequency(&pair[0], &pair[1]) > 0),
+            );
+            steps += allows.len() * frequency;
+            allowed += allows.iter().filter(|&&allows| allows).count() * frequency;
+        }
+        if steps == 0 {
+            1.0
+        } else {
+            allowed as f64 / steps as f64
+        }
+    }
+
+    /// Iterates over the edges as `(from, to, frequency)`, ordered by the source and then the
+    /// target activity in the order of [`DirectlyFollowsGraph::activities`].
+    pub fn edges(&self) -> impl Iterator<Item = (&str, &str, usize)> {
+        self.edges.iter().map(|(&(from, to), &frequency)| {
+            (
+                self.activities[from].as_str(),
+                self.activities[to].as_str(),
+                frequency,
+            )
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use super::*;
+
+    #[test]
+    fn test_from_traces_and_variants() {
+        let traces = [vec!["A", "B", "C"], vec!["A", "B", "C"], vec!["A", "C"]];
+        let dfg = DirectlyFollowsGraph::from_traces(traces.iter().map(|trace| trace.iter()));
+
+        assert_eq!(dfg.activities(), ["A", "B", "C"]);
+        assert_eq!(dfg.activity_frequency("B"), 2);
+        assert_eq!(dfg.frequency("A", "B"), 2);
+        assert_eq!(dfg.frequency("A", "C"), 1);
+        assert_eq!(dfg.frequency("C", "A"), 0);
+        assert_eq!(dfg.frequency("A", "unknown"), 0);
+        assert_eq!(dfg.end_frequency("C"), 3);
+        let edges: Vec<_> = dfg.edges().collect();
+        assert_eq!(edges, [("A", "B", 2), ("A", "C", 1), ("B", "C", 2)]);
+
+        let log = VariantLog::from_trace_iter(
+            traces
+                .iter()
+                .map(|trace| trace.iter().map(|activity| activity.to_string()).collect()),
+        );
+        assert_eq!(DirectlyFollowsGraph::from_variant_log(&log), dfg);
+
+        assert_eq!(dfg.fitness(&log), 1.0);
+        let filtered = dfg.filter_edges(2);
+        assert_eq!(filtered.edges().count(), 2);
+        assert_eq!(filtered.activities(), dfg.activities());
+        // A, C loses its only edge, one of its three steps
+        assert_eq!(filtered.fitness(&log), 10.0 / 11.0);
+
+        let mapping = HashMap::from([
+            ("B".to_string(), "BC".to_string()),
+            ("C".to_string(), "BC".to_string()),
+        ]);
+        let merged = dfg.rename_activities(&mapping);
+        assert_eq!(merged.activities(), ["A", "BC"]);
+        assert_eq!(merged.activity_frequency("BC"), 5);
+        assert_eq!(merged.frequency("A", "BC"), 3);
+        assert_eq!(merged.frequency("BC", "BC"), 2);
+        assert_eq!(merged.end_frequency("BC"), 3);
+    }
+
+    #[test]
+    fn test_ongoing_cases() {
+        let log = VariantLog::from_traces(vec![
+            vec!["A".to_string(), "B".to_string()],
+            vec!["A".to_string(), "B".to_string()],
+        ])
+        .mark_ongoing(&HashSet::from(["case_1".to_string()]));
+        let dfg = DirectlyFollowsGraph::from_variant_log(&log);
+        assert_eq!(dfg.frequency("A", "B"), 2);
+        assert_eq!(dfg.start_frequency("A"), 2);
+        assert_eq!(dfg.end_frequency("B"), 1);
+    }
+}
@@ -0,0 +1,364 @@
+use std::collections::BTreeMap;
+
+use chrono::{DateTime, Duration, Utc};
+use process_mining::event_log::EventLog;
+
+use crate::metrics::MetricResult;
+use crate::parser::timezone::{to_target, TimezoneOptions};
+use crate::parser::xes_extensions::{CONCEPT_NAME, COST_TOTAL, TIME_TIMESTAMP};
+use crate::parser::{Classifier, ParseError};
+use crate::render::escape_xml;
+use crate::timeline::{case_timelines, CaseTimeline};
+
+/// Column names of the CSV export, as expected by Disco and Celonis.
+pub const DISCO_CSV_HEADER: [&str; 5] = ["Case ID", "Activity", "Start", "Complete", "Resource"];
+
+const DISCO_DATE_FORMAT: &str = "%Y-%m-%d %H:%M:%S";
+
+/// Exports the log as CSV with one row per activity instance (see
+/// [`crate::timeline::case_timelines`]), using the column convention of Disco and Celonis
+/// (see [`DISCO_CSV_HEADER`]).
+pub fn disco_csv(log: &EventLog, classifier: &Classifier) -> Result<String, ParseError> {
+    disco_csv_with_timezone(log, classifier, &TimezoneOptions::default())
+}
+
+/// Same as [`disco_csv`], but with the timestamps in the target offset of `timezone` instead of
+/// UTC.
+pub fn disco_csv_with_timezone(
+    log: &EventLog,
+    classifier: &Classifier,
+    timezone: &TimezoneOptions,
+) -> Result<String, ParseError> {
+    Ok(disco_csv_from_timelines(
+        &case_timelines(log, classifier)?,
+        timezone,
+    ))
+}
+
+/// Same as [`disco_csv_with_timezone`], for activity instances that were already derived,
+/// e.g. the cleaned [`AnalysisResult::timelines`](crate::analysis::AnalysisResult::timelines).
+pub fn disco_csv_from_timelines(timelines: &[CaseTimeline], timezone: &TimezoneOptions) -> String {
+    let mut output = DISCO_CSV_HEADER.join(",");
+    output.push('\n');
+
+    for timeline in timelines {
+        for instance in &timeline.instances {
+            let format_date = |date: Option<DateTime<Utc>>| {
+                date.map(|date| to_target(date, timezone).format(DISCO_DATE_FORMAT).to_string())
+                    .unwrap_or_default()
+            };
+            let row = [
+                csv_field(&timeline.case_id),
+                csv_field(&instance.activity),
+                format_date(instance.start),
+                format_date(instance.complete),
+                csv_field(instance.resource.as_deref().unwrap_or("")),
+            ];
+            output.push_str(&row.join(","));
+            output.push('\n');
+        }
+    }
+
+    output
+}
+
+// The type the standard extensions define for `key`, `string` for other keys and for a value
+// that doesn't parse as its type
+fn xes_type(key: &str, value: &str) -> &'static str {
+    match key {
+        COST_TOTAL if value.parse::<f64>().is_ok() => "float",
+        _ if key.starts_with("time:") && DateTime::parse_from_rfc3339(value).is_ok() => "date",
+        _ => "string",
+    }
+}
+
+/// The standard XES extensions as name, prefix and URI. Concept and time are always declared,
+/// the others if an exported attribute key has their prefix.
+const XES_EXTENSIONS: [(&str, &str, &str); 5] = [
+    ("Concept", "concept", "http://www.xes-standard.org/concept.xesext"),
+    ("Time", "time", "http://www.xes-standard.org/time.xesext"),
+    ("Lifecycle", "lifecycle", "http://www.xes-standard.org/lifecycle.xesext"),
+    ("Organizational", "org", "http://www.xes-standard.org/org.xesext"),
+    ("Cost", "cost", "http://www.xes-standard.org/cost.xesext"),
+];
+
+/// Which attributes of the cases and events the XES export writes besides `concept:name` and
+/// `time:timestamp`.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub enum ExportedAttributes {
+    /// Only the case ids, activities and timestamps.
+    None,
+    /// Every attribute carried by the log (resource, lifecycle transition, case attributes, ...).
+    #[default]
+    All,
+    /// The attributes with the given keys.
+    Keys(Vec<String>),
+}
+
+impl ExportedAttributes {
+    fn includes(&self, key: &str) -> bool {
+        match self {
+            ExportedAttributes::None => false,
+            ExportedAttributes::All => true,
+            ExportedAttributes::Keys(keys) => keys.iter().any(|k| k == key),
+        }
+    }
+}
+
+/// Exports the log as XES with the log, case and event attributes selected by `attributes`,
+/// declaring the standard extensions of their keys, so that a log converted from another source
+/// keeps what was recorded. The attributes are written with the type the extensions define for
+/// their keys, e.g. `cost:total` as `<float>`, and as `<string>` otherwise, as the log only
+/// keeps their text. Timestamps are written in the target offset of `timezone`; an event
+/// without timestamp gets one second after the previous event of its case, so that the order of
+/// the events survives.
+pub fn xes(
+    log: &crate::EventLog,
+    attributes: &ExportedAttributes,
+    timezone: &TimezoneOptions,
+) -> String {
+    let exported = |element_attributes: &BTreeMap<String, String>| -> Vec<(String, String)> {
+        element_attributes
+            .iter()
+            .filter(|(key, _)| *key != CONCEPT_NAME && *key != TIME_TIMESTAMP)
+            .filter(|(key, _)| attributes.includes(key))
+            .map(|(key, value)| (key.clone(), value.clone()))
+            .collect()
+    };
+    let string = |key: &str, value: &str| {
+        format!(
+            "<{} key=\"{}\" value=\"{}\"/>",
+            xes_type(key, value),
+            escape_xml(key),
+            escape_xml(value)
+        )
+    };
+
+    let mut body = String::new();
+    let mut prefixes = vec!["concept", "time"];
+    let mut use_key = |key: &str| {
+        if let Some((prefix, _)) = key.split_once(':') {
+            let known = XES_EXTENSIONS.iter().find(|(_, known, _)| *known == prefix);
+            if let Some((_, known, _)) = known {
+                if !prefixes.contains(known) {
+                    prefixes.push(known);
+                }
+            }
+        }
+    };
+    // the name of the log is kept like the case ids and activities
+    for (key, value) in &log.attributes {
+        if key == CONCEPT_NAME || attributes.includes(key) {
+            use_key(key);
+            body.push_str(&format!("  {}\n", string(key, value)));
+        }
+    }
+    for trace in &log.traces {
+        body.push_str("  <trace>\n");
+        body.push_str(&format!("    {}\n", string(CONCEPT_NAME, &trace.case_id)));
+        for (key, value) in exported(&trace.attributes) {
+            use_key(&key);
+            body.push_str(&format!("    {}\n", string(&key, &value)));
+        }
+
+        let mut previous = DateTime::<Utc>::default();
+        for event in &trace.events {
+            let timestamp = event
+                .timestamp
+                .unwrap_or_else(|| previous + Duration::seconds(1));
+            previous = timestamp;
+            body.push_str("    <event>\n");
+            body.push_str(&format!("      {}\n", string(CONCEPT_NAME, &event.activity)));
+            body.push_str(&format!(
+                "      <date key=\"{}\" value=\"{}\"/>\n",
+                TIME_TIMESTAMP,
+                to_target(timestamp, timezone).to_rfc3339()
+            ));
+            for (key, value) in exported(&event.attributes) {
+                use_key(&key);
+                body.push_str(&format!("      {}\n", string(&key, &value)));
+            }
+            body.push_str("    </event>\n");
+        }
+        body.push_str("  </trace>\n");
+    }
+
+    let mut output = concat!(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n",
+        "<log xes.version=\"1.0\" xes.features=\"nested-attributes\" ",
+        "xmlns=\"http://www.xes-standard.org/\">\n"
+    )
+    .to_string();
+    for (name, prefix, uri) in XES_EXTENSIONS {
+        if prefixes.contains(&prefix) {
+            output.push_str(&format!(
+                "  <extension name=\"{}\" prefix=\"{}\" uri=\"{}\"/>\n",
+                name, prefix, uri
+            ));
+        }
+    }
+    output.push_str(&body);
+    output.push_str("</log>\n");
+    output
+}
+
+/// Exports computed metrics as CSV with the columns `id`, `label`, `value` and the bounds of the
+/// confidence interval `lower` and `upper`; undefined values are left empty.
+pub fn metrics_csv(metrics: &[MetricResult]) -> String {
+    let mut output = "id,label,value,lower,upper\n".to_string();
+    for metric in metrics {
+        let value = metric.value.as_f64().map(|value| value.to_string()).unwrap_or_default();
+        let (lower, upper) = match metric.confidence_interval {
+            Some(interval) => (interval.lower.to_string(), interval.upper.to_string()),
+            None => (String::new(), String::new()),
+        };
+        output.push_str(&format!(
+            "{},{},{},{},{}\n",
+            csv_field(&metric.id),
+            csv_field(&metric.label),
+            value,
+            lower,
+            upper
+        ));
+    }
+    output
+}
+
+// Quotes a field if it contains a separator, a quote or a line break
+pub(crate) fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::metrics::{ConfidenceInterval, MetricValue};
+    use crate::parser::{parse_into_event_log_with_config, parse_into_log, ImportConfig};
+
+    #[test]
+    fn test_disco_csv() {
+        let content = r#"<log>
+            <trace>
+                <string key="concept:name" value="order, 1"/>
+                <event>
+                    <string key="concept:name" value="Check"/>
+                    <string key="lifecycle:transition" value="start"/>
+                    <date key="time:timestamp" value="2024-01-01T10:00:00+00:00"/>
+                </event>
+                <event>
+                    <string key="concept:name" value="Check"/>
+                    <string key="lifecycle:transition" value="complete"/>
+                    <string key="org:resource" value="Pete"/>
+                    <date key="time:timestamp" value="2024-01-01T10:30:00+00:00"/>
+                </event>
+                <event>
+                    <string key="concept:name" value="Ship"/>
+                    <date key="time:timestamp" value="2024-01-01T12:00:00+00:00"/>
+                </event>
+            </trace>
+        </log>"#;
+        let log = parse_into_event_log_with_config(None, Some(content), &ImportConfig::default())
+            .unwrap();
+
+        assert_eq!(
+            disco_csv(&log, &Classifier::ConceptName).unwrap(),
+            "Case ID,Activity,Start,Complete,Resource\n\
+             \"order, 1\",Check,2024-01-01 10:00:00,2024-01-01 10:30:00,Pete\n\
+             \"order, 1\",Ship,,2024-01-01 12:00:00,\n"
+        );
+        let timezone = TimezoneOptions {
+            target_offset_minutes: Some(-60),
+            ..Default::default()
+        };
+        assert!(disco_csv_with_timezone(&log, &Classifier::ConceptName, &timezone)
+            .unwrap()
+            .contains("Ship,,2024-01-01 11:00:00,"));
+    }
+
+    #[test]
+    fn test_xes() {
+        let content = r#"<log>
+            <trace>
+                <string key="concept:name" value="order &amp; 1"/>
+                <string key="channel" value="web"/>
+                <float key="cost:total" value="12.5"/>
+                <event>
+                    <string key="concept:name" value="Check"/>
+                    <string key="org:resource" value="Pete"/>
+                    <date key="time:timestamp" value="2024-01-01T10:00:00+00:00"/>
+                </event>
+                <event>
+                    <string key="concept:name" value="Ship"/>
+                    <int key="items" value="3"/>
+                    <date key="time:timestamp" value="2024-01-01T12:00:00+00:00"/>
+                </event>
+            </trace>
+        </log>"#;
+        let log = parse_into_log(None, Some(content)).unwrap();
+        assert_eq!(log.traces[0].attributes["channel"], "web");
+
+        let exported = xes(&log, &ExportedAttributes::All, &TimezoneOptions::default());
+        assert!(exported.contains("prefix=\"org\""));
+        assert!(exported.contains("<float key=\"cost:total\" value=\"12.5\"/>"));
+        assert!(exported.contains("<string key=\"org:resource\" value=\"Pete\"/>"));
+        assert_eq!(xes_type("cost:total", "n/a"), "string");
+        assert_eq!(xes_type("time:planned", "2024-01-01T08:00:00+00:00"), "date");
+        assert_eq!(parse_into_log(None, Some(&exported)).unwrap(), log);
+
+        let exported = xes(
+            &log,
+            &ExportedAttributes::Keys(vec!["channel".to_string(), "cost:total".to_string()]),
+            &TimezoneOptions::default(),
+        );
+        let reimported = parse_into_log(None, Some(&exported)).unwrap();
+        assert_eq!(reimported.traces[0].attributes, log.traces[0].attributes);
+        assert!(reimported.traces[0].events[0].attributes.is_empty());
+        assert!(!exported.contains("prefix=\"org\""));
+
+        let log = parse_into_log(Some("./sample-data/exercise2.xes"), None).unwrap();
+        let exported = xes(&log, &ExportedAttributes::All, &TimezoneOptions::default());
+        let reimported = parse_into_log(None, Some(&exported)).unwrap();
+        assert_eq!(reimported.traces, log.traces);
+        assert_eq!(reimported.attributes, log.attributes);
+
+        // events without timestamp keep their order
+        let log = crate::EventLog::from_traces(vec![vec!["A".to_string(), "B".to_string()]]);
+        let exported = xes(&log, &ExportedAttributes::None, &TimezoneOptions::default());
+        assert_eq!(
+            parse_into_log(None, Some(&exported)).unwrap().to_traces(),
+            log.to_traces()
+        );
+    }
+
+    #[test]
+    fn test_metrics_csv() {
+        let metrics = [
+            MetricResult {
+                id: "variants".to_string(),
+                label: "#variants".to_string(),
+                value: MetricValue::Count(3),
+                confidence_interval: None,
+            },
+            MetricResult {
+                id: "custom".to_string(),
+                label: "a, b".to_string(),
+                value: MetricValue::Ratio(0.5),
+                confidence_interval: Some(ConfidenceInterval { lower: 0.25, upper: 0.75 }),
+            },
+            MetricResult {
+                id: "undefined".to_string(),
+                label: "-".to_string(),
+                value: MetricValue::Undefined,
+                confidence_interval: None,
+            },
+        ];
+        assert_eq!(
+            metrics_csv(&metrics),
+            "id,label,value,lower,upper\nvariants,#variants,3,,\ncustom,\"a, b\",0.5,0.25,0.75\nundefined,-,,,\n"
+        );
+    }
+}
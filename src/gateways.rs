@@ -0,0 +1,189 @@
+//! Splits and joins of the directly-follows graph: after which activities the process decides
+//! between branches (XOR) and where it runs them in parallel (AND), so decision points can be
+//! listed explicitly instead of read off the matrix.
+
+use std::collections::{BTreeMap, BTreeSet, HashSet};
+
+use crate::dfg::DirectlyFollowsGraph;
+use crate::i18n::{Language, Message};
+use crate::variant_log::VariantLog;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum GatewayPosition {
+    /// The branches follow the activity.
+    Split,
+    /// The branches precede the activity.
+    Join,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GatewayKind {
+    /// Exactly one branch is taken.
+    Xor,
+    /// All branches are taken, in any order.
+    And,
+}
+
+/// An activity with several directly following (or preceding) activities.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Gateway {
+    pub activity: String,
+    pub position: GatewayPosition,
+    pub kind: GatewayKind,
+    /// The branching activities, ordered by label.
+    pub branches: Vec<String>,
+    /// How clearly the cases agree with `kind`, from 0.5 (as much XOR as AND, e.g. an
+    /// inclusive choice) to 1.
+    pub confidence: f64,
+}
+
+/// Detects the splits and joins of `log`, ordered by activity, splits first.
+///
+/// Every activity with at least two directly following activities is a split; activities
+/// that also directly precede it run in parallel to it rather than after it and are left out,
+/// as are self-loops. Among the cases containing the activity, a pair of branches is exclusive
+/// to the share of cases with either branch that don't contain both; the split is an XOR
+/// split if its branches are exclusive by at least half on average, and an AND split
+/// otherwise, with the average exclusivity or co-occurrence as confidence. Joins are detected the same way
+/// from the directly preceding activities.
+pub fn gateways(log: &VariantLog) -> Vec<Gateway> {
+    let dfg = DirectlyFollowsGraph::from_variant_log(log);
+    let mut successors: BTreeMap<&str, BTreeSet<&str>> = BTreeMap::new();
+    let mut predecessors: BTreeMap<&str, BTreeSet<&str>> = BTreeMap::new();
+    for (from, to, _) in dfg.edges() {
+        if from != to && dfg.frequency(to, from) == 0 {
+            successors.entry(from).or_default().insert(to);
+            predecessors.entry(to).or_default().insert(from);
+        }
+    }
+    let variants: Vec<(HashSet<&str>, usize)> = log
+        .variants()
+        .map(|(variant, frequency)| (variant.iter().map(String::as_str).collect(), frequency))
+        .collect();
+
+    let mut gateways = Vec::new();
+    for (position, neighbors) in [
+        (GatewayPosition::Split, successors),
+        (GatewayPosition::Join, predecessors),
+    ] {
+        for (activity, branches) in neighbors {
+            if branches.len() < 2 {
+                continue;
+            }
+            let Some(exclusivity) = exclusivity(&variants, activity, &branches) else {
+                continue;
+            };
+            let (kind, confidence) = if exclusivity >= 0.5 {
+                (GatewayKind::Xor, exclusivity)
+            } else {
+                (GatewayKind::And, 1.0 - exclusivity)
+            };
+            gateways.push(Gateway {
+                activity: activity.to_string(),
+                position,
+                kind,
+                branches: branches.iter().map(|branch| branch.to_string()).collect(),
+                confidence,
+            });
+        }
+    }
+    gateways
+}
+
+// The mean exclusivity of all pairs of branches within the cases containing `activity`
+fn exclusivity(
+    variants: &[(HashSet<&str>, usize)],
+    activity: &str,
+    branches: &BTreeSet<&str>,
+) -> Option<f64> {
+    let branches: Vec<&str> = branches.iter().copied().collect();
+    let mut shares = Vec::new();
+    for (idx, first) in branches.iter().enumerate() {
+        for second in &branches[idx + 1..] {
+            let (mut either, mut both) = (0, 0);
+            for (activities, frequency) in variants {
+                if !activities.contains(activity) {
+                    continue;
+                }
+                match (activities.contains(first), activities.contains(second)) {
+                    (true, true) => {
+                        either += frequency;
+                        both += frequency;
+                    }
+                    (true, false) | (false, true) => either += frequency,
+                    (false, false) => {}
+                }
+            }
+            if either > 0 {
+                shares.push(1.0 - both as f64 / either as f64);
+            }
+        }
+    }
+    (!shares.is_empty()).then(|| shares.iter().sum::<f64>() / shares.len() as f64)
+}
+
+/// One line per split and join with its kind, confidence and branches.
+pub fn report(gateways: &[Gateway], language: Language) -> String {
+    let mut output = format!(
+        "{:<32}{:<10}{:<8}{:<12}{}\n",
+        Message::Activity.text(language),
+        Message::Gateway.text(language),
+        Message::Type.text(language),
+        Message::Confidence.text(language),
+        Message::Branches.text(language)
+    );
+    for gateway in gateways {
+        let position = match gateway.position {
+            GatewayPosition::Split => Message::Split,
+            GatewayPosition::Join => Message::Join,
+        };
+        let kind = match gateway.kind {
+            GatewayKind::Xor => "XOR",
+            GatewayKind::And => "AND",
+        };
+        output.push_str(&format!(
+            "{:<32}{:<10}{:<8}{:<12.4}{}\n",
+            gateway.activity,
+            position.text(language),
+            kind,
+            gateway.confidence,
+            gateway.branches.join(", ")
+        ));
+    }
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_gateways() {
+        let trace = |activities: &str| activities.chars().map(|a| a.to_string()).collect();
+        // A chooses between B and C, which are both followed by D and E in parallel
+        let log = VariantLog::from_traces(vec![
+            trace("ABDEGH"),
+            trace("ABEDGH"),
+            trace("ACDEGH"),
+            trace("ACEDGH"),
+        ]);
+        let gateways = gateways(&log);
+        let summary: Vec<(&str, GatewayPosition, GatewayKind, f64)> = gateways
+            .iter()
+            .map(|g| (g.activity.as_str(), g.position, g.kind, g.confidence))
+            .collect();
+        assert_eq!(
+            summary,
+            [
+                ("A", GatewayPosition::Split, GatewayKind::Xor, 1.0),
+                ("B", GatewayPosition::Split, GatewayKind::And, 1.0),
+                ("C", GatewayPosition::Split, GatewayKind::And, 1.0),
+                ("D", GatewayPosition::Join, GatewayKind::Xor, 1.0),
+                ("E", GatewayPosition::Join, GatewayKind::Xor, 1.0),
+                ("G", GatewayPosition::Join, GatewayKind::And, 1.0),
+            ]
+        );
+        assert_eq!(gateways[0].branches, ["B", "C"]);
+        assert!(report(&gateways, Language::English).contains("XOR"));
+    }
+}
@@ -0,0 +1,288 @@
+use crate::i18n::{Language, Message};
+
+/// Description of a metric shown in the reports.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Metric {
+    /// Stable identifier, e.g. for linking or looking the metric up.
+    pub id: &'static str,
+    /// The label the metric is shown with in reports.
+    pub label: Message,
+    pub formula: &'static str,
+    /// How to read the value.
+    pub interpretation: &'static str,
+    /// Publication the metric is taken from, if any.
+    pub reference: Option<&'static str>,
+}
+
+impl Metric {
+    /// Short explanation of the metric, e.g. for a tooltip.
+    pub fn summary(&self) -> String {
+        format!("{}\n{}", self.formula, self.interpretation)
+    }
+}
+
+/// All metrics reported by the library, in the order they appear in the reports.
+pub const METRICS: &[Metric] = &[
+    Metric {
+        id: "relations",
+        label: Message::Relations,
+        formula: "|A|²",
+        interpretation: "Number of ordered activity pairs (including each activity with itself) \
+                         that are checked for dependencies.",
+        reference: None,
+    },
+    Metric {
+        id: "independences_per_relations",
+        label: Message::IndependencesPerRelations,
+        formula: "#pairs without temporal and existential dependency / |A|²",
+        interpretation: "Share of activity pairs that are completely independent. High values \
+                         indicate loosely structured, flexible processes.",
+        reference: None,
+    },
+    Metric {
+        id: "temporal_independences_per_relations",
+        label: Message::TemporalIndependencesPerRelations,
+        formula: "#pairs without temporal dependency / |A|²",
+        interpretation: "Share of activity pairs whose order isn't constrained, regardless of \
+                         whether they occur together.",
+        reference: None,
+    },
+    Metric {
+        id: "max_variant_frequency_per_traces",
+        label: Message::MaxVariantFrequencyPerTraces,
+        formula: "max_v freq(v) / #traces",
+        interpretation: "Share of cases following the most frequent variant. Values close to 1 \
+                         mean the process has one dominant path.",
+        reference: None,
+    },
+    Metric {
+        id: "variants_per_traces",
+        label: Message::VariantsPerTraces,
+        formula: "#variants / #traces",
+        interpretation: "Close to 0 if many cases share few variants, 1 if every case is unique.",
+        reference: None,
+    },
+    Metric {
+        id: "eventual_equivalences",
+        label: Message::EventualEquivalences,
+        formula: "#pairs (a, b) with a eventually followed by b and a occurring iff b occurs",
+        interpretation: "Activity pairs that always occur together and in the same order, with \
+                         other activities possibly in between.",
+        reference: None,
+    },
+    Metric {
+        id: "direct_equivalences",
+        label: Message::DirectEquivalences,
+        formula: "#pairs (a, b) with a directly followed by b and a occurring iff b occurs",
+        interpretation: "Activity pairs that always occur together, one immediately after the \
+                         other.",
+        reference: None,
+    },
+    Metric {
+        id: "variants",
+        label: Message::Variants,
+        formula: "#distinct activity sequences",
+        interpretation: "Number of different paths through the process.",
+        reference: None,
+    },
+    Metric {
+        id: "max_variant_frequency_per_variants",
+        label: Message::MaxVariantFrequencyPerVariants,
+        formula: "(max_v freq(v) / #traces) / #variants",
+        interpretation: "Dominance of the most frequent variant relative to the number of \
+                         variants.",
+        reference: None,
+    },
+    Metric {
+        id: "variant_entropy",
+        label: Message::VariantEntropy,
+        formula: "S·log(S) − Σ_p |p|·log(|p|), S = #states of the extended prefix automaton, \
+                  p = its partitions",
+        interpretation: "Variability of the log based on how the prefix automaton splits into \
+                         variants. 0 for a single variant, growing with the number and length \
+                         of distinct paths. The partition strategy is shown with the value.",
+        reference: Some(
+            "Back, Debois, Slaats: Entropy as a Measure of Log Variability. \
+             Journal on Data Semantics 8, 2019",
+        ),
+    },
+    Metric {
+        id: "normalized_variant_entropy",
+        label: Message::NormalizedVariantEntropy,
+        formula: "variant entropy / (S·log(S))",
+        interpretation: "Variant entropy scaled to [0, 1], comparable between logs of different \
+                         sizes. 0 means no variability, 1 that every path is a separate variant.",
+        reference: Some(
+            "Back, Debois, Slaats: Entropy as a Measure of Log Variability. \
+             Journal on Data Semantics 8, 2019",
+        ),
+    },
+    Metric {
+        id: "lempel_ziv_complexity",
+        label: Message::LempelZivComplexity,
+        formula: "c(n)·log_k(n) / n, c = #phrases of the Lempel-Ziv parsing of all cases in a \
+                  row, n = #events + #cases, k = #activities + 1",
+        interpretation: "How well the log compresses. Close to 0 if the cases repeat a few \
+                         patterns, close to 1 if the order of the activities looks random.",
+        reference: Some(
+            "Lempel, Ziv: On the Complexity of Finite Sequences. IEEE Transactions on \
+             Information Theory 22, 1976",
+        ),
+    },
+    Metric {
+        id: "level_of_detail",
+        label: Message::LevelOfDetail,
+        formula: "Σ_t |distinct activities of t| / #traces",
+        interpretation: "How many different activities a case touches on average. High values \
+                         indicate fine-grained logs.",
+        reference: Some(
+            "Günther: Process Mining in Flexible Environments. PhD thesis, TU Eindhoven, 2009",
+        ),
+    },
+    Metric {
+        id: "affinity",
+        label: Message::Affinity,
+        formula: "mean over all pairs of traces of |F1 ∩ F2| / |F1 ∪ F2|, F = directly-follows \
+                  pairs of a trace",
+        interpretation: "How similar the cases are in their local ordering. 1 if all cases \
+                         follow the same transitions, near 0 for unrelated cases.",
+        reference: Some(
+            "Günther: Process Mining in Flexible Environments. PhD thesis, TU Eindhoven, 2009",
+        ),
+    },
+    Metric {
+        id: "deviation_from_random",
+        label: Message::DeviationFromRandom,
+        formula: "‖M − λ‖ / max ‖M − λ‖, M = directly-follows counts, λ = #transitions / |A|²",
+        interpretation: "How structured the transitions are. 0 if every activity follows every \
+                         other one equally often, 1 if the log repeats a single transition.",
+        reference: Some(
+            "Günther: Process Mining in Flexible Environments. PhD thesis, TU Eindhoven, 2009",
+        ),
+    },
+    Metric {
+        id: "fitness",
+        label: Message::Fitness,
+        formula: "½·(1 − missing / consumed) + ½·(1 − remaining / produced)",
+        interpretation: "Token-based fitness of the log on the model. 1 if every case can be \
+                         replayed, lower the more tokens had to be created or were left over.",
+        reference: Some(
+            "Rozinat, van der Aalst: Conformance Checking of Processes Based on Monitoring \
+             Real Behavior. Information Systems 33(1), 2008",
+        ),
+    },
+    Metric {
+        id: "fitting_traces",
+        label: Message::FittingTraces,
+        formula: "#cases replayed without missing or remaining tokens / #cases",
+        interpretation: "Cases that are fully compliant with the model.",
+        reference: None,
+    },
+    Metric {
+        id: "model_nodes",
+        label: Message::ModelNodes,
+        formula: "|N|",
+        interpretation: "Number of nodes of the model, e.g. places and transitions of a Petri \
+                         net. Smaller models are easier to understand.",
+        reference: None,
+    },
+    Metric {
+        id: "model_edges",
+        label: Message::ModelEdges,
+        formula: "|E|",
+        interpretation: "Number of edges of the model, e.g. arcs of a Petri net.",
+        reference: None,
+    },
+    Metric {
+        id: "average_connector_degree",
+        label: Message::AverageConnectorDegree,
+        formula: "Σ_c (in(c) + out(c)) / #connectors, connectors having in(c) > 1 or out(c) > 1",
+        interpretation: "Mean number of edges of the splits and joins. High values mean many \
+                         paths meet at single points, which makes models error-prone to read.",
+        reference: Some("Mendling: Metrics for Process Models. LNBIP 6, Springer 2008"),
+    },
+    Metric {
+        id: "cyclomatic_complexity",
+        label: Message::CyclomaticComplexity,
+        formula: "|E| − |N| + 2·#connected components",
+        interpretation: "Number of independent paths through the model. 1 for a sequence, \
+                         higher the more choices and loops the model has.",
+        reference: Some("McCabe: A Complexity Measure. IEEE TSE 2(4), 1976"),
+    },
+    Metric {
+        id: "ltl_satisfaction_rate",
+        label: Message::LtlFormulas,
+        formula: "#traces satisfying φ / #traces",
+        interpretation: "Share of cases satisfying an LTL formula on finite traces.",
+        reference: Some(
+            "De Giacomo, Vardi: Linear Temporal Logic and Linear Dynamic Logic on Finite \
+             Traces. IJCAI 2013",
+        ),
+    },
+    Metric {
+        id: "declare_support_confidence",
+        label: Message::DeclareConstraints,
+        formula: "support = #satisfying traces / #traces, confidence = #satisfying traces / \
+                  #traces activating the constraint",
+        interpretation: "Support is the share of cases satisfying a constraint, confidence the \
+                         share among cases where it applies at all (non-vacuously).",
+        reference: Some(
+            "Maggi, Mooij, van der Aalst: User-Guided Discovery of Declarative Process \
+             Models. CIDM 2011",
+        ),
+    },
+];
+
+/// Looks a metric up by its [`Metric::id`].
+pub fn metric(id: &str) -> Option<&'static Metric> {
+    METRICS.iter().find(|metric| metric.id == id)
+}
+
+/// Looks up the metric shown with `label` in the reports.
+pub fn metric_for_label(label: Message) -> Option<&'static Metric> {
+    METRICS.iter().find(|metric| metric.label == label)
+}
+
+/// Renders a plain text glossary of `metrics` for reports.
+pub fn glossary(metrics: &[&Metric], language: Language) -> String {
+    metrics
+        .iter()
+        .map(|metric| {
+            let mut entry = format!(
+                "{}\n    {}\n    {}",
+                metric.label.text(language),
+                metric.formula,
+                metric.interpretation
+            );
+            if let Some(reference) = metric.reference {
+                entry.push_str(&format!("\n    {}", reference));
+            }
+            entry
+        })
+        .collect::<Vec<String>>()
+        .join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashSet;
+
+    #[test]
+    fn test_unique_ids_and_labels() {
+        let ids: HashSet<_> = METRICS.iter().map(|metric| metric.id).collect();
+        let labels: HashSet<_> = METRICS.iter().map(|metric| metric.label).collect();
+        assert_eq!(ids.len(), METRICS.len());
+        assert_eq!(labels.len(), METRICS.len());
+    }
+
+    #[test]
+    fn test_glossary() {
+        let entropy = metric("normalized_variant_entropy").unwrap();
+        assert_eq!(metric_for_label(Message::NormalizedVariantEntropy), Some(entropy));
+
+        let text = glossary(&[entropy], Language::German);
+        assert!(text.starts_with("Normalisierte Varianten-Entropie\n"));
+        assert!(text.contains("Journal on Data Semantics"));
+    }
+}
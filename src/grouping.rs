@@ -0,0 +1,234 @@
+//! Grouping activities into clusters, so the process maps of logs with many activities can be
+//! drawn with one super-node per cluster instead of hundreds of crossing edges.
+
+use std::cmp::Reverse;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+
+use serde::{Deserialize, Serialize};
+
+use crate::dfg::DirectlyFollowsGraph;
+
+/// How the activities are grouped.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum GroupingStrategy {
+    /// Activities sharing the label part before the separator, e.g. `Invoice: Send` and
+    /// `Invoice: Check` with the separator `:`. Activities without the separator stay alone.
+    Prefix(String),
+    /// A category per activity; activities without one stay alone.
+    Categories(BTreeMap<String, String>),
+    /// At most the given number of clusters of activities that directly follow each other
+    /// often, see [`connectivity_clusters`].
+    Connectivity(usize),
+}
+
+impl Default for GroupingStrategy {
+    fn default() -> Self {
+        GroupingStrategy::Connectivity(10)
+    }
+}
+
+/// Grouping of the activities of the process map.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ActivityGrouping {
+    pub strategy: GroupingStrategy,
+    /// Draw every group as one super-node; if not, the activities are drawn in the color of
+    /// their group.
+    pub collapsed: bool,
+}
+
+impl Default for ActivityGrouping {
+    fn default() -> Self {
+        ActivityGrouping {
+            strategy: GroupingStrategy::default(),
+            collapsed: true,
+        }
+    }
+}
+
+/// A named group of at least two activities.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ActivityGroup {
+    pub name: String,
+    pub activities: BTreeSet<String>,
+}
+
+impl GroupingStrategy {
+    /// Groups the activities of `dfg`, ordered by name. Groups of a single activity are left
+    /// out.
+    pub fn groups(&self, dfg: &DirectlyFollowsGraph) -> Vec<ActivityGroup> {
+        let groups: BTreeMap<String, BTreeSet<String>> = match self {
+            GroupingStrategy::Prefix(separator) => dfg
+                .activities()
+                .iter()
+                .filter_map(|activity| {
+                    let (prefix, _) = activity.split_once(separator.as_str())?;
+                    Some((prefix.trim().to_string(), activity.clone()))
+                })
+                .fold(BTreeMap::new(), collect_group),
+            GroupingStrategy::Categories(categories) => dfg
+                .activities()
+                .iter()
+                .filter_map(|activity| Some((categories.get(activity)?.clone(), activity.clone())))
+                .fold(BTreeMap::new(), collect_group),
+            GroupingStrategy::Connectivity(clusters) => connectivity_clusters(dfg, *clusters)
+                .into_iter()
+                .map(|group| (group.name, group.activities))
+                .collect(),
+        };
+        groups
+            .into_iter()
+            .filter(|(_, activities)| activities.len() > 1)
+            .map(|(name, activities)| ActivityGroup { name, activities })
+            .collect()
+    }
+}
+
+fn collect_group(
+    mut groups: BTreeMap<String, BTreeSet<String>>,
+    (group, activity): (String, String),
+) -> BTreeMap<String, BTreeSet<String>> {
+    groups.entry(group).or_default().insert(activity);
+    groups
+}
+
+/// Maps every activity of `groups` to the name of its group.
+pub fn group_mapping(groups: &[ActivityGroup]) -> HashMap<String, String> {
+    groups
+        .iter()
+        .flat_map(|group| {
+            group
+                .activities
+                .iter()
+                .map(|activity| (activity.clone(), group.name.clone()))
+        })
+        .collect()
+}
+
+/// Clusters the activities of `dfg` bottom-up until at most `clusters` remain: starting from
+/// one cluster per activity, the two clusters with the most directly-follows steps between
+/// them relative to their sizes (average linkage) are merged, as long as any two clusters are
+/// connected at all.
+///
+/// A cluster is named after its most frequent activity followed by the number of the other
+/// activities, e.g. `Create order +3`.
+pub fn connectivity_clusters(dfg: &DirectlyFollowsGraph, clusters: usize) -> Vec<ActivityGroup> {
+    let activities = dfg.activities();
+    let index: HashMap<&str, usize> = activities
+        .iter()
+        .enumerate()
+        .map(|(idx, activity)| (activity.as_str(), idx))
+        .collect();
+    let n = activities.len();
+    // steps between two clusters in either direction, indexed by the clusters' first activity
+    let mut weights = vec![vec![0.0; n]; n];
+    for (from, to, frequency) in dfg.edges() {
+        let (from, to) = (index[from], index[to]);
+        if from != to {
+            weights[from][to] += frequency as f64;
+            weights[to][from] += frequency as f64;
+        }
+    }
+    let mut members: Vec<Vec<usize>> = (0..n).map(|idx| vec![idx]).collect();
+    let mut alive: Vec<usize> = (0..n).collect();
+
+    while alive.len() > clusters.max(1) {
+        let mut best: Option<(f64, usize, usize)> = None;
+        for (pos, &a) in alive.iter().enumerate() {
+            for &b in &alive[pos + 1..] {
+                let linkage = weights[a][b] / (members[a].len() * members[b].len()) as f64;
+                if linkage > 0.0 && best.is_none_or(|(max, _, _)| linkage > max) {
+                    best = Some((linkage, a, b));
+                }
+            }
+        }
+        let Some((_, a, b)) = best else {
+            break;
+        };
+        let merged = std::mem::take(&mut members[b]);
+        members[a].extend(merged);
+        for &c in &alive {
+            let weight = weights[b][c];
+            weights[a][c] += weight;
+            weights[c][a] += weight;
+        }
+        alive.retain(|&c| c != b);
+    }
+
+    alive
+        .into_iter()
+        .map(|cluster| {
+            let cluster = &members[cluster];
+            // the first of equally frequent activities
+            let most_frequent = cluster
+                .iter()
+                .copied()
+                .max_by_key(|&idx| (dfg.activity_frequency(&activities[idx]), Reverse(idx)))
+                .map(|idx| activities[idx].clone())
+                .unwrap_or_default();
+            let name = match cluster.len() {
+                1 => most_frequent,
+                len => format!("{} +{}", most_frequent, len - 1),
+            };
+            ActivityGroup {
+                name,
+                activities: cluster.iter().map(|&idx| activities[idx].clone()).collect(),
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn dfg() -> DirectlyFollowsGraph {
+        // two tightly knit parts, connected by a rare step
+        let mut dfg = DirectlyFollowsGraph::new();
+        dfg.add_trace(["Order: Create", "Order: Check", "Order: Approve"], 10);
+        dfg.add_trace(["Invoice: Send", "Invoice: Pay"], 10);
+        dfg.add_trace(["Order: Approve", "Invoice: Send"], 1);
+        dfg
+    }
+
+    #[test]
+    fn test_prefix_and_categories() {
+        let groups = GroupingStrategy::Prefix(":".to_string()).groups(&dfg());
+        let names: Vec<&str> = groups.iter().map(|group| group.name.as_str()).collect();
+        assert_eq!(names, ["Invoice", "Order"]);
+        assert_eq!(groups[1].activities.len(), 3);
+
+        let categories = BTreeMap::from([
+            ("Order: Create".to_string(), "Ordering".to_string()),
+            ("Invoice: Pay".to_string(), "Paying".to_string()),
+        ]);
+        // groups of a single activity are left out
+        assert!(GroupingStrategy::Categories(categories)
+            .groups(&dfg())
+            .is_empty());
+
+        let mapping = group_mapping(&groups);
+        assert_eq!(mapping["Invoice: Pay"], "Invoice");
+        assert!(!mapping.contains_key("unknown"));
+    }
+
+    #[test]
+    fn test_connectivity_clusters() {
+        let clusters = connectivity_clusters(&dfg(), 2);
+        let names: BTreeSet<&str> = clusters
+            .iter()
+            .map(|cluster| cluster.name.as_str())
+            .collect();
+        assert_eq!(
+            names,
+            BTreeSet::from(["Invoice: Send +1", "Order: Approve +2"])
+        );
+
+        // unconnected activities are never merged
+        let mut unconnected = DirectlyFollowsGraph::new();
+        unconnected.add_trace(["A"], 1);
+        unconnected.add_trace(["B"], 1);
+        assert_eq!(connectivity_clusters(&unconnected, 1).len(), 2);
+    }
+}
@@ -0,0 +1,218 @@
+use std::collections::{BTreeMap, HashSet};
+
+use process_mining::event_log::EventLog;
+
+use crate::activity_labels::find_similar_labels;
+use crate::completeness::{incomplete_cases, CompletenessSettings};
+use crate::config::AnalysisConfig;
+use crate::i18n::{Language, Message};
+use crate::parser::duplicates::count_duplicate_events;
+use crate::parser::xes_extensions::XesExtensions;
+use crate::parser::{event_label, parse_into_event_log_with_config, Classifier, ParseError};
+use crate::timeline::case_timelines;
+
+/// A data quality problem the health score checks for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HealthCheck {
+    /// Events without a timestamp.
+    MissingTimestamps,
+    /// Exact duplicates of an earlier event, see
+    /// [`crate::parser::duplicates::count_duplicate_events`].
+    DuplicateEvents,
+    /// Cases flagged by [`crate::completeness::incomplete_cases`].
+    IncompleteCases,
+    /// Events whose label looks like a misspelling of a more frequent label, see
+    /// [`crate::activity_labels::find_similar_labels`].
+    InconsistentLabels,
+}
+
+impl HealthCheck {
+    pub fn message(&self) -> Message {
+        match self {
+            HealthCheck::MissingTimestamps => Message::MissingTimestamps,
+            HealthCheck::DuplicateEvents => Message::DuplicateEventsCheck,
+            HealthCheck::IncompleteCases => Message::IncompleteCasesCheck,
+            HealthCheck::InconsistentLabels => Message::InconsistentLabels,
+        }
+    }
+}
+
+/// How many events or cases a check found problems with.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CheckResult {
+    pub check: HealthCheck,
+    pub affected: usize,
+    /// Number of events, or cases for [`HealthCheck::IncompleteCases`].
+    pub total: usize,
+}
+
+impl CheckResult {
+    /// Share of the events or cases without the problem, from 0 to 100.
+    pub fn score(&self) -> f64 {
+        if self.total == 0 {
+            100.0
+        } else {
+            100.0 * (1.0 - self.affected as f64 / self.total as f64)
+        }
+    }
+}
+
+/// The data quality of a log: one score from 0 (unusable) to 100 (no problems found) and the
+/// checks it is made of.
+#[derive(Debug, Clone, PartialEq)]
+pub struct LogHealth {
+    pub checks: Vec<CheckResult>,
+}
+
+impl LogHealth {
+    /// Checks a log as it was imported, before duplicate events are dropped.
+    pub fn assess(
+        log: &EventLog,
+        classifier: &Classifier,
+        completeness: &CompletenessSettings,
+    ) -> Result<Self, ParseError> {
+        let resolved = classifier.resolve(log)?;
+        let events = log.traces.iter().flat_map(|trace| &trace.events);
+        let total_events = events.clone().count();
+
+        let mut label_frequencies: BTreeMap<String, usize> = BTreeMap::new();
+        for event in events.clone() {
+            if let Some(label) = event_label(event, &resolved, &log.global_event_attrs) {
+                *label_frequencies.entry(label).or_default() += 1;
+            }
+        }
+        let misspelled: HashSet<String> = find_similar_labels(&label_frequencies)
+            .into_iter()
+            .map(|pair| pair.duplicate)
+            .collect();
+
+        let timelines = case_timelines(log, classifier)?;
+        let check = |check, affected| CheckResult {
+            check,
+            affected,
+            total: total_events,
+        };
+        Ok(LogHealth {
+            checks: vec![
+                check(
+                    HealthCheck::MissingTimestamps,
+                    events.filter(|event| event.timestamp().is_none()).count(),
+                ),
+                check(
+                    HealthCheck::DuplicateEvents,
+                    count_duplicate_events(log, classifier)?,
+                ),
+                CheckResult {
+                    check: HealthCheck::IncompleteCases,
+                    affected: incomplete_cases(&timelines, completeness).len(),
+                    total: timelines.len(),
+                },
+                check(
+                    HealthCheck::InconsistentLabels,
+                    misspelled
+                        .iter()
+                        .map(|label| label_frequencies[label])
+                        .sum(),
+                ),
+            ],
+        })
+    }
+
+    /// Imports an XES document with the settings of `config` and checks it.
+    pub fn of(content: &str, config: &AnalysisConfig) -> Result<Self, ParseError> {
+        let mut import_config = config.import.import_config();
+        import_config.drop_duplicate_events = false;
+        let log = parse_into_event_log_with_config(None, Some(content), &import_config)?;
+        Self::assess(&log, &config.import.classifier, &config.completeness)
+    }
+
+    /// The mean score of the checks.
+    pub fn score(&self) -> f64 {
+        if self.checks.is_empty() {
+            return 100.0;
+        }
+        self.checks.iter().map(CheckResult::score).sum::<f64>() / self.checks.len() as f64
+    }
+
+    /// The score, followed by one line per check with its score and the affected events or
+    /// cases.
+    pub fn report(&self, language: Language) -> String {
+        let mut output = format!(
+            "{:<48}{:.1} / 100\n",
+            format!("{}:", Message::DataQuality.text(language)),
+            self.score()
+        );
+        for result in &self.checks {
+            output.push_str(&format!(
+                "  {:<46}{:<10.1}{} / {}\n",
+                result.check.message().text(language),
+                result.score(),
+                result.affected,
+                result.total
+            ));
+        }
+        output
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CONTENT: &str = r#"<log>
+        <trace>
+            <string key="concept:name" value="1"/>
+            <event>
+                <string key="concept:name" value="Register"/>
+                <date key="time:timestamp" value="2024-01-01T10:00:00+00:00"/>
+            </event>
+            <event>
+                <string key="concept:name" value="Register"/>
+                <date key="time:timestamp" value="2024-01-01T10:00:00+00:00"/>
+            </event>
+            <event>
+                <string key="concept:name" value="Approve"/>
+                <date key="time:timestamp" value="2024-01-01T11:00:00+00:00"/>
+            </event>
+        </trace>
+        <trace>
+            <string key="concept:name" value="2"/>
+            <event>
+                <string key="concept:name" value="register"/>
+                <date key="time:timestamp" value="2024-01-02T10:00:00+00:00"/>
+            </event>
+            <event>
+                <string key="concept:name" value="Approve"/>
+            </event>
+        </trace>
+    </log>"#;
+
+    #[test]
+    fn test_health() {
+        let health = LogHealth::of(CONTENT, &AnalysisConfig::default()).unwrap();
+        let affected: Vec<_> = health
+            .checks
+            .iter()
+            .map(|result| (result.check, result.affected, result.total))
+            .collect();
+        assert_eq!(
+            affected,
+            vec![
+                (HealthCheck::MissingTimestamps, 1, 5),
+                (HealthCheck::DuplicateEvents, 1, 5),
+                (HealthCheck::IncompleteCases, 0, 2),
+                (HealthCheck::InconsistentLabels, 1, 5),
+            ]
+        );
+        assert!((health.score() - 85.0).abs() < 1e-9);
+        assert!(health
+            .report(Language::English)
+            .starts_with("Data quality:"));
+    }
+
+    #[test]
+    fn test_empty_log() {
+        let health = LogHealth::of("<log></log>", &AnalysisConfig::default()).unwrap();
+        assert_eq!(health.score(), 100.0);
+    }
+}
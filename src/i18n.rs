@@ -0,0 +1,617 @@
+use std::fmt;
+use std::str::FromStr;
+
+use serde::{Deserialize, Serialize};
+
+/// Languages the UI and the generated reports are available in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum Language {
+    #[default]
+    #[serde(rename = "en")]
+    English,
+    #[serde(rename = "de")]
+    German,
+}
+
+impl Language {
+    pub const ALL: [Language; 2] = [Language::English, Language::German];
+
+    /// The ISO 639-1 code of the language.
+    pub fn code(&self) -> &'static str {
+        match self {
+            Language::English => "en",
+            Language::German => "de",
+        }
+    }
+
+    /// The name of the language in the language itself.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Language::English => "English",
+            Language::German => "Deutsch",
+        }
+    }
+}
+
+impl FromStr for Language {
+    type Err = String;
+
+    /// Parses a language code, ignoring a region suffix (e.g. `de-AT`).
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let code = s.split(['-', '_']).next().unwrap_or_default();
+        Language::ALL
+            .into_iter()
+            .find(|language| language.code().eq_ignore_ascii_case(code))
+            .ok_or_else(|| format!("Unsupported language: {}", s))
+    }
+}
+
+impl fmt::Display for Language {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+/// Every translatable phrase of the UI and the reports.
+///
+/// Phrases may contain `{}` placeholders, which are filled in order by [`Message::format`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Message {
+    // UI controls
+    TextPlaceholder,
+    Seed,
+    CaseAttributes,
+    CaseAttributesPlaceholder,
+    TimestampKey,
+    ResourceKey,
+    LifecycleEvents,
+    LifecycleComplete,
+    LifecycleStart,
+    LifecycleAll,
+    MaxGap,
+    MaxGapPlaceholder,
+    SessionGap,
+    MinSupport,
+    BusinessHours,
+    MergeSimilarLabels,
+    DropDuplicateEvents,
+    ExcludeIncompleteCases,
+    MarkOngoingCases,
+    ImportModel,
+    ImportXes,
+    ConvertToXes,
+    DownloadXes,
+    ExportCsv,
+    ExportXes,
+    ExportWipCsv,
+    ExportActivityProfileCsv,
+    LoadConfig,
+    SaveConfig,
+    Palette,
+    PaletteDefault,
+    PaletteColorBlindSafe,
+    PaletteGrayscale,
+    DarkBackground,
+    FontSize,
+    Language,
+    Case,
+    FirstCase,
+    ShowTimeline,
+    ShowVariants,
+    ShowRepetitions,
+    ShowGateways,
+    MineDecisions,
+    ShowBatching,
+    ShowActivityProfile,
+    ShowProcessMap,
+    ReferenceCase,
+    CompareWithReference,
+    // status messages
+    ErrorReadingFile,
+    ErrorLoadingFile,
+    ErrorLoadingModel,
+    ErrorLoadingConfig,
+    ModelLoaded,
+    UnknownCase,
+    // report
+    Relations,
+    IndependencesPerRelations,
+    TemporalIndependencesPerRelations,
+    MaxVariantFrequencyPerTraces,
+    VariantsPerTraces,
+    EventualEquivalences,
+    DirectEquivalences,
+    Variants,
+    MaxVariantFrequencyPerVariants,
+    VariantEntropy,
+    NormalizedVariantEntropy,
+    MarkovPerplexity,
+    LempelZivComplexity,
+    Magnitude,
+    Variety,
+    LevelOfDetail,
+    AverageTraceLength,
+    Affinity,
+    DeviationFromRandom,
+    RelationshipTypeFrequencies,
+    SimilarActivityLabels,
+    MergedActivityLabels,
+    DataQuality,
+    MissingTimestamps,
+    DuplicateEventsCheck,
+    IncompleteCasesCheck,
+    InconsistentLabels,
+    Diagnostics,
+    MixedTimestampOffsets,
+    DuplicateEvents,
+    DuplicateEventsDropped,
+    UnfilteredLifecycleCases,
+    IncompleteCases,
+    IncompleteCasesExcluded,
+    IncompleteCasesOngoing,
+    TraceLengthOutliersExcluded,
+    ApproximateMatrix,
+    EstimatedCells,
+    Implication,
+    ReverseImplication,
+    LtlFormulas,
+    DeclareConstraints,
+    TokenReplay,
+    Fitness,
+    FittingTraces,
+    FitnessTrend,
+    DeviatingVariants,
+    DeviationsByActivity,
+    Deviation,
+    Skipped,
+    Inserted,
+    OutOfOrder,
+    Occurrences,
+    ExampleCases,
+    TemporalConstraints,
+    TemporalViolations,
+    ActivityPair,
+    Median,
+    Limit,
+    SlaBreaches,
+    Sla,
+    Breaches,
+    BreachRate,
+    WorstCases,
+    LongDistanceDependencies,
+    Confidence,
+    Lift,
+    Gateway,
+    Type,
+    Split,
+    Join,
+    Branches,
+    DecisionPoint,
+    Decisions,
+    Accuracy,
+    Support,
+    WhatIfSimulation,
+    Baseline,
+    Scenario,
+    Change,
+    MeanCycleTime,
+    MedianCycleTime,
+    ThroughputPerDay,
+    MeanWip,
+    TraceLengthDistribution,
+    TraceLengthOutliers,
+    Events,
+    WeightedBy,
+    TotalWeight,
+    CasesWithoutWeight,
+    Weight,
+    Share,
+    ResourceDependencies,
+    SameResource,
+    DifferentResources,
+    HoldsOnly,
+    ActivityProfile,
+    TraceDiff,
+    Similarity,
+    RepresentativeCases,
+    RepairSuggestions,
+    Repair,
+    InsertActivity,
+    RelabelActivity,
+    MoveActivity,
+    RemoveActivity,
+    AfterActivity,
+    BeforeActivity,
+    CaseArrivals,
+    MeanInterArrivalTime,
+    InterArrivalTimePercentiles,
+    ArrivalsPerDay,
+    Period,
+    ModelNodes,
+    ModelEdges,
+    AverageConnectorDegree,
+    CyclomaticComplexity,
+    RecommendThreshold,
+    RecommendedThreshold,
+    Threshold,
+    Simplicity,
+    Score,
+    Glossary,
+    ConfidenceIntervals,
+    Activity,
+    Cases,
+    DistanceInEvents,
+    DistanceInTime,
+    SimultaneousBatches,
+    ResourceBursts,
+    BatchedShare,
+    Batches,
+    MeanBatchSize,
+    CohortAttribute,
+    CohortAttributePlaceholder,
+    CompareCohorts,
+    DurationPercentiles,
+    Summary,
+    DependencyMatrix,
+    VariantTable,
+    Metric,
+    Value,
+    Variant,
+    Frequency,
+    Activities,
+    ActivityInstances,
+    FirstEvent,
+    LastEvent,
+    Duration,
+    WaitingTime,
+    Resources,
+}
+
+impl Message {
+    /// Returns the phrase in `language`.
+    pub fn text(&self, language: Language) -> &'static str {
+        let (english, german) = match self {
+            Message::TextPlaceholder => ("Enter your text here", "Text hier eingeben"),
+            Message::Seed => ("Seed", "Seed"),
+            Message::CaseAttributes => ("Case attributes", "Fallattribute"),
+            Message::CaseAttributesPlaceholder => ("e.g. order, item", "z.B. order, item"),
+            Message::TimestampKey => ("Timestamp attribute", "Zeitstempelattribut"),
+            Message::ResourceKey => ("Resource attribute", "Ressourcenattribut"),
+            Message::LifecycleEvents => ("Lifecycle events", "Lebenszyklus-Ereignisse"),
+            Message::LifecycleComplete => ("complete only", "nur complete"),
+            Message::LifecycleStart => ("start only", "nur start"),
+            Message::LifecycleAll => ("all", "alle"),
+            Message::MaxGap => ("Max. gap (min)", "Max. Lücke (min)"),
+            Message::MaxGapPlaceholder => ("off", "aus"),
+            Message::SessionGap => ("Session gap (min)", "Sitzungslücke (min)"),
+            Message::BusinessHours => ("Business hours", "Geschäftszeiten"),
+            Message::MinSupport => ("Min. support (traces)", "Min. Support (Traces)"),
+            Message::MergeSimilarLabels => ("Merge similar labels", "Ähnliche Labels zusammenführen"),
+            Message::DropDuplicateEvents => ("Drop duplicate events", "Doppelte Events entfernen"),
+            Message::ExcludeIncompleteCases => ("Exclude incomplete cases", "Unvollständige Fälle ausschließen"),
+            Message::MarkOngoingCases => ("Incomplete cases as ongoing", "Unvollständige Fälle als laufend"),
+            Message::ImportModel => ("Import Model", "Modell importieren"),
+            Message::ImportXes => ("Import XES", "XES importieren"),
+            Message::ConvertToXes => ("Convert To XES", "In XES umwandeln"),
+            Message::DownloadXes => ("Download XES", "XES herunterladen"),
+            Message::ExportCsv => ("Export CSV", "CSV exportieren"),
+            Message::ExportXes => ("Export XES with attributes", "XES mit Attributen exportieren"),
+            Message::ExportWipCsv => ("Export WIP CSV", "WIP-CSV exportieren"),
+            Message::ExportActivityProfileCsv => ("Export Activity Profile CSV", "Aktivitätsprofil-CSV exportieren"),
+            Message::LoadConfig => ("Load Config", "Konfiguration laden"),
+            Message::SaveConfig => ("Save Config", "Konfiguration speichern"),
+            Message::Palette => ("Palette", "Farbpalette"),
+            Message::PaletteDefault => ("Default", "Standard"),
+            Message::PaletteColorBlindSafe => ("Color-blind safe", "Farbenblind-sicher"),
+            Message::PaletteGrayscale => ("Grayscale", "Graustufen"),
+            Message::DarkBackground => ("Dark", "Dunkel"),
+            Message::FontSize => ("Font size", "Schriftgröße"),
+            Message::Language => ("Language", "Sprache"),
+            Message::Case => ("Case", "Fall"),
+            Message::FirstCase => ("first case", "erster Fall"),
+            Message::ShowTimeline => ("Show Timeline", "Zeitstrahl anzeigen"),
+            Message::ShowVariants => ("Show Variants", "Varianten anzeigen"),
+            Message::ShowRepetitions => ("Show Repetitions", "Wiederholungen anzeigen"),
+            Message::ShowGateways => ("Show Splits and Joins", "Verzweigungen anzeigen"),
+            Message::MineDecisions => ("Mine Decisions", "Entscheidungen analysieren"),
+            Message::ShowBatching => ("Show Batching", "Batching anzeigen"),
+            Message::ShowActivityProfile => ("Show Activity Profile", "Aktivitätsprofil anzeigen"),
+            Message::ShowProcessMap => ("Show Process Map", "Prozesslandkarte anzeigen"),
+            Message::ReferenceCase => ("Reference case", "Referenzfall"),
+            Message::CompareWithReference => ("Compare with Reference", "Mit Referenz vergleichen"),
+            Message::ErrorReadingFile => ("Error reading file", "Fehler beim Lesen der Datei"),
+            Message::ErrorLoadingFile => ("Error loading file: {}", "Fehler beim Laden der Datei: {}"),
+            Message::ErrorLoadingModel => {
+                ("Error loading model: {}", "Fehler beim Laden des Modells: {}")
+            }
+            Message::ErrorLoadingConfig => (
+                "Error loading configuration: {}",
+                "Fehler beim Laden der Konfiguration: {}",
+            ),
+            Message::ModelLoaded => (
+                "Loaded model {}, import an event log to check its conformance.",
+                "Modell {} geladen, importieren Sie ein Ereignislog, um dessen Konformität zu prüfen.",
+            ),
+            Message::UnknownCase => ("Unknown case: {}", "Unbekannter Fall: {}"),
+            Message::Relations => ("#relations", "#Relationen"),
+            Message::IndependencesPerRelations => {
+                ("#independence / #relations", "#Unabhängigkeiten / #Relationen")
+            }
+            Message::TemporalIndependencesPerRelations => (
+                "#temporal independence / #relations",
+                "#zeitliche Unabhängigkeiten / #Relationen",
+            ),
+            Message::MaxVariantFrequencyPerTraces => (
+                "max. frequency of variants / total #traces",
+                "max. Variantenhäufigkeit / #Traces gesamt",
+            ),
+            Message::VariantsPerTraces => ("#variants / total #traces", "#Varianten / #Traces gesamt"),
+            Message::EventualEquivalences => ("#(Eventual, <=>)", "#(Eventual, <=>)"),
+            Message::DirectEquivalences => ("#(Direct, <=>)", "#(Direct, <=>)"),
+            Message::Variants => ("#variants", "#Varianten"),
+            Message::MaxVariantFrequencyPerVariants => (
+                "max. frequency of variants / #variants",
+                "max. Variantenhäufigkeit / #Varianten",
+            ),
+            Message::VariantEntropy => ("Variant Entropy", "Varianten-Entropie"),
+            Message::NormalizedVariantEntropy => {
+                ("Normalized Variant Entropy", "Normalisierte Varianten-Entropie")
+            }
+            Message::MarkovPerplexity => {
+                ("Markov perplexity (order {})", "Markov-Perplexität (Ordnung {})")
+            }
+            Message::LempelZivComplexity => ("Lempel-Ziv complexity", "Lempel-Ziv-Komplexität"),
+            Message::Magnitude => ("Events (magnitude)", "Events (Umfang)"),
+            Message::Variety => ("Activities (variety)", "Aktivitäten (Vielfalt)"),
+            Message::LevelOfDetail => ("Level of detail", "Detaillierungsgrad"),
+            Message::AverageTraceLength => ("Average trace length", "Mittlere Trace-Länge"),
+            Message::Affinity => ("Affinity", "Affinität"),
+            Message::DeviationFromRandom => ("Deviation from random", "Abweichung vom Zufall"),
+            Message::RelationshipTypeFrequencies => {
+                ("Relationship Type Frequencies", "Häufigkeiten der Beziehungstypen")
+            }
+            Message::DataQuality => ("Data quality", "Datenqualität"),
+            Message::MissingTimestamps => ("Events without timestamp", "Events ohne Zeitstempel"),
+            Message::DuplicateEventsCheck => ("Duplicate events", "Doppelte Events"),
+            Message::IncompleteCasesCheck => ("Incomplete cases", "Unvollständige Fälle"),
+            Message::InconsistentLabels => ("Inconsistently spelled labels", "Uneinheitlich geschriebene Bezeichnungen"),
+            Message::Diagnostics => ("Diagnostics", "Diagnose"),
+            Message::MixedTimestampOffsets => (
+                "The log mixes timestamps with different UTC offsets: {}. Set the time zone of timestamps without offset to avoid ordering errors.",
+                "Das Log mischt Zeitstempel mit unterschiedlichen UTC-Offsets: {}. Legen Sie die Zeitzone von Zeitstempeln ohne Offset fest, um Fehler in der Reihenfolge zu vermeiden.",
+            ),
+            Message::DuplicateEvents => (
+                "{} duplicate events (same case, activity and timestamp as an earlier event).",
+                "{} doppelte Events (gleicher Fall, gleiche Aktivität und gleicher Zeitstempel wie ein früheres Event).",
+            ),
+            Message::DuplicateEventsDropped => (
+                "{} duplicate events (same case, activity and timestamp as an earlier event) were dropped.",
+                "{} doppelte Events (gleicher Fall, gleiche Aktivität und gleicher Zeitstempel wie ein früheres Event) wurden entfernt.",
+            ),
+            Message::UnfilteredLifecycleCases => (
+                "{} cases have no event with the selected lifecycle transitions, so all of their events are analyzed: {}.",
+                "{} Fälle haben kein Event mit den ausgewählten Lifecycle-Transitionen, daher werden alle ihre Events analysiert: {}.",
+            ),
+            Message::IncompleteCases => (
+                "{} cases look incomplete (atypical end activity or inactive long before the end of the log).",
+                "{} Fälle wirken unvollständig (untypische Endaktivität oder lange vor dem Ende des Logs inaktiv).",
+            ),
+            Message::IncompleteCasesExcluded => (
+                "{} cases look incomplete (atypical end activity or inactive long before the end of the log) and were excluded from the duration and dependency statistics.",
+                "{} Fälle wirken unvollständig (untypische Endaktivität oder lange vor dem Ende des Logs inaktiv) und wurden aus den Dauer- und Abhängigkeitsstatistiken ausgeschlossen.",
+            ),
+            Message::IncompleteCasesOngoing => (
+                "{} cases look incomplete (atypical end activity or inactive long before the end of the log) and are analyzed as ongoing cases, without end activity and duration.",
+                "{} Fälle wirken unvollständig (untypische Endaktivität oder lange vor dem Ende des Logs inaktiv) und werden als laufende Fälle ohne Endaktivität und Dauer analysiert.",
+            ),
+            Message::TraceLengthOutliersExcluded => (
+                "{} cases with an outlying trace length were excluded from the analysis.",
+                "{} Fälle mit einer ausreißenden Trace-Länge wurden von der Analyse ausgeschlossen.",
+            ),
+            Message::ApproximateMatrix => (
+                "The dependency matrix was computed on a sample of {} of the {} cases.",
+                "Die Abhängigkeitsmatrix wurde auf einer Stichprobe von {} der {} Fälle berechnet.",
+            ),
+            Message::EstimatedCells => (
+                "Matrix Cells Left Open by the Sample (confidence intervals)",
+                "Von der Stichprobe offen gelassene Matrixzellen (Konfidenzintervalle)",
+            ),
+            Message::Implication => ("Implication", "Implikation"),
+            Message::ReverseImplication => ("Reverse implication", "Umgekehrte Implikation"),
+            Message::SimilarActivityLabels => ("Similar Activity Labels", "Ähnliche Aktivitätslabels"),
+            Message::MergedActivityLabels => (
+                "Merged Activity Labels",
+                "Zusammengeführte Aktivitätslabels",
+            ),
+            Message::LtlFormulas => (
+                "LTLf Formulas (satisfaction rate)",
+                "LTLf-Formeln (Erfüllungsrate)",
+            ),
+            Message::DeclareConstraints => (
+                "DECLARE Constraints (support / confidence / violating traces)",
+                "DECLARE-Constraints (Support / Konfidenz / verletzende Traces)",
+            ),
+            Message::TokenReplay => ("Conformance (Token Replay)", "Konformität (Token Replay)"),
+            Message::Fitness => ("Fitness", "Fitness"),
+            Message::FittingTraces => ("Fitting traces", "Passende Traces"),
+            Message::FitnessTrend => ("Fitness over time", "Fitness im Zeitverlauf"),
+            Message::DeviatingVariants => (
+                "Alignments of the most frequent deviating variants",
+                "Alignments der häufigsten abweichenden Varianten",
+            ),
+            Message::DeviationsByActivity => ("Deviations by activity", "Abweichungen je Aktivität"),
+            Message::Deviation => ("Deviation", "Abweichung"),
+            Message::Skipped => ("skipped", "übersprungen"),
+            Message::Inserted => ("inserted", "eingefügt"),
+            Message::OutOfOrder => ("out of order", "in falscher Reihenfolge"),
+            Message::Occurrences => ("Occurrences", "Vorkommen"),
+            Message::ExampleCases => ("Example cases", "Beispielfälle"),
+            Message::TemporalConstraints => ("Temporal constraints", "Zeitliche Vorgaben"),
+            Message::TemporalViolations => (
+                "Cases exceeding a temporal constraint",
+                "Fälle über einer zeitlichen Vorgabe",
+            ),
+            Message::ActivityPair => ("Activity pair", "Aktivitätspaar"),
+            Message::Median => ("Median", "Median"),
+            Message::Limit => ("Limit", "Grenze"),
+            Message::SlaBreaches => ("SLA breaches", "SLA-Verletzungen"),
+            Message::Sla => ("SLA", "SLA"),
+            Message::Breaches => ("Breaches", "Verletzungen"),
+            Message::BreachRate => ("Breach rate", "Verletzungsquote"),
+            Message::WorstCases => ("worst cases", "schlimmste Fälle"),
+            Message::LongDistanceDependencies => (
+                "Long-distance dependencies",
+                "Fernabhängigkeiten",
+            ),
+            Message::Confidence => ("Confidence", "Konfidenz"),
+            Message::Lift => ("Lift", "Lift"),
+            Message::Gateway => ("Gateway", "Gateway"),
+            Message::Type => ("Type", "Typ"),
+            Message::Split => ("split", "Split"),
+            Message::Join => ("join", "Join"),
+            Message::Branches => ("Branches", "Zweige"),
+            Message::DecisionPoint => ("Decision point", "Entscheidungspunkt"),
+            Message::Decisions => ("decisions", "Entscheidungen"),
+            Message::Accuracy => ("accuracy", "Genauigkeit"),
+            Message::Support => ("support", "Support"),
+            Message::WhatIfSimulation => ("What-if simulation", "Was-wäre-wenn-Simulation"),
+            Message::Baseline => ("Baseline", "Ausgangslage"),
+            Message::Scenario => ("Scenario", "Szenario"),
+            Message::Change => ("Change", "Änderung"),
+            Message::MeanCycleTime => ("Mean cycle time", "Mittlere Durchlaufzeit"),
+            Message::MedianCycleTime => ("Median cycle time", "Median der Durchlaufzeit"),
+            Message::ThroughputPerDay => ("Throughput per day", "Durchsatz pro Tag"),
+            Message::MeanWip => ("Mean work in progress", "Mittlere Anzahl offener Fälle"),
+            Message::TraceLengthDistribution => ("Trace length distribution", "Verteilung der Trace-Längen"),
+            Message::TraceLengthOutliers => (
+                "{} cases with an outlying length (outside {} to {} events)",
+                "{} Fälle mit ausreißender Länge (außerhalb von {} bis {} Events)",
+            ),
+            Message::Events => ("Events", "Events"),
+            Message::WeightedBy => ("Weighted by {}", "Gewichtet nach {}"),
+            Message::TotalWeight => ("Total weight", "Gesamtgewicht"),
+            Message::CasesWithoutWeight => ("Cases without weight", "Fälle ohne Gewicht"),
+            Message::Weight => ("Weight", "Gewicht"),
+            Message::Share => ("Share", "Anteil"),
+            Message::ResourceDependencies => (
+                "Dependencies by resource context",
+                "Abhängigkeiten nach Ressourcenkontext",
+            ),
+            Message::SameResource => ("Same resource", "Gleiche Ressource"),
+            Message::DifferentResources => ("Different resources", "Verschiedene Ressourcen"),
+            Message::HoldsOnly => ("Holds only with", "Gilt nur bei"),
+            Message::CaseArrivals => ("Case arrivals", "Fallankünfte"),
+            Message::MeanInterArrivalTime => ("Mean inter-arrival time", "Mittlere Zwischenankunftszeit"),
+            Message::InterArrivalTimePercentiles => (
+                "Inter-arrival time P25 / P50 / P75 / P90",
+                "Zwischenankunftszeit P25 / P50 / P75 / P90",
+            ),
+            Message::ArrivalsPerDay => ("Arrivals per day", "Ankünfte pro Tag"),
+            Message::TraceDiff => (
+                "Case {} compared with the reference case {}",
+                "Fall {} im Vergleich mit dem Referenzfall {}",
+            ),
+            Message::Similarity => ("Similarity", "Ähnlichkeit"),
+            Message::RepresentativeCases => ("Representative cases", "Repräsentative Fälle"),
+            Message::RepairSuggestions => ("Repair suggestions", "Reparaturvorschläge"),
+            Message::Repair => ("Repair", "Reparatur"),
+            Message::InsertActivity => ("insert {}", "{} einfügen"),
+            Message::RelabelActivity => ("relabel {} as {}", "{} in {} umbenennen"),
+            Message::MoveActivity => ("move {}", "{} verschieben"),
+            Message::RemoveActivity => ("remove {}", "{} entfernen"),
+            Message::AfterActivity => ("after {}", "nach {}"),
+            Message::BeforeActivity => ("before {}", "vor {}"),
+            Message::ActivityProfile => (
+                "Events by hour of the day and weekday",
+                "Events nach Tageszeit und Wochentag",
+            ),
+            Message::Period => ("Period", "Zeitraum"),
+            Message::ModelNodes => ("Model nodes", "Modellknoten"),
+            Message::ModelEdges => ("Model edges", "Modellkanten"),
+            Message::AverageConnectorDegree => ("Avg. connector degree", "Mittlerer Konnektorgrad"),
+            Message::CyclomaticComplexity => ("Cyclomatic complexity", "Zyklomatische Komplexität"),
+            Message::RecommendThreshold => ("Recommend DFG threshold", "DFG-Schwellenwert empfehlen"),
+            Message::RecommendedThreshold => ("Recommended threshold", "Empfohlener Schwellenwert"),
+            Message::Threshold => ("Threshold", "Schwelle"),
+            Message::Simplicity => ("Simplicity", "Einfachheit"),
+            Message::Score => ("Score", "Bewertung"),
+            Message::Glossary => ("Glossary", "Glossar"),
+            Message::ConfidenceIntervals => ("Confidence intervals", "Konfidenzintervalle"),
+            Message::Activity => ("Activity", "Aktivität"),
+            Message::Cases => ("Cases", "Fälle"),
+            Message::DistanceInEvents => ("Events (min / median / max)", "Events (Min. / Median / Max.)"),
+            Message::DistanceInTime => ("Time (min / median / max)", "Zeit (Min. / Median / Max.)"),
+            Message::SimultaneousBatches => (
+                "Executed for several cases at the same time",
+                "Für mehrere Fälle gleichzeitig ausgeführt",
+            ),
+            Message::ResourceBursts => (
+                "Executed by one resource in quick succession",
+                "Von einer Ressource in schneller Folge ausgeführt",
+            ),
+            Message::BatchedShare => ("Batched share", "Anteil im Batch"),
+            Message::Batches => ("Batches", "Batches"),
+            Message::MeanBatchSize => ("Mean batch size", "Mittlere Batchgröße"),
+            Message::CohortAttribute => ("Cohort attribute", "Kohortenattribut"),
+            Message::CohortAttributePlaceholder => ("e.g. channel", "z.B. channel"),
+            Message::CompareCohorts => ("Compare cohorts", "Kohorten vergleichen"),
+            Message::DurationPercentiles => {
+                ("Duration P25 / P50 / P75 / P90", "Dauer P25 / P50 / P75 / P90")
+            }
+            Message::Summary => ("Summary", "Zusammenfassung"),
+            Message::DependencyMatrix => ("Dependency matrix", "Abhängigkeitsmatrix"),
+            Message::VariantTable => ("Variants", "Varianten"),
+            Message::Metric => ("Metric", "Kennzahl"),
+            Message::Value => ("Value", "Wert"),
+            Message::Variant => ("Variant", "Variante"),
+            Message::Frequency => ("Frequency", "Häufigkeit"),
+            Message::Activities => ("Activities", "Aktivitäten"),
+            Message::ActivityInstances => ("Activity instances", "Aktivitätsinstanzen"),
+            Message::FirstEvent => ("First event", "Erstes Event"),
+            Message::LastEvent => ("Last event", "Letztes Event"),
+            Message::Duration => ("Duration", "Dauer"),
+            Message::WaitingTime => ("Waiting time", "Wartezeit"),
+            Message::Resources => ("Resources", "Ressourcen"),
+        };
+        match language {
+            Language::English => english,
+            Language::German => german,
+        }
+    }
+
+    /// Returns the phrase in `language` with its `{}` placeholders replaced by `args` in order.
+    /// Surplus placeholders are left as they are.
+    pub fn format(&self, language: Language, args: &[&str]) -> String {
+        let mut parts = self.text(language).split("{}");
+        let mut output = parts.next().unwrap_or_default().to_string();
+        for (idx, part) in parts.enumerate() {
+            output.push_str(args.get(idx).copied().unwrap_or("{}"));
+            output.push_str(part);
+        }
+        output
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_language() {
+        assert_eq!("de-AT".parse(), Ok(Language::German));
+        assert_eq!("EN".parse(), Ok(Language::English));
+        assert!("fr".parse::<Language>().is_err());
+    }
+
+    #[test]
+    fn test_format() {
+        assert_eq!(
+            Message::UnknownCase.format(Language::German, &["c1"]),
+            "Unbekannter Fall: c1"
+        );
+        assert_eq!(
+            Message::ModelLoaded.format(Language::English, &[]),
+            "Loaded model {}, import an event log to check its conformance."
+        );
+    }
+}
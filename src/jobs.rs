@@ -0,0 +1,577 @@
+//! Analyses that run in the background: a log is submitted, its job id is returned right
+//! away, and the status and the result are polled later. This is what a server mode needs
+//! for logs whose analysis takes longer than a request may.
+//!
+//! A fixed number of worker threads runs the queued jobs in the order they were submitted.
+//! The memory of a job is bounded by the size of its log, as the analysis needs a multiple
+//! of it, and the results of only so many finished jobs are kept.
+//!
+//! With an [`Authenticator`] and an [`AuditLog`], submissions via [`JobQueue::submit_as`] need
+//! a valid API key and are recorded, see [`crate::audit`]. Clients can only submit the content
+//! of a log, or the path of a file in the configured upload directory.
+
+use std::collections::{HashMap, VecDeque};
+use std::fs::File;
+use std::io::Read;
+use std::path::PathBuf;
+use std::panic::{catch_unwind, AssertUnwindSafe};
+use std::sync::{Arc, Condvar, Mutex, MutexGuard};
+use std::thread::JoinHandle;
+use std::time::{Duration, Instant};
+
+use crate::analysis::{analyze, AnalysisResult, LogInput};
+use crate::audit::{AccessError, AuditEntry, AuditLog, Authenticator};
+use crate::config::AnalysisConfig;
+use crate::parser::{decode_xes, decompressed_size};
+
+/// How many jobs run at once, how large their logs may be and how many results are kept.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct JobSettings {
+    /// Number of jobs that run at the same time, at least 1.
+    pub concurrency: usize,
+    /// Largest log (file or content) in bytes a job accepts, unlimited if `None`. Compressed
+    /// files are measured decompressed.
+    pub max_log_bytes: Option<u64>,
+    /// Number of finished jobs whose status and result are kept; beyond that, the jobs that
+    /// finished first are forgotten as if they were removed.
+    pub max_finished_jobs: usize,
+}
+
+impl Default for JobSettings {
+    fn default() -> Self {
+        JobSettings {
+            concurrency: 2,
+            max_log_bytes: None,
+            max_finished_jobs: 100,
+        }
+    }
+}
+
+pub type JobId = u64;
+
+/// Where a job is.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum JobStatus {
+    /// Waiting for a worker, behind `position` other jobs.
+    Queued { position: usize },
+    /// Running for `elapsed`.
+    Running { elapsed: Duration },
+    /// Finished, the result can be fetched.
+    Done,
+    /// The log was rejected or couldn't be analyzed.
+    Failed(String),
+}
+
+impl JobStatus {
+    /// Whether the job is done or failed.
+    pub fn is_finished(&self) -> bool {
+        matches!(self, JobStatus::Done | JobStatus::Failed(_))
+    }
+}
+
+enum JobState {
+    Queued,
+    Running(Instant),
+    Done(Arc<AnalysisResult>),
+    Failed(String),
+}
+
+struct Job {
+    id: JobId,
+    input: LogInput,
+    config: AnalysisConfig,
+}
+
+// A log to analyze as it was submitted
+struct Submission {
+    input: LogInput,
+    // how the log is named in the audit log, if not by `input`
+    log: Option<String>,
+    // why the job fails right away
+    rejection: Option<String>,
+}
+
+impl Submission {
+    fn of(input: LogInput) -> Self {
+        Submission {
+            input,
+            log: None,
+            rejection: None,
+        }
+    }
+}
+
+fn too_large(max: u64) -> String {
+    format!("the log has more than the limit of {} bytes", max)
+}
+
+#[derive(Default)]
+struct State {
+    next_id: JobId,
+    queue: VecDeque<Job>,
+    jobs: HashMap<JobId, JobState>,
+    // the done and failed jobs, in the order they finished
+    finished: VecDeque<JobId>,
+    shutdown: bool,
+}
+
+impl State {
+    // Stores the outcome of a job and forgets the jobs that finished first beyond
+    // `max_finished`
+    fn finish(&mut self, id: JobId, job_state: JobState, max_finished: usize) {
+        self.jobs.insert(id, job_state);
+        self.finished.push_back(id);
+        while self.finished.len() > max_finished {
+            if let Some(oldest) = self.finished.pop_front() {
+                self.jobs.remove(&oldest);
+            }
+        }
+    }
+}
+
+#[derive(Default)]
+struct Shared {
+    state: Mutex<State>,
+    // signalled when a job is queued or the queue shuts down
+    queued: Condvar,
+    // signalled when a job is done or failed
+    finished: Condvar,
+}
+
+impl Shared {
+    fn lock(&self) -> MutexGuard<'_, State> {
+        // a panicking analysis is caught outside of the lock, so the state stays consistent
+        self.state
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+/// Runs analyses on worker threads, see the [module documentation](self).
+pub struct JobQueue {
+    settings: JobSettings,
+    shared: Arc<Shared>,
+    workers: Vec<JoinHandle<()>>,
+    authenticator: Option<Box<dyn Authenticator>>,
+    audit_log: Option<Box<dyn AuditLog>>,
+    upload_dir: Option<PathBuf>,
+}
+
+impl JobQueue {
+    /// Starts the workers.
+    pub fn new(settings: JobSettings) -> Self {
+        let shared = Arc::new(Shared::default());
+        let workers = (0..settings.concurrency.max(1))
+            .map(|_| {
+                let shared = Arc::clone(&shared);
+                std::thread::spawn(move || work(&shared, settings.max_finished_jobs))
+            })
+            .collect();
+        JobQueue {
+            settings,
+            shared,
+            workers,
+            authenticator: None,
+            audit_log: None,
+            upload_dir: None,
+        }
+    }
+
+    /// Only accepts submissions via [`JobQueue::submit_as`] with an API key `authenticator`
+    /// knows.
+    pub fn with_authenticator(mut self, authenticator: impl Authenticator + 'static) -> Self {
+        self.authenticator = Some(Box::new(authenticator));
+        self
+    }
+
+    /// Lets [`JobQueue::submit_as`] accept paths of files in `dir`, relative to it.
+    pub fn with_upload_dir(mut self, dir: impl Into<PathBuf>) -> Self {
+        self.upload_dir = Some(dir.into());
+        self
+    }
+
+    /// Records every submission in `audit_log`.
+    pub fn with_audit_log(mut self, audit_log: impl AuditLog + 'static) -> Self {
+        self.audit_log = Some(Box::new(audit_log));
+        self
+    }
+
+    /// Queues the analysis of `input` with `config`. A log larger than the limit of the
+    /// settings fails right away.
+    ///
+    /// This doesn't check an API key, it is meant for the embedding application itself;
+    /// requests of clients go through [`JobQueue::submit_as`]. If recording the submission in
+    /// the audit log fails, the job fails.
+    pub fn submit(&self, input: LogInput, config: AnalysisConfig) -> JobId {
+        self.enqueue(None, Submission::of(input), config).unwrap_or_else(|(id, _)| id)
+    }
+
+    /// Like [`JobQueue::submit`], for the user `api_key` belongs to. The job isn't queued if
+    /// the key is invalid, `input` is a path outside of the upload directory or the submission
+    /// can't be recorded in the audit log. Without an authenticator every key is accepted, for
+    /// an anonymous user; without an upload directory only the content of logs is. An uploaded
+    /// file is read when it is submitted, not when the job runs.
+    pub fn submit_as(
+        &self,
+        api_key: &str,
+        input: LogInput,
+        config: AnalysisConfig,
+    ) -> Result<JobId, AccessError> {
+        let user = match &self.authenticator {
+            Some(authenticator) => {
+                Some(authenticator.authenticate(api_key).ok_or(AccessError::Denied)?)
+            }
+            None => None,
+        };
+        let submission = match input {
+            LogInput::Path(path) => self.read_upload(&path)?,
+            content => Submission::of(content),
+        };
+        match self.enqueue(user, submission, config) {
+            Ok(id) => Ok(id),
+            Err((id, error)) => {
+                self.remove(id);
+                Err(AccessError::Audit(error))
+            }
+        }
+    }
+
+    // Reads an uploaded file, resolved against the upload directory; links are followed, so a
+    // path can't lead out of it. The file is opened at the resolved path that was checked and
+    // read right away, so it can't be swapped for another one before the job runs.
+    fn read_upload(&self, path: &str) -> Result<Submission, AccessError> {
+        let forbidden = || AccessError::Forbidden(path.to_string());
+        let dir = self
+            .upload_dir
+            .as_ref()
+            .and_then(|dir| dir.canonicalize().ok())
+            .ok_or_else(forbidden)?;
+        let resolved = dir.join(path).canonicalize().map_err(|_| forbidden())?;
+        if !resolved.starts_with(&dir) {
+            return Err(forbidden());
+        }
+        let file = File::open(&resolved).map_err(|_| forbidden())?;
+        let log = resolved.to_string_lossy().into_owned();
+
+        // one byte more than the limit, to know that the file exceeds it
+        let limit = self.settings.max_log_bytes.map_or(u64::MAX, |max| max.saturating_add(1));
+        let mut bytes = Vec::new();
+        let content = match file.take(limit).read_to_end(&mut bytes) {
+            Err(error) => Err(format!("{}: {}", log, error)),
+            Ok(read) if read as u64 == limit => Err(too_large(limit - 1)),
+            Ok(_) => decode_xes(&bytes).map_err(|error| error.with_file(&log).to_string()),
+        };
+        let (content, rejection) = match content {
+            Ok(content) => (content, None),
+            Err(reason) => (String::new(), Some(reason)),
+        };
+        Ok(Submission {
+            input: LogInput::Content(content),
+            log: Some(log),
+            rejection,
+        })
+    }
+
+    // Records the submission and queues the job; if the record fails, the job fails with the
+    // error, which is returned with its id. The record is written outside of the lock, so
+    // neither other submissions nor the workers wait for it.
+    fn enqueue(
+        &self,
+        user: Option<String>,
+        submission: Submission,
+        config: AnalysisConfig,
+    ) -> Result<JobId, (JobId, std::io::Error)> {
+        let Submission {
+            input,
+            log,
+            rejection,
+        } = submission;
+        let rejection = rejection.or_else(|| {
+            let max = self.settings.max_log_bytes?;
+            let size = match &input {
+                LogInput::Path(path) => match decompressed_size(path, max) {
+                    Ok(size) => size,
+                    Err(error) => return Some(format!("{}: {}", path, error)),
+                },
+                LogInput::Content(content) => content.len() as u64,
+            };
+            (size > max).then(|| too_large(max))
+        });
+
+        let id = {
+            let mut state = self.shared.lock();
+            state.next_id += 1;
+            state.next_id - 1
+        };
+        let recorded = match &self.audit_log {
+            Some(audit_log) => {
+                let mut entry = AuditEntry::new(user, id, &input, &config);
+                if let Some(log) = log {
+                    entry.log = log;
+                }
+                audit_log.record(&entry)
+            }
+            None => Ok(()),
+        };
+
+        let mut state = self.shared.lock();
+        let max_finished = self.settings.max_finished_jobs;
+        if let Err(error) = recorded {
+            let reason = format!("could not write the audit log: {}", error);
+            state.finish(id, JobState::Failed(reason), max_finished);
+            return Err((id, error));
+        }
+        match rejection {
+            Some(reason) => state.finish(id, JobState::Failed(reason), max_finished),
+            None => {
+                state.jobs.insert(id, JobState::Queued);
+                state.queue.push_back(Job { id, input, config });
+                self.shared.queued.notify_one();
+            }
+        }
+        Ok(id)
+    }
+
+    /// The status of a job, `None` for an unknown id.
+    pub fn status(&self, id: JobId) -> Option<JobStatus> {
+        let state = self.shared.lock();
+        status(&state, id)
+    }
+
+    /// The result of a done job, `None` if the job isn't done (yet).
+    pub fn result(&self, id: JobId) -> Option<Arc<AnalysisResult>> {
+        match self.shared.lock().jobs.get(&id)? {
+            JobState::Done(result) => Some(Arc::clone(result)),
+            _ => None,
+        }
+    }
+
+    /// Blocks until the job is done or failed and returns its status, `None` for an unknown
+    /// id.
+    pub fn wait(&self, id: JobId) -> Option<JobStatus> {
+        let mut state = self.shared.lock();
+        loop {
+            let status = status(&state, id)?;
+            if status.is_finished() {
+                return Some(status);
+            }
+            state = self
+                .shared
+                .finished
+                .wait(state)
+                .unwrap_or_else(|poisoned| poisoned.into_inner());
+        }
+    }
+
+    /// Forgets a finished job and its result; queued and running jobs are kept. Returns
+    /// whether the job was removed.
+    pub fn remove(&self, id: JobId) -> bool {
+        let mut state = self.shared.lock();
+        let finished = matches!(
+            state.jobs.get(&id),
+            Some(JobState::Done(_) | JobState::Failed(_))
+        );
+        if finished {
+            state.jobs.remove(&id);
+            state.finished.retain(|other| *other != id);
+        }
+        finished
+    }
+}
+
+impl Drop for JobQueue {
+    // Lets the running jobs finish, drops the queued ones and stops the workers
+    fn drop(&mut self) {
+        self.shared.lock().shutdown = true;
+        self.shared.queued.notify_all();
+        for worker in self.workers.drain(..) {
+            let _ = worker.join();
+        }
+    }
+}
+
+fn status(state: &State, id: JobId) -> Option<JobStatus> {
+    Some(match state.jobs.get(&id)? {
+        JobState::Queued => JobStatus::Queued {
+            position: state
+                .queue
+                .iter()
+                .position(|job| job.id == id)
+                .unwrap_or_default(),
+        },
+        JobState::Running(started) => JobStatus::Running {
+            elapsed: started.elapsed(),
+        },
+        JobState::Done(_) => JobStatus::Done,
+        JobState::Failed(reason) => JobStatus::Failed(reason.clone()),
+    })
+}
+
+// The loop of a worker thread: takes the next job until the queue shuts down
+fn work(shared: &Shared, max_finished: usize) {
+    loop {
+        let job = {
+            let mut state = shared.lock();
+            loop {
+                if state.shutdown {
+                    return;
+                }
+                if let Some(job) = state.queue.pop_front() {
+                    state.jobs.insert(job.id, JobState::Running(Instant::now()));
+                    break job;
+                }
+                state = shared
+                    .queued
+                    .wait(state)
+                    .unwrap_or_else(|poisoned| poisoned.into_inner());
+            }
+        };
+
+        let outcome = catch_unwind(AssertUnwindSafe(|| analyze(job.input, job.config)));
+        let job_state = match outcome {
+            Ok(Ok(result)) => JobState::Done(Arc::new(result)),
+            Ok(Err(error)) => JobState::Failed(error.to_string()),
+            Err(_) => JobState::Failed("the analysis stopped unexpectedly".to_string()),
+        };
+        shared.lock().finish(job.id, job_state, max_finished);
+        shared.finished.notify_all();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::audit::{ApiKeys, AuditFile};
+
+    #[test]
+    fn test_job_queue() {
+        let queue = JobQueue::new(JobSettings {
+            concurrency: 1,
+            max_log_bytes: Some(1_000_000),
+            max_finished_jobs: 3,
+        });
+        let done = queue.submit(
+            LogInput::Path("./sample-data/exercise2.xes".to_string()),
+            AnalysisConfig::default(),
+        );
+        let failed = queue.submit(
+            LogInput::Content("<log><trace></log>".to_string()),
+            AnalysisConfig::default(),
+        );
+        let too_large = queue.submit(
+            LogInput::Content(" ".repeat(1_000_001)),
+            AnalysisConfig::default(),
+        );
+        assert!(matches!(
+            queue.status(too_large),
+            Some(JobStatus::Failed(_))
+        ));
+
+        assert_eq!(queue.wait(done), Some(JobStatus::Done));
+        assert!(!queue.result(done).unwrap().log.is_empty());
+        assert!(matches!(queue.wait(failed), Some(JobStatus::Failed(_))));
+        assert!(queue.result(failed).is_none());
+
+        assert!(queue.remove(done));
+        assert_eq!(queue.status(done), None);
+        assert_eq!(queue.wait(JobId::MAX), None);
+
+        // only the last three finished jobs are kept
+        let rejected: Vec<JobId> = (0..3)
+            .map(|_| queue.submit(LogInput::Path("missing.xes".to_string()), Default::default()))
+            .collect();
+        assert_eq!(queue.status(too_large), None);
+        assert_eq!(queue.status(failed), None);
+        assert!(rejected
+            .iter()
+            .all(|id| matches!(queue.status(*id), Some(JobStatus::Failed(_)))));
+    }
+
+    struct FailingAuditLog;
+
+    impl AuditLog for FailingAuditLog {
+        fn record(&self, _entry: &AuditEntry) -> std::io::Result<()> {
+            Err(std::io::Error::other("disk full"))
+        }
+    }
+
+    #[test]
+    fn test_submit_as() {
+        let path = std::env::temp_dir().join(format!("egypt-jobs-{}.jsonl", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        let queue = JobQueue::new(JobSettings::default())
+            .with_authenticator(ApiKeys::new().with_key("secret", "alice"))
+            .with_audit_log(AuditFile::new(&path))
+            .with_upload_dir("./sample-data");
+        let input = LogInput::Path("exercise2.xes".to_string());
+
+        let id = queue
+            .submit_as("secret", input.clone(), AnalysisConfig::default())
+            .unwrap();
+        for guess in ["guess", "secre", "secret "] {
+            assert!(matches!(
+                queue.submit_as(guess, input.clone(), AnalysisConfig::default()),
+                Err(AccessError::Denied)
+            ));
+        }
+        assert_eq!(queue.wait(id), Some(JobStatus::Done));
+
+        let entries = AuditFile::new(&path).entries().unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].user.as_deref(), Some("alice"));
+        assert_eq!(entries[0].job, id);
+        assert!(entries[0].log.ends_with("exercise2.xes"));
+
+        for outside in ["../Cargo.toml", "/etc/passwd", "missing.xes"] {
+            assert!(matches!(
+                queue.submit_as("secret", LogInput::Path(outside.to_string()), Default::default()),
+                Err(AccessError::Forbidden(_))
+            ));
+        }
+
+        let queue = JobQueue::new(JobSettings::default()).with_audit_log(FailingAuditLog);
+        assert!(matches!(
+            queue.submit_as("", input.clone(), AnalysisConfig::default()),
+            Err(AccessError::Forbidden(_))
+        ));
+        let content = LogInput::Content("<log/>".to_string());
+        assert!(matches!(
+            queue.submit_as("", content, AnalysisConfig::default()),
+            Err(AccessError::Audit(_))
+        ));
+        let id = queue.submit(input, AnalysisConfig::default());
+        assert!(matches!(queue.status(id), Some(JobStatus::Failed(_))));
+    }
+
+    #[test]
+    fn test_upload_is_read_at_submission() {
+        let dir = std::env::temp_dir().join(format!("egypt-uploads-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::copy("./sample-data/exercise2.xes", dir.join("log.xes")).unwrap();
+        let queue = JobQueue::new(JobSettings {
+            concurrency: 1,
+            ..Default::default()
+        })
+        .with_upload_dir(&dir);
+
+        // the first job keeps the worker busy while the upload is replaced
+        let busy = queue.submit(
+            LogInput::Path("./sample-data/exercise2.xes".to_string()),
+            AnalysisConfig::default(),
+        );
+        let id = queue
+            .submit_as("", LogInput::Path("log.xes".to_string()), AnalysisConfig::default())
+            .unwrap();
+        std::fs::write(dir.join("log.xes"), "not a log").unwrap();
+        assert_eq!(queue.wait(busy), Some(JobStatus::Done));
+        assert_eq!(queue.wait(id), Some(JobStatus::Done));
+
+        std::fs::write(dir.join("log.xes"), "<log><trace></log>").unwrap();
+        let id = queue
+            .submit_as("", LogInput::Path("log.xes".to_string()), AnalysisConfig::default())
+            .unwrap();
+        assert!(matches!(queue.wait(id), Some(JobStatus::Failed(_))));
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -1,32 +1,313 @@
 use chrono::{DateTime, Duration, Utc};
 use dependency_types::{
-    dependency::Dependency, existential::check_existential_dependency,
-    temporal::check_temporal_dependency,
+    custom::{DependencyChecker, VariantIndex},
+    dependency::Dependency, existential::check_existential_dependency_from_table,
+    presence::ActivityPresence,
+    significance::Significance,
+    temporal::TemporalFootprints,
 };
-use std::collections::{HashMap, HashSet};
+use parser::count_variants;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+use variant_log::{CaseMetadata, VariantLog};
 
+pub use analysis::{analyze, AnalysisResult, DependencyMatrix, LogInput};
+
+pub mod activity_labels;
+pub mod activity_profile;
+pub mod alignment;
+pub mod analysis;
+pub mod arrivals;
+#[cfg(feature = "arrow")]
+pub mod arrow;
+pub mod attributes;
+pub mod audit;
+pub mod batching;
+pub mod calendar;
+pub mod cohorts;
+pub mod complexity;
+pub mod completeness;
+pub mod conformance;
+pub mod config;
+pub mod decisions;
+pub mod declare;
+pub mod dfg;
 pub mod dependency_types;
+pub mod deviations;
+pub mod export;
+pub mod gateways;
+pub mod glossary;
+pub mod grouping;
+pub mod health;
+pub mod i18n;
+pub mod jobs;
+pub mod long_distance;
+pub mod ltl;
+pub mod markov;
+pub mod metrics;
+pub mod monitoring;
+pub mod pages;
 pub mod parser;
+#[cfg(feature = "postgres")]
+pub mod postgres;
+pub mod random;
+pub mod recommendation;
+pub mod render;
+pub mod repair;
+pub mod repetition;
+pub mod representatives;
+pub mod resource_dependencies;
+pub mod resources;
+pub mod sampling;
+pub mod simplicity;
+pub mod simulation;
+pub mod sla;
+#[cfg(feature = "sqlite")]
+pub mod sqlite;
+pub mod subprocess;
+pub mod taxonomy;
+pub mod temporal_constraints;
+pub mod timeline;
+pub mod trace_diff;
+pub mod trace_length;
+pub mod validation;
+pub mod variant_log;
+pub mod weights;
+pub mod wip;
+#[cfg(feature = "xlsx")]
+pub mod xlsx;
+
+/// One event of a case: which activity happened, when, and whatever else was recorded.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LogEvent {
+    pub activity: String,
+    pub timestamp: Option<DateTime<Utc>>,
+    /// The other attributes of the event (resource, lifecycle transition, ...) as text.
+    pub attributes: BTreeMap<String, String>,
+}
+
+impl LogEvent {
+    /// An event without timestamp and attributes.
+    pub fn new(activity: impl Into<String>) -> Self {
+        LogEvent {
+            activity: activity.into(),
+            timestamp: None,
+            attributes: BTreeMap::new(),
+        }
+    }
+
+    /// Who executed the event: its `org:resource`, or the attribute set as
+    /// [`parser::ImportConfig::resource_key`].
+    pub fn resource(&self) -> Option<&str> {
+        self.attributes
+            .get(parser::xes_extensions::ORG_RESOURCE)
+            .map(String::as_str)
+    }
+}
+
+/// The events of one case, in the order they happened.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Trace {
+    pub case_id: String,
+    /// The attributes of the case (customer, channel, ...) as text, except for its
+    /// `concept:name`, which is the case id.
+    pub attributes: BTreeMap<String, String>,
+    pub events: Vec<LogEvent>,
+}
+
+impl Trace {
+    pub fn activities(&self) -> impl Iterator<Item = &str> {
+        self.events.iter().map(|event| event.activity.as_str())
+    }
+}
+
+/// An event log as the analyses see it: one [`Trace`] per case, holding the events that are
+/// analyzed (see [`parser::parse_into_log`]).
+///
+/// The analyses themselves mostly work on the activity sequences; [`EventLog::to_variant_log`]
+/// and [`EventLog::to_traces`] convert into the forms they take.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct EventLog {
+    pub traces: Vec<Trace>,
+    /// The attributes of the log itself (source, description, ...) as text.
+    pub attributes: BTreeMap<String, String>,
+    /// The names of the classifiers declared in the log, in declaration order.
+    pub classifiers: Vec<String>,
+}
+
+impl EventLog {
+    /// Builds a log from plain activity sequences, naming the cases `case_0`, `case_1`, ...
+    pub fn from_traces(traces: Vec<Vec<String>>) -> Self {
+        EventLog {
+            traces: traces
+                .into_iter()
+                .enumerate()
+                .map(|(case_idx, activities)| Trace {
+                    case_id: format!("case_{}", case_idx),
+                    attributes: BTreeMap::new(),
+                    events: activities.into_iter().map(LogEvent::new).collect(),
+                })
+                .collect(),
+            ..Default::default()
+        }
+    }
+
+    /// The number of cases.
+    pub fn len(&self) -> usize {
+        self.traces.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.traces.is_empty()
+    }
+
+    pub fn activities(&self) -> BTreeSet<String> {
+        self.traces
+            .iter()
+            .flat_map(|trace| trace.activities())
+            .map(|activity| activity.to_string())
+            .collect()
+    }
+
+    /// The activity sequence of every case.
+    pub fn to_traces(&self) -> Vec<Vec<String>> {
+        self.traces
+            .iter()
+            .map(|trace| trace.activities().map(|activity| activity.to_string()).collect())
+            .collect()
+    }
+
+    /// The activity sequences stored as variants, keeping the case ids.
+    pub fn to_variant_log(&self) -> VariantLog {
+        let mut log = VariantLog::new();
+        for trace in &self.traces {
+            log.push(
+                CaseMetadata {
+                    case_id: trace.case_id.clone(),
+                    ongoing: false,
+                    attributes: trace.attributes.clone(),
+                },
+                trace.activities().map(|activity| activity.to_string()).collect(),
+            );
+        }
+        log
+    }
+}
 
-#[derive(Debug, Clone, PartialEq, Eq, Hash)]
-pub struct Event {
+impl From<&EventLog> for VariantLog {
+    fn from(log: &EventLog) -> Self {
+        log.to_variant_log()
+    }
+}
+
+/// An event as the [`ExtendedPrefixAutomaton`] sees it: the activity reduced to a single
+/// character and the case whose previous event it follows.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct PrefixEvent {
     pub case: String,
     pub activity: char,
     pub predecessor: Option<String>,
 }
 
+/// The former name of [`PrefixEvent`].
+#[deprecated(note = "renamed to `PrefixEvent`")]
+pub type Event = PrefixEvent;
+
+/// Identifier of a state in an [`ExtendedPrefixAutomaton`].
+///
+/// States are numbered in the order they are created, the root always being `StateId(0)`.
+/// For display purposes the root is shown as `root` and every other state as `s<n>`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct StateId(pub usize);
+
+impl StateId {
+    pub const ROOT: StateId = StateId(0);
+}
+
+impl std::fmt::Display for StateId {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        if *self == StateId::ROOT {
+            write!(f, "root")
+        } else {
+            write!(f, "s{}", self.0)
+        }
+    }
+}
+
+impl std::str::FromStr for StateId {
+    type Err = String;
+
+    /// Parses the display form (`root`, `s<n>`) back into a `StateId`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s == "root" {
+            return Ok(StateId::ROOT);
+        }
+        s.strip_prefix('s')
+            .and_then(|n| n.parse().ok())
+            .filter(|&n| n > 0)
+            .map(StateId)
+            .ok_or_else(|| format!("Invalid state id: {}", s))
+    }
+}
+
+/// How the states of an [`ExtendedPrefixAutomaton`] are grouped into the partitions that the
+/// variant entropy is computed from. Papers on log entropy differ in this choice, so reports
+/// name the strategy next to the entropy.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum PartitionStrategy {
+    /// A new partition starts whenever a state gets a second successor, i.e. wherever
+    /// variants branch off (Back et al.).
+    #[default]
+    Branching,
+    /// One partition per prefix length: all states at the same depth below the root.
+    PrefixLength,
+    /// One partition per number of repeated activities on the path from the root, so loops
+    /// rather than branches separate the partitions.
+    RepetitionCount,
+}
+
+impl PartitionStrategy {
+    /// The name of the strategy in configuration files and reports.
+    pub fn name(&self) -> &'static str {
+        match self {
+            PartitionStrategy::Branching => "branching",
+            PartitionStrategy::PrefixLength => "prefix-length",
+            PartitionStrategy::RepetitionCount => "repetition-count",
+        }
+    }
+}
+
+impl std::fmt::Display for PartitionStrategy {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        f.pad(self.name())
+    }
+}
+
 #[derive(Debug)]
 pub struct State {
     pub partition: Option<usize>,
-    pub sequences: HashSet<Event>,
+    pub sequences: BTreeSet<PrefixEvent>,
+    /// Number of events that reached this state (always 0 for the root).
+    pub frequency: usize,
 }
 
 #[derive(Debug)]
 pub struct ExtendedPrefixAutomaton {
-    pub states: HashMap<String, State>,
-    pub transitions: Vec<(String, char, String)>,
-    pub activities: HashSet<char>,
-    pub root: String,
+    /// All states, indexed by their [`StateId`].
+    pub states: Vec<State>,
+    /// All transitions in insertion order, kept around for exporting the automaton.
+    pub transitions: Vec<(StateId, char, StateId)>,
+    pub activities: BTreeSet<char>,
+    pub root: StateId,
+    // (source, activity) -> target, so that following a transition doesn't need a linear scan
+    transition_index: HashMap<(StateId, char), StateId>,
+    // outgoing transitions per state, indexed like `states`
+    successors: Vec<Vec<(char, StateId)>>,
+    // the transition into each state (`None` for the root), indexed like `states`
+    incoming: Vec<Option<(StateId, char)>>,
+    max_partition: usize,
+    partition_strategy: PartitionStrategy,
 }
 
 impl Default for ExtendedPrefixAutomaton {
@@ -37,62 +318,145 @@ impl Default for ExtendedPrefixAutomaton {
 
 impl ExtendedPrefixAutomaton {
     pub fn new() -> Self {
-        let root_id = "root".to_string();
-        let mut states = HashMap::new();
-        states.insert(
-            root_id.clone(),
-            State {
-                partition: None,
-                sequences: HashSet::new(),
-            },
-        );
+        let states = vec![State {
+            partition: None,
+            sequences: BTreeSet::new(),
+            frequency: 0,
+        }];
 
         ExtendedPrefixAutomaton {
             states,
             transitions: Vec::new(),
-            activities: HashSet::new(),
-            root: root_id,
+            activities: BTreeSet::new(),
+            root: StateId::ROOT,
+            transition_index: HashMap::new(),
+            successors: vec![Vec::new()],
+            incoming: vec![None],
+            max_partition: 0,
+            partition_strategy: PartitionStrategy::default(),
+        }
+    }
+
+    /// The strategy the states were partitioned with.
+    pub fn partition_strategy(&self) -> PartitionStrategy {
+        self.partition_strategy
+    }
+
+    pub fn state(&self, id: StateId) -> &State {
+        &self.states[id.0]
+    }
+
+    /// Iterates over all states together with their ids.
+    pub fn state_ids(&self) -> impl Iterator<Item = StateId> {
+        (0..self.states.len()).map(StateId)
+    }
+
+    /// Returns the state reached by following the `activity` transition out of `source`, if any.
+    pub fn transition(&self, source: StateId, activity: char) -> Option<StateId> {
+        self.transition_index.get(&(source, activity)).copied()
+    }
+
+    /// Returns the outgoing transitions of a state as `(activity, target)` pairs.
+    pub fn successors(&self, source: StateId) -> &[(char, StateId)] {
+        &self.successors[source.0]
+    }
+
+    /// Returns how many events reached the given state.
+    pub fn state_frequency(&self, id: StateId) -> usize {
+        self.state(id).frequency
+    }
+
+    /// Returns how many distinct cases passed through the given state.
+    pub fn state_case_frequency(&self, id: StateId) -> usize {
+        self.state(id)
+            .sequences
+            .iter()
+            .map(|event| &event.case)
+            .collect::<HashSet<_>>()
+            .len()
+    }
+
+    /// Returns how many events took the `activity` transition out of `source`, if it exists.
+    ///
+    /// Every state apart from the root has exactly one incoming transition, so this is the
+    /// frequency of the transition's target state.
+    pub fn transition_frequency(&self, source: StateId, activity: char) -> Option<usize> {
+        self.transition(source, activity)
+            .map(|target| self.state_frequency(target))
+    }
+
+    // Whether `activity` occurs on the path from the root to `state`
+    fn path_contains(&self, mut state: StateId, activity: char) -> bool {
+        while let Some((source, label)) = self.incoming[state.0] {
+            if label == activity {
+                return true;
+            }
+            state = source;
         }
+        false
     }
 
-    pub fn build(plain_log: Vec<Vec<Event>>) -> Self {
+    pub fn build(plain_log: Vec<Vec<PrefixEvent>>) -> Self {
+        Self::build_with_partition_strategy(plain_log, PartitionStrategy::default())
+    }
+
+    /// Same as [`ExtendedPrefixAutomaton::build`], but partitions the states with `strategy`.
+    pub fn build_with_partition_strategy(
+        plain_log: Vec<Vec<PrefixEvent>>,
+        strategy: PartitionStrategy,
+    ) -> Self {
         let mut epa = ExtendedPrefixAutomaton::new();
-        let mut last_at: HashMap<String, String> = HashMap::new();
+        epa.partition_strategy = strategy;
+        let mut last_at: HashMap<String, StateId> = HashMap::new();
 
         for trace in plain_log {
             for event in trace {
                 let pred_at = event.predecessor
                     .as_ref()
                     .and_then(|case| last_at.get(case))
-                    .unwrap_or(&epa.root)
-                    .to_string();
+                    .copied()
+                    .unwrap_or(epa.root);
 
-                let current_at = if let Some(target) = epa.transitions.iter()
-                    .find(|(source, act, _)| source == &pred_at && *act == event.activity)
-                    .map(|(_, _, target)| target.to_string())
-                {
+                let current_at = if let Some(target) = epa.transition(pred_at, event.activity) {
                     target
                 } else {
-                    let new_state_id = format!("s{}", epa.states.len());
-                    let current_c = if pred_at == epa.root {
-                        1
-                    } else if epa.transitions.iter().any(|(source, _, _)| source == &pred_at) {
-                        epa.states.values().filter_map(|s| s.partition).max().unwrap_or(0) + 1
-                    } else {
-                        epa.states[&pred_at].partition.unwrap_or(0)
+                    let new_state_id = StateId(epa.states.len());
+                    let pred_c = epa.state(pred_at).partition;
+                    let current_c = match strategy {
+                        PartitionStrategy::Branching => {
+                            if pred_at == epa.root {
+                                1
+                            } else if !epa.successors[pred_at.0].is_empty() {
+                                epa.max_partition + 1
+                            } else {
+                                pred_c.unwrap_or(0)
+                            }
+                        }
+                        PartitionStrategy::PrefixLength => pred_c.unwrap_or(0) + 1,
+                        PartitionStrategy::RepetitionCount => {
+                            let repeated = epa.path_contains(pred_at, event.activity);
+                            pred_c.unwrap_or(1) + usize::from(repeated)
+                        }
                     };
+                    epa.max_partition = epa.max_partition.max(current_c);
 
-                    epa.states.insert(new_state_id.clone(), State {
+                    epa.states.push(State {
                         partition: Some(current_c),
-                        sequences: HashSet::new(),
+                        sequences: BTreeSet::new(),
+                        frequency: 0,
                     });
-                    epa.transitions.push((pred_at, event.activity, new_state_id.clone()));
+                    epa.successors.push(Vec::new());
+                    epa.incoming.push(Some((pred_at, event.activity)));
+                    epa.transition_index.insert((pred_at, event.activity), new_state_id);
+                    epa.successors[pred_at.0].push((event.activity, new_state_id));
+                    epa.transitions.push((pred_at, event.activity, new_state_id));
                     epa.activities.insert(event.activity);
 
                     new_state_id
                 };
 
-                epa.states.get_mut(&current_at).unwrap().sequences.insert(event.clone());
+                epa.states[current_at.0].sequences.insert(event.clone());
+                epa.states[current_at.0].frequency += 1;
                 last_at.insert(event.case.clone(), current_at);
             }
         }
@@ -100,38 +464,94 @@ impl ExtendedPrefixAutomaton {
         epa
     }
 
-    pub fn variant_entropy(&self) -> f64 {
-        let s = self.states.len() as f64;
-        let s = if s > 1.0 { s - 1.0 } else { s };
+    /// Builds the automaton from every case of `log`.
+    ///
+    /// The automaton identifies activities by a single character, so activities are
+    /// represented by the first character of their label.
+    pub fn from_variant_log(log: &VariantLog) -> Self {
+        Self::from_variant_log_with_partition_strategy(log, PartitionStrategy::default())
+    }
 
-        let partition_sizes: HashMap<usize, usize> = self.states.values()
+    /// Same as [`ExtendedPrefixAutomaton::from_variant_log`], but partitions the states with
+    /// `strategy`.
+    pub fn from_variant_log_with_partition_strategy(
+        log: &VariantLog,
+        strategy: PartitionStrategy,
+    ) -> Self {
+        let plain_log: Vec<Vec<PrefixEvent>> = log
+            .traces()
+            .enumerate()
+            .map(|(case_idx, trace)| {
+                trace
+                    .iter()
+                    .enumerate()
+                    .map(|(event_idx, activity)| PrefixEvent {
+                        case: format!("case_{}", case_idx),
+                        activity: activity.chars().next().unwrap_or_default(),
+                        predecessor: (event_idx > 0).then(|| format!("case_{}", case_idx)),
+                    })
+                    .collect()
+            })
+            .collect();
+
+        Self::build_with_partition_strategy(plain_log, strategy)
+    }
+
+    /// Builds the automaton from every case of `log`, see
+    /// [`ExtendedPrefixAutomaton::from_variant_log`].
+    pub fn from_event_log(log: &EventLog) -> Self {
+        Self::from_variant_log(&log.to_variant_log())
+    }
+
+    /// Builds the suffix automaton of `log`: the prefix automaton of the reversed cases.
+    ///
+    /// Paths from the root read the cases backwards from their last activity, so the states
+    /// right below the root are the end activities, and the variant entropy measures how
+    /// differently the cases arrive at their end instead of how differently they start.
+    pub fn from_reversed_variant_log(log: &VariantLog) -> Self {
+        Self::from_variant_log(&log.reversed())
+    }
+
+    /// Builds the automaton of what happened before `activity`: for every occurrence of it,
+    /// the events up to and including it, read backwards.
+    ///
+    /// The root has a single transition for `activity`; the paths below it lead from the
+    /// directly preceding activities back to the start of the case, with the frequencies of
+    /// the states counting how often each history occurred.
+    pub fn preceding(log: &VariantLog, activity: &str) -> Self {
+        let mut histories = VariantLog::new();
+        for (case, trace) in log.cases().iter().zip(log.traces()) {
+            for (idx, _) in trace.iter().enumerate().filter(|(_, a)| *a == activity) {
+                histories.push(case.clone(), trace[..=idx].iter().rev().cloned().collect());
+            }
+        }
+        Self::from_variant_log(&histories)
+    }
+
+    // Number of states in each partition, in the order of the partitions so that the entropies
+    // always sum up the same way
+    fn partition_sizes(&self) -> impl Iterator<Item = usize> {
+        let partition_sizes: BTreeMap<usize, usize> = self.states.iter()
             .filter_map(|state| state.partition)
-            .fold(HashMap::new(), |mut acc, partition| {
+            .fold(BTreeMap::new(), |mut acc, partition| {
                 *acc.entry(partition).or_insert(0) += 1;
                 acc
             });
+        partition_sizes.into_values()
+    }
 
-        let sum_term: f64 = partition_sizes.values()
-            .map(|&size| {
-                let size_f64 = size as f64;
-                size_f64 * size_f64.log(10.0)
-            })
-            .sum();
-
-        s * s.log(10.0) - sum_term
+    pub fn variant_entropy(&self) -> f64 {
+        egypt_core::entropy::variant_entropy(self.states.len(), self.partition_sizes())
     }
 
     pub fn normalized_variant_entropy(&self) -> f64 {
-        let e_v = self.variant_entropy();
-        let s = self.states.len() as f64;
-        let s = if s > 1.0 { s - 1.0 } else { s };
-        e_v / (s * s.log(10.0))
+        egypt_core::entropy::normalized_variant_entropy(self.states.len(), self.partition_sizes())
     }
 }
 
 pub fn generate_xes(text: &str) -> String {
     let mut output = String::with_capacity(text.len() * 2);
-    let traces = get_traces(text);
+    let traces = iter_traces(text);
 
     output.push_str("<log xes.version=\"1.0\" xes.features=\"nested-attributes\" openxes.version=\"1.0RC7\" xmlns=\"http://www.xes-standard.org/\">\n");
 
@@ -150,7 +570,7 @@ pub fn generate_xes(text: &str) -> String {
                 <string key=\"concept:name\" value=\"{}\"/>\n\
                 <date key=\"time:timestamp\" value=\"{}\"/>\n\
                 </event>\n",
-                event,
+                render::escape_xml(event),
                 starting_time.to_rfc3339()
             ));
         }
@@ -163,8 +583,8 @@ pub fn generate_xes(text: &str) -> String {
     output
 }
 
-pub fn generate_adj_matrix_from_traces(traces: Vec<Vec<String>>) -> (String, usize, usize, usize, usize, usize, HashMap<String, usize>) {
-    let mut activities = HashSet::new();
+pub fn generate_adj_matrix_from_traces(traces: Vec<Vec<String>>) -> DependencyMatrix {
+    let mut activities = BTreeSet::new();
 
     traces.iter().for_each(|trace| {
         trace.iter().for_each(|activity| {
@@ -175,22 +595,133 @@ pub fn generate_adj_matrix_from_traces(traces: Vec<Vec<String>>) -> (String, usi
     generate_adj_matrix_from_activities_and_traces(&activities, traces)
 }
 
+/// Removes the activities that occur in fewer than `min_support` traces from all traces, so rare,
+/// noisy activities don't add rows and columns to the matrix. Traces keep their order, even if
+/// all of their activities are removed.
+pub fn filter_activities_by_frequency(
+    traces: Vec<Vec<String>>,
+    min_support: usize,
+) -> Vec<Vec<String>> {
+    let mut support: HashMap<&str, usize> = HashMap::new();
+    for trace in &traces {
+        let distinct: HashSet<&str> = trace.iter().map(String::as_str).collect();
+        for activity in distinct {
+            *support.entry(activity).or_insert(0) += 1;
+        }
+    }
+    let rare: HashSet<String> = support
+        .into_iter()
+        .filter(|&(_, traces)| traces < min_support)
+        .map(|(activity, _)| activity.to_string())
+        .collect();
+
+    traces
+        .into_iter()
+        .map(|mut trace| {
+            trace.retain(|activity| !rare.contains(activity));
+            trace
+        })
+        .collect()
+}
+
+pub fn generate_adj_matrix_from_variant_log(log: &VariantLog) -> DependencyMatrix {
+    generate_adj_matrix_from_activities_and_variants(&log.activities(), &log.weighted_variants())
+}
+
 pub fn generate_adj_matrix_from_activities_and_traces(
-    activities: &HashSet<String>,
+    activities: &BTreeSet<String>,
     traces: Vec<Vec<String>>,
-) -> (String, usize, usize, usize, usize, usize, HashMap<String, usize>) {
+) -> DependencyMatrix {
+    // every dependency check only depends on the sequence of activities, so it is enough to
+    // look at each variant once and weight it by how often it occurs
+    let converted_traces = traces
+        .iter()
+        .map(|v| v.iter().map(|s| s.as_str()).collect());
+    let variants: Vec<(Vec<&str>, usize)> =
+        count_variants(converted_traces).into_iter().collect();
+
+    generate_adj_matrix_from_activities_and_variants(activities, &variants)
+}
+
+pub fn generate_adj_matrix_from_activities_and_variants(
+    activities: &BTreeSet<String>,
+    variants: &[(Vec<&str>, usize)],
+) -> DependencyMatrix {
+    generate_adj_matrix_from_activities_and_variants_with_options(
+        activities,
+        variants,
+        &MatrixOptions::default(),
+        &[],
+    )
+}
+
+/// Everything that decides which dependencies are shown in the matrix.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct MatrixOptions {
+    /// Share of traces that has to satisfy a temporal dependency.
+    pub temporal_threshold: f64,
+    /// Share of traces that has to satisfy an existential dependency.
+    pub existential_threshold: f64,
+    /// Test existential dependencies for significance, annotate them with their p-value and
+    /// suppress the ones that could be a coincidence, see
+    /// [`dependency_types::significance`].
+    pub significance: Option<Significance>,
+    /// Minimum number of traces containing at least one of the two activities, cells of rarer
+    /// pairs show [`INSUFFICIENT_DATA`] instead of a dependency.
+    pub min_support: usize,
+}
+
+/// Cell of an activity pair below [`MatrixOptions::min_support`].
+pub const INSUFFICIENT_DATA: &str = "insufficient";
+
+impl Default for MatrixOptions {
+    fn default() -> Self {
+        MatrixOptions {
+            temporal_threshold: 1.0,
+            existential_threshold: 1.0,
+            significance: None,
+            min_support: 0,
+        }
+    }
+}
+
+/// The dependency matrix of `log` with the thresholds, significance test and minimum support of
+/// `options`, additionally showing the relations `checkers` find in the matrix cells.
+pub fn generate_adj_matrix_from_variant_log_with_options(
+    log: &VariantLog,
+    options: &MatrixOptions,
+    checkers: &[&dyn DependencyChecker],
+) -> DependencyMatrix {
+    generate_adj_matrix_from_activities_and_variants_with_options(
+        &log.activities(),
+        &log.weighted_variants(),
+        options,
+        checkers,
+    )
+}
+
+pub fn generate_adj_matrix_from_activities_and_variants_with_options(
+    activities: &BTreeSet<String>,
+    variants: &[(Vec<&str>, usize)],
+    options: &MatrixOptions,
+    checkers: &[&dyn DependencyChecker],
+) -> DependencyMatrix {
+    let cells = MatrixCells::new(variants, options, checkers);
     let max_dependency_width = 15;
+    // rows and columns in the order of the activity names, so the same log always gives the
+    // same matrix
+    let activities: Vec<&String> = activities.iter().collect();
 
     let mut output = String::with_capacity(activities.len() * activities.len() * 20);
     let mut full_independences = 0;
     let mut pure_existences = 0;
     let mut eventual_equivalences = 0;
     let mut direct_equivalences = 0;
-    let mut relationship_counts = HashMap::new();
+    let mut relationship_counts = BTreeMap::new();
 
     // Header
     output.push_str(&format!("{:<15}", " "));
-    for activity in activities {
+    for activity in &activities {
         output.push_str(&format!("{:<15}", activity));
     }
     output.push('\n');
@@ -203,22 +734,16 @@ pub fn generate_adj_matrix_from_activities_and_traces(
         )
     };
 
-    for from in activities {
+    for &from in &activities {
         output.push_str(&format!("{:<15}", from));
-        for to in activities {
-            if to != from {
-                let converted_traces: Vec<Vec<&str>> = traces
-                    .iter()
-                    .map(|v| v.iter().map(|s| s.as_str()).collect())
-                    .collect();
-                let temporal_dependency = check_temporal_dependency(from, to, &converted_traces, 1.0);
-                let existential_dependency = check_existential_dependency(from, to, &converted_traces, 1.0);
-                let dependency = Dependency::new(
-                    from.to_string(),
-                    to.to_string(),
-                    temporal_dependency.clone(),
-                    existential_dependency.clone(),
-                );
+        for &to in &activities {
+            if to != from && !cells.has_support(from, to) {
+                *relationship_counts.entry(INSUFFICIENT_DATA.to_string()).or_insert(0) += 1;
+                output.push_str(&format!("{:<15}", INSUFFICIENT_DATA));
+            } else if to != from {
+                let dependency = cells.dependency(from, to);
+                let temporal_dependency = dependency.temporal_dependency.clone();
+                let existential_dependency = dependency.existential_dependency.clone();
 
                 let temporal_type = match &temporal_dependency {
                     Some(td) => match td.dependency_type {
@@ -232,7 +757,8 @@ pub fn generate_adj_matrix_from_activities_and_traces(
                         dependency_types::existential::DependencyType::Equivalence => "equivalence",
                         dependency_types::existential::DependencyType::Implication => "implication",
                         dependency_types::existential::DependencyType::NegatedEquivalence => "negated equivalence",
-                        _ => "other",
+                        dependency_types::existential::DependencyType::Nand => "nand",
+                        dependency_types::existential::DependencyType::Or => "or",
                     },
                     None => "none",
                 };
@@ -265,11 +791,119 @@ pub fn generate_adj_matrix_from_activities_and_traces(
         output.push('\n');
     }
 
-    (output, full_independences, pure_existences, eventual_equivalences, direct_equivalences, activities.len(), relationship_counts)
+    DependencyMatrix {
+        text: output,
+        full_independences,
+        pure_existences,
+        eventual_equivalences,
+        direct_equivalences,
+        activities: activities.len(),
+        relationship_counts,
+        approximation: None,
+    }
+}
+
+/// The dependencies of all pairs of distinct activities of `log`, as they are shown in the
+/// cells of [`generate_adj_matrix_from_variant_log_with_options`], ordered by activity name.
+/// Pairs below [`MatrixOptions::min_support`] are left out.
+pub fn dependencies_from_variant_log_with_options(
+    log: &VariantLog,
+    options: &MatrixOptions,
+    checkers: &[&dyn DependencyChecker],
+) -> Vec<Dependency> {
+    let variants = log.weighted_variants();
+    let cells = MatrixCells::new(&variants, options, checkers);
+    let activities: Vec<String> = log.activities().into_iter().collect();
+
+    let mut dependencies = Vec::new();
+    for from in &activities {
+        for to in activities.iter().filter(|to| *to != from) {
+            if cells.has_support(from, to) {
+                dependencies.push(cells.dependency(from, to));
+            }
+        }
+    }
+    dependencies
+}
+
+/// The dependency of one pair of activities of `log`, as shown in its cell of
+/// [`generate_adj_matrix_from_variant_log_with_options`], without evaluating the other cells;
+/// `None` if the pair is below [`MatrixOptions::min_support`].
+pub fn pair_dependency_from_variant_log_with_options(
+    log: &VariantLog,
+    options: &MatrixOptions,
+    from: &str,
+    to: &str,
+) -> Option<Dependency> {
+    let variants = log.weighted_variants();
+    let cells = MatrixCells::new(&variants, options, &[]);
+    cells.has_support(from, to).then(|| cells.dependency(from, to))
+}
+
+// Evaluates the cells of a matrix, sharing one pass over the variants between all pairs
+struct MatrixCells<'a> {
+    index: VariantIndex<'a>,
+    presence: ActivityPresence,
+    footprints: TemporalFootprints,
+    options: &'a MatrixOptions,
+    checkers: &'a [&'a dyn DependencyChecker],
+}
+
+impl<'a> MatrixCells<'a> {
+    fn new(
+        variants: &'a [(Vec<&'a str>, usize)],
+        options: &'a MatrixOptions,
+        checkers: &'a [&'a dyn DependencyChecker],
+    ) -> Self {
+        MatrixCells {
+            index: VariantIndex::new(variants),
+            presence: ActivityPresence::new(variants),
+            footprints: TemporalFootprints::new(variants),
+            options,
+            checkers,
+        }
+    }
+
+    // whether enough traces contain at least one of the activities
+    fn has_support(&self, from: &str, to: &str) -> bool {
+        let table = self.presence.table(from, to);
+        table.total() - table.neither >= self.options.min_support
+    }
+
+    fn dependency(&self, from: &str, to: &str) -> Dependency {
+        let options = self.options;
+        let temporal_dependency = self
+            .footprints
+            .dependency(from, to, options.temporal_threshold);
+        let table = self.presence.table(from, to);
+        let mut existential_dependency =
+            check_existential_dependency_from_table(from, to, &table, options.existential_threshold);
+        let mut existential_p_value = None;
+        if let (Some(significance), Some(_)) = (&options.significance, &existential_dependency) {
+            existential_p_value = significance.p_value(&table);
+            if existential_p_value.is_some_and(|p| p > significance.alpha) {
+                existential_dependency = None;
+                existential_p_value = None;
+            }
+        }
+        let mut dependency = Dependency::new(
+            from.to_string(),
+            to.to_string(),
+            temporal_dependency,
+            existential_dependency,
+        );
+        dependency.existential_p_value = existential_p_value;
+        dependency.custom_dependencies = self
+            .checkers
+            .iter()
+            .filter_map(|checker| checker.check(from, to, &self.index))
+            .collect();
+        dependency
+    }
 }
 
 pub fn get_activities_and_traces(text: &str) -> (Vec<String>, Vec<Vec<&str>>) {
-    let mut activities = HashSet::new();
+    let mut activities = BTreeSet::new();
     let mut traces = Vec::new();
 
     for line in text.lines() {
@@ -288,6 +922,12 @@ pub fn get_activities_and_traces(text: &str) -> (Vec<String>, Vec<Vec<&str>>) {
 }
 
 pub fn get_traces(text: &str) -> Vec<Vec<&str>> {
+    iter_traces(text).collect()
+}
+
+/// Same as [`get_traces`], but yields the traces one by one instead of collecting them, e.g. to
+/// count variants or build a [`dfg::DirectlyFollowsGraph`] from a large text.
+pub fn iter_traces(text: &str) -> impl Iterator<Item = Vec<&str>> {
     text.lines()
         .filter_map(|line| {
             let trace: Vec<&str> = line
@@ -300,13 +940,32 @@ pub fn get_traces(text: &str) -> Vec<Vec<&str>> {
                 Some(trace)
             }
         })
-        .collect()
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_generate_xes_escapes_labels() {
+        let xes = generate_xes("a<b,\"c\" & d\n");
+        let traces = parser::parse_into_traces(None, Some(&xes)).unwrap();
+        assert_eq!(traces, [["a<b", "\"c\" & d"]]);
+    }
+
+    #[test]
+    fn test_filter_activities_by_frequency() {
+        let traces: Vec<Vec<String>> = ["a,b,c,c", "a,b", "a,d", "c"]
+            .iter()
+            .map(|trace| trace.split(',').map(String::from).collect())
+            .collect();
+        let filtered = filter_activities_by_frequency(traces.clone(), 2);
+        assert_eq!(filtered, [vec!["a", "b", "c", "c"], vec!["a", "b"], vec!["a"], vec!["c"]]);
+        let filtered = filter_activities_by_frequency(traces.clone(), 3);
+        assert_eq!(filtered, [vec!["a"], vec!["a"], vec!["a"], vec![]]);
+        assert_eq!(filter_activities_by_frequency(traces.clone(), 0), traces);
+    }
+
     #[test]
     fn test_get_activities_and_traces() {
         let traces = "
@@ -349,6 +1008,276 @@ activity 3,activity 1,activity 1,activity 2,
         assert_eq!(expected_traces, traces);
     }
 
+    #[test]
+    fn test_custom_dependency_checker() {
+        use dependency_types::custom::CustomDependency;
+
+        // holds if `from` and `to` never occur in the same trace
+        struct Exclusive;
+        impl DependencyChecker for Exclusive {
+            fn check(&self, from: &str, to: &str, log: &VariantIndex) -> Option<CustomDependency> {
+                log.variants_with(from)
+                    .all(|(variant, _)| !variant.contains(&to))
+                    .then(|| CustomDependency {
+                        label: "xor".to_string(),
+                        confidence: 1.0,
+                    })
+            }
+        }
+
+        let log = VariantLog::from_traces(vec![
+            vec!["A".to_string(), "B".to_string()],
+            vec!["A".to_string(), "C".to_string()],
+        ]);
+        let matrix = generate_adj_matrix_from_variant_log_with_options(
+            &log,
+            &MatrixOptions::default(),
+            &[&Exclusive],
+        );
+        // only B/C and C/B are exclusive
+        assert_eq!(matrix.text.matches(",xor").count(), 2);
+    }
+
+    #[test]
+    fn test_nand_and_or() {
+        let log = |traces: &[&[&str]]| {
+            VariantLog::from_traces(
+                traces
+                    .iter()
+                    .map(|trace| trace.iter().map(|activity| activity.to_string()).collect())
+                    .collect(),
+            )
+        };
+        let cell = |log: &VariantLog| {
+            pair_dependency_from_variant_log_with_options(log, &MatrixOptions::default(), "A", "B")
+                .and_then(|dependency| dependency.existential_dependency)
+                .map(|dependency| dependency.dependency_type)
+        };
+
+        // never together, but some traces have neither
+        let nand = log(&[&["A"], &["B"], &["C"]]);
+        assert_eq!(cell(&nand), Some(dependency_types::existential::DependencyType::Nand));
+        assert!(generate_adj_matrix_from_variant_log(&nand).text.contains("⊼"));
+        // at least one of them in every trace, sometimes both
+        let or = log(&[&["A"], &["B"], &["A", "B"]]);
+        assert_eq!(cell(&or), Some(dependency_types::existential::DependencyType::Or));
+        // exactly one of them in every trace
+        let xor = log(&[&["A"], &["B"]]);
+        assert_eq!(
+            cell(&xor),
+            Some(dependency_types::existential::DependencyType::NegatedEquivalence)
+        );
+    }
+
+    #[test]
+    fn test_significance_suppresses_coincidences() {
+        let traces = |a_b: usize, a: usize, b: usize, neither: usize| {
+            let mut traces = Vec::new();
+            traces.extend(vec![vec!["A".to_string(), "B".to_string()]; a_b]);
+            traces.extend(vec![vec!["A".to_string()]; a]);
+            traces.extend(vec![vec!["B".to_string()]; b]);
+            traces.extend(vec![vec!["C".to_string()]; neither]);
+            VariantLog::from_traces(traces)
+        };
+        let options = MatrixOptions {
+            significance: Some(Significance::default()),
+            ..Default::default()
+        };
+
+        // A ⇔ B holds, but with two cases this could well be a coincidence
+        let matrix =
+            generate_adj_matrix_from_variant_log_with_options(&traces(1, 0, 0, 1), &options, &[]);
+        assert!(!matrix.text.contains("⇔"));
+        assert!(generate_adj_matrix_from_traces(vec![
+            vec!["A".to_string(), "B".to_string()],
+            vec!["C".to_string()],
+        ])
+        .text
+        .contains("⇔"));
+
+        let matrix =
+            generate_adj_matrix_from_variant_log_with_options(&traces(10, 0, 0, 10), &options, &[]);
+        assert!(matrix.text.contains("⇔(p=0.000)"));
+    }
+
+    #[test]
+    fn test_min_support() {
+        let mut traces = vec![vec!["A".to_string(), "B".to_string()]; 5];
+        traces.extend(vec![vec!["C".to_string(), "D".to_string()]; 2]);
+        let options = MatrixOptions {
+            min_support: 3,
+            ..Default::default()
+        };
+        let log = VariantLog::from_traces(traces);
+        let matrix = generate_adj_matrix_from_variant_log_with_options(&log, &options, &[]);
+
+        // C/D and D/C are only backed by two traces, all other pairs by at least five
+        assert_eq!(matrix.text.matches(INSUFFICIENT_DATA).count(), 2);
+        assert_eq!(matrix.relationship_counts[INSUFFICIENT_DATA], 2);
+        assert_eq!(matrix.full_independences, 0);
+
+        let dependencies =
+            dependencies_from_variant_log_with_options(&log, &options, &[]);
+        assert_eq!(dependencies.len(), 4 * 3 - 2);
+        assert!(!dependencies.iter().any(|d| d.from == "C" && d.to == "D"));
+        assert_eq!((dependencies[0].from.as_str(), dependencies[0].to.as_str()), ("A", "B"));
+    }
+
+    fn plain_log(traces: &[&str]) -> Vec<Vec<PrefixEvent>> {
+        traces
+            .iter()
+            .enumerate()
+            .map(|(case_idx, trace)| {
+                trace
+                    .chars()
+                    .enumerate()
+                    .map(|(event_idx, activity)| PrefixEvent {
+                        case: format!("case_{}", case_idx),
+                        activity,
+                        predecessor: (event_idx > 0).then(|| format!("case_{}", case_idx)),
+                    })
+                    .collect()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_event_log_conversions() {
+        let traces: Vec<Vec<String>> = [["A", "B"].as_slice(), &["A", "B"], &["C"]]
+            .iter()
+            .map(|trace| trace.iter().map(|activity| activity.to_string()).collect())
+            .collect();
+        let log = EventLog::from_traces(traces);
+        assert_eq!(log.len(), 3);
+        assert_eq!(log.traces[2].case_id, "case_2");
+        assert_eq!(log.activities().len(), 3);
+
+        let variants = VariantLog::from(&log);
+        assert_eq!(variants.variant_count(), 2);
+        assert_eq!(variants.to_traces(), log.to_traces());
+        assert_eq!(variants.cases()[1].case_id, "case_1");
+
+        let epa = ExtendedPrefixAutomaton::from_event_log(&log);
+        assert_eq!(epa.states.len(), 4);
+    }
+
+    #[test]
+    fn test_epa_build() {
+        let epa = ExtendedPrefixAutomaton::build(plain_log(&["abc", "abd", "ac", "abc"]));
+
+        // root, a, ab, abc, abd, ac
+        assert_eq!(epa.states.len(), 6);
+        assert_eq!(epa.transitions.len(), 5);
+
+        let a = epa.transition(epa.root, 'a').unwrap();
+        let ab = epa.transition(a, 'b').unwrap();
+        let abc = epa.transition(ab, 'c').unwrap();
+        let abd = epa.transition(ab, 'd').unwrap();
+        let ac = epa.transition(a, 'c').unwrap();
+        assert!(epa.transition(epa.root, 'b').is_none());
+
+        assert_eq!(epa.state(a).partition, Some(1));
+        assert_eq!(epa.state(ab).partition, Some(1));
+        assert_eq!(epa.state(abc).partition, Some(1));
+        assert_eq!(epa.state(abd).partition, Some(2));
+        assert_eq!(epa.state(ac).partition, Some(3));
+        assert_eq!(epa.state(abc).sequences.len(), 2);
+    }
+
+    #[test]
+    fn test_epa_frequencies() {
+        let epa = ExtendedPrefixAutomaton::build(plain_log(&["abc", "abd", "ac", "abc"]));
+        let a = epa.transition(epa.root, 'a').unwrap();
+        let ab = epa.transition(a, 'b').unwrap();
+
+        assert_eq!(epa.state_frequency(a), 4);
+        assert_eq!(epa.state_case_frequency(a), 4);
+        assert_eq!(epa.state_frequency(ab), 3);
+        assert_eq!(epa.transition_frequency(ab, 'c'), Some(2));
+        assert_eq!(epa.transition_frequency(ab, 'd'), Some(1));
+        assert_eq!(epa.transition_frequency(a, 'c'), Some(1));
+        assert_eq!(epa.transition_frequency(a, 'd'), None);
+        assert_eq!(epa.successors(a).len(), 2);
+    }
+
+    #[test]
+    fn test_reversed_epa() {
+        let log = VariantLog::from_traces(
+            ["abc", "abd", "ac", "xbc"]
+                .iter()
+                .map(|trace| trace.chars().map(String::from).collect())
+                .collect(),
+        );
+
+        let suffixes = ExtendedPrefixAutomaton::from_reversed_variant_log(&log);
+        let c = suffixes.transition(suffixes.root, 'c').unwrap();
+        assert_eq!(suffixes.state_frequency(c), 3);
+        assert_eq!(suffixes.successors(c).len(), 2);
+        assert!(suffixes.transition(suffixes.root, 'a').is_none());
+        // cases sharing their start but not their end have more distinct suffixes than prefixes
+        let shared_start = VariantLog::from_traces(vec![
+            vec!["a".to_string(), "b".to_string(), "c".to_string()],
+            vec!["a".to_string(), "b".to_string(), "d".to_string()],
+        ]);
+        assert_eq!(
+            ExtendedPrefixAutomaton::from_reversed_variant_log(&shared_start).states.len(),
+            7
+        );
+        assert_eq!(
+            ExtendedPrefixAutomaton::from_variant_log(&shared_start).states.len(),
+            5
+        );
+        assert_eq!(
+            ExtendedPrefixAutomaton::from_reversed_variant_log(&shared_start).variant_entropy(),
+            ExtendedPrefixAutomaton::from_variant_log(&shared_start.reversed()).variant_entropy()
+        );
+
+        let before_b = ExtendedPrefixAutomaton::preceding(&log, "b");
+        assert_eq!(before_b.successors(before_b.root).len(), 1);
+        let b = before_b.transition(before_b.root, 'b').unwrap();
+        assert_eq!(before_b.state_frequency(b), 3);
+        assert_eq!(before_b.transition_frequency(b, 'a'), Some(2));
+        assert_eq!(before_b.transition_frequency(b, 'x'), Some(1));
+    }
+
+    #[test]
+    fn test_partition_strategies() {
+        let log = VariantLog::from_traces(
+            ["aba", "ab", "c"]
+                .iter()
+                .map(|trace| trace.chars().map(String::from).collect())
+                .collect(),
+        );
+        let partitions = |strategy| {
+            let epa = ExtendedPrefixAutomaton::from_variant_log_with_partition_strategy(&log, strategy);
+            assert_eq!(epa.partition_strategy(), strategy);
+            let a = epa.transition(epa.root, 'a').unwrap();
+            let ab = epa.transition(a, 'b').unwrap();
+            let aba = epa.transition(ab, 'a').unwrap();
+            let c = epa.transition(epa.root, 'c').unwrap();
+            [a, ab, aba, c].map(|state| epa.state(state).partition.unwrap())
+        };
+
+        assert_eq!(partitions(PartitionStrategy::Branching), [1, 1, 1, 1]);
+        assert_eq!(partitions(PartitionStrategy::PrefixLength), [1, 2, 3, 1]);
+        assert_eq!(partitions(PartitionStrategy::RepetitionCount), [1, 1, 2, 1]);
+        assert_eq!(
+            ExtendedPrefixAutomaton::from_variant_log(&log).partition_strategy(),
+            PartitionStrategy::Branching
+        );
+        assert_eq!(PartitionStrategy::RepetitionCount.to_string(), "repetition-count");
+    }
+
+    #[test]
+    fn test_state_id_display_round_trip() {
+        assert_eq!(StateId::ROOT.to_string(), "root");
+        assert_eq!(StateId(17).to_string(), "s17");
+        assert_eq!("root".parse::<StateId>(), Ok(StateId::ROOT));
+        assert_eq!("s17".parse::<StateId>(), Ok(StateId(17)));
+        assert!("s0".parse::<StateId>().is_err());
+        assert!("17".parse::<StateId>().is_err());
+    }
+
     #[test]
     fn test_get_traces() {
         let traces = "
@@ -382,5 +1311,10 @@ activity 3,activity 1,activity 1,activity 2,
             vec!["activity 3", "activity 1", "activity 1", "activity 2"],
         ];
         assert_eq!(expected_traces, get_traces(traces));
+
+        // streaming the traces straight into the variant count
+        let variants = count_variants(iter_traces(traces));
+        assert_eq!(variants.values().sum::<usize>(), expected_traces.len());
+        assert_eq!(variants[&expected_traces[0]], 1);
     }
 }
@@ -0,0 +1,149 @@
+//! Long-distance dependencies: an early choice that determines a later one, e.g. cases that
+//! take the express branch always end with the express shipment, although other activities
+//! lie in between. Neither the directly-follows graph nor the matrix, which relates every pair
+//! of activities on its own, shows such non-free-choice behavior.
+
+use std::collections::{BTreeMap, HashSet};
+
+use serde::{Deserialize, Serialize};
+
+use crate::dfg::DirectlyFollowsGraph;
+use crate::i18n::{Language, Message};
+use crate::variant_log::VariantLog;
+
+/// How strong a long-distance dependency has to be to be reported.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct LongDistanceSettings {
+    /// Share of the cases with the first activity in which the second one follows later.
+    pub min_confidence: f64,
+    /// Cases in which the second activity follows the first one.
+    pub min_cases: usize,
+}
+
+impl Default for LongDistanceSettings {
+    fn default() -> Self {
+        LongDistanceSettings {
+            min_confidence: 0.95,
+            min_cases: 2,
+        }
+    }
+}
+
+/// Cases with `from` later contain `to`, but never directly after it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct LongDistanceDependency {
+    pub from: String,
+    pub to: String,
+    /// Cases in which `to` follows `from`.
+    pub cases: usize,
+    /// Share of the cases with `from` in which `to` follows.
+    pub confidence: f64,
+    /// How much more likely `to` is in the cases with `from` than in all cases.
+    pub lift: f64,
+}
+
+/// Finds the long-distance dependencies of `log`, the most confident first.
+///
+/// Both activities have to be optional, i.e. missing in some cases, so the dependency is
+/// between two choices rather than a plain sequence, and the second one has to be more likely
+/// after the first one than in general (a lift above 1).
+pub fn long_distance_dependencies(
+    log: &VariantLog,
+    settings: &LongDistanceSettings,
+) -> Vec<LongDistanceDependency> {
+    let total = log.len();
+    let dfg = DirectlyFollowsGraph::from_variant_log(log);
+    let mut cases_with: BTreeMap<&str, usize> = BTreeMap::new();
+    let mut follows: BTreeMap<(&str, &str), usize> = BTreeMap::new();
+    for (variant, frequency) in log.variants() {
+        let mut seen: HashSet<&str> = HashSet::new();
+        let mut pairs: HashSet<(&str, &str)> = HashSet::new();
+        for activity in variant {
+            for &earlier in &seen {
+                if earlier != activity {
+                    pairs.insert((earlier, activity));
+                }
+            }
+            seen.insert(activity);
+        }
+        for activity in seen {
+            *cases_with.entry(activity).or_default() += frequency;
+        }
+        for pair in pairs {
+            *follows.entry(pair).or_default() += frequency;
+        }
+    }
+
+    let mut dependencies: Vec<LongDistanceDependency> = follows
+        .into_iter()
+        .filter(|&((from, to), cases)| {
+            cases >= settings.min_cases.max(1)
+                && cases_with[from] < total
+                && cases_with[to] < total
+                && dfg.frequency(from, to) == 0
+        })
+        .map(|((from, to), cases)| LongDistanceDependency {
+            from: from.to_string(),
+            to: to.to_string(),
+            cases,
+            confidence: cases as f64 / cases_with[from] as f64,
+            lift: (cases as f64 / cases_with[from] as f64) / (cases_with[to] as f64 / total as f64),
+        })
+        .filter(|dependency| {
+            dependency.confidence >= settings.min_confidence && dependency.lift > 1.0
+        })
+        .collect();
+    // stable, so equally confident dependencies stay ordered by activity pair
+    dependencies.sort_by(|a, b| b.confidence.total_cmp(&a.confidence));
+    dependencies
+}
+
+/// One line per dependency with its number of cases, confidence and lift.
+pub fn report(dependencies: &[LongDistanceDependency], language: Language) -> String {
+    let mut output = format!(
+        "{}:\n{:<48}{:<10}{:<12}{}\n",
+        Message::LongDistanceDependencies.text(language),
+        Message::ActivityPair.text(language),
+        Message::Cases.text(language),
+        Message::Confidence.text(language),
+        Message::Lift.text(language)
+    );
+    for dependency in dependencies {
+        output.push_str(&format!(
+            "{:<48}{:<10}{:<12.4}{:.4}\n",
+            format!("{} => {}", dependency.from, dependency.to),
+            dependency.cases,
+            dependency.confidence,
+            dependency.lift
+        ));
+    }
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_long_distance_dependencies() {
+        let trace = |activities: &str| activities.chars().map(|a| a.to_string()).collect();
+        let log = VariantLog::from_traces(vec![
+            trace("SABDE"),
+            trace("SACDE"),
+            trace("SXBFE"),
+            trace("SXCFE"),
+        ]);
+        let dependencies = long_distance_dependencies(&log, &LongDistanceSettings::default());
+
+        let pairs: Vec<(&str, &str)> = dependencies
+            .iter()
+            .map(|d| (d.from.as_str(), d.to.as_str()))
+            .collect();
+        // S and E are in every case, B and C directly precede D and F
+        assert_eq!(pairs, [("A", "D"), ("X", "F")]);
+        assert_eq!(dependencies[0].cases, 2);
+        assert_eq!(dependencies[0].lift, 2.0);
+        assert!(report(&dependencies, Language::English).contains("A => D"));
+    }
+}
@@ -0,0 +1,366 @@
+use crate::variant_log::VariantLog;
+
+/// A linear temporal logic formula on finite traces (LTLf) over activity propositions.
+///
+/// An activity proposition holds at a position if the event there has that activity.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Formula {
+    True,
+    False,
+    Activity(String),
+    Not(Box<Formula>),
+    And(Box<Formula>, Box<Formula>),
+    Or(Box<Formula>, Box<Formula>),
+    Implies(Box<Formula>, Box<Formula>),
+    /// `X φ`: there is a next position and `φ` holds there.
+    Next(Box<Formula>),
+    /// `F φ`: `φ` holds now or at some later position.
+    Eventually(Box<Formula>),
+    /// `G φ`: `φ` holds now and at every later position.
+    Globally(Box<Formula>),
+    /// `φ U ψ`: `ψ` holds eventually and `φ` holds at every position before.
+    Until(Box<Formula>, Box<Formula>),
+}
+
+impl Formula {
+    /// Returns `true` if the formula holds at the start of `trace`.
+    ///
+    /// Every subformula is evaluated once per position, from the end of the trace backwards,
+    /// so checking is linear in the length of the trace times the size of the formula. On the
+    /// empty trace propositions and `X`/`F`/`U` are false while `G` is true.
+    pub fn holds(&self, trace: &[String]) -> bool {
+        self.evaluate(trace)[0]
+    }
+
+    // Truth value at every position, plus one for the (empty) suffix after the last event
+    fn evaluate(&self, trace: &[String]) -> Vec<bool> {
+        let n = trace.len();
+        let backwards = |end: bool, step: &dyn Fn(usize, bool) -> bool| {
+            let mut values = vec![end; n + 1];
+            for i in (0..n).rev() {
+                values[i] = step(i, values[i + 1]);
+            }
+            values
+        };
+
+        match self {
+            Formula::True => vec![true; n + 1],
+            Formula::False => vec![false; n + 1],
+            Formula::Activity(activity) => {
+                let mut values: Vec<bool> = trace.iter().map(|e| e == activity).collect();
+                values.push(false);
+                values
+            }
+            Formula::Not(f) => f.evaluate(trace).into_iter().map(|v| !v).collect(),
+            Formula::And(l, r) => zip(l.evaluate(trace), r.evaluate(trace), |l, r| l && r),
+            Formula::Or(l, r) => zip(l.evaluate(trace), r.evaluate(trace), |l, r| l || r),
+            Formula::Implies(l, r) => zip(l.evaluate(trace), r.evaluate(trace), |l, r| !l || r),
+            Formula::Next(f) => {
+                let inner = f.evaluate(trace);
+                (0..=n).map(|i| i + 1 < n && inner[i + 1]).collect()
+            }
+            Formula::Eventually(f) => {
+                let inner = f.evaluate(trace);
+                backwards(false, &|i, later| inner[i] || later)
+            }
+            Formula::Globally(f) => {
+                let inner = f.evaluate(trace);
+                backwards(true, &|i, later| inner[i] && later)
+            }
+            Formula::Until(l, r) => {
+                let (left, right) = (l.evaluate(trace), r.evaluate(trace));
+                backwards(false, &|i, later| right[i] || (left[i] && later))
+            }
+        }
+    }
+}
+
+fn zip(left: Vec<bool>, right: Vec<bool>, op: impl Fn(bool, bool) -> bool) -> Vec<bool> {
+    left.into_iter().zip(right).map(|(l, r)| op(l, r)).collect()
+}
+
+impl std::fmt::Display for Formula {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Formula::True => write!(f, "true"),
+            Formula::False => write!(f, "false"),
+            Formula::Activity(activity) if is_plain_name(activity) => write!(f, "{}", activity),
+            Formula::Activity(activity) => write!(f, "\"{}\"", activity),
+            Formula::Not(inner) => write!(f, "!{}", inner),
+            Formula::And(l, r) => write!(f, "({} && {})", l, r),
+            Formula::Or(l, r) => write!(f, "({} || {})", l, r),
+            Formula::Implies(l, r) => write!(f, "({} -> {})", l, r),
+            Formula::Next(inner) => write!(f, "X {}", inner),
+            Formula::Eventually(inner) => write!(f, "F {}", inner),
+            Formula::Globally(inner) => write!(f, "G {}", inner),
+            Formula::Until(l, r) => write!(f, "({} U {})", l, r),
+        }
+    }
+}
+
+const KEYWORDS: [&str; 6] = ["X", "F", "G", "U", "true", "false"];
+
+fn is_plain_name(name: &str) -> bool {
+    !name.is_empty()
+        && name.chars().all(is_name_char)
+        && !KEYWORDS.contains(&name)
+}
+
+fn is_name_char(c: char) -> bool {
+    c.is_alphanumeric() || matches!(c, '_' | '.' | ':')
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Name(String),
+    Quoted(String),
+    Not,
+    And,
+    Or,
+    Implies,
+    Open,
+    Close,
+}
+
+fn tokenize(s: &str) -> Result<Vec<Token>, String> {
+    let mut tokens = Vec::new();
+    let mut chars = s.chars().peekable();
+
+    while let Some(&c) = chars.peek() {
+        match c {
+            c if c.is_whitespace() => {
+                chars.next();
+            }
+            '(' | ')' | '!' => {
+                chars.next();
+                tokens.push(match c {
+                    '(' => Token::Open,
+                    ')' => Token::Close,
+                    _ => Token::Not,
+                });
+            }
+            '&' | '|' => {
+                chars.next();
+                // accept both `&` and `&&` (`|` and `||`)
+                if chars.peek() == Some(&c) {
+                    chars.next();
+                }
+                tokens.push(if c == '&' { Token::And } else { Token::Or });
+            }
+            '-' => {
+                chars.next();
+                if chars.next() != Some('>') {
+                    return Err("Expected '->'".to_string());
+                }
+                tokens.push(Token::Implies);
+            }
+            '"' => {
+                chars.next();
+                let mut name = String::new();
+                loop {
+                    match chars.next() {
+                        Some('"') => break,
+                        Some(c) => name.push(c),
+                        None => return Err("Unterminated quoted activity".to_string()),
+                    }
+                }
+                tokens.push(Token::Quoted(name));
+            }
+            c if is_name_char(c) => {
+                let mut name = String::new();
+                while let Some(&c) = chars.peek().filter(|c| is_name_char(**c)) {
+                    name.push(c);
+                    chars.next();
+                }
+                tokens.push(Token::Name(name));
+            }
+            c => return Err(format!("Unexpected character '{}'", c)),
+        }
+    }
+
+    Ok(tokens)
+}
+
+// Recursive descent parser, from lowest to highest precedence: ->, ||, &&, U, unary operators
+struct Parser {
+    tokens: Vec<Token>,
+    pos: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos)
+    }
+
+    fn next(&mut self) -> Option<Token> {
+        let token = self.tokens.get(self.pos).cloned();
+        self.pos += 1;
+        token
+    }
+
+    fn implication(&mut self) -> Result<Formula, String> {
+        let left = self.disjunction()?;
+        if self.peek() == Some(&Token::Implies) {
+            self.next();
+            // right associative
+            let right = self.implication()?;
+            return Ok(Formula::Implies(Box::new(left), Box::new(right)));
+        }
+        Ok(left)
+    }
+
+    fn disjunction(&mut self) -> Result<Formula, String> {
+        let mut left = self.conjunction()?;
+        while self.peek() == Some(&Token::Or) {
+            self.next();
+            left = Formula::Or(Box::new(left), Box::new(self.conjunction()?));
+        }
+        Ok(left)
+    }
+
+    fn conjunction(&mut self) -> Result<Formula, String> {
+        let mut left = self.until()?;
+        while self.peek() == Some(&Token::And) {
+            self.next();
+            left = Formula::And(Box::new(left), Box::new(self.until()?));
+        }
+        Ok(left)
+    }
+
+    fn until(&mut self) -> Result<Formula, String> {
+        let left = self.unary()?;
+        if self.peek() == Some(&Token::Name("U".to_string())) {
+            self.next();
+            let right = self.until()?;
+            return Ok(Formula::Until(Box::new(left), Box::new(right)));
+        }
+        Ok(left)
+    }
+
+    fn unary(&mut self) -> Result<Formula, String> {
+        match self.next() {
+            Some(Token::Not) => Ok(Formula::Not(Box::new(self.unary()?))),
+            Some(Token::Open) => {
+                let inner = self.implication()?;
+                match self.next() {
+                    Some(Token::Close) => Ok(inner),
+                    _ => Err("Expected ')'".to_string()),
+                }
+            }
+            Some(Token::Quoted(name)) => Ok(Formula::Activity(name)),
+            Some(Token::Name(name)) => match name.as_str() {
+                "X" => Ok(Formula::Next(Box::new(self.unary()?))),
+                "F" => Ok(Formula::Eventually(Box::new(self.unary()?))),
+                "G" => Ok(Formula::Globally(Box::new(self.unary()?))),
+                "true" => Ok(Formula::True),
+                "false" => Ok(Formula::False),
+                "U" => Err("Unexpected 'U'".to_string()),
+                _ => Ok(Formula::Activity(name)),
+            },
+            Some(token) => Err(format!("Unexpected {:?}", token)),
+            None => Err("Unexpected end of formula".to_string()),
+        }
+    }
+}
+
+impl std::str::FromStr for Formula {
+    type Err = String;
+
+    /// Parses formulas like `G(A -> F B)`, `!B U A` or `F("Send invoice" && X Pay)`.
+    ///
+    /// `X`, `F`, `G`, `U`, `true` and `false` are keywords; activities with that name or with
+    /// characters other than letters, digits, `_`, `.` and `:` have to be quoted.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut parser = Parser {
+            tokens: tokenize(s)?,
+            pos: 0,
+        };
+        let formula = parser.implication()?;
+        match parser.peek() {
+            None => Ok(formula),
+            Some(token) => Err(format!("Unexpected {:?} after formula", token)),
+        }
+    }
+}
+
+/// How many cases satisfy a formula.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FormulaStatistics {
+    pub formula: Formula,
+    pub satisfied: usize,
+    pub violated: usize,
+}
+
+impl FormulaStatistics {
+    pub fn satisfaction_rate(&self) -> f64 {
+        let total = self.satisfied + self.violated;
+        if total == 0 {
+            return 0.0;
+        }
+        self.satisfied as f64 / total as f64
+    }
+}
+
+/// Checks the formula against every case of the log.
+pub fn check_formula(formula: &Formula, log: &VariantLog) -> FormulaStatistics {
+    let mut statistics = FormulaStatistics {
+        formula: formula.clone(),
+        satisfied: 0,
+        violated: 0,
+    };
+    for (variant, frequency) in log.variants() {
+        if formula.holds(variant) {
+            statistics.satisfied += frequency;
+        } else {
+            statistics.violated += frequency;
+        }
+    }
+    statistics
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::variant_log::trace;
+
+    fn holds(formula: &str, activities: &[&str]) -> bool {
+        formula.parse::<Formula>().unwrap().holds(&trace(activities))
+    }
+
+    #[test]
+    fn test_operators() {
+        assert!(holds("G(A -> F B)", &["A", "C", "B", "A", "B"]));
+        assert!(!holds("G(A -> F B)", &["A", "B", "A"]));
+        assert!(holds("A && X B", &["A", "B"]));
+        // strong next: there is no position after the last event
+        assert!(!holds("F(B && X true)", &["A", "B"]));
+        assert!(holds("!B U A", &["C", "A", "B"]));
+        assert!(!holds("!B U A", &["C", "B", "A"]));
+        assert!(holds("G !D", &[]));
+        assert!(!holds("F A", &[]));
+        assert!(holds("F \"Send invoice\"", &["Send invoice"]));
+    }
+
+    #[test]
+    fn test_parse() {
+        let formula: Formula = "G(A -> F B) || !X\"U\"".parse().unwrap();
+        assert_eq!(formula.to_string(), "(G (A -> F B) || !X \"U\")");
+        assert_eq!(formula.to_string().parse::<Formula>(), Ok(formula));
+        assert!("G(A -> ".parse::<Formula>().is_err());
+        assert!("A B".parse::<Formula>().is_err());
+    }
+
+    #[test]
+    fn test_check_formula() {
+        let log = VariantLog::from_traces(vec![
+            trace(&["A", "B"]),
+            trace(&["A"]),
+            trace(&["A", "B"]),
+            trace(&["C"]),
+        ]);
+        let statistics = check_formula(&"G(A -> F B)".parse().unwrap(), &log);
+        assert_eq!(statistics.satisfied, 3);
+        assert_eq!(statistics.violated, 1);
+        assert_eq!(statistics.satisfaction_rate(), 0.75);
+    }
+}
@@ -0,0 +1,300 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use serde::Serialize;
+
+use crate::export::csv_field;
+use crate::i18n::{Language, Message};
+use crate::metrics::{LogMetric, LogView, MetricValue};
+use crate::variant_log::VariantLog;
+
+/// Column names of [`MarkovModel::to_csv`].
+pub const TRANSITIONS_CSV_HEADER: [&str; 4] = ["context", "next", "count", "probability"];
+
+// Separates the activities of a context in the CSV export
+const CONTEXT_SEPARATOR: &str = " > ";
+
+/// A k-th order Markov chain over the activities of a log: the probability of the next
+/// activity, or of the end of the trace, given the last `order` activities.
+///
+/// Contexts at the start of a trace are shorter than `order`, so the model also knows which
+/// activities start a trace. Probabilities are smoothed with add-`smoothing` (Laplace)
+/// smoothing over the activities seen in training, the end of the trace and one bucket for all
+/// unknown activities, so held-out traces with unseen transitions still have a finite
+/// perplexity.
+///
+/// ```
+/// use egypt::markov::MarkovModel;
+/// use egypt::variant_log::VariantLog;
+///
+/// let traces = vec![vec!["A".to_string(), "B".to_string()]; 3];
+/// let model = MarkovModel::fit(&VariantLog::from_traces(traces), 1, 0.0);
+/// assert_eq!(model.probability(&["A"], Some("B")), 1.0);
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct MarkovModel {
+    order: usize,
+    smoothing: f64,
+    activities: BTreeSet<String>,
+    // context -> next activity (`None` for the end of the trace) -> count
+    transitions: BTreeMap<Vec<String>, BTreeMap<Option<String>, usize>>,
+}
+
+/// One observed transition of a [`MarkovModel`].
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Transition {
+    pub context: Vec<String>,
+    /// `None` for the end of the trace.
+    pub next: Option<String>,
+    pub count: usize,
+    /// Smoothed probability of `next` after `context`.
+    pub probability: f64,
+}
+
+impl MarkovModel {
+    /// Fits a model of the given order on the variants of `log`, weighted by their frequency.
+    /// An order of 0 ignores the context and only counts the activities. The traces of ongoing
+    /// cases count for the activities that follow each other, but not for the ends.
+    pub fn fit(log: &VariantLog, order: usize, smoothing: f64) -> Self {
+        assert!(smoothing >= 0.0, "smoothing must not be negative");
+        let mut model = MarkovModel {
+            order,
+            smoothing,
+            activities: BTreeSet::new(),
+            transitions: BTreeMap::new(),
+        };
+        for (variant, frequency, ongoing) in log.variants_with_ongoing() {
+            model.activities.extend(variant.iter().cloned());
+            for (context, next) in steps(variant, order) {
+                // ongoing cases haven't ended yet
+                let count = if next.is_some() {
+                    frequency
+                } else {
+                    frequency - ongoing
+                };
+                if count == 0 {
+                    continue;
+                }
+                *model
+                    .transitions
+                    .entry(context.to_vec())
+                    .or_default()
+                    .entry(next.cloned())
+                    .or_default() += count;
+            }
+        }
+        model
+    }
+
+    pub fn order(&self) -> usize {
+        self.order
+    }
+
+    /// The activities seen in training.
+    pub fn activities(&self) -> &BTreeSet<String> {
+        &self.activities
+    }
+
+    /// Probability of `next` (`None` for the end of the trace) after the last `order`
+    /// activities of `context`. A context without observations gives every outcome the same
+    /// probability, or 0 without smoothing.
+    pub fn probability<S: AsRef<str>>(&self, context: &[S], next: Option<&str>) -> f64 {
+        let context: Vec<String> = context[context.len().saturating_sub(self.order)..]
+            .iter()
+            .map(|activity| activity.as_ref().to_string())
+            .collect();
+        let counts = self.transitions.get(&context);
+        let total: usize = counts.map_or(0, |counts| counts.values().sum());
+        let count = counts
+            .and_then(|counts| counts.get(&next.map(str::to_string)))
+            .copied()
+            .unwrap_or(0);
+
+        // the known activities, the end of the trace and the unknown activities
+        let outcomes = (self.activities.len() + 2) as f64;
+        let denominator = total as f64 + self.smoothing * outcomes;
+        if denominator == 0.0 {
+            return 0.0;
+        }
+        (count as f64 + self.smoothing) / denominator
+    }
+
+    /// Perplexity of the model on the traces of `log`: the exponential of the mean negative log
+    /// likelihood of every activity and trace end, weighted by the variant frequencies. Lower is
+    /// better; a model that always knows the next activity has a perplexity of 1. Infinite if
+    /// the unsmoothed model rules out an observed transition, `None` for an empty log.
+    pub fn perplexity(&self, log: &VariantLog) -> Option<f64> {
+        let mut log_likelihood = 0.0;
+        let mut steps_count = 0;
+        for (variant, frequency) in log.variants() {
+            for (context, next) in steps(variant, self.order) {
+                let probability = self.probability(context, next.map(String::as_str));
+                log_likelihood += frequency as f64 * probability.ln();
+                steps_count += frequency;
+            }
+        }
+        (steps_count > 0).then(|| (-log_likelihood / steps_count as f64).exp())
+    }
+
+    /// The observed transitions with their smoothed probabilities, ordered by context.
+    pub fn transitions(&self) -> Vec<Transition> {
+        self.transitions
+            .iter()
+            .flat_map(|(context, counts)| {
+                counts.iter().map(move |(next, &count)| Transition {
+                    context: context.clone(),
+                    next: next.clone(),
+                    count,
+                    probability: self.probability(context, next.as_deref()),
+                })
+            })
+            .collect()
+    }
+
+    /// Exports the transitions as CSV (see [`TRANSITIONS_CSV_HEADER`]); the activities of a
+    /// context are joined with `" > "`, the end of the trace is an empty `next`.
+    pub fn to_csv(&self) -> String {
+        let mut output = TRANSITIONS_CSV_HEADER.join(",");
+        output.push('\n');
+        for transition in self.transitions() {
+            output.push_str(&format!(
+                "{},{},{},{}\n",
+                csv_field(&transition.context.join(CONTEXT_SEPARATOR)),
+                csv_field(transition.next.as_deref().unwrap_or("")),
+                transition.count,
+                transition.probability
+            ));
+        }
+        output
+    }
+
+    /// Exports the transitions as a JSON array of [`Transition`]s.
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(&self.transitions())
+            .expect("transitions are always valid JSON")
+    }
+}
+
+// Every prediction in a trace: the preceding activities (at most `order`) and the next
+// activity, or `None` for the end of the trace
+fn steps(trace: &[String], order: usize) -> impl Iterator<Item = (&[String], Option<&String>)> {
+    (0..=trace.len()).map(move |idx| (&trace[idx.saturating_sub(order)..idx], trace.get(idx)))
+}
+
+/// The perplexity of a Laplace-smoothed Markov model of the given order on the log it was
+/// fitted on: how many next activities the log leaves open on average, given the last `order`
+/// activities. Not one of the built-in metrics; register it with
+/// [`crate::metrics::MetricRegistry::register`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MarkovPerplexity {
+    order: usize,
+    id: String,
+}
+
+impl MarkovPerplexity {
+    pub fn new(order: usize) -> Self {
+        MarkovPerplexity {
+            order,
+            id: format!("markov_perplexity_{}", order),
+        }
+    }
+}
+
+impl LogMetric for MarkovPerplexity {
+    fn id(&self) -> &str {
+        &self.id
+    }
+
+    fn label(&self, language: Language) -> String {
+        Message::MarkovPerplexity.format(language, &[&self.order.to_string()])
+    }
+
+    fn compute(&self, view: &LogView) -> MetricValue {
+        MarkovModel::fit(view.log(), self.order, 1.0)
+            .perplexity(view.log())
+            .map_or(MetricValue::Undefined, MetricValue::Number)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use super::*;
+    use crate::metrics::MetricRegistry;
+
+    fn log(traces: &[&[&str]]) -> VariantLog {
+        VariantLog::from_traces(
+            traces
+                .iter()
+                .map(|trace| trace.iter().map(|s| s.to_string()).collect())
+                .collect(),
+        )
+    }
+
+    #[test]
+    fn test_fit_and_perplexity() {
+        let training = log(&[&["A", "B", "C"], &["A", "B", "C"], &["A", "C"]]);
+
+        let first_order = MarkovModel::fit(&training, 1, 0.0);
+        assert_eq!(first_order.probability(&["A"], Some("B")), 2.0 / 3.0);
+        assert_eq!(first_order.probability(&["B", "C"], None), 1.0);
+        assert_eq!(first_order.probability::<&str>(&[], Some("A")), 1.0);
+        assert_eq!(first_order.probability(&["unknown"], Some("A")), 0.0);
+        // A, C has two certain steps out of three, A, B, C three out of four
+        let expected = (-(2.0 * (2.0f64 / 3.0).ln() + (1.0f64 / 3.0).ln()) / 11.0).exp();
+        assert!((first_order.perplexity(&training).unwrap() - expected).abs() < 1e-9);
+
+        // the second order model knows that C ends both variants
+        let second_order = MarkovModel::fit(&training, 2, 0.0);
+        assert_eq!(second_order.probability(&["A", "B"], Some("C")), 1.0);
+        assert_eq!(
+            second_order.perplexity(&training),
+            first_order.perplexity(&training)
+        );
+
+        let held_out = log(&[&["A", "D"]]);
+        assert_eq!(first_order.perplexity(&held_out), Some(f64::INFINITY));
+        let smoothed = MarkovModel::fit(&training, 1, 1.0);
+        // A, B, C, end and the unknown activities
+        assert_eq!(smoothed.probability(&["A"], Some("D")), 1.0 / 8.0);
+        assert!(smoothed.perplexity(&held_out).unwrap().is_finite());
+        assert_eq!(smoothed.perplexity(&VariantLog::new()), None);
+    }
+
+    #[test]
+    fn test_ongoing_cases() {
+        // the case stopped after A is still running, so it doesn't show that A ends cases
+        let training = log(&[&["A", "B"], &["A"]])
+            .mark_ongoing(&HashSet::from(["case_1".to_string()]));
+        let model = MarkovModel::fit(&training, 1, 0.0);
+        assert_eq!(model.probability(&["A"], Some("B")), 1.0);
+        assert_eq!(model.probability(&["A"], None), 0.0);
+        assert_eq!(model.probability::<&str>(&[], Some("A")), 1.0);
+    }
+
+    #[test]
+    fn test_export() {
+        let model = MarkovModel::fit(&log(&[&["A", "B"], &["A"]]), 1, 0.0);
+        assert_eq!(
+            model.to_csv(),
+            "context,next,count,probability\n\
+             ,A,2,1\n\
+             A,,1,0.5\n\
+             A,B,1,0.5\n\
+             B,,1,1\n"
+        );
+        let transitions: serde_json::Value = serde_json::from_str(&model.to_json()).unwrap();
+        assert_eq!(transitions[1]["next"], serde_json::Value::Null);
+        assert_eq!(transitions[2]["context"][0], "A");
+    }
+
+    #[test]
+    fn test_metric() {
+        let mut registry = MetricRegistry::empty();
+        registry.register(Box::new(MarkovPerplexity::new(1)));
+        let results = registry.compute(&log(&[&["A", "B"]]), Language::English);
+        assert_eq!(results[0].id, "markov_perplexity_1");
+        assert_eq!(results[0].label, "Markov perplexity (order 1)");
+        assert!(matches!(results[0].value, MetricValue::Number(value) if value > 1.0));
+    }
+}
@@ -0,0 +1,442 @@
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+use crate::dependency_types::custom::VariantIndex;
+use crate::glossary::{self, Metric};
+use crate::i18n::{Language, Message};
+use crate::random::SeededRng;
+use crate::variant_log::VariantLog;
+use crate::{ExtendedPrefixAutomaton, PartitionStrategy};
+
+/// Read-only access to the log a [`LogMetric`] is computed on.
+pub struct LogView<'a> {
+    log: &'a VariantLog,
+    variants: Vec<(Vec<&'a str>, usize)>,
+}
+
+impl<'a> LogView<'a> {
+    pub fn new(log: &'a VariantLog) -> Self {
+        LogView {
+            log,
+            variants: log.weighted_variants(),
+        }
+    }
+
+    pub fn log(&self) -> &'a VariantLog {
+        self.log
+    }
+
+    /// The variants with their frequencies, see [`VariantLog::weighted_variants`].
+    pub fn variants(&self) -> &[(Vec<&'a str>, usize)] {
+        &self.variants
+    }
+
+    /// Index of the variants every activity occurs in.
+    pub fn index(&self) -> VariantIndex<'_> {
+        VariantIndex::new(&self.variants)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum MetricValue {
+    Count(usize),
+    /// A share between 0 and 1.
+    Ratio(f64),
+    Number(f64),
+    /// The metric is undefined for the log, e.g. a ratio of an empty log.
+    Undefined,
+}
+
+impl MetricValue {
+    pub fn as_f64(&self) -> Option<f64> {
+        match *self {
+            MetricValue::Count(count) => Some(count as f64),
+            MetricValue::Ratio(value) | MetricValue::Number(value) => Some(value),
+            MetricValue::Undefined => None,
+        }
+    }
+}
+
+impl fmt::Display for MetricValue {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        // padded, so values can be aligned in columns
+        let text = match self {
+            MetricValue::Count(count) => count.to_string(),
+            MetricValue::Ratio(value) | MetricValue::Number(value) => format!("{:.4}", value),
+            MetricValue::Undefined => "-".to_string(),
+        };
+        f.pad(&text)
+    }
+}
+
+/// A log-level metric that is shown in the reports and exports.
+pub trait LogMetric {
+    /// Stable identifier, used as the key in exports.
+    fn id(&self) -> &str;
+
+    /// Name shown in reports.
+    fn label(&self, language: Language) -> String;
+
+    /// Explanation for the glossary and tooltips, if any.
+    fn glossary(&self) -> Option<&Metric> {
+        None
+    }
+
+    fn compute(&self, log: &LogView) -> MetricValue;
+}
+
+/// A computed metric, ready to be reported or exported.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MetricResult {
+    pub id: String,
+    pub label: String,
+    pub value: MetricValue,
+    /// Only computed for ratios and other non-integer values, see [`Bootstrap`].
+    pub confidence_interval: Option<ConfidenceInterval>,
+}
+
+/// Range of a metric's value over the resampled logs, see [`Bootstrap`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ConfidenceInterval {
+    pub lower: f64,
+    pub upper: f64,
+}
+
+impl fmt::Display for ConfidenceInterval {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "[{:.4}, {:.4}]", self.lower, self.upper)
+    }
+}
+
+/// Estimates the sampling noise of metrics by resampling the traces of a log with replacement
+/// and taking the percentiles of the resampled values (percentile bootstrap).
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Bootstrap {
+    /// Number of resampled logs.
+    pub resamples: usize,
+    /// Share of the resampled values inside the interval, e.g. 0.95.
+    pub confidence_level: f64,
+}
+
+impl Default for Bootstrap {
+    fn default() -> Self {
+        Bootstrap {
+            resamples: 200,
+            confidence_level: 0.95,
+        }
+    }
+}
+
+impl Bootstrap {
+    /// Draws as many cases as `log` has, with replacement.
+    pub fn resample(&self, log: &VariantLog, rng: &mut SeededRng) -> VariantLog {
+        VariantLog::from_traces(
+            (0..log.len())
+                .map(|_| log.trace(rng.gen_index(log.len())).to_vec())
+                .collect(),
+        )
+    }
+
+    /// The percentile interval of `values`, `None` if there are none.
+    pub fn interval(&self, mut values: Vec<f64>) -> Option<ConfidenceInterval> {
+        if values.is_empty() {
+            return None;
+        }
+        values.sort_by(f64::total_cmp);
+        let tail = (1.0 - self.confidence_level.clamp(0.0, 1.0)) / 2.0;
+        let percentile = |share: f64| values[(share * (values.len() - 1) as f64).round() as usize];
+        Some(ConfidenceInterval {
+            lower: percentile(tail),
+            upper: percentile(1.0 - tail),
+        })
+    }
+}
+
+/// The metrics computed for every report, built-in and user-supplied ones alike.
+pub struct MetricRegistry {
+    metrics: Vec<Box<dyn LogMetric>>,
+}
+
+impl Default for MetricRegistry {
+    fn default() -> Self {
+        Self::with_builtins()
+    }
+}
+
+impl MetricRegistry {
+    /// A registry without any metrics.
+    pub fn empty() -> Self {
+        MetricRegistry {
+            metrics: Vec::new(),
+        }
+    }
+
+    /// A registry with the built-in log metrics: variant counts and frequencies and the
+    /// (normalized) variant entropy.
+    pub fn with_builtins() -> Self {
+        Self::with_builtins_and_partition_strategy(PartitionStrategy::default())
+    }
+
+    /// Same as [`MetricRegistry::with_builtins`], but the variant entropies partition the
+    /// prefix automaton with `strategy`; their labels name the strategy.
+    pub fn with_builtins_and_partition_strategy(strategy: PartitionStrategy) -> Self {
+        let mut registry = Self::empty();
+        for builtin in BUILTINS {
+            registry.register(Box::new(builtin.with_partition_strategy(strategy)));
+        }
+        registry
+    }
+
+    /// Adds a metric after the already registered ones; a metric with the same id is replaced
+    /// in place.
+    pub fn register(&mut self, metric: Box<dyn LogMetric>) {
+        match self.metrics.iter().position(|m| m.id() == metric.id()) {
+            Some(idx) => self.metrics[idx] = metric,
+            None => self.metrics.push(metric),
+        }
+    }
+
+    pub fn metrics(&self) -> impl Iterator<Item = &dyn LogMetric> {
+        self.metrics.iter().map(|metric| metric.as_ref())
+    }
+
+    /// Computes all metrics on `log`, in order of registration.
+    pub fn compute(&self, log: &VariantLog, language: Language) -> Vec<MetricResult> {
+        let view = LogView::new(log);
+        self.metrics()
+            .map(|metric| MetricResult {
+                id: metric.id().to_string(),
+                label: metric.label(language),
+                value: metric.compute(&view),
+                confidence_interval: None,
+            })
+            .collect()
+    }
+
+    /// Same as [`MetricRegistry::compute`], but also bootstraps confidence intervals of the
+    /// metrics with ratios or other non-integer values, e.g. the variant entropy.
+    pub fn compute_with_bootstrap(
+        &self,
+        log: &VariantLog,
+        language: Language,
+        bootstrap: &Bootstrap,
+        seed: u64,
+    ) -> Vec<MetricResult> {
+        let mut results = self.compute(log, language);
+        if log.is_empty() {
+            return results;
+        }
+        let resampled: Vec<usize> = results
+            .iter()
+            .enumerate()
+            .filter(|(_, result)| matches!(result.value, MetricValue::Ratio(_) | MetricValue::Number(_)))
+            .map(|(idx, _)| idx)
+            .collect();
+
+        let mut rng = SeededRng::new(seed);
+        let mut values = vec![Vec::with_capacity(bootstrap.resamples); results.len()];
+        for _ in 0..bootstrap.resamples {
+            let sample = bootstrap.resample(log, &mut rng);
+            let view = LogView::new(&sample);
+            for &idx in &resampled {
+                if let Some(value) = self.metrics[idx].compute(&view).as_f64() {
+                    values[idx].push(value);
+                }
+            }
+        }
+        for (result, values) in results.iter_mut().zip(values) {
+            result.confidence_interval = bootstrap.interval(values);
+        }
+        results
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+enum Builtin {
+    Variants,
+    VariantsPerTraces,
+    MaxVariantFrequencyPerTraces,
+    MaxVariantFrequencyPerVariants,
+    VariantEntropy(PartitionStrategy),
+    NormalizedVariantEntropy(PartitionStrategy),
+}
+
+const BUILTINS: &[Builtin] = &[
+    Builtin::MaxVariantFrequencyPerTraces,
+    Builtin::VariantsPerTraces,
+    Builtin::Variants,
+    Builtin::MaxVariantFrequencyPerVariants,
+    Builtin::VariantEntropy(PartitionStrategy::Branching),
+    Builtin::NormalizedVariantEntropy(PartitionStrategy::Branching),
+];
+
+impl Builtin {
+    fn with_partition_strategy(&self, strategy: PartitionStrategy) -> Self {
+        match self {
+            Builtin::VariantEntropy(_) => Builtin::VariantEntropy(strategy),
+            Builtin::NormalizedVariantEntropy(_) => Builtin::NormalizedVariantEntropy(strategy),
+            builtin => *builtin,
+        }
+    }
+
+    fn message(&self) -> Message {
+        match self {
+            Builtin::Variants => Message::Variants,
+            Builtin::VariantsPerTraces => Message::VariantsPerTraces,
+            Builtin::MaxVariantFrequencyPerTraces => Message::MaxVariantFrequencyPerTraces,
+            Builtin::MaxVariantFrequencyPerVariants => Message::MaxVariantFrequencyPerVariants,
+            Builtin::VariantEntropy(_) => Message::VariantEntropy,
+            Builtin::NormalizedVariantEntropy(_) => Message::NormalizedVariantEntropy,
+        }
+    }
+}
+
+impl LogMetric for Builtin {
+    fn id(&self) -> &str {
+        glossary::metric_for_label(self.message())
+            .map(|metric| metric.id)
+            .expect("every built-in metric is described in the glossary")
+    }
+
+    fn label(&self, language: Language) -> String {
+        let label = self.message().text(language);
+        match self {
+            Builtin::VariantEntropy(strategy) | Builtin::NormalizedVariantEntropy(strategy) => {
+                format!("{} ({})", label, strategy)
+            }
+            _ => label.to_string(),
+        }
+    }
+
+    fn glossary(&self) -> Option<&Metric> {
+        glossary::metric_for_label(self.message())
+    }
+
+    fn compute(&self, view: &LogView) -> MetricValue {
+        let log = view.log();
+        if log.is_empty() {
+            return match self {
+                Builtin::Variants => MetricValue::Count(0),
+                _ => MetricValue::Undefined,
+            };
+        }
+        let max_variant_frequency = || {
+            log.variants().map(|(_, frequency)| frequency).max().unwrap_or(0) as f64
+                / log.len() as f64
+        };
+
+        match self {
+            Builtin::Variants => MetricValue::Count(log.variant_count()),
+            Builtin::VariantsPerTraces => {
+                MetricValue::Ratio(log.variant_count() as f64 / log.len() as f64)
+            }
+            Builtin::MaxVariantFrequencyPerTraces => MetricValue::Ratio(max_variant_frequency()),
+            Builtin::MaxVariantFrequencyPerVariants => {
+                MetricValue::Number(max_variant_frequency() / log.variant_count() as f64)
+            }
+            Builtin::VariantEntropy(strategy) => MetricValue::Number(
+                ExtendedPrefixAutomaton::from_variant_log_with_partition_strategy(log, *strategy)
+                    .variant_entropy(),
+            ),
+            Builtin::NormalizedVariantEntropy(strategy) => MetricValue::Ratio(
+                ExtendedPrefixAutomaton::from_variant_log_with_partition_strategy(log, *strategy)
+                    .normalized_variant_entropy(),
+            ),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct MeanTraceLength;
+
+    impl LogMetric for MeanTraceLength {
+        fn id(&self) -> &str {
+            "mean_trace_length"
+        }
+
+        fn label(&self, _language: Language) -> String {
+            "Mean trace length".to_string()
+        }
+
+        fn compute(&self, log: &LogView) -> MetricValue {
+            let events: usize = log
+                .variants()
+                .iter()
+                .map(|(variant, frequency)| variant.len() * frequency)
+                .sum();
+            MetricValue::Number(events as f64 / log.log().len() as f64)
+        }
+    }
+
+    fn log() -> VariantLog {
+        VariantLog::from_traces(vec![
+            vec!["A".to_string(), "B".to_string()],
+            vec!["A".to_string(), "B".to_string()],
+            vec!["A".to_string()],
+            vec!["C".to_string()],
+        ])
+    }
+
+    #[test]
+    fn test_builtins() {
+        let results = MetricRegistry::with_builtins().compute(&log(), Language::English);
+        let value = |id: &str| results.iter().find(|r| r.id == id).unwrap().value;
+
+        assert_eq!(value("variants"), MetricValue::Count(3));
+        assert_eq!(value("variants_per_traces"), MetricValue::Ratio(0.75));
+        assert_eq!(value("max_variant_frequency_per_traces"), MetricValue::Ratio(0.5));
+        assert!(results.iter().all(|r| r.value != MetricValue::Undefined));
+        let entropy = results.iter().find(|r| r.id == "variant_entropy").unwrap();
+        assert_eq!(entropy.label, "Variant Entropy (branching)");
+
+        let by_length =
+            MetricRegistry::with_builtins_and_partition_strategy(PartitionStrategy::PrefixLength)
+                .compute(&log(), Language::English);
+        let entropy = by_length.iter().find(|r| r.id == "variant_entropy").unwrap();
+        assert_eq!(entropy.label, "Variant Entropy (prefix-length)");
+        assert_ne!(value("variant_entropy"), entropy.value);
+    }
+
+    #[test]
+    fn test_custom_metric() {
+        let mut registry = MetricRegistry::with_builtins();
+        let builtins = registry.metrics().count();
+        registry.register(Box::new(MeanTraceLength));
+
+        let results = registry.compute(&log(), Language::German);
+        assert_eq!(results.len(), builtins + 1);
+        assert_eq!(results[builtins].value, MetricValue::Number(1.5));
+        assert_eq!(results[builtins].value.to_string(), "1.5000");
+        assert_eq!(
+            MetricRegistry::with_builtins().compute(&VariantLog::new(), Language::English)[0].value,
+            MetricValue::Undefined
+        );
+    }
+
+    #[test]
+    fn test_bootstrap() {
+        let registry = MetricRegistry::with_builtins();
+        let bootstrap = Bootstrap::default();
+        let results = registry.compute_with_bootstrap(&log(), Language::English, &bootstrap, 7);
+        let result = |id: &str| results.iter().find(|r| r.id == id).unwrap();
+
+        assert_eq!(result("variants").confidence_interval, None);
+        let interval = result("variants_per_traces").confidence_interval.unwrap();
+        assert!(interval.lower <= 0.75 && 0.75 <= interval.upper);
+        assert!(interval.lower < interval.upper);
+        assert!(result("variant_entropy").confidence_interval.is_some());
+        // the same seed gives the same intervals
+        assert_eq!(
+            registry.compute_with_bootstrap(&log(), Language::English, &bootstrap, 7),
+            results
+        );
+
+        let interval = bootstrap.interval((0..=100).map(f64::from).collect()).unwrap();
+        assert_eq!(interval, ConfidenceInterval { lower: 3.0, upper: 98.0 });
+    }
+}
@@ -0,0 +1,202 @@
+use std::collections::{BTreeMap, BTreeSet, HashSet, VecDeque};
+
+use chrono::{DateTime, Duration, Utc};
+
+use crate::dependency_types::{
+    dependency::Dependency, existential::check_existential_dependency_weighted,
+    temporal::check_temporal_dependency_weighted,
+};
+
+/// Bounds the cases kept by a [`SlidingWindow`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WindowSize {
+    /// Keep only the `n` most recently completed cases.
+    Cases(usize),
+    /// Keep the cases that completed within this duration before the most recent one.
+    Time(Duration),
+}
+
+#[derive(Debug, Clone)]
+struct WindowCase {
+    completed_at: DateTime<Utc>,
+    trace: Vec<String>,
+}
+
+/// Dependency statistics over the most recent cases of a (live) stream of completed cases.
+///
+/// Adding a case and evicting the cases that fell out of the window only update the variant
+/// and activity counts, so the dependencies can be re-evaluated at any time on the variants of
+/// the current window without keeping the whole history.
+#[derive(Debug, Clone)]
+pub struct SlidingWindow {
+    size: WindowSize,
+    cases: VecDeque<WindowCase>,
+    variant_frequencies: BTreeMap<Vec<String>, usize>,
+    // number of cases in the window containing the activity
+    activity_frequencies: BTreeMap<String, usize>,
+}
+
+impl SlidingWindow {
+    pub fn new(size: WindowSize) -> Self {
+        SlidingWindow {
+            size,
+            cases: VecDeque::new(),
+            variant_frequencies: BTreeMap::new(),
+            activity_frequencies: BTreeMap::new(),
+        }
+    }
+
+    /// Adds a completed case and evicts the cases that no longer fit into the window.
+    ///
+    /// Cases are expected to arrive in order of completion.
+    ///
+    /// # Returns
+    /// The traces of the evicted cases, oldest first.
+    pub fn push(&mut self, trace: Vec<String>, completed_at: DateTime<Utc>) -> Vec<Vec<String>> {
+        *self.variant_frequencies.entry(trace.clone()).or_insert(0) += 1;
+        for activity in trace.iter().collect::<HashSet<_>>() {
+            *self.activity_frequencies.entry(activity.clone()).or_insert(0) += 1;
+        }
+        self.cases.push_back(WindowCase { completed_at, trace });
+
+        let mut evicted = Vec::new();
+        while self.is_over_capacity(completed_at) {
+            let case = self.cases.pop_front().expect("window is not empty");
+            self.remove(&case.trace);
+            evicted.push(case.trace);
+        }
+        evicted
+    }
+
+    /// Returns the number of cases currently in the window.
+    pub fn len(&self) -> usize {
+        self.cases.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.cases.is_empty()
+    }
+
+    /// Returns the activities occurring in at least one case of the window.
+    pub fn activities(&self) -> BTreeSet<String> {
+        self.activity_frequencies.keys().cloned().collect()
+    }
+
+    /// Returns how many cases of the window contain `activity`.
+    pub fn activity_frequency(&self, activity: &str) -> usize {
+        self.activity_frequencies.get(activity).copied().unwrap_or(0)
+    }
+
+    /// Returns the variants of the window in the `(trace, frequency)` form expected by the
+    /// weighted dependency checks.
+    pub fn weighted_variants(&self) -> Vec<(Vec<&str>, usize)> {
+        self.variant_frequencies
+            .iter()
+            .map(|(variant, &frequency)| {
+                (variant.iter().map(|s| s.as_str()).collect(), frequency)
+            })
+            .collect()
+    }
+
+    /// Evaluates the dependency between two activities on the current window.
+    pub fn dependency(&self, from: &str, to: &str, threshold: f64) -> Dependency {
+        let variants = self.weighted_variants();
+        Dependency::new(
+            from.to_string(),
+            to.to_string(),
+            check_temporal_dependency_weighted(from, to, &variants, threshold),
+            check_existential_dependency_weighted(from, to, &variants, threshold),
+        )
+    }
+
+    /// Evaluates the dependencies between all pairs of distinct activities of the window.
+    pub fn dependencies(&self, threshold: f64) -> Vec<Dependency> {
+        let variants = self.weighted_variants();
+        let activities: Vec<String> = self.activities().into_iter().collect();
+
+        let mut dependencies = Vec::new();
+        for from in &activities {
+            for to in activities.iter().filter(|to| *to != from) {
+                dependencies.push(Dependency::new(
+                    from.clone(),
+                    to.clone(),
+                    check_temporal_dependency_weighted(from, to, &variants, threshold),
+                    check_existential_dependency_weighted(from, to, &variants, threshold),
+                ));
+            }
+        }
+        dependencies
+    }
+
+    fn is_over_capacity(&self, now: DateTime<Utc>) -> bool {
+        match self.size {
+            WindowSize::Cases(n) => self.cases.len() > n,
+            WindowSize::Time(duration) => self
+                .cases
+                .front()
+                .is_some_and(|case| now - case.completed_at > duration),
+        }
+    }
+
+    fn remove(&mut self, trace: &[String]) {
+        decrement(&mut self.variant_frequencies, trace);
+        for activity in trace.iter().collect::<HashSet<_>>() {
+            decrement(&mut self.activity_frequencies, activity.as_str());
+        }
+    }
+}
+
+// Decrements a count, dropping the entry once it reaches zero
+fn decrement<K, Q>(counts: &mut BTreeMap<K, usize>, key: &Q)
+where
+    K: std::borrow::Borrow<Q> + Ord,
+    Q: Ord + ?Sized,
+{
+    if let Some(count) = counts.get_mut(key) {
+        *count -= 1;
+        if *count == 0 {
+            counts.remove(key);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::variant_log::trace;
+    use chrono::TimeZone;
+
+    fn hour(h: u32) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2024, 1, 1, h, 0, 0).unwrap()
+    }
+
+    #[test]
+    fn test_case_window_evicts_oldest() {
+        let mut window = SlidingWindow::new(WindowSize::Cases(2));
+        assert!(window.push(trace(&["A", "B"]), hour(1)).is_empty());
+        assert!(window.push(trace(&["A", "B"]), hour(2)).is_empty());
+        assert!(window.dependency("A", "B", 1.0).temporal_dependency.is_some());
+
+        window.push(trace(&["B", "A"]), hour(3));
+        let evicted = window.push(trace(&["B", "A", "C"]), hour(4));
+        assert_eq!(evicted, [trace(&["A", "B"])]);
+        assert_eq!(window.len(), 2);
+        assert_eq!(window.activity_frequency("A"), 2);
+        assert_eq!(window.activity_frequency("C"), 1);
+
+        let dependency = window.dependency("B", "A", 1.0);
+        assert!(dependency.temporal_dependency.is_some());
+        assert_eq!(window.dependencies(1.0).len(), 6);
+    }
+
+    #[test]
+    fn test_time_window() {
+        let mut window = SlidingWindow::new(WindowSize::Time(Duration::hours(2)));
+        window.push(trace(&["A"]), hour(1));
+        window.push(trace(&["B"]), hour(2));
+        let evicted = window.push(trace(&["C"]), hour(4));
+        assert_eq!(evicted, [trace(&["A"])]);
+        assert_eq!(window.activities().len(), 2);
+        assert_eq!(window.activity_frequency("A"), 0);
+    }
+}
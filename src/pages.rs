@@ -0,0 +1,146 @@
+//! Parts of an analysis result, for clients like dashboards that show a page of the matrix,
+//! the most frequent variants or a single activity pair at a time instead of fetching the
+//! whole result.
+
+use crate::analysis::AnalysisResult;
+use crate::dependency_types::significance::ContingencyTable;
+use crate::dfg::DirectlyFollowsGraph;
+use crate::pair_dependency_from_variant_log_with_options;
+use crate::INSUFFICIENT_DATA;
+
+/// The items `offset..offset + items.len()` of a list of `total` items.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Page<T> {
+    pub offset: usize,
+    pub total: usize,
+    pub items: Vec<T>,
+}
+
+impl<T> Page<T> {
+    /// Takes at most `limit` items from `offset` on.
+    pub fn new(items: impl ExactSizeIterator<Item = T>, offset: usize, limit: usize) -> Self {
+        let total = items.len();
+        Page {
+            offset,
+            total,
+            items: items.skip(offset).take(limit).collect(),
+        }
+    }
+
+    /// Whether there are items after this page.
+    pub fn has_more(&self) -> bool {
+        self.offset + self.items.len() < self.total
+    }
+}
+
+/// Some rows of the dependency matrix, see [`AnalysisResult::matrix_page`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MatrixPage {
+    /// The line with the activities of the columns.
+    pub header: String,
+    /// One line per activity, with its dependencies to the activities of the columns.
+    pub rows: Page<String>,
+}
+
+/// What the analysis knows about one pair of activities.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PairDetails {
+    pub from: String,
+    pub to: String,
+    /// The cell of the pair in the matrix, empty for an activity with itself.
+    pub cell: String,
+    /// Cases with both activities, only one of them or neither.
+    pub presence: ContingencyTable,
+    /// How often `to` directly follows `from`.
+    pub directly_follows: usize,
+}
+
+impl AnalysisResult {
+    /// The rows `offset..offset + limit` of the dependency matrix, in the order of the report.
+    pub fn matrix_page(&self, offset: usize, limit: usize) -> MatrixPage {
+        let mut lines = self.matrix.text.lines();
+        let header = lines.next().unwrap_or_default().to_string();
+        let rows: Vec<String> = lines.map(str::to_string).collect();
+        MatrixPage {
+            header,
+            rows: Page::new(rows.into_iter(), offset, limit),
+        }
+    }
+
+    /// The variants of the analyzed log from the `offset`-th most frequent one on, with their
+    /// frequencies; `variant_page(0, k)` are the top `k` variants.
+    pub fn variant_page(&self, offset: usize, limit: usize) -> Page<(Vec<String>, usize)> {
+        let variants: Vec<(Vec<String>, usize)> = self
+            .log
+            .top_variants(self.log.variant_count())
+            .into_iter()
+            .map(|(variant, frequency)| (variant.to_vec(), frequency))
+            .collect();
+        Page::new(variants.into_iter(), offset, limit)
+    }
+
+    /// The matrix cell, co-occurrence and directly-follows frequency of `from` and `to`,
+    /// computed for this pair only; `None` if one of them isn't an activity of the log.
+    pub fn pair_details(&self, from: &str, to: &str) -> Option<PairDetails> {
+        let activities = self.log.activities();
+        if !activities.contains(from) || !activities.contains(to) {
+            return None;
+        }
+        let options = self.config.matrix_options();
+        let cell = if from == to {
+            String::new()
+        } else {
+            pair_dependency_from_variant_log_with_options(&self.log, &options, from, to)
+                .map_or(INSUFFICIENT_DATA.to_string(), |dependency| {
+                    dependency.to_string()
+                })
+        };
+        let variants = self.log.weighted_variants();
+        let dfg = DirectlyFollowsGraph::from_variant_log(&self.log);
+        Some(PairDetails {
+            from: from.to_string(),
+            to: to.to_string(),
+            cell,
+            presence: ContingencyTable::from_variants(from, to, &variants),
+            directly_follows: dfg.frequency(from, to),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::analysis::{analyze, LogInput};
+    use crate::config::AnalysisConfig;
+
+    #[test]
+    fn test_pages() {
+        let result = analyze(
+            LogInput::Path("./sample-data/exercise2.xes".to_string()),
+            AnalysisConfig::default(),
+        )
+        .unwrap();
+
+        let page = result.matrix_page(1, 2);
+        assert_eq!(page.rows.total, result.matrix.activities);
+        assert_eq!(page.rows.items.len(), 2);
+        assert!(page.rows.has_more());
+        assert!(result.matrix.text.starts_with(&page.header));
+        assert!(result.matrix.text.contains(&page.rows.items[0]));
+        assert!(result
+            .matrix_page(result.matrix.activities, 10)
+            .rows
+            .items
+            .is_empty());
+
+        let variants = result.variant_page(0, 1);
+        assert_eq!(variants.total, result.log.variant_count());
+        assert_eq!(variants.items[0].1, result.log.top_variants(1)[0].1);
+
+        let trace = &variants.items[0].0;
+        let details = result.pair_details(&trace[0], &trace[1]).unwrap();
+        assert!(result.matrix.text.contains(details.cell.trim()));
+        assert!(details.directly_follows > 0);
+        assert!(details.presence.both > 0);
+        assert!(result.pair_details(&trace[0], "unknown").is_none());
+    }
+}
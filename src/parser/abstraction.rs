@@ -0,0 +1,280 @@
+//! Event abstraction for fine-grained logs, e.g. click streams: runs of the same activity are
+//! collapsed into one event, and user-defined sequences of low-level activities are replaced
+//! by one high-level activity, so the dependency analysis sees the steps of the process
+//! instead of every single click.
+
+use std::collections::HashMap;
+
+use process_mining::event_log::event_log_struct::EventLogClassifier;
+use process_mining::event_log::{
+    AttributeValue, Attributes, Event, EventLog, XESEditableAttribute,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::parser::xes_extensions::{XesExtensions, CONCEPT_NAME};
+use crate::parser::{event_label, Classifier, ParseError};
+
+/// Attribute with the number of low-level events a merged event stands for. Events without
+/// it stand for themselves.
+pub const COUNT_KEY: &str = "egypt:count";
+
+/// A sequence of low-level activities that is replaced by one high-level activity.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ActivityPattern {
+    /// The low-level activities, in order. Repetitions of an activity within a match are
+    /// allowed, e.g. `["Click", "Type"]` also matches `Click, Type, Type`.
+    pub activities: Vec<String>,
+    /// Label of the high-level activity.
+    pub activity: String,
+}
+
+/// Which abstractions are applied to the events of every case.
+#[derive(Debug, Clone, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct EventAbstraction {
+    /// Merge consecutive events of the same activity.
+    pub collapse_repetitions: bool,
+    /// Patterns tried in order at every position of a case; the first one that matches wins.
+    pub patterns: Vec<ActivityPattern>,
+}
+
+impl EventAbstraction {
+    pub fn is_active(&self) -> bool {
+        self.collapse_repetitions || !self.patterns.is_empty()
+    }
+}
+
+/// Applies `abstraction` to every trace of `log`, ordered by timestamp, and returns the number
+/// of removed events.
+///
+/// Events are merged per lifecycle transition: of the merged events, the first one of every
+/// transition is kept (so the start and completion of an activity stay paired) and carries
+/// the number of events it stands for in [`COUNT_KEY`]. Events matching a pattern are renamed
+/// via `concept:name`, so patterns only change the labels of classifiers using it.
+pub fn abstract_events(
+    log: &mut EventLog,
+    classifier: &Classifier,
+    abstraction: &EventAbstraction,
+) -> Result<usize, ParseError> {
+    if !abstraction.is_active() {
+        return Ok(0);
+    }
+    let classifier = classifier.resolve(log)?;
+    let mut removed = 0;
+    for trace in &mut log.traces {
+        trace.events.sort_by_key(|event| event.timestamp());
+        let events = std::mem::take(&mut trace.events);
+        let before = events.len();
+        trace.events = abstract_trace(events, &classifier, &log.global_event_attrs, abstraction);
+        removed += before - trace.events.len();
+    }
+    Ok(removed)
+}
+
+fn abstract_trace(
+    events: Vec<Event>,
+    classifier: &EventLogClassifier,
+    global_event_attrs: &Option<Attributes>,
+    abstraction: &EventAbstraction,
+) -> Vec<Event> {
+    let labels: Vec<Option<String>> = events
+        .iter()
+        .map(|event| event_label(event, classifier, global_event_attrs))
+        .collect();
+    // maximal runs of events with the same label, as ranges of event indices
+    let mut runs: Vec<(usize, usize)> = Vec::new();
+    for (idx, label) in labels.iter().enumerate() {
+        match runs.last_mut() {
+            Some((start, end)) if label.is_some() && labels[*start] == *label => *end = idx + 1,
+            _ => runs.push((idx, idx + 1)),
+        }
+    }
+
+    // groups of events that are merged, with the label of a matched pattern
+    let mut groups: Vec<(usize, usize, Option<&str>)> = Vec::new();
+    let mut run = 0;
+    while run < runs.len() {
+        let matched = abstraction.patterns.iter().find(|pattern| {
+            !pattern.activities.is_empty()
+                && runs[run..].len() >= pattern.activities.len()
+                && pattern
+                    .activities
+                    .iter()
+                    .zip(&runs[run..])
+                    .all(|(activity, &(start, _))| labels[start].as_ref() == Some(activity))
+        });
+        match matched {
+            Some(pattern) => {
+                let last = run + pattern.activities.len() - 1;
+                groups.push((runs[run].0, runs[last].1, Some(&pattern.activity)));
+                run = last + 1;
+            }
+            None if abstraction.collapse_repetitions => {
+                groups.push((runs[run].0, runs[run].1, None));
+                run += 1;
+            }
+            None => {
+                let (start, end) = runs[run];
+                groups.extend((start..end).map(|idx| (idx, idx + 1, None)));
+                run += 1;
+            }
+        }
+    }
+
+    let mut events: Vec<Option<Event>> = events.into_iter().map(Some).collect();
+    let mut abstracted = Vec::new();
+    for (start, end, activity) in groups {
+        // the kept event of every lifecycle transition, with the number of events it stands for
+        let mut kept: Vec<(Event, i64)> = Vec::new();
+        let mut transitions: HashMap<Option<String>, usize> = HashMap::new();
+        for event in events[start..end].iter_mut().filter_map(Option::take) {
+            let count = event_count(&event);
+            let transition = event.lifecycle_transition().map(str::to_lowercase);
+            match transitions.get(&transition) {
+                Some(&idx) => kept[idx].1 += count,
+                None => {
+                    transitions.insert(transition, kept.len());
+                    kept.push((event, count));
+                }
+            }
+        }
+        for (mut event, count) in kept {
+            if let Some(activity) = activity {
+                event.attributes.remove_with_key(CONCEPT_NAME);
+                event.attributes.add_to_attributes(
+                    CONCEPT_NAME.to_string(),
+                    AttributeValue::String(activity.to_string()),
+                );
+            }
+            if count > 1 {
+                event.attributes.remove_with_key(COUNT_KEY);
+                event
+                    .attributes
+                    .add_to_attributes(COUNT_KEY.to_string(), AttributeValue::Int(count));
+            }
+            abstracted.push(event);
+        }
+    }
+    // merging by transition may leave a group's events out of timestamp order
+    abstracted.sort_by_key(|event| event.timestamp());
+    abstracted
+}
+
+/// Number of low-level events `event` stands for, see [`COUNT_KEY`].
+pub fn event_count(event: &Event) -> i64 {
+    event
+        .attribute_value(COUNT_KEY)
+        .and_then(AttributeValue::try_as_int)
+        .copied()
+        .unwrap_or(1)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::{parse_into_event_log_with_config, ImportConfig};
+
+    fn log(activities: &[(&str, &str)]) -> EventLog {
+        let events: String = activities
+            .iter()
+            .enumerate()
+            .map(|(minute, (activity, transition))| {
+                format!(
+                    r#"<event>
+                        <string key="concept:name" value="{}"/>
+                        <string key="lifecycle:transition" value="{}"/>
+                        <date key="time:timestamp" value="2024-01-01T10:{:02}:00+00:00"/>
+                    </event>"#,
+                    activity, transition, minute
+                )
+            })
+            .collect();
+        let content = format!("<log><trace>{}</trace></log>", events);
+        parse_into_event_log_with_config(None, Some(&content), &ImportConfig::default()).unwrap()
+    }
+
+    fn labels(log: &EventLog) -> Vec<(String, i64)> {
+        log.traces[0]
+            .events
+            .iter()
+            .map(|event| {
+                (
+                    event.concept_name().unwrap_or_default().to_string(),
+                    event_count(event),
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_collapse_repetitions() {
+        let mut log = log(&[
+            ("Click", "complete"),
+            ("Click", "complete"),
+            ("Click", "complete"),
+            ("Type", "start"),
+            ("Type", "complete"),
+            ("Type", "start"),
+            ("Type", "complete"),
+            ("Click", "complete"),
+        ]);
+        let abstraction = EventAbstraction {
+            collapse_repetitions: true,
+            patterns: Vec::new(),
+        };
+        assert_eq!(
+            abstract_events(&mut log, &Classifier::ConceptName, &abstraction).unwrap(),
+            4
+        );
+        assert_eq!(
+            labels(&log),
+            [
+                ("Click".to_string(), 3),
+                ("Type".to_string(), 2),
+                ("Type".to_string(), 2),
+                ("Click".to_string(), 1)
+            ]
+        );
+        assert_eq!(
+            log.traces[0].events[1].lifecycle_transition(),
+            Some("start")
+        );
+    }
+
+    #[test]
+    fn test_patterns() {
+        let mut log = log(&[
+            ("Open form", "complete"),
+            ("Type", "complete"),
+            ("Type", "complete"),
+            ("Submit", "complete"),
+            ("Type", "complete"),
+            ("Type", "complete"),
+        ]);
+        let abstraction = EventAbstraction {
+            collapse_repetitions: false,
+            patterns: vec![
+                ActivityPattern {
+                    activities: vec!["Type".to_string(), "Submit".to_string()],
+                    activity: "Fill form".to_string(),
+                },
+                ActivityPattern {
+                    activities: Vec::new(),
+                    activity: "Never".to_string(),
+                },
+            ],
+        };
+        abstract_events(&mut log, &Classifier::ConceptName, &abstraction).unwrap();
+        // repetitions outside of a match are kept
+        assert_eq!(
+            labels(&log),
+            [
+                ("Open form".to_string(), 1),
+                ("Fill form".to_string(), 3),
+                ("Type".to_string(), 1),
+                ("Type".to_string(), 1)
+            ]
+        );
+    }
+}
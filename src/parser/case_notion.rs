@@ -0,0 +1,283 @@
+use std::collections::HashMap;
+
+use chrono::Duration;
+use process_mining::event_log::{Attribute, AttributeValue, Event, EventLog, Trace};
+
+use crate::parser::xes_extensions::{XesExtensions, CONCEPT_NAME};
+
+/// Separates the attribute values of a composite case id, e.g. `order-1|item-3`.
+pub const CASE_ID_SEPARATOR: &str = "|";
+
+/// Re-groups all events of `log` into new traces, one per distinct combination of values of
+/// the attributes `keys`.
+///
+/// Each value is looked up on the event first and on its original trace second, so a trace
+/// level attribute (e.g. an order id) can be combined with an event level one (e.g. an item
+/// id). Events without a value for one of the keys are dropped. The new traces are ordered by
+/// first occurrence and named with their (composite) case id via `concept:name`; the events
+/// keep their original order, which is re-sorted by timestamp when the traces are flattened.
+pub fn recase(mut log: EventLog, keys: &[String]) -> EventLog {
+    log.traces = group_by_keys(std::mem::take(&mut log.traces), keys)
+        .into_iter()
+        .map(|(case_id, events)| named_trace(case_id, events))
+        .collect();
+    log
+}
+
+/// Correlates events of logs without (usable) case ids by the time between them.
+///
+/// The events are first grouped like in [`recase`] (all events form a single stream if `keys`
+/// is empty), then every group is ordered by timestamp and split into a new case whenever
+/// two consecutive events are more than `max_gap` apart. Events without a timestamp are
+/// dropped. The cases are named `<group>#<n>`, or `case_<n>` if `keys` is empty.
+pub fn split_by_time_gap(mut log: EventLog, max_gap: Duration, keys: &[String]) -> EventLog {
+    let mut traces = Vec::new();
+
+    for (group, events) in group_by_keys(std::mem::take(&mut log.traces), keys) {
+        let mut events: Vec<_> = events
+            .into_iter()
+            .filter_map(|event| event.timestamp().map(|date| (date, event)))
+            .collect();
+        events.sort_by_key(|(date, _)| *date);
+
+        let mut cases: Vec<Vec<Event>> = Vec::new();
+        let mut last_date = None;
+        for (date, event) in events {
+            match cases.last_mut() {
+                Some(case) if last_date.is_some_and(|last| date - last <= max_gap) => {
+                    case.push(event)
+                }
+                _ => cases.push(vec![event]),
+            }
+            last_date = Some(date);
+        }
+
+        for (case_idx, events) in cases.into_iter().enumerate() {
+            let case_id = if keys.is_empty() {
+                format!("case_{}", traces.len())
+            } else {
+                format!("{}#{}", group, case_idx)
+            };
+            traces.push(named_trace(case_id, events));
+        }
+    }
+
+    log.traces = traces;
+    log
+}
+
+/// Splits every trace of `log` into sessions: ordered by timestamp, a new session starts
+/// whenever two consecutive events are more than `max_gap` apart. Events without a timestamp
+/// are dropped. The sessions keep the attributes of their trace and are named
+/// `<case>#<n>`, counting from 0, with cases without name called `case_<index>`.
+pub fn split_into_sessions(mut log: EventLog, max_gap: Duration) -> EventLog {
+    let mut sessions = Vec::new();
+    for (case_idx, trace) in std::mem::take(&mut log.traces).into_iter().enumerate() {
+        let case_id = trace
+            .concept_name()
+            .map(|name| name.to_string())
+            .unwrap_or_else(|| format!("case_{}", case_idx));
+        let mut events: Vec<_> = trace
+            .events
+            .into_iter()
+            .filter_map(|event| event.timestamp().map(|date| (date, event)))
+            .collect();
+        events.sort_by_key(|(date, _)| *date);
+
+        let mut trace_sessions: Vec<Vec<Event>> = Vec::new();
+        let mut last_date = None;
+        for (date, event) in events {
+            match trace_sessions.last_mut() {
+                Some(session) if last_date.is_some_and(|last| date - last <= max_gap) => {
+                    session.push(event)
+                }
+                _ => trace_sessions.push(vec![event]),
+            }
+            last_date = Some(date);
+        }
+
+        for (session_idx, events) in trace_sessions.into_iter().enumerate() {
+            let mut attributes = trace.attributes.clone();
+            attributes.retain(|attribute| attribute.key != CONCEPT_NAME);
+            attributes.push(Attribute::new(
+                CONCEPT_NAME.to_string(),
+                AttributeValue::String(format!("{}#{}", case_id, session_idx)),
+            ));
+            sessions.push(Trace { attributes, events });
+        }
+    }
+
+    log.traces = sessions;
+    log
+}
+
+fn named_trace(case_id: String, events: Vec<Event>) -> Trace {
+    Trace {
+        attributes: vec![Attribute::new(
+            CONCEPT_NAME.to_string(),
+            AttributeValue::String(case_id),
+        )],
+        events,
+    }
+}
+
+// Groups all events by their (composite) key value, in order of first occurrence
+fn group_by_keys(traces: Vec<Trace>, keys: &[String]) -> Vec<(String, Vec<Event>)> {
+    let mut groups: Vec<(String, Vec<Event>)> = Vec::new();
+    let mut group_lookup: HashMap<String, usize> = HashMap::new();
+
+    for trace in traces {
+        let trace_attributes = trace.attributes;
+        for event in trace.events {
+            let values: Option<Vec<String>> = keys
+                .iter()
+                .map(|key| {
+                    event
+                        .text_attribute(key)
+                        .or_else(|| trace_attributes.text_attribute(key))
+                })
+                .collect();
+            let Some(values) = values else {
+                continue;
+            };
+
+            let case_id = values.join(CASE_ID_SEPARATOR);
+            let group_idx = *group_lookup.entry(case_id.clone()).or_insert_with(|| {
+                groups.push((case_id, Vec::new()));
+                groups.len() - 1
+            });
+            groups[group_idx].1.push(event);
+        }
+    }
+
+    groups
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::xes_extensions::TIME_TIMESTAMP;
+    use chrono::{TimeZone, Utc};
+
+    fn string_attribute(key: &str, value: &str) -> Attribute {
+        Attribute::new(key.to_string(), AttributeValue::String(value.to_string()))
+    }
+
+    fn event(activity: &str, item: Option<&str>) -> Event {
+        let mut attributes = vec![string_attribute(CONCEPT_NAME, activity)];
+        attributes.extend(item.map(|item| string_attribute("item", item)));
+        Event { attributes }
+    }
+
+    #[test]
+    fn test_composite_case_notion() {
+        let log = EventLog {
+            attributes: Vec::new(),
+            traces: vec![
+                Trace {
+                    attributes: vec![string_attribute("order", "o1")],
+                    events: vec![
+                        event("A", Some("i1")),
+                        event("A", Some("i2")),
+                        event("B", None),
+                        event("C", Some("i1")),
+                    ],
+                },
+                Trace {
+                    attributes: vec![string_attribute("order", "o2")],
+                    events: vec![event("A", Some("i1"))],
+                },
+            ],
+            extensions: None,
+            classifiers: None,
+            global_trace_attrs: None,
+            global_event_attrs: None,
+        };
+
+        let recased = recase(log, &["order".to_string(), "item".to_string()]);
+        let case_ids: Vec<&str> = recased
+            .traces
+            .iter()
+            .map(|trace| trace.concept_name().unwrap())
+            .collect();
+        assert_eq!(case_ids, ["o1|i1", "o1|i2", "o2|i1"]);
+        assert_eq!(recased.traces[0].events.len(), 2);
+        assert_eq!(recased.traces[0].events[1].concept_name(), Some("C"));
+    }
+
+    #[test]
+    fn test_split_by_time_gap() {
+        let at = |activity: &str, hour: u32| Event {
+            attributes: vec![
+                string_attribute(CONCEPT_NAME, activity),
+                Attribute::new(
+                    TIME_TIMESTAMP.to_string(),
+                    AttributeValue::Date(Utc.with_ymd_and_hms(2024, 1, 1, hour, 0, 0).unwrap()),
+                ),
+            ],
+        };
+        let log = EventLog {
+            attributes: Vec::new(),
+            traces: vec![Trace {
+                attributes: Vec::new(),
+                events: vec![at("A", 1), at("B", 2), at("A", 8), at("C", 3), at("B", 9)],
+            }],
+            extensions: None,
+            classifiers: None,
+            global_trace_attrs: None,
+            global_event_attrs: None,
+        };
+
+        let split = split_by_time_gap(log, Duration::hours(2), &[]);
+        let traces: Vec<Vec<&str>> = split
+            .traces
+            .iter()
+            .map(|trace| trace.events.iter().map(|e| e.concept_name().unwrap()).collect())
+            .collect();
+        assert_eq!(traces, [vec!["A", "B", "C"], vec!["A", "B"]]);
+        assert_eq!(split.traces[1].concept_name(), Some("case_1"));
+    }
+
+    #[test]
+    fn test_split_into_sessions() {
+        let at = |activity: &str, minute: u32| Event {
+            attributes: vec![
+                string_attribute(CONCEPT_NAME, activity),
+                Attribute::new(
+                    TIME_TIMESTAMP.to_string(),
+                    AttributeValue::Date(Utc.with_ymd_and_hms(2024, 1, 1, 10, minute, 0).unwrap()),
+                ),
+            ],
+        };
+        let log = EventLog {
+            attributes: Vec::new(),
+            traces: vec![
+                Trace {
+                    attributes: vec![
+                        string_attribute(CONCEPT_NAME, "user-1"),
+                        string_attribute("region", "EU"),
+                    ],
+                    events: vec![at("Log in", 0), at("Search", 5), at("Log in", 50), at("Buy", 55)],
+                },
+                Trace {
+                    attributes: Vec::new(),
+                    events: vec![at("Log in", 0), at("Search", 1)],
+                },
+            ],
+            extensions: None,
+            classifiers: None,
+            global_trace_attrs: None,
+            global_event_attrs: None,
+        };
+
+        let sessions = split_into_sessions(log, Duration::minutes(30));
+        let case_ids: Vec<&str> = sessions
+            .traces
+            .iter()
+            .map(|trace| trace.concept_name().unwrap())
+            .collect();
+        assert_eq!(case_ids, ["user-1#0", "user-1#1", "case_1#0"]);
+        assert_eq!(sessions.traces[1].events[1].concept_name(), Some("Buy"));
+        assert_eq!(sessions.traces[1].string_attribute("region"), Some("EU"));
+    }
+}
@@ -0,0 +1,147 @@
+use chrono::Duration;
+use process_mining::event_log::event_log_struct::EventLogClassifier;
+use process_mining::event_log::EventLog;
+use process_mining::XESImportOptions;
+use serde::{Deserialize, Serialize};
+
+use crate::parser::abstraction::EventAbstraction;
+use crate::parser::error::{ParseError, ParseErrorKind};
+use crate::parser::time_window::TimeWindow;
+use crate::parser::timezone::TimezoneOptions;
+use crate::parser::xes_extensions::CONCEPT_NAME;
+
+/// Selects how the activity label of an event is derived.
+#[derive(Debug, Clone, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Classifier {
+    /// Use the bare `concept:name` attribute.
+    #[default]
+    ConceptName,
+    /// Use the first classifier declared in the log (falling back to `concept:name` if the log
+    /// doesn't declare any).
+    LogDefault,
+    /// Use the classifier with the given name, as declared in the log.
+    Named(String),
+    /// Combine the values of the given attribute keys, like a classifier declared in the log would.
+    Keys(Vec<String>),
+}
+
+impl Classifier {
+    /// Resolves the choice against the classifiers declared by `log`.
+    ///
+    /// Fails if a [`Classifier::Named`] classifier is not declared in the log.
+    pub fn resolve(&self, log: &EventLog) -> Result<EventLogClassifier, ParseError> {
+        let concept_name = || EventLogClassifier {
+            name: "Event Name".to_string(),
+            keys: vec![CONCEPT_NAME.to_string()],
+        };
+
+        match self {
+            Classifier::ConceptName => Ok(concept_name()),
+            Classifier::LogDefault => Ok(log
+                .classifiers
+                .as_ref()
+                .and_then(|classifiers| classifiers.first().cloned())
+                .unwrap_or_else(concept_name)),
+            Classifier::Named(name) => log.get_classifier_by_name(name.as_str()).ok_or_else(|| {
+                ParseError::new(ParseErrorKind::UnknownClassifier {
+                    name: name.clone(),
+                    available: log
+                        .classifiers
+                        .iter()
+                        .flatten()
+                        .map(|classifier| classifier.name.clone())
+                        .collect(),
+                })
+            }),
+            Classifier::Keys(keys) => Ok(EventLogClassifier {
+                name: keys.join(" "),
+                keys: keys.clone(),
+            }),
+        }
+    }
+}
+
+/// Selects the events of a trace by their `lifecycle:transition`. A trace without any event
+/// of the selected transitions keeps all of its events, so logs without lifecycle information
+/// are analyzed as they are; the import diagnostics name these cases, see
+/// [`crate::parser::unfiltered_cases`].
+#[derive(Debug, Clone, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LifecyclePolicy {
+    /// Only `complete` events.
+    #[default]
+    CompleteOnly,
+    /// Only `start` events, e.g. to analyze when work on the activities begins.
+    StartOnly,
+    /// Every event, e.g. to see start and complete events as separate steps.
+    All,
+    /// Only events with one of the given transitions (ignoring case).
+    Custom(Vec<String>),
+}
+
+impl LifecyclePolicy {
+    /// The selected transitions, empty if all events are selected.
+    pub fn transitions(&self) -> Vec<&str> {
+        match self {
+            LifecyclePolicy::CompleteOnly => vec!["complete"],
+            LifecyclePolicy::StartOnly => vec!["start"],
+            LifecyclePolicy::All => Vec::new(),
+            LifecyclePolicy::Custom(transitions) => {
+                transitions.iter().map(String::as_str).collect()
+            }
+        }
+    }
+}
+
+/// Selects what groups events into cases.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub enum CaseNotion {
+    /// Keep the traces as they are in the log.
+    #[default]
+    Trace,
+    /// Re-group all events by the values of the given attribute keys. An event attribute takes
+    /// precedence over a trace attribute with the same key; events that have no value for one
+    /// of the keys are dropped. See [`crate::parser::case_notion::recase`].
+    Attributes(Vec<String>),
+    /// Correlate events without case ids by time: events are grouped by the values of `keys`
+    /// (all events form one stream if `keys` is empty), ordered by timestamp, and a new case
+    /// starts whenever two consecutive events are more than `max_gap` apart. See
+    /// [`crate::parser::case_notion::split_by_time_gap`].
+    TimeGap { max_gap: Duration, keys: Vec<String> },
+}
+
+/// Options controlling how an event log is turned into traces.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct ImportConfig {
+    pub classifier: Classifier,
+    pub case_notion: CaseNotion,
+    /// Which events are analyzed by their lifecycle transition.
+    pub lifecycle: LifecyclePolicy,
+    /// Attribute key of the event timestamps if it isn't `time:timestamp`, see
+    /// [`crate::parser::timezone::use_timestamp_key`].
+    pub timestamp_key: Option<String>,
+    /// Attribute key of the event resources if it isn't `org:resource`, e.g. `User`.
+    pub resource_key: Option<String>,
+    /// Split every case into sessions at gaps between events of more than this, see
+    /// [`crate::parser::case_notion::split_into_sessions`].
+    pub session_gap: Option<Duration>,
+    /// Keep only the events or the cases of a period, see
+    /// [`crate::parser::time_window::apply_time_window`].
+    pub time_window: Option<TimeWindow>,
+    /// Options passed through to the underlying XES importer (date format, attribute
+    /// allowlists, ...).
+    pub xes_options: XESImportOptions,
+    /// Normalize non-standard XES serializations (namespace prefixed elements, whitespace
+    /// around keys and values) before parsing, see [`crate::parser::lenient::normalize_xes`].
+    pub lenient: bool,
+    /// Time zone of timestamps without UTC offset and of exported timestamps, see
+    /// [`crate::parser::timezone`].
+    pub timezone: TimezoneOptions,
+    /// Remove exact duplicate events before the analysis, see
+    /// [`crate::parser::duplicates::remove_duplicate_events`].
+    pub drop_duplicate_events: bool,
+    /// Collapse repetitions and replace patterns of low-level events, see
+    /// [`crate::parser::abstraction::abstract_events`].
+    pub abstraction: EventAbstraction,
+}
@@ -0,0 +1,169 @@
+use crate::i18n::{Language, Message};
+use crate::parser::duplicates::count_duplicate_events;
+use crate::parser::timezone::TimestampOffsets;
+use process_mining::event_log::EventLog;
+
+use crate::parser::{import_log, unfiltered_cases, ImportConfig, ParseError};
+
+/// Number of cases named in the warning about cases the lifecycle filter kept as they are.
+const MAX_SHOWN_CASES: usize = 10;
+
+/// Data quality problems of a log that don't prevent the analysis, but may distort it.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct ImportDiagnostics {
+    pub timestamp_offsets: TimestampOffsets,
+    /// Exact duplicate events, see [`crate::parser::duplicates::count_duplicate_events`].
+    pub duplicate_events: usize,
+    /// Cases without any event of the selected lifecycle transitions, which keep all of their
+    /// events, see [`crate::parser::unfiltered_cases`].
+    pub unfiltered_cases: Vec<String>,
+}
+
+impl ImportDiagnostics {
+    /// Examines an XES document as it is imported with `config`, but before duplicate events
+    /// are dropped.
+    pub fn of(content: &str, config: &ImportConfig) -> Result<Self, ParseError> {
+        let log = import_log(
+            None,
+            Some(content),
+            &ImportConfig {
+                drop_duplicate_events: false,
+                ..config.clone()
+            },
+        )?;
+        Self::assess(content, &log, config)
+    }
+
+    /// Examines an XES document and the log imported from it with `config` before duplicate
+    /// events were dropped, see [`crate::parser::ImportedLog::with_duplicates`].
+    pub fn assess(content: &str, log: &EventLog, config: &ImportConfig) -> Result<Self, ParseError> {
+        Ok(ImportDiagnostics {
+            timestamp_offsets: TimestampOffsets::scan(content, &config.timezone),
+            duplicate_events: count_duplicate_events(log, &config.classifier)?,
+            unfiltered_cases: unfiltered_cases(log, &config.lifecycle),
+        })
+    }
+
+    /// One warning per problem found, empty if there are none.
+    pub fn messages(&self, config: &ImportConfig, language: Language) -> Vec<String> {
+        let mut messages = Vec::new();
+        if self.timestamp_offsets.is_mixed() {
+            messages.push(
+                Message::MixedTimestampOffsets
+                    .format(language, &[&self.timestamp_offsets.to_string()]),
+            );
+        }
+        if self.duplicate_events > 0 {
+            let message = if config.drop_duplicate_events {
+                Message::DuplicateEventsDropped
+            } else {
+                Message::DuplicateEvents
+            };
+            messages.push(message.format(language, &[&self.duplicate_events.to_string()]));
+        }
+        if !self.unfiltered_cases.is_empty() {
+            let shown: Vec<&str> = self
+                .unfiltered_cases
+                .iter()
+                .take(MAX_SHOWN_CASES)
+                .map(String::as_str)
+                .collect();
+            let mut cases = shown.join(", ");
+            if self.unfiltered_cases.len() > MAX_SHOWN_CASES {
+                cases.push_str(", …");
+            }
+            messages.push(
+                Message::UnfilteredLifecycleCases
+                    .format(language, &[&self.unfiltered_cases.len().to_string(), &cases]),
+            );
+        }
+        messages
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::ImportedLog;
+
+    #[test]
+    fn test_diagnostics() {
+        let content = r#"<log>
+            <trace>
+                <event>
+                    <string key="concept:name" value="A"/>
+                    <date key="time:timestamp" value="2024-01-01T10:00:00"/>
+                </event>
+                <event>
+                    <string key="concept:name" value="A"/>
+                    <date key="time:timestamp" value="2024-01-01T10:00:00"/>
+                </event>
+                <event>
+                    <string key="concept:name" value="B"/>
+                    <date key="time:timestamp" value="2024-01-01T11:00:00+01:00"/>
+                </event>
+            </trace>
+        </log>"#;
+        let config = ImportConfig {
+            drop_duplicate_events: true,
+            ..Default::default()
+        };
+        let diagnostics = ImportDiagnostics::of(content, &config).unwrap();
+
+        assert_eq!(diagnostics.duplicate_events, 1);
+        assert!(diagnostics.timestamp_offsets.is_mixed());
+        let messages = diagnostics.messages(&config, Language::English);
+        assert_eq!(messages.len(), 2);
+        assert!(messages[1].starts_with("1 duplicate"));
+        assert_eq!(
+            ImportDiagnostics::default().messages(&config, Language::English),
+            Vec::<String>::new()
+        );
+
+        let imported = ImportedLog::import(None, Some(content), &config).unwrap();
+        assert_eq!(imported.log.traces[0].events.len(), 2);
+        assert_eq!(imported.with_duplicates().traces[0].events.len(), 3);
+        let assessed = ImportDiagnostics::assess(content, imported.with_duplicates(), &config);
+        assert_eq!(assessed.unwrap(), diagnostics);
+    }
+
+    #[test]
+    fn test_unfiltered_lifecycle_cases() {
+        let content = r#"<log>
+            <trace>
+                <string key="concept:name" value="filtered"/>
+                <event>
+                    <string key="concept:name" value="A"/>
+                    <string key="lifecycle:transition" value="start"/>
+                    <date key="time:timestamp" value="2024-01-01T10:00:00+00:00"/>
+                </event>
+                <event>
+                    <string key="concept:name" value="A"/>
+                    <string key="lifecycle:transition" value="complete"/>
+                    <date key="time:timestamp" value="2024-01-01T11:00:00+00:00"/>
+                </event>
+            </trace>
+            <trace>
+                <string key="concept:name" value="started only"/>
+                <event>
+                    <string key="concept:name" value="A"/>
+                    <string key="lifecycle:transition" value="start"/>
+                    <date key="time:timestamp" value="2024-01-01T10:00:00+00:00"/>
+                </event>
+            </trace>
+        </log>"#;
+        let config = ImportConfig::default();
+        let diagnostics = ImportDiagnostics::of(content, &config).unwrap();
+        assert_eq!(diagnostics.unfiltered_cases, ["started only"]);
+        let messages = diagnostics.messages(&config, Language::English);
+        assert_eq!(messages.len(), 1);
+        assert!(messages[0].starts_with("1 cases"));
+        assert!(messages[0].ends_with(": started only."));
+
+        let all = ImportConfig {
+            lifecycle: crate::parser::LifecyclePolicy::All,
+            ..Default::default()
+        };
+        assert!(ImportDiagnostics::of(content, &all).unwrap().unfiltered_cases.is_empty());
+    }
+}
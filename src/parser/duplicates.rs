@@ -0,0 +1,121 @@
+use std::collections::HashSet;
+
+use process_mining::event_log::event_log_struct::EventLogClassifier;
+use process_mining::event_log::{Attributes, EventLog, Trace};
+
+use crate::parser::xes_extensions::XesExtensions;
+use crate::parser::{event_label, Classifier, ParseError};
+
+// For every event of the trace, whether it repeats an earlier event with the same label,
+// lifecycle transition and timestamp
+fn duplicate_flags(
+    trace: &Trace,
+    classifier: &EventLogClassifier,
+    global_event_attrs: &Option<Attributes>,
+) -> Vec<bool> {
+    let mut seen = HashSet::new();
+    trace
+        .events
+        .iter()
+        .map(|event| {
+            let key = (
+                event_label(event, classifier, global_event_attrs),
+                event
+                    .lifecycle_transition()
+                    .map(|transition| transition.to_lowercase()),
+                event.timestamp(),
+            );
+            // events without label or timestamp can't be told apart reliably
+            key.0.is_some() && key.2.is_some() && !seen.insert(key)
+        })
+        .collect()
+}
+
+/// Counts the exact duplicates in the log: events of the same case with the same activity,
+/// lifecycle transition and timestamp as an earlier event, e.g. from retries of the source
+/// system.
+pub fn count_duplicate_events(
+    log: &EventLog,
+    classifier: &Classifier,
+) -> Result<usize, ParseError> {
+    let classifier = classifier.resolve(log)?;
+    Ok(log
+        .traces
+        .iter()
+        .flat_map(|trace| duplicate_flags(trace, &classifier, &log.global_event_attrs))
+        .filter(|&duplicate| duplicate)
+        .count())
+}
+
+/// Removes the exact duplicates (see [`count_duplicate_events`]) and returns their number.
+pub fn remove_duplicate_events(
+    log: &mut EventLog,
+    classifier: &Classifier,
+) -> Result<usize, ParseError> {
+    let classifier = classifier.resolve(log)?;
+    let mut removed = 0;
+    for trace in &mut log.traces {
+        let mut flags = duplicate_flags(trace, &classifier, &log.global_event_attrs).into_iter();
+        let before = trace.events.len();
+        trace.events.retain(|_| !flags.next().unwrap_or(false));
+        removed += before - trace.events.len();
+    }
+    Ok(removed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::{parse_into_event_log_with_config, ImportConfig};
+
+    const CONTENT: &str = r#"<log>
+        <trace>
+            <event>
+                <string key="concept:name" value="A"/>
+                <date key="time:timestamp" value="2024-01-01T10:00:00+00:00"/>
+            </event>
+            <event>
+                <string key="concept:name" value="A"/>
+                <date key="time:timestamp" value="2024-01-01T10:00:00+00:00"/>
+            </event>
+            <event>
+                <string key="concept:name" value="A"/>
+                <string key="lifecycle:transition" value="start"/>
+                <date key="time:timestamp" value="2024-01-01T10:00:00+00:00"/>
+            </event>
+            <event>
+                <string key="concept:name" value="B"/>
+                <date key="time:timestamp" value="2024-01-01T10:00:00+00:00"/>
+            </event>
+        </trace>
+        <trace>
+            <event>
+                <string key="concept:name" value="A"/>
+                <date key="time:timestamp" value="2024-01-01T10:00:00+00:00"/>
+            </event>
+        </trace>
+    </log>"#;
+
+    #[test]
+    fn test_duplicate_events() {
+        let mut log =
+            parse_into_event_log_with_config(None, Some(CONTENT), &ImportConfig::default())
+                .unwrap();
+        assert_eq!(
+            count_duplicate_events(&log, &Classifier::ConceptName).unwrap(),
+            1
+        );
+        assert_eq!(log.traces[0].events.len(), 4);
+
+        assert_eq!(
+            remove_duplicate_events(&mut log, &Classifier::ConceptName).unwrap(),
+            1
+        );
+        assert_eq!(log.traces[0].events.len(), 3);
+        assert_eq!(log.traces[1].events.len(), 1);
+        assert_eq!(
+            count_duplicate_events(&log, &Classifier::ConceptName).unwrap(),
+            0
+        );
+    }
+}
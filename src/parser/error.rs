@@ -0,0 +1,227 @@
+use process_mining::event_log::import_xes::XESParseError;
+use process_mining::petri_net::import_pnml::PNMLParseError;
+use quick_xml::events::Event as XmlEvent;
+use quick_xml::Reader;
+
+/// A location in the parsed document.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Position {
+    pub byte: usize,
+    /// 1-based line number
+    pub line: usize,
+    /// 1-based column number (in characters)
+    pub column: usize,
+}
+
+impl Position {
+    /// Computes line and column of a byte offset into `content`.
+    pub fn from_byte_offset(content: &str, byte: usize) -> Self {
+        let byte = byte.min(content.len());
+        let before = content.get(..byte).unwrap_or(content);
+        let line = before.matches('\n').count() + 1;
+        let line_start = before.rfind('\n').map_or(0, |idx| idx + 1);
+        let column = before[line_start..].chars().count() + 1;
+        Position { byte, line, column }
+    }
+}
+
+#[derive(Debug)]
+pub enum ParseErrorKind {
+    Xes(XESParseError),
+    UnknownClassifier { name: String, available: Vec<String> },
+    Pnml(PNMLParseError),
+    InvalidConstraint(String),
+    Ocel(String),
+}
+
+/// An error that occurred while importing an event log, together with the context needed to
+/// show the user a helpful message.
+#[derive(Debug)]
+pub struct ParseError {
+    pub kind: Box<ParseErrorKind>,
+    pub file: Option<String>,
+    pub position: Option<Position>,
+    pub suggestion: Option<String>,
+}
+
+impl ParseError {
+    pub fn new(kind: ParseErrorKind) -> Self {
+        ParseError {
+            kind: Box::new(kind),
+            file: None,
+            position: None,
+            suggestion: None,
+        }
+    }
+
+    /// Wraps an error of the XES importer, using the raw document (if available) to locate
+    /// XML syntax errors and to guess what went wrong.
+    pub fn from_xes(error: XESParseError, content: Option<&[u8]>) -> Self {
+        let position = match (&error, content) {
+            (XESParseError::XMLParsingError(_), Some(content)) => {
+                std::str::from_utf8(content).ok().and_then(locate_xml_error)
+            }
+            _ => None,
+        };
+        let suggestion = content.and_then(|content| suggest(&error, content));
+
+        ParseError {
+            kind: Box::new(ParseErrorKind::Xes(error)),
+            file: None,
+            position,
+            suggestion,
+        }
+    }
+
+    pub fn with_file(mut self, file: &str) -> Self {
+        self.file = Some(file.to_string());
+        self
+    }
+
+    /// A short, human-readable description of the problem (without file, position or hints).
+    pub fn message(&self) -> String {
+        match self.kind.as_ref() {
+            ParseErrorKind::Xes(error) => describe(error),
+            ParseErrorKind::UnknownClassifier { name, available } => format!(
+                "the log does not declare a classifier named \"{}\" (available: {})",
+                name,
+                if available.is_empty() {
+                    "none".to_string()
+                } else {
+                    available.join(", ")
+                }
+            ),
+            ParseErrorKind::Pnml(error) => format!("the document is not a valid PNML Petri net ({})", error),
+            ParseErrorKind::InvalidConstraint(message) => format!("invalid DECLARE constraint: {}", message),
+            ParseErrorKind::Ocel(message) => format!("the document is not a valid OCEL 2.0 log ({})", message),
+        }
+    }
+}
+
+impl std::fmt::Display for ParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Failed to parse")?;
+        if let Some(file) = &self.file {
+            write!(f, " {}", file)?;
+        }
+        if let Some(position) = &self.position {
+            write!(f, " at line {}, column {}", position.line, position.column)?;
+        }
+        write!(f, ": {}", self.message())?;
+        if let Some(suggestion) = &self.suggestion {
+            write!(f, "\nHint: {}", suggestion)?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for ParseError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self.kind.as_ref() {
+            ParseErrorKind::Xes(error) => Some(error),
+            ParseErrorKind::UnknownClassifier { .. } => None,
+            ParseErrorKind::Pnml(error) => Some(error),
+            ParseErrorKind::InvalidConstraint(_) => None,
+            ParseErrorKind::Ocel(_) => None,
+        }
+    }
+}
+
+impl From<XESParseError> for ParseError {
+    fn from(error: XESParseError) -> Self {
+        ParseError::from_xes(error, None)
+    }
+}
+
+fn describe(error: &XESParseError) -> String {
+    match error {
+        XESParseError::NoTopLevelLog => "the document has no top-level <log> element".to_string(),
+        XESParseError::AttributeOutsideLog => {
+            "an attribute was found outside of the <log> element".to_string()
+        }
+        XESParseError::MissingKey(key) => {
+            format!("an element is missing the required XML attribute \"{}\"", key)
+        }
+        XESParseError::InvalidKeyValue(key) => {
+            format!("the XML attribute \"{}\" has an invalid value", key)
+        }
+        XESParseError::IOError(error) => format!("the file could not be read ({})", error),
+        XESParseError::XMLParsingError(error) => format!("the document is not valid XML ({})", error),
+        other => format!("the XES structure is inconsistent ({:?})", other),
+    }
+}
+
+// Re-reads the document with a plain XML reader to find where the syntax error is
+fn locate_xml_error(content: &str) -> Option<Position> {
+    let mut reader = Reader::from_str(content);
+    loop {
+        let before = reader.buffer_position();
+        match reader.read_event() {
+            Ok(XmlEvent::Eof) => return None,
+            Ok(_) => continue,
+            Err(_) => return Some(Position::from_byte_offset(content, before)),
+        }
+    }
+}
+
+// Guesses what kind of file was uploaded instead of an XES document
+fn suggest(error: &XESParseError, content: &[u8]) -> Option<String> {
+    let text = String::from_utf8_lossy(&content[..content.len().min(4096)]);
+    let trimmed = text.trim_start_matches('\u{feff}').trim_start();
+
+    if content.starts_with(&[0x1f, 0x8b]) || trimmed.starts_with('\u{1f}') {
+        return Some("the file appears to be gzip-compressed but could not be decompressed, check that it is complete".to_string());
+    }
+    if content.starts_with(b"PK") {
+        return Some("the file appears to be a zip archive, extract the .xes file first".to_string());
+    }
+    if trimmed.starts_with('{') || trimmed.starts_with('[') {
+        return Some("the file appears to be JSON, not XES".to_string());
+    }
+    if !trimmed.starts_with('<') && trimmed.lines().next().is_some_and(|line| line.contains(',')) {
+        return Some(
+            "the file appears to be comma separated text, paste it into the text area instead"
+                .to_string(),
+        );
+    }
+    if matches!(error, XESParseError::NoTopLevelLog) && text.contains(":log") {
+        return Some(
+            "the elements seem to use a namespace prefix, try the lenient parsing mode".to_string(),
+        );
+    }
+
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_position_from_byte_offset() {
+        let content = "<log>\n  <trace>\n  </tr>";
+        assert_eq!(
+            Position::from_byte_offset(content, 8),
+            Position {
+                byte: 8,
+                line: 2,
+                column: 3
+            }
+        );
+    }
+
+    #[test]
+    fn test_gzip_suggestion() {
+        let error = ParseError::from_xes(XESParseError::NoTopLevelLog, Some(&[0x1f, 0x8b, 0x08]));
+        assert!(error.to_string().contains("gzip"));
+    }
+
+    #[test]
+    fn test_display() {
+        let error = ParseError::from_xes(XESParseError::MissingKey("key"), None).with_file("a.xes");
+        assert_eq!(
+            error.to_string(),
+            "Failed to parse a.xes: an element is missing the required XML attribute \"key\""
+        );
+    }
+}
@@ -0,0 +1,54 @@
+use regex::Regex;
+use std::sync::OnceLock;
+
+fn prefixed_element() -> &'static Regex {
+    static RE: OnceLock<Regex> = OnceLock::new();
+    RE.get_or_init(|| Regex::new(r"<(/?)[A-Za-z_][\w.-]*:([A-Za-z_][\w.-]*)").unwrap())
+}
+
+fn padded_key_or_value() -> &'static Regex {
+    static RE: OnceLock<Regex> = OnceLock::new();
+    RE.get_or_init(|| Regex::new(r#"\b(key|value)\s*=\s*"\s*([^"]*?)\s*""#).unwrap())
+}
+
+/// Rewrites an XES document so that exports which deviate from the standard's serialization
+/// can still be read by the strict XES parser.
+///
+/// - Namespace prefixes are removed from element names (`<xes:event>` becomes `<event>`).
+/// - Whitespace around `key` and `value` XML attributes is trimmed
+///   (`key=" concept:name "` becomes `key="concept:name"`).
+pub fn normalize_xes(content: &str) -> String {
+    let content = prefixed_element().replace_all(content, "<$1$2");
+    padded_key_or_value()
+        .replace_all(&content, r#"$1="$2""#)
+        .into_owned()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_strips_element_prefixes() {
+        let content = r#"<xes:log xmlns:xes="http://www.xes-standard.org/"><xes:trace></xes:trace></xes:log>"#;
+        assert_eq!(
+            normalize_xes(content),
+            r#"<log xmlns:xes="http://www.xes-standard.org/"><trace></trace></log>"#
+        );
+    }
+
+    #[test]
+    fn test_trims_keys_and_values() {
+        let content = r#"<string key=" concept:name" value = "A  " />"#;
+        assert_eq!(
+            normalize_xes(content),
+            r#"<string key="concept:name" value="A" />"#
+        );
+    }
+
+    #[test]
+    fn test_standard_document_is_unchanged() {
+        let content = r#"<?xml version="1.0"?><log><string key="concept:name" value="A"/></log>"#;
+        assert_eq!(normalize_xes(content), content);
+    }
+}
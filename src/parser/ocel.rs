@@ -0,0 +1,432 @@
+//! OCEL 2.0 object-centric event logs, in their JSON and XML formats.
+//!
+//! An event of an object-centric log relates to any number of objects of different types, e.g.
+//! an order, its items and a delivery, instead of exactly one case. The analyses of this crate
+//! need cases, so the log is flattened on one object type: every object of that type becomes a
+//! case with the events related to it. An event related to several objects of the type is part
+//! of each of their cases.
+//!
+//! Only what the flattening needs is read: the types of the objects and the activities, times
+//! and objects of the events. Attributes and object-to-object relationships are skipped.
+
+use std::collections::{BTreeMap, HashMap};
+
+use chrono::{DateTime, Utc};
+use quick_xml::events::{BytesStart, Event as XmlEvent};
+use quick_xml::Reader;
+use serde::Deserialize;
+
+use super::error::{ParseErrorKind, Position};
+use super::timezone::{parse_naive, parse_with_offset};
+use super::ParseError;
+use crate::variant_log::{CaseMetadata, VariantLog};
+
+/// An event of an object-centric log.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ObjectCentricEvent {
+    pub id: String,
+    /// The event type.
+    pub activity: String,
+    /// `None` if the log doesn't give one.
+    pub timestamp: Option<DateTime<Utc>>,
+    /// Ids of the objects the event relates to.
+    pub objects: Vec<String>,
+}
+
+/// The events of an object-centric log and the types of its objects.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ObjectCentricLog {
+    /// The events ordered by their timestamp, events without timestamp first; events with the
+    /// same timestamp keep their order in the document.
+    pub events: Vec<ObjectCentricEvent>,
+    /// The type of every object by its id.
+    pub object_types: BTreeMap<String, String>,
+}
+
+impl ObjectCentricLog {
+    fn new(mut events: Vec<ObjectCentricEvent>, object_types: BTreeMap<String, String>) -> Self {
+        events.sort_by_key(|event| event.timestamp);
+        ObjectCentricLog {
+            events,
+            object_types,
+        }
+    }
+
+    /// Every object type with its number of objects, by name, to choose the type to flatten
+    /// on.
+    pub fn object_type_counts(&self) -> Vec<(String, usize)> {
+        let mut counts: BTreeMap<&str, usize> = BTreeMap::new();
+        for object_type in self.object_types.values() {
+            *counts.entry(object_type).or_default() += 1;
+        }
+        counts
+            .into_iter()
+            .map(|(object_type, count)| (object_type.to_string(), count))
+            .collect()
+    }
+
+    /// Flattens the log on `object_type`: one case per object of the type with an event,
+    /// named by the object id, with the activities of its events in the order of their
+    /// timestamps. The cases are ordered by their first event.
+    ///
+    /// The traces of the result feed [`crate::generate_adj_matrix_from_traces`] via
+    /// [`VariantLog::to_traces`], and the prefix automaton via
+    /// [`crate::ExtendedPrefixAutomaton::from_variant_log`].
+    pub fn flatten(&self, object_type: &str) -> VariantLog {
+        let mut cases: Vec<(&str, Vec<String>)> = Vec::new();
+        let mut case_index: HashMap<&str, usize> = HashMap::new();
+        for event in &self.events {
+            for object in &event.objects {
+                if self.object_types.get(object).map(String::as_str) != Some(object_type) {
+                    continue;
+                }
+                let idx = *case_index.entry(object).or_insert_with(|| {
+                    cases.push((object, Vec::new()));
+                    cases.len() - 1
+                });
+                cases[idx].1.push(event.activity.clone());
+            }
+        }
+
+        let mut log = VariantLog::new();
+        for (object, trace) in cases {
+            log.push(
+                CaseMetadata {
+                    case_id: object.to_string(),
+                    ongoing: false,
+                    attributes: Default::default(),
+                },
+                trace,
+            );
+        }
+        log
+    }
+}
+
+/// Parses an OCEL 2.0 log in the JSON or the XML format, whichever the document starts with.
+pub fn parse_ocel(
+    path: Option<&str>,
+    content: Option<&str>,
+) -> Result<ObjectCentricLog, ParseError> {
+    let read;
+    let content = match (path, content) {
+        (Some(path), _) => {
+            read = std::fs::read_to_string(path).map_err(|error| {
+                ParseError::new(ParseErrorKind::Ocel(format!(
+                    "the file could not be read ({})",
+                    error
+                )))
+                .with_file(path)
+            })?;
+            read.as_str()
+        }
+        (None, Some(content)) => content,
+        _ => panic!("Either path or content must be provided, not both"),
+    };
+    let content = content.trim_start_matches('\u{feff}');
+    let result = if content.trim_start().starts_with('<') {
+        parse_ocel_xml(content)
+    } else {
+        parse_ocel_json(content)
+    };
+    result.map_err(|error| match path {
+        Some(path) => error.with_file(path),
+        None => error,
+    })
+}
+
+fn ocel_error(message: impl Into<String>) -> ParseError {
+    ParseError::new(ParseErrorKind::Ocel(message.into()))
+}
+
+fn parse_timestamp(value: &str) -> Result<DateTime<Utc>, ParseError> {
+    let value = value.trim();
+    parse_with_offset(value)
+        .map(|date| date.with_timezone(&Utc))
+        .or_else(|| parse_naive(value).map(|date| date.and_utc()))
+        .ok_or_else(|| ocel_error(format!("invalid time \"{}\"", value)))
+}
+
+#[derive(Deserialize)]
+struct JsonLog {
+    #[serde(default)]
+    objects: Vec<JsonObject>,
+    #[serde(default)]
+    events: Vec<JsonEvent>,
+}
+
+#[derive(Deserialize)]
+struct JsonObject {
+    id: String,
+    #[serde(rename = "type")]
+    object_type: String,
+}
+
+#[derive(Deserialize)]
+struct JsonEvent {
+    id: String,
+    #[serde(rename = "type")]
+    event_type: String,
+    time: Option<String>,
+    relationships: Option<Vec<JsonRelationship>>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct JsonRelationship {
+    object_id: String,
+}
+
+fn parse_ocel_json(content: &str) -> Result<ObjectCentricLog, ParseError> {
+    let log: JsonLog = serde_json::from_str(content).map_err(|error| {
+        let mut parse_error = ocel_error(error.to_string());
+        if error.line() > 0 {
+            let line_start: usize = content
+                .split_inclusive('\n')
+                .take(error.line() - 1)
+                .map(str::len)
+                .sum();
+            parse_error.position = Some(Position::from_byte_offset(
+                content,
+                line_start + error.column().saturating_sub(1),
+            ));
+        }
+        parse_error
+    })?;
+    let events = log
+        .events
+        .into_iter()
+        .map(|event| {
+            Ok(ObjectCentricEvent {
+                timestamp: event.time.as_deref().map(parse_timestamp).transpose()?,
+                id: event.id,
+                activity: event.event_type,
+                objects: event
+                    .relationships
+                    .unwrap_or_default()
+                    .into_iter()
+                    .map(|relationship| relationship.object_id)
+                    .collect(),
+            })
+        })
+        .collect::<Result<_, ParseError>>()?;
+    let object_types = log
+        .objects
+        .into_iter()
+        .map(|object| (object.id, object.object_type))
+        .collect();
+    Ok(ObjectCentricLog::new(events, object_types))
+}
+
+fn parse_ocel_xml(content: &str) -> Result<ObjectCentricLog, ParseError> {
+    let mut reader = Reader::from_str(content);
+    reader.trim_text(true);
+    let mut events = Vec::new();
+    let mut object_types = BTreeMap::new();
+    // the event whose relationships are being read
+    let mut current: Option<ObjectCentricEvent> = None;
+    loop {
+        let position = reader.buffer_position();
+        let at = |error: ParseError| ParseError {
+            position: Some(Position::from_byte_offset(content, position)),
+            ..error
+        };
+        let (element, empty) = match reader.read_event() {
+            Ok(XmlEvent::Start(element)) => (element, false),
+            Ok(XmlEvent::Empty(element)) => (element, true),
+            Ok(XmlEvent::End(element)) => {
+                if element.name().as_ref() == b"event" {
+                    events.extend(current.take());
+                }
+                continue;
+            }
+            Ok(XmlEvent::Eof) => break,
+            Ok(_) => continue,
+            Err(error) => return Err(at(ocel_error(format!("invalid XML ({})", error)))),
+        };
+        match element.name().as_ref() {
+            b"object" if current.is_none() => {
+                let id = required_attribute(&element, "id").map_err(at)?;
+                let object_type = required_attribute(&element, "type").map_err(at)?;
+                object_types.insert(id, object_type);
+            }
+            b"event" => {
+                let event = ObjectCentricEvent {
+                    id: required_attribute(&element, "id").map_err(at)?,
+                    activity: required_attribute(&element, "type").map_err(at)?,
+                    timestamp: attribute(&element, "time")
+                        .map_err(at)?
+                        .as_deref()
+                        .map(parse_timestamp)
+                        .transpose()
+                        .map_err(at)?,
+                    objects: Vec::new(),
+                };
+                if empty {
+                    events.push(event);
+                } else {
+                    current = Some(event);
+                }
+            }
+            b"relationship" => {
+                if let Some(event) = current.as_mut() {
+                    event
+                        .objects
+                        .push(required_attribute(&element, "object-id").map_err(at)?);
+                }
+            }
+            _ => {}
+        }
+    }
+    Ok(ObjectCentricLog::new(events, object_types))
+}
+
+fn attribute(element: &BytesStart, name: &str) -> Result<Option<String>, ParseError> {
+    match element.try_get_attribute(name) {
+        Ok(Some(attribute)) => attribute
+            .unescape_value()
+            .map(|value| Some(value.into_owned()))
+            .map_err(|error| ocel_error(format!("invalid value of \"{}\" ({})", name, error))),
+        Ok(None) => Ok(None),
+        Err(error) => Err(ocel_error(format!("invalid XML attribute ({})", error))),
+    }
+}
+
+fn required_attribute(element: &BytesStart, name: &str) -> Result<String, ParseError> {
+    attribute(element, name)?.ok_or_else(|| {
+        ocel_error(format!(
+            "<{}> without \"{}\"",
+            String::from_utf8_lossy(element.name().as_ref()),
+            name
+        ))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{generate_adj_matrix_from_traces, ExtendedPrefixAutomaton};
+
+    const JSON: &str = r#"{
+        "objectTypes": [{ "name": "order", "attributes": [] }, { "name": "item", "attributes": [] }],
+        "eventTypes": [{ "name": "place order", "attributes": [] }],
+        "objects": [
+            { "id": "o1", "type": "order", "attributes": [] },
+            { "id": "o2", "type": "order" },
+            { "id": "i1", "type": "item", "relationships": [{ "objectId": "o1", "qualifier": "part of" }] },
+            { "id": "i2", "type": "item" }
+        ],
+        "events": [
+            { "id": "e3", "type": "pay order", "time": "2024-01-03T09:00:00Z",
+              "relationships": [{ "objectId": "o1", "qualifier": "" }] },
+            { "id": "e1", "type": "place order", "time": "2024-01-01T09:00:00+01:00",
+              "attributes": [{ "name": "price", "value": 10 }],
+              "relationships": [
+                { "objectId": "o1", "qualifier": "order" },
+                { "objectId": "i1", "qualifier": "item" },
+                { "objectId": "i2", "qualifier": "item" }
+              ] },
+            { "id": "e2", "type": "pick item", "time": "2024-01-02T09:00:00",
+              "relationships": [{ "objectId": "i1", "qualifier": "" }] },
+            { "id": "e4", "type": "place order", "time": "2024-01-04T09:00:00Z",
+              "relationships": [{ "objectId": "o2", "qualifier": "" }] }
+        ]
+    }"#;
+
+    const XML: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<log>
+  <object-types>
+    <object-type name="order"><attributes/></object-type>
+    <object-type name="item"><attributes/></object-type>
+  </object-types>
+  <objects>
+    <object id="o1" type="order">
+      <attributes/>
+      <objects><relationship object-id="i1" qualifier="contains"/></objects>
+    </object>
+    <object id="o2" type="order"/>
+    <object id="i1" type="item"/>
+    <object id="i2" type="item"/>
+  </objects>
+  <events>
+    <event id="e1" type="place order" time="2024-01-01T09:00:00+01:00">
+      <attributes><attribute name="price">10</attribute></attributes>
+      <objects>
+        <relationship object-id="o1" qualifier="order"/>
+        <relationship object-id="i1" qualifier="item"/>
+        <relationship object-id="i2" qualifier="item"/>
+      </objects>
+    </event>
+    <event id="e2" type="pick item" time="2024-01-02T09:00:00">
+      <objects><relationship object-id="i1" qualifier=""/></objects>
+    </event>
+    <event id="e3" type="pay order" time="2024-01-03T09:00:00Z">
+      <objects><relationship object-id="o1" qualifier=""/></objects>
+    </event>
+    <event id="e4" type="place order" time="2024-01-04T09:00:00Z">
+      <objects><relationship object-id="o2" qualifier=""/></objects>
+    </event>
+  </events>
+</log>"#;
+
+    #[test]
+    fn test_parse_ocel() {
+        for content in [JSON, XML] {
+            let log = parse_ocel(None, Some(content)).unwrap();
+            assert_eq!(
+                log.object_type_counts(),
+                [("item".to_string(), 2), ("order".to_string(), 2)]
+            );
+            assert_eq!(log.events[0].id, "e1");
+            assert_eq!(
+                log.events[0].timestamp,
+                Some("2024-01-01T08:00:00Z".parse().unwrap())
+            );
+
+            let orders = log.flatten("order");
+            let case_ids: Vec<&str> = orders
+                .cases()
+                .iter()
+                .map(|case| case.case_id.as_str())
+                .collect();
+            assert_eq!(case_ids, ["o1", "o2"]);
+            assert_eq!(
+                orders.to_traces(),
+                [vec!["place order", "pay order"], vec!["place order"]]
+            );
+            let items = log.flatten("item");
+            assert_eq!(
+                items.to_traces(),
+                [vec!["place order", "pick item"], vec!["place order"]]
+            );
+            assert!(log.flatten("delivery").is_empty());
+
+            let matrix = generate_adj_matrix_from_traces(orders.to_traces());
+            assert_eq!(matrix.activities, 2);
+            let epa = ExtendedPrefixAutomaton::from_variant_log(&items);
+            assert_eq!(epa.states.len(), 3);
+        }
+    }
+
+    #[test]
+    fn test_parse_ocel_errors() {
+        let error = parse_ocel(None, Some(r#"{"events": [{"id": "e1"}]}"#)).unwrap_err();
+        assert!(matches!(*error.kind, ParseErrorKind::Ocel(_)));
+        assert_eq!(error.position.map(|position| position.line), Some(1));
+
+        let error = parse_ocel(
+            None,
+            Some("<log>\n<events><event id=\"e1\"/></events></log>"),
+        )
+        .unwrap_err();
+        assert!(error.message().contains("without \"type\""));
+        assert_eq!(error.position.map(|position| position.line), Some(2));
+
+        let error = parse_ocel(
+            None,
+            Some(r#"<log><event id="e1" type="a" time="yesterday"/></log>"#),
+        )
+        .unwrap_err();
+        assert!(error.message().contains("invalid time"));
+    }
+}
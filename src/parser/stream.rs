@@ -0,0 +1,197 @@
+//! Streaming XES import: the traces of a log are parsed one at a time from a reader, so
+//! analyses that only aggregate over the traces, like the directly-follows graph or the
+//! variant counts, run with bounded memory on logs of several gigabytes.
+//!
+//! Everything that needs the whole log at once (case notion, sessions, duplicates, abstraction,
+//! lenient parsing and time zones) is not applied; only the classifier, the lifecycle policy and
+//! the options of the underlying XES importer are.
+
+use std::fs::File;
+use std::io::{BufRead, BufReader};
+
+use flate2::read::MultiGzDecoder;
+use process_mining::event_log::event_log_struct::EventLogClassifier;
+use process_mining::event_log::import_xes::XESParseError;
+use process_mining::event_log::stream_xes::XESParsingTraceStream;
+use process_mining::event_log::Attributes;
+use process_mining::{EventLog, XESImportOptions};
+use quick_xml::Reader;
+
+use super::xes_extensions::CONCEPT_NAME;
+use super::{
+    case_id, text_attributes, trace_activities, Classifier, ExpandEmptyEvents, LifecyclePolicy,
+    ParseError,
+};
+use crate::variant_log::{CaseMetadata, VariantLog};
+
+/// Iterates over the traces of an XES log, parsing them lazily from a reader.
+///
+/// Every item is the case of a trace with its activity sequence, derived like in
+/// [`super::parse_into_variant_log`]. After an error the iterator ends.
+pub struct TraceIterator<'a> {
+    traces: XESParsingTraceStream<'a>,
+    classifier: EventLogClassifier,
+    global_event_attrs: Option<Attributes>,
+    lifecycle: LifecyclePolicy,
+    case_idx: usize,
+    file: Option<String>,
+}
+
+impl<'a> TraceIterator<'a> {
+    /// Starts parsing the XES document of `reader`, gzip-compressed or not, up to its first
+    /// trace. The classifier is resolved against the classifiers declared before that trace.
+    pub fn new(
+        reader: impl BufRead + 'a,
+        classifier: &Classifier,
+        options: XESImportOptions,
+    ) -> Result<Self, ParseError> {
+        let mut reader = reader;
+        let compressed = reader
+            .fill_buf()
+            .map_err(XESParseError::from)?
+            .starts_with(&[0x1f, 0x8b]);
+        let reader: Box<dyn BufRead + 'a> = if compressed {
+            Box::new(BufReader::new(MultiGzDecoder::new(reader)))
+        } else {
+            Box::new(reader)
+        };
+        let reader: Box<dyn BufRead + 'a> =
+            Box::new(BufReader::new(ExpandEmptyEvents::new(reader)));
+        let (traces, log_data) =
+            XESParsingTraceStream::try_new(Box::new(Reader::from_reader(reader)), options)?;
+
+        // the classifier only looks at the declared classifiers
+        let declared = EventLog {
+            attributes: Attributes::new(),
+            traces: Vec::new(),
+            extensions: None,
+            classifiers: Some(log_data.classifiers),
+            global_trace_attrs: None,
+            global_event_attrs: None,
+        };
+        Ok(TraceIterator {
+            traces,
+            classifier: classifier.resolve(&declared)?,
+            global_event_attrs: Some(log_data.global_event_attrs),
+            lifecycle: LifecyclePolicy::default(),
+            case_idx: 0,
+            file: None,
+        })
+    }
+
+    /// Same as [`TraceIterator::new`], but reads the file at `path`.
+    pub fn from_path(
+        path: &str,
+        classifier: &Classifier,
+        options: XESImportOptions,
+    ) -> Result<Self, ParseError> {
+        let file = File::open(path)
+            .map_err(|error| ParseError::from(XESParseError::from(error)).with_file(path))?;
+        let mut traces = TraceIterator::new(BufReader::new(file), classifier, options)
+            .map_err(|error| error.with_file(path))?;
+        traces.file = Some(path.to_string());
+        Ok(traces)
+    }
+
+    /// Selects the events of the traces with `lifecycle` instead of the default policy.
+    pub fn with_lifecycle(mut self, lifecycle: LifecyclePolicy) -> Self {
+        self.lifecycle = lifecycle;
+        self
+    }
+
+    /// Collects the remaining traces into a [`VariantLog`], which only holds every distinct
+    /// activity sequence once.
+    pub fn into_variant_log(self) -> Result<VariantLog, ParseError> {
+        let mut log = VariantLog::new();
+        for trace in self {
+            let (metadata, trace) = trace?;
+            log.push(metadata, trace);
+        }
+        Ok(log)
+    }
+}
+
+impl Iterator for TraceIterator<'_> {
+    type Item = Result<(CaseMetadata, Vec<String>), ParseError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let Some(trace) = (&mut self.traces).next() else {
+            // the stream ends on the first error and keeps it
+            let error = self.traces.error.take()?;
+            let error = ParseError::from(error);
+            return Some(Err(match &self.file {
+                Some(file) => error.with_file(file),
+                None => error,
+            }));
+        };
+        let metadata = CaseMetadata {
+            case_id: case_id(&trace, self.case_idx),
+            ongoing: false,
+            attributes: text_attributes(&trace, &[CONCEPT_NAME]),
+        };
+        self.case_idx += 1;
+        Some(Ok((
+            metadata,
+            trace_activities(
+                &trace,
+                &self.classifier,
+                &self.global_event_attrs,
+                &self.lifecycle,
+            ),
+        )))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dfg::DirectlyFollowsGraph;
+    use crate::parser::{parse_into_traces, parse_into_variant_log};
+
+    const PATH: &str = "./sample-data/Example_SemiStructured.xes";
+
+    #[test]
+    fn test_trace_iterator() {
+        let traces: Vec<Vec<String>> =
+            TraceIterator::from_path(PATH, &Classifier::ConceptName, XESImportOptions::default())
+                .unwrap()
+                .map(|trace| trace.unwrap().1)
+                .collect();
+        assert_eq!(traces, parse_into_traces(Some(PATH), None).unwrap());
+
+        let log =
+            TraceIterator::from_path(PATH, &Classifier::ConceptName, XESImportOptions::default())
+                .unwrap()
+                .into_variant_log()
+                .unwrap();
+        let expected = parse_into_variant_log(Some(PATH), None).unwrap();
+        assert_eq!(log.cases(), expected.cases());
+        assert_eq!(
+            DirectlyFollowsGraph::from_variant_log(&log),
+            DirectlyFollowsGraph::from_traces(&traces)
+        );
+    }
+
+    #[test]
+    fn test_trace_iterator_from_reader() {
+        let content = r#"<log xes.version="1.0">
+            <trace><string key="concept:name" value="1"/><event/>
+                <event><string key="concept:name" value="A"/><date key="time:timestamp" value="2024-01-01T00:00:00Z"/></event>
+            </trace>
+            <trace><event><string key="concept:name" value="B"/></trace>
+        </log>"#;
+        let mut traces = TraceIterator::new(
+            content.as_bytes(),
+            &Classifier::ConceptName,
+            XESImportOptions::default(),
+        )
+        .unwrap();
+
+        let (metadata, trace) = traces.next().unwrap().unwrap();
+        assert_eq!(metadata.case_id, "1");
+        assert_eq!(trace, ["A"]);
+        // the mismatched closing tag ends the stream with an error
+        assert!(traces.next().unwrap().is_err());
+        assert!(traces.next().is_none());
+    }
+}
@@ -0,0 +1,119 @@
+//! Restricting a log to a period, e.g. to study the cases of one quarter without slicing the
+//! log in another tool first.
+
+use chrono::{DateTime, Utc};
+use process_mining::event_log::EventLog;
+use serde::{Deserialize, Serialize};
+
+use crate::parser::xes_extensions::XesExtensions;
+
+/// What is kept of the cases that overlap the window.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum WindowMode {
+    /// The events within the window; cases without any of them are dropped.
+    #[default]
+    Events,
+    /// The cases whose events all lie within the window, with all of their events.
+    Cases,
+}
+
+/// The period `start..end` of a log, including `start` and excluding `end`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct TimeWindow {
+    pub start: DateTime<Utc>,
+    pub end: DateTime<Utc>,
+    #[serde(default)]
+    pub mode: WindowMode,
+}
+
+impl TimeWindow {
+    pub fn new(start: DateTime<Utc>, end: DateTime<Utc>, mode: WindowMode) -> Self {
+        TimeWindow { start, end, mode }
+    }
+
+    /// Whether `date` lies within the window.
+    pub fn contains(&self, date: DateTime<Utc>) -> bool {
+        self.start <= date && date < self.end
+    }
+}
+
+/// Keeps the events or the whole cases of `log` within `window`, see [`WindowMode`]. Events
+/// without a timestamp can't be placed in the window: they are dropped when filtering events
+/// and ignored when filtering cases, so a case without any timestamp is dropped either way.
+pub fn apply_time_window(log: &mut EventLog, window: &TimeWindow) {
+    match window.mode {
+        WindowMode::Events => {
+            for trace in &mut log.traces {
+                trace
+                    .events
+                    .retain(|event| event.timestamp().is_some_and(|date| window.contains(date)));
+            }
+            log.traces.retain(|trace| !trace.events.is_empty());
+        }
+        WindowMode::Cases => log.traces.retain(|trace| {
+            let dates: Vec<DateTime<Utc>> = trace
+                .events
+                .iter()
+                .filter_map(|event| event.timestamp())
+                .collect();
+            !dates.is_empty() && dates.into_iter().all(|date| window.contains(date))
+        }),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::{parse_into_traces_with_config, ImportConfig};
+    use chrono::TimeZone;
+
+    const LOG: &str = r#"<log>
+        <trace>
+            <event>
+                <string key="concept:name" value="A"/>
+                <date key="time:timestamp" value="2024-01-30T10:00:00+00:00"/>
+            </event>
+            <event>
+                <string key="concept:name" value="B"/>
+                <date key="time:timestamp" value="2024-02-02T10:00:00+00:00"/>
+            </event>
+        </trace>
+        <trace>
+            <event>
+                <string key="concept:name" value="C"/>
+                <date key="time:timestamp" value="2024-02-05T10:00:00+00:00"/>
+            </event>
+            <event>
+                <string key="concept:name" value="D"/>
+                <date key="time:timestamp" value="2024-02-06T10:00:00+00:00"/>
+            </event>
+        </trace>
+        <trace>
+            <event>
+                <string key="concept:name" value="E"/>
+                <date key="time:timestamp" value="2024-03-01T00:00:00+00:00"/>
+            </event>
+        </trace>
+    </log>"#;
+
+    fn traces(mode: WindowMode) -> Vec<Vec<String>> {
+        let february = TimeWindow::new(
+            Utc.with_ymd_and_hms(2024, 2, 1, 0, 0, 0).unwrap(),
+            Utc.with_ymd_and_hms(2024, 3, 1, 0, 0, 0).unwrap(),
+            mode,
+        );
+        let config = ImportConfig {
+            time_window: Some(february),
+            ..Default::default()
+        };
+        parse_into_traces_with_config(None, Some(LOG), &config).unwrap()
+    }
+
+    #[test]
+    fn test_time_window() {
+        assert_eq!(traces(WindowMode::Events), [vec!["B"], vec!["C", "D"]]);
+        assert_eq!(traces(WindowMode::Cases), [vec!["C", "D"]]);
+    }
+}
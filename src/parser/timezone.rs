@@ -0,0 +1,206 @@
+use std::collections::BTreeMap;
+use std::fmt;
+use std::sync::OnceLock;
+
+use chrono::{DateTime, FixedOffset, NaiveDateTime, Utc};
+use process_mining::event_log::{AttributeValue, EventLog, XESEditableAttribute};
+use regex::{Captures, Regex};
+use serde::{Deserialize, Serialize};
+
+use super::xes_extensions::{XesExtensions, TIME_TIMESTAMP};
+
+/// How timestamps without and with UTC offsets are interpreted and shown.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct TimezoneOptions {
+    /// UTC offset in minutes of timestamps that don't specify one, UTC if missing.
+    pub naive_offset_minutes: Option<i32>,
+    /// UTC offset in minutes that exported timestamps are shown in, UTC if missing.
+    pub target_offset_minutes: Option<i32>,
+}
+
+impl TimezoneOptions {
+    pub fn naive_offset(&self) -> Option<FixedOffset> {
+        self.naive_offset_minutes.and_then(offset)
+    }
+
+    pub fn target_offset(&self) -> FixedOffset {
+        self.target_offset_minutes.and_then(offset).unwrap_or(utc())
+    }
+}
+
+fn offset(minutes: i32) -> Option<FixedOffset> {
+    FixedOffset::east_opt(minutes * 60)
+}
+
+fn utc() -> FixedOffset {
+    FixedOffset::east_opt(0).expect("valid offset")
+}
+
+fn date_attribute() -> &'static Regex {
+    static RE: OnceLock<Regex> = OnceLock::new();
+    RE.get_or_init(|| Regex::new(r#"(<date\s[^>]*?\bvalue\s*=\s*")([^"]*)(")"#).unwrap())
+}
+
+// The formats without offset the XES importer falls back to
+pub(super) fn parse_naive(value: &str) -> Option<NaiveDateTime> {
+    NaiveDateTime::parse_from_str(value, "%Y-%m-%dT%H:%M:%S%.f").ok()
+}
+
+pub(super) fn parse_with_offset(value: &str) -> Option<DateTime<FixedOffset>> {
+    DateTime::parse_from_rfc3339(value)
+        .or_else(|_| DateTime::parse_from_str(value, "%Y-%m-%dT%H:%M:%S:%f%:z"))
+        .ok()
+}
+
+/// Adds `offset` to every `<date>` value without a UTC offset, which the XES importer would
+/// otherwise read as UTC.
+pub fn apply_naive_offset(content: &str, offset: FixedOffset) -> String {
+    date_attribute()
+        .replace_all(content, |captures: &Captures| {
+            let value = &captures[2];
+            match parse_naive(value.trim()).map(|date| date.and_local_timezone(offset).single()) {
+                Some(Some(date)) => {
+                    format!("{}{}{}", &captures[1], date.to_rfc3339(), &captures[3])
+                }
+                _ => captures[0].to_string(),
+            }
+        })
+        .into_owned()
+}
+
+/// Makes the attribute `key` the timestamp of every event, for logs that record when an event
+/// happened under another key than `time:timestamp`, e.g. `EventTime`. Date attributes are
+/// taken as they are; text is read like an XES date, also with a space instead of the `T`,
+/// and without UTC offset it is in the naive offset of `options`. Events without a readable
+/// value for `key` are left without timestamp.
+pub fn use_timestamp_key(log: &mut EventLog, key: &str, options: &TimezoneOptions) {
+    let naive_offset = options.naive_offset().unwrap_or(utc());
+    for event in log.traces.iter_mut().flat_map(|trace| &mut trace.events) {
+        let timestamp = match event.attribute_value(key) {
+            Some(AttributeValue::Date(date)) => Some(*date),
+            Some(AttributeValue::String(value)) => {
+                let value = value.trim().replacen(' ', "T", 1);
+                parse_with_offset(&value)
+                    .or_else(|| parse_naive(&value)?.and_local_timezone(naive_offset).single())
+                    .map(|date| date.with_timezone(&Utc))
+            }
+            _ => None,
+        };
+        event.attributes.remove_with_key(TIME_TIMESTAMP);
+        if let Some(timestamp) = timestamp {
+            event
+                .attributes
+                .add_to_attributes(TIME_TIMESTAMP.to_string(), AttributeValue::Date(timestamp));
+        }
+    }
+}
+
+/// The UTC offsets of the `<date>` values of a log.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct TimestampOffsets {
+    /// Number of timestamps without offset.
+    pub naive: usize,
+    /// Number of timestamps per offset in minutes.
+    pub offsets: BTreeMap<i32, usize>,
+}
+
+impl TimestampOffsets {
+    /// Collects the offsets of all `<date>` values in an XES document. Timestamps without
+    /// offset count as timestamps with the naive offset of `options`, if there is one.
+    pub fn scan(content: &str, options: &TimezoneOptions) -> Self {
+        let mut result = TimestampOffsets::default();
+        for captures in date_attribute().captures_iter(content) {
+            let value = captures[2].trim();
+            let offset = match parse_with_offset(value) {
+                Some(date) => Some(date.offset().local_minus_utc() / 60),
+                None if parse_naive(value).is_some() => match options.naive_offset_minutes {
+                    Some(minutes) => Some(minutes),
+                    None => {
+                        result.naive += 1;
+                        None
+                    }
+                },
+                None => None,
+            };
+            if let Some(offset) = offset {
+                *result.offsets.entry(offset).or_default() += 1;
+            }
+        }
+        result
+    }
+
+    /// `true` if the log has timestamps with different offsets, or both with and without one,
+    /// which often means that local time and UTC got mixed up.
+    pub fn is_mixed(&self) -> bool {
+        self.offsets.len() + usize::from(self.naive > 0) > 1
+    }
+}
+
+impl fmt::Display for TimestampOffsets {
+    /// Lists the offsets with their number of timestamps, e.g. `+00:00 (12), +02:00 (3)`.
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let mut parts: Vec<String> = self
+            .offsets
+            .iter()
+            .map(|(minutes, count)| {
+                let sign = if *minutes < 0 { '-' } else { '+' };
+                format!(
+                    "{}{:02}:{:02} ({})",
+                    sign,
+                    minutes.abs() / 60,
+                    minutes.abs() % 60,
+                    count
+                )
+            })
+            .collect();
+        if self.naive > 0 {
+            parts.push(format!("none ({})", self.naive));
+        }
+        write!(f, "{}", parts.join(", "))
+    }
+}
+
+/// Shows a timestamp in the target offset of `options`.
+pub fn to_target(date: DateTime<Utc>, options: &TimezoneOptions) -> DateTime<FixedOffset> {
+    date.with_timezone(&options.target_offset())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CONTENT: &str = r#"<event>
+        <date key="time:timestamp" value="2024-01-01T10:00:00"/>
+        <date key="time:timestamp" value="2024-01-01T10:00:00+02:00"/>
+        <date key="time:timestamp" value="2024-01-01T09:00:00Z"/>
+        <date key="other" value="not a date"/>
+    </event>"#;
+
+    #[test]
+    fn test_scan() {
+        let offsets = TimestampOffsets::scan(CONTENT, &TimezoneOptions::default());
+        assert_eq!(offsets.naive, 1);
+        assert_eq!(offsets.offsets, BTreeMap::from([(0, 1), (120, 1)]));
+        assert!(offsets.is_mixed());
+        assert_eq!(offsets.to_string(), "+00:00 (1), +02:00 (1), none (1)");
+        let options = TimezoneOptions {
+            naive_offset_minutes: Some(0),
+            ..Default::default()
+        };
+        let offsets = TimestampOffsets::scan(CONTENT, &options);
+        assert_eq!(offsets.offsets, BTreeMap::from([(0, 2), (120, 1)]));
+        let content = r#"<date key="t" value="2024-01-01T10:00:00"/>"#;
+        assert!(!TimestampOffsets::scan(content, &options).is_mixed());
+    }
+
+    #[test]
+    fn test_apply_naive_offset() {
+        let content = apply_naive_offset(CONTENT, FixedOffset::east_opt(3600).unwrap());
+        assert!(content.contains(r#"value="2024-01-01T10:00:00+01:00""#));
+        // dates with offset and invalid values are left alone
+        assert!(content.contains(r#"value="2024-01-01T10:00:00+02:00""#));
+        assert!(content.contains(r#"value="not a date""#));
+        assert_eq!(TimestampOffsets::scan(&content, &TimezoneOptions::default()).naive, 0);
+    }
+}
@@ -0,0 +1,161 @@
+use chrono::{DateTime, Utc};
+use process_mining::event_log::{Attribute, AttributeValue, Event, Trace};
+
+pub const CONCEPT_NAME: &str = "concept:name";
+pub const CONCEPT_INSTANCE: &str = "concept:instance";
+pub const TIME_TIMESTAMP: &str = "time:timestamp";
+pub const LIFECYCLE_TRANSITION: &str = "lifecycle:transition";
+pub const ORG_RESOURCE: &str = "org:resource";
+pub const ORG_ROLE: &str = "org:role";
+pub const ORG_GROUP: &str = "org:group";
+pub const COST_TOTAL: &str = "cost:total";
+pub const COST_CURRENCY: &str = "cost:currency";
+
+/// Typed access to the attributes defined by the standard XES extensions
+/// (concept, time, lifecycle, org and cost).
+///
+/// Every accessor returns `None` if the attribute is missing or has an unexpected type,
+/// so logs that don't declare (or only partially use) an extension can be handled without
+/// special casing.
+pub trait XesExtensions {
+    fn xes_attributes(&self) -> &[Attribute];
+
+    fn attribute_value(&self, key: &str) -> Option<&AttributeValue> {
+        self.xes_attributes()
+            .iter()
+            .find(|a| a.key == key)
+            .map(|a| &a.value)
+    }
+
+    fn string_attribute(&self, key: &str) -> Option<&str> {
+        self.attribute_value(key)
+            .and_then(|value| value.try_as_string())
+            .map(|value| value.as_str())
+    }
+
+    /// Renders a scalar attribute (string, id, number, boolean or date) as text, e.g. to use
+    /// it as an identifier.
+    fn text_attribute(&self, key: &str) -> Option<String> {
+        match self.attribute_value(key)? {
+            AttributeValue::String(value) => Some(value.clone()),
+            AttributeValue::ID(value) => Some(value.to_string()),
+            AttributeValue::Int(value) => Some(value.to_string()),
+            AttributeValue::Float(value) => Some(value.to_string()),
+            AttributeValue::Boolean(value) => Some(value.to_string()),
+            AttributeValue::Date(value) => Some(value.to_rfc3339()),
+            _ => None,
+        }
+    }
+
+    fn concept_name(&self) -> Option<&str> {
+        self.string_attribute(CONCEPT_NAME)
+    }
+
+    fn concept_instance(&self) -> Option<&str> {
+        self.string_attribute(CONCEPT_INSTANCE)
+    }
+
+    fn timestamp(&self) -> Option<DateTime<Utc>> {
+        self.attribute_value(TIME_TIMESTAMP)
+            .and_then(|value| value.try_as_date())
+            .copied()
+    }
+
+    fn lifecycle_transition(&self) -> Option<&str> {
+        self.string_attribute(LIFECYCLE_TRANSITION)
+    }
+
+    /// Returns `true` if the lifecycle transition equals `transition` (ignoring case).
+    fn has_lifecycle_transition(&self, transition: &str) -> bool {
+        self.lifecycle_transition()
+            .is_some_and(|t| t.eq_ignore_ascii_case(transition))
+    }
+
+    fn org_resource(&self) -> Option<&str> {
+        self.string_attribute(ORG_RESOURCE)
+    }
+
+    fn org_role(&self) -> Option<&str> {
+        self.string_attribute(ORG_ROLE)
+    }
+
+    fn org_group(&self) -> Option<&str> {
+        self.string_attribute(ORG_GROUP)
+    }
+
+    /// Returns the total cost, accepting both float and int attributes.
+    fn cost_total(&self) -> Option<f64> {
+        match self.attribute_value(COST_TOTAL)? {
+            AttributeValue::Float(value) => Some(*value),
+            AttributeValue::Int(value) => Some(*value as f64),
+            _ => None,
+        }
+    }
+
+    fn cost_currency(&self) -> Option<&str> {
+        self.string_attribute(COST_CURRENCY)
+    }
+}
+
+impl XesExtensions for [Attribute] {
+    fn xes_attributes(&self) -> &[Attribute] {
+        self
+    }
+}
+
+impl XesExtensions for Event {
+    fn xes_attributes(&self) -> &[Attribute] {
+        &self.attributes
+    }
+}
+
+impl XesExtensions for Trace {
+    fn xes_attributes(&self) -> &[Attribute] {
+        &self.attributes
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    #[test]
+    fn test_typed_accessors() {
+        let date = Utc.with_ymd_and_hms(2024, 1, 2, 3, 4, 5).unwrap();
+        let event = Event {
+            attributes: vec![
+                Attribute::new(CONCEPT_NAME.to_string(), AttributeValue::String("A".to_string())),
+                Attribute::new(TIME_TIMESTAMP.to_string(), AttributeValue::Date(date)),
+                Attribute::new(
+                    LIFECYCLE_TRANSITION.to_string(),
+                    AttributeValue::String("COMPLETE".to_string()),
+                ),
+                Attribute::new(ORG_RESOURCE.to_string(), AttributeValue::String("Pete".to_string())),
+                Attribute::new(COST_TOTAL.to_string(), AttributeValue::Int(12)),
+            ],
+        };
+
+        assert_eq!(event.concept_name(), Some("A"));
+        assert_eq!(event.timestamp(), Some(date));
+        assert!(event.has_lifecycle_transition("complete"));
+        assert_eq!(event.org_resource(), Some("Pete"));
+        assert_eq!(event.cost_total(), Some(12.0));
+    }
+
+    #[test]
+    fn test_missing_or_mistyped_attributes() {
+        let event = Event {
+            attributes: vec![Attribute::new(
+                TIME_TIMESTAMP.to_string(),
+                AttributeValue::String("yesterday".to_string()),
+            )],
+        };
+
+        assert_eq!(event.concept_name(), None);
+        assert_eq!(event.timestamp(), None);
+        assert!(!event.has_lifecycle_transition("complete"));
+        assert_eq!(event.org_role(), None);
+        assert_eq!(event.cost_total(), None);
+    }
+}
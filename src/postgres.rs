@@ -0,0 +1,85 @@
+//! Extracting event logs directly from PostgreSQL.
+//!
+//! Only available with the `postgres` feature.
+
+use postgres::fallible_iterator::FallibleIterator;
+use postgres::types::ToSql;
+use postgres::Client;
+
+use crate::variant_log::{EventCollector, VariantLog};
+
+/// Runs `query` and builds a [`VariantLog`] from its rows.
+///
+/// The query must return (at least) three columns: the case id and the activity as `text`
+/// (cast them in the query if necessary) and, third, any sortable timestamp column. The rows
+/// are ordered by the timestamp on the server and streamed into the cases one by one, so only
+/// the activity sequences are kept in memory, not the rows themselves. Rows with a `NULL` case
+/// id or activity are skipped.
+///
+/// # Example
+/// ```no_run
+/// let mut client = postgres::Client::connect("host=localhost user=egypt", postgres::NoTls)?;
+/// let log = egypt::postgres::query_variant_log(
+///     &mut client,
+///     "SELECT order_id::text, step, created_at FROM order_events WHERE created_at > $1",
+///     &[&"2024-01-01"],
+/// )?;
+/// # Ok::<(), postgres::Error>(())
+/// ```
+pub fn query_variant_log(
+    client: &mut Client,
+    query: &str,
+    params: &[&(dyn ToSql + Sync)],
+) -> Result<VariantLog, postgres::Error> {
+    let mut rows = client.query_raw(ordered_query(query).as_str(), params.iter().copied())?;
+
+    let mut events = EventCollector::new();
+    while let Some(row) = rows.next()? {
+        if let (Some(case_id), Some(activity)) = (row.try_get(0)?, row.try_get(1)?) {
+            events.push(case_id, activity);
+        }
+    }
+
+    Ok(events.finish())
+}
+
+// Wraps the user's query so the events arrive in timestamp order
+fn ordered_query(query: &str) -> String {
+    let query = query.trim().trim_end_matches(';');
+    format!("SELECT * FROM ({}) AS egypt_events ORDER BY 3", query)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ordered_query() {
+        assert_eq!(
+            ordered_query(" SELECT a, b, c FROM events;\n"),
+            "SELECT * FROM (SELECT a, b, c FROM events) AS egypt_events ORDER BY 3"
+        );
+    }
+
+    // needs a running server, e.g. EGYPT_TEST_POSTGRES="host=localhost user=postgres"
+    #[test]
+    fn test_query_variant_log() {
+        let Ok(config) = std::env::var("EGYPT_TEST_POSTGRES") else {
+            return;
+        };
+        let mut client = Client::connect(&config, postgres::NoTls).unwrap();
+
+        let log = query_variant_log(
+            &mut client,
+            "SELECT * FROM (VALUES
+                 ('c1', 'B', 2), ('c1', 'A', 1), ('c2', 'A', 3), ('c2', 'B', 4), ('c3', NULL, 5)
+             ) AS events (case_id, activity, ts)
+             WHERE ts > $1",
+            &[&0],
+        )
+        .unwrap();
+        assert_eq!(log.len(), 2);
+        assert_eq!(log.variant_count(), 1);
+        assert_eq!(log.trace(0), ["A", "B"]);
+    }
+}
@@ -0,0 +1,93 @@
+/// Seed used by every stochastic analysis unless the caller provides one.
+pub const DEFAULT_SEED: u64 = 42;
+
+/// A small, deterministic pseudo random number generator (SplitMix64).
+///
+/// All stochastic features (sampling, resampling, shuffling, simulation) take one of these
+/// instead of drawing from the OS entropy source, so that an analysis run with the same seed
+/// always produces the same numbers, on every platform (including wasm).
+#[derive(Debug, Clone)]
+pub struct SeededRng {
+    state: u64,
+}
+
+impl Default for SeededRng {
+    fn default() -> Self {
+        Self::new(DEFAULT_SEED)
+    }
+}
+
+impl SeededRng {
+    pub fn new(seed: u64) -> Self {
+        SeededRng { state: seed }
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    /// Returns a uniformly distributed float in `[0, 1)`.
+    pub fn next_f64(&mut self) -> f64 {
+        // use the upper 53 bits, which is exactly the precision of an f64 mantissa
+        (self.next_u64() >> 11) as f64 / (1u64 << 53) as f64
+    }
+
+    /// Returns a uniformly distributed index in `[0, upper)`.
+    ///
+    /// # Panics
+    /// Panics if `upper` is zero.
+    pub fn gen_index(&mut self, upper: usize) -> usize {
+        assert!(upper > 0, "Upper bound must be greater than 0");
+        (self.next_f64() * upper as f64) as usize
+    }
+
+    /// Shuffles the given slice in place (Fisher-Yates).
+    pub fn shuffle<T>(&mut self, items: &mut [T]) {
+        for i in (1..items.len()).rev() {
+            let j = self.gen_index(i + 1);
+            items.swap(i, j);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_same_seed_same_sequence() {
+        let mut a = SeededRng::new(7);
+        let mut b = SeededRng::new(7);
+        let a: Vec<u64> = (0..10).map(|_| a.next_u64()).collect();
+        let b: Vec<u64> = (0..10).map(|_| b.next_u64()).collect();
+        assert_eq!(a, b);
+
+        let mut c = SeededRng::new(8);
+        let c: Vec<u64> = (0..10).map(|_| c.next_u64()).collect();
+        assert_ne!(a, c);
+    }
+
+    #[test]
+    fn test_gen_index_in_range() {
+        let mut rng = SeededRng::default();
+        for _ in 0..1000 {
+            assert!(rng.gen_index(5) < 5);
+            let f = rng.next_f64();
+            assert!((0.0..1.0).contains(&f));
+        }
+    }
+
+    #[test]
+    fn test_shuffle_is_permutation() {
+        let mut rng = SeededRng::default();
+        let mut items: Vec<usize> = (0..20).collect();
+        rng.shuffle(&mut items);
+        let mut sorted = items.clone();
+        sorted.sort();
+        assert_eq!(sorted, (0..20).collect::<Vec<_>>());
+    }
+}
@@ -0,0 +1,181 @@
+use serde::{Deserialize, Serialize};
+
+use crate::dfg::DirectlyFollowsGraph;
+use crate::i18n::{Language, Message};
+use crate::simplicity::Simplicity;
+use crate::variant_log::VariantLog;
+
+/// Sweeps the edge filter threshold of the directly-follows graph and recommends the one with
+/// the best trade-off between fitness and simplicity.
+///
+/// The threshold is relative: a threshold of `t` keeps the edges occurring at least
+/// `t * frequency of the most frequent edge` times. Every threshold is scored with
+/// `fitness_weight * fitness + (1 - fitness_weight) * simplicity`, where the fitness is
+/// [`DirectlyFollowsGraph::fitness`] and the simplicity is the share of the edges that were
+/// filtered out.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ThresholdSweep {
+    /// Number of steps between threshold 0 (all edges) and 1 (only the most frequent edges).
+    pub steps: usize,
+    /// Weight of the fitness in the score, between 0 and 1.
+    pub fitness_weight: f64,
+}
+
+impl Default for ThresholdSweep {
+    fn default() -> Self {
+        ThresholdSweep {
+            steps: 20,
+            fitness_weight: 0.7,
+        }
+    }
+}
+
+/// Fitness, simplicity and score of one threshold of a [`ThresholdSweep`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TradeOff {
+    pub threshold: f64,
+    pub fitness: f64,
+    /// Share of the edges that were filtered out.
+    pub simplicity: f64,
+    pub score: f64,
+    /// Size and complexity of the filtered graph.
+    pub model: Simplicity,
+}
+
+/// The trade-off curve of a [`ThresholdSweep`] and the threshold with the best score.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ThresholdRecommendation {
+    /// One entry per threshold, from 0 to 1.
+    pub curve: Vec<TradeOff>,
+    /// Index of the recommended threshold in `curve`.
+    pub recommended: usize,
+}
+
+impl ThresholdSweep {
+    /// Runs the sweep on the directly-follows graph of `log`; `None` if the graph has no
+    /// edges. Of thresholds with the same score, the lowest one is recommended.
+    pub fn run(&self, log: &VariantLog) -> Option<ThresholdRecommendation> {
+        let dfg = DirectlyFollowsGraph::from_variant_log(log);
+        let total_edges = dfg.edges().count();
+        let max_frequency = dfg.edges().map(|(_, _, frequency)| frequency).max()?;
+        let fitness_weight = self.fitness_weight.clamp(0.0, 1.0);
+        let steps = self.steps.max(1);
+
+        let curve: Vec<TradeOff> = (0..=steps)
+            .map(|step| {
+                let threshold = step as f64 / steps as f64;
+                let min_frequency = (threshold * max_frequency as f64).ceil() as usize;
+                let filtered = dfg.filter_edges(min_frequency);
+                let fitness = filtered.fitness(log);
+                let simplicity = 1.0 - filtered.edges().count() as f64 / total_edges as f64;
+                TradeOff {
+                    threshold,
+                    fitness,
+                    simplicity,
+                    score: fitness_weight * fitness + (1.0 - fitness_weight) * simplicity,
+                    model: Simplicity::of_dfg(&filtered),
+                }
+            })
+            .collect();
+
+        let mut recommended = 0;
+        for (idx, trade_off) in curve.iter().enumerate() {
+            if trade_off.score > curve[recommended].score {
+                recommended = idx;
+            }
+        }
+        Some(ThresholdRecommendation { curve, recommended })
+    }
+}
+
+impl ThresholdRecommendation {
+    pub fn recommended(&self) -> &TradeOff {
+        &self.curve[self.recommended]
+    }
+
+    /// The recommended threshold, followed by the trade-off curve with one line per
+    /// threshold; the recommended line is marked with `*`.
+    pub fn report(&self, language: Language) -> String {
+        let mut output = format!(
+            "{:<48}{:.2}\n{:<12}{:<10}{:<12}{:<10}{:<14}{}\n",
+            format!("{}:", Message::RecommendedThreshold.text(language)),
+            self.recommended().threshold,
+            Message::Threshold.text(language),
+            Message::Fitness.text(language),
+            Message::Simplicity.text(language),
+            Message::Score.text(language),
+            Message::ModelEdges.text(language),
+            Message::AverageConnectorDegree.text(language),
+        );
+        for (idx, trade_off) in self.curve.iter().enumerate() {
+            let marker = if idx == self.recommended { "*" } else { " " };
+            output.push_str(&format!(
+                "{}{:<11.2}{:<10.4}{:<12.4}{:<10.4}{:<14}{:.4}\n",
+                marker,
+                trade_off.threshold,
+                trade_off.fitness,
+                trade_off.simplicity,
+                trade_off.score,
+                trade_off.model.edges,
+                trade_off.model.average_connector_degree
+            ));
+        }
+        output
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn log(traces: &[(&[&str], usize)]) -> VariantLog {
+        VariantLog::from_traces(
+            traces
+                .iter()
+                .flat_map(|(trace, frequency)| {
+                    vec![trace.iter().map(|s| s.to_string()).collect(); *frequency]
+                })
+                .collect(),
+        )
+    }
+
+    #[test]
+    fn test_sweep() {
+        // a main path and rare noise edges
+        let log = log(&[
+            (&["A", "B", "C"], 20),
+            (&["A", "C", "B"], 1),
+            (&["B", "A", "C"], 1),
+        ]);
+        let recommendation = ThresholdSweep {
+            steps: 4,
+            fitness_weight: 0.5,
+        }
+        .run(&log)
+        .unwrap();
+
+        assert_eq!(recommendation.curve.len(), 5);
+        let first = recommendation.curve[0];
+        assert_eq!((first.fitness, first.simplicity), (1.0, 0.0));
+        // dropping the noise keeps almost all of the fitness
+        let recommended = recommendation.recommended();
+        assert_eq!(recommended.threshold, 0.25);
+        assert_eq!(recommended.model.edges, 2);
+        assert!(recommended.fitness > 0.9);
+        assert!(recommendation
+            .report(Language::English)
+            .starts_with("Recommended threshold:"));
+
+        // without any weight on simplicity, nothing should be filtered
+        let fitness_only = ThresholdSweep {
+            steps: 4,
+            fitness_weight: 1.0,
+        }
+        .run(&log)
+        .unwrap();
+        assert_eq!(fitness_only.recommended().threshold, 0.0);
+
+        assert_eq!(ThresholdSweep::default().run(&VariantLog::new()), None);
+    }
+}
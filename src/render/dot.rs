@@ -0,0 +1,173 @@
+use std::collections::{HashMap, HashSet};
+
+use crate::dfg::DirectlyFollowsGraph;
+use crate::grouping::{group_mapping, ActivityGroup};
+use crate::render::theme::text_color_on;
+use crate::render::Theme;
+
+/// Exports a directly-follows graph in the Graphviz DOT language, with the same nodes, edges
+/// and colors as [`crate::render::graph::grouped_dfg_svg`]. The activities of every group are
+/// either `collapsed` into one node, drawn as a stack of boxes, or placed in a cluster subgraph
+/// labelled with the group name.
+pub fn dfg_dot(
+    dfg: &DirectlyFollowsGraph,
+    groups: &[ActivityGroup],
+    collapsed: bool,
+    theme: &Theme,
+) -> String {
+    let contracted;
+    let dfg = if collapsed && !groups.is_empty() {
+        contracted = dfg.rename_activities(&group_mapping(groups));
+        &contracted
+    } else {
+        dfg
+    };
+    // node ids don't depend on the labels, so activities may be called `start` or `end`
+    let ids: HashMap<&str, String> = dfg
+        .activities()
+        .iter()
+        .enumerate()
+        .map(|(idx, activity)| (activity.as_str(), format!("a{}", idx)))
+        .collect();
+    let node = |activity: &str, attributes: &str| {
+        format!(
+            "\"{}\" [label=\"{}\\n{}\"{}];\n",
+            ids[activity],
+            escape_dot(activity),
+            dfg.activity_frequency(activity),
+            attributes
+        )
+    };
+    // groups are filled with their own color, like in the SVG
+    let fill = |color: &str| {
+        format!(", fillcolor=\"{}\", fontcolor=\"{}\"", color, text_color_on(color))
+    };
+
+    let font = format!(
+        "fontname=\"{}\", fontsize={}",
+        escape_dot(&theme.font_family),
+        theme.font_size
+    );
+    let mut dot = format!(
+        "digraph process_map {{\n\
+         rankdir=TB;\n\
+         bgcolor=\"{background}\";\n\
+         node [shape=box, style=\"rounded,filled\", color=\"{background}\", \
+         fillcolor=\"{accent}\", fontcolor=\"{accent_text}\", {font}];\n\
+         edge [color=\"{muted}\", fontcolor=\"{muted}\", {font}];\n\
+         start [shape=circle, label=\"\", width=0.3, color=\"{muted}\", \
+         fillcolor=\"{muted}\"];\n\
+         end [shape=doublecircle, label=\"\", width=0.3, color=\"{muted}\", \
+         fillcolor=\"{background}\"];\n",
+        background = theme.background_color(),
+        accent = theme.accent_color(),
+        accent_text = text_color_on(theme.accent_color()),
+        muted = theme.muted_text_color(),
+        font = font,
+    );
+    let mut grouped = HashSet::new();
+    for (idx, group) in groups.iter().enumerate() {
+        let color = theme.palette.color(idx + 1);
+        if collapsed {
+            let members: Vec<&str> = group.activities.iter().map(String::as_str).collect();
+            let tooltip = format!(
+                ", shape=box3d, tooltip=\"{}\"{}",
+                escape_dot(&members.join(", ")),
+                fill(color)
+            );
+            dot.push_str(&node(&group.name, &tooltip));
+            grouped.insert(group.name.as_str());
+        } else {
+            dot.push_str(&format!(
+                "subgraph cluster_{} {{\nlabel=\"{}\";\ncolor=\"{}\";\nfontcolor=\"{}\";\n",
+                idx,
+                escape_dot(&group.name),
+                color,
+                theme.text_color()
+            ));
+            for activity in group
+                .activities
+                .iter()
+                .filter(|a| ids.contains_key(a.as_str()))
+            {
+                dot.push_str(&node(activity, &fill(color)));
+                grouped.insert(activity.as_str());
+            }
+            dot.push_str("}\n");
+        }
+    }
+    for activity in dfg.activities() {
+        if !grouped.contains(activity.as_str()) {
+            dot.push_str(&node(activity, ""));
+        }
+    }
+
+    let mut edges: Vec<(String, String, usize)> = dfg
+        .edges()
+        .map(|(from, to, frequency)| (ids[from].clone(), ids[to].clone(), frequency))
+        .collect();
+    for activity in dfg.activities() {
+        let id = &ids[activity.as_str()];
+        match dfg.start_frequency(activity) {
+            0 => {}
+            frequency => edges.push(("start".to_string(), id.clone(), frequency)),
+        }
+        match dfg.end_frequency(activity) {
+            0 => {}
+            frequency => edges.push((id.clone(), "end".to_string(), frequency)),
+        }
+    }
+    // the edges of the graph come in no particular order
+    edges.sort();
+    let max_frequency = edges
+        .iter()
+        .map(|(_, _, frequency)| *frequency)
+        .max()
+        .unwrap_or(1);
+    for (from, to, frequency) in edges {
+        dot.push_str(&format!(
+            "\"{}\" -> \"{}\" [label=\"{}\", penwidth={:.1}];\n",
+            from,
+            to,
+            frequency,
+            1.0 + 3.0 * frequency as f64 / max_frequency as f64
+        ));
+    }
+    dot.push_str("}\n");
+    dot
+}
+
+fn escape_dot(text: &str) -> String {
+    text.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::grouping::GroupingStrategy;
+
+    #[test]
+    fn test_dfg_dot() {
+        let dfg = DirectlyFollowsGraph::from_traces([
+            ["Order: Create", "Order: Check", "Ship \"fast\""],
+            ["Order: Create", "Order: Check", "end"],
+        ]);
+        let theme = Theme::default();
+        let dot = dfg_dot(&dfg, &[], false, &theme);
+        assert!(dot.starts_with("digraph process_map {"));
+        assert!(dot.contains("bgcolor=\"#ffffff\""));
+        assert!(dot.contains("fillcolor=\"#4e79a7\", fontcolor=\"#ffffff\", fontname="));
+        assert!(dot.contains("label=\"Ship \\\"fast\\\"\\n1\""));
+        assert!(dot.contains("\"start\" -> \"a0\" [label=\"2\", penwidth=4.0];"));
+        // an activity called `end` is not the end node
+        assert!(dot.contains("\"a3\" -> \"end\""));
+
+        let groups = GroupingStrategy::Prefix(":".to_string()).groups(&dfg);
+        let clustered = dfg_dot(&dfg, &groups, false, &theme);
+        assert!(clustered.contains("subgraph cluster_0 {\nlabel=\"Order\";\ncolor=\"#f28e2b\";"));
+        let collapsed = dfg_dot(&dfg, &groups, true, &theme);
+        assert!(collapsed.contains("label=\"Order\\n4\", shape=box3d"));
+        assert!(collapsed.contains("fillcolor=\"#f28e2b\""));
+        assert!(collapsed.contains("\"a0\" -> \"a0\" [label=\"2\""));
+    }
+}
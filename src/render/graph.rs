@@ -0,0 +1,396 @@
+use std::collections::HashMap;
+
+use crate::dfg::DirectlyFollowsGraph;
+use crate::grouping::{group_mapping, ActivityGroup};
+use crate::render::layout::{layered_layout, Layout, LayoutSettings, Point};
+use crate::render::theme::text_color_on;
+use crate::render::{escape_xml, shorten, Theme};
+use crate::ExtendedPrefixAutomaton;
+
+const MARGIN: f64 = 20.0;
+// room right of the nodes for self-loops and shifted back edges
+const LOOP_WIDTH: f64 = 40.0;
+const MAX_EDGE_WIDTH: f64 = 4.0;
+// characters of an activity label that fit into a node
+const MAX_LABEL_LENGTH: usize = 16;
+// offset of the box behind a collapsed group
+const STACK_OFFSET: f64 = 4.0;
+const LEGEND_LINE: f64 = 18.0;
+
+/// Renders a directly-follows graph as a process map: the activities as boxes with their
+/// frequency, connected by arrows whose width grows with how often one activity directly
+/// followed the other. A circle above the activities leads to the start activities, a circle
+/// below them is reached from the end activities.
+///
+/// The graph is laid out top to bottom with [`layered_layout`], so no Graphviz is needed.
+pub fn dfg_svg(dfg: &DirectlyFollowsGraph, theme: &Theme) -> String {
+    grouped_dfg_svg(dfg, &[], false, theme)
+}
+
+/// Same as [`dfg_svg`], but with the activities of every group either `collapsed` into one
+/// super-node, drawn as a stack of boxes, or drawn in the color of their group, listed in a
+/// legend below the map.
+pub fn grouped_dfg_svg(
+    dfg: &DirectlyFollowsGraph,
+    groups: &[ActivityGroup],
+    collapsed: bool,
+    theme: &Theme,
+) -> String {
+    let contracted;
+    let dfg = if collapsed && !groups.is_empty() {
+        contracted = dfg.rename_activities(&group_mapping(groups));
+        &contracted
+    } else {
+        dfg
+    };
+    // fill and tooltip of every node that stands for a group or belongs to one
+    let mut group_nodes: HashMap<&str, (&str, String)> = HashMap::new();
+    for (idx, group) in groups.iter().enumerate() {
+        let color = theme.palette.color(idx + 1);
+        let members: Vec<&str> = group.activities.iter().map(String::as_str).collect();
+        if collapsed {
+            group_nodes.insert(
+                &group.name,
+                (color, format!("{}: {}", group.name, members.join(", "))),
+            );
+        } else {
+            for activity in members {
+                group_nodes.insert(activity, (color, format!("{} ({})", activity, group.name)));
+            }
+        }
+    }
+
+    let index: HashMap<&str, usize> = dfg
+        .activities()
+        .iter()
+        .enumerate()
+        .map(|(idx, activity)| (activity.as_str(), idx + 2))
+        .collect();
+    let (start, end) = (0, 1);
+
+    let mut edges: Vec<(usize, usize, usize)> = dfg
+        .edges()
+        .map(|(from, to, frequency)| (index[from], index[to], frequency))
+        .collect();
+    for activity in dfg.activities() {
+        let node = index[activity.as_str()];
+        match dfg.start_frequency(activity) {
+            0 => {}
+            frequency => edges.push((start, node, frequency)),
+        }
+        match dfg.end_frequency(activity) {
+            0 => {}
+            frequency => edges.push((node, end, frequency)),
+        }
+    }
+    // the edges of the graph come in no particular order
+    edges.sort();
+
+    let settings = LayoutSettings::default();
+    let layout = layered_layout(
+        index.len() + 2,
+        &edges
+            .iter()
+            .map(|&(from, to, _)| (from, to))
+            .collect::<Vec<_>>(),
+        &settings,
+    );
+    let offset = |point: Point| Point {
+        x: point.x + MARGIN,
+        y: point.y + MARGIN,
+    };
+
+    let legend_height = if collapsed {
+        0.0
+    } else {
+        LEGEND_LINE * groups.len() as f64
+    };
+    let mut svg = graph_header(&layout, legend_height, theme);
+    let max_frequency = edges.iter().map(|&(_, _, frequency)| frequency).max();
+    for (&(from, _, frequency), route) in edges.iter().zip(&layout.edges) {
+        let width = edge_width(frequency, max_frequency);
+        if route.is_empty() {
+            svg.push_str(&self_loop(
+                offset(layout.nodes[from]),
+                settings.node_width / 2.0,
+                frequency,
+                width,
+                theme,
+            ));
+        } else {
+            svg.push_str(&edge(
+                &route.iter().map(|&point| offset(point)).collect::<Vec<_>>(),
+                &frequency.to_string(),
+                width,
+                theme,
+            ));
+        }
+    }
+
+    let radius = settings.node_height / 2.0;
+    for (node, filled) in [(start, true), (end, false)] {
+        let center = offset(layout.nodes[node]);
+        svg.push_str(&format!(
+            "<circle cx=\"{:.1}\" cy=\"{:.1}\" r=\"{:.1}\" fill=\"{}\" stroke=\"{}\" stroke-width=\"2\"/>\n",
+            center.x,
+            center.y,
+            radius / 2.0,
+            if filled { theme.muted_text_color() } else { theme.background_color() },
+            theme.muted_text_color()
+        ));
+    }
+    for activity in dfg.activities() {
+        let center = offset(layout.nodes[index[activity.as_str()]]);
+        let (fill, title) = match group_nodes.get(activity.as_str()) {
+            Some((color, title)) => (*color, title.clone()),
+            None => (theme.accent_color(), activity.clone()),
+        };
+        let (x, y) = (center.x - settings.node_width / 2.0, center.y - radius);
+        if collapsed && group_nodes.contains_key(activity.as_str()) {
+            // a stack of boxes for the activities hidden in the super-node
+            svg.push_str(&format!(
+                "<rect x=\"{:.1}\" y=\"{:.1}\" width=\"{:.1}\" height=\"{:.1}\" rx=\"6\" fill=\"{}\" stroke=\"{}\"/>\n",
+                x + STACK_OFFSET,
+                y - STACK_OFFSET,
+                settings.node_width,
+                settings.node_height,
+                fill,
+                theme.background_color()
+            ));
+        }
+        svg.push_str(&format!(
+            "<rect x=\"{:.1}\" y=\"{:.1}\" width=\"{:.1}\" height=\"{:.1}\" rx=\"6\" fill=\"{}\" stroke=\"{}\"><title>{}</title></rect>\n",
+            x,
+            y,
+            settings.node_width,
+            settings.node_height,
+            fill,
+            theme.background_color(),
+            escape_xml(&title)
+        ));
+        svg.push_str(&format!(
+            "<text x=\"{:.1}\" y=\"{:.1}\" fill=\"{}\" text-anchor=\"middle\">{}<tspan x=\"{:.1}\" dy=\"1.2em\" font-size=\"0.8em\">{}</tspan></text>\n",
+            center.x,
+            center.y - 3.0,
+            text_color_on(fill),
+            escape_xml(&shorten(activity, MAX_LABEL_LENGTH)),
+            center.x,
+            dfg.activity_frequency(activity)
+        ));
+    }
+    if !collapsed {
+        for (idx, group) in groups.iter().enumerate() {
+            let y = layout.height + 2.0 * MARGIN + LEGEND_LINE * idx as f64;
+            svg.push_str(&format!(
+                "<rect x=\"{:.1}\" y=\"{:.1}\" width=\"12\" height=\"12\" fill=\"{}\"/>\n\
+                 <text x=\"{:.1}\" y=\"{:.1}\">{}</text>\n",
+                MARGIN,
+                y,
+                theme.palette.color(idx + 1),
+                MARGIN + 18.0,
+                y + 10.0,
+                escape_xml(&group.name)
+            ));
+        }
+    }
+    svg.push_str("</svg>\n");
+    svg
+}
+
+/// Renders an extended prefix automaton: the states as circles colored by their partition,
+/// the transitions as arrows labelled with their activity and frequency, and the root at the
+/// top.
+pub fn epa_svg(epa: &ExtendedPrefixAutomaton, theme: &Theme) -> String {
+    let settings = LayoutSettings {
+        node_width: 40.0,
+        node_height: 40.0,
+        node_gap: 20.0,
+        layer_gap: 50.0,
+    };
+    let edges: Vec<(usize, usize)> = epa
+        .transitions
+        .iter()
+        .map(|(source, _, target)| (source.0, target.0))
+        .collect();
+    let layout = layered_layout(epa.states.len(), &edges, &settings);
+    let offset = |point: Point| Point {
+        x: point.x + MARGIN,
+        y: point.y + MARGIN,
+    };
+
+    let mut svg = graph_header(&layout, 0.0, theme);
+    let frequencies: Vec<usize> = epa
+        .transitions
+        .iter()
+        .map(|&(source, activity, _)| epa.transition_frequency(source, activity).unwrap_or(0))
+        .collect();
+    let max_frequency = frequencies.iter().copied().max();
+    for ((&(_, activity, _), &frequency), route) in
+        epa.transitions.iter().zip(&frequencies).zip(&layout.edges)
+    {
+        svg.push_str(&edge(
+            &route.iter().map(|&point| offset(point)).collect::<Vec<_>>(),
+            &format!("{} ({})", activity, frequency),
+            edge_width(frequency, max_frequency),
+            theme,
+        ));
+    }
+
+    for id in epa.state_ids() {
+        let center = offset(layout.nodes[id.0]);
+        let fill = match epa.state(id).partition {
+            Some(partition) => theme.palette.color(partition),
+            None => theme.background_color(),
+        };
+        svg.push_str(&format!(
+            "<circle cx=\"{:.1}\" cy=\"{:.1}\" r=\"{:.1}\" fill=\"{}\" stroke=\"{}\"><title>{}: {}</title></circle>\n",
+            center.x,
+            center.y,
+            settings.node_height / 2.0,
+            fill,
+            theme.muted_text_color(),
+            id,
+            epa.state_frequency(id)
+        ));
+        svg.push_str(&format!(
+            "<text x=\"{:.1}\" y=\"{:.1}\" fill=\"{}\" text-anchor=\"middle\" font-size=\"0.8em\">{}</text>\n",
+            center.x,
+            center.y + 4.0,
+            text_color_on(fill),
+            id
+        ));
+    }
+    svg.push_str("</svg>\n");
+    svg
+}
+
+// The SVG header fitting the layout and a legend below it, with the arrow head of the edges
+fn graph_header(layout: &Layout, legend_height: f64, theme: &Theme) -> String {
+    let mut svg = theme.svg_header(
+        layout.width + 2.0 * MARGIN + LOOP_WIDTH,
+        layout.height + 2.0 * MARGIN + legend_height,
+    );
+    svg.push_str(&format!(
+        "<defs><marker id=\"arrow\" viewBox=\"0 0 10 10\" refX=\"10\" refY=\"5\" markerWidth=\"8\" markerHeight=\"8\" markerUnits=\"userSpaceOnUse\" orient=\"auto\"><path d=\"M 0 0 L 10 5 L 0 10 z\" fill=\"{}\"/></marker></defs>\n",
+        theme.muted_text_color()
+    ));
+    svg
+}
+
+fn edge_width(frequency: usize, max_frequency: Option<usize>) -> f64 {
+    match max_frequency {
+        Some(max) if max > 0 => 1.0 + (MAX_EDGE_WIDTH - 1.0) * frequency as f64 / max as f64,
+        _ => 1.0,
+    }
+}
+
+// An arrow along `route`, labelled in the middle of its middle segment
+fn edge(route: &[Point], label: &str, width: f64, theme: &Theme) -> String {
+    let path: Vec<String> = route
+        .iter()
+        .map(|point| format!("{:.1},{:.1}", point.x, point.y))
+        .collect();
+    let middle = route.len() / 2;
+    let (a, b) = (route[middle.saturating_sub(1)], route[middle]);
+    format!(
+        "<polyline points=\"{}\" fill=\"none\" stroke=\"{}\" stroke-width=\"{:.1}\" marker-end=\"url(#arrow)\"/>\n\
+         <text x=\"{:.1}\" y=\"{:.1}\" fill=\"{}\" font-size=\"0.8em\">{}</text>\n",
+        path.join(" "),
+        theme.muted_text_color(),
+        width,
+        (a.x + b.x) / 2.0 + 4.0,
+        (a.y + b.y) / 2.0,
+        theme.muted_text_color(),
+        escape_xml(label)
+    )
+}
+
+// A loop leaving and entering the right side of the node centered at `center`
+fn self_loop(
+    center: Point,
+    half_width: f64,
+    frequency: usize,
+    width: f64,
+    theme: &Theme,
+) -> String {
+    let x = center.x + half_width;
+    format!(
+        "<path d=\"M {:.1},{:.1} C {:.1},{:.1} {:.1},{:.1} {:.1},{:.1}\" fill=\"none\" stroke=\"{}\" stroke-width=\"{:.1}\" marker-end=\"url(#arrow)\"/>\n\
+         <text x=\"{:.1}\" y=\"{:.1}\" fill=\"{}\" font-size=\"0.8em\">{}</text>\n",
+        x,
+        center.y - 8.0,
+        x + LOOP_WIDTH * 0.75,
+        center.y - 24.0,
+        x + LOOP_WIDTH * 0.75,
+        center.y + 24.0,
+        x,
+        center.y + 8.0,
+        theme.muted_text_color(),
+        width,
+        x + LOOP_WIDTH * 0.6,
+        center.y + 4.0,
+        theme.muted_text_color(),
+        frequency
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::variant_log::VariantLog;
+
+    fn log() -> VariantLog {
+        VariantLog::from_traces(vec![
+            vec!["A".to_string(), "B".to_string(), "B".to_string()],
+            vec!["A".to_string(), "C".to_string()],
+            vec!["A".to_string(), "C".to_string()],
+        ])
+    }
+
+    #[test]
+    fn test_dfg_svg() {
+        let dfg = DirectlyFollowsGraph::from_variant_log(&log());
+        let svg = dfg_svg(&dfg, &Theme::default());
+
+        assert_eq!(svg.matches("<rect x=").count(), 3);
+        assert_eq!(svg.matches("<circle").count(), 2);
+        // A -> B, A -> C, start -> A, B -> end and C -> end
+        assert_eq!(svg.matches("<polyline").count(), 5);
+        // the arrow head and the loop on B
+        assert_eq!(svg.matches("<path").count(), 2);
+        assert!(svg.starts_with("<svg"));
+        assert!(svg.ends_with("</svg>\n"));
+    }
+
+    #[test]
+    fn test_grouped_dfg_svg() {
+        let dfg = DirectlyFollowsGraph::from_variant_log(&log());
+        let groups = [ActivityGroup {
+            name: "B or C".to_string(),
+            activities: ["B".to_string(), "C".to_string()].into(),
+        }];
+        let theme = Theme::default();
+
+        // A and the group, which is drawn as a stack of two boxes
+        let collapsed = grouped_dfg_svg(&dfg, &groups, true, &theme);
+        assert_eq!(collapsed.matches("<rect x=").count(), 3);
+        assert!(collapsed.contains("<title>B or C: B, C</title>"));
+
+        let expanded = grouped_dfg_svg(&dfg, &groups, false, &theme);
+        // the three activities and the legend entry
+        assert_eq!(expanded.matches("<rect x=").count(), 4);
+        assert!(expanded.contains(">B or C</text>"));
+        assert_eq!(expanded.matches(theme.palette.color(1)).count(), 3);
+    }
+
+    #[test]
+    fn test_epa_svg() {
+        let epa = ExtendedPrefixAutomaton::from_variant_log(&log());
+        let svg = epa_svg(&epa, &Theme::default());
+
+        assert_eq!(svg.matches("<circle").count(), epa.states.len());
+        assert_eq!(svg.matches("<polyline").count(), epa.transitions.len());
+        assert!(svg.contains(">A (3)</text>"));
+        assert!(svg.contains(">root</text>"));
+    }
+}
@@ -0,0 +1,297 @@
+//! Layered (Sugiyama-style) layout of directed graphs, so process maps can be drawn as SVG
+//! without Graphviz.
+//!
+//! The layout breaks cycles by reversing back edges, assigns every node the layer of the
+//! longest path leading to it, routes edges spanning several layers through dummy nodes,
+//! orders the layers with the barycenter heuristic to reduce crossings and finally centers
+//! every layer horizontally.
+
+/// Sizes and spacing of a [`layered_layout`], in pixels.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct LayoutSettings {
+    pub node_width: f64,
+    pub node_height: f64,
+    /// Horizontal space between the nodes of a layer.
+    pub node_gap: f64,
+    /// Vertical space between layers.
+    pub layer_gap: f64,
+}
+
+impl Default for LayoutSettings {
+    fn default() -> Self {
+        LayoutSettings {
+            node_width: 120.0,
+            node_height: 36.0,
+            node_gap: 30.0,
+            layer_gap: 60.0,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Point {
+    pub x: f64,
+    pub y: f64,
+}
+
+/// Positions of the nodes and routes of the edges of a graph, top to bottom.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Layout {
+    /// Center of every node, indexed like the nodes of the graph.
+    pub nodes: Vec<Point>,
+    /// Layer of every node, 0 being the top layer.
+    pub layers: Vec<usize>,
+    /// Route of every edge, indexed like the edges of the graph: from the border of its source
+    /// to the border of its target, bending in every layer it crosses. Empty for self-loops,
+    /// which are left to the renderer.
+    pub edges: Vec<Vec<Point>>,
+    pub width: f64,
+    pub height: f64,
+}
+
+// Rounds of barycenter ordering, each sweeping down and up once
+const ORDERING_ROUNDS: usize = 4;
+
+/// Lays out the graph with the nodes `0..nodes` and the given directed edges.
+pub fn layered_layout(nodes: usize, edges: &[(usize, usize)], settings: &LayoutSettings) -> Layout {
+    let reversed = back_edges(nodes, edges);
+    let oriented: Vec<Option<(usize, usize)>> = edges
+        .iter()
+        .zip(&reversed)
+        .map(|(&(from, to), &reversed)| match (from == to, reversed) {
+            (true, _) => None,
+            (false, false) => Some((from, to)),
+            (false, true) => Some((to, from)),
+        })
+        .collect();
+    let layers = longest_path_layers(nodes, oriented.iter().flatten().copied());
+
+    // split edges spanning several layers into chains through dummy nodes
+    let mut layer_of = layers.clone();
+    let mut successors: Vec<Vec<usize>> = vec![Vec::new(); nodes];
+    let mut predecessors: Vec<Vec<usize>> = vec![Vec::new(); nodes];
+    let chains: Vec<Vec<usize>> = oriented
+        .iter()
+        .map(|edge| {
+            let Some((from, to)) = *edge else {
+                return Vec::new();
+            };
+            let mut chain = vec![from];
+            for layer in layers[from] + 1..layers[to] {
+                layer_of.push(layer);
+                successors.push(Vec::new());
+                predecessors.push(Vec::new());
+                chain.push(layer_of.len() - 1);
+            }
+            chain.push(to);
+            for pair in chain.windows(2) {
+                successors[pair[0]].push(pair[1]);
+                predecessors[pair[1]].push(pair[0]);
+            }
+            chain
+        })
+        .collect();
+
+    let layer_count = layers.iter().max().map_or(0, |max| max + 1);
+    let mut order: Vec<Vec<usize>> = vec![Vec::new(); layer_count];
+    for (node, &layer) in layer_of.iter().enumerate() {
+        order[layer].push(node);
+    }
+    for _ in 0..ORDERING_ROUNDS {
+        for layer in 1..layer_count {
+            reorder(&mut order, layer, layer - 1, &predecessors);
+        }
+        for layer in (0..layer_count.saturating_sub(1)).rev() {
+            reorder(&mut order, layer, layer + 1, &successors);
+        }
+    }
+
+    // dummy nodes only take up the space of a gap
+    let slot = |node: usize| {
+        if node < nodes {
+            settings.node_width + settings.node_gap
+        } else {
+            settings.node_gap
+        }
+    };
+    let layer_widths: Vec<f64> = order
+        .iter()
+        .map(|layer| layer.iter().map(|&node| slot(node)).sum::<f64>())
+        .collect();
+    let width = layer_widths.iter().copied().fold(0.0, f64::max);
+    let mut positions = vec![Point { x: 0.0, y: 0.0 }; layer_of.len()];
+    for (layer, layer_nodes) in order.iter().enumerate() {
+        // without the gap after the last node of the widest layer
+        let mut x = (width - layer_widths[layer] - settings.node_gap) / 2.0;
+        let y =
+            layer as f64 * (settings.node_height + settings.layer_gap) + settings.node_height / 2.0;
+        for &node in layer_nodes {
+            positions[node] = Point {
+                x: x + slot(node) / 2.0,
+                y,
+            };
+            x += slot(node);
+        }
+    }
+
+    let half_height = settings.node_height / 2.0;
+    let edges = chains
+        .iter()
+        .zip(&reversed)
+        .map(|(chain, &reversed)| {
+            if chain.is_empty() {
+                return Vec::new();
+            }
+            let mut route: Vec<Point> = chain.iter().map(|&node| positions[node]).collect();
+            // leave the source at its bottom and enter the target at its top
+            route[0].y += half_height;
+            let end = route.len() - 1;
+            route[end].y -= half_height;
+            if reversed {
+                // keep back edges apart from the forward edges between the same nodes
+                for point in route.iter_mut() {
+                    point.x += settings.node_width / 4.0;
+                }
+                route.reverse();
+            }
+            route
+        })
+        .collect();
+
+    positions.truncate(nodes);
+    Layout {
+        nodes: positions,
+        layers,
+        edges,
+        width: (width - settings.node_gap).max(0.0),
+        height: (layer_count as f64 * (settings.node_height + settings.layer_gap)
+            - settings.layer_gap)
+            .max(0.0),
+    }
+}
+
+// Marks the edges that close a cycle in a depth-first search, in node order
+fn back_edges(nodes: usize, edges: &[(usize, usize)]) -> Vec<bool> {
+    let mut outgoing: Vec<Vec<usize>> = vec![Vec::new(); nodes];
+    for (idx, &(from, _)) in edges.iter().enumerate() {
+        outgoing[from].push(idx);
+    }
+    // 0: unvisited, 1: on the stack, 2: done
+    let mut state = vec![0u8; nodes];
+    let mut reversed = vec![false; edges.len()];
+    for root in 0..nodes {
+        if state[root] != 0 {
+            continue;
+        }
+        state[root] = 1;
+        let mut stack = vec![(root, 0)];
+        while let Some((node, next)) = stack.last_mut() {
+            let node = *node;
+            match outgoing[node].get(*next) {
+                Some(&edge) => {
+                    *next += 1;
+                    let to = edges[edge].1;
+                    match state[to] {
+                        0 => {
+                            state[to] = 1;
+                            stack.push((to, 0));
+                        }
+                        1 => reversed[edge] = to != node,
+                        _ => {}
+                    }
+                }
+                None => {
+                    state[node] = 2;
+                    stack.pop();
+                }
+            }
+        }
+    }
+    reversed
+}
+
+// Layer of every node in an acyclic graph: the length of the longest path leading to it
+fn longest_path_layers(nodes: usize, edges: impl Iterator<Item = (usize, usize)>) -> Vec<usize> {
+    let mut outgoing: Vec<Vec<usize>> = vec![Vec::new(); nodes];
+    let mut incoming = vec![0; nodes];
+    for (from, to) in edges {
+        outgoing[from].push(to);
+        incoming[to] += 1;
+    }
+    let mut layers = vec![0; nodes];
+    let mut ready: Vec<usize> = (0..nodes).filter(|&node| incoming[node] == 0).collect();
+    while let Some(node) = ready.pop() {
+        for &to in &outgoing[node] {
+            layers[to] = layers[to].max(layers[node] + 1);
+            incoming[to] -= 1;
+            if incoming[to] == 0 {
+                ready.push(to);
+            }
+        }
+    }
+    layers
+}
+
+// Sorts `layer` by the mean position of the neighbors of its nodes in `fixed`; nodes without
+// neighbors there keep their position
+fn reorder(order: &mut [Vec<usize>], layer: usize, fixed: usize, neighbors: &[Vec<usize>]) {
+    let mut position = vec![0.0; neighbors.len()];
+    for (idx, &node) in order[fixed].iter().enumerate() {
+        position[node] = idx as f64;
+    }
+    let mut keyed: Vec<(f64, usize)> = order[layer]
+        .iter()
+        .enumerate()
+        .map(|(idx, &node)| {
+            let adjacent = &neighbors[node];
+            let barycenter = if adjacent.is_empty() {
+                idx as f64
+            } else {
+                adjacent.iter().map(|&n| position[n]).sum::<f64>() / adjacent.len() as f64
+            };
+            (barycenter, node)
+        })
+        .collect();
+    keyed.sort_by(|a, b| a.0.total_cmp(&b.0));
+    order[layer] = keyed.into_iter().map(|(_, node)| node).collect();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_layers_and_routes() {
+        // A -> B -> C, A -> C, C -> A and a self-loop on B
+        let edges = [(0, 1), (1, 2), (0, 2), (2, 0), (1, 1)];
+        let settings = LayoutSettings::default();
+        let layout = layered_layout(3, &edges, &settings);
+
+        assert_eq!(layout.layers, [0, 1, 2]);
+        assert!(layout.nodes[0].y < layout.nodes[1].y);
+        // the edge skipping a layer bends once, in the middle layer
+        assert_eq!(layout.edges[2].len(), 3);
+        assert_eq!(layout.edges[2][1].y, layout.nodes[1].y);
+        // the back edge starts at the top of C and ends at the bottom of A
+        let back = &layout.edges[3];
+        assert_eq!(back.len(), 3);
+        assert_eq!(back[0].y, layout.nodes[2].y - settings.node_height / 2.0);
+        assert_eq!(back[2].y, layout.nodes[0].y + settings.node_height / 2.0);
+        assert!(layout.edges[4].is_empty());
+        assert_eq!(
+            layout.height,
+            3.0 * settings.node_height + 2.0 * settings.layer_gap
+        );
+    }
+
+    #[test]
+    fn test_crossing_reduction() {
+        // 0 -> 3 and 1 -> 2 cross in the initial order
+        let layout = layered_layout(4, &[(0, 3), (1, 2)], &LayoutSettings::default());
+        assert!(layout.nodes[0].x < layout.nodes[1].x);
+        assert!(layout.nodes[3].x < layout.nodes[2].x);
+
+        let empty = layered_layout(0, &[], &LayoutSettings::default());
+        assert_eq!((empty.width, empty.height), (0.0, 0.0));
+    }
+}
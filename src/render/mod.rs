@@ -0,0 +1,66 @@
+use chrono::Duration;
+
+pub mod dot;
+pub mod graph;
+pub mod layout;
+pub mod theme;
+pub mod timeline;
+pub mod variants;
+
+pub use theme::Theme;
+
+/// Escapes text for use in SVG/HTML content and attribute values.
+pub fn escape_xml(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+/// Cuts `label` to `max_chars` characters, ending in `…` if it was longer.
+pub fn shorten(label: &str, max_chars: usize) -> String {
+    if label.chars().count() <= max_chars {
+        label.to_string()
+    } else {
+        let prefix: String = label.chars().take(max_chars.saturating_sub(1)).collect();
+        format!("{}…", prefix)
+    }
+}
+
+/// Formats a duration with its two most significant units, e.g. `2d 3h`, `5m 10s` or `0s`.
+pub fn format_duration(duration: Duration) -> String {
+    let sign = if duration < Duration::zero() { "-" } else { "" };
+    let seconds = duration.num_seconds().abs();
+    let units = [
+        ("d", seconds / 86_400),
+        ("h", seconds / 3_600 % 24),
+        ("m", seconds / 60 % 60),
+        ("s", seconds % 60),
+    ];
+
+    let parts: Vec<String> = units
+        .iter()
+        .skip_while(|(_, value)| *value == 0)
+        .take(2)
+        .filter(|(_, value)| *value > 0)
+        .map(|(unit, value)| format!("{}{}", value, unit))
+        .collect();
+    if parts.is_empty() {
+        "0s".to_string()
+    } else {
+        format!("{}{}", sign, parts.join(" "))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_format_duration() {
+        assert_eq!(format_duration(Duration::seconds(0)), "0s");
+        assert_eq!(format_duration(Duration::seconds(310)), "5m 10s");
+        assert_eq!(format_duration(Duration::hours(51) + Duration::seconds(5)), "2d 3h");
+        assert_eq!(format_duration(Duration::hours(-2)), "-2h");
+    }
+}
@@ -0,0 +1,157 @@
+use serde::{Deserialize, Serialize};
+
+/// Colors used for activities (or other categories) in visual exports.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Palette {
+    /// The Tableau 10 palette.
+    #[default]
+    Default,
+    /// The Okabe-Ito palette, distinguishable with the common forms of color blindness.
+    ColorBlindSafe,
+    Grayscale,
+}
+
+const DEFAULT_COLORS: [&str; 10] = [
+    "#4e79a7", "#f28e2b", "#e15759", "#76b7b2", "#59a14f", "#edc948", "#b07aa1", "#ff9da7",
+    "#9c755f", "#bab0ac",
+];
+const COLOR_BLIND_SAFE_COLORS: [&str; 8] = [
+    "#e69f00", "#56b4e9", "#009e73", "#f0e442", "#0072b2", "#d55e00", "#cc79a7", "#999999",
+];
+const GRAYSCALE_COLORS: [&str; 5] = ["#252525", "#525252", "#737373", "#969696", "#bdbdbd"];
+
+impl Palette {
+    pub fn colors(&self) -> &'static [&'static str] {
+        match self {
+            Palette::Default => &DEFAULT_COLORS,
+            Palette::ColorBlindSafe => &COLOR_BLIND_SAFE_COLORS,
+            Palette::Grayscale => &GRAYSCALE_COLORS,
+        }
+    }
+
+    /// Returns the `idx`-th color, cycling if there are more categories than colors.
+    pub fn color(&self, idx: usize) -> &'static str {
+        let colors = self.colors();
+        colors[idx % colors.len()]
+    }
+}
+
+impl std::str::FromStr for Palette {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "default" => Ok(Palette::Default),
+            "color-blind-safe" => Ok(Palette::ColorBlindSafe),
+            "grayscale" => Ok(Palette::Grayscale),
+            _ => Err(format!("Unknown palette: {}", s)),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Background {
+    #[default]
+    Light,
+    Dark,
+}
+
+/// Visual settings shared by all rendered artifacts (SVG views, exports and the web UI), so
+/// they look the same wherever they are shown.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Theme {
+    pub palette: Palette,
+    pub background: Background,
+    /// Base font size in pixels.
+    pub font_size: f64,
+    pub font_family: String,
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Theme {
+            palette: Palette::default(),
+            background: Background::default(),
+            font_size: 12.0,
+            font_family: "sans-serif".to_string(),
+        }
+    }
+}
+
+impl Theme {
+    pub fn background_color(&self) -> &'static str {
+        match self.background {
+            Background::Light => "#ffffff",
+            Background::Dark => "#393939",
+        }
+    }
+
+    pub fn text_color(&self) -> &'static str {
+        match self.background {
+            Background::Light => "#000000",
+            Background::Dark => "#ffffff",
+        }
+    }
+
+    /// Color for secondary information like annotations and axes.
+    pub fn muted_text_color(&self) -> &'static str {
+        match self.background {
+            Background::Light => "#666666",
+            Background::Dark => "#aaaaaa",
+        }
+    }
+
+    /// Color for single-series elements, e.g. the bars of a timeline.
+    pub fn accent_color(&self) -> &'static str {
+        self.palette.color(0)
+    }
+
+    /// The opening `<svg>` tag with the theme's font and a background covering the image.
+    pub fn svg_header(&self, width: f64, height: f64) -> String {
+        format!(
+            "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{:.0}\" height=\"{:.0}\" font-family=\"{}\" font-size=\"{}\" fill=\"{}\">\n\
+             <rect width=\"100%\" height=\"100%\" fill=\"{}\"/>\n",
+            width,
+            height,
+            self.font_family,
+            self.font_size,
+            self.text_color(),
+            self.background_color()
+        )
+    }
+}
+
+/// Returns black or white, whichever is more readable on the `#rrggbb` color `fill`.
+pub fn text_color_on(fill: &str) -> &'static str {
+    let channel = |idx: usize| {
+        fill.get(idx..idx + 2)
+            .and_then(|hex| u8::from_str_radix(hex, 16).ok())
+            .unwrap_or(0) as f64
+    };
+    let luminance = 0.299 * channel(1) + 0.587 * channel(3) + 0.114 * channel(5);
+    if luminance > 150.0 {
+        "#000000"
+    } else {
+        "#ffffff"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_palette_cycles() {
+        assert_eq!(Palette::ColorBlindSafe.color(0), Palette::ColorBlindSafe.color(8));
+        assert_eq!("grayscale".parse(), Ok(Palette::Grayscale));
+    }
+
+    #[test]
+    fn test_text_color_on() {
+        assert_eq!(text_color_on("#f0e442"), "#000000");
+        assert_eq!(text_color_on("#0072b2"), "#ffffff");
+    }
+}
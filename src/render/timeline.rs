@@ -0,0 +1,112 @@
+use crate::calendar::BusinessCalendar;
+use crate::render::{escape_xml, format_duration, Theme};
+use crate::timeline::CaseTimeline;
+
+const WIDTH: f64 = 900.0;
+const LABEL_WIDTH: f64 = 180.0;
+const ROW_HEIGHT: f64 = 24.0;
+const HEADER_HEIGHT: f64 = 30.0;
+const MIN_BAR_WIDTH: f64 = 4.0;
+
+/// Renders a case as a Gantt chart: one row per activity instance, with a bar from its start to
+/// its completion on a shared time axis and the waiting time since the previous instance.
+/// Durations and waiting times are labeled in business time if there is a calendar.
+pub fn timeline_svg(timeline: &CaseTimeline, theme: &Theme, calendar: Option<&BusinessCalendar>) -> String {
+    let rows = timeline.instances.len();
+    let height = HEADER_HEIGHT * 2.0 + ROW_HEIGHT * rows as f64;
+    let chart_width = WIDTH - LABEL_WIDTH - 100.0;
+
+    let mut svg = theme.svg_header(WIDTH, height);
+    svg.push_str(&format!(
+        "<text x=\"0\" y=\"18\" font-weight=\"bold\">Case {}</text>\n",
+        escape_xml(&timeline.case_id)
+    ));
+
+    let Some((first, last)) = timeline.span() else {
+        svg.push_str("<text x=\"0\" y=\"48\">No timestamps recorded</text>\n</svg>\n");
+        return svg;
+    };
+    let total = (last - first).num_milliseconds().max(1) as f64;
+    let x = |time: chrono::DateTime<chrono::Utc>| {
+        LABEL_WIDTH + (time - first).num_milliseconds() as f64 / total * chart_width
+    };
+
+    let waiting_times = timeline.waiting_times_in(calendar);
+    for (row, (instance, waiting)) in timeline.instances.iter().zip(waiting_times).enumerate() {
+        let y = HEADER_HEIGHT + ROW_HEIGHT * row as f64;
+        svg.push_str(&format!(
+            "<text x=\"0\" y=\"{}\">{}</text>\n",
+            y + 16.0,
+            escape_xml(&instance.activity)
+        ));
+
+        let Some(begin) = instance.begin() else {
+            continue;
+        };
+        let end = instance.complete.unwrap_or(begin);
+        let bar_x = x(begin);
+        let bar_width = (x(end) - bar_x).max(MIN_BAR_WIDTH);
+        let tooltip = match instance.duration_in(calendar) {
+            Some(duration) => format!("{} ({})", instance.activity, format_duration(duration)),
+            None => instance.activity.clone(),
+        };
+        svg.push_str(&format!(
+            "<rect x=\"{:.1}\" y=\"{}\" width=\"{:.1}\" height=\"{}\" fill=\"{}\"><title>{}</title></rect>\n",
+            bar_x,
+            y + 4.0,
+            bar_width,
+            ROW_HEIGHT - 8.0,
+            theme.accent_color(),
+            escape_xml(&tooltip)
+        ));
+        if let Some(waiting) = waiting {
+            svg.push_str(&format!(
+                "<text x=\"{:.1}\" y=\"{}\" fill=\"{}\">+{}</text>\n",
+                bar_x + bar_width + 4.0,
+                y + 16.0,
+                theme.muted_text_color(),
+                format_duration(waiting)
+            ));
+        }
+    }
+
+    let axis_y = HEADER_HEIGHT + ROW_HEIGHT * rows as f64 + 16.0;
+    svg.push_str(&format!(
+        "<text x=\"{}\" y=\"{}\">{}</text>\n<text x=\"{}\" y=\"{}\" text-anchor=\"end\">{}</text>\n",
+        LABEL_WIDTH,
+        axis_y,
+        first.format("%Y-%m-%d %H:%M:%S"),
+        LABEL_WIDTH + chart_width,
+        axis_y,
+        last.format("%Y-%m-%d %H:%M:%S")
+    ));
+    svg.push_str("</svg>\n");
+    svg
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{TimeZone, Utc};
+
+    #[test]
+    fn test_timeline_svg() {
+        let at = |hour| Some(Utc.with_ymd_and_hms(2024, 1, 1, hour, 0, 0).unwrap());
+        let timeline = CaseTimeline::of(
+            "c<1>",
+            &[("Check", at(10), at(11), None), ("Ship", None, at(14), None)],
+        );
+
+        let theme = Theme::default();
+        let svg = timeline_svg(&timeline, &theme, None);
+        assert!(svg.contains("Case c&lt;1&gt;"));
+        assert_eq!(svg.matches(&format!("fill=\"{}\"", theme.accent_color())).count(), 2);
+        assert!(svg.contains("+3h"));
+
+        let calendar = BusinessCalendar {
+            end: chrono::NaiveTime::from_hms_opt(12, 0, 0).unwrap(),
+            ..Default::default()
+        };
+        assert!(timeline_svg(&timeline, &theme, Some(&calendar)).contains("+1h"));
+    }
+}
@@ -0,0 +1,103 @@
+use std::collections::HashMap;
+
+use crate::render::theme::text_color_on;
+use crate::render::{escape_xml, shorten, Theme};
+
+const CHEVRON_WIDTH: f64 = 110.0;
+const CHEVRON_HEIGHT: f64 = 28.0;
+const CHEVRON_TIP: f64 = 10.0;
+const ROW_GAP: f64 = 12.0;
+const INFO_WIDTH: f64 = 140.0;
+// characters of an activity label that fit into a chevron
+const MAX_LABEL_LENGTH: usize = 13;
+
+/// Renders variants as rows of colored chevrons, one per activity, with the number and share
+/// of cases following each variant. Every activity keeps its color across all rows.
+///
+/// # Parameters
+/// - `variants`: the variants to draw with their frequencies, e.g. from
+///   [`crate::variant_log::VariantLog::top_variants`].
+/// - `total_cases`: the number of cases of the whole log, used for the percentages.
+pub fn variants_svg(variants: &[(&[String], usize)], total_cases: usize, theme: &Theme) -> String {
+    let mut colors: HashMap<&str, &str> = HashMap::new();
+    for activity in variants.iter().flat_map(|(variant, _)| variant.iter()) {
+        let next_color = theme.palette.color(colors.len());
+        colors.entry(activity.as_str()).or_insert(next_color);
+    }
+
+    let longest = variants.iter().map(|(variant, _)| variant.len()).max().unwrap_or(0);
+    let width = INFO_WIDTH + CHEVRON_WIDTH * longest as f64 + CHEVRON_TIP;
+    let height = (CHEVRON_HEIGHT + ROW_GAP) * variants.len() as f64;
+
+    let mut svg = theme.svg_header(width, height);
+    for (row, (variant, frequency)) in variants.iter().enumerate() {
+        let y = (CHEVRON_HEIGHT + ROW_GAP) * row as f64;
+        let share = if total_cases == 0 {
+            0.0
+        } else {
+            *frequency as f64 / total_cases as f64 * 100.0
+        };
+        svg.push_str(&format!(
+            "<text x=\"0\" y=\"{:.1}\">#{} {} cases ({:.1}%)</text>\n",
+            y + CHEVRON_HEIGHT / 2.0 + 4.0,
+            row + 1,
+            frequency,
+            share
+        ));
+
+        for (idx, activity) in variant.iter().enumerate() {
+            let x = INFO_WIDTH + CHEVRON_WIDTH * idx as f64;
+            let color = colors[activity.as_str()];
+            svg.push_str(&format!(
+                "<polygon points=\"{}\" fill=\"{}\"><title>{}</title></polygon>\n",
+                chevron_points(x, y),
+                color,
+                escape_xml(activity)
+            ));
+            svg.push_str(&format!(
+                "<text x=\"{:.1}\" y=\"{:.1}\" fill=\"{}\">{}</text>\n",
+                x + CHEVRON_TIP + 4.0,
+                y + CHEVRON_HEIGHT / 2.0 + 4.0,
+                text_color_on(color),
+                escape_xml(&shorten(activity, MAX_LABEL_LENGTH))
+            ));
+        }
+    }
+    svg.push_str("</svg>\n");
+    svg
+}
+
+fn chevron_points(x: f64, y: f64) -> String {
+    let (w, h, tip) = (CHEVRON_WIDTH - 2.0, CHEVRON_HEIGHT, CHEVRON_TIP);
+    [
+        (x, y),
+        (x + w - tip, y),
+        (x + w, y + h / 2.0),
+        (x + w - tip, y + h),
+        (x, y + h),
+        (x + tip, y + h / 2.0),
+    ]
+    .iter()
+    .map(|(px, py)| format!("{:.1},{:.1}", px, py))
+    .collect::<Vec<_>>()
+    .join(" ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_variants_svg() {
+        let first = vec!["A".to_string(), "Send invoice to customer".to_string()];
+        let second = vec!["A".to_string()];
+        let theme = Theme::default();
+        let svg = variants_svg(&[(&first, 3), (&second, 1)], 4, &theme);
+
+        assert_eq!(svg.matches("<polygon").count(), 3);
+        assert!(svg.contains("#1 3 cases (75.0%)"));
+        assert!(svg.contains("Send invoice…"));
+        // the same activity gets the same color in every row
+        assert_eq!(svg.matches(theme.palette.color(0)).count(), 2);
+    }
+}
@@ -0,0 +1,497 @@
+//! Repair suggestions for deviating cases, to tell data-quality problems from genuine process
+//! deviations: a case that lacks an activity which nearly all comparable cases have, or that
+//! recorded an activity under a label close to the expected one, more likely has a gap or a
+//! typo in its data than took another path through the process.
+//!
+//! The suggestions come either from the alignments with a model or from high-confidence
+//! dependencies of the log itself, e.g. "cases with `A` nearly always contain `B`".
+
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+
+use serde::{Deserialize, Serialize};
+
+use crate::activity_labels::{label_similarity, similarity};
+use crate::alignment::{AlignmentMove, AlignmentReport, TraceAlignment};
+use crate::i18n::{Language, Message};
+use crate::variant_log::VariantLog;
+
+/// Example cases kept per suggestion.
+const MAX_EXAMPLES: usize = 3;
+
+/// How confident a dependency has to be for its violations to be suggested as repairs.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct RepairSettings {
+    /// Share of the cases with the first activity that also contain the second one.
+    pub min_confidence: f64,
+}
+
+impl Default for RepairSettings {
+    fn default() -> Self {
+        RepairSettings {
+            min_confidence: 0.9,
+        }
+    }
+}
+
+/// A change of a case that would remove a deviation. The positions refer to the neighbouring
+/// events of the case, `None` if the position isn't restricted on that side.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Repair {
+    /// The activity is missing and should be inserted between `after` and `before`.
+    Insert {
+        activity: String,
+        after: Option<String>,
+        before: Option<String>,
+    },
+    /// The event recorded as `from` is probably `to`.
+    Relabel { from: String, to: String },
+    /// The activity was recorded, but belongs between `after` and `before`.
+    Move {
+        activity: String,
+        after: Option<String>,
+        before: Option<String>,
+    },
+    /// The event doesn't belong to the case, e.g. a duplicate.
+    Remove { activity: String },
+}
+
+impl Repair {
+    pub fn describe(&self, language: Language) -> String {
+        let position = |after: &Option<String>, before: &Option<String>| {
+            let bounds: Vec<String> = [
+                after
+                    .as_ref()
+                    .map(|after| Message::AfterActivity.format(language, &[after])),
+                before
+                    .as_ref()
+                    .map(|before| Message::BeforeActivity.format(language, &[before])),
+            ]
+            .into_iter()
+            .flatten()
+            .collect();
+            if bounds.is_empty() {
+                String::new()
+            } else {
+                format!(" ({})", bounds.join(", "))
+            }
+        };
+        match self {
+            Repair::Insert {
+                activity,
+                after,
+                before,
+            } => Message::InsertActivity.format(language, &[activity]) + &position(after, before),
+            Repair::Relabel { from, to } => Message::RelabelActivity.format(language, &[from, to]),
+            Repair::Move {
+                activity,
+                after,
+                before,
+            } => Message::MoveActivity.format(language, &[activity]) + &position(after, before),
+            Repair::Remove { activity } => Message::RemoveActivity.format(language, &[activity]),
+        }
+    }
+}
+
+/// A repair that applies to several cases.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RepairSuggestion {
+    pub repair: Repair,
+    /// Cases the repair applies to.
+    pub cases: usize,
+    /// How likely the repair is right, from 0 to 1: for repairs from dependencies the
+    /// confidence of the violated dependency, for relabelings from a model the similarity of
+    /// both labels, and 1 for the other repairs from a model.
+    pub confidence: f64,
+    /// The first cases the repair applies to, in log order.
+    pub examples: Vec<String>,
+}
+
+/// The repairs of one alignment. An activity that is both a log-only and a model-only move
+/// is moved to the position of the model-only move. Between two synchronous moves, the
+/// remaining log-only and model-only moves are paired up as relabelings in their order, the
+/// model-only moves left over are insertions and the log-only moves left over removals.
+/// Positions refer to the neighbouring synchronous moves.
+pub fn alignment_repairs(alignment: &TraceAlignment) -> Vec<Repair> {
+    let mut counts: BTreeMap<&str, (usize, usize)> = BTreeMap::new();
+    for step in &alignment.moves {
+        match step {
+            AlignmentMove::LogOnly(activity) => counts.entry(activity).or_default().0 += 1,
+            AlignmentMove::ModelOnly(activity) => counts.entry(activity).or_default().1 += 1,
+            AlignmentMove::Synchronous(_) => {}
+        }
+    }
+    // per activity the log-only and model-only moves still to pair up as moves
+    let mut moved: BTreeMap<&str, (usize, usize)> = counts
+        .into_iter()
+        .map(|(activity, (log_only, model_only))| {
+            let moved = log_only.min(model_only);
+            (activity, (moved, moved))
+        })
+        .collect();
+
+    let mut repairs = Vec::new();
+    let mut previous: Option<String> = None;
+    // the deviations since the last synchronous move
+    let (mut misplaced, mut log_only, mut model_only) = (Vec::new(), Vec::new(), Vec::new());
+    // a last step without activity for the deviations at the end
+    for step in alignment.moves.iter().map(Some).chain([None]) {
+        let next = match step {
+            Some(AlignmentMove::LogOnly(activity)) => {
+                match moved.get_mut(activity.as_str()) {
+                    Some((log_side, _)) if *log_side > 0 => *log_side -= 1,
+                    _ => log_only.push(activity),
+                }
+                continue;
+            }
+            Some(AlignmentMove::ModelOnly(activity)) => {
+                match moved.get_mut(activity.as_str()) {
+                    Some((_, model_side)) if *model_side > 0 => {
+                        *model_side -= 1;
+                        misplaced.push(activity);
+                    }
+                    _ => model_only.push(activity),
+                }
+                continue;
+            }
+            Some(AlignmentMove::Synchronous(activity)) => Some(activity.clone()),
+            None => None,
+        };
+
+        for activity in misplaced.drain(..) {
+            repairs.push(Repair::Move {
+                activity: activity.clone(),
+                after: previous.clone(),
+                before: next.clone(),
+            });
+        }
+        let pairs = log_only.len().min(model_only.len());
+        for (from, to) in log_only.iter().zip(&model_only) {
+            repairs.push(Repair::Relabel {
+                from: from.to_string(),
+                to: to.to_string(),
+            });
+        }
+        for activity in &model_only[pairs..] {
+            repairs.push(Repair::Insert {
+                activity: activity.to_string(),
+                after: previous.clone(),
+                before: next.clone(),
+            });
+        }
+        for activity in &log_only[pairs..] {
+            repairs.push(Repair::Remove {
+                activity: activity.to_string(),
+            });
+        }
+        log_only.clear();
+        model_only.clear();
+        previous = next;
+    }
+    repairs
+}
+
+/// Repairs the alignments of `report` suggest for the cases of `log`, see
+/// [`alignment_repairs`], the most widespread first. Cases whose variant couldn't be aligned
+/// are left out.
+pub fn suggest_from_alignments(
+    report: &AlignmentReport,
+    log: &VariantLog,
+) -> Vec<RepairSuggestion> {
+    let by_variant: HashMap<&[String], BTreeMap<Repair, f64>> = report
+        .variants
+        .iter()
+        .filter_map(|(variant, _, alignment)| {
+            let repairs = alignment_repairs(alignment.as_ref()?)
+                .into_iter()
+                .map(|repair| {
+                    let confidence = match &repair {
+                        Repair::Relabel { from, to } => label_similarity(from, to),
+                        _ => 1.0,
+                    };
+                    (repair, confidence)
+                })
+                .collect();
+            Some((variant.as_slice(), repairs))
+        })
+        .collect();
+    aggregate(log, &by_variant)
+}
+
+// An activity missing in a variant according to the dependencies it violates
+struct MissingActivity<'a> {
+    // the activities it should follow and precede
+    after: Vec<&'a str>,
+    before: Vec<&'a str>,
+    // the highest confidence of the violated dependencies
+    confidence: f64,
+}
+
+/// Repairs for the cases of `log` that violate one of its high-confidence dependencies: if at
+/// least `min_confidence` of the cases with `A` also contain `B`, the cases with `A` but
+/// without `B` probably miss `B`, on the side of `A` where `B` occurs in most of the other
+/// cases. If several dependencies require the same activity, it goes between the closest of
+/// their activities. If such a case contains a label that looks like a misspelling of `B`
+/// (see [`crate::activity_labels`]), relabeling it is suggested instead. The most widespread
+/// suggestions come first.
+pub fn suggest_from_dependencies(
+    log: &VariantLog,
+    settings: &RepairSettings,
+) -> Vec<RepairSuggestion> {
+    let variants: Vec<(&[String], usize)> = log.variants().collect();
+    let presence: Vec<HashSet<&str>> = variants
+        .iter()
+        .map(|(variant, _)| variant.iter().map(String::as_str).collect())
+        .collect();
+    let activities: BTreeSet<&str> = presence.iter().flatten().copied().collect();
+
+    let mut missing: BTreeMap<(usize, &str), MissingActivity> = BTreeMap::new();
+    for &from in &activities {
+        for &to in &activities {
+            if from == to {
+                continue;
+            }
+            // cases with both activities, and with `to` after the first `from`
+            let (mut both, mut only_from, mut follows) = (0, 0, 0);
+            for ((variant, frequency), present) in variants.iter().zip(&presence) {
+                match (present.contains(from), present.contains(to)) {
+                    (true, true) => {
+                        both += frequency;
+                        let first = variant.iter().position(|activity| activity == from);
+                        if variant.iter().rposition(|activity| activity == to) > first {
+                            follows += frequency;
+                        }
+                    }
+                    (true, false) => only_from += frequency,
+                    _ => {}
+                }
+            }
+            if only_from == 0 || both == 0 {
+                continue;
+            }
+            let confidence = both as f64 / (both + only_from) as f64;
+            if confidence < settings.min_confidence {
+                continue;
+            }
+            for (idx, present) in presence.iter().enumerate() {
+                if !present.contains(from) || present.contains(to) {
+                    continue;
+                }
+                let activity = missing.entry((idx, to)).or_insert_with(|| MissingActivity {
+                    after: Vec::new(),
+                    before: Vec::new(),
+                    confidence,
+                });
+                if 2 * follows >= both {
+                    activity.after.push(from);
+                } else {
+                    activity.before.push(from);
+                }
+                activity.confidence = activity.confidence.max(confidence);
+            }
+        }
+    }
+
+    let mut by_variant: Vec<BTreeMap<Repair, f64>> = vec![BTreeMap::new(); variants.len()];
+    for ((idx, to), activity) in missing {
+        let variant = variants[idx].0;
+        let misspelled = variant
+            .iter()
+            .find(|activity| similarity(activity, to).is_some());
+        let repair = match misspelled {
+            Some(label) => Repair::Relabel {
+                from: label.clone(),
+                to: to.to_string(),
+            },
+            None => {
+                let position = |activity: &&str| variant.iter().position(|a| a == activity);
+                Repair::Insert {
+                    activity: to.to_string(),
+                    after: activity
+                        .after
+                        .into_iter()
+                        .max_by_key(position)
+                        .map(str::to_string),
+                    before: activity
+                        .before
+                        .into_iter()
+                        .min_by_key(position)
+                        .map(str::to_string),
+                }
+            }
+        };
+        let best = by_variant[idx].entry(repair).or_insert(activity.confidence);
+        *best = best.max(activity.confidence);
+    }
+
+    let by_variant: HashMap<&[String], BTreeMap<Repair, f64>> = variants
+        .iter()
+        .map(|(variant, _)| *variant)
+        .zip(by_variant)
+        .collect();
+    aggregate(log, &by_variant)
+}
+
+// Counts the cases of `log` per repair of their variant, the most widespread and then the
+// most confident first
+fn aggregate(
+    log: &VariantLog,
+    by_variant: &HashMap<&[String], BTreeMap<Repair, f64>>,
+) -> Vec<RepairSuggestion> {
+    let mut aggregated: BTreeMap<&Repair, RepairSuggestion> = BTreeMap::new();
+    for (case, trace) in log.cases().iter().zip(log.traces()) {
+        let Some(repairs) = by_variant.get(trace) else {
+            continue;
+        };
+        for (repair, &confidence) in repairs {
+            let suggestion = aggregated
+                .entry(repair)
+                .or_insert_with(|| RepairSuggestion {
+                    repair: repair.clone(),
+                    cases: 0,
+                    confidence,
+                    examples: Vec::new(),
+                });
+            suggestion.cases += 1;
+            suggestion.confidence = suggestion.confidence.max(confidence);
+            if suggestion.examples.len() < MAX_EXAMPLES {
+                suggestion.examples.push(case.case_id.clone());
+            }
+        }
+    }
+
+    let mut suggestions: Vec<RepairSuggestion> = aggregated.into_values().collect();
+    // stable, so equally ranked suggestions stay ordered by repair
+    suggestions.sort_by(|a, b| {
+        b.cases
+            .cmp(&a.cases)
+            .then(b.confidence.total_cmp(&a.confidence))
+    });
+    suggestions
+}
+
+/// One line per suggestion with its number of cases, confidence and example cases.
+pub fn report(suggestions: &[RepairSuggestion], language: Language) -> String {
+    let mut output = format!(
+        "{}:\n{:<56}{:<12}{:<12}{}\n",
+        Message::RepairSuggestions.text(language),
+        Message::Repair.text(language),
+        Message::Cases.text(language),
+        Message::Confidence.text(language),
+        Message::ExampleCases.text(language)
+    );
+    for suggestion in suggestions {
+        output.push_str(&format!(
+            "{:<56}{:<12}{:<12.4}{}\n",
+            suggestion.repair.describe(language),
+            suggestion.cases,
+            suggestion.confidence,
+            suggestion.examples.join(", ")
+        ));
+    }
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::variant_log::trace;
+
+    fn alignment(moves: &[(&str, char)]) -> TraceAlignment {
+        TraceAlignment {
+            moves: moves
+                .iter()
+                .map(|&(activity, side)| match side {
+                    'l' => AlignmentMove::LogOnly(activity.to_string()),
+                    'm' => AlignmentMove::ModelOnly(activity.to_string()),
+                    _ => AlignmentMove::Synchronous(activity.to_string()),
+                })
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn test_alignment_repairs() {
+        let between =
+            |after: &str, before: &str| (Some(after.to_string()), Some(before.to_string()));
+        let (after, before) = between("A", "C");
+        assert_eq!(
+            alignment_repairs(&alignment(&[
+                ("D", 'l'),
+                ("A", 's'),
+                ("Approve", 'l'),
+                ("Aprove", 'm'),
+                ("B", 'm'),
+                ("C", 's'),
+                ("D", 'm'),
+                ("X", 'l'),
+            ])),
+            [
+                Repair::Relabel {
+                    from: "Approve".to_string(),
+                    to: "Aprove".to_string()
+                },
+                Repair::Insert {
+                    activity: "B".to_string(),
+                    after,
+                    before
+                },
+                Repair::Move {
+                    activity: "D".to_string(),
+                    after: Some("C".to_string()),
+                    before: None
+                },
+                Repair::Remove {
+                    activity: "X".to_string()
+                },
+            ]
+        );
+        assert!(alignment_repairs(&alignment(&[("A", 's')])).is_empty());
+    }
+
+    #[test]
+    fn test_suggest_from_dependencies() {
+        let mut traces = vec![trace(&["Register", "Check", "Approve"]); 18];
+        traces.push(trace(&["Register", "Approve"]));
+        traces.push(trace(&["Register", "check", "Approve"]));
+        let log = VariantLog::from_traces(traces);
+
+        let suggestions = suggest_from_dependencies(&log, &RepairSettings::default());
+        let repairs: Vec<(&Repair, usize)> = suggestions
+            .iter()
+            .map(|suggestion| (&suggestion.repair, suggestion.cases))
+            .collect();
+        assert_eq!(
+            repairs,
+            [
+                (
+                    &Repair::Insert {
+                        activity: "Check".to_string(),
+                        after: Some("Register".to_string()),
+                        before: Some("Approve".to_string())
+                    },
+                    1
+                ),
+                (
+                    &Repair::Relabel {
+                        from: "check".to_string(),
+                        to: "Check".to_string()
+                    },
+                    1
+                ),
+            ]
+        );
+        assert_eq!(suggestions[0].confidence, 0.9);
+        assert_eq!(suggestions[0].examples, ["case_18"]);
+        let report = report(&suggestions, Language::English);
+        assert!(report.contains("insert Check (after Register, before Approve)"));
+        assert!(report.contains("relabel check as Check"));
+        assert!(suggest_from_dependencies(
+            &log,
+            &RepairSettings {
+                min_confidence: 0.95
+            }
+        )
+        .is_empty());
+    }
+}
@@ -0,0 +1,195 @@
+use std::collections::{BTreeMap, HashMap};
+
+use chrono::Duration;
+
+use crate::calendar::{elapsed, BusinessCalendar};
+use crate::i18n::{Language, Message};
+use crate::render::format_duration;
+use crate::timeline::CaseTimeline;
+
+/// Minimum, median, mean and maximum of a non-empty list of values.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Summary {
+    pub count: usize,
+    pub min: f64,
+    pub median: f64,
+    pub mean: f64,
+    pub max: f64,
+}
+
+impl Summary {
+    pub fn of(values: impl IntoIterator<Item = f64>) -> Option<Self> {
+        let mut values: Vec<f64> = values.into_iter().collect();
+        if values.is_empty() {
+            return None;
+        }
+        values.sort_by(f64::total_cmp);
+        let count = values.len();
+        let median = if count.is_multiple_of(2) {
+            (values[count / 2 - 1] + values[count / 2]) / 2.0
+        } else {
+            values[count / 2]
+        };
+        Some(Summary {
+            count,
+            min: values[0],
+            median,
+            mean: values.iter().sum::<f64>() / count as f64,
+            max: values[count - 1],
+        })
+    }
+}
+
+/// How far apart the consecutive occurrences of an activity within the same case are.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SelfDistances {
+    pub activity: String,
+    /// Number of cases the activity occurs in more than once.
+    pub repeating_cases: usize,
+    /// Position difference of consecutive occurrences, 1 if the activity directly repeats.
+    pub events: Vec<usize>,
+    /// Time between the beginnings of consecutive occurrences, if both were recorded.
+    pub times: Vec<Duration>,
+}
+
+impl SelfDistances {
+    pub fn event_summary(&self) -> Option<Summary> {
+        Summary::of(self.events.iter().map(|&distance| distance as f64))
+    }
+
+    /// Summary of [`SelfDistances::times`] in seconds.
+    pub fn time_summary(&self) -> Option<Summary> {
+        Summary::of(
+            self.times
+                .iter()
+                .map(|time| time.num_milliseconds() as f64 / 1000.0),
+        )
+    }
+}
+
+/// Collects the self-distances of every activity that occurs more than once in at least one
+/// case, sorted by activity. Times are measured in business time if there is a calendar.
+pub fn self_distances(
+    timelines: &[CaseTimeline],
+    calendar: Option<&BusinessCalendar>,
+) -> Vec<SelfDistances> {
+    let mut distances: BTreeMap<&str, SelfDistances> = BTreeMap::new();
+
+    for timeline in timelines {
+        // position and beginning of the last occurrence of every activity
+        let mut last: HashMap<&str, (usize, Option<_>)> = HashMap::new();
+        let mut repeating: Vec<&str> = Vec::new();
+        for (position, instance) in timeline.instances.iter().enumerate() {
+            let activity = instance.activity.as_str();
+            let begin = instance.begin();
+            if let Some((last_position, last_begin)) = last.insert(activity, (position, begin)) {
+                let entry = distances.entry(activity).or_insert_with(|| SelfDistances {
+                    activity: activity.to_string(),
+                    repeating_cases: 0,
+                    events: Vec::new(),
+                    times: Vec::new(),
+                });
+                entry.events.push(position - last_position);
+                if let (Some(last_begin), Some(begin)) = (last_begin, begin) {
+                    entry.times.push(elapsed(calendar, last_begin, begin));
+                }
+                if !repeating.contains(&activity) {
+                    repeating.push(activity);
+                    entry.repeating_cases += 1;
+                }
+            }
+        }
+    }
+
+    distances.into_values().collect()
+}
+
+/// One line per repeated activity with its number of repeating cases and the minimum, median
+/// and maximum self-distance in events and in time.
+pub fn report(distances: &[SelfDistances], language: Language) -> String {
+    let mut output = format!(
+        "{:<32}{:<12}{:<24}{}\n",
+        Message::Activity.text(language),
+        Message::Cases.text(language),
+        Message::DistanceInEvents.text(language),
+        Message::DistanceInTime.text(language)
+    );
+    for distance in distances {
+        let events = distance
+            .event_summary()
+            .map(|s| format!("{} / {} / {}", s.min, s.median, s.max))
+            .unwrap_or_default();
+        let times = distance
+            .time_summary()
+            .map(|s| {
+                let format = |seconds: f64| {
+                    format_duration(Duration::milliseconds((seconds * 1000.0) as i64))
+                };
+                format!(
+                    "{} / {} / {}",
+                    format(s.min),
+                    format(s.median),
+                    format(s.max)
+                )
+            })
+            .unwrap_or_else(|| "-".to_string());
+        output.push_str(&format!(
+            "{:<32}{:<12}{:<24}{}\n",
+            distance.activity, distance.repeating_cases, events, times
+        ));
+    }
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{TimeZone, Utc};
+
+    use super::*;
+    use crate::timeline::TestInstance;
+
+    fn completed(activity: &str, minute: i64) -> TestInstance<'_> {
+        let date = Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap() + Duration::minutes(minute);
+        (activity, None, Some(date), None)
+    }
+
+    #[test]
+    fn test_self_distances() {
+        let timelines = [
+            CaseTimeline::of(
+                "1",
+                &[completed("A", 0), completed("B", 10), completed("A", 20), completed("A", 25)],
+            ),
+            CaseTimeline::of("2", &[completed("A", 0), completed("A", 60)]),
+            CaseTimeline::of("3", &[completed("B", 0)]),
+        ];
+        let distances = self_distances(&timelines, None);
+
+        assert_eq!(distances.len(), 1);
+        let a = &distances[0];
+        assert_eq!(a.activity, "A");
+        assert_eq!(a.repeating_cases, 2);
+        assert_eq!(a.events, [2, 1, 1]);
+        assert_eq!(
+            a.times,
+            [
+                Duration::minutes(20),
+                Duration::minutes(5),
+                Duration::minutes(60)
+            ]
+        );
+
+        let events = a.event_summary().unwrap();
+        assert_eq!((events.min, events.median, events.max), (1.0, 1.0, 2.0));
+        assert_eq!(a.time_summary().unwrap().median, 1200.0);
+        assert!(report(&distances, Language::English).contains("5m / 20m / 1h"));
+    }
+
+    #[test]
+    fn test_summary() {
+        assert_eq!(Summary::of([]), None);
+        let summary = Summary::of([4.0, 1.0, 3.0, 2.0]).unwrap();
+        assert_eq!(summary.median, 2.5);
+        assert_eq!(summary.mean, 2.5);
+    }
+}
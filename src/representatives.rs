@@ -0,0 +1,217 @@
+//! Representative cases: a real case standing in for a variant or a cluster of variants, with
+//! its case id, duration and attributes, so reports and the variant explorer can point to a
+//! concrete example instead of an abstract activity sequence.
+
+use std::collections::{BTreeMap, HashMap};
+
+use chrono::Duration;
+
+use crate::calendar::{elapsed, BusinessCalendar};
+use crate::cohorts::DurationPercentiles;
+use crate::i18n::{Language, Message};
+use crate::render::format_duration;
+use crate::timeline::CaseTimeline;
+use crate::trace_diff::diff_traces;
+use crate::variant_log::VariantLog;
+
+/// What a case contributes to the choice and the description of a representative.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CaseSummary {
+    pub case_id: String,
+    pub trace: Vec<String>,
+    /// From the first to the last event, `None` without timestamps.
+    pub duration: Option<Duration>,
+    /// The attributes of the case, see [`crate::variant_log::CaseMetadata::attributes`].
+    pub attributes: BTreeMap<String, String>,
+}
+
+/// Summarizes every case of `log`, with the duration of its timeline in `timelines`, e.g. the
+/// analyzed log and timelines of [`crate::AnalysisResult`]. Durations are measured in business
+/// time if there is a calendar.
+pub fn case_summaries(
+    log: &VariantLog,
+    timelines: &[CaseTimeline],
+    calendar: Option<&BusinessCalendar>,
+) -> Vec<CaseSummary> {
+    let spans: HashMap<&str, _> = timelines
+        .iter()
+        .filter_map(|timeline| Some((timeline.case_id.as_str(), timeline.span()?)))
+        .collect();
+    log.cases()
+        .iter()
+        .zip(log.traces())
+        .map(|(case, trace)| CaseSummary {
+            case_id: case.case_id.clone(),
+            trace: trace.to_vec(),
+            duration: spans
+                .get(case.case_id.as_str())
+                .map(|&(first, last)| elapsed(calendar, first, last)),
+            attributes: case.attributes.clone(),
+        })
+        .collect()
+}
+
+/// The representative of a group of cases, e.g. a cluster of variants: among the cases of
+/// the medoid variant, whose trace needs the fewest insertions and deletions to turn into
+/// the traces of all cases of the group, the case with the duration closest to the median
+/// duration of that variant. Ties go to the more frequent variant and to the earlier case.
+/// `None` for no cases.
+pub fn representative<'a>(cases: &[&'a CaseSummary]) -> Option<&'a CaseSummary> {
+    // the variants in the order of their first case
+    let mut variants: Vec<(&[String], Vec<&CaseSummary>)> = Vec::new();
+    for &case in cases {
+        match variants
+            .iter_mut()
+            .find(|(trace, _)| *trace == case.trace.as_slice())
+        {
+            Some((_, variant_cases)) => variant_cases.push(case),
+            None => variants.push((&case.trace, vec![case])),
+        }
+    }
+
+    let distance = |trace: &[String]| -> usize {
+        variants
+            .iter()
+            .map(|(other, other_cases)| diff_traces(trace, other).cost() * other_cases.len())
+            .sum()
+    };
+    let (_, medoid_cases) = variants
+        .iter()
+        .map(|variant| (variant, distance(variant.0)))
+        .min_by(|((_, a_cases), a), ((_, b_cases), b)| {
+            a.cmp(b).then(b_cases.len().cmp(&a_cases.len()))
+        })?
+        .0;
+
+    let median = DurationPercentiles::of(
+        medoid_cases
+            .iter()
+            .filter_map(|case| case.duration)
+            .collect(),
+    )
+    .map(|percentiles| percentiles.median);
+    match median {
+        Some(median) => medoid_cases
+            .iter()
+            .filter_map(|&case| Some((case, (case.duration? - median).abs())))
+            .min_by_key(|&(_, deviation)| deviation)
+            .map(|(case, _)| case),
+        None => medoid_cases.first().copied(),
+    }
+}
+
+/// A case standing in for a group of cases.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Representative<'a> {
+    pub case: &'a CaseSummary,
+    /// Cases of the group.
+    pub cases: usize,
+    /// Median duration of the cases of the group, `None` without timestamps.
+    pub median_duration: Option<Duration>,
+}
+
+impl<'a> Representative<'a> {
+    /// The representative of `cases`, see [`representative`].
+    pub fn of(cases: &[&'a CaseSummary]) -> Option<Self> {
+        Some(Representative {
+            case: representative(cases)?,
+            cases: cases.len(),
+            median_duration: DurationPercentiles::of(
+                cases.iter().filter_map(|case| case.duration).collect(),
+            )
+            .map(|percentiles| percentiles.median),
+        })
+    }
+}
+
+/// The representatives of the `top` most frequent variants of `cases`, most frequent first;
+/// variants with the same frequency keep their order of first occurrence.
+pub fn variant_representatives(cases: &[CaseSummary], top: usize) -> Vec<Representative<'_>> {
+    let mut variants: Vec<Vec<&CaseSummary>> = Vec::new();
+    let mut variant_index: BTreeMap<&[String], usize> = BTreeMap::new();
+    for case in cases {
+        let idx = *variant_index.entry(&case.trace).or_insert_with(|| {
+            variants.push(Vec::new());
+            variants.len() - 1
+        });
+        variants[idx].push(case);
+    }
+    variants.sort_by_key(|variant_cases| std::cmp::Reverse(variant_cases.len()));
+    variants
+        .iter()
+        .take(top)
+        .filter_map(|variant_cases| Representative::of(variant_cases))
+        .collect()
+}
+
+/// One line per representative with its case id and duration, the number and median
+/// duration of the cases it stands for and its attributes.
+pub fn report(representatives: &[Representative], language: Language) -> String {
+    let mut output = format!(
+        "{}:\n{:<10}{:<24}{:<16}{:<10}{:<28}{}\n",
+        Message::RepresentativeCases.text(language),
+        Message::Variant.text(language),
+        Message::Case.text(language),
+        Message::Duration.text(language),
+        Message::Cases.text(language),
+        Message::MedianCycleTime.text(language),
+        Message::CaseAttributes.text(language)
+    );
+    let duration = |duration: Option<Duration>| duration.map_or("-".to_string(), format_duration);
+    for (idx, representative) in representatives.iter().enumerate() {
+        let attributes: Vec<String> = representative
+            .case
+            .attributes
+            .iter()
+            .map(|(key, value)| format!("{}={}", key, value))
+            .collect();
+        output.push_str(&format!(
+            "{:<10}{:<24}{:<16}{:<10}{:<28}{}\n",
+            idx + 1,
+            representative.case.case_id,
+            duration(representative.case.duration),
+            representative.cases,
+            duration(representative.median_duration),
+            attributes.join(", ")
+        ));
+    }
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn case(case_id: &str, trace: &[&str], hours: i64) -> CaseSummary {
+        CaseSummary {
+            case_id: case_id.to_string(),
+            trace: trace.iter().map(|activity| activity.to_string()).collect(),
+            duration: Some(Duration::hours(hours)),
+            attributes: BTreeMap::from([("channel".to_string(), "web".to_string())]),
+        }
+    }
+
+    #[test]
+    fn test_representative() {
+        let cases = [
+            case("1", &["A", "B", "C"], 10),
+            case("2", &["A", "B", "C"], 2),
+            case("3", &["A", "B", "C"], 4),
+            case("4", &["A", "C"], 1),
+            case("5", &["A", "B", "B", "C"], 1),
+        ];
+        let all: Vec<&CaseSummary> = cases.iter().collect();
+        // the most central variant, and in it the case with the median duration
+        assert_eq!(representative(&all).unwrap().case_id, "3");
+        assert_eq!(representative(&all[3..]).unwrap().case_id, "4");
+        assert!(representative(&[]).is_none());
+
+        let representatives = variant_representatives(&cases, 2);
+        assert_eq!(representatives.len(), 2);
+        assert_eq!(representatives[0].case.case_id, "3");
+        assert_eq!(representatives[0].cases, 3);
+        assert_eq!(representatives[0].median_duration, Some(Duration::hours(4)));
+        assert_eq!(representatives[1].case.case_id, "4");
+        assert!(report(&representatives, Language::English).contains("channel=web"));
+    }
+}
@@ -0,0 +1,199 @@
+//! Resource-aware dependencies: whether `B` follows `A` depending on who executes them, e.g.
+//! the check always comes after the entry when one clerk does both, but in any order when two
+//! different clerks share the work. The plain matrix mixes both contexts and can only show
+//! the weaker dependency.
+
+use std::collections::{BTreeMap, HashMap};
+
+use serde::{Deserialize, Serialize};
+
+use crate::i18n::{Language, Message};
+use crate::timeline::CaseTimeline;
+
+/// When a dependency holds in a resource context.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ResourceDependencySettings {
+    /// Share of the cases of a context in which `B` has to follow `A`.
+    pub threshold: f64,
+    /// Cases both contexts need for the pair to be compared.
+    pub min_cases: usize,
+}
+
+impl Default for ResourceDependencySettings {
+    fn default() -> Self {
+        ResourceDependencySettings {
+            threshold: 1.0,
+            min_cases: 2,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ResourceContext {
+    /// Both activities are executed by the same resource.
+    SameResource,
+    DifferentResources,
+}
+
+/// Cases with both activities in one resource context.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct ContextCases {
+    pub cases: usize,
+    /// Cases in which `B` follows `A`.
+    pub follows: usize,
+}
+
+impl ContextCases {
+    pub fn share(&self) -> f64 {
+        if self.cases == 0 {
+            0.0
+        } else {
+            self.follows as f64 / self.cases as f64
+        }
+    }
+}
+
+/// A dependency `from` → `to` that holds in one resource context only.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ResourceDependency {
+    pub from: String,
+    pub to: String,
+    pub same_resource: ContextCases,
+    pub different_resources: ContextCases,
+    /// The context the dependency holds in.
+    pub holds_only: ResourceContext,
+}
+
+/// Finds the activity pairs whose temporal dependency holds when both are executed by the
+/// same resource but not by different ones, or the other way round, ordered by pair.
+///
+/// Only the first instance of each activity of a case is considered, and only cases in which
+/// both instances have a resource.
+pub fn resource_dependencies(
+    timelines: &[CaseTimeline],
+    settings: &ResourceDependencySettings,
+) -> Vec<ResourceDependency> {
+    // the cases with the same resource, then the ones with different resources
+    let mut pairs: BTreeMap<(&str, &str), [ContextCases; 2]> = BTreeMap::new();
+    for timeline in timelines {
+        let mut first: HashMap<&str, (usize, &str)> = HashMap::new();
+        for (idx, instance) in timeline.instances.iter().enumerate() {
+            if let Some(resource) = &instance.resource {
+                first
+                    .entry(&instance.activity)
+                    .or_insert((idx, resource.as_str()));
+            }
+        }
+        for (&from, &(from_idx, from_resource)) in &first {
+            for (&to, &(to_idx, to_resource)) in &first {
+                if from == to {
+                    continue;
+                }
+                let context = &mut pairs.entry((from, to)).or_default()
+                    [usize::from(from_resource != to_resource)];
+                context.cases += 1;
+                if from_idx < to_idx {
+                    context.follows += 1;
+                }
+            }
+        }
+    }
+
+    pairs
+        .into_iter()
+        .filter(|(_, contexts)| {
+            contexts
+                .iter()
+                .all(|context| context.cases >= settings.min_cases.max(1))
+        })
+        .filter_map(|((from, to), [same, different])| {
+            let holds_only = match (
+                same.share() >= settings.threshold,
+                different.share() >= settings.threshold,
+            ) {
+                (true, false) => ResourceContext::SameResource,
+                (false, true) => ResourceContext::DifferentResources,
+                _ => return None,
+            };
+            Some(ResourceDependency {
+                from: from.to_string(),
+                to: to.to_string(),
+                same_resource: same,
+                different_resources: different,
+                holds_only,
+            })
+        })
+        .collect()
+}
+
+/// One line per dependency with the share of cases following it in both contexts.
+pub fn report(dependencies: &[ResourceDependency], language: Language) -> String {
+    let mut output = format!(
+        "{}:\n{:<48}{:<20}{:<20}{}\n",
+        Message::ResourceDependencies.text(language),
+        Message::ActivityPair.text(language),
+        Message::SameResource.text(language),
+        Message::DifferentResources.text(language),
+        Message::HoldsOnly.text(language)
+    );
+    let cases = |context: &ContextCases| format!("{}/{}", context.follows, context.cases);
+    for dependency in dependencies {
+        let holds_only = match dependency.holds_only {
+            ResourceContext::SameResource => Message::SameResource,
+            ResourceContext::DifferentResources => Message::DifferentResources,
+        };
+        output.push_str(&format!(
+            "{:<48}{:<20}{:<20}{}\n",
+            format!("{} -> {}", dependency.from, dependency.to),
+            cases(&dependency.same_resource),
+            cases(&dependency.different_resources),
+            holds_only.text(language)
+        ));
+    }
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::timeline::TestInstance;
+
+    fn performed<'a>(activity: &'a str, resource: &'a str) -> TestInstance<'a> {
+        (activity, None, None, Some(resource))
+    }
+
+    #[test]
+    fn test_resource_dependencies() {
+        let timelines = [
+            CaseTimeline::of(
+                "",
+                &[performed("Enter", "Ann"), performed("Check", "Ann"), performed("Close", "Bob")],
+            ),
+            CaseTimeline::of(
+                "",
+                &[performed("Enter", "Bob"), performed("Check", "Bob"), performed("Close", "Ann")],
+            ),
+            CaseTimeline::of(
+                "",
+                &[performed("Enter", "Ann"), performed("Check", "Bob"), performed("Close", "Ann")],
+            ),
+            CaseTimeline::of(
+                "",
+                &[performed("Check", "Ann"), performed("Enter", "Bob"), performed("Close", "Bob")],
+            ),
+        ];
+        let dependencies =
+            resource_dependencies(&timelines, &ResourceDependencySettings::default());
+
+        assert_eq!(dependencies.len(), 1);
+        let dependency = &dependencies[0];
+        assert_eq!(
+            (dependency.from.as_str(), dependency.to.as_str()),
+            ("Enter", "Check")
+        );
+        assert_eq!(dependency.holds_only, ResourceContext::SameResource);
+        assert_eq!(dependency.different_resources.share(), 0.5);
+        assert!(report(&dependencies, Language::English).contains("2/2"));
+    }
+}
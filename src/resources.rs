@@ -0,0 +1,157 @@
+//! Organizational views of a log: the cases projected onto the resources that executed their
+//! events, as variant logs, so the dependency matrix and the other analyses of activity
+//! sequences also show how the work is handed over between resources and what each one does.
+
+use std::collections::BTreeSet;
+
+use crate::parser::xes_extensions::ORG_RESOURCE;
+use crate::variant_log::{CaseMetadata, VariantLog};
+use crate::EventLog;
+
+impl EventLog {
+    /// The resources that executed at least one event, in the order of their names.
+    pub fn resources(&self) -> BTreeSet<String> {
+        self.traces
+            .iter()
+            .flat_map(|trace| &trace.events)
+            .filter_map(|event| event.resource())
+            .map(str::to_string)
+            .collect()
+    }
+
+    /// Who hands over to whom: per case, the resources of its events in order, with
+    /// consecutive events of the same resource as one step. Events without a resource are left
+    /// out.
+    pub fn handover_log(&self) -> VariantLog {
+        let mut log = VariantLog::new();
+        for trace in &self.traces {
+            let mut resources: Vec<String> = trace
+                .events
+                .iter()
+                .filter_map(|event| event.resource())
+                .map(str::to_string)
+                .collect();
+            resources.dedup();
+            log.push(
+                CaseMetadata {
+                    case_id: trace.case_id.clone(),
+                    ongoing: false,
+                    attributes: trace.attributes.clone(),
+                },
+                resources,
+            );
+        }
+        log
+    }
+
+    /// Who did what: per case and resource, the activities the resource executed, in order.
+    /// The cases are named `<case>/<resource>`, ordered by case and then by the first event of
+    /// the resource, and have the resource as `org:resource` attribute, so
+    /// [`VariantLog::group_by_attribute`] gives the work of every resource. Events without a
+    /// resource are left out.
+    pub fn resource_log(&self) -> VariantLog {
+        let mut log = VariantLog::new();
+        for trace in &self.traces {
+            let mut work: Vec<(&str, Vec<String>)> = Vec::new();
+            for event in &trace.events {
+                let Some(resource) = event.resource() else {
+                    continue;
+                };
+                let activity = event.activity.clone();
+                match work.iter_mut().find(|(other, _)| *other == resource) {
+                    Some((_, activities)) => activities.push(activity),
+                    None => work.push((resource, vec![activity])),
+                }
+            }
+            for (resource, activities) in work {
+                let mut attributes = trace.attributes.clone();
+                attributes.insert(ORG_RESOURCE.to_string(), resource.to_string());
+                log.push(
+                    CaseMetadata {
+                        case_id: format!("{}/{}", trace.case_id, resource),
+                        ongoing: false,
+                        attributes,
+                    },
+                    activities,
+                );
+            }
+        }
+        log
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::{parse_into_log_with_config, ImportConfig};
+    use crate::{LogEvent, Trace};
+
+    fn event(activity: &str, resource: Option<&str>) -> LogEvent {
+        let mut event = LogEvent::new(activity);
+        if let Some(resource) = resource {
+            event
+                .attributes
+                .insert(ORG_RESOURCE.to_string(), resource.to_string());
+        }
+        event
+    }
+
+    #[test]
+    fn test_resource_views() {
+        let log = EventLog {
+            traces: vec![Trace {
+                case_id: "c1".to_string(),
+                attributes: Default::default(),
+                events: vec![
+                    event("Register", Some("Pete")),
+                    event("Check", Some("Pete")),
+                    event("Decide", Some("Sara")),
+                    event("Archive", None),
+                    event("Notify", Some("Pete")),
+                ],
+            }],
+            ..Default::default()
+        };
+        assert_eq!(
+            log.resources().into_iter().collect::<Vec<_>>(),
+            ["Pete", "Sara"]
+        );
+        assert_eq!(log.handover_log().trace(0), ["Pete", "Sara", "Pete"]);
+
+        let work = log.resource_log();
+        assert_eq!(work.len(), 2);
+        assert_eq!(work.cases()[0].case_id, "c1/Pete");
+        assert_eq!(work.trace(0), ["Register", "Check", "Notify"]);
+        assert_eq!(work.trace(1), ["Decide"]);
+        let by_resource = work.group_by_attribute(ORG_RESOURCE);
+        assert_eq!(by_resource[&Some("Sara".to_string())].len(), 1);
+    }
+
+    #[test]
+    fn test_resource_key() {
+        let content = r#"<log>
+            <trace>
+                <string key="concept:name" value="c1"/>
+                <event>
+                    <string key="concept:name" value="A"/>
+                    <string key="org:resource" value="system"/>
+                    <string key="User" value="pete"/>
+                    <date key="time:timestamp" value="2024-01-01T10:00:00+00:00"/>
+                </event>
+                <event>
+                    <string key="concept:name" value="B"/>
+                    <string key="org:resource" value="system"/>
+                    <date key="time:timestamp" value="2024-01-01T11:00:00+00:00"/>
+                </event>
+            </trace>
+        </log>"#;
+        let config = ImportConfig {
+            resource_key: Some("User".to_string()),
+            ..Default::default()
+        };
+        let log = parse_into_log_with_config(None, Some(content), &config).unwrap();
+        let events = &log.traces[0].events;
+        assert_eq!(events[0].resource(), Some("pete"));
+        assert_eq!(events[1].resource(), None);
+    }
+}
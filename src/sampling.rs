@@ -0,0 +1,294 @@
+//! Approximate dependency matrices of very large logs: above a number of cases, the matrix is
+//! computed on a uniform sample of the cases instead of the whole log, and the cells whose
+//! existential dependency the sample can't settle come with confidence intervals of the shares
+//! behind it. Only the matrix,
+//! whose cost grows with the number of cases times the squared number of activities, is
+//! sampled; the prefix automaton, the log metrics and the other analyses still see every case.
+
+use serde::{Deserialize, Serialize};
+
+use crate::dependency_types::presence::ActivityPresence;
+use crate::dependency_types::significance::erfc;
+use crate::i18n::{Language, Message};
+use crate::metrics::ConfidenceInterval;
+use crate::random::SeededRng;
+use crate::variant_log::VariantLog;
+
+/// When the matrix is computed on a sample, and how large the sample is.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct SamplingSettings {
+    /// Logs with more cases are sampled.
+    pub min_cases: usize,
+    /// Number of sampled cases.
+    pub sample_size: usize,
+    /// Probability that an interval contains the share of the whole log, e.g. 0.95.
+    pub confidence_level: f64,
+}
+
+impl Default for SamplingSettings {
+    fn default() -> Self {
+        SamplingSettings {
+            min_cases: 1_000_000,
+            sample_size: 100_000,
+            confidence_level: 0.95,
+        }
+    }
+}
+
+impl SamplingSettings {
+    /// Whether the matrix of `log` is computed on a sample.
+    pub fn applies(&self, log: &VariantLog) -> bool {
+        log.len() > self.min_cases && log.len() > self.sample_size
+    }
+}
+
+/// Draws `size` distinct cases of `log` uniformly at random, so every variant is drawn in
+/// proportion to its frequency. The drawn cases keep their order; a log with at most `size`
+/// cases is returned as it is.
+pub fn sample_cases(log: &VariantLog, size: usize, rng: &mut SeededRng) -> VariantLog {
+    // selection sampling (Knuth's algorithm S): a single pass, without shuffling the indices
+    // of all cases
+    let size = size.min(log.len());
+    let mut selected = Vec::with_capacity(size);
+    for case_idx in 0..log.len() {
+        let needed = size - selected.len();
+        if needed == 0 {
+            break;
+        }
+        if rng.gen_index(log.len() - case_idx) < needed {
+            selected.push(case_idx);
+        }
+    }
+    log.select_cases(&selected)
+}
+
+/// The shares behind the existential dependency of a matrix cell, estimated on a sample.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CellEstimate {
+    pub from: String,
+    pub to: String,
+    /// Share of the cases with `from` that also contain `to`, 1 if `from` implies `to`;
+    /// `None` if no sampled case contains `from`.
+    pub implication: Option<ConfidenceInterval>,
+    /// Share of the cases with `to` that also contain `from`.
+    pub reverse_implication: Option<ConfidenceInterval>,
+}
+
+/// How an approximate matrix was computed, see the [module documentation](self).
+#[derive(Debug, Clone, PartialEq)]
+pub struct Approximation {
+    pub sampled_cases: usize,
+    pub total_cases: usize,
+    /// The estimates of the pairs of distinct activities of the sample whose implication or
+    /// reverse implication may or may not hold in the whole log, because a confidence interval
+    /// contains the existential threshold, ordered by pair. The intervals of the other cells
+    /// lie clearly on one side of the threshold and aren't kept.
+    pub cells: Vec<CellEstimate>,
+}
+
+impl Approximation {
+    /// Estimates the cells of the matrix of `sample`, drawn from a log of `total_cases`, and
+    /// keeps the ones left open at `threshold`, see [`Approximation::cells`].
+    pub fn of(
+        sample: &VariantLog,
+        total_cases: usize,
+        confidence_level: f64,
+        threshold: f64,
+    ) -> Self {
+        let variants = sample.weighted_variants();
+        let presence = ActivityPresence::new(&variants);
+        let activities = sample.activities();
+        let interval = |successes: usize, trials: usize| {
+            let share = successes as f64 / trials as f64;
+            wilson_interval(successes, trials, confidence_level)
+                .map(|interval| finite_population(interval, share, sample.len(), total_cases))
+        };
+
+        let open = |interval: Option<ConfidenceInterval>| {
+            interval.is_some_and(|interval| {
+                interval.lower < threshold && threshold <= interval.upper
+            })
+        };
+
+        let mut cells = Vec::new();
+        for from in &activities {
+            for to in activities.iter().filter(|to| *to != from) {
+                let table = presence.table(from, to);
+                let (with_from, _, with_to, _) = table.margins();
+                let implication = interval(table.both, with_from);
+                let reverse_implication = interval(table.both, with_to);
+                if open(implication) || open(reverse_implication) {
+                    cells.push(CellEstimate {
+                        from: from.clone(),
+                        to: to.clone(),
+                        implication,
+                        reverse_implication,
+                    });
+                }
+            }
+        }
+        Approximation {
+            sampled_cases: sample.len(),
+            total_cases,
+            cells,
+        }
+    }
+
+    /// The estimate of the cell `from` → `to`, `None` if the sample settles the cell or one of
+    /// the activities isn't in the sample.
+    pub fn cell(&self, from: &str, to: &str) -> Option<&CellEstimate> {
+        self.cells
+            .binary_search_by(|cell| (cell.from.as_str(), cell.to.as_str()).cmp(&(from, to)))
+            .ok()
+            .map(|idx| &self.cells[idx])
+    }
+
+    /// One line per open cell with the confidence intervals of its implication and reverse
+    /// implication, `-` if the sample has no case to estimate them from.
+    pub fn report(&self, language: Language) -> String {
+        let interval = |interval: Option<ConfidenceInterval>| {
+            interval.map_or("-".to_string(), |interval| interval.to_string())
+        };
+        let mut output = format!(
+            "{}:\n{:<48}{:<20}{}\n",
+            Message::EstimatedCells.text(language),
+            Message::ActivityPair.text(language),
+            Message::Implication.text(language),
+            Message::ReverseImplication.text(language)
+        );
+        for cell in &self.cells {
+            output.push_str(&format!(
+                "{:<48}{:<20}{}\n",
+                format!("{} => {}", cell.from, cell.to),
+                interval(cell.implication),
+                interval(cell.reverse_implication)
+            ));
+        }
+        output
+    }
+}
+
+/// Wilson score interval of a share of `successes` in `trials`, `None` without trials.
+pub fn wilson_interval(
+    successes: usize,
+    trials: usize,
+    confidence_level: f64,
+) -> Option<ConfidenceInterval> {
+    if trials == 0 {
+        return None;
+    }
+    let n = trials as f64;
+    let share = successes as f64 / n;
+    let z = z_score(confidence_level);
+    let denominator = 1.0 + z * z / n;
+    let center = (share + z * z / (2.0 * n)) / denominator;
+    let half_width = z / denominator * (share * (1.0 - share) / n + z * z / (4.0 * n * n)).sqrt();
+    Some(ConfidenceInterval {
+        lower: (center - half_width).max(0.0),
+        upper: (center + half_width).min(1.0),
+    })
+}
+
+// Narrows an interval towards the observed share by the finite population correction, as the
+// sampled cases are drawn without replacement; a sample of the whole log has no uncertainty left
+fn finite_population(
+    interval: ConfidenceInterval,
+    share: f64,
+    sampled: usize,
+    total: usize,
+) -> ConfidenceInterval {
+    if total <= 1 {
+        return interval;
+    }
+    let correction = ((total - sampled.min(total)) as f64 / (total - 1) as f64).sqrt();
+    ConfidenceInterval {
+        lower: share - (share - interval.lower) * correction,
+        upper: share + (interval.upper - share) * correction,
+    }
+}
+
+// The two-sided quantile of the standard normal distribution for `confidence_level`, by
+// bisection of its tail probability
+fn z_score(confidence_level: f64) -> f64 {
+    let tail = 1.0 - confidence_level.clamp(0.0, 1.0);
+    let (mut low, mut high) = (0.0, 10.0);
+    for _ in 0..60 {
+        let z = (low + high) / 2.0;
+        if erfc(z / std::f64::consts::SQRT_2) > tail {
+            low = z;
+        } else {
+            high = z;
+        }
+    }
+    (low + high) / 2.0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn traces(traces: &[&[&str]]) -> Vec<Vec<String>> {
+        traces
+            .iter()
+            .map(|trace| trace.iter().map(|activity| activity.to_string()).collect())
+            .collect()
+    }
+
+    #[test]
+    fn test_sample_cases() {
+        let mut all = traces(&vec![&["A", "B"][..]; 900]);
+        all.extend(traces(&vec![&["A", "C"][..]; 100]));
+        let log = VariantLog::from_traces(all);
+
+        let sample = sample_cases(&log, 200, &mut SeededRng::new(7));
+        assert_eq!(sample.len(), 200);
+        let rare = sample.activity_frequencies()["C"];
+        assert!((5..=40).contains(&rare), "{} cases with C", rare);
+        // the cases keep their order
+        let ids: Vec<usize> = sample
+            .cases()
+            .iter()
+            .map(|case| case.case_id["case_".len()..].parse().unwrap())
+            .collect();
+        assert!(ids.windows(2).all(|pair| pair[0] < pair[1]));
+        assert_eq!(sample_cases(&log, 2000, &mut SeededRng::new(7)).len(), 1000);
+
+        let settings = SamplingSettings {
+            min_cases: 500,
+            sample_size: 200,
+            ..Default::default()
+        };
+        assert!(settings.applies(&log));
+        assert!(!SamplingSettings::default().applies(&log));
+
+        // B => A holds in the sample, but a case of the whole log might break it
+        let approximation = Approximation::of(&sample, log.len(), 0.95, 1.0);
+        let implication = approximation.cell("B", "A").unwrap().implication.unwrap();
+        assert_eq!(implication.upper, 1.0);
+        assert!(implication.lower > 0.95);
+        // B and C never occur together, in the sample or anywhere else
+        assert!(approximation.cell("B", "C").is_none());
+        assert!(approximation.cell("A", "unknown").is_none());
+        let report = approximation.report(Language::English);
+        assert!(report.contains(&format!("{:<48}{}", "B => A", implication)));
+        assert_eq!(report.lines().count(), approximation.cells.len() + 2);
+
+        let approximation = Approximation::of(&sample, log.len(), 0.95, 0.9);
+        let share = approximation.cell("A", "B").unwrap().implication.unwrap();
+        assert!(share.lower < 0.9 && share.upper > 0.9, "{}", share);
+        assert!(approximation.cell("B", "C").is_none());
+    }
+
+    #[test]
+    fn test_wilson_interval() {
+        assert!((z_score(0.95) - 1.96).abs() < 0.001);
+        let interval = wilson_interval(50, 100, 0.95).unwrap();
+        assert!((interval.lower - 0.4038).abs() < 0.001);
+        assert!((interval.upper - 0.5962).abs() < 0.001);
+        assert_eq!(wilson_interval(0, 0, 0.95), None);
+
+        let exact = finite_population(interval, 0.5, 100, 100);
+        assert!((exact.lower - 0.5).abs() < 1e-9 && (exact.upper - 0.5).abs() < 1e-9);
+    }
+}
@@ -0,0 +1,168 @@
+use std::collections::HashMap;
+
+use process_mining::petri_net::petri_net_struct::ArcType;
+use process_mining::PetriNet;
+
+use crate::dfg::DirectlyFollowsGraph;
+use crate::i18n::Message;
+
+/// Size and structural complexity of a process model, seen as a directed graph: the smaller
+/// and the less interconnected, the easier a model is to read. Comparing these numbers for
+/// models discovered with different filter settings shows what a better fitness costs.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Simplicity {
+    pub nodes: usize,
+    pub edges: usize,
+    /// Mean number of edges of the connectors, i.e. the nodes with more than one incoming or
+    /// outgoing edge (splits and joins); 0 without connectors.
+    pub average_connector_degree: f64,
+    /// `edges - nodes + 2 * connected components`, the number of independent cycles plus
+    /// decision points.
+    pub cyclomatic_complexity: usize,
+}
+
+impl Simplicity {
+    /// Measures a graph with the nodes `0..nodes` and the given directed edges.
+    pub fn of_graph(nodes: usize, edges: &[(usize, usize)]) -> Self {
+        let mut incoming = vec![0; nodes];
+        let mut outgoing = vec![0; nodes];
+        let mut components = Components::new(nodes);
+        for &(from, to) in edges {
+            outgoing[from] += 1;
+            incoming[to] += 1;
+            components.union(from, to);
+        }
+
+        let connector_degrees: Vec<usize> = (0..nodes)
+            .filter(|&node| incoming[node] > 1 || outgoing[node] > 1)
+            .map(|node| incoming[node] + outgoing[node])
+            .collect();
+        let average_connector_degree = if connector_degrees.is_empty() {
+            0.0
+        } else {
+            connector_degrees.iter().sum::<usize>() as f64 / connector_degrees.len() as f64
+        };
+
+        Simplicity {
+            nodes,
+            edges: edges.len(),
+            average_connector_degree,
+            cyclomatic_complexity: (edges.len() + 2 * components.count()).saturating_sub(nodes),
+        }
+    }
+
+    /// Measures a directly-follows graph, with one node per activity.
+    pub fn of_dfg(dfg: &DirectlyFollowsGraph) -> Self {
+        let index: HashMap<&str, usize> = dfg
+            .activities()
+            .iter()
+            .enumerate()
+            .map(|(idx, activity)| (activity.as_str(), idx))
+            .collect();
+        let edges: Vec<(usize, usize)> = dfg
+            .edges()
+            .map(|(from, to, _)| (index[from], index[to]))
+            .collect();
+        Self::of_graph(index.len(), &edges)
+    }
+
+    /// Measures a Petri net, with places and transitions as nodes and arcs as edges.
+    pub fn of_petri_net(net: &PetriNet) -> Self {
+        let mut index = HashMap::new();
+        for id in net.places.keys().chain(net.transitions.keys()) {
+            let next = index.len();
+            index.entry(*id).or_insert(next);
+        }
+        let edges: Vec<(usize, usize)> = net
+            .arcs
+            .iter()
+            .filter_map(|arc| {
+                let (from, to) = match arc.from_to {
+                    ArcType::PlaceTransition(place, transition) => (place, transition),
+                    ArcType::TransitionPlace(transition, place) => (transition, place),
+                };
+                Some((*index.get(&from)?, *index.get(&to)?))
+            })
+            .collect();
+        Self::of_graph(index.len(), &edges)
+    }
+
+    /// The measures with the labels they are reported with.
+    pub fn metrics(&self) -> [(Message, String); 4] {
+        [
+            (Message::ModelNodes, self.nodes.to_string()),
+            (Message::ModelEdges, self.edges.to_string()),
+            (
+                Message::AverageConnectorDegree,
+                format!("{:.4}", self.average_connector_degree),
+            ),
+            (
+                Message::CyclomaticComplexity,
+                self.cyclomatic_complexity.to_string(),
+            ),
+        ]
+    }
+}
+
+// Weakly connected components, as a union-find over the node indices
+struct Components {
+    parents: Vec<usize>,
+}
+
+impl Components {
+    fn new(nodes: usize) -> Self {
+        Components {
+            parents: (0..nodes).collect(),
+        }
+    }
+
+    fn root(&mut self, mut node: usize) -> usize {
+        while self.parents[node] != node {
+            self.parents[node] = self.parents[self.parents[node]];
+            node = self.parents[node];
+        }
+        node
+    }
+
+    fn union(&mut self, a: usize, b: usize) {
+        let (a, b) = (self.root(a), self.root(b));
+        self.parents[a] = b;
+    }
+
+    fn count(&mut self) -> usize {
+        (0..self.parents.len())
+            .filter(|&node| self.root(node) == node)
+            .count()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::parse_petri_net;
+
+    #[test]
+    fn test_graph() {
+        // A -> B -> D, A -> C -> D, D -> A and a separate E
+        let simplicity = Simplicity::of_graph(5, &[(0, 1), (0, 2), (1, 3), (2, 3), (3, 0)]);
+        assert_eq!(simplicity.nodes, 5);
+        assert_eq!(simplicity.edges, 5);
+        // A has 3 edges, D has 3 edges
+        assert_eq!(simplicity.average_connector_degree, 3.0);
+        assert_eq!(simplicity.cyclomatic_complexity, 4);
+
+        let dfg = DirectlyFollowsGraph::from_traces([["A", "B", "C"], ["A", "C", "C"]]);
+        let simplicity = Simplicity::of_dfg(&dfg);
+        assert_eq!((simplicity.nodes, simplicity.edges), (3, 4));
+        assert_eq!(simplicity.cyclomatic_complexity, 3);
+        assert_eq!(Simplicity::of_graph(0, &[]).cyclomatic_complexity, 0);
+    }
+
+    #[test]
+    fn test_petri_net() {
+        let net = parse_petri_net(Some("./sample-data/sequence.pnml"), None).unwrap();
+        let simplicity = Simplicity::of_petri_net(&net);
+        assert_eq!(simplicity.nodes, net.places.len() + net.transitions.len());
+        assert_eq!(simplicity.edges, net.arcs.len());
+    }
+}
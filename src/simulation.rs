@@ -0,0 +1,410 @@
+//! What-if simulation: cases are generated from what the log shows (the variants, the waiting
+//! and processing times of the activities and the time between arrivals) and run once as
+//! observed and once with changed parameters, to compare the key figures of both.
+//!
+//! The simulation has no resources, so cases don't compete for capacity: faster activities
+//! shorten the cycle times directly, and more arrivals raise the throughput and the work in
+//! progress, not the waiting times.
+
+use std::collections::{BTreeMap, HashSet};
+
+use chrono::Duration;
+use serde::{Deserialize, Serialize};
+
+use crate::arrivals::inter_arrival_times;
+use crate::i18n::{Language, Message};
+use crate::random::SeededRng;
+use crate::render::format_duration;
+use crate::timeline::CaseTimeline;
+
+/// Changed parameters of a simulation; the default leaves everything as observed.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct WhatIf {
+    /// Factor of the processing times of all activities, e.g. 0.8 for 20% faster.
+    pub duration_scaling: f64,
+    /// Factors of the processing times of single activities, instead of `duration_scaling`.
+    pub activity_duration_scaling: BTreeMap<String, f64>,
+    /// Factor of the number of arriving cases per time, e.g. 1.5 for 50% more cases.
+    pub arrival_rate_scaling: f64,
+    /// Activities left out of every case, together with the waiting time before them.
+    pub removed_activities: Vec<String>,
+}
+
+impl Default for WhatIf {
+    fn default() -> Self {
+        WhatIf {
+            duration_scaling: 1.0,
+            activity_duration_scaling: BTreeMap::new(),
+            arrival_rate_scaling: 1.0,
+            removed_activities: Vec::new(),
+        }
+    }
+}
+
+impl WhatIf {
+    fn duration_scaling(&self, activity: &str) -> f64 {
+        self.activity_duration_scaling
+            .get(activity)
+            .copied()
+            .unwrap_or(self.duration_scaling)
+            .max(0.0)
+    }
+}
+
+/// How the times between the arrivals of simulated cases are drawn.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum ArrivalProcess {
+    /// From the observed inter-arrival times.
+    #[default]
+    Empirical,
+    /// A Poisson process with the observed arrival rate, i.e. exponentially distributed
+    /// inter-arrival times with the observed mean.
+    Poisson,
+}
+
+/// Number of simulated cases, their arrival process and the changed parameters.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct SimulationSettings {
+    pub cases: usize,
+    pub arrivals: ArrivalProcess,
+    pub what_if: WhatIf,
+}
+
+impl Default for SimulationSettings {
+    fn default() -> Self {
+        SimulationSettings {
+            cases: 1000,
+            arrivals: ArrivalProcess::default(),
+            what_if: WhatIf::default(),
+        }
+    }
+}
+
+/// The observed behavior cases are simulated from.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SimulationModel {
+    /// Every variant as often as it was observed.
+    variants: Vec<Vec<String>>,
+    /// Observed processing times per activity.
+    processing_times: BTreeMap<String, Vec<Duration>>,
+    /// Observed waiting times before an activity, since the previous instance of the case ended.
+    waiting_times: BTreeMap<String, Vec<Duration>>,
+    /// Observed times between the first timestamps of consecutive cases.
+    inter_arrival_times: Vec<Duration>,
+}
+
+impl SimulationModel {
+    /// Learns the model from the cases of `timelines` with at least one timestamp, `None` if
+    /// there are none. Activities without start timestamps take no processing time.
+    pub fn fit(timelines: &[CaseTimeline]) -> Option<Self> {
+        let mut model = SimulationModel {
+            variants: Vec::new(),
+            processing_times: BTreeMap::new(),
+            waiting_times: BTreeMap::new(),
+            inter_arrival_times: Vec::new(),
+        };
+        for timeline in timelines {
+            if timeline.span().is_none() {
+                continue;
+            }
+            model.variants.push(
+                timeline
+                    .instances
+                    .iter()
+                    .map(|instance| instance.activity.clone())
+                    .collect(),
+            );
+            for (instance, waiting) in timeline.instances.iter().zip(timeline.waiting_times()) {
+                if let Some(duration) = instance.duration() {
+                    model
+                        .processing_times
+                        .entry(instance.activity.clone())
+                        .or_default()
+                        .push(duration);
+                }
+                if let Some(waiting) = waiting {
+                    model
+                        .waiting_times
+                        .entry(instance.activity.clone())
+                        .or_default()
+                        .push(waiting.max(Duration::zero()));
+                }
+            }
+        }
+        if model.variants.is_empty() {
+            return None;
+        }
+        model.inter_arrival_times = inter_arrival_times(timelines);
+        Some(model)
+    }
+
+    /// Simulates `cases` cases arriving by `arrivals` with the parameters of `what_if`.
+    pub fn simulate(
+        &self,
+        cases: usize,
+        arrivals: ArrivalProcess,
+        what_if: &WhatIf,
+        rng: &mut SeededRng,
+    ) -> SimulatedKpis {
+        let removed: HashSet<&str> = what_if
+            .removed_activities
+            .iter()
+            .map(String::as_str)
+            .collect();
+        let arrival_scaling = if what_if.arrival_rate_scaling > 0.0 {
+            1.0 / what_if.arrival_rate_scaling
+        } else {
+            1.0
+        };
+        let sample = |times: Option<&Vec<Duration>>, rng: &mut SeededRng| match times {
+            Some(times) if !times.is_empty() => times[rng.gen_index(times.len())],
+            _ => Duration::zero(),
+        };
+        let mean_inter_arrival_time = if self.inter_arrival_times.is_empty() {
+            0.0
+        } else {
+            self.inter_arrival_times
+                .iter()
+                .map(|time| time.num_milliseconds() as f64)
+                .sum::<f64>()
+                / self.inter_arrival_times.len() as f64
+        };
+        let inter_arrival_time = |rng: &mut SeededRng| match arrivals {
+            ArrivalProcess::Empirical => sample(Some(&self.inter_arrival_times), rng),
+            ArrivalProcess::Poisson => Duration::milliseconds(
+                (-mean_inter_arrival_time * (1.0 - rng.next_f64()).ln()).round() as i64,
+            ),
+        };
+
+        let mut arrival = Duration::zero();
+        let mut cycle_times = Vec::with_capacity(cases);
+        let mut last_completion = Duration::zero();
+        for case in 0..cases {
+            if case > 0 {
+                arrival += scale(inter_arrival_time(rng), arrival_scaling);
+            }
+            let variant = &self.variants[rng.gen_index(self.variants.len())];
+            let mut cycle_time = Duration::zero();
+            for (idx, activity) in variant
+                .iter()
+                .filter(|activity| !removed.contains(activity.as_str()))
+                .enumerate()
+            {
+                if idx > 0 {
+                    cycle_time += sample(self.waiting_times.get(activity), rng);
+                }
+                cycle_time += scale(
+                    sample(self.processing_times.get(activity), rng),
+                    what_if.duration_scaling(activity),
+                );
+            }
+            last_completion = last_completion.max(arrival + cycle_time);
+            cycle_times.push(cycle_time);
+        }
+        SimulatedKpis::of(cycle_times, last_completion)
+    }
+
+    /// Simulates the observed behavior and the changed one with the same seed.
+    pub fn compare(&self, settings: &SimulationSettings, seed: u64) -> SimulationComparison {
+        SimulationComparison {
+            baseline: self.simulate(
+                settings.cases,
+                settings.arrivals,
+                &WhatIf::default(),
+                &mut SeededRng::new(seed),
+            ),
+            scenario: self.simulate(
+                settings.cases,
+                settings.arrivals,
+                &settings.what_if,
+                &mut SeededRng::new(seed),
+            ),
+        }
+    }
+}
+
+fn scale(duration: Duration, factor: f64) -> Duration {
+    Duration::milliseconds((duration.num_milliseconds() as f64 * factor).round() as i64)
+}
+
+/// Key figures of a simulation run.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SimulatedKpis {
+    pub cases: usize,
+    pub mean_cycle_time: Duration,
+    pub median_cycle_time: Duration,
+    /// Completed cases per day, from the first arrival to the last completion.
+    pub throughput_per_day: f64,
+    /// Mean number of cases in progress over the same time.
+    pub mean_wip: f64,
+}
+
+impl SimulatedKpis {
+    fn of(mut cycle_times: Vec<Duration>, makespan: Duration) -> Self {
+        cycle_times.sort();
+        let total: Duration = cycle_times.iter().copied().sum();
+        let days = makespan.num_milliseconds() as f64 / Duration::days(1).num_milliseconds() as f64;
+        let cases = cycle_times.len();
+        SimulatedKpis {
+            cases,
+            mean_cycle_time: if cases == 0 {
+                Duration::zero()
+            } else {
+                total / cases as i32
+            },
+            median_cycle_time: cycle_times
+                .get(cases.saturating_sub(1) / 2)
+                .copied()
+                .unwrap_or_else(Duration::zero),
+            throughput_per_day: if days > 0.0 { cases as f64 / days } else { 0.0 },
+            mean_wip: if makespan > Duration::zero() {
+                total.num_milliseconds() as f64 / makespan.num_milliseconds() as f64
+            } else {
+                0.0
+            },
+        }
+    }
+}
+
+/// The key figures of the observed behavior and of the changed parameters.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SimulationComparison {
+    pub baseline: SimulatedKpis,
+    pub scenario: SimulatedKpis,
+}
+
+impl SimulationComparison {
+    /// One line per key figure with its baseline and scenario value and the relative change.
+    pub fn report(&self, language: Language) -> String {
+        let change = |baseline: f64, scenario: f64| {
+            if baseline == 0.0 {
+                "-".to_string()
+            } else {
+                format!("{:+.1}%", (scenario / baseline - 1.0) * 100.0)
+            }
+        };
+        let millis = |duration: Duration| duration.num_milliseconds() as f64;
+        let (baseline, scenario) = (&self.baseline, &self.scenario);
+        let rows = [
+            (
+                Message::MeanCycleTime,
+                format_duration(baseline.mean_cycle_time),
+                format_duration(scenario.mean_cycle_time),
+                change(
+                    millis(baseline.mean_cycle_time),
+                    millis(scenario.mean_cycle_time),
+                ),
+            ),
+            (
+                Message::MedianCycleTime,
+                format_duration(baseline.median_cycle_time),
+                format_duration(scenario.median_cycle_time),
+                change(
+                    millis(baseline.median_cycle_time),
+                    millis(scenario.median_cycle_time),
+                ),
+            ),
+            (
+                Message::ThroughputPerDay,
+                format!("{:.2}", baseline.throughput_per_day),
+                format!("{:.2}", scenario.throughput_per_day),
+                change(baseline.throughput_per_day, scenario.throughput_per_day),
+            ),
+            (
+                Message::MeanWip,
+                format!("{:.2}", baseline.mean_wip),
+                format!("{:.2}", scenario.mean_wip),
+                change(baseline.mean_wip, scenario.mean_wip),
+            ),
+        ];
+        let mut output = format!(
+            "{} ({} {}):\n{:<32}{:<16}{:<16}{}\n",
+            Message::WhatIfSimulation.text(language),
+            scenario.cases,
+            Message::Cases.text(language),
+            "",
+            Message::Baseline.text(language),
+            Message::Scenario.text(language),
+            Message::Change.text(language)
+        );
+        for (label, baseline, scenario, change) in rows {
+            output.push_str(&format!(
+                "{:<32}{:<16}{:<16}{}\n",
+                label.text(language),
+                baseline,
+                scenario,
+                change
+            ));
+        }
+        output
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{TimeZone, Utc};
+
+    use super::*;
+    use crate::random::DEFAULT_SEED;
+    use crate::timeline::TestInstance;
+
+    // started and completed on `day` of January 2024
+    fn instance(activity: &str, day: u32, start: u32, complete: u32) -> TestInstance<'_> {
+        let time = |hour| Some(Utc.with_ymd_and_hms(2024, 1, day, hour, 0, 0).unwrap());
+        (activity, time(start), time(complete), None)
+    }
+
+    #[test]
+    fn test_what_if() {
+        let timelines: Vec<CaseTimeline> = (1..=4)
+            .map(|day| {
+                CaseTimeline::of(
+                    &day.to_string(),
+                    &[instance("A", day, 8, 10), instance("B", day, 12, 16)],
+                )
+            })
+            .collect();
+        let model = SimulationModel::fit(&timelines).unwrap();
+        assert!(SimulationModel::fit(&[]).is_none());
+
+        let baseline = model.simulate(
+            10,
+            ArrivalProcess::Empirical,
+            &WhatIf::default(),
+            &mut SeededRng::default(),
+        );
+        assert_eq!(baseline.mean_cycle_time, Duration::hours(8));
+        // nine days between the arrivals, and the last case
+        assert_eq!(baseline.mean_wip, 80.0 / (9.0 * 24.0 + 8.0));
+
+        let poisson = model.simulate(
+            1000,
+            ArrivalProcess::Poisson,
+            &WhatIf::default(),
+            &mut SeededRng::default(),
+        );
+        // arrivals don't change the cycle times, one case arrives per day on average
+        assert_eq!(poisson.mean_cycle_time, Duration::hours(8));
+        assert!((poisson.throughput_per_day - 1.0).abs() < 0.1);
+
+        let settings = SimulationSettings {
+            cases: 10,
+            arrivals: ArrivalProcess::Empirical,
+            what_if: WhatIf {
+                activity_duration_scaling: [("B".to_string(), 0.5)].into(),
+                arrival_rate_scaling: 2.0,
+                removed_activities: vec!["A".to_string()],
+                ..Default::default()
+            },
+        };
+        let comparison = model.compare(&settings, DEFAULT_SEED);
+        assert_eq!(comparison.baseline, baseline);
+        // the waiting time before B goes with A
+        assert_eq!(comparison.scenario.mean_cycle_time, Duration::hours(2));
+        assert!(comparison.scenario.throughput_per_day > 1.8);
+        assert!(comparison.report(Language::English).contains("-75.0%"));
+    }
+}
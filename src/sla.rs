@@ -0,0 +1,343 @@
+//! Service level agreements: maximum durations of whole cases or between two activities, and
+//! how often, how badly and since when the cases breach them.
+
+use std::cmp::Reverse;
+use std::collections::BTreeMap;
+
+use chrono::{DateTime, Datelike, Duration, Months, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::calendar::{elapsed, BusinessCalendar};
+use crate::i18n::{Language, Message};
+use crate::render::format_duration;
+use crate::timeline::CaseTimeline;
+
+/// A maximum duration within a case. Without activities, the whole case is measured.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Sla {
+    pub name: String,
+    /// Measured from the first completion of this activity, from the start of the case if
+    /// missing.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub from: Option<String>,
+    /// Measured to the next start of this activity, to the end of the case if missing.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub to: Option<String>,
+    pub max_minutes: i64,
+}
+
+impl Sla {
+    pub fn max_duration(&self) -> Duration {
+        Duration::minutes(self.max_minutes)
+    }
+
+    /// The measured time of `timeline` and the time the measurement ended, `None` if the case
+    /// lacks the activities or timestamps.
+    pub fn measure(
+        &self,
+        timeline: &CaseTimeline,
+        calendar: Option<&BusinessCalendar>,
+    ) -> Option<(Duration, DateTime<Utc>)> {
+        let (case_start, case_end) = timeline.span()?;
+        let from = match &self.from {
+            Some(activity) => timeline
+                .instances
+                .iter()
+                .filter(|instance| instance.activity == *activity)
+                .find_map(|instance| instance.complete.or(instance.start))?,
+            None => case_start,
+        };
+        let to = match &self.to {
+            Some(activity) => timeline
+                .instances
+                .iter()
+                .filter(|instance| instance.activity == *activity)
+                .filter_map(|instance| instance.begin())
+                .find(|&begin| begin >= from)?,
+            None => case_end,
+        };
+        Some((elapsed(calendar, from, to), to))
+    }
+}
+
+/// Length of the periods of a trend, e.g. of SLA breaches.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum SlaPeriod {
+    Day,
+    Week,
+    #[default]
+    Month,
+}
+
+impl SlaPeriod {
+    /// The first day of the period containing `date`.
+    pub fn start(&self, date: NaiveDate) -> NaiveDate {
+        match self {
+            SlaPeriod::Day => date,
+            SlaPeriod::Week => date - Duration::days(date.weekday().num_days_from_monday() as i64),
+            SlaPeriod::Month => date.with_day(1).expect("every month has a first day"),
+        }
+    }
+
+    /// The first day of the period after the one starting on `start`.
+    pub fn next(&self, start: NaiveDate) -> NaiveDate {
+        match self {
+            SlaPeriod::Day => start + Duration::days(1),
+            SlaPeriod::Week => start + Duration::days(7),
+            SlaPeriod::Month => start
+                .checked_add_months(Months::new(1))
+                .expect("date in range"),
+        }
+    }
+}
+
+/// The SLAs to check and how their breaches are reported.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct SlaSettings {
+    pub slas: Vec<Sla>,
+    /// Cases are assigned to the period in which their measurement ended.
+    pub period: SlaPeriod,
+    /// Breaching cases listed per SLA.
+    pub worst_cases: usize,
+}
+
+impl Default for SlaSettings {
+    fn default() -> Self {
+        SlaSettings {
+            slas: Vec::new(),
+            period: SlaPeriod::default(),
+            worst_cases: 5,
+        }
+    }
+}
+
+/// A case that took longer than an SLA allows.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SlaBreach {
+    pub case_id: String,
+    pub elapsed: Duration,
+}
+
+/// Measured cases and breaches of an SLA within one period.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SlaPeriodStats {
+    /// The first day of the period.
+    pub start: NaiveDate,
+    pub cases: usize,
+    pub breaches: usize,
+}
+
+impl SlaPeriodStats {
+    pub fn breach_rate(&self) -> f64 {
+        breach_rate(self.breaches, self.cases)
+    }
+}
+
+/// The breaches of one SLA over all cases.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SlaReport {
+    pub sla: Sla,
+    /// Cases the SLA could be measured in.
+    pub cases: usize,
+    pub breaches: usize,
+    /// The breaching cases that exceeded the SLA the most, worst first.
+    pub worst: Vec<SlaBreach>,
+    /// Ordered by period, periods without measured cases are left out.
+    pub trend: Vec<SlaPeriodStats>,
+}
+
+impl SlaReport {
+    pub fn breach_rate(&self) -> f64 {
+        breach_rate(self.breaches, self.cases)
+    }
+}
+
+fn breach_rate(breaches: usize, cases: usize) -> f64 {
+    if cases == 0 {
+        0.0
+    } else {
+        breaches as f64 / cases as f64
+    }
+}
+
+/// Checks every case of `timelines` against every SLA of `settings`, in business time if there
+/// is a calendar.
+pub fn sla_reports(
+    timelines: &[CaseTimeline],
+    settings: &SlaSettings,
+    calendar: Option<&BusinessCalendar>,
+) -> Vec<SlaReport> {
+    settings
+        .slas
+        .iter()
+        .map(|sla| {
+            let mut breaches = Vec::new();
+            let mut trend: BTreeMap<NaiveDate, SlaPeriodStats> = BTreeMap::new();
+            let mut cases = 0;
+            for timeline in timelines {
+                let Some((time, ended)) = sla.measure(timeline, calendar) else {
+                    continue;
+                };
+                let start = settings.period.start(ended.date_naive());
+                let period = trend.entry(start).or_insert(SlaPeriodStats {
+                    start,
+                    cases: 0,
+                    breaches: 0,
+                });
+                cases += 1;
+                period.cases += 1;
+                if time > sla.max_duration() {
+                    period.breaches += 1;
+                    breaches.push(SlaBreach {
+                        case_id: timeline.case_id.clone(),
+                        elapsed: time,
+                    });
+                }
+            }
+            let breach_count = breaches.len();
+            breaches.sort_by_key(|breach| Reverse(breach.elapsed));
+            breaches.truncate(settings.worst_cases);
+            SlaReport {
+                sla: sla.clone(),
+                cases,
+                breaches: breach_count,
+                worst: breaches,
+                trend: trend.into_values().collect(),
+            }
+        })
+        .collect()
+}
+
+/// One line per SLA with its breach count and rate, then the worst cases and the trend of
+/// every SLA.
+pub fn report(reports: &[SlaReport], language: Language) -> String {
+    let mut output = format!(
+        "{}:\n{:<32}{:<16}{:<10}{:<12}{}\n",
+        Message::SlaBreaches.text(language),
+        Message::Sla.text(language),
+        Message::Limit.text(language),
+        Message::Cases.text(language),
+        Message::Breaches.text(language),
+        Message::BreachRate.text(language)
+    );
+    for report in reports {
+        output.push_str(&format!(
+            "{:<32}{:<16}{:<10}{:<12}{:.4}\n",
+            report.sla.name,
+            format_duration(report.sla.max_duration()),
+            report.cases,
+            report.breaches,
+            report.breach_rate()
+        ));
+    }
+    for report in reports {
+        output.push_str(&format!(
+            "\n{} ({}):\n{:<24}{}\n",
+            report.sla.name,
+            Message::WorstCases.text(language),
+            Message::Case.text(language),
+            Message::Duration.text(language)
+        ));
+        for breach in &report.worst {
+            output.push_str(&format!(
+                "{:<24}{}\n",
+                breach.case_id,
+                format_duration(breach.elapsed)
+            ));
+        }
+        output.push_str(&format!(
+            "{:<16}{:<10}{:<12}{}\n",
+            Message::Period.text(language),
+            Message::Cases.text(language),
+            Message::Breaches.text(language),
+            Message::BreachRate.text(language)
+        ));
+        for period in &report.trend {
+            output.push_str(&format!(
+                "{:<16}{:<10}{:<12}{:.4}\n",
+                period.start.to_string(),
+                period.cases,
+                period.breaches,
+                period.breach_rate()
+            ));
+        }
+    }
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::TimeZone;
+
+    use super::*;
+    use crate::timeline::TestInstance;
+
+    // completed on `day` of January 2024 at `hour`
+    fn completed(activity: &str, day: u32, hour: u32) -> TestInstance<'_> {
+        (activity, None, Some(Utc.with_ymd_and_hms(2024, 1, day, hour, 0, 0).unwrap()), None)
+    }
+
+    #[test]
+    fn test_sla_reports() {
+        let timelines = [
+            CaseTimeline::of(
+                "1",
+                &[completed("Create", 1, 8), completed("Approve", 1, 10), completed("Ship", 2, 8)],
+            ),
+            CaseTimeline::of(
+                "2",
+                &[completed("Create", 1, 8), completed("Approve", 1, 20), completed("Ship", 1, 22)],
+            ),
+            CaseTimeline::of(
+                "3",
+                &[completed("Create", 8, 8), completed("Approve", 8, 9), completed("Ship", 8, 10)],
+            ),
+            CaseTimeline::of("4", &[completed("Create", 9, 8), completed("Ship", 12, 8)]),
+        ];
+        let settings = SlaSettings {
+            slas: vec![
+                Sla {
+                    name: "Approval".to_string(),
+                    from: Some("Create".to_string()),
+                    to: Some("Approve".to_string()),
+                    max_minutes: 4 * 60,
+                },
+                Sla {
+                    name: "Delivery".to_string(),
+                    from: None,
+                    to: None,
+                    max_minutes: 24 * 60,
+                },
+            ],
+            period: SlaPeriod::Week,
+            worst_cases: 1,
+        };
+        let reports = sla_reports(&timelines, &settings, None);
+
+        // case 4 was never approved
+        let approval = &reports[0];
+        assert_eq!((approval.cases, approval.breaches), (3, 1));
+        assert_eq!(approval.worst[0].case_id, "2");
+
+        let delivery = &reports[1];
+        assert_eq!((delivery.cases, delivery.breaches), (4, 1));
+        assert_eq!(delivery.breach_rate(), 0.25);
+        assert_eq!(delivery.worst[0].elapsed, Duration::days(3));
+        let trend: Vec<(NaiveDate, usize, usize)> = delivery
+            .trend
+            .iter()
+            .map(|period| (period.start, period.cases, period.breaches))
+            .collect();
+        assert_eq!(
+            trend,
+            [
+                (NaiveDate::from_ymd_opt(2024, 1, 1).unwrap(), 2, 0),
+                (NaiveDate::from_ymd_opt(2024, 1, 8).unwrap(), 2, 1)
+            ]
+        );
+        assert!(report(&reports, Language::English).contains("Approval"));
+    }
+}
@@ -0,0 +1,250 @@
+//! Reading event logs from and writing analysis results to SQLite databases.
+//!
+//! Only available with the `sqlite` feature.
+
+use std::sync::Mutex;
+
+use rusqlite::{params, Connection};
+
+use crate::audit::{AuditEntry, AuditLog};
+use crate::dependency_types::dependency::Dependency;
+use crate::variant_log::{EventCollector, VariantLog};
+
+pub const METRICS_TABLE: &str = "egypt_metrics";
+pub const MATRIX_TABLE: &str = "egypt_matrix";
+pub const VARIANTS_TABLE: &str = "egypt_variants";
+pub const AUDIT_TABLE: &str = "egypt_audit";
+
+/// Where the events are stored in the database.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EventTable {
+    pub table: String,
+    pub case_column: String,
+    pub activity_column: String,
+    /// Column the events of a case are ordered by; the row order is used if `None`.
+    pub timestamp_column: Option<String>,
+}
+
+impl Default for EventTable {
+    fn default() -> Self {
+        EventTable {
+            table: "events".to_string(),
+            case_column: "case_id".to_string(),
+            activity_column: "activity".to_string(),
+            timestamp_column: Some("timestamp".to_string()),
+        }
+    }
+}
+
+/// Reads the events of `table` into a [`VariantLog`], one case per distinct value of the case
+/// column, in order of first appearance.
+pub fn read_variant_log(conn: &Connection, table: &EventTable) -> rusqlite::Result<VariantLog> {
+    let order_by = match &table.timestamp_column {
+        Some(timestamp) => format!(" ORDER BY {}, rowid", quote(timestamp)),
+        None => " ORDER BY rowid".to_string(),
+    };
+    let query = format!(
+        "SELECT CAST({} AS TEXT), CAST({} AS TEXT) FROM {}{}",
+        quote(&table.case_column),
+        quote(&table.activity_column),
+        quote(&table.table),
+        order_by
+    );
+
+    let mut events = EventCollector::new();
+    let mut statement = conn.prepare(&query)?;
+    let mut rows = statement.query([])?;
+    while let Some(row) = rows.next()? {
+        // events without case or activity can't be placed in a trace
+        if let (Some(case_id), Some(activity)) = (row.get::<_, Option<String>>(0)?, row.get(1)?) {
+            events.push(case_id, activity);
+        }
+    }
+
+    Ok(events.finish())
+}
+
+/// Writes named metric values to the `egypt_metrics` table, replacing existing values.
+pub fn write_metrics(conn: &Connection, metrics: &[(&str, f64)]) -> rusqlite::Result<()> {
+    conn.execute_batch(&format!(
+        "CREATE TABLE IF NOT EXISTS {} (name TEXT PRIMARY KEY, value REAL)",
+        METRICS_TABLE
+    ))?;
+    let mut statement = conn.prepare(&format!(
+        "INSERT OR REPLACE INTO {} (name, value) VALUES (?1, ?2)",
+        METRICS_TABLE
+    ))?;
+    for (name, value) in metrics {
+        statement.execute(params![name, value])?;
+    }
+    Ok(())
+}
+
+/// Writes one row per matrix cell to the `egypt_matrix` table, replacing its previous content.
+pub fn write_matrix(conn: &Connection, dependencies: &[Dependency]) -> rusqlite::Result<()> {
+    conn.execute_batch(&format!(
+        "DROP TABLE IF EXISTS {0};
+         CREATE TABLE {0} (
+             from_activity TEXT NOT NULL,
+             to_activity TEXT NOT NULL,
+             temporal TEXT,
+             existential TEXT,
+             custom TEXT
+         )",
+        MATRIX_TABLE
+    ))?;
+    let mut statement = conn.prepare(&format!(
+        "INSERT INTO {} (from_activity, to_activity, temporal, existential, custom)
+         VALUES (?1, ?2, ?3, ?4, ?5)",
+        MATRIX_TABLE
+    ))?;
+    for dependency in dependencies {
+        statement.execute(params![
+            dependency.from,
+            dependency.to,
+            dependency.temporal_dependency.as_ref().map(|d| d.to_string()),
+            dependency.existential_dependency.as_ref().map(|d| d.to_string()),
+            (!dependency.custom_dependencies.is_empty()).then(|| {
+                dependency
+                    .custom_dependencies
+                    .iter()
+                    .map(|d| d.to_string())
+                    .collect::<Vec<_>>()
+                    .join(",")
+            }),
+        ])?;
+    }
+    Ok(())
+}
+
+/// Writes the variants of `log` to the `egypt_variants` table, replacing its previous content.
+/// The activities of a variant are joined with `,`.
+pub fn write_variants(conn: &Connection, log: &VariantLog) -> rusqlite::Result<()> {
+    conn.execute_batch(&format!(
+        "DROP TABLE IF EXISTS {0};
+         CREATE TABLE {0} (variant INTEGER PRIMARY KEY, activities TEXT NOT NULL, frequency INTEGER NOT NULL)",
+        VARIANTS_TABLE
+    ))?;
+    let mut statement = conn.prepare(&format!(
+        "INSERT INTO {} (variant, activities, frequency) VALUES (?1, ?2, ?3)",
+        VARIANTS_TABLE
+    ))?;
+    for (variant_idx, (variant, frequency)) in log.variants().enumerate() {
+        statement.execute(params![variant_idx as i64, variant.join(","), frequency as i64])?;
+    }
+    Ok(())
+}
+
+/// Appends `entry` to the `egypt_audit` table, with the configuration as TOML.
+pub fn write_audit_entry(conn: &Connection, entry: &AuditEntry) -> rusqlite::Result<()> {
+    conn.execute_batch(&format!(
+        "CREATE TABLE IF NOT EXISTS {} (
+             time TEXT NOT NULL,
+             user TEXT,
+             job INTEGER NOT NULL,
+             log TEXT NOT NULL,
+             config TEXT NOT NULL
+         )",
+        AUDIT_TABLE
+    ))?;
+    let config = entry
+        .config
+        .to_toml()
+        .map_err(|error| rusqlite::Error::ToSqlConversionFailure(Box::new(error)))?;
+    conn.execute(
+        &format!(
+            "INSERT INTO {} (time, user, job, log, config) VALUES (?1, ?2, ?3, ?4, ?5)",
+            AUDIT_TABLE
+        ),
+        params![
+            entry.time.to_rfc3339(),
+            entry.user,
+            entry.job as i64,
+            entry.log,
+            config
+        ],
+    )?;
+    Ok(())
+}
+
+impl AuditLog for Mutex<Connection> {
+    fn record(&self, entry: &AuditEntry) -> std::io::Result<()> {
+        let conn = self.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+        write_audit_entry(&conn, entry).map_err(std::io::Error::other)
+    }
+}
+
+// Quotes an identifier so user supplied table/column names can't break the query
+fn quote(identifier: &str) -> String {
+    format!("\"{}\"", identifier.replace('"', "\"\""))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn events_db() -> Connection {
+        let conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch(
+            "CREATE TABLE events (case_id TEXT, activity TEXT, timestamp TEXT);
+             INSERT INTO events VALUES
+                 ('c1', 'B', '2024-01-01T11:00'),
+                 ('c1', 'A', '2024-01-01T10:00'),
+                 ('c2', 'A', '2024-01-02T10:00'),
+                 ('c2', 'B', '2024-01-02T11:00'),
+                 ('c3', 'C', '2024-01-03T10:00');",
+        )
+        .unwrap();
+        conn
+    }
+
+    #[test]
+    fn test_read_variant_log() {
+        let log = read_variant_log(&events_db(), &EventTable::default()).unwrap();
+        assert_eq!(log.len(), 3);
+        assert_eq!(log.variant_count(), 2);
+        assert_eq!(log.cases()[0].case_id, "c1");
+        assert_eq!(log.trace(0), ["A", "B"]);
+    }
+
+    #[test]
+    fn test_write_results() {
+        let conn = events_db();
+        let log = read_variant_log(&conn, &EventTable::default()).unwrap();
+        write_variants(&conn, &log).unwrap();
+        write_metrics(&conn, &[("variants", 2.0)]).unwrap();
+        write_metrics(&conn, &[("variants", 3.0)]).unwrap();
+        write_matrix(&conn, &[Dependency::new("A".into(), "B".into(), None, None)]).unwrap();
+
+        let frequency: i64 = conn
+            .query_row("SELECT frequency FROM egypt_variants WHERE activities = 'A,B'", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(frequency, 2);
+        let value: f64 = conn
+            .query_row("SELECT value FROM egypt_metrics WHERE name = 'variants'", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(value, 3.0);
+        let cells: i64 = conn
+            .query_row("SELECT COUNT(*) FROM egypt_matrix", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(cells, 1);
+    }
+    #[test]
+    fn test_audit_log() {
+        let audit = Mutex::new(Connection::open_in_memory().unwrap());
+        let config = crate::config::AnalysisConfig::default();
+        let input = crate::analysis::LogInput::Path("a.xes".to_string());
+        audit
+            .record(&AuditEntry::new(Some("alice".into()), 7, &input, &config))
+            .unwrap();
+
+        let conn = audit.into_inner().unwrap();
+        let (user, job, stored): (String, i64, String) = conn
+            .query_row("SELECT user, job, config FROM egypt_audit", [], |row| {
+                Ok((row.get(0)?, row.get(1)?, row.get(2)?))
+            })
+            .unwrap();
+        assert_eq!((user.as_str(), job), ("alice", 7));
+        assert_eq!(crate::config::AnalysisConfig::from_toml(&stored).unwrap(), config);
+    }
+}
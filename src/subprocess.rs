@@ -0,0 +1,93 @@
+//! Sub-processes scoped by two milestone activities, e.g. everything between `Ticket Opened`
+//! and `Ticket Closed`, for drilling down from the whole process into one of its parts. See
+//! [`crate::analysis::AnalysisResult::sub_process`] for the standard analyses of a sub-log.
+
+use crate::variant_log::{CaseMetadata, VariantLog};
+
+/// The activities starting and ending a sub-process.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Milestones {
+    pub start: String,
+    pub end: String,
+}
+
+impl Milestones {
+    pub fn new(start: &str, end: &str) -> Self {
+        Milestones {
+            start: start.to_string(),
+            end: end.to_string(),
+        }
+    }
+
+    /// The episodes of `trace`: every occurrence of the start milestone up to and including
+    /// the next occurrence of the end milestone. A start milestone within an episode does not
+    /// begin a new one, and an episode without end is left out.
+    pub fn episodes<'a>(&self, trace: &'a [String]) -> Vec<&'a [String]> {
+        let mut episodes = Vec::new();
+        let mut begin = None;
+        for (idx, activity) in trace.iter().enumerate() {
+            match begin {
+                None if *activity == self.start => begin = Some(idx),
+                Some(start) if *activity == self.end => {
+                    episodes.push(&trace[start..=idx]);
+                    begin = None;
+                }
+                _ => {}
+            }
+        }
+        episodes
+    }
+
+    /// Extracts the sub-log between the milestones: every episode of a case becomes a case
+    /// named after the original with its episode number, e.g. `c1/2` for the second episode
+    /// of `c1`. Cases without a complete episode are left out.
+    pub fn sub_log(&self, log: &VariantLog) -> VariantLog {
+        let mut sub_log = VariantLog::new();
+        for (case, trace) in log.cases().iter().zip(log.traces()) {
+            for (number, episode) in (1..).zip(self.episodes(trace)) {
+                sub_log.push(
+                    CaseMetadata {
+                        case_id: format!("{}/{}", case.case_id, number),
+                        ongoing: false,
+                        attributes: case.attributes.clone(),
+                    },
+                    episode.to_vec(),
+                );
+            }
+        }
+        sub_log
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::variant_log::trace;
+
+    #[test]
+    fn test_sub_log() {
+        let milestones = Milestones::new("Open", "Close");
+        let log = VariantLog::from_traces(vec![
+            trace(&[
+                "Log in", "Open", "Work", "Close", "Open", "Open", "Close", "Log out",
+            ]),
+            trace(&["Open", "Work"]),
+            trace(&["Close", "Work"]),
+        ]);
+        let sub_log = milestones.sub_log(&log);
+
+        let case_ids: Vec<&str> = sub_log
+            .cases()
+            .iter()
+            .map(|case| case.case_id.as_str())
+            .collect();
+        assert_eq!(case_ids, ["case_0/1", "case_0/2"]);
+        assert_eq!(
+            sub_log.to_traces(),
+            [
+                trace(&["Open", "Work", "Close"]),
+                trace(&["Open", "Open", "Close"])
+            ]
+        );
+    }
+}
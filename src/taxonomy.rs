@@ -0,0 +1,146 @@
+//! Activity taxonomies: an is-a hierarchy over activity labels, e.g. `Create Purchase Order`
+//! is an `Ordering` activity, which in turn is a `Procurement` activity. Mapping the events
+//! of a log to one level of the hierarchy before the analysis lets the same log be analyzed
+//! at several granularities.
+
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+use serde::{Deserialize, Serialize};
+
+use crate::variant_log::VariantLog;
+
+/// An activity hierarchy and the level the log is abstracted to.
+///
+/// ```toml
+/// level = 0
+///
+/// [parents]
+/// "Create Purchase Order" = "Ordering"
+/// "Approve Purchase Order" = "Ordering"
+/// "Ordering" = "Procurement"
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ActivityTaxonomy {
+    /// The category of every activity or category that has one.
+    pub parents: BTreeMap<String, String>,
+    /// Level the activities are mapped to, 0 being the top-level categories. Activities at
+    /// or above this level keep their label.
+    pub level: usize,
+}
+
+impl ActivityTaxonomy {
+    /// `label` followed by its categories, up to its top-level category. A cycle in the
+    /// parents is cut where it closes.
+    pub fn ancestors<'a>(&'a self, label: &'a str) -> Vec<&'a str> {
+        let mut ancestors = vec![label];
+        let mut seen = HashSet::from([label]);
+        let mut current = label;
+        while let Some(parent) = self.parents.get(current) {
+            if !seen.insert(parent.as_str()) {
+                break;
+            }
+            ancestors.push(parent);
+            current = parent;
+        }
+        ancestors
+    }
+
+    /// Level of `label` in the hierarchy: 0 for top-level categories and labels without a
+    /// category.
+    pub fn depth(&self, label: &str) -> usize {
+        self.ancestors(label).len() - 1
+    }
+
+    /// The category of `label` at `level`, or `label` itself if it is at or above that level.
+    pub fn abstraction<'a>(&'a self, label: &'a str, level: usize) -> &'a str {
+        let ancestors = self.ancestors(label);
+        let depth = ancestors.len() - 1;
+        if level < depth {
+            ancestors[depth - level]
+        } else {
+            label
+        }
+    }
+
+    /// Maps the given activities to their category at `level`; activities that keep their
+    /// label have no entry.
+    pub fn mapping<'a>(
+        &self,
+        activities: impl IntoIterator<Item = &'a String>,
+        level: usize,
+    ) -> HashMap<String, String> {
+        activities
+            .into_iter()
+            .filter_map(|activity| {
+                let category = self.abstraction(activity, level);
+                (category != activity).then(|| (activity.clone(), category.to_string()))
+            })
+            .collect()
+    }
+
+    /// Replaces every activity of `log` by its category at the configured level. Variants
+    /// that become equal are merged; consecutive events of the same category are kept.
+    pub fn apply(&self, log: VariantLog) -> VariantLog {
+        let mapping = self.mapping(&log.activities(), self.level);
+        if mapping.is_empty() {
+            log
+        } else {
+            log.rename_activities(&mapping)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn taxonomy(level: usize) -> ActivityTaxonomy {
+        ActivityTaxonomy {
+            parents: BTreeMap::from([
+                ("Create PO".to_string(), "Ordering".to_string()),
+                ("Approve PO".to_string(), "Ordering".to_string()),
+                ("Ordering".to_string(), "Procurement".to_string()),
+                ("Pay".to_string(), "Payment".to_string()),
+            ]),
+            level,
+        }
+    }
+
+    #[test]
+    fn test_abstraction() {
+        let taxonomy = taxonomy(0);
+        assert_eq!(
+            taxonomy.ancestors("Create PO"),
+            ["Create PO", "Ordering", "Procurement"]
+        );
+        assert_eq!(taxonomy.depth("Create PO"), 2);
+        assert_eq!(taxonomy.depth("Unknown"), 0);
+        assert_eq!(taxonomy.abstraction("Create PO", 0), "Procurement");
+        assert_eq!(taxonomy.abstraction("Create PO", 1), "Ordering");
+        assert_eq!(taxonomy.abstraction("Create PO", 2), "Create PO");
+        // activities above the level keep their label
+        assert_eq!(taxonomy.abstraction("Pay", 1), "Pay");
+
+        let mut cyclic = taxonomy.clone();
+        cyclic
+            .parents
+            .insert("Procurement".to_string(), "Create PO".to_string());
+        assert_eq!(cyclic.depth("Create PO"), 2);
+    }
+
+    #[test]
+    fn test_apply() {
+        let log = VariantLog::from_traces(vec![
+            vec!["Create PO".to_string(), "Pay".to_string()],
+            vec!["Approve PO".to_string(), "Pay".to_string()],
+        ]);
+        let abstracted = taxonomy(1).apply(log.clone());
+        assert_eq!(abstracted.variant_count(), 1);
+        assert_eq!(abstracted.trace(0), ["Ordering", "Pay"]);
+
+        let top = taxonomy(0).apply(log.clone());
+        assert_eq!(top.trace(1), ["Procurement", "Payment"]);
+        assert_eq!(taxonomy(2).apply(log.clone()).to_traces(), log.to_traces());
+    }
+}
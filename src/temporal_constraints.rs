@@ -0,0 +1,266 @@
+//! Temporal conformance: the time that usually passes between related activities, e.g. `B`
+//! follows `A` within two days in 95% of the cases, and the cases that take longer. This
+//! complements the control-flow dependencies with expectations on time.
+
+use std::cmp::Reverse;
+use std::collections::BTreeMap;
+
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::calendar::{elapsed, BusinessCalendar};
+use crate::i18n::{Language, Message};
+use crate::render::format_duration;
+use crate::timeline::CaseTimeline;
+
+/// How temporal constraints are mined.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct TemporalConstraintSettings {
+    /// Share of the observed times a constraint allows; longer times are violations.
+    pub percentile: f64,
+    /// Activity pairs observed in fewer cases are not constrained.
+    pub min_cases: usize,
+}
+
+impl Default for TemporalConstraintSettings {
+    fn default() -> Self {
+        TemporalConstraintSettings {
+            percentile: 0.95,
+            min_cases: 5,
+        }
+    }
+}
+
+/// The expected time from the completion of `from` to the start of a later `to`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TemporalConstraint {
+    pub from: String,
+    pub to: String,
+    /// Cases in which `to` follows `from`.
+    pub cases: usize,
+    pub median: Duration,
+    /// The configured percentile of the observed times.
+    pub limit: Duration,
+}
+
+/// A case in which `to` followed `from` later than its constraint allows.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TemporalViolation {
+    pub case_id: String,
+    pub from: String,
+    pub to: String,
+    pub elapsed: Duration,
+    pub limit: Duration,
+}
+
+/// The mined constraints and their violations.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TemporalConformance {
+    pub constraints: Vec<TemporalConstraint>,
+    /// Ordered by how far they exceed their limit, worst first.
+    pub violations: Vec<TemporalViolation>,
+}
+
+impl TemporalConformance {
+    /// Mines the constraints of `timelines` and checks the same cases against them.
+    pub fn of(
+        timelines: &[CaseTimeline],
+        settings: &TemporalConstraintSettings,
+        calendar: Option<&BusinessCalendar>,
+    ) -> Self {
+        let constraints = mine_temporal_constraints(timelines, settings, calendar);
+        let violations = temporal_violations(timelines, &constraints, calendar);
+        TemporalConformance {
+            constraints,
+            violations,
+        }
+    }
+
+    /// One line per constraint, then one line per violation.
+    pub fn report(&self, language: Language) -> String {
+        let mut output = format!(
+            "{}:\n{:<48}{:<12}{:<16}{}\n",
+            Message::TemporalConstraints.text(language),
+            Message::ActivityPair.text(language),
+            Message::Cases.text(language),
+            Message::Median.text(language),
+            Message::Limit.text(language)
+        );
+        for constraint in &self.constraints {
+            output.push_str(&format!(
+                "{:<48}{:<12}{:<16}{}\n",
+                format!("{} -> {}", constraint.from, constraint.to),
+                constraint.cases,
+                format_duration(constraint.median),
+                format_duration(constraint.limit)
+            ));
+        }
+        output.push_str(&format!(
+            "\n{}:\n{:<24}{:<48}{:<16}{}\n",
+            Message::TemporalViolations.text(language),
+            Message::Case.text(language),
+            Message::ActivityPair.text(language),
+            Message::Duration.text(language),
+            Message::Limit.text(language)
+        ));
+        for violation in &self.violations {
+            output.push_str(&format!(
+                "{:<24}{:<48}{:<16}{}\n",
+                violation.case_id,
+                format!("{} -> {}", violation.from, violation.to),
+                format_duration(violation.elapsed),
+                format_duration(violation.limit)
+            ));
+        }
+        output
+    }
+}
+
+// For every pair of different activities, the time from the first completion of the first
+// activity to the first start of the second one after it
+fn pair_times<'a>(
+    timeline: &'a CaseTimeline,
+    calendar: Option<&BusinessCalendar>,
+) -> BTreeMap<(&'a str, &'a str), Duration> {
+    let mut first_end: BTreeMap<&'a str, DateTime<Utc>> = BTreeMap::new();
+    let mut times = BTreeMap::new();
+    for instance in &timeline.instances {
+        let Some(begin) = instance.begin() else {
+            continue;
+        };
+        for (&from, &end) in &first_end {
+            if from != instance.activity && end <= begin {
+                times
+                    .entry((from, instance.activity.as_str()))
+                    .or_insert_with(|| elapsed(calendar, end, begin));
+            }
+        }
+        if let Some(end) = instance.complete.or(instance.start) {
+            first_end.entry(&instance.activity).or_insert(end);
+        }
+    }
+    times
+}
+
+/// Mines a constraint for every pair of activities in which the second follows the first in
+/// at least `min_cases` cases. Per case, the time from the first completion of the first
+/// activity to the next start of the second one counts; the limit is the configured
+/// nearest-rank percentile of these times. Ordered by activity pair.
+pub fn mine_temporal_constraints(
+    timelines: &[CaseTimeline],
+    settings: &TemporalConstraintSettings,
+    calendar: Option<&BusinessCalendar>,
+) -> Vec<TemporalConstraint> {
+    let mut observed: BTreeMap<(String, String), Vec<Duration>> = BTreeMap::new();
+    for timeline in timelines {
+        for ((from, to), time) in pair_times(timeline, calendar) {
+            observed
+                .entry((from.to_string(), to.to_string()))
+                .or_default()
+                .push(time);
+        }
+    }
+    observed
+        .into_iter()
+        .filter(|(_, times)| times.len() >= settings.min_cases.max(1))
+        .map(|((from, to), mut times)| {
+            times.sort();
+            let percentile = |share: f64| {
+                times[(share.clamp(0.0, 1.0) * (times.len() - 1) as f64).round() as usize]
+            };
+            TemporalConstraint {
+                from,
+                to,
+                cases: times.len(),
+                median: percentile(0.5),
+                limit: percentile(settings.percentile),
+            }
+        })
+        .collect()
+}
+
+/// Checks every case against `constraints`, see [`mine_temporal_constraints`] for how the
+/// times are measured.
+pub fn temporal_violations(
+    timelines: &[CaseTimeline],
+    constraints: &[TemporalConstraint],
+    calendar: Option<&BusinessCalendar>,
+) -> Vec<TemporalViolation> {
+    let limits: BTreeMap<(&str, &str), Duration> = constraints
+        .iter()
+        .map(|constraint| {
+            (
+                (constraint.from.as_str(), constraint.to.as_str()),
+                constraint.limit,
+            )
+        })
+        .collect();
+    let mut violations: Vec<TemporalViolation> = timelines
+        .iter()
+        .flat_map(|timeline| {
+            pair_times(timeline, calendar)
+                .into_iter()
+                .filter_map(|(pair, time)| {
+                    let limit = *limits.get(&pair)?;
+                    (time > limit).then(|| TemporalViolation {
+                        case_id: timeline.case_id.clone(),
+                        from: pair.0.to_string(),
+                        to: pair.1.to_string(),
+                        elapsed: time,
+                        limit,
+                    })
+                })
+                .collect::<Vec<_>>()
+        })
+        .collect();
+    violations.sort_by_key(|violation| Reverse(violation.elapsed - violation.limit));
+    violations
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::TimeZone;
+
+    use super::*;
+    use crate::timeline::TestInstance;
+
+    fn completed(activity: &str, hour: u32) -> TestInstance<'_> {
+        (activity, None, Some(Utc.with_ymd_and_hms(2024, 1, 1, hour, 0, 0).unwrap()), None)
+    }
+
+    #[test]
+    fn test_temporal_conformance() {
+        let timelines = [
+            CaseTimeline::of("1", &[completed("A", 0), completed("B", 1), completed("C", 2)]),
+            CaseTimeline::of("2", &[completed("A", 0), completed("B", 2)]),
+            CaseTimeline::of("3", &[completed("A", 0), completed("B", 1)]),
+            CaseTimeline::of("4", &[completed("A", 0), completed("A", 5), completed("B", 9)]),
+        ];
+        let settings = TemporalConstraintSettings {
+            percentile: 0.5,
+            min_cases: 2,
+        };
+        let conformance = TemporalConformance::of(&timelines, &settings, None);
+
+        // C follows in a single case only
+        assert_eq!(conformance.constraints.len(), 1);
+        let constraint = &conformance.constraints[0];
+        assert_eq!(
+            (constraint.from.as_str(), constraint.to.as_str()),
+            ("A", "B")
+        );
+        assert_eq!(constraint.cases, 4);
+        assert_eq!(constraint.limit, Duration::hours(2));
+
+        // measured from the first A
+        let case_ids: Vec<&str> = conformance
+            .violations
+            .iter()
+            .map(|violation| violation.case_id.as_str())
+            .collect();
+        assert_eq!(case_ids, ["4"]);
+        assert_eq!(conformance.violations[0].elapsed, Duration::hours(9));
+        assert!(conformance.report(Language::English).contains("A -> B"));
+    }
+}
@@ -0,0 +1,265 @@
+use std::collections::{HashMap, HashSet, VecDeque};
+
+use chrono::{DateTime, Duration, Utc};
+use process_mining::event_log::EventLog;
+
+use crate::calendar::{elapsed, BusinessCalendar};
+use crate::parser::xes_extensions::XesExtensions;
+use crate::parser::{event_label, Classifier, ParseError};
+
+/// One execution of an activity within a case.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ActivityInstance {
+    pub activity: String,
+    pub start: Option<DateTime<Utc>>,
+    pub complete: Option<DateTime<Utc>>,
+    pub resource: Option<String>,
+}
+
+impl ActivityInstance {
+    /// The time the instance began: its start, or its completion if no start was recorded.
+    pub fn begin(&self) -> Option<DateTime<Utc>> {
+        self.start.or(self.complete)
+    }
+
+    /// Time between start and completion, if both were recorded.
+    pub fn duration(&self) -> Option<Duration> {
+        self.duration_in(None)
+    }
+
+    /// Same as [`ActivityInstance::duration`], but in business time if there is a calendar.
+    pub fn duration_in(&self, calendar: Option<&BusinessCalendar>) -> Option<Duration> {
+        Some(elapsed(calendar, self.start?, self.complete?))
+    }
+}
+
+/// The activity instances of a case, in the order they began.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CaseTimeline {
+    pub case_id: String,
+    pub instances: Vec<ActivityInstance>,
+}
+
+impl CaseTimeline {
+    /// For every instance, the time since the previous instance ended (`None` for the first).
+    pub fn waiting_times(&self) -> Vec<Option<Duration>> {
+        self.waiting_times_in(None)
+    }
+
+    /// Same as [`CaseTimeline::waiting_times`], but in business time if there is a calendar.
+    pub fn waiting_times_in(&self, calendar: Option<&BusinessCalendar>) -> Vec<Option<Duration>> {
+        let mut previous_end: Option<DateTime<Utc>> = None;
+        self.instances
+            .iter()
+            .map(|instance| {
+                let waiting = previous_end
+                    .zip(instance.begin())
+                    .map(|(end, begin)| elapsed(calendar, end, begin));
+                previous_end = instance.complete.or(instance.start).or(previous_end);
+                waiting
+            })
+            .collect()
+    }
+
+    /// Time between the first and the last recorded timestamp of the case.
+    pub fn span(&self) -> Option<(DateTime<Utc>, DateTime<Utc>)> {
+        let timestamps = self
+            .instances
+            .iter()
+            .flat_map(|instance| [instance.start, instance.complete])
+            .flatten();
+        let (min, max) = timestamps.fold((None, None), |(min, max), t| {
+            (
+                Some(min.map_or(t, |m: DateTime<Utc>| m.min(t))),
+                Some(max.map_or(t, |m: DateTime<Utc>| m.max(t))),
+            )
+        });
+        min.zip(max)
+    }
+}
+
+#[cfg(test)]
+impl CaseTimeline {
+    /// A timeline of `instances`, each given as activity, start, completion and resource, for
+    /// the tests of the analyses built on timelines.
+    pub(crate) fn of(case_id: &str, instances: &[TestInstance]) -> Self {
+        CaseTimeline {
+            case_id: case_id.to_string(),
+            instances: instances
+                .iter()
+                .map(|&(activity, start, complete, resource)| ActivityInstance {
+                    activity: activity.to_string(),
+                    start,
+                    complete,
+                    resource: resource.map(str::to_string),
+                })
+                .collect(),
+        }
+    }
+}
+
+#[cfg(test)]
+pub(crate) type TestInstance<'a> =
+    (&'a str, Option<DateTime<Utc>>, Option<DateTime<Utc>>, Option<&'a str>);
+
+/// Key figures of one case, e.g. for a case table.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CaseKpis {
+    pub case_id: String,
+    pub activity_instances: usize,
+    /// First and last recorded timestamp of the case.
+    pub first: Option<DateTime<Utc>>,
+    pub last: Option<DateTime<Utc>>,
+    /// Time from the first to the last timestamp.
+    pub duration: Option<Duration>,
+    /// Summed time between the instances, `None` if no waiting time could be measured.
+    pub waiting_time: Option<Duration>,
+    /// Number of distinct resources that executed the instances.
+    pub resources: usize,
+}
+
+impl CaseTimeline {
+    /// The key figures of the case; times are measured in business time if there is a
+    /// calendar.
+    pub fn kpis(&self, calendar: Option<&BusinessCalendar>) -> CaseKpis {
+        let span = self.span();
+        let waiting_time = self
+            .waiting_times_in(calendar)
+            .into_iter()
+            .flatten()
+            .reduce(|total, waiting| total + waiting);
+        let resources: HashSet<&str> = self
+            .instances
+            .iter()
+            .filter_map(|instance| instance.resource.as_deref())
+            .collect();
+        CaseKpis {
+            case_id: self.case_id.clone(),
+            activity_instances: self.instances.len(),
+            first: span.map(|(first, _)| first),
+            last: span.map(|(_, last)| last),
+            duration: span.map(|(first, last)| elapsed(calendar, first, last)),
+            waiting_time,
+            resources: resources.len(),
+        }
+    }
+}
+
+/// Groups the events of every trace into activity instances.
+///
+/// Within each trace, a `start` event is paired with the next `complete` event of the same
+/// activity; events without a lifecycle transition count as `complete` events. Other lifecycle
+/// transitions (schedule, suspend, ...) are left out. Cases without `concept:name` are named
+/// `case_<index>`.
+pub fn case_timelines(log: &EventLog, classifier: &Classifier) -> Result<Vec<CaseTimeline>, ParseError> {
+    let classifier = classifier.resolve(log)?;
+    let mut timelines = Vec::with_capacity(log.traces.len());
+
+    for (case_idx, trace) in log.traces.iter().enumerate() {
+        let case_id = trace
+            .concept_name()
+            .map(|name| name.to_string())
+            .unwrap_or_else(|| format!("case_{}", case_idx));
+
+        let mut events: Vec<_> = trace.events.iter().collect();
+        events.sort_by_key(|event| event.timestamp());
+
+        let mut instances: Vec<ActivityInstance> = Vec::new();
+        // started instances of every activity that are still waiting for their completion
+        let mut open: HashMap<String, VecDeque<usize>> = HashMap::new();
+
+        for event in events {
+            let Some(activity) = event_label(event, &classifier, &log.global_event_attrs) else {
+                continue;
+            };
+            let resource = event.org_resource().map(|resource| resource.to_string());
+            let is_start = event.has_lifecycle_transition("start");
+            let is_complete =
+                event.lifecycle_transition().is_none() || event.has_lifecycle_transition("complete");
+
+            if is_start {
+                open.entry(activity.clone()).or_default().push_back(instances.len());
+                instances.push(ActivityInstance {
+                    activity,
+                    start: event.timestamp(),
+                    complete: None,
+                    resource,
+                });
+            } else if is_complete {
+                match open.get_mut(&activity).and_then(|started| started.pop_front()) {
+                    Some(instance_idx) => {
+                        let instance = &mut instances[instance_idx];
+                        instance.complete = event.timestamp();
+                        instance.resource = resource.or(instance.resource.take());
+                    }
+                    None => instances.push(ActivityInstance {
+                        activity,
+                        start: None,
+                        complete: event.timestamp(),
+                        resource,
+                    }),
+                }
+            }
+        }
+
+        timelines.push(CaseTimeline { case_id, instances });
+    }
+
+    Ok(timelines)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::{parse_into_event_log_with_config, ImportConfig};
+
+    #[test]
+    fn test_case_timelines() {
+        let content = r#"<log>
+            <trace>
+                <string key="concept:name" value="c1"/>
+                <event>
+                    <string key="concept:name" value="Check"/>
+                    <string key="lifecycle:transition" value="start"/>
+                    <date key="time:timestamp" value="2024-01-01T10:00:00+00:00"/>
+                </event>
+                <event>
+                    <string key="concept:name" value="Ship"/>
+                    <date key="time:timestamp" value="2024-01-01T12:00:00+00:00"/>
+                </event>
+                <event>
+                    <string key="concept:name" value="Check"/>
+                    <string key="lifecycle:transition" value="complete"/>
+                    <date key="time:timestamp" value="2024-01-01T10:30:00+00:00"/>
+                </event>
+            </trace>
+        </log>"#;
+        let log = parse_into_event_log_with_config(None, Some(content), &ImportConfig::default())
+            .unwrap();
+        let timelines = case_timelines(&log, &Classifier::ConceptName).unwrap();
+
+        let timeline = &timelines[0];
+        assert_eq!(timeline.case_id, "c1");
+        assert_eq!(timeline.instances.len(), 2);
+        assert_eq!(timeline.instances[0].duration(), Some(Duration::minutes(30)));
+        assert_eq!(timeline.waiting_times(), [None, Some(Duration::minutes(90))]);
+        // 2024-01-01 is a Monday, working hours starting at 11:00
+        let calendar = BusinessCalendar {
+            start: chrono::NaiveTime::from_hms_opt(11, 0, 0).unwrap(),
+            ..Default::default()
+        };
+        assert_eq!(timeline.waiting_times_in(Some(&calendar)), [None, Some(Duration::hours(1))]);
+        assert_eq!(timeline.instances[0].duration_in(Some(&calendar)), Some(Duration::zero()));
+        assert_eq!(
+            timeline.span().map(|(first, last)| last - first),
+            Some(Duration::hours(2))
+        );
+
+        let kpis = timeline.kpis(None);
+        assert_eq!(kpis.activity_instances, 2);
+        assert_eq!(kpis.duration, Some(Duration::hours(2)));
+        assert_eq!(kpis.waiting_time, Some(Duration::minutes(90)));
+        assert_eq!(kpis.resources, 0);
+        assert_eq!(timeline.kpis(Some(&calendar)).duration, Some(Duration::hours(1)));
+    }
+}
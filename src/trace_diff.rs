@@ -0,0 +1,181 @@
+//! Differences between two cases, e.g. a problematic case and a "good" reference case: the
+//! case is aligned with the reference trace like with a model, on the longest common
+//! subsequence of both, so the activities it skipped, inserted or executed out of order
+//! stand out.
+
+use crate::alignment::{AlignmentMove, TraceAlignment};
+use crate::deviations::{alignment_deviations, DeviationKind};
+use crate::i18n::{Language, Message};
+use crate::render::theme::Theme;
+use crate::variant_log::VariantLog;
+
+/// Aligns `case` with `reference` on their longest common subsequence: the common activities
+/// are synchronous moves, the other activities of the case log-only moves and the other
+/// activities of the reference model-only moves. Where both traces differ at the same
+/// position, the reference's activities come first.
+pub fn diff_traces(reference: &[String], case: &[String]) -> TraceAlignment {
+    // common[i][j]: length of the longest common subsequence of reference[i..] and case[j..]
+    let mut common = vec![vec![0usize; case.len() + 1]; reference.len() + 1];
+    for i in (0..reference.len()).rev() {
+        for j in (0..case.len()).rev() {
+            common[i][j] = if reference[i] == case[j] {
+                common[i + 1][j + 1] + 1
+            } else {
+                common[i + 1][j].max(common[i][j + 1])
+            };
+        }
+    }
+
+    let mut moves = Vec::with_capacity(reference.len().max(case.len()));
+    let (mut i, mut j) = (0, 0);
+    while i < reference.len() && j < case.len() {
+        if reference[i] == case[j] {
+            moves.push(AlignmentMove::Synchronous(case[j].clone()));
+            i += 1;
+            j += 1;
+        } else if common[i + 1][j] >= common[i][j + 1] {
+            moves.push(AlignmentMove::ModelOnly(reference[i].clone()));
+            i += 1;
+        } else {
+            moves.push(AlignmentMove::LogOnly(case[j].clone()));
+            j += 1;
+        }
+    }
+    moves.extend(reference[i..].iter().cloned().map(AlignmentMove::ModelOnly));
+    moves.extend(case[j..].iter().cloned().map(AlignmentMove::LogOnly));
+    TraceAlignment { moves }
+}
+
+/// The differences of a case from a reference case.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TraceDiff {
+    pub reference_case: String,
+    pub case: String,
+    /// The case aligned with the reference, see [`diff_traces`].
+    pub alignment: TraceAlignment,
+}
+
+impl TraceDiff {
+    /// Compares the cases with the ids `case` and `reference_case` of `log`, `None` if one of
+    /// them isn't in the log.
+    pub fn of(log: &VariantLog, reference_case: &str, case: &str) -> Option<Self> {
+        let trace = |case_id: &str| {
+            log.cases()
+                .iter()
+                .position(|case| case.case_id == case_id)
+                .map(|idx| log.trace(idx))
+        };
+        Some(TraceDiff {
+            reference_case: reference_case.to_string(),
+            case: case.to_string(),
+            alignment: diff_traces(trace(reference_case)?, trace(case)?),
+        })
+    }
+
+    /// Share of the activities of the longer trace that both traces have in common, in the
+    /// same order; 1 for two empty traces.
+    pub fn similarity(&self) -> f64 {
+        let (mut same, mut reference, mut case) = (0, 0, 0);
+        for step in &self.alignment.moves {
+            match step {
+                AlignmentMove::Synchronous(_) => same += 1,
+                AlignmentMove::ModelOnly(_) => reference += 1,
+                AlignmentMove::LogOnly(_) => case += 1,
+            }
+        }
+        let longer = same + reference.max(case);
+        if longer == 0 {
+            1.0
+        } else {
+            same as f64 / longer as f64
+        }
+    }
+
+    /// The activities the case skipped, inserted or executed out of order, with how often.
+    pub fn deviations(&self) -> Vec<(String, DeviationKind, usize)> {
+        alignment_deviations(&self.alignment)
+            .into_iter()
+            .map(|((activity, kind), count)| (activity, kind, count))
+            .collect()
+    }
+
+    /// Both traces as an HTML table, the case above the reference, with the differences
+    /// highlighted.
+    pub fn html(&self, theme: &Theme) -> String {
+        self.alignment
+            .labeled_html(theme, &self.case, &self.reference_case)
+    }
+
+    /// Both traces aligned, the case above the reference, then the similarity and the
+    /// deviations per activity.
+    pub fn report(&self, language: Language) -> String {
+        let mut output = format!(
+            "{}\n\n{}\n\n{:<32}{:.4}\n",
+            Message::TraceDiff.format(language, &[&self.case, &self.reference_case]),
+            self.alignment
+                .labeled_text(&self.case, &self.reference_case),
+            format!("{}:", Message::Similarity.text(language)),
+            self.similarity()
+        );
+        let deviations = self.deviations();
+        if !deviations.is_empty() {
+            output.push_str(&format!(
+                "\n{:<32}{:<24}{}\n",
+                Message::Activity.text(language),
+                Message::Deviation.text(language),
+                Message::Occurrences.text(language)
+            ));
+        }
+        for (activity, kind, count) in deviations {
+            output.push_str(&format!(
+                "{:<32}{:<24}{}\n",
+                activity,
+                kind.message().text(language),
+                count
+            ));
+        }
+        output
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::variant_log::trace;
+    use crate::variant_log::CaseMetadata;
+
+    #[test]
+    fn test_diff_traces() {
+        let alignment = diff_traces(&trace(&["A", "B", "C", "D"]), &trace(&["A", "C", "B", "E"]));
+        assert_eq!(
+            alignment.labeled_text("case", "reference"),
+            "case      | A | >> | C | >> | B  | E\nreference | A | B  | C | D  | >> | >>"
+        );
+        assert_eq!(alignment.cost(), 4);
+        assert!(diff_traces(&trace(&["A"]), &trace(&["A"])).is_fitting());
+    }
+
+    #[test]
+    fn test_trace_diff() {
+        let mut log = VariantLog::new();
+        for (case_id, activities) in [("good", ["A", "B", "C"]), ("bad", ["A", "C", "B"])] {
+            log.push(
+                CaseMetadata {
+                    case_id: case_id.to_string(),
+                    ongoing: false,
+                    attributes: Default::default(),
+                },
+                trace(&activities),
+            );
+        }
+        let diff = TraceDiff::of(&log, "good", "bad").unwrap();
+
+        assert_eq!(diff.similarity(), 2.0 / 3.0);
+        assert_eq!(
+            diff.deviations(),
+            [("B".to_string(), DeviationKind::OutOfOrder, 1)]
+        );
+        assert!(diff.report(Language::English).contains("out of order"));
+        assert!(TraceDiff::of(&log, "good", "unknown").is_none());
+    }
+}
@@ -0,0 +1,173 @@
+//! The distribution of the trace lengths and the cases whose length is an outlier, e.g. test
+//! cases with a single event or runaway loops, which can be excluded before they skew the
+//! other statistics.
+
+use std::collections::{BTreeMap, HashSet};
+
+use serde::{Deserialize, Serialize};
+
+use crate::i18n::{Language, Message};
+use crate::variant_log::VariantLog;
+
+/// How the bounds of the typical trace lengths are determined.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum OutlierMethod {
+    /// Tukey's fences: more than `iqr_factor` interquartile ranges outside the quartiles.
+    #[default]
+    Iqr,
+    /// Below the `lower_percentile` or above the `upper_percentile` of the lengths.
+    Percentile,
+}
+
+/// When a trace length is an outlier and whether such cases are analyzed.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct TraceLengthSettings {
+    pub method: OutlierMethod,
+    pub iqr_factor: f64,
+    pub lower_percentile: f64,
+    pub upper_percentile: f64,
+    /// Leave the outliers out of all following analyses.
+    pub exclude_outliers: bool,
+}
+
+impl Default for TraceLengthSettings {
+    fn default() -> Self {
+        TraceLengthSettings {
+            method: OutlierMethod::Iqr,
+            iqr_factor: 1.5,
+            lower_percentile: 0.01,
+            upper_percentile: 0.99,
+            exclude_outliers: false,
+        }
+    }
+}
+
+/// The number of cases per trace length and the outliers among them.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TraceLengthDistribution {
+    /// Number of cases per number of events.
+    pub histogram: BTreeMap<usize, usize>,
+    /// Lengths below this are outliers.
+    pub lower_bound: f64,
+    /// Lengths above this are outliers.
+    pub upper_bound: f64,
+    /// The ids of the cases with an outlying length, in log order.
+    pub outliers: Vec<String>,
+}
+
+impl TraceLengthDistribution {
+    /// Counts the trace lengths of `log` and flags the outliers by `settings`. Percentiles
+    /// are nearest-rank percentiles.
+    pub fn of(log: &VariantLog, settings: &TraceLengthSettings) -> Self {
+        let mut histogram = BTreeMap::new();
+        for trace in log.traces() {
+            *histogram.entry(trace.len()).or_default() += 1;
+        }
+        let lengths: Vec<usize> = histogram
+            .iter()
+            .flat_map(|(&length, &cases)| std::iter::repeat_n(length, cases))
+            .collect();
+        let percentile = |share: f64| {
+            lengths
+                .get(
+                    (share.clamp(0.0, 1.0) * lengths.len().saturating_sub(1) as f64).round()
+                        as usize,
+                )
+                .map_or(0.0, |&length| length as f64)
+        };
+        let (lower_bound, upper_bound) = match settings.method {
+            OutlierMethod::Iqr => {
+                let (q1, q3) = (percentile(0.25), percentile(0.75));
+                let range = (q3 - q1) * settings.iqr_factor;
+                (q1 - range, q3 + range)
+            }
+            OutlierMethod::Percentile => (
+                percentile(settings.lower_percentile),
+                percentile(settings.upper_percentile),
+            ),
+        };
+        let outliers = log
+            .cases()
+            .iter()
+            .zip(log.traces())
+            .filter(|(_, trace)| {
+                (trace.len() as f64) < lower_bound || trace.len() as f64 > upper_bound
+            })
+            .map(|(case, _)| case.case_id.clone())
+            .collect();
+        TraceLengthDistribution {
+            histogram,
+            lower_bound,
+            upper_bound,
+            outliers,
+        }
+    }
+
+    /// The outlier case ids, e.g. for [`VariantLog::without_cases`].
+    pub fn outlier_ids(&self) -> HashSet<String> {
+        self.outliers.iter().cloned().collect()
+    }
+
+    /// One line per trace length with its number of cases and a bar, then the outlier bounds.
+    pub fn report(&self, language: Language) -> String {
+        let max = self.histogram.values().copied().max().unwrap_or(0);
+        let mut output = format!(
+            "{}:\n{:<10}{:<10}\n",
+            Message::TraceLengthDistribution.text(language),
+            Message::Events.text(language),
+            Message::Cases.text(language)
+        );
+        for (length, &cases) in &self.histogram {
+            output.push_str(&format!(
+                "{:<10}{:<10}{}\n",
+                length,
+                cases,
+                "#".repeat((cases * 40).div_ceil(max))
+            ));
+        }
+        output.push_str(&Message::TraceLengthOutliers.format(
+            language,
+            &[
+                &self.outliers.len().to_string(),
+                &format!("{:.1}", self.lower_bound),
+                &format!("{:.1}", self.upper_bound),
+            ],
+        ));
+        output.push('\n');
+        output
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_trace_length_outliers() {
+        let trace = |activities: &str| activities.chars().map(|a| a.to_string()).collect();
+        let mut traces = vec![trace("ABC"); 6];
+        traces.extend([trace("ABCD"), trace("AB"), trace("A"), trace("ABCBCBCBCD")]);
+        let log = VariantLog::from_traces(traces);
+
+        let iqr = TraceLengthDistribution::of(&log, &TraceLengthSettings::default());
+        assert_eq!(iqr.histogram[&3], 6);
+        assert_eq!((iqr.lower_bound, iqr.upper_bound), (3.0, 3.0));
+        assert_eq!(iqr.outliers.len(), 4);
+
+        let settings = TraceLengthSettings {
+            method: OutlierMethod::Percentile,
+            lower_percentile: 0.1,
+            upper_percentile: 0.9,
+            ..Default::default()
+        };
+        let percentile = TraceLengthDistribution::of(&log, &settings);
+        assert_eq!((percentile.lower_bound, percentile.upper_bound), (2.0, 4.0));
+        assert_eq!(percentile.outliers.len(), 2);
+        assert_eq!(log.without_cases(&percentile.outlier_ids()).len(), 8);
+        assert!(percentile
+            .report(Language::English)
+            .contains("2 cases with an outlying length"));
+    }
+}
@@ -0,0 +1,191 @@
+use std::collections::HashMap;
+
+use crate::random::SeededRng;
+use crate::variant_log::VariantLog;
+
+/// What is kept together when a log is split into training and test cases.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SplitStrategy {
+    /// Every case is assigned on its own. Cases of the same variant usually end up on both
+    /// sides, so the test cases are not entirely unseen.
+    #[default]
+    ByCase,
+    /// All cases of a variant are assigned together, so no test trace was seen in training.
+    /// The sizes of the parts only approximate the requested ones, as variants are kept whole.
+    ByVariant,
+}
+
+/// A log split into cases to fit on and cases to evaluate on.
+#[derive(Debug, Clone)]
+pub struct Split {
+    pub train: VariantLog,
+    pub test: VariantLog,
+}
+
+/// Splits `log` into a training and a test part with about `test_share` of the cases in the
+/// test part.
+///
+/// # Panics
+/// Panics if `test_share` is not between 0 and 1.
+pub fn holdout(log: &VariantLog, test_share: f64, strategy: SplitStrategy, seed: u64) -> Split {
+    assert!(
+        (0.0..=1.0).contains(&test_share),
+        "test share must be between 0 and 1"
+    );
+    let test_size = (test_share * log.len() as f64).round() as usize;
+    let mut train = Vec::new();
+    let mut test = Vec::new();
+    for group in shuffled_groups(log, strategy, seed) {
+        if test.len() < test_size {
+            test.extend(group);
+        } else {
+            train.extend(group);
+        }
+    }
+    split(log, train, test)
+}
+
+/// Splits `log` into `k` folds of about the same number of cases and returns one split per
+/// fold, with that fold as the test part and the other folds as the training part. Every case
+/// is in exactly one test part.
+///
+/// ```
+/// use egypt::markov::MarkovModel;
+/// use egypt::validation::{k_fold, SplitStrategy};
+/// use egypt::variant_log::VariantLog;
+///
+/// let traces = vec![vec!["A".to_string(), "B".to_string()]; 10];
+/// let log = VariantLog::from_traces(traces);
+/// for split in k_fold(&log, 5, SplitStrategy::ByCase, 42) {
+///     let model = MarkovModel::fit(&split.train, 1, 1.0);
+///     assert!(model.perplexity(&split.test).is_some());
+/// }
+/// ```
+///
+/// # Panics
+/// Panics if `k` is 0.
+pub fn k_fold(log: &VariantLog, k: usize, strategy: SplitStrategy, seed: u64) -> Vec<Split> {
+    assert!(k > 0, "k must be greater than 0");
+    // the groups are shuffled, so adding each to the smallest fold keeps the folds balanced
+    // without sorting the variants by frequency
+    let mut folds: Vec<Vec<usize>> = vec![Vec::new(); k];
+    for group in shuffled_groups(log, strategy, seed) {
+        let smallest = (0..k).min_by_key(|&fold| folds[fold].len()).unwrap_or(0);
+        folds[smallest].extend(group);
+    }
+
+    (0..k)
+        .map(|test_fold| {
+            let train = folds
+                .iter()
+                .enumerate()
+                .filter(|&(fold, _)| fold != test_fold)
+                .flat_map(|(_, cases)| cases.iter().copied())
+                .collect();
+            split(log, train, folds[test_fold].clone())
+        })
+        .collect()
+}
+
+// The case positions that are assigned together, in random order
+fn shuffled_groups(log: &VariantLog, strategy: SplitStrategy, seed: u64) -> Vec<Vec<usize>> {
+    let mut groups: Vec<Vec<usize>> = match strategy {
+        SplitStrategy::ByCase => (0..log.len()).map(|case_idx| vec![case_idx]).collect(),
+        SplitStrategy::ByVariant => {
+            let mut groups: Vec<Vec<usize>> = Vec::new();
+            let mut group_of: HashMap<&[String], usize> = HashMap::new();
+            for (case_idx, trace) in log.traces().enumerate() {
+                let group = *group_of.entry(trace).or_insert_with(|| {
+                    groups.push(Vec::new());
+                    groups.len() - 1
+                });
+                groups[group].push(case_idx);
+            }
+            groups
+        }
+    };
+    SeededRng::new(seed).shuffle(&mut groups);
+    groups
+}
+
+// Both parts keep the original case order
+fn split(log: &VariantLog, mut train: Vec<usize>, mut test: Vec<usize>) -> Split {
+    train.sort_unstable();
+    test.sort_unstable();
+    Split {
+        train: log.select_cases(&train),
+        test: log.select_cases(&test),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use super::*;
+
+    fn log() -> VariantLog {
+        // 20 cases of 5 variants with 1 to 8 cases each
+        let traces = [("A", 8), ("B", 5), ("C", 3), ("D", 3), ("E", 1)]
+            .iter()
+            .flat_map(|&(activity, frequency)| vec![vec![activity.to_string()]; frequency])
+            .collect();
+        VariantLog::from_traces(traces)
+    }
+
+    fn case_ids(log: &VariantLog) -> HashSet<String> {
+        log.cases()
+            .iter()
+            .map(|case| case.case_id.clone())
+            .collect()
+    }
+
+    #[test]
+    fn test_holdout() {
+        let log = log();
+        let split = holdout(&log, 0.25, SplitStrategy::ByCase, 1);
+        assert_eq!(split.test.len(), 5);
+        assert_eq!(split.train.len(), 15);
+        assert!(case_ids(&split.train).is_disjoint(&case_ids(&split.test)));
+        // the same seed gives the same split
+        let again = holdout(&log, 0.25, SplitStrategy::ByCase, 1);
+        assert_eq!(case_ids(&again.test), case_ids(&split.test));
+
+        let split = holdout(&log, 0.25, SplitStrategy::ByVariant, 1);
+        assert_eq!(split.train.len() + split.test.len(), 20);
+        assert!(split.test.len() >= 5);
+        assert!(split
+            .train
+            .activities()
+            .is_disjoint(&split.test.activities()));
+    }
+
+    #[test]
+    fn test_k_fold() {
+        let log = log();
+        for strategy in [SplitStrategy::ByCase, SplitStrategy::ByVariant] {
+            let splits = k_fold(&log, 3, strategy, 7);
+            assert_eq!(splits.len(), 3);
+            let mut tested = HashSet::new();
+            for split in &splits {
+                assert_eq!(split.train.len() + split.test.len(), 20);
+                assert!(case_ids(&split.train).is_disjoint(&case_ids(&split.test)));
+                tested.extend(case_ids(&split.test));
+            }
+            assert_eq!(tested, case_ids(&log));
+
+            if strategy == SplitStrategy::ByVariant {
+                for split in &splits {
+                    assert!(split
+                        .train
+                        .activities()
+                        .is_disjoint(&split.test.activities()));
+                }
+            } else {
+                for split in &splits {
+                    assert!((6..=7).contains(&split.test.len()));
+                }
+            }
+        }
+    }
+}
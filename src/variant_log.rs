@@ -0,0 +1,501 @@
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+use std::hash::{Hash, Hasher};
+
+/// Per-case information that is kept next to the variant a case follows.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CaseMetadata {
+    pub case_id: String,
+    /// The case was still running when the log was extracted, so its trace is only a prefix:
+    /// its last activity doesn't end the case, and its duration isn't final.
+    pub ongoing: bool,
+    /// The scalar trace attributes as text, e.g. `cost` or `customer-type`, without
+    /// `concept:name`, which is the case id.
+    pub attributes: BTreeMap<String, String>,
+}
+
+/// An event log stored as a table of unique variants plus, for every case, the index of the
+/// variant it follows.
+///
+/// Real-world logs usually have orders of magnitude fewer variants than cases, so storing
+/// each distinct activity sequence only once keeps memory proportional to the number of
+/// variants, and analyses that only depend on the control flow can iterate over the variants
+/// (weighted by their frequency) instead of over every single trace.
+#[derive(Debug, Clone, Default)]
+pub struct VariantLog {
+    variants: Vec<Vec<String>>,
+    frequencies: Vec<usize>,
+    // number of ongoing cases per variant
+    ongoing: Vec<usize>,
+    // indices of the variants per hash of their activities, so each variant is only stored in
+    // `variants`
+    variant_lookup: HashMap<u64, Vec<usize>>,
+    case_variants: Vec<usize>,
+    cases: Vec<CaseMetadata>,
+}
+
+/// Groups a flat stream of `(case id, activity)` events into cases one event at a time, e.g.
+/// while reading the rows of a database query, so the events are never collected first. See
+/// [`VariantLog::from_events`].
+#[derive(Debug, Clone, Default)]
+pub struct EventCollector {
+    cases: Vec<(String, Vec<String>)>,
+    case_lookup: HashMap<String, usize>,
+}
+
+impl EventCollector {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Appends `activity` to the case `case_id`.
+    pub fn push(&mut self, case_id: String, activity: String) {
+        let case_idx = match self.case_lookup.get(&case_id) {
+            Some(&idx) => idx,
+            None => {
+                self.case_lookup.insert(case_id.clone(), self.cases.len());
+                self.cases.push((case_id, Vec::new()));
+                self.cases.len() - 1
+            }
+        };
+        self.cases[case_idx].1.push(activity);
+    }
+
+    /// The log of the collected cases, ordered by their first event.
+    pub fn finish(self) -> VariantLog {
+        let mut log = VariantLog::new();
+        for (case_id, trace) in self.cases {
+            log.push(
+                CaseMetadata {
+                    case_id,
+                    ongoing: false,
+                    attributes: Default::default(),
+                },
+                trace,
+            );
+        }
+        log
+    }
+}
+
+impl VariantLog {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Builds a variant log from plain traces, naming the cases `case_0`, `case_1`, ...
+    pub fn from_traces(traces: Vec<Vec<String>>) -> Self {
+        Self::from_trace_iter(traces)
+    }
+
+    /// Same as [`VariantLog::from_traces`], but the traces can be streamed, e.g. straight from
+    /// a reader, so only the distinct variants are ever held in memory.
+    pub fn from_trace_iter(traces: impl IntoIterator<Item = Vec<String>>) -> Self {
+        let mut log = VariantLog::new();
+        for (case_idx, trace) in traces.into_iter().enumerate() {
+            log.push(
+                CaseMetadata {
+                    case_id: format!("case_{}", case_idx),
+                    ongoing: false,
+                    attributes: Default::default(),
+                },
+                trace,
+            );
+        }
+        log
+    }
+
+    /// Builds a variant log from a flat stream of `(case id, activity)` events, e.g. the rows
+    /// of a database query ordered by time. The cases are ordered by their first event.
+    pub fn from_events(events: impl IntoIterator<Item = (String, String)>) -> Self {
+        let mut collector = EventCollector::new();
+        for (case_id, activity) in events {
+            collector.push(case_id, activity);
+        }
+        collector.finish()
+    }
+
+    /// Adds a case to the log, reusing the stored variant if the same sequence was seen before.
+    pub fn push(&mut self, case: CaseMetadata, trace: Vec<String>) {
+        let mut hasher = DefaultHasher::new();
+        trace.hash(&mut hasher);
+        let candidates = self.variant_lookup.entry(hasher.finish()).or_default();
+        let variant_idx = match candidates.iter().find(|&&idx| self.variants[idx] == trace) {
+            Some(&idx) => idx,
+            None => {
+                let idx = self.variants.len();
+                candidates.push(idx);
+                self.variants.push(trace);
+                self.frequencies.push(0);
+                self.ongoing.push(0);
+                idx
+            }
+        };
+
+        self.frequencies[variant_idx] += 1;
+        self.ongoing[variant_idx] += usize::from(case.ongoing);
+        self.case_variants.push(variant_idx);
+        self.cases.push(case);
+    }
+
+    /// Returns the number of cases in the log.
+    pub fn len(&self) -> usize {
+        self.cases.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.cases.is_empty()
+    }
+
+    /// Returns the number of distinct variants in the log.
+    pub fn variant_count(&self) -> usize {
+        self.variants.len()
+    }
+
+    /// Iterates over the distinct variants together with the number of cases following them,
+    /// in order of first occurrence.
+    pub fn variants(&self) -> impl Iterator<Item = (&[String], usize)> {
+        self.variants
+            .iter()
+            .zip(self.frequencies.iter())
+            .map(|(variant, &frequency)| (variant.as_slice(), frequency))
+    }
+
+    /// Same as [`VariantLog::variants`], with the number of ongoing cases following each
+    /// variant as the third element.
+    pub fn variants_with_ongoing(&self) -> impl Iterator<Item = (&[String], usize, usize)> {
+        self.variants()
+            .zip(self.ongoing.iter())
+            .map(|((variant, frequency), &ongoing)| (variant, frequency, ongoing))
+    }
+
+    /// Returns the number of ongoing cases, see [`CaseMetadata::ongoing`].
+    pub fn ongoing_cases(&self) -> usize {
+        self.ongoing.iter().sum()
+    }
+
+    /// Returns the `k` most frequent variants with their frequencies, most frequent first;
+    /// variants with the same frequency keep their order of first occurrence.
+    pub fn top_variants(&self, k: usize) -> Vec<(&[String], usize)> {
+        let mut variants: Vec<(&[String], usize)> = self.variants().collect();
+        variants.sort_by_key(|&(_, frequency)| std::cmp::Reverse(frequency));
+        variants.truncate(k);
+        variants
+    }
+
+    /// Returns the variants in the `(trace, frequency)` form expected by the weighted
+    /// dependency checks.
+    pub fn weighted_variants(&self) -> Vec<(Vec<&str>, usize)> {
+        self.variants()
+            .map(|(variant, frequency)| {
+                (variant.iter().map(|s| s.as_str()).collect(), frequency)
+            })
+            .collect()
+    }
+
+    /// Returns the activity sequence of the case at the given position.
+    pub fn trace(&self, case_idx: usize) -> &[String] {
+        &self.variants[self.case_variants[case_idx]]
+    }
+
+    /// Iterates over the activity sequences of all cases, in the original case order.
+    pub fn traces(&self) -> impl Iterator<Item = &[String]> {
+        self.case_variants
+            .iter()
+            .map(|&variant_idx| self.variants[variant_idx].as_slice())
+    }
+
+    pub fn cases(&self) -> &[CaseMetadata] {
+        &self.cases
+    }
+
+    /// Returns the set of all activities occurring in the log, in the order of their names.
+    pub fn activities(&self) -> BTreeSet<String> {
+        self.variants.iter().flatten().cloned().collect()
+    }
+
+    /// Returns how many events of the whole log carry each activity.
+    pub fn activity_frequencies(&self) -> BTreeMap<String, usize> {
+        let mut frequencies = BTreeMap::new();
+        for (variant, frequency) in self.variants() {
+            for activity in variant {
+                *frequencies.entry(activity.clone()).or_insert(0) += frequency;
+            }
+        }
+        frequencies
+    }
+
+    /// Returns a copy of the log with activities renamed according to `mapping`; activities
+    /// without an entry are kept as they are. Variants that become equal are merged.
+    pub fn rename_activities(&self, mapping: &HashMap<String, String>) -> VariantLog {
+        let mut log = VariantLog::new();
+        for (case, trace) in self.cases.iter().zip(self.traces()) {
+            let trace = trace
+                .iter()
+                .map(|activity| mapping.get(activity).unwrap_or(activity).clone())
+                .collect();
+            log.push(case.clone(), trace);
+        }
+        log
+    }
+
+    /// Returns a copy of the log without the events of the given activities. Variants that
+    /// become equal are merged; cases are kept even if all of their events are removed.
+    pub fn remove_activities(&self, activities: &HashSet<String>) -> VariantLog {
+        let mut log = VariantLog::new();
+        for (case, trace) in self.cases.iter().zip(self.traces()) {
+            let trace = trace
+                .iter()
+                .filter(|activity| !activities.contains(*activity))
+                .cloned()
+                .collect();
+            log.push(case.clone(), trace);
+        }
+        log
+    }
+
+    /// Returns a copy of the log with only the cases whose variant occurs at least
+    /// `min_frequency` times.
+    pub fn frequent_variants(&self, min_frequency: usize) -> VariantLog {
+        let mut log = VariantLog::new();
+        for (case, &variant_idx) in self.cases.iter().zip(&self.case_variants) {
+            if self.frequencies[variant_idx] >= min_frequency {
+                log.push(case.clone(), self.variants[variant_idx].clone());
+            }
+        }
+        log
+    }
+
+    /// Returns a copy of the log without the given cases.
+    pub fn without_cases(&self, case_ids: &HashSet<String>) -> VariantLog {
+        let mut log = VariantLog::new();
+        for (case, &variant_idx) in self.cases.iter().zip(&self.case_variants) {
+            if !case_ids.contains(&case.case_id) {
+                log.push(case.clone(), self.variants[variant_idx].clone());
+            }
+        }
+        log
+    }
+
+    /// Returns a copy of the log with the given cases flagged as ongoing, see
+    /// [`CaseMetadata::ongoing`]. Cases flagged before stay ongoing.
+    pub fn mark_ongoing(&self, case_ids: &HashSet<String>) -> VariantLog {
+        let mut log = VariantLog::new();
+        for (case, &variant_idx) in self.cases.iter().zip(&self.case_variants) {
+            let case = CaseMetadata {
+                ongoing: case.ongoing || case_ids.contains(&case.case_id),
+                ..case.clone()
+            };
+            log.push(case, self.variants[variant_idx].clone());
+        }
+        log
+    }
+
+    /// Returns a copy of the log without the ongoing cases, e.g. for statistics that need
+    /// finished cases.
+    pub fn completed(&self) -> VariantLog {
+        let mut log = VariantLog::new();
+        for (case, &variant_idx) in self.cases.iter().zip(&self.case_variants) {
+            if !case.ongoing {
+                log.push(case.clone(), self.variants[variant_idx].clone());
+            }
+        }
+        log
+    }
+
+    /// Returns a copy of the log with only the cases at the given positions, in the given
+    /// order.
+    pub fn select_cases(&self, case_indices: &[usize]) -> VariantLog {
+        let mut log = VariantLog::new();
+        for &case_idx in case_indices {
+            let variant_idx = self.case_variants[case_idx];
+            log.push(self.cases[case_idx].clone(), self.variants[variant_idx].clone());
+        }
+        log
+    }
+
+    /// Returns a copy of the log with only the cases `predicate` accepts, e.g. the cases of a
+    /// customer type before the dependency matrix is built.
+    pub fn filter_cases(&self, predicate: impl Fn(&CaseMetadata) -> bool) -> VariantLog {
+        let mut log = VariantLog::new();
+        for (case, &variant_idx) in self.cases.iter().zip(&self.case_variants) {
+            if predicate(case) {
+                log.push(case.clone(), self.variants[variant_idx].clone());
+            }
+        }
+        log
+    }
+
+    /// Splits the log by the value of the case attribute `key`, with the cases without the
+    /// attribute under `None`, so every group can be analyzed on its own.
+    pub fn group_by_attribute(&self, key: &str) -> BTreeMap<Option<String>, VariantLog> {
+        let mut groups: BTreeMap<Option<String>, VariantLog> = BTreeMap::new();
+        for (case, &variant_idx) in self.cases.iter().zip(&self.case_variants) {
+            groups
+                .entry(case.attributes.get(key).cloned())
+                .or_default()
+                .push(case.clone(), self.variants[variant_idx].clone());
+        }
+        groups
+    }
+
+    /// Returns a copy of the log with the activities of every case in reverse order.
+    pub fn reversed(&self) -> VariantLog {
+        let mut log = VariantLog::new();
+        for (case, trace) in self.cases.iter().zip(self.traces()) {
+            log.push(case.clone(), trace.iter().rev().cloned().collect());
+        }
+        log
+    }
+
+    /// Expands the log back into one `Vec<String>` per case.
+    pub fn to_traces(&self) -> Vec<Vec<String>> {
+        self.traces().map(|trace| trace.to_vec()).collect()
+    }
+}
+
+/// The activities of a trace as owned labels, for the tests of the analyses built on traces.
+#[cfg(test)]
+pub(crate) fn trace(activities: &[&str]) -> Vec<String> {
+    activities.iter().map(|activity| activity.to_string()).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn traces(traces: &[&[&str]]) -> Vec<Vec<String>> {
+        traces
+            .iter()
+            .map(|trace| trace.iter().map(|s| s.to_string()).collect())
+            .collect()
+    }
+
+    #[test]
+    fn test_variants_are_shared() {
+        let original = traces(&[
+            &["A", "B", "C"],
+            &["A", "C"],
+            &["A", "B", "C"],
+            &["A", "B", "C"],
+        ]);
+        let log = VariantLog::from_traces(original.clone());
+
+        assert_eq!(log.len(), 4);
+        assert_eq!(log.variant_count(), 2);
+        let variants: Vec<(&[String], usize)> = log.variants().collect();
+        assert_eq!(variants[0].1, 3);
+        assert_eq!(variants[1].1, 1);
+        assert_eq!(log.trace(1), original[1].as_slice());
+        assert_eq!(log.cases()[3].case_id, "case_3");
+        assert_eq!(log.to_traces(), original);
+        assert_eq!(log.top_variants(1), [(original[0].as_slice(), 3)]);
+    }
+
+    #[test]
+    fn test_rename_activities_merges_variants() {
+        let log = VariantLog::from_traces(traces(&[&["A", "b"], &["A", "B"], &["A", "B"]]));
+        assert_eq!(log.activity_frequencies()["B"], 2);
+
+        let mapping = HashMap::from([("b".to_string(), "B".to_string())]);
+        let renamed = log.rename_activities(&mapping);
+        assert_eq!(renamed.variant_count(), 1);
+        assert_eq!(renamed.len(), 3);
+        assert_eq!(renamed.activity_frequencies()["B"], 3);
+    }
+
+    #[test]
+    fn test_filters() {
+        let log = VariantLog::from_traces(traces(&[&["A", "x", "B"], &["A", "B"], &["C"]]));
+
+        let without_x = log.remove_activities(&HashSet::from(["x".to_string()]));
+        assert_eq!(without_x.variant_count(), 2);
+        assert_eq!(without_x.len(), 3);
+
+        let frequent = without_x.frequent_variants(2);
+        assert_eq!(frequent.to_traces(), traces(&[&["A", "B"], &["A", "B"]]));
+        assert_eq!(frequent.cases()[1].case_id, "case_1");
+
+        let without_first = log.without_cases(&HashSet::from(["case_0".to_string()]));
+        assert_eq!(without_first.to_traces(), traces(&[&["A", "B"], &["C"]]));
+
+        let reversed = log.reversed();
+        assert_eq!(reversed.trace(0), traces(&[&["B", "x", "A"]])[0].as_slice());
+        assert_eq!(reversed.cases()[0].case_id, "case_0");
+
+        let selected = log.select_cases(&[2, 0]);
+        assert_eq!(selected.to_traces(), traces(&[&["C"], &["A", "x", "B"]]));
+        assert_eq!(selected.cases()[1].case_id, "case_0");
+    }
+
+    #[test]
+    fn test_ongoing_cases() {
+        let log = VariantLog::from_traces(traces(&[&["A", "B"], &["A"], &["A", "B"]]));
+        let marked = log.mark_ongoing(&HashSet::from(["case_0".to_string(), "case_1".to_string()]));
+        assert_eq!(marked.ongoing_cases(), 2);
+        assert!(marked.cases()[0].ongoing);
+        let variants: Vec<(usize, usize)> = marked
+            .variants_with_ongoing()
+            .map(|(_, frequency, ongoing)| (frequency, ongoing))
+            .collect();
+        assert_eq!(variants, [(2, 1), (1, 1)]);
+        // the flag survives the filters
+        assert_eq!(marked.reversed().ongoing_cases(), 2);
+
+        let completed = marked.completed();
+        assert_eq!(completed.to_traces(), traces(&[&["A", "B"]]));
+        assert_eq!(completed.cases()[0].case_id, "case_2");
+    }
+
+    #[test]
+    fn test_case_attributes() {
+        let mut log = VariantLog::new();
+        for (case_id, customer, trace) in [
+            ("c1", Some("gold"), ["A", "B"]),
+            ("c2", None, ["A", "C"]),
+            ("c3", Some("gold"), ["A", "C"]),
+        ] {
+            let case = CaseMetadata {
+                case_id: case_id.to_string(),
+                ongoing: false,
+                attributes: customer
+                    .map(|customer| ("customer".to_string(), customer.to_string()))
+                    .into_iter()
+                    .collect(),
+            };
+            log.push(case, trace.iter().map(|activity| activity.to_string()).collect());
+        }
+
+        let gold =
+            log.filter_cases(|case| case.attributes.get("customer").is_some_and(|c| c == "gold"));
+        assert_eq!(gold.to_traces(), traces(&[&["A", "B"], &["A", "C"]]));
+        assert_eq!(gold.cases()[1].case_id, "c3");
+
+        let groups = log.group_by_attribute("customer");
+        assert_eq!(groups.len(), 2);
+        assert_eq!(groups[&Some("gold".to_string())].len(), 2);
+        assert_eq!(groups[&None].cases()[0].case_id, "c2");
+    }
+
+    #[test]
+    fn test_from_events() {
+        let events = [("c2", "A"), ("c1", "A"), ("c2", "B"), ("c1", "B"), ("c3", "C")];
+        let log = VariantLog::from_events(
+            events
+                .iter()
+                .map(|(case, activity)| (case.to_string(), activity.to_string())),
+        );
+        assert_eq!(log.cases()[0].case_id, "c2");
+        assert_eq!(log.to_traces(), traces(&[&["A", "B"], &["A", "B"], &["C"]]));
+    }
+
+    #[test]
+    fn test_activities() {
+        let log = VariantLog::from_traces(traces(&[&["C"], &["B", "A"]]));
+        // ordered by name, not by first occurrence
+        let activities: Vec<String> = log.activities().into_iter().collect();
+        assert_eq!(activities, ["A", "B", "C"]);
+        let frequencies: Vec<String> = log.activity_frequencies().into_keys().collect();
+        assert_eq!(frequencies, activities);
+    }
+}
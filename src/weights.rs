@@ -0,0 +1,307 @@
+//! Weighted analysis: every case counts with a weight taken from a case attribute, e.g. its
+//! order value, instead of once, to answer which variants and dependencies carry the most
+//! revenue rather than the most cases.
+
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+use chrono::Duration;
+use process_mining::event_log::EventLog;
+use serde::{Deserialize, Serialize};
+
+use crate::attributes::parse_number;
+use crate::calendar::{elapsed, BusinessCalendar};
+use crate::i18n::{Language, Message};
+use crate::parser::xes_extensions::XesExtensions;
+use crate::render::format_duration;
+use crate::timeline::CaseTimeline;
+use crate::variant_log::VariantLog;
+
+/// The case attribute holding the weights.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct WeightSettings {
+    pub attribute: String,
+    /// Weight of the cases without a numeric value of the attribute.
+    pub missing_weight: f64,
+    /// Number of variants and dependencies in the report.
+    pub top: usize,
+}
+
+impl Default for WeightSettings {
+    fn default() -> Self {
+        WeightSettings {
+            attribute: String::new(),
+            missing_weight: 0.0,
+            top: 10,
+        }
+    }
+}
+
+/// The numeric values of the trace attribute `attribute` by case id; cases without one are
+/// left out.
+pub fn case_weights(log: &EventLog, attribute: &str) -> HashMap<String, f64> {
+    log.traces
+        .iter()
+        .enumerate()
+        .filter_map(|(case_idx, trace)| {
+            let weight = parse_number(&trace.text_attribute(attribute)?)?;
+            let case_id = trace
+                .concept_name()
+                .map(str::to_string)
+                .unwrap_or_else(|| format!("case_{}", case_idx));
+            Some((case_id, weight))
+        })
+        .collect()
+}
+
+/// A variant with the summed weight of its cases.
+#[derive(Debug, Clone, PartialEq)]
+pub struct WeightedVariant {
+    pub activities: Vec<String>,
+    pub cases: usize,
+    pub weight: f64,
+    /// Share of the total weight.
+    pub share: f64,
+}
+
+/// The weight of the cases in which `to` eventually follows `from`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct WeightedSupport {
+    pub from: String,
+    pub to: String,
+    pub weight: f64,
+    /// Share of the weight of the cases containing `from`.
+    pub share: f64,
+}
+
+/// Variants, dependencies and durations of a log with weighted cases.
+#[derive(Debug, Clone, PartialEq)]
+pub struct WeightedAnalysis {
+    pub attribute: String,
+    pub total_weight: f64,
+    /// Cases that got the missing weight.
+    pub missing: usize,
+    /// The variants, heaviest first.
+    pub variants: Vec<WeightedVariant>,
+    /// The activity pairs, heaviest first.
+    pub supports: Vec<WeightedSupport>,
+    /// Weighted mean of the case durations, `None` without timestamps or weights.
+    pub mean_duration: Option<Duration>,
+    /// Duration up to which the cases carry half of the weight.
+    pub median_duration: Option<Duration>,
+}
+
+impl WeightedAnalysis {
+    /// Weighs the cases of `log` with `weights`, see [`case_weights`]. Durations are the
+    /// spans of the matching `timelines`, in business time if there is a calendar.
+    pub fn of(
+        log: &VariantLog,
+        timelines: &[CaseTimeline],
+        weights: &HashMap<String, f64>,
+        settings: &WeightSettings,
+        calendar: Option<&BusinessCalendar>,
+    ) -> Self {
+        let weight_of = |case_id: &str| {
+            weights
+                .get(case_id)
+                .copied()
+                .unwrap_or(settings.missing_weight)
+        };
+        let mut variants: HashMap<&[String], (usize, f64)> = HashMap::new();
+        for (case, trace) in log.cases().iter().zip(log.traces()) {
+            let (cases, weight) = variants.entry(trace).or_default();
+            *cases += 1;
+            *weight += weight_of(&case.case_id);
+        }
+        let total_weight: f64 = variants.values().map(|(_, weight)| weight).sum();
+        let share = |weight: f64, total: f64| if total > 0.0 { weight / total } else { 0.0 };
+
+        let mut with_activity: BTreeMap<&str, f64> = BTreeMap::new();
+        let mut follows: BTreeMap<(&str, &str), f64> = BTreeMap::new();
+        for (&variant, &(_, weight)) in &variants {
+            let mut seen: HashSet<&str> = HashSet::new();
+            let mut pairs: HashSet<(&str, &str)> = HashSet::new();
+            for activity in variant {
+                pairs.extend(
+                    seen.iter()
+                        .filter(|&&earlier| earlier != activity)
+                        .map(|&earlier| (earlier, activity.as_str())),
+                );
+                seen.insert(activity);
+            }
+            for activity in seen {
+                *with_activity.entry(activity).or_default() += weight;
+            }
+            for pair in pairs {
+                *follows.entry(pair).or_default() += weight;
+            }
+        }
+        let mut supports: Vec<WeightedSupport> = follows
+            .into_iter()
+            .map(|((from, to), weight)| WeightedSupport {
+                from: from.to_string(),
+                to: to.to_string(),
+                weight,
+                share: share(weight, with_activity[from]),
+            })
+            .collect();
+        // stable, so equally heavy pairs stay ordered by activity
+        supports.sort_by(|a, b| b.weight.total_cmp(&a.weight));
+
+        let mut variants: Vec<WeightedVariant> = variants
+            .into_iter()
+            .map(|(activities, (cases, weight))| WeightedVariant {
+                activities: activities.to_vec(),
+                cases,
+                weight,
+                share: share(weight, total_weight),
+            })
+            .collect();
+        variants.sort_by(|a, b| {
+            b.weight
+                .total_cmp(&a.weight)
+                .then_with(|| b.cases.cmp(&a.cases))
+                .then_with(|| a.activities.cmp(&b.activities))
+        });
+
+        let mut durations: Vec<(Duration, f64)> = timelines
+            .iter()
+            .filter_map(|timeline| {
+                let (first, last) = timeline.span()?;
+                Some((elapsed(calendar, first, last), weight_of(&timeline.case_id)))
+            })
+            .filter(|&(_, weight)| weight > 0.0)
+            .collect();
+        durations.sort_by_key(|&(duration, _)| duration);
+        let duration_weight: f64 = durations.iter().map(|(_, weight)| weight).sum();
+        let mean_duration = (duration_weight > 0.0).then(|| {
+            let millis: f64 = durations
+                .iter()
+                .map(|(duration, weight)| duration.num_milliseconds() as f64 * weight)
+                .sum();
+            Duration::milliseconds((millis / duration_weight).round() as i64)
+        });
+        let mut cumulated = 0.0;
+        let median_duration = durations.iter().find_map(|&(duration, weight)| {
+            cumulated += weight;
+            (cumulated >= duration_weight / 2.0).then_some(duration)
+        });
+
+        WeightedAnalysis {
+            attribute: settings.attribute.clone(),
+            total_weight,
+            missing: log
+                .cases()
+                .iter()
+                .filter(|case| !weights.contains_key(&case.case_id))
+                .count(),
+            variants,
+            supports,
+            mean_duration,
+            median_duration,
+        }
+    }
+
+    /// The total weight, the weighted durations and the `top` heaviest variants and activity
+    /// pairs.
+    pub fn report(&self, top: usize, language: Language) -> String {
+        let duration =
+            |duration: Option<Duration>| duration.map_or_else(|| "-".to_string(), format_duration);
+        let mut output = format!(
+            "{}:\n{:<32}{:.2}\n{:<32}{}\n{:<32}{}\n{:<32}{}\n\n{:<16}{:<10}{:<10}{}\n",
+            Message::WeightedBy.format(language, &[&self.attribute]),
+            Message::TotalWeight.text(language),
+            self.total_weight,
+            Message::CasesWithoutWeight.text(language),
+            self.missing,
+            Message::MeanCycleTime.text(language),
+            duration(self.mean_duration),
+            Message::MedianCycleTime.text(language),
+            duration(self.median_duration),
+            Message::Weight.text(language),
+            Message::Share.text(language),
+            Message::Cases.text(language),
+            Message::Variant.text(language)
+        );
+        for variant in self.variants.iter().take(top) {
+            output.push_str(&format!(
+                "{:<16.2}{:<10}{:<10}{}\n",
+                variant.weight,
+                format!("{:.1}%", variant.share * 100.0),
+                variant.cases,
+                variant.activities.join(", ")
+            ));
+        }
+        output.push_str(&format!(
+            "\n{:<16}{:<10}{}\n",
+            Message::Weight.text(language),
+            Message::Share.text(language),
+            Message::ActivityPair.text(language)
+        ));
+        for support in self.supports.iter().take(top) {
+            output.push_str(&format!(
+                "{:<16.2}{:<10}{} -> {}\n",
+                support.weight,
+                format!("{:.1}%", support.share * 100.0),
+                support.from,
+                support.to
+            ));
+        }
+        output
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::{
+        parse_into_event_log_with_config, parse_into_variant_log_with_config, ImportConfig,
+    };
+    use crate::timeline::case_timelines;
+
+    #[test]
+    fn test_weighted_analysis() {
+        let content = r#"<log>
+            <trace><string key="concept:name" value="1"/><float key="value" value="60"/>
+                <event><string key="concept:name" value="A"/><date key="time:timestamp" value="2024-01-01T00:00:00Z"/></event>
+                <event><string key="concept:name" value="B"/><date key="time:timestamp" value="2024-01-01T01:00:00Z"/></event>
+            </trace>
+            <trace><string key="concept:name" value="2"/><float key="value" value="10"/>
+                <event><string key="concept:name" value="A"/><date key="time:timestamp" value="2024-01-01T00:00:00Z"/></event>
+                <event><string key="concept:name" value="C"/><date key="time:timestamp" value="2024-01-01T10:00:00Z"/></event>
+            </trace>
+            <trace><string key="concept:name" value="3"/><float key="value" value="30"/>
+                <event><string key="concept:name" value="A"/><date key="time:timestamp" value="2024-01-01T00:00:00Z"/></event>
+                <event><string key="concept:name" value="C"/><date key="time:timestamp" value="2024-01-01T04:00:00Z"/></event>
+            </trace>
+            <trace><string key="concept:name" value="4"/>
+                <event><string key="concept:name" value="A"/><date key="time:timestamp" value="2024-01-01T00:00:00Z"/></event>
+                <event><string key="concept:name" value="C"/><date key="time:timestamp" value="2024-01-01T02:00:00Z"/></event>
+            </trace>
+        </log>"#;
+        let config = ImportConfig::default();
+        let event_log = parse_into_event_log_with_config(None, Some(content), &config).unwrap();
+        let log = parse_into_variant_log_with_config(None, Some(content), &config).unwrap();
+        let timelines = case_timelines(&event_log, &config.classifier).unwrap();
+        let weights = case_weights(&event_log, "value");
+        assert_eq!(weights.len(), 3);
+
+        let settings = WeightSettings {
+            attribute: "value".to_string(),
+            ..Default::default()
+        };
+        let analysis = WeightedAnalysis::of(&log, &timelines, &weights, &settings, None);
+        assert_eq!(analysis.total_weight, 100.0);
+        assert_eq!(analysis.missing, 1);
+        // the rarer variant carries more weight
+        assert_eq!(analysis.variants[0].activities, ["A", "B"]);
+        assert_eq!(analysis.variants[1].cases, 3);
+        assert_eq!(analysis.variants[1].weight, 40.0);
+        assert_eq!(analysis.supports[0].share, 0.6);
+        assert_eq!(analysis.mean_duration, Some(Duration::minutes(168)));
+        assert_eq!(analysis.median_duration, Some(Duration::hours(1)));
+        assert!(analysis
+            .report(settings.top, Language::English)
+            .contains("A -> B"));
+    }
+}
@@ -0,0 +1,179 @@
+//! Work in progress over time: how many cases are open and how many wait before each activity,
+//! sampled at a fixed interval, e.g. as a basis for capacity planning.
+
+use std::collections::BTreeMap;
+
+use chrono::{DateTime, Duration, Utc};
+
+use crate::export::csv_field;
+use crate::timeline::CaseTimeline;
+
+const WIP_DATE_FORMAT: &str = "%Y-%m-%d %H:%M:%S";
+
+/// The work in progress at one point in time.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WipSample {
+    pub time: DateTime<Utc>,
+    /// Cases whose first timestamp is at or before `time` and whose last one is after it.
+    pub active_cases: usize,
+    /// Per activity of the series, the cases in which the previous instance has ended but the
+    /// next instance, one of the activity, hasn't begun yet.
+    pub waiting: Vec<usize>,
+}
+
+/// Work in progress sampled over the whole time span of a log.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WipSeries {
+    /// The activities cases waited before at all, ordered by label; the queue lengths of
+    /// [`WipSample::waiting`] are in this order.
+    pub activities: Vec<String>,
+    pub samples: Vec<WipSample>,
+}
+
+impl WipSeries {
+    /// Samples the work in progress of `timelines` every `interval` (at least a second), from
+    /// the first timestamp of the log up to its last one. Cases and instances without
+    /// timestamps are left out.
+    pub fn of(timelines: &[CaseTimeline], interval: Duration) -> Self {
+        let step = interval.num_seconds().max(1);
+        let cases: Vec<_> = timelines
+            .iter()
+            .filter_map(|timeline| Some((timeline, timeline.span()?)))
+            .collect();
+        let (Some(first), Some(last)) = (
+            cases.iter().map(|(_, (start, _))| *start).min(),
+            cases.iter().map(|(_, (_, end))| *end).max(),
+        ) else {
+            return WipSeries {
+                activities: Vec::new(),
+                samples: Vec::new(),
+            };
+        };
+        let len = ((last - first).num_seconds() / step) as usize + 1;
+
+        // Every interval from `from` (inclusive) to `to` (exclusive) is counted at the samples
+        // within it, as +1 at the first of them and -1 after the last one
+        let count = |changes: &mut Vec<i64>, from: DateTime<Utc>, to: DateTime<Utc>| {
+            let sample_at = |time: DateTime<Utc>| {
+                (((time - first).num_seconds() + step - 1) / step).min(len as i64) as usize
+            };
+            changes[sample_at(from)] += 1;
+            changes[sample_at(to)] -= 1;
+        };
+        let mut active = vec![0; len + 1];
+        let mut waiting: BTreeMap<&str, Vec<i64>> = BTreeMap::new();
+        for (timeline, (start, end)) in &cases {
+            count(&mut active, *start, *end);
+            let mut previous_end: Option<DateTime<Utc>> = None;
+            for instance in &timeline.instances {
+                if let (Some(end), Some(begin)) = (previous_end, instance.begin()) {
+                    if end < begin {
+                        let changes = waiting
+                            .entry(&instance.activity)
+                            .or_insert_with(|| vec![0; len + 1]);
+                        count(changes, end, begin);
+                    }
+                }
+                previous_end = instance.complete.or(instance.start).or(previous_end);
+            }
+        }
+
+        let totals = |changes: &[i64]| -> Vec<usize> {
+            changes[..len]
+                .iter()
+                .scan(0, |total, change| {
+                    *total += change;
+                    Some(*total as usize)
+                })
+                .collect()
+        };
+        let active = totals(&active);
+        let queues: Vec<Vec<usize>> = waiting.values().map(|changes| totals(changes)).collect();
+        WipSeries {
+            activities: waiting
+                .keys()
+                .map(|activity| activity.to_string())
+                .collect(),
+            samples: (0..len)
+                .map(|idx| WipSample {
+                    time: first + Duration::seconds(step * idx as i64),
+                    active_cases: active[idx],
+                    waiting: queues.iter().map(|queue| queue[idx]).collect(),
+                })
+                .collect(),
+        }
+    }
+
+    /// The series as CSV with the columns `time`, `active_cases` and a `waiting:<activity>`
+    /// column per activity.
+    pub fn to_csv(&self) -> String {
+        let mut output = "time,active_cases".to_string();
+        for activity in &self.activities {
+            output.push(',');
+            output.push_str(&csv_field(&format!("waiting:{}", activity)));
+        }
+        output.push('\n');
+        for sample in &self.samples {
+            output.push_str(&format!(
+                "{},{}",
+                sample.time.format(WIP_DATE_FORMAT),
+                sample.active_cases
+            ));
+            for waiting in &sample.waiting {
+                output.push_str(&format!(",{}", waiting));
+            }
+            output.push('\n');
+        }
+        output
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::TimeZone;
+
+    use super::*;
+    use crate::timeline::TestInstance;
+
+    fn instance(activity: &str, start: Option<u32>, complete: u32) -> TestInstance<'_> {
+        let time = |hour| Utc.with_ymd_and_hms(2024, 1, 1, hour, 0, 0).unwrap();
+        (activity, start.map(time), Some(time(complete)), None)
+    }
+
+    #[test]
+    fn test_wip_series() {
+        let timelines = [
+            CaseTimeline::of("1", &[instance("A", None, 0), instance("B", Some(2), 3)]),
+            CaseTimeline::of(
+                "2",
+                &[instance("A", None, 1), instance("B", Some(1), 2), instance("C", None, 4)],
+            ),
+            CaseTimeline::of("3", &[]),
+        ];
+        let series = WipSeries::of(&timelines, Duration::hours(1));
+
+        assert_eq!(series.activities, ["B", "C"]);
+        let counts: Vec<(usize, Vec<usize>)> = series
+            .samples
+            .iter()
+            .map(|sample| (sample.active_cases, sample.waiting.clone()))
+            .collect();
+        assert_eq!(
+            counts,
+            [
+                (1, vec![1, 0]),
+                (2, vec![1, 0]),
+                (2, vec![0, 1]),
+                (1, vec![0, 1]),
+                (0, vec![0, 0]),
+            ]
+        );
+        assert_eq!(
+            series.to_csv().lines().take(2).collect::<Vec<_>>(),
+            [
+                "time,active_cases,waiting:B,waiting:C",
+                "2024-01-01 00:00:00,1,1,0"
+            ]
+        );
+    }
+}
@@ -0,0 +1,186 @@
+//! Exporting an analysis as an Excel workbook, for readers who want to keep working with the
+//! numbers in a spreadsheet.
+//!
+//! Only available with the `xlsx` feature.
+
+use std::cmp::Reverse;
+use std::collections::HashMap;
+
+use chrono::Duration;
+use rust_xlsxwriter::{Format, Workbook, Worksheet, XlsxError};
+
+use crate::analysis::AnalysisResult;
+use crate::i18n::{Language, Message};
+use crate::{dependencies_from_variant_log_with_options, INSUFFICIENT_DATA};
+
+const DATETIME_FORMAT: &str = "yyyy-mm-dd hh:mm:ss";
+const DURATION_FORMAT: &str = "[h]:mm:ss";
+
+/// Renders `result` as an XLSX workbook with the sheets summary, dependency matrix, variants
+/// and cases, labelled in the configured language. Timestamps are in UTC.
+pub fn report_xlsx(result: &AnalysisResult) -> Result<Vec<u8>, XlsxError> {
+    let mut workbook = Workbook::new();
+    write_summary(workbook.add_worksheet(), result)?;
+    write_matrix(workbook.add_worksheet(), result)?;
+    write_variants(workbook.add_worksheet(), result)?;
+    write_cases(workbook.add_worksheet(), result)?;
+    workbook.save_to_buffer()
+}
+
+fn write_summary(sheet: &mut Worksheet, result: &AnalysisResult) -> Result<(), XlsxError> {
+    let language = result.config.language;
+    sheet.set_name(Message::Summary.text(language))?;
+    write_header(sheet, &[Message::Metric, Message::Value], language)?;
+    for (row, (label, value)) in (1..).zip(result.summary()) {
+        sheet.write_string(row, 0, label)?;
+        // values with a confidence interval stay text
+        match value.parse::<f64>() {
+            Ok(number) => sheet.write_number(row, 1, number)?,
+            Err(_) => sheet.write_string(row, 1, value)?,
+        };
+    }
+    sheet.autofit();
+    Ok(())
+}
+
+// One row and column per activity, the same cells as the text matrix but in name order
+fn write_matrix(sheet: &mut Worksheet, result: &AnalysisResult) -> Result<(), XlsxError> {
+    sheet.set_name(Message::DependencyMatrix.text(result.config.language))?;
+    let header = Format::new().set_bold();
+    let dependencies = dependencies_from_variant_log_with_options(
+        &result.log,
+        &result.config.matrix_options(),
+        &[],
+    );
+    let cells: HashMap<(&str, &str), String> = dependencies
+        .iter()
+        .map(|dependency| {
+            (
+                (dependency.from.as_str(), dependency.to.as_str()),
+                dependency.to_string(),
+            )
+        })
+        .collect();
+    let mut activities: Vec<String> = result.log.activities().into_iter().collect();
+    activities.sort();
+
+    for (col, activity) in (1..).zip(&activities) {
+        sheet.write_string_with_format(0, col, activity, &header)?;
+    }
+    for (row, from) in (1..).zip(&activities) {
+        sheet.write_string_with_format(row, 0, from, &header)?;
+        for (col, to) in (1..).zip(&activities) {
+            if from == to {
+                continue;
+            }
+            let cell = cells
+                .get(&(from.as_str(), to.as_str()))
+                .map_or(INSUFFICIENT_DATA, String::as_str);
+            sheet.write_string(row, col, cell)?;
+        }
+    }
+    sheet.set_freeze_panes(1, 1)?;
+    sheet.autofit();
+    Ok(())
+}
+
+fn write_variants(sheet: &mut Worksheet, result: &AnalysisResult) -> Result<(), XlsxError> {
+    let language = result.config.language;
+    sheet.set_name(Message::VariantTable.text(language))?;
+    write_header(
+        sheet,
+        &[Message::Variant, Message::Frequency, Message::Activities],
+        language,
+    )?;
+    let mut variants: Vec<_> = result.log.variants().collect();
+    variants.sort_by_key(|(_, frequency)| Reverse(*frequency));
+    for (row, (variant, frequency)) in (1..).zip(variants) {
+        sheet.write_number(row, 0, row)?;
+        sheet.write_number(row, 1, frequency as f64)?;
+        sheet.write_string(row, 2, variant.join(", "))?;
+    }
+    sheet.set_freeze_panes(1, 0)?;
+    sheet.autofit();
+    Ok(())
+}
+
+fn write_cases(sheet: &mut Worksheet, result: &AnalysisResult) -> Result<(), XlsxError> {
+    let language = result.config.language;
+    sheet.set_name(Message::Cases.text(language))?;
+    write_header(
+        sheet,
+        &[
+            Message::Case,
+            Message::ActivityInstances,
+            Message::FirstEvent,
+            Message::LastEvent,
+            Message::Duration,
+            Message::WaitingTime,
+            Message::Resources,
+        ],
+        language,
+    )?;
+    let datetime = Format::new().set_num_format(DATETIME_FORMAT);
+    let duration = Format::new().set_num_format(DURATION_FORMAT);
+    for (row, case) in (1..).zip(&result.cases) {
+        sheet.write_string(row, 0, &case.case_id)?;
+        sheet.write_number(row, 1, case.activity_instances as f64)?;
+        for (col, timestamp) in [(2, case.first), (3, case.last)] {
+            if let Some(timestamp) = timestamp {
+                sheet.write_datetime_with_format(row, col, timestamp.naive_utc(), &datetime)?;
+            }
+        }
+        for (col, time) in [(4, case.duration), (5, case.waiting_time)] {
+            if let Some(time) = time {
+                sheet.write_number_with_format(row, col, days(time), &duration)?;
+            }
+        }
+        sheet.write_number(row, 6, case.resources as f64)?;
+    }
+    sheet.set_freeze_panes(1, 0)?;
+    sheet.autofit();
+    Ok(())
+}
+
+fn write_header(
+    sheet: &mut Worksheet,
+    columns: &[Message],
+    language: Language,
+) -> Result<(), XlsxError> {
+    let header = Format::new().set_bold();
+    for (col, column) in (0..).zip(columns) {
+        sheet.write_string_with_format(0, col, column.text(language), &header)?;
+    }
+    Ok(())
+}
+
+// Excel stores durations as fractions of a day
+fn days(duration: Duration) -> f64 {
+    duration.num_milliseconds() as f64 / Duration::days(1).num_milliseconds() as f64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::AnalysisConfig;
+    use crate::{analyze, LogInput};
+
+    #[test]
+    fn test_report_xlsx() {
+        for language in Language::ALL {
+            let config = AnalysisConfig {
+                language,
+                ..Default::default()
+            };
+            let result = analyze(
+                LogInput::Path("./sample-data/exercise2.xes".to_string()),
+                config,
+            )
+            .unwrap();
+            let workbook = report_xlsx(&result).unwrap();
+            // XLSX files are zip archives
+            assert!(workbook.starts_with(b"PK"));
+        }
+        assert_eq!(days(Duration::hours(36)), 1.5);
+    }
+}
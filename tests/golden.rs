@@ -0,0 +1,105 @@
+//! Golden-file tests of the output formats over the sample logs: every output is compared with
+//! the file of the same name in `tests/golden`. After an intended change of an output, run
+//!
+//! ```text
+//! UPDATE_GOLDEN=1 cargo test --test golden
+//! ```
+//!
+//! and review the diff of the golden files.
+
+use std::fs;
+use std::path::PathBuf;
+
+use egypt::complexity::ComplexityProfile;
+use egypt::config::AnalysisConfig;
+use egypt::dfg::DirectlyFollowsGraph;
+use egypt::markov::MarkovModel;
+use egypt::parser::{parse_into_variant_log_with_config, ImportConfig};
+use egypt::render::dot::dfg_dot;
+use egypt::render::Theme;
+use egypt::variant_log::VariantLog;
+use egypt::{generate_adj_matrix_from_variant_log, generate_xes, PartitionStrategy};
+
+const LOGS: [&str; 2] = ["exercise2", "Example_SemiStructured"];
+
+fn assert_golden(name: &str, actual: &str) {
+    let path = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/golden")
+        .join(name);
+    if std::env::var_os("UPDATE_GOLDEN").is_some() {
+        fs::create_dir_all(path.parent().expect("golden files are in a directory")).unwrap();
+        fs::write(&path, actual).unwrap();
+        return;
+    }
+    let expected = fs::read_to_string(&path).unwrap_or_else(|e| {
+        panic!(
+            "Could not read {}: {} (run with UPDATE_GOLDEN=1 to create it)",
+            path.display(),
+            e
+        )
+    });
+    if expected == actual {
+        return;
+    }
+    let (line, expected_line, actual_line) = expected
+        .lines()
+        .map(Some)
+        .chain(std::iter::repeat(None))
+        .zip(actual.lines().map(Some).chain(std::iter::repeat(None)))
+        .enumerate()
+        .find(|(_, (expected, actual))| expected != actual)
+        .map(|(idx, (expected, actual))| (idx + 1, expected, actual))
+        .unwrap_or((0, None, None));
+    panic!(
+        "{} differs from the golden file in line {}:\nexpected: {}\nactual:   {}\n\
+         (run with UPDATE_GOLDEN=1 if the change is intended)",
+        name,
+        line,
+        expected_line.unwrap_or("<end of file>"),
+        actual_line.unwrap_or("<end of file>")
+    );
+}
+
+fn sample_log(name: &str) -> VariantLog {
+    let path = format!("{}/sample-data/{}.xes", env!("CARGO_MANIFEST_DIR"), name);
+    parse_into_variant_log_with_config(Some(&path), None, &ImportConfig::default()).unwrap()
+}
+
+#[test]
+fn test_matrix() {
+    for name in LOGS {
+        let matrix = generate_adj_matrix_from_variant_log(&sample_log(name));
+        assert_golden(&format!("{}.matrix.csv", name), &matrix.text);
+    }
+}
+
+#[test]
+fn test_dot() {
+    for name in LOGS {
+        let dfg = DirectlyFollowsGraph::from_variant_log(&sample_log(name));
+        assert_golden(&format!("{}.dot", name), &dfg_dot(&dfg, &[], false, &Theme::default()));
+    }
+}
+
+#[test]
+fn test_json() {
+    for name in LOGS {
+        let log = sample_log(name);
+        let profile = ComplexityProfile::of(&log, PartitionStrategy::default());
+        assert_golden(&format!("{}.complexity.json", name), &profile.to_json());
+        let markov = MarkovModel::fit(&log, 1, 1.0);
+        assert_golden(&format!("{}.markov.json", name), &markov.to_json());
+    }
+    assert_golden("config.json", &AnalysisConfig::default().to_json());
+}
+
+// Only the small log: the export repeats every event and would be a large golden file for
+// the other one
+#[test]
+fn test_xes() {
+    let text: String = sample_log(LOGS[0])
+        .traces()
+        .map(|trace| format!("{}\n", trace.join(",")))
+        .collect();
+    assert_golden(&format!("{}.xes", LOGS[0]), &generate_xes(&text));
+}